@@ -66,6 +66,14 @@ pub enum InstantiationError {
     /// This error occurs when an import from a different store is used.
     #[cfg_attr(feature = "std", error("incorrect OS or architecture"))]
     DifferentArchOS,
+
+    /// The module is interpreted and the store already holds instances of
+    /// native modules, or the other way around.
+    #[cfg_attr(
+        feature = "std",
+        error("cannot mix interpreted and native modules in the same store")
+    )]
+    MixedCode,
 }
 
 /// A struct representing an aborted instruction execution, with a message
@@ -242,6 +250,28 @@ impl fmt::Display for RuntimeError {
                 func_index,
                 frame.module_offset()
             )?;
+            // Where the guest's source code is known, show it like a native
+            // backtrace, with a line for each inlined function.
+            for location in frame.source_locations() {
+                writeln!(f)?;
+                write!(f, "        at ")?;
+                if let Some(name) = location.function_name() {
+                    match rustc_demangle::try_demangle(name) {
+                        Ok(name) => write!(f, "{} (", name)?,
+                        Err(_) => write!(f, "{} (", name)?,
+                    }
+                }
+                write!(f, "{}", location.file().unwrap_or("<unknown>"))?;
+                if let Some(line) = location.line() {
+                    write!(f, ":{}", line)?;
+                    if let Some(column) = location.column() {
+                        write!(f, ":{}", column)?;
+                    }
+                }
+                if location.function_name().is_some() {
+                    write!(f, ")")?;
+                }
+            }
         }
         Ok(())
     }
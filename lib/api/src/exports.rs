@@ -127,6 +127,12 @@ impl Exports {
         self.get(name)
     }
 
+    /// Get an export as a `Tag`.
+    #[cfg(feature = "sys")]
+    pub fn get_tag(&self, name: &str) -> Result<&crate::Tag, ExportError> {
+        self.get(name)
+    }
+
     /// Get an export as a `Func`.
     pub fn get_function(&self, name: &str) -> Result<&Function, ExportError> {
         self.get(name)
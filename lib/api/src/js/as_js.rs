@@ -47,7 +47,7 @@ pub fn param_from_js(ty: &Type, js_val: &JsValue) -> Value {
             let big_num: u128 = js_sys::BigInt::from(js_val.clone()).try_into().unwrap();
             Value::V128(big_num)
         }
-        Type::ExternRef | Type::FuncRef => unimplemented!(
+        Type::ExternRef | Type::FuncRef | Type::ExceptionRef => unimplemented!(
             "The type `{:?}` is not yet supported in the JS Function API",
             ty
         ),
@@ -194,6 +194,7 @@ impl AsJs for Extern {
             ExternType::Table(table_type) => {
                 Ok(Self::Table(Table::from_jsvalue(store, table_type, val)?))
             }
+            ExternType::Tag(_) => Err(JsError::new("Tags are not yet supported in the JS API")),
         }
     }
 }
@@ -287,7 +288,11 @@ impl AsJs for Global {
         global_type: &Self::DefinitionType,
         value: &JsValue,
     ) -> Result<Self, JsError> {
-        if value.is_instance_of::<JsGlobal>() {
+        if global_type.ty == Type::ExceptionRef {
+            Err(JsError::new(
+                "`exnref` globals are not yet supported in the JS API",
+            ))
+        } else if value.is_instance_of::<JsGlobal>() {
             Ok(Global::from_vm_extern(
                 store,
                 VMGlobal::new(
@@ -94,6 +94,9 @@ impl Global {
                     //     VMExternRef::from_raw(raw).map(|e| ExternRef::from_vm_externref(store, e)),
                     // )
                 }
+                Type::ExceptionRef => {
+                    unreachable!("`exnref` globals are rejected by `Global::from_jsvalue`")
+                }
             };
             Value::from_raw(store, ty.ty, raw)
         }
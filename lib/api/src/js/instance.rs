@@ -7,6 +7,7 @@ use crate::store::AsStoreMut;
 use crate::Extern;
 use crate::{errors::InstantiationError, js::js_handle::JsHandle};
 use js_sys::WebAssembly;
+use wasmer_types::{ExternType, Type};
 
 #[derive(Clone, PartialEq, Eq)]
 pub struct Instance {
@@ -53,6 +54,13 @@ impl Instance {
 
         let exports = module
             .exports()
+            // Tags and `exnref` globals have no representation in this API,
+            // so they are left out of the exports.
+            .filter(|export_type| match export_type.ty() {
+                ExternType::Tag(_) => false,
+                ExternType::Global(global_type) => global_type.ty != Type::ExceptionRef,
+                _ => true,
+            })
             .map(|export_type| {
                 let name = export_type.name();
                 let extern_type = export_type.ty();
@@ -58,7 +58,7 @@ pub fn param_from_js(context: &JSContext, ty: &Type, js_val: &JSValue) -> Value
             };
             Value::V128(number)
         }
-        Type::ExternRef | Type::FuncRef => unimplemented!(
+        Type::ExternRef | Type::FuncRef | Type::ExceptionRef => unimplemented!(
             "The type `{:?}` is not yet supported in the JS Function API",
             ty
         ),
@@ -128,6 +128,12 @@ impl AsJs for Extern {
                     VMFunction::new(obj_val, function_type.clone()),
                 )))
             }
+            ExternType::Global(global_type) if global_type.ty == Type::ExceptionRef => {
+                Err(JSValue::string(
+                    &context,
+                    "`exnref` globals are not yet supported in the JSC API".to_string(),
+                ))
+            }
             ExternType::Global(global_type) => {
                 let obj_val = val.to_object(&context).unwrap();
                 Ok(Self::Global(Global::from_vm_extern(
@@ -149,6 +155,10 @@ impl AsJs for Extern {
                     VMTable::new(obj_val, table_type.clone()),
                 )))
             }
+            ExternType::Tag(_) => Err(JSValue::string(
+                &context,
+                "Tags are not yet supported in the JSC API".to_string(),
+            )),
         }
     }
 }
@@ -7,6 +7,7 @@ use crate::jsc::vm::VMInstance;
 use crate::module::Module;
 use crate::store::AsStoreMut;
 use crate::Extern;
+use wasmer_types::{ExternType, Type};
 
 #[derive(Clone, PartialEq, Eq)]
 pub struct Instance {
@@ -60,6 +61,13 @@ impl Instance {
 
         let exports = exports_ty
             .iter()
+            // Tags and `exnref` globals have no representation in this API,
+            // so they are left out of the exports.
+            .filter(|export_type| match export_type.ty() {
+                ExternType::Tag(_) => false,
+                ExternType::Global(global_type) => global_type.ty != Type::ExceptionRef,
+                _ => true,
+            })
             .map(|export_type| {
                 let name = export_type.name();
                 let mut store = store.as_store_mut();
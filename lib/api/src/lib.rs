@@ -74,7 +74,10 @@
 //!   * [`wasmer-compiler-cranelift`] provides the right balance between
 //!     compilation-time and runtime performance, useful for development,
 //!   * [`wasmer-compiler-llvm`] provides a deeply optimized executable
-//!     code with the fastest runtime speed, ideal for production.
+//!     code with the fastest runtime speed, ideal for production,
+//!   * [`wasmer-compiler-interpreter`] doesn't generate any executable
+//!     code and interprets WebAssembly instead, for environments where
+//!     JIT compilation is forbidden.
 //!
 //! * **Headless mode** — Once a WebAssembly module has been compiled, it
 //!   is possible to serialize it in a file for example, and later execute
@@ -296,6 +299,10 @@
 #![cfg_attr(feature = "singlepass", doc = "(enabled),")]
 #![cfg_attr(not(feature = "singlepass"), doc = "(disabled),")]
 //!   enables Wasmer's [Singlepass compiler][wasmer-compiler-singlepass],
+//! - `interpreter`
+#![cfg_attr(feature = "interpreter", doc = "(enabled),")]
+#![cfg_attr(not(feature = "interpreter"), doc = "(disabled),")]
+//!   enables Wasmer's [interpreter][wasmer-compiler-interpreter],
 //! - `wat`
 #![cfg_attr(feature = "wat", doc = "(enabled),")]
 #![cfg_attr(not(feature = "wat"), doc = "(disabled),")]
@@ -387,6 +394,7 @@
 //! [`wasmer-compiler-singlepass`]: https://docs.rs/wasmer-compiler-singlepass/
 //! [`wasmer-compiler-llvm`]: https://docs.rs/wasmer-compiler-llvm/
 //! [`wasmer-compiler-cranelift`]: https://docs.rs/wasmer-compiler-cranelift/
+//! [`wasmer-compiler-interpreter`]: https://docs.rs/wasmer-compiler-interpreter/
 //! [`wasmer-wasix`]: https://docs.rs/wasmer-wasix/
 //! [`wasm-pack`]: https://github.com/rustwasm/wasm-pack/
 //! [`wasm-bindgen`]: https://github.com/rustwasm/wasm-bindgen
@@ -519,8 +527,9 @@ pub use wasmer_types::{
     is_wasm, Bytes, CompileError, CpuFeature, DeserializeError, ExportIndex, ExportType,
     ExternType, FrameInfo, FunctionType, GlobalInit, GlobalType, ImportType, LocalFunctionIndex,
     MemoryError, MemoryType, MiddlewareError, Mutability, OnCalledAction, Pages,
-    ParseCpuFeatureError, SerializeError, TableType, Target, Type, ValueType, WasmError,
-    WasmResult, WASM_MAX_PAGES, WASM_MIN_PAGES, WASM_PAGE_SIZE,
+    ParseCpuFeatureError, ParseProfileError, Profile, SerializeError, SourceLocation, TableType,
+    TagType, Target, Type, ValueType, WasmError, WasmResult, WASM_MAX_PAGES, WASM_MIN_PAGES,
+    WASM_PAGE_SIZE,
 };
 #[cfg(feature = "wat")]
 pub use wat::parse_bytes as wat2wasm;
@@ -6,12 +6,16 @@ use std::path::Path;
 
 use crate::engine::AsEngineRef;
 use thiserror::Error;
+#[cfg(feature = "compiler")]
+use wasmer_types::Target;
 #[cfg(feature = "wat")]
 use wasmer_types::WasmError;
 use wasmer_types::{
     CompileError, DeserializeError, ExportsIterator, ImportsIterator, ModuleInfo, SerializeError,
 };
 use wasmer_types::{ExportType, ImportType};
+#[cfg(feature = "sys")]
+use wasmer_types::{FunctionIndex, Profile};
 
 use crate::into_bytes::IntoBytes;
 
@@ -468,6 +472,93 @@ impl Module {
     }
 }
 
+#[cfg(feature = "sys")]
+impl Module {
+    /// Whether the function at `index` was recompiled with the optimizing
+    /// compiler of the engine's [`TierUp`](crate::sys::TierUp).
+    ///
+    /// Imported functions are never tiered up.
+    pub fn is_tiered_up(&self, index: FunctionIndex) -> bool {
+        self.0.is_tiered_up(index)
+    }
+
+    /// Serializes the module like [`Module::serialize`], and signs it for
+    /// `engine` with `signer`, so that it can be loaded with
+    /// [`Module::deserialize_checked`] by the holders of its public key.
+    #[cfg(feature = "signing")]
+    pub fn serialize_signed(
+        &self,
+        engine: &impl AsEngineRef,
+        signer: &crate::sys::ArtifactSigner,
+    ) -> Result<Bytes, SerializeError> {
+        self.0.serialize_signed(engine, signer)
+    }
+
+    /// Deserializes a module serialized with [`Module::serialize_signed`].
+    ///
+    /// The signature is checked against `verifier` before anything is
+    /// loaded, along with the engine the module was signed for, so unlike
+    /// [`Module::deserialize`] this can be used on bytes coming from an
+    /// untrusted cache or registry.
+    #[cfg(feature = "signing")]
+    pub fn deserialize_checked(
+        engine: &impl AsEngineRef,
+        bytes: impl IntoBytes,
+        verifier: &crate::sys::ArtifactVerifier,
+    ) -> Result<Self, DeserializeError> {
+        Ok(Self(module_imp::Module::deserialize_checked(
+            engine, bytes, verifier,
+        )?))
+    }
+
+    /// Compiles a WebAssembly binary for another `target` and serializes
+    /// it, without loading it.
+    ///
+    /// The target may have another architecture than the host, or other
+    /// CPU features, such as a baseline and a more recent CPU of the same
+    /// architecture. The binary is compiled with the compiler and features
+    /// of `engine`. The result can be loaded on the target with
+    /// [`Module::deserialize`] or, among the artifacts compiled for several
+    /// targets, with [`Module::deserialize_best`].
+    #[cfg(feature = "compiler")]
+    pub fn precompile_for(
+        engine: &impl AsEngineRef,
+        binary: &[u8],
+        target: &Target,
+    ) -> Result<Bytes, CompileError> {
+        module_imp::Module::precompile_for(engine, binary, target)
+    }
+
+    /// Deserializes the module among `candidates` that suits the host best,
+    /// such as the artifacts created with [`Module::precompile_for`] for
+    /// several CPUs.
+    ///
+    /// The best module is the one using the most CPU features the target of
+    /// `engine` supports, among the ones compiled for its architecture and
+    /// operating system.
+    ///
+    /// # Safety
+    ///
+    /// See [`Module::deserialize`].
+    pub unsafe fn deserialize_best<B: IntoBytes>(
+        engine: &impl AsEngineRef,
+        candidates: impl IntoIterator<Item = B>,
+    ) -> Result<Self, DeserializeError> {
+        Ok(Self(module_imp::Module::deserialize_best(
+            engine, candidates,
+        )?))
+    }
+
+    /// The calls and branches counted by the instances of this module so
+    /// far, if it was compiled with profile instrumentation.
+    ///
+    /// The profile can be fed back to the LLVM compiler to optimize the
+    /// module for the recorded workload.
+    pub fn profile(&self) -> Option<Profile> {
+        self.0.profile()
+    }
+}
+
 impl fmt::Debug for Module {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         f.debug_struct("Module")
@@ -395,14 +395,12 @@ pub fn parse_import_section<'data>(
                 initial,
                 maximum,
             }) => {
-                if memory64 {
-                    unimplemented!("64bit memory not implemented yet");
-                }
                 module_info.declare_memory_import(
                     MemoryType {
                         minimum: Pages(initial as u32),
                         maximum: maximum.map(|p| Pages(p as u32)),
                         shared,
+                        memory64,
                     },
                     module_name,
                     field_name,
@@ -483,13 +481,11 @@ pub fn parse_memory_section(
             initial,
             maximum,
         } = entry.map_err(transform_err)?;
-        if memory64 {
-            unimplemented!("64bit memory not implemented yet");
-        }
         module_info.declare_memory(MemoryType {
             minimum: Pages(initial as u32),
             maximum: maximum.map(|p| Pages(p as u32)),
             shared,
+            memory64,
         })?;
     }
 
@@ -7,6 +7,7 @@ pub use wasmer_compiler::{
 #[cfg(feature = "compiler")]
 use wasmer_types::Features;
 use wasmer_types::{DeserializeError, HashAlgorithm, Target};
+use wasmer_vm::MemoryWatch;
 
 /// Get the default config for the sys Engine
 #[allow(unreachable_code)]
@@ -18,6 +19,8 @@ pub fn get_default_compiler_config() -> Option<Box<dyn wasmer_compiler::Compiler
             Some(Box::<wasmer_compiler_llvm::LLVM>::default())
         } else if #[cfg(feature = "singlepass")] {
             Some(Box::<wasmer_compiler_singlepass::Singlepass>::default())
+        } else if #[cfg(feature = "interpreter")] {
+            Some(Box::<wasmer_compiler_interpreter::Interpreter>::default())
         }
         else {
             None
@@ -60,6 +63,15 @@ pub trait NativeEngineExt {
     /// Sets the hash algorithm
     fn set_hash_algorithm(&mut self, hash_algorithm: Option<HashAlgorithm>);
 
+    /// Sets whether the modules compiled by this engine serialize
+    /// reproducibly (see [`wasmer_compiler::Engine::set_reproducible`]).
+    fn set_reproducible(&mut self, reproducible: bool);
+
+    /// Sets where new instances report the memory accesses of modules
+    /// compiled with the memory watch enabled (see
+    /// [`CompilerConfig::enable_memory_watch`]).
+    fn set_memory_watch(&mut self, memory_watch: Option<Arc<MemoryWatch>>);
+
     /// Create a headless `Engine`
     ///
     /// A headless engine is an engine without any compiler attached.
@@ -160,4 +172,12 @@ impl NativeEngineExt for crate::engine::Engine {
     fn set_hash_algorithm(&mut self, hash_algorithm: Option<HashAlgorithm>) {
         self.0.set_hash_algorithm(hash_algorithm)
     }
+
+    fn set_reproducible(&mut self, reproducible: bool) {
+        self.0.set_reproducible(reproducible)
+    }
+
+    fn set_memory_watch(&mut self, memory_watch: Option<Arc<MemoryWatch>>) {
+        self.0.set_memory_watch(memory_watch)
+    }
 }
@@ -0,0 +1,152 @@
+use std::error::Error;
+use std::fmt;
+
+use wasmer_vm::{StoreHandle, Trap, VMException, VMExceptionRef};
+
+use crate::store::{AsStoreMut, AsStoreRef};
+use crate::{RuntimeError, Tag, Value};
+
+/// An exception of the exception-handling proposal, thrown by a WebAssembly
+/// `throw` or by a host function returning it as its error.
+///
+/// An exception that no `try_table` catches is returned as the error of
+/// the call into WebAssembly, and can be taken back with
+/// [`RuntimeError::downcast`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Exception {
+    handle: StoreHandle<VMException>,
+}
+
+impl Exception {
+    /// Creates a new exception of the given `tag`, carrying the values of
+    /// `payload`.
+    ///
+    /// Fails if the values don't match the parameters of the tag.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # use wasmer::{Exception, Store, Tag, TagType, Type, Value};
+    /// # let mut store = Store::default();
+    /// #
+    /// let tag = Tag::new(&mut store, TagType::new([Type::I32]));
+    /// let exception = Exception::new(&mut store, &tag, &[Value::I32(42)]).unwrap();
+    ///
+    /// assert_eq!(exception.tag(&mut store), tag);
+    /// assert_eq!(exception.payload(&mut store), vec![Value::I32(42)]);
+    /// ```
+    pub fn new(
+        store: &mut impl AsStoreMut,
+        tag: &Tag,
+        payload: &[Value],
+    ) -> Result<Self, RuntimeError> {
+        if !tag.is_from_store(store) || payload.iter().any(|value| !value.is_from_store(store)) {
+            return Err(RuntimeError::new("cross-`Store` values are not supported"));
+        }
+        let ty = tag.ty(store);
+        if payload
+            .iter()
+            .map(Value::ty)
+            .ne(ty.params().iter().copied())
+        {
+            return Err(RuntimeError::new(format!(
+                "Exception payload doesn't match its tag. Expected {:?} but got {:?}",
+                ty.params(),
+                payload.iter().map(Value::ty).collect::<Vec<_>>(),
+            )));
+        }
+        let payload = payload.iter().map(|value| value.as_raw(store)).collect();
+        let exception = VMException::new(tag.internal_handle(), payload);
+        Ok(Self {
+            handle: StoreHandle::new(store.objects_mut(), exception),
+        })
+    }
+
+    /// Returns the tag of the exception.
+    pub fn tag(&self, store: &mut impl AsStoreMut) -> Tag {
+        let tag = self.handle.get(store.as_store_ref().objects()).tag();
+        Tag::from_vm_extern(store, tag)
+    }
+
+    /// Returns the values carried by the exception.
+    pub fn payload(&self, store: &mut impl AsStoreMut) -> Vec<Value> {
+        let objects = store.as_store_ref().objects();
+        let exception = self.handle.get(objects);
+        let types = exception.tag().get(objects).ty().params().to_vec();
+        let payload = exception.payload().to_vec();
+        types
+            .into_iter()
+            .zip(payload)
+            .map(|(ty, raw)| unsafe { Value::from_raw(store, ty, raw) })
+            .collect()
+    }
+
+    pub(crate) fn vm_exceptionref(&self) -> VMExceptionRef {
+        VMExceptionRef(self.handle.internal_handle())
+    }
+
+    pub(crate) unsafe fn from_vm_exceptionref(
+        store: &mut impl AsStoreMut,
+        vm_exceptionref: VMExceptionRef,
+    ) -> Self {
+        Self {
+            handle: StoreHandle::from_internal(store.objects_mut().id(), vm_exceptionref.0),
+        }
+    }
+
+    /// Checks whether this `Exception` can be used with the given store.
+    pub fn is_from_store(&self, store: &impl AsStoreRef) -> bool {
+        self.handle.store_id() == store.as_store_ref().objects().id()
+    }
+}
+
+impl fmt::Display for Exception {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "uncaught exception")
+    }
+}
+
+impl Error for Exception {}
+
+impl From<Exception> for RuntimeError {
+    fn from(exception: Exception) -> Self {
+        Self::user(Box::new(exception))
+    }
+}
+
+/// Returns the exception carried by the error of a host function, to leave
+/// it pending in the store rather than raising a trap, if the instances of
+/// the store propagate exceptions.
+pub(crate) fn host_exception(
+    store: &impl AsStoreRef,
+    error: &(dyn Error + 'static),
+) -> Option<VMExceptionRef> {
+    if !store.as_store_ref().objects().exceptions_enabled() {
+        return None;
+    }
+    let exception = match error.downcast_ref::<Exception>() {
+        Some(exception) => exception,
+        None => error
+            .downcast_ref::<RuntimeError>()?
+            .downcast_ref::<Exception>()?,
+    };
+    if exception.is_from_store(store) {
+        Some(exception.vm_exceptionref())
+    } else {
+        None
+    }
+}
+
+/// Turns the exception left pending in the store by a call into
+/// WebAssembly, if any, into the error of the call.
+pub(crate) fn pending_exception_result(
+    store: &mut impl AsStoreMut,
+    result: Result<(), Trap>,
+) -> Result<(), RuntimeError> {
+    let exception = store.as_store_ref().objects().take_pending_exception();
+    result?;
+    match exception {
+        Some(exception) => Err(unsafe { Exception::from_vm_exceptionref(store, exception) }.into()),
+        None => Ok(()),
+    }
+}
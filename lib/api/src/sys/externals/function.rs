@@ -2,6 +2,7 @@ use crate::externals::function::{HostFunction, WithEnv, WithoutEnv};
 use crate::native_type::{FromToNativeWasmType, IntoResult, NativeWasmTypeInto, WasmTypeList};
 use crate::store::{AsStoreMut, AsStoreRef, StoreInner, StoreMut};
 use crate::sys::engine::NativeEngineExt;
+use crate::sys::exception::{host_exception, pending_exception_result};
 use crate::vm::{VMExternFunction, VMFunctionCallback};
 use crate::{FunctionEnv, FunctionEnvMut, FunctionType, RuntimeError, Value};
 use std::panic::{self, AssertUnwindSafe};
@@ -54,7 +55,24 @@ impl Function {
                     store_mut,
                     func_env: func_env.clone(),
                 };
-                let returns = func(env, &args)?;
+                let returns = match func(env, &args) {
+                    Ok(returns) => returns,
+                    Err(error) => {
+                        // Exceptions are left pending for the caller, with
+                        // zeroed results.
+                        if let Some(exception) = host_exception(&store, &error) {
+                            store
+                                .as_store_ref()
+                                .objects()
+                                .set_pending_exception(Some(exception));
+                            for i in 0..func_ty.results().len() {
+                                *values_vec.add(i) = RawValue { u128: 0 };
+                            }
+                            return Ok(());
+                        }
+                        return Err(error);
+                    }
+                };
 
                 // We need to dynamically check that the returns
                 // match the expected types, as well as expected length.
@@ -304,9 +322,7 @@ impl Function {
             }
             r
         };
-        if let Err(error) = result {
-            return Err(error.into());
-        }
+        pending_exception_result(store, result)?;
 
         // Load the return values out of `values_vec`.
         let signature = self.ty(store);
@@ -512,7 +528,15 @@ macro_rules! impl_host_function {
 
                         match result {
                             Ok(Ok(result)) => return result.into_c_struct(&mut store),
-                            Ok(Err(trap)) => raise_user_trap(Box::new(trap)),
+                            Ok(Err(trap)) => {
+                                // Exceptions are left pending for the
+                                // caller, with zeroed results.
+                                if let Some(exception) = host_exception(&store, &trap) {
+                                    store.as_store_ref().objects().set_pending_exception(Some(exception));
+                                    return std::mem::zeroed();
+                                }
+                                raise_user_trap(Box::new(trap))
+                            }
                             Err(panic) => resume_panic(panic) ,
                         }
                     }
@@ -589,7 +613,15 @@ macro_rules! impl_host_function {
 
                         match result {
                             Ok(Ok(result)) => return result.into_c_struct(&mut store),
-                            Ok(Err(trap)) => raise_user_trap(Box::new(trap)),
+                            Ok(Err(trap)) => {
+                                // Exceptions are left pending for the
+                                // caller, with zeroed results.
+                                if let Some(exception) = host_exception(&store, &trap) {
+                                    store.as_store_ref().objects().set_pending_exception(Some(exception));
+                                    return std::mem::zeroed();
+                                }
+                                raise_user_trap(Box::new(trap))
+                            }
                             Err(panic) => resume_panic(panic) ,
                         }
                     }
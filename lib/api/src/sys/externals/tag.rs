@@ -0,0 +1,72 @@
+use crate::exports::{ExportError, Exportable};
+use crate::store::{AsStoreMut, AsStoreRef};
+use crate::Extern;
+use wasmer_types::TagType;
+use wasmer_vm::{InternalStoreHandle, StoreHandle, VMExtern, VMTag};
+
+/// A WebAssembly `tag` instance, identifying the exceptions of the
+/// exception-handling proposal thrown with it.
+///
+/// Spec: <https://webassembly.github.io/exception-handling/core/exec/runtime.html#tag-instances>
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Tag {
+    handle: StoreHandle<VMTag>,
+}
+
+impl Tag {
+    /// Creates a new `Tag` of the given type.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # use wasmer::{Store, Tag, TagType, Type};
+    /// # let mut store = Store::default();
+    /// #
+    /// let tag = Tag::new(&mut store, TagType::new([Type::I32]));
+    ///
+    /// assert_eq!(tag.ty(&store).params(), &[Type::I32]);
+    /// ```
+    pub fn new(store: &mut impl AsStoreMut, ty: TagType) -> Self {
+        Self {
+            handle: StoreHandle::new(store.objects_mut(), VMTag::new(ty)),
+        }
+    }
+
+    /// Returns the [`TagType`] of the `Tag`.
+    pub fn ty(&self, store: &impl AsStoreRef) -> TagType {
+        self.handle.get(store.as_store_ref().objects()).ty().clone()
+    }
+
+    pub(crate) fn from_vm_extern(
+        store: &mut impl AsStoreMut,
+        vm_extern: InternalStoreHandle<VMTag>,
+    ) -> Self {
+        Self {
+            handle: unsafe {
+                StoreHandle::from_internal(store.as_store_ref().objects().id(), vm_extern)
+            },
+        }
+    }
+
+    /// Checks whether this `Tag` can be used with the given store.
+    pub fn is_from_store(&self, store: &impl AsStoreRef) -> bool {
+        self.handle.store_id() == store.as_store_ref().objects().id()
+    }
+
+    pub(crate) fn to_vm_extern(&self) -> VMExtern {
+        VMExtern::Tag(self.handle.internal_handle())
+    }
+
+    pub(crate) fn internal_handle(&self) -> InternalStoreHandle<VMTag> {
+        self.handle.internal_handle()
+    }
+}
+
+impl<'a> Exportable<'a> for Tag {
+    fn get_self_from_extern(_extern: &'a Extern) -> Result<&'a Self, ExportError> {
+        match _extern {
+            Extern::Tag(tag) => Ok(tag),
+            _ => Err(ExportError::IncompatibleType),
+        }
+    }
+}
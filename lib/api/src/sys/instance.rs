@@ -34,6 +34,7 @@ impl From<wasmer_compiler::InstantiationError> for InstantiationError {
             wasmer_compiler::InstantiationError::Link(e) => Self::Link(e.into()),
             wasmer_compiler::InstantiationError::Start(e) => Self::Start(e.into()),
             wasmer_compiler::InstantiationError::CpuFeature(e) => Self::CpuFeature(e),
+            wasmer_compiler::InstantiationError::MixedCode => Self::MixedCode,
         }
     }
 }
@@ -1,5 +1,6 @@
 pub(crate) mod engine;
 pub(crate) mod errors;
+pub(crate) mod exception;
 pub(crate) mod extern_ref;
 pub(crate) mod externals;
 pub(crate) mod instance;
@@ -9,27 +10,38 @@ pub(super) mod tunables;
 pub(crate) mod typed_function;
 
 pub use crate::sys::engine::{get_default_compiler_config, NativeEngineExt};
+pub use crate::sys::exception::Exception;
+pub use crate::sys::externals::tag::Tag;
 pub use crate::sys::tunables::BaseTunables;
 #[cfg(feature = "compiler")]
 pub use wasmer_compiler::{
-    wasmparser, CompilerConfig, FunctionMiddleware, MiddlewareReaderState, ModuleMiddleware,
+    wasmparser, CompilerConfig, FunctionMiddleware, MiddlewareReaderState, ModuleMiddleware, TierUp,
 };
-pub use wasmer_compiler::{Artifact, EngineBuilder, Features, Tunables};
+pub use wasmer_compiler::{
+    Artifact, ArtifactBuild, ArtifactSummary, EngineBuilder, Features, Tunables,
+};
+#[cfg(feature = "signing")]
+pub use wasmer_compiler::{ArtifactSigner, ArtifactVerifier, InvalidKeyError};
 #[cfg(feature = "cranelift")]
 pub use wasmer_compiler_cranelift::{Cranelift, CraneliftOptLevel};
+#[cfg(feature = "interpreter")]
+pub use wasmer_compiler_interpreter::Interpreter;
 #[cfg(feature = "llvm")]
 pub use wasmer_compiler_llvm::{LLVMOptLevel, LLVM};
 #[cfg(feature = "singlepass")]
 pub use wasmer_compiler_singlepass::Singlepass;
 
-pub use wasmer_vm::VMConfig;
+pub use wasmer_vm::{
+    attach_debugger, detach_debugger, DebugFrame, Debugger, MemoryAccess, MemoryAccessKind,
+    MemoryWatch, VMConfig,
+};
 
 pub(crate) mod vm {
     //! The `vm` module re-exports wasmer-vm types.
     use wasmer_vm::InternalStoreHandle;
     pub(crate) use wasmer_vm::{
-        VMExtern, VMExternRef, VMFuncRef, VMFunction, VMFunctionBody, VMFunctionEnvironment,
-        VMGlobal, VMInstance, VMMemory, VMTable, VMTrampoline,
+        VMExceptionRef, VMExtern, VMExternRef, VMFuncRef, VMFunction, VMFunctionBody,
+        VMFunctionEnvironment, VMGlobal, VMInstance, VMMemory, VMTable, VMTrampoline,
     };
 
     pub(crate) type VMExternTable = InternalStoreHandle<VMTable>;
@@ -3,14 +3,17 @@ use std::sync::Arc;
 
 use bytes::Bytes;
 use wasmer_compiler::{Artifact, ArtifactCreate};
+#[cfg(feature = "compiler")]
+use wasmer_types::Target;
 use wasmer_types::{
-    CompileError, DeserializeError, ExportsIterator, ImportsIterator, ModuleInfo, SerializeError,
+    CompileError, DeserializeError, ExportsIterator, FunctionIndex, ImportsIterator, ModuleInfo,
+    Profile, SerializeError,
 };
 use wasmer_types::{ExportType, ImportType};
 
 use crate::{
     engine::AsEngineRef, sys::engine::NativeEngineExt, vm::VMInstance, AsStoreMut, AsStoreRef,
-    InstantiationError, IntoBytes,
+    Exception, InstantiationError, IntoBytes,
 };
 
 #[derive(Clone, PartialEq, Eq)]
@@ -71,6 +74,64 @@ impl Module {
         self.artifact.serialize().map(|bytes| bytes.into())
     }
 
+    #[cfg(feature = "signing")]
+    pub(crate) fn serialize_signed(
+        &self,
+        engine: &impl AsEngineRef,
+        signer: &wasmer_compiler::ArtifactSigner,
+    ) -> Result<Bytes, SerializeError> {
+        let bytes = self.artifact.serialize()?;
+        Ok(signer
+            .sign(&engine.as_engine_ref().engine().0, &bytes)
+            .into())
+    }
+
+    #[cfg(feature = "signing")]
+    #[tracing::instrument(level = "debug", skip_all)]
+    pub(crate) fn deserialize_checked(
+        engine: &impl AsEngineRef,
+        bytes: impl IntoBytes,
+        verifier: &wasmer_compiler::ArtifactVerifier,
+    ) -> Result<Self, DeserializeError> {
+        let bytes = bytes.into_bytes();
+        let artifact = engine
+            .as_engine_ref()
+            .engine()
+            .0
+            .deserialize_verified(bytes.into(), verifier)?;
+        Ok(Self::from_artifact(artifact))
+    }
+
+    #[cfg(feature = "compiler")]
+    pub(crate) fn precompile_for(
+        engine: &impl AsEngineRef,
+        binary: &[u8],
+        target: &Target,
+    ) -> Result<Bytes, CompileError> {
+        let bytes = engine
+            .as_engine_ref()
+            .engine()
+            .0
+            .precompile(binary, target)?;
+        Ok(bytes.into())
+    }
+
+    #[tracing::instrument(level = "debug", skip_all)]
+    pub(crate) unsafe fn deserialize_best<B: IntoBytes>(
+        engine: &impl AsEngineRef,
+        candidates: impl IntoIterator<Item = B>,
+    ) -> Result<Self, DeserializeError> {
+        let candidates = candidates
+            .into_iter()
+            .map(|bytes| bytes.into_bytes().into());
+        let artifact = engine
+            .as_engine_ref()
+            .engine()
+            .0
+            .deserialize_best(candidates)?;
+        Ok(Self::from_artifact(artifact))
+    }
+
     #[tracing::instrument(level = "debug", skip_all)]
     pub unsafe fn deserialize_unchecked(
         engine: &impl AsEngineRef,
@@ -157,14 +218,23 @@ impl Module {
                     .collect::<Vec<_>>(),
                 objects,
             )?;
+            instance_handle.set_memory_watch(engine.0.memory_watch().cloned());
 
             // After the instance handle is created, we need to initialize
             // the data, call the start function and so. However, if any
             // of this steps traps, we still need to keep the instance alive
             // as some of the Instance elements may have placed in other
             // instance tables.
-            self.artifact
-                .finish_instantiation(config, signal_handler, &mut instance_handle)?;
+            let result =
+                self.artifact
+                    .finish_instantiation(config, signal_handler, &mut instance_handle);
+            // An exception thrown by the start function is left pending.
+            let exception = store.as_store_ref().objects().take_pending_exception();
+            result?;
+            if let Some(exception) = exception {
+                let exception = Exception::from_vm_exceptionref(store, exception);
+                return Err(InstantiationError::Start(exception.into()));
+            }
 
             Ok(instance_handle)
         }
@@ -195,6 +265,17 @@ impl Module {
         self.info().custom_sections(name)
     }
 
+    pub(crate) fn is_tiered_up(&self, index: FunctionIndex) -> bool {
+        self.artifact
+            .module_info()
+            .local_func_index(index)
+            .map_or(false, |index| self.artifact.is_tiered_up(index))
+    }
+
+    pub(crate) fn profile(&self) -> Option<Profile> {
+        self.artifact.profile()
+    }
+
     pub(crate) fn info(&self) -> &ModuleInfo {
         self.artifact.module_info()
     }
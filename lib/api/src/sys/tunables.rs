@@ -55,6 +55,14 @@ mod tests {
             }
             s => panic!("Unexpected memory style: {:?}", s),
         }
+
+        // Small maximum on a 64-bit memory
+        let requested = MemoryType::new(3, Some(16), true).with_memory64(true);
+        let style = tunables.memory_style(&requested);
+        match style {
+            MemoryStyle::Dynamic { offset_guard_size } => assert_eq!(offset_guard_size, 256),
+            s => panic!("Unexpected memory style: {:?}", s),
+        }
     }
 
     #[derive(Debug)]
@@ -89,6 +97,7 @@ mod tests {
                 minimum: Pages::from(18u32),
                 maximum: Some(Pages::from(18u32)),
                 shared: false,
+                memory64: false,
             }
         }
         fn size(&self) -> Pages {
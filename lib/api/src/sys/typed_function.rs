@@ -4,6 +4,7 @@ use wasmer_types::RawValue;
 use crate::native_type::NativeWasmTypeInto;
 use crate::store::{AsStoreMut, AsStoreRef};
 use crate::sys::engine::NativeEngineExt;
+use crate::sys::exception::pending_exception_result;
 
 macro_rules! impl_native_traits {
     (  $( $x:ident ),* ) => {
@@ -73,7 +74,7 @@ macro_rules! impl_native_traits {
                     }
                     break;
                 }
-                r?;
+                pending_exception_result(store, r)?;
 
                 let num_rets = rets_list.len();
                 if !using_rets_array && num_rets > 0 {
@@ -156,7 +157,7 @@ macro_rules! impl_native_traits {
                     }
                     break;
                 }
-                r?;
+                pending_exception_result(store, r)?;
 
                 let num_rets = rets_list.len();
                 if !using_rets_array && num_rets > 0 {
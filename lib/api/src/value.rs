@@ -4,8 +4,12 @@ use std::string::{String, ToString};
 
 use wasmer_types::Type;
 
+#[cfg(feature = "sys")]
+use crate::vm::VMExceptionRef;
 use crate::vm::{VMExternRef, VMFuncRef};
 
+#[cfg(feature = "sys")]
+use crate::Exception;
 use crate::ExternRef;
 use crate::Function;
 
@@ -45,6 +49,10 @@ pub enum Value {
 
     /// A 128-bit number
     V128(u128),
+
+    /// A reference to an exception of the exception-handling proposal.
+    #[cfg(feature = "sys")]
+    ExceptionRef(Option<Exception>),
 }
 
 macro_rules! accessors {
@@ -87,6 +95,8 @@ impl Value {
             Self::ExternRef(_) => Type::ExternRef,
             Self::FuncRef(_) => Type::FuncRef,
             Self::V128(_) => Type::V128,
+            #[cfg(feature = "sys")]
+            Self::ExceptionRef(_) => Type::ExceptionRef,
         }
     }
 
@@ -103,6 +113,10 @@ impl Value {
             Self::FuncRef(None) => RawValue { funcref: 0 },
             Self::ExternRef(Some(ref e)) => e.vm_externref().into_raw(),
             Self::ExternRef(None) => RawValue { externref: 0 },
+            #[cfg(feature = "sys")]
+            Self::ExceptionRef(Some(ref e)) => e.vm_exceptionref().into_raw(),
+            #[cfg(feature = "sys")]
+            Self::ExceptionRef(None) => RawValue { exnref: 0 },
         }
     }
 
@@ -123,6 +137,12 @@ impl Value {
             Type::ExternRef => Self::ExternRef(
                 VMExternRef::from_raw(raw).map(|e| ExternRef::from_vm_externref(store, e)),
             ),
+            #[cfg(feature = "sys")]
+            Type::ExceptionRef => Self::ExceptionRef(
+                VMExceptionRef::from_raw(raw).map(|e| Exception::from_vm_exceptionref(store, e)),
+            ),
+            #[cfg(not(feature = "sys"))]
+            Type::ExceptionRef => unimplemented!("exception references are not supported"),
         }
     }
 
@@ -144,6 +164,10 @@ impl Value {
             | Self::FuncRef(None) => true,
             Self::ExternRef(Some(e)) => e.is_from_store(store),
             Self::FuncRef(Some(f)) => f.is_from_store(store),
+            #[cfg(feature = "sys")]
+            Self::ExceptionRef(None) => true,
+            #[cfg(feature = "sys")]
+            Self::ExceptionRef(Some(e)) => e.is_from_store(store),
         }
     }
 
@@ -159,6 +183,14 @@ impl Value {
     }
 }
 
+#[cfg(feature = "sys")]
+impl Value {
+    accessors! {
+        e
+        (ExceptionRef(&Option<Exception>) exceptionref unwrap_exceptionref e)
+    }
+}
+
 impl fmt::Debug for Value {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         match self {
@@ -171,6 +203,10 @@ impl fmt::Debug for Value {
             Self::FuncRef(None) => write!(f, "Null FuncRef"),
             Self::FuncRef(Some(v)) => write!(f, "FuncRef({:?})", v),
             Self::V128(v) => write!(f, "V128({:?})", v),
+            #[cfg(feature = "sys")]
+            Self::ExceptionRef(None) => write!(f, "Null ExceptionRef"),
+            #[cfg(feature = "sys")]
+            Self::ExceptionRef(Some(v)) => write!(f, "ExceptionRef({:?})", v),
         }
     }
 }
@@ -185,6 +221,8 @@ impl ToString for Value {
             Self::ExternRef(_) => "externref".to_string(),
             Self::FuncRef(_) => "funcref".to_string(),
             Self::V128(v) => v.to_string(),
+            #[cfg(feature = "sys")]
+            Self::ExceptionRef(_) => "exnref".to_string(),
         }
     }
 }
@@ -18,6 +18,9 @@ pub(crate) use crate::sys::vm::{
     VMFuncRef, VMFunctionCallback, VMFunctionEnvironment, VMInstance, VMTrampoline,
 };
 
+#[cfg(feature = "sys")]
+pub(crate) use crate::sys::vm::VMExceptionRef;
+
 #[cfg(feature = "js")]
 pub use crate::js::vm::{VMFunction, VMGlobal, VMMemory, VMSharedMemory, VMTable};
 
@@ -168,6 +168,7 @@ fn memory_new() -> Result<(), String> {
         shared: false,
         minimum: Pages(0),
         maximum: Some(Pages(10)),
+        memory64: false,
     };
     let memory = Memory::new(&mut store, memory_type).map_err(|e| format!("{e:?}"))?;
     assert_eq!(memory.view(&store).size(), Pages(0));
@@ -51,7 +51,7 @@ pub unsafe extern "C" fn wasm_func_new(
     let function_type = function_type?;
     let callback = callback?;
     let store = store?;
-    let mut store_mut = store.inner.store_mut();
+    let mut store_mut = c_try!(store.inner.store_mut());
 
     let func_sig = &function_type.inner().function_type;
     let num_rets = func_sig.results().len();
@@ -107,7 +107,7 @@ pub unsafe extern "C" fn wasm_func_new_with_env(
     let function_type = function_type?;
     let callback = callback?;
     let store = store?;
-    let mut store_mut = store.inner.store_mut();
+    let mut store_mut = c_try!(store.inner.store_mut());
 
     let func_sig = &function_type.inner().function_type;
     let num_rets = func_sig.results().len();
@@ -202,7 +202,10 @@ pub unsafe extern "C" fn wasm_func_call(
     let func = func?;
     let args = args?;
     let mut store = func.extern_.store.clone();
-    let mut store_mut = store.store_mut();
+    let mut store_mut = match store.store_mut() {
+        Ok(store_mut) => store_mut,
+        Err(error) => return Some(Box::new(RuntimeError::new(error.to_string()).into())),
+    };
     let params = args
         .as_slice()
         .iter()
@@ -229,20 +232,14 @@ pub unsafe extern "C" fn wasm_func_call(
 
 #[no_mangle]
 pub unsafe extern "C" fn wasm_func_param_arity(func: &wasm_func_t) -> usize {
-    func.extern_
-        .function()
-        .ty(&func.extern_.store.store())
-        .params()
-        .len()
+    let store = c_try!(func.extern_.store.store(); otherwise 0);
+    func.extern_.function().ty(&store).params().len()
 }
 
 #[no_mangle]
 pub unsafe extern "C" fn wasm_func_result_arity(func: &wasm_func_t) -> usize {
-    func.extern_
-        .function()
-        .ty(&func.extern_.store.store())
-        .results()
-        .len()
+    let store = c_try!(func.extern_.store.store(); otherwise 0);
+    func.extern_.function().ty(&store).results().len()
 }
 
 #[no_mangle]
@@ -250,7 +247,8 @@ pub unsafe extern "C" fn wasm_func_type(
     func: Option<&wasm_func_t>,
 ) -> Option<Box<wasm_functype_t>> {
     let func = func?;
+    let store = c_try!(func.extern_.store.store());
     Some(Box::new(wasm_functype_t::new(
-        func.extern_.function().ty(&func.extern_.store.store()),
+        func.extern_.function().ty(&store),
     )))
 }
@@ -29,7 +29,7 @@ pub unsafe extern "C" fn wasm_global_new(
 ) -> Option<Box<wasm_global_t>> {
     let global_type = global_type?;
     let store = store?;
-    let mut store_mut = store.inner.store_mut();
+    let mut store_mut = c_try!(store.inner.store_mut());
     let val = val?;
 
     let global_type = &global_type.inner().global_type;
@@ -59,10 +59,9 @@ pub unsafe extern "C" fn wasm_global_get(
     // own
     out: &mut wasm_val_t,
 ) {
-    let value = global
-        .extern_
-        .global()
-        .get(&mut global.extern_.store.store_mut());
+    let mut store = global.extern_.store.clone();
+    let mut store_mut = c_try!(store.store_mut(); otherwise ());
+    let value = global.extern_.global().get(&mut store_mut);
     *out = value.try_into().unwrap();
 }
 
@@ -71,10 +70,12 @@ pub unsafe extern "C" fn wasm_global_get(
 #[no_mangle]
 pub unsafe extern "C" fn wasm_global_set(global: &mut wasm_global_t, val: &wasm_val_t) {
     let value: Value = val.try_into().unwrap();
+    let mut store = global.extern_.store.clone();
+    let mut store_mut = c_try!(store.store_mut(); otherwise ());
     c_try!(global
         .extern_
         .global()
-        .set(&mut global.extern_.store.store_mut(), value); otherwise ());
+        .set(&mut store_mut, value); otherwise ());
 }
 
 #[no_mangle]
@@ -90,8 +91,9 @@ pub unsafe extern "C" fn wasm_global_type(
     global: Option<&wasm_global_t>,
 ) -> Option<Box<wasm_globaltype_t>> {
     let global = global?;
+    let store = c_try!(global.extern_.store.store());
     Some(Box::new(wasm_globaltype_t::new(
-        global.extern_.global().ty(&global.extern_.store.store()),
+        global.extern_.global().ty(&store),
     )))
 }
 
@@ -25,7 +25,7 @@ pub unsafe extern "C" fn wasm_memory_new(
 ) -> Option<Box<wasm_memory_t>> {
     let memory_type = memory_type?;
     let store = store?;
-    let mut store_mut = store.inner.store_mut();
+    let mut store_mut = c_try!(store.inner.store_mut());
     let memory_type = memory_type.inner().memory_type;
     let memory = c_try!(Memory::new(&mut store_mut, memory_type));
     Some(Box::new(wasm_memory_t {
@@ -55,50 +55,41 @@ pub unsafe extern "C" fn wasm_memory_type(
     memory: Option<&wasm_memory_t>,
 ) -> Option<Box<wasm_memorytype_t>> {
     let memory = memory?;
+    let store = c_try!(memory.extern_.store.store());
     Some(Box::new(wasm_memorytype_t::new(
-        memory.extern_.memory().ty(&memory.extern_.store.store()),
+        memory.extern_.memory().ty(&store),
     )))
 }
 
 // get a raw pointer into bytes
 #[no_mangle]
 pub unsafe extern "C" fn wasm_memory_data(memory: &mut wasm_memory_t) -> *mut u8 {
-    memory
-        .extern_
-        .memory()
-        .view(&memory.extern_.store.store())
-        .data_ptr()
+    let store = c_try!(memory.extern_.store.store(); otherwise std::ptr::null_mut());
+    memory.extern_.memory().view(&store).data_ptr()
 }
 
 // size in bytes
 #[no_mangle]
 pub unsafe extern "C" fn wasm_memory_data_size(memory: &wasm_memory_t) -> usize {
-    memory
-        .extern_
-        .memory()
-        .view(&memory.extern_.store.store())
-        .size()
-        .bytes()
-        .0
+    let store = c_try!(memory.extern_.store.store(); otherwise 0);
+    memory.extern_.memory().view(&store).size().bytes().0
 }
 
 // size in pages
 #[no_mangle]
 pub unsafe extern "C" fn wasm_memory_size(memory: &wasm_memory_t) -> u32 {
-    memory
-        .extern_
-        .memory()
-        .view(&memory.extern_.store.store())
-        .size()
-        .0 as _
+    let store = c_try!(memory.extern_.store.store(); otherwise 0);
+    memory.extern_.memory().view(&store).size().0 as _
 }
 
 // delta is in pages
 #[no_mangle]
 pub unsafe extern "C" fn wasm_memory_grow(memory: &mut wasm_memory_t, delta: u32) -> bool {
+    let mut store = memory.extern_.store.clone();
+    let mut store_mut = c_try!(store.store_mut(); otherwise false);
     memory
         .extern_
         .memory()
-        .grow(&mut memory.extern_.store.store_mut(), Pages(delta))
+        .grow(&mut store_mut, Pages(delta))
         .is_ok()
 }
@@ -3,7 +3,7 @@ mod global;
 mod memory;
 mod table;
 
-use super::store::StoreRef;
+use super::store::{StoreRef, StoreSuspendedError};
 // use super::types::{wasm_externkind_enum, wasm_externkind_t};
 pub use function::*;
 pub use global::*;
@@ -63,8 +63,8 @@ impl wasm_extern_t {
 // }
 
 impl wasm_extern_t {
-    pub(crate) unsafe fn ty(&self) -> ExternType {
-        self.inner.ty(&self.store.store())
+    pub(crate) unsafe fn ty(&self) -> Result<ExternType, StoreSuspendedError> {
+        Ok(self.inner.ty(&self.store.store()?))
     }
 }
 
@@ -39,7 +39,8 @@ pub unsafe extern "C" fn wasm_table_copy(table: &wasm_table_t) -> Box<wasm_table
 
 #[no_mangle]
 pub unsafe extern "C" fn wasm_table_size(table: &wasm_table_t) -> usize {
-    table.extern_.table().size(&table.extern_.store.store()) as _
+    let store = c_try!(table.extern_.store.store(); otherwise 0);
+    table.extern_.table().size(&store) as _
 }
 
 #[no_mangle]
@@ -55,7 +55,7 @@ pub unsafe extern "C" fn wasmer_funcenv_new(
         data = &NULL_ENV_PLACEHOLDER as *const u32 as *mut u32 as *mut c_void;
     }
     let inner = FunctionCEnv::new(std::ptr::NonNull::new_unchecked(data));
-    let _ = FunctionEnv::new(&mut store.inner.store_mut(), inner);
+    let _ = FunctionEnv::new(&mut c_try!(store.inner.store_mut()), inner);
     Some(Box::new(wasmer_funcenv_t { inner }))
 }
 
@@ -2,6 +2,7 @@ use super::externals::{wasm_extern_t, wasm_extern_vec_t};
 use super::module::wasm_module_t;
 use super::store::{wasm_store_t, StoreRef};
 use super::trap::wasm_trap_t;
+use super::types::is_representable;
 use wasmer_api::{Extern, Instance, InstantiationError};
 
 /// Opaque type representing a WebAssembly instance.
@@ -42,11 +43,23 @@ pub unsafe extern "C" fn wasm_instance_new(
     trap: Option<&mut *mut wasm_trap_t>,
 ) -> Option<Box<wasm_instance_t>> {
     let store = store?;
-    let mut store_mut = store.inner.store_mut();
+    let mut store_mut = c_try!(store.inner.store_mut());
     let module = module?;
     let imports = imports?;
 
     let wasm_module = &module.inner;
+
+    if !wasm_module
+        .imports()
+        .all(|import| is_representable(import.ty()))
+    {
+        crate::error::update_last_error(
+            "modules importing tags or `exnref` values can't be instantiated with the Wasm C API",
+        );
+
+        return None;
+    }
+
     let module_imports = wasm_module.imports();
     let module_import_count = module_imports.len();
     let externs = imports
@@ -91,6 +104,12 @@ pub unsafe extern "C" fn wasm_instance_new(
 
             return None;
         }
+
+        Err(e @ InstantiationError::MixedCode) => {
+            crate::error::update_last_error(e);
+
+            return None;
+        }
     };
 
     Some(Box::new(wasm_instance_t {
@@ -198,9 +217,18 @@ pub unsafe extern "C" fn wasm_instance_exports(
 ) {
     let original_instance = instance;
     let instance = &instance.inner;
+    let store = match original_instance.store.store() {
+        Ok(store) => store,
+        Err(error) => {
+            crate::error::update_last_error(error);
+            out.set_buffer(vec![]);
+            return;
+        }
+    };
     let extern_vec: Vec<Option<Box<wasm_extern_t>>> = instance
         .exports
         .iter()
+        .filter(|(_name, r#extern)| is_representable(&r#extern.ty(&store)))
         .map(|(_name, r#extern)| {
             Some(Box::new(wasm_extern_t::new(
                 original_instance.store.clone(),
@@ -315,4 +343,100 @@ mod tests {
         })
         .success();
     }
+
+    #[cfg(feature = "cranelift")]
+    #[cfg_attr(coverage, ignore)]
+    #[test]
+    fn test_instance_tags_are_left_out() {
+        (assert_c! {
+            #include "tests/wasmer.h"
+
+            int main() {
+                // Exception handling is only supported by Cranelift.
+                wasm_config_t* config = wasm_config_new();
+                wasm_config_set_compiler(config, CRANELIFT);
+
+                wasmer_features_t* features = wasmer_features_new();
+                wasmer_features_exceptions(features, true);
+                wasm_config_set_features(config, features);
+
+                wasm_engine_t* engine = wasm_engine_new_with_config(config);
+                wasm_store_t* store = wasm_store_new(engine);
+
+                // A module exporting a tag is usable, without the tag.
+                {
+                    wasm_byte_vec_t wat;
+                    wasmer_byte_vec_new_from_string(
+                        &wat,
+                        "(module\n"
+                        "  (tag (export \"tag\") (param i32))\n"
+                        "  (func (export \"function\")))"
+                    );
+                    wasm_byte_vec_t wasm;
+                    wat2wasm(&wat, &wasm);
+
+                    wasm_module_t* module = wasm_module_new(store, &wasm);
+                    assert(module);
+
+                    wasm_exporttype_vec_t export_types;
+                    wasm_module_exports(module, &export_types);
+                    assert(export_types.size == 1);
+                    wasmer_assert_name(wasm_exporttype_name(export_types.data[0]), "function");
+
+                    wasm_extern_vec_t imports = WASM_EMPTY_VEC;
+                    wasm_trap_t* trap = NULL;
+                    wasm_instance_t* instance = wasm_instance_new(store, module, &imports, &trap);
+                    assert(instance);
+
+                    wasm_extern_vec_t exports;
+                    wasm_instance_exports(instance, &exports);
+                    assert(exports.size == 1);
+                    assert(wasm_extern_as_func(exports.data[0]));
+
+                    wasm_extern_vec_delete(&exports);
+                    wasm_instance_delete(instance);
+                    wasm_exporttype_vec_delete(&export_types);
+                    wasm_module_delete(module);
+                    wasm_byte_vec_delete(&wasm);
+                    wasm_byte_vec_delete(&wat);
+                }
+
+                // A module importing a tag can't be instantiated.
+                {
+                    wasm_byte_vec_t wat;
+                    wasmer_byte_vec_new_from_string(
+                        &wat,
+                        "(module\n"
+                        "  (import \"ns\" \"tag\" (tag (param i32))))"
+                    );
+                    wasm_byte_vec_t wasm;
+                    wat2wasm(&wat, &wasm);
+
+                    wasm_module_t* module = wasm_module_new(store, &wasm);
+                    assert(module);
+
+                    wasm_importtype_vec_t import_types;
+                    wasm_module_imports(module, &import_types);
+                    assert(import_types.size == 0);
+
+                    wasm_extern_vec_t imports = WASM_EMPTY_VEC;
+                    wasm_trap_t* trap = NULL;
+                    wasm_instance_t* instance = wasm_instance_new(store, module, &imports, &trap);
+                    assert(!instance);
+                    assert(wasmer_last_error_length() > 0);
+
+                    wasm_importtype_vec_delete(&import_types);
+                    wasm_module_delete(module);
+                    wasm_byte_vec_delete(&wasm);
+                    wasm_byte_vec_delete(&wat);
+                }
+
+                wasm_store_delete(store);
+                wasm_engine_delete(engine);
+
+                return 0;
+            }
+        })
+        .success();
+    }
 }
@@ -21,13 +21,13 @@
 //! Every module comes with examples and entry points to guide the
 //! discovery of this API.
 
-/// `Context`.
-mod function_env;
-
 /// Private Rust macros.
 #[macro_use]
 mod macros;
 
+/// `Context`.
+mod function_env;
+
 /// An engine drives the compilation and the runtime.
 ///
 /// Entry points: A default engine is created with
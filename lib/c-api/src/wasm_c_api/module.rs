@@ -1,5 +1,7 @@
 use super::store::wasm_store_t;
-use super::types::{wasm_byte_vec_t, wasm_exporttype_vec_t, wasm_importtype_vec_t};
+use super::types::{
+    is_representable, wasm_byte_vec_t, wasm_exporttype_vec_t, wasm_importtype_vec_t,
+};
 use std::ptr::NonNull;
 use wasmer_api::Module;
 
@@ -29,7 +31,7 @@ pub unsafe extern "C" fn wasm_module_new(
     store: Option<&mut wasm_store_t>,
     bytes: Option<&wasm_byte_vec_t>,
 ) -> Option<Box<wasm_module_t>> {
-    let store = store?.inner.store_mut();
+    let store = c_try!(store?.inner.store_mut());
     let bytes = bytes?;
 
     let module = c_try!(Module::from_binary(&store, bytes.as_slice()));
@@ -92,7 +94,7 @@ pub unsafe extern "C" fn wasm_module_validate(
     bytes: Option<&wasm_byte_vec_t>,
 ) -> bool {
     let store = match store {
-        Some(store) => store.inner.store_mut(),
+        Some(store) => c_try!(store.inner.store_mut(); otherwise false),
         None => return false,
     };
     let bytes = match bytes {
@@ -222,6 +224,7 @@ pub unsafe extern "C" fn wasm_module_exports(
     let exports = module
         .inner
         .exports()
+        .filter(|export| is_representable(export.ty()))
         .map(|export| Some(Box::new(export.into())))
         .collect();
 
@@ -362,6 +365,7 @@ pub unsafe extern "C" fn wasm_module_imports(
     let imports = module
         .inner
         .imports()
+        .filter(|import| is_representable(import.ty()))
         .map(|import| Some(Box::new(import.into())))
         .collect();
 
@@ -458,7 +462,8 @@ pub unsafe extern "C" fn wasm_module_deserialize(
 ) -> Option<NonNull<wasm_module_t>> {
     let bytes = bytes?;
 
-    let module = c_try!(Module::deserialize(&store.inner.store(), bytes.as_slice()));
+    let store = c_try!(store.inner.store());
+    let module = c_try!(Module::deserialize(&store, bytes.as_slice()));
 
     Some(NonNull::new_unchecked(Box::into_raw(Box::new(
         wasm_module_t { inner: module },
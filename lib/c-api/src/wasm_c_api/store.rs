@@ -1,20 +1,54 @@
 use super::engine::wasm_engine_t;
-use std::cell::UnsafeCell;
+use std::cell::{Cell, UnsafeCell};
 use std::rc::Rc;
+use thiserror::Error;
 use wasmer_api::{AsStoreMut, AsStoreRef, Store, StoreMut, StoreRef as BaseStoreRef};
 
+/// The error of using a store held by a suspended metered call.
+#[derive(Debug, Error)]
+#[error("a store cannot be used while a metered call is suspended on it")]
+pub struct StoreSuspendedError;
+
+struct StoreCell {
+    store: UnsafeCell<Store>,
+    suspended: Cell<bool>,
+}
+
 #[derive(Clone)]
 pub struct StoreRef {
-    inner: Rc<UnsafeCell<Store>>,
+    inner: Rc<StoreCell>,
 }
 
 impl StoreRef {
-    pub unsafe fn store(&self) -> BaseStoreRef<'_> {
-        (*self.inner.get()).as_store_ref()
+    pub unsafe fn store(&self) -> Result<BaseStoreRef<'_>, StoreSuspendedError> {
+        self.check_not_suspended()?;
+        Ok((*self.inner.store.get()).as_store_ref())
+    }
+
+    pub unsafe fn store_mut(&mut self) -> Result<StoreMut<'_>, StoreSuspendedError> {
+        self.check_not_suspended()?;
+        Ok((*self.inner.store.get()).as_store_mut())
+    }
+
+    /// Raw pointer to the store, for code running on another thread
+    /// while the current one waits for it.
+    pub(crate) fn as_ptr(&self) -> *mut Store {
+        self.inner.store.get()
+    }
+
+    /// Marks the store as held by a suspended call, which keeps a
+    /// mutable reference to it until it is resumed or stopped.
+    pub(crate) fn set_suspended(&self, suspended: bool) {
+        self.inner.suspended.set(suspended);
     }
 
-    pub unsafe fn store_mut(&mut self) -> StoreMut<'_> {
-        (*self.inner.get()).as_store_mut()
+    /// Fails if the store is held by a suspended call: using it would
+    /// alias the reference of the call.
+    fn check_not_suspended(&self) -> Result<(), StoreSuspendedError> {
+        if self.inner.suspended.get() {
+            return Err(StoreSuspendedError);
+        }
+        Ok(())
     }
 }
 
@@ -38,7 +72,10 @@ pub unsafe extern "C" fn wasm_store_new(
 
     Some(Box::new(wasm_store_t {
         inner: StoreRef {
-            inner: Rc::new(UnsafeCell::new(store)),
+            inner: Rc::new(StoreCell {
+                store: UnsafeCell::new(store),
+                suspended: Cell::new(false),
+            }),
         },
     }))
 }
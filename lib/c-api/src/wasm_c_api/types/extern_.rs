@@ -6,7 +6,7 @@ use super::{
 use std::convert::{TryFrom, TryInto};
 use std::mem;
 use thiserror::Error;
-use wasmer_api::ExternType;
+use wasmer_api::{Extern, ExternType, Type};
 
 #[allow(non_camel_case_types)]
 pub type wasm_externkind_t = u8;
@@ -32,10 +32,43 @@ impl From<&ExternType> for wasm_externkind_enum {
             ExternType::Global(_) => Self::WASM_EXTERN_GLOBAL,
             ExternType::Table(_) => Self::WASM_EXTERN_TABLE,
             ExternType::Memory(_) => Self::WASM_EXTERN_MEMORY,
+            ExternType::Tag(_) => unreachable!("tags are left out of the Wasm C API"),
         }
     }
 }
 
+impl From<&Extern> for wasm_externkind_enum {
+    fn from(other: &Extern) -> Self {
+        match other {
+            Extern::Function(_) => Self::WASM_EXTERN_FUNC,
+            Extern::Global(_) => Self::WASM_EXTERN_GLOBAL,
+            Extern::Table(_) => Self::WASM_EXTERN_TABLE,
+            Extern::Memory(_) => Self::WASM_EXTERN_MEMORY,
+            Extern::Tag(_) => unreachable!("tags are left out of the Wasm C API"),
+        }
+    }
+}
+
+/// Whether `extern_type` can be represented in the Wasm C API, which has
+/// neither a tag extern kind nor an `exnref` value kind.
+///
+/// Externs that can't be represented are left out of the imports and
+/// exports of modules and instances, and modules importing them can't be
+/// instantiated.
+pub(crate) fn is_representable(extern_type: &ExternType) -> bool {
+    match extern_type {
+        ExternType::Function(function_type) => !function_type
+            .params()
+            .iter()
+            .chain(function_type.results())
+            .any(|ty| *ty == Type::ExceptionRef),
+        ExternType::Global(global_type) => global_type.ty != Type::ExceptionRef,
+        ExternType::Table(table_type) => table_type.ty != Type::ExceptionRef,
+        ExternType::Memory(_) => true,
+        ExternType::Tag(_) => false,
+    }
+}
+
 #[derive(Debug, Clone)]
 pub(crate) enum WasmExternType {
     Function(WasmFunctionType),
@@ -66,6 +99,7 @@ impl wasm_externtype_t {
                 ExternType::Memory(memory_type) => {
                     WasmExternType::Memory(WasmMemoryType::new(memory_type))
                 }
+                ExternType::Tag(_) => unreachable!("tags are left out of the Wasm C API"),
             },
         }
     }
@@ -84,13 +118,15 @@ impl From<&ExternType> for wasm_externtype_t {
 }
 
 #[no_mangle]
-pub unsafe extern "C" fn wasm_extern_type(r#extern: &wasm_extern_t) -> Box<wasm_externtype_t> {
-    Box::new(wasm_externtype_t::new(r#extern.ty()))
+pub unsafe extern "C" fn wasm_extern_type(
+    r#extern: &wasm_extern_t,
+) -> Option<Box<wasm_externtype_t>> {
+    Some(Box::new(wasm_externtype_t::new(c_try!(r#extern.ty()))))
 }
 
 #[no_mangle]
 pub unsafe extern "C" fn wasm_extern_kind(r#extern: &wasm_extern_t) -> wasm_externkind_t {
-    wasm_externkind_enum::from(r#extern.ty()) as wasm_externkind_t
+    wasm_externkind_enum::from(&r#extern.inner) as wasm_externkind_t
 }
 
 #[no_mangle]
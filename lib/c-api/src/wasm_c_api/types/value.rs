@@ -24,6 +24,7 @@ impl From<Type> for wasm_valkind_enum {
             Type::V128 => todo!("no v128 type in Wasm C API yet!"),
             Type::ExternRef => Self::WASM_ANYREF,
             Type::FuncRef => Self::WASM_FUNCREF,
+            Type::ExceptionRef => unreachable!("`exnref` is left out of the Wasm C API"),
         }
     }
 }
@@ -362,3 +362,38 @@ pub extern "C" fn wasmer_features_memory64(
 
     true
 }
+
+/// Configures whether the WebAssembly exception handling proposal will
+/// be enabled.
+///
+/// The [WebAssembly exception handling proposal][proposal] is not
+/// currently fully standardized and is undergoing development. It is
+/// only supported by the Cranelift compiler. Support for this feature
+/// can be enabled through this method for appropriate WebAssembly
+/// modules.
+///
+/// Tags and `exnref` values have no representation in the Wasm C API,
+/// so they are left out of the imports and exports of modules and
+/// instances, and modules importing them can't be instantiated.
+///
+/// This is `false` by default.
+///
+/// [proposal]: https://github.com/WebAssembly/exception-handling
+///
+/// # Example
+///
+/// See the module's documentation.
+#[no_mangle]
+pub extern "C" fn wasmer_features_exceptions(
+    features: Option<&mut wasmer_features_t>,
+    enable: bool,
+) -> bool {
+    let features = match features {
+        Some(features) => features,
+        _ => return false,
+    };
+
+    features.inner.exceptions(enable);
+
+    true
+}
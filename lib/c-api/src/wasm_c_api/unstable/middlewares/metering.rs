@@ -5,6 +5,11 @@
 //! are executed in total and putting a limit on the total number of
 //! operators executed.
 //!
+//! A metering middleware created with
+//! [`wasmer_metering_new_resumable`] suspends a call when the points
+//! are exhausted rather than trapping, so that it can be resumed
+//! later with new points. See [`wasmer_metering_call_new`].
+//!
 //! # Example
 //!
 //! ```rust
@@ -131,13 +136,21 @@
 //! # }
 //! ```
 
+use super::super::super::externals::wasm_func_t;
 use super::super::super::instance::wasm_instance_t;
+use super::super::super::store::StoreRef;
+use super::super::super::trap::wasm_trap_t;
+use super::super::super::value::wasm_val_vec_t;
 use super::super::parser::operator::wasmer_parser_operator_t;
 use super::wasmer_middleware_t;
-use std::sync::Arc;
+use crate::error::update_last_error;
+use std::mem::MaybeUninit;
+use std::sync::{mpsc, Arc};
+use std::thread::{self, JoinHandle, ThreadId};
 use wasmer_api::wasmparser::Operator;
+use wasmer_api::{Function, FunctionEnv, FunctionEnvMut, Instance, RuntimeError, Store, Value};
 use wasmer_middlewares::{
-    metering::{get_remaining_points, set_remaining_points, MeteringPoints},
+    metering::{get_remaining_points, set_remaining_points, set_yield_function, MeteringPoints},
     Metering,
 };
 
@@ -184,6 +197,30 @@ pub extern "C" fn wasmer_metering_new(
     })
 }
 
+/// Creates a new metering middleware like [`wasmer_metering_new`],
+/// except that a call is suspended rather than trapping when the
+/// points are exhausted, if it runs through
+/// [`wasmer_metering_call_new`].
+///
+/// Calls made with `wasm_func_call` still trap when the points are
+/// exhausted.
+///
+/// # Example
+///
+/// See [`wasmer_metering_call_new`].
+#[no_mangle]
+pub extern "C" fn wasmer_metering_new_resumable(
+    initial_limit: u64,
+    cost_function: wasmer_metering_cost_function_t,
+) -> Box<wasmer_metering_t> {
+    let cost_function: Box<dyn Fn(&Operator) -> u64 + Send + Sync> =
+        Box::new(move |operator: &Operator| -> u64 { cost_function(operator.into()) });
+
+    Box::new(wasmer_metering_t {
+        inner: Arc::new(Metering::new(initial_limit, cost_function).with_yield()),
+    })
+}
+
 /// Deletes a [`wasmer_metering_t`].
 ///
 /// # Example
@@ -197,6 +234,9 @@ pub extern "C" fn wasmer_metering_delete(_metering: Option<Box<wasmer_metering_t
 /// points. Notice that it could include zero! Zero doesn't mean
 /// points are exhausted _yet_.
 ///
+/// While a call of the instance is suspended, `u64::MAX` is returned
+/// and the last error is set. See [`wasmer_metering_call_new`].
+///
 /// # Example
 ///
 /// See module's documentation.
@@ -204,7 +244,9 @@ pub extern "C" fn wasmer_metering_delete(_metering: Option<Box<wasmer_metering_t
 pub unsafe extern "C" fn wasmer_metering_get_remaining_points(
     instance: &mut wasm_instance_t,
 ) -> u64 {
-    match get_remaining_points(&mut instance.store.store_mut(), &instance.inner) {
+    let mut store_mut = c_try!(instance.store.store_mut(); otherwise std::u64::MAX);
+
+    match get_remaining_points(&mut store_mut, &instance.inner) {
         MeteringPoints::Remaining(value) => value,
         MeteringPoints::Exhausted => std::u64::MAX,
     }
@@ -212,6 +254,9 @@ pub unsafe extern "C" fn wasmer_metering_get_remaining_points(
 
 /// Returns true if the remaning points are exhausted, false otherwise.
 ///
+/// While a call of the instance is suspended, true is returned and the
+/// last error is set. See [`wasmer_metering_call_new`].
+///
 /// # Example
 ///
 /// See module's documentation.
@@ -219,8 +264,10 @@ pub unsafe extern "C" fn wasmer_metering_get_remaining_points(
 pub unsafe extern "C" fn wasmer_metering_points_are_exhausted(
     instance: &mut wasm_instance_t,
 ) -> bool {
+    let mut store_mut = c_try!(instance.store.store_mut(); otherwise true);
+
     matches!(
-        get_remaining_points(&mut instance.store.store_mut(), &instance.inner),
+        get_remaining_points(&mut store_mut, &instance.inner),
         MeteringPoints::Exhausted,
     )
 }
@@ -302,7 +349,9 @@ pub unsafe extern "C" fn wasmer_metering_set_remaining_points(
     instance: &mut wasm_instance_t,
     new_limit: u64,
 ) {
-    set_remaining_points(&mut instance.store.store_mut(), &instance.inner, new_limit);
+    let mut store_mut = c_try!(instance.store.store_mut(); otherwise ());
+
+    set_remaining_points(&mut store_mut, &instance.inner, new_limit);
 }
 
 /// Transforms a [`wasmer_metering_t`] into a generic
@@ -324,3 +373,454 @@ pub extern "C" fn wasmer_metering_as_middleware(
         inner: metering.inner,
     }))
 }
+
+/// The state of a [`wasmer_metering_call_t`] after it ran.
+///
+/// # Example
+///
+/// See [`wasmer_metering_call_new`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[repr(C)]
+#[allow(non_camel_case_types)]
+pub enum wasmer_metering_call_status_t {
+    /// The points are exhausted, and the call is suspended until it
+    /// is resumed.
+    WASMER_METERING_CALL_SUSPENDED = 0,
+    /// The call has returned.
+    WASMER_METERING_CALL_FINISHED = 1,
+    /// The call has trapped.
+    WASMER_METERING_CALL_TRAPPED = 2,
+    /// The call had already returned or trapped, and has not run.
+    WASMER_METERING_CALL_ALREADY_DONE = 3,
+    /// The call could not run, see `wasmer_last_error_message`.
+    WASMER_METERING_CALL_FAILED = 4,
+}
+
+/// What the thread running a call reports.
+enum CallEvent {
+    Suspended,
+    Finished(Result<Box<[Value]>, RuntimeError>),
+}
+
+/// The environment of the function called by the instance when its
+/// points are exhausted.
+struct YieldEnv {
+    instance: Instance,
+    events: Option<mpsc::Sender<CallEvent>>,
+    resumes: Option<mpsc::Receiver<u64>>,
+}
+
+/// Suspends the call until it is resumed with new points, or stops
+/// it if the call is deleted meanwhile.
+fn yield_call(mut env: FunctionEnvMut<YieldEnv>) -> Result<(), RuntimeError> {
+    let (data, mut store) = env.data_and_store_mut();
+
+    let points = match (&data.events, &data.resumes) {
+        (Some(events), Some(resumes)) if events.send(CallEvent::Suspended).is_ok() => {
+            resumes.recv().ok()
+        }
+        _ => None,
+    };
+
+    match points {
+        Some(points) => {
+            set_remaining_points(&mut store, &data.instance, points);
+
+            Ok(())
+        }
+        None => Err(RuntimeError::new("the metered call has been cancelled")),
+    }
+}
+
+/// A call running on its own thread, which is parked while the call
+/// is suspended.
+struct RunningCall {
+    resumes: mpsc::Sender<u64>,
+    events: mpsc::Receiver<CallEvent>,
+    thread: JoinHandle<()>,
+}
+
+/// The store of a call, moved to the thread running it.
+struct StorePtr(*mut Store);
+
+// SAFETY: the store is only used by the thread running the call while
+// the thread owning it, which is the only one to resume the call,
+// waits for the call to be suspended or to finish, and the store
+// rejects any use while the call is suspended.
+unsafe impl Send for StorePtr {}
+
+impl StorePtr {
+    unsafe fn get<'a>(self) -> &'a mut Store {
+        &mut *self.0
+    }
+}
+
+/// Opaque type representing a resumable call to a function of an
+/// instance compiled with a metering middleware created by
+/// [`wasmer_metering_new_resumable`].
+///
+/// # Example
+///
+/// See [`wasmer_metering_call_new`].
+#[allow(non_camel_case_types)]
+pub struct wasmer_metering_call_t {
+    store: StoreRef,
+    instance: Instance,
+    function: Function,
+    params: Vec<Value>,
+    yield_env: FunctionEnv<YieldEnv>,
+    running: Option<RunningCall>,
+    owner: ThreadId,
+    done: bool,
+}
+
+impl wasmer_metering_call_t {
+    /// Waits for the call to be suspended or to finish.
+    fn wait(&mut self) -> Option<Result<Box<[Value]>, RuntimeError>> {
+        let running = self.running.as_ref()?;
+
+        let result = match running.events.recv() {
+            Ok(CallEvent::Suspended) => return None,
+            Ok(CallEvent::Finished(result)) => result,
+            Err(_) => Err(RuntimeError::new("the metered call has panicked")),
+        };
+        self.stop();
+
+        Some(result)
+    }
+
+    /// Joins the thread running the call, if any, and detaches the
+    /// call from the instance.
+    fn stop(&mut self) {
+        if let Some(RunningCall {
+            resumes,
+            events,
+            thread,
+        }) = self.running.take()
+        {
+            // A suspended call is stopped once it cannot be resumed anymore.
+            drop(resumes);
+            let _ = thread.join();
+            drop(events);
+            self.store.set_suspended(false);
+        }
+
+        if !self.done {
+            // The store has been released by the call, if any.
+            let mut store_mut = unsafe { self.store.store_mut() }
+                .expect("the store of a stopped call is not suspended");
+            let yield_env = self.yield_env.as_mut(&mut store_mut);
+            yield_env.events = None;
+            yield_env.resumes = None;
+            set_yield_function(&mut store_mut, &self.instance, None);
+
+            self.done = true;
+        }
+    }
+}
+
+impl Drop for wasmer_metering_call_t {
+    fn drop(&mut self) {
+        self.stop();
+    }
+}
+
+/// Prepares a resumable call of `func`, an export of `instance`, with
+/// the arguments `args`. The call starts when it is resumed for the
+/// first time with [`wasmer_metering_call_resume`].
+///
+/// The instance must have been compiled with a metering middleware
+/// created by [`wasmer_metering_new_resumable`]. When its points are
+/// exhausted, the call is suspended instead of trapping, and
+/// `wasmer_metering_call_resume` returns, so that an event loop can
+/// interleave the execution of WebAssembly with other work.
+///
+/// Only one call per instance can be in progress at a time, and the
+/// instance and its store must outlive the call.
+///
+/// # Threads
+///
+/// The call runs on a thread of its own, spawned when it is resumed
+/// for the first time, while the thread resuming it waits. Hence the
+/// WebAssembly code, and the host functions it calls, e.g. the ones
+/// created with `wasm_func_new` or the WASI ones, don't run on the
+/// thread creating the call: they must not depend on its thread-local
+/// state, such as the last error.
+///
+/// The call must be resumed and deleted on the thread that created
+/// it. Resuming it on another thread returns
+/// `WASMER_METERING_CALL_FAILED`, and deleting it on another thread
+/// leaks it, with the last error set in both cases.
+///
+/// A suspended call holds its store until it is resumed or deleted:
+/// meanwhile, the functions using the store or anything created in
+/// it, such as the instance, fail, like for invalid arguments, and
+/// set the last error.
+///
+/// # Example
+///
+/// ```rust
+/// # use wasmer_inline_c::assert_c;
+/// # fn main() {
+/// #    (assert_c! {
+/// # #include "tests/wasmer.h"
+/// #
+/// // Every operator costs 1 point.
+/// uint64_t cost_function(wasmer_parser_operator_t wasm_operator) {
+///     (void) wasm_operator;
+///     return 1;
+/// }
+///
+/// int main() {
+///     wasmer_metering_t* metering = wasmer_metering_new_resumable(0, cost_function);
+///     wasmer_middleware_t* middleware = wasmer_metering_as_middleware(metering);
+///
+///     wasm_config_t* config = wasm_config_new();
+///     wasm_config_push_middleware(config, middleware);
+///
+///     wasm_engine_t* engine = wasm_engine_new_with_config(config);
+///     wasm_store_t* store = wasm_store_new(engine);
+///
+///     // A function summing the integers from 1 to `n`.
+///     wasm_byte_vec_t wat;
+///     wasmer_byte_vec_new_from_string(
+///         &wat,
+///         "(module\n"
+///         "  (func (export \"sum\") (param $n i32) (result i32)\n"
+///         "    (local $sum i32)\n"
+///         "    (block $done\n"
+///         "      (loop $next\n"
+///         "        (br_if $done (i32.eqz (local.get $n)))\n"
+///         "        (local.set $sum (i32.add (local.get $sum) (local.get $n)))\n"
+///         "        (local.set $n (i32.sub (local.get $n) (i32.const 1)))\n"
+///         "        (br $next)))\n"
+///         "    (local.get $sum)))"
+///     );
+///     wasm_byte_vec_t wasm;
+///     wat2wasm(&wat, &wasm);
+///
+///     wasm_module_t* module = wasm_module_new(store, &wasm);
+///     assert(module);
+///
+///     wasm_extern_vec_t imports = WASM_EMPTY_VEC;
+///     wasm_instance_t* instance = wasm_instance_new(store, module, &imports, NULL);
+///     assert(instance);
+///
+///     wasm_extern_vec_t exports;
+///     wasm_instance_exports(instance, &exports);
+///     const wasm_func_t* sum = wasm_extern_as_func(exports.data[0]);
+///     assert(sum);
+///
+///     wasm_val_t arguments[1] = { WASM_I32_VAL(100) };
+///     wasm_val_t results[1] = { WASM_INIT_VAL };
+///     wasm_val_vec_t arguments_as_array = WASM_ARRAY_VEC(arguments);
+///     wasm_val_vec_t results_as_array = WASM_ARRAY_VEC(results);
+///
+///     wasmer_metering_call_t* call = wasmer_metering_call_new(instance, sum, &arguments_as_array);
+///     assert(call);
+///
+///     // Run the call by slices of 100 points, doing other work in between.
+///     wasm_trap_t* trap = NULL;
+///     int slices = 1;
+///
+///     while (wasmer_metering_call_resume(call, 100, &results_as_array, &trap) == WASMER_METERING_CALL_SUSPENDED) {
+///         // The store can't be used until the call is resumed.
+///         assert(wasmer_metering_get_remaining_points(instance) == UINT64_MAX);
+///         assert(wasmer_last_error_length() > 0);
+///
+///         slices += 1;
+///     }
+///
+///     assert(trap == NULL);
+///     assert(results[0].of.i32 == 5050);
+///     assert(slices > 1);
+///     assert(wasmer_metering_get_remaining_points(instance) <= 100);
+///
+///     // A finished call can't be resumed.
+///     assert(wasmer_metering_call_resume(call, 100, &results_as_array, &trap) == WASMER_METERING_CALL_ALREADY_DONE);
+///
+///     wasmer_metering_call_delete(call);
+///     wasm_extern_vec_delete(&exports);
+///     wasm_instance_delete(instance);
+///     wasm_module_delete(module);
+///     wasm_byte_vec_delete(&wasm);
+///     wasm_byte_vec_delete(&wat);
+///     wasm_store_delete(store);
+///     wasm_engine_delete(engine);
+///
+///     return 0;
+/// }
+/// #    })
+/// #    .success();
+/// # }
+/// ```
+#[no_mangle]
+pub unsafe extern "C" fn wasmer_metering_call_new(
+    instance: &mut wasm_instance_t,
+    func: &wasm_func_t,
+    args: &wasm_val_vec_t,
+) -> Option<Box<wasmer_metering_call_t>> {
+    let params = c_try!(args
+        .as_slice()
+        .iter()
+        .cloned()
+        .map(TryInto::try_into)
+        .collect::<Result<Vec<Value>, _>>());
+
+    let mut store = instance.store.clone();
+    let mut store_mut = c_try!(store.store_mut());
+
+    if instance
+        .inner
+        .exports
+        .get_table("wasmer_metering_yield")
+        .is_err()
+    {
+        update_last_error(
+            "the instance has not been compiled with a resumable metering middleware",
+        );
+
+        return None;
+    }
+
+    let yield_env = FunctionEnv::new(
+        &mut store_mut,
+        YieldEnv {
+            instance: instance.inner.clone(),
+            events: None,
+            resumes: None,
+        },
+    );
+
+    Some(Box::new(wasmer_metering_call_t {
+        store: instance.store.clone(),
+        instance: instance.inner.clone(),
+        function: func.extern_.function(),
+        params,
+        yield_env,
+        running: None,
+        owner: thread::current().id(),
+        done: false,
+    }))
+}
+
+/// Runs `call` with `points` remaining points, until it returns,
+/// traps, or is suspended because the points are exhausted.
+///
+/// When the call returns, its results are written into `results`,
+/// which must be sized like for `wasm_func_call`. When it traps, the
+/// trap is written into `trap`. A call cannot be resumed once it has
+/// returned or trapped: `WASMER_METERING_CALL_ALREADY_DONE` is
+/// returned instead.
+///
+/// The call must be resumed on the thread that created it, otherwise
+/// `WASMER_METERING_CALL_FAILED` is returned.
+///
+/// # Example
+///
+/// See [`wasmer_metering_call_new`].
+#[no_mangle]
+pub unsafe extern "C" fn wasmer_metering_call_resume(
+    call: &mut wasmer_metering_call_t,
+    points: u64,
+    results: &mut wasm_val_vec_t,
+    trap: Option<&mut *mut wasm_trap_t>,
+) -> wasmer_metering_call_status_t {
+    if call.owner != thread::current().id() {
+        update_last_error("a metered call must be resumed on the thread that created it");
+
+        return wasmer_metering_call_status_t::WASMER_METERING_CALL_FAILED;
+    }
+
+    if call.done {
+        update_last_error("the metered call has already returned or trapped");
+
+        return wasmer_metering_call_status_t::WASMER_METERING_CALL_ALREADY_DONE;
+    }
+
+    match &call.running {
+        Some(running) => {
+            // The thread running the call waits for new points, and
+            // uses the store again.
+            call.store.set_suspended(false);
+            let _ = running.resumes.send(points);
+        }
+        None => {
+            let mut store_mut = c_try!(
+                call.store.store_mut();
+                otherwise wasmer_metering_call_status_t::WASMER_METERING_CALL_FAILED
+            );
+            let (events_sender, events) = mpsc::channel();
+            let (resumes, resumes_receiver) = mpsc::channel();
+
+            set_remaining_points(&mut store_mut, &call.instance, points);
+
+            let yield_env = call.yield_env.as_mut(&mut store_mut);
+            yield_env.events = Some(events_sender.clone());
+            yield_env.resumes = Some(resumes_receiver);
+
+            let yield_function =
+                Function::new_typed_with_env(&mut store_mut, &call.yield_env, yield_call);
+            set_yield_function(&mut store_mut, &call.instance, Some(yield_function));
+
+            let store = StorePtr(call.store.as_ptr());
+            let function = call.function.clone();
+            let params = std::mem::take(&mut call.params);
+            let thread = thread::spawn(move || {
+                let store = unsafe { store.get() };
+                let result = function.call(store, &params);
+                let _ = events_sender.send(CallEvent::Finished(result));
+            });
+
+            call.running = Some(RunningCall {
+                resumes,
+                events,
+                thread,
+            });
+        }
+    }
+
+    match call.wait() {
+        None => {
+            call.store.set_suspended(true);
+
+            wasmer_metering_call_status_t::WASMER_METERING_CALL_SUSPENDED
+        }
+        Some(Ok(values)) => {
+            for (slot, value) in results.as_uninit_slice().iter_mut().zip(values.iter()) {
+                *slot = MaybeUninit::new(value.try_into().expect("Results conversion failed"));
+            }
+
+            wasmer_metering_call_status_t::WASMER_METERING_CALL_FINISHED
+        }
+        Some(Err(error)) => {
+            if let Some(trap) = trap {
+                *trap = Box::into_raw(Box::new(error.into()));
+            }
+
+            wasmer_metering_call_status_t::WASMER_METERING_CALL_TRAPPED
+        }
+    }
+}
+
+/// Deletes a [`wasmer_metering_call_t`].
+///
+/// A suspended call is stopped, as if it had trapped.
+///
+/// The call must be deleted on the thread that created it, otherwise
+/// it is leaked, and the last error is set.
+///
+/// # Example
+///
+/// See [`wasmer_metering_call_new`].
+#[no_mangle]
+pub extern "C" fn wasmer_metering_call_delete(call: Option<Box<wasmer_metering_call_t>>) {
+    if let Some(call) = call {
+        if call.owner != thread::current().id() {
+            update_last_error("a metered call must be deleted on the thread that created it");
+
+            // Stopping the call would use its store from another thread.
+            std::mem::forget(call);
+        }
+    }
+}
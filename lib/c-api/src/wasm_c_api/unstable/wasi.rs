@@ -158,7 +158,7 @@ unsafe fn wasi_get_unordered_imports_inner(
 ) -> Option<()> {
     let wasi_env = wasi_env?;
     let store = &mut wasi_env.store;
-    let mut store_mut = store.store_mut();
+    let mut store_mut = c_try!(store.store_mut());
     let module = module?;
 
     let import_object = c_try!(wasi_env.inner.import_object(&mut store_mut, &module.inner));
@@ -0,0 +1,186 @@
+//! A `VirtualFile` forwarding reads and writes to C callbacks.
+//!
+//! This is what backs `wasi_config_set_stdin_callback`,
+//! `wasi_config_set_stdout_callback` and
+//! `wasi_config_set_stderr_callback`.
+
+use std::fmt;
+use std::io::{self, SeekFrom};
+use std::os::raw::{c_char, c_void};
+use std::pin::Pin;
+use std::task::{Context, Poll};
+use wasmer_wasix::virtual_fs::{self, VirtualFile};
+
+/// Callback used to feed a WASI input stream (e.g. `stdin`).
+///
+/// It must write at most `buffer_len` bytes into `buffer` and return
+/// the number of bytes written. Returning `0` signals the end of the
+/// stream, and returning a negative value signals an error.
+#[allow(non_camel_case_types)]
+pub type wasi_read_callback_t =
+    unsafe extern "C" fn(user_data: *mut c_void, buffer: *mut c_char, buffer_len: usize) -> isize;
+
+/// Callback used to receive a WASI output stream (e.g. `stdout` or
+/// `stderr`).
+///
+/// It receives `buffer_len` bytes in `buffer` and must return the
+/// number of bytes consumed, or a negative value to signal an error.
+#[allow(non_camel_case_types)]
+pub type wasi_write_callback_t =
+    unsafe extern "C" fn(user_data: *mut c_void, buffer: *const c_char, buffer_len: usize) -> isize;
+
+#[derive(Clone, Copy)]
+enum Callback {
+    Read(wasi_read_callback_t),
+    Write(wasi_write_callback_t),
+}
+
+/// A file whose content is produced or consumed by a C callback.
+///
+/// The callback is invoked synchronously, on the thread running the
+/// WebAssembly code that performs the I/O.
+#[derive(Clone)]
+pub(crate) struct CallbackFile {
+    callback: Callback,
+    user_data: *mut c_void,
+}
+
+// SAFETY: the caller of the `wasi_config_set_*_callback` functions
+// promises that the callback and its user data can be used from the
+// thread running the WebAssembly code.
+unsafe impl Send for CallbackFile {}
+unsafe impl Sync for CallbackFile {}
+
+impl CallbackFile {
+    pub(crate) fn reader(callback: wasi_read_callback_t, user_data: *mut c_void) -> Self {
+        Self {
+            callback: Callback::Read(callback),
+            user_data,
+        }
+    }
+
+    pub(crate) fn writer(callback: wasi_write_callback_t, user_data: *mut c_void) -> Self {
+        Self {
+            callback: Callback::Write(callback),
+            user_data,
+        }
+    }
+}
+
+impl fmt::Debug for CallbackFile {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let kind = match self.callback {
+            Callback::Read(_) => "read",
+            Callback::Write(_) => "write",
+        };
+
+        f.debug_struct("CallbackFile")
+            .field("callback", &kind)
+            .field("user_data", &self.user_data)
+            .finish()
+    }
+}
+
+fn callback_result(result: isize, limit: usize) -> io::Result<usize> {
+    if result < 0 {
+        Err(io::Error::new(
+            io::ErrorKind::Other,
+            "the WASI I/O callback reported an error",
+        ))
+    } else {
+        Ok((result as usize).min(limit))
+    }
+}
+
+impl tokio::io::AsyncRead for CallbackFile {
+    fn poll_read(
+        self: Pin<&mut Self>,
+        _cx: &mut Context<'_>,
+        buf: &mut tokio::io::ReadBuf<'_>,
+    ) -> Poll<io::Result<()>> {
+        let Callback::Read(read) = self.callback else {
+            return Poll::Ready(Err(io::ErrorKind::PermissionDenied.into()));
+        };
+
+        let unfilled = buf.initialize_unfilled();
+        let result = unsafe {
+            read(
+                self.user_data,
+                unfilled.as_mut_ptr() as *mut c_char,
+                unfilled.len(),
+            )
+        };
+        let read = callback_result(result, unfilled.len())?;
+        buf.advance(read);
+
+        Poll::Ready(Ok(()))
+    }
+}
+
+impl tokio::io::AsyncWrite for CallbackFile {
+    fn poll_write(
+        self: Pin<&mut Self>,
+        _cx: &mut Context<'_>,
+        buf: &[u8],
+    ) -> Poll<io::Result<usize>> {
+        let Callback::Write(write) = self.callback else {
+            return Poll::Ready(Err(io::ErrorKind::PermissionDenied.into()));
+        };
+
+        let result = unsafe { write(self.user_data, buf.as_ptr() as *const c_char, buf.len()) };
+
+        Poll::Ready(callback_result(result, buf.len()))
+    }
+
+    fn poll_flush(self: Pin<&mut Self>, _cx: &mut Context<'_>) -> Poll<io::Result<()>> {
+        Poll::Ready(Ok(()))
+    }
+
+    fn poll_shutdown(self: Pin<&mut Self>, _cx: &mut Context<'_>) -> Poll<io::Result<()>> {
+        Poll::Ready(Ok(()))
+    }
+}
+
+impl tokio::io::AsyncSeek for CallbackFile {
+    fn start_seek(self: Pin<&mut Self>, _position: SeekFrom) -> io::Result<()> {
+        Ok(())
+    }
+
+    fn poll_complete(self: Pin<&mut Self>, _cx: &mut Context<'_>) -> Poll<io::Result<u64>> {
+        Poll::Ready(Ok(0))
+    }
+}
+
+impl VirtualFile for CallbackFile {
+    fn last_accessed(&self) -> u64 {
+        0
+    }
+
+    fn last_modified(&self) -> u64 {
+        0
+    }
+
+    fn created_time(&self) -> u64 {
+        0
+    }
+
+    fn size(&self) -> u64 {
+        0
+    }
+
+    fn set_len(&mut self, _new_size: u64) -> virtual_fs::Result<()> {
+        Ok(())
+    }
+
+    fn unlink(&mut self) -> virtual_fs::Result<()> {
+        Ok(())
+    }
+
+    fn poll_read_ready(self: Pin<&mut Self>, _cx: &mut Context<'_>) -> Poll<io::Result<usize>> {
+        Poll::Ready(Ok(8192))
+    }
+
+    fn poll_write_ready(self: Pin<&mut Self>, _cx: &mut Context<'_>) -> Poll<io::Result<usize>> {
+        Poll::Ready(Ok(8192))
+    }
+}
@@ -0,0 +1,1112 @@
+//! Custom filesystems for WASI programs.
+//!
+//! A [`wasi_fs_t`] is either backed by callbacks supplied by the
+//! embedder through a [`wasi_fs_vtable_t`], or lives in memory (see
+//! [`wasi_fs_new_memory`]). It is attached to a WASI configuration
+//! with [`wasi_config_set_fs`]; directories are then exposed to the
+//! program with `wasi_config_preopen_dir` or `wasi_config_mapdir` as
+//! usual, but resolved inside the custom filesystem instead of the
+//! host one.
+
+use super::super::types::wasm_byte_vec_t;
+use super::wasi_config_t;
+use crate::error::update_last_error;
+use std::ffi::{CStr, CString};
+use std::fmt;
+use std::future::Future;
+use std::io::{self, SeekFrom};
+use std::os::raw::{c_char, c_void};
+use std::path::{Path, PathBuf};
+use std::pin::Pin;
+use std::sync::Arc;
+use std::task::{Context, Poll};
+use wasmer_wasix::runtime::task_manager::InlineWaker;
+use wasmer_wasix::virtual_fs::{
+    self, AsyncReadExt, AsyncWriteExt, DirEntry, FileOpener, FileSystem, FileType, FsError,
+    Metadata, OpenOptions, OpenOptionsConfig, ReadDir, TmpFileSystem, VirtualFile,
+};
+
+/// Status returned by the callbacks of a [`wasi_fs_vtable_t`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[repr(C)]
+#[allow(non_camel_case_types)]
+pub enum wasi_fs_error_t {
+    /// The operation succeeded.
+    WASI_FS_OK = 0,
+    /// The entry does not exist.
+    WASI_FS_NOT_FOUND = 1,
+    /// The entry already exists.
+    WASI_FS_ALREADY_EXISTS = 2,
+    /// The operation is not allowed.
+    WASI_FS_PERMISSION_DENIED = 3,
+    /// A directory was expected.
+    WASI_FS_NOT_A_DIRECTORY = 4,
+    /// A file was expected.
+    WASI_FS_NOT_A_FILE = 5,
+    /// The directory to remove is not empty.
+    WASI_FS_DIRECTORY_NOT_EMPTY = 6,
+    /// An argument is invalid.
+    WASI_FS_INVALID_INPUT = 7,
+    /// The storage is full.
+    WASI_FS_STORAGE_FULL = 8,
+    /// Any other failure.
+    WASI_FS_IO_ERROR = 9,
+}
+
+impl From<wasi_fs_error_t> for FsError {
+    fn from(other: wasi_fs_error_t) -> Self {
+        match other {
+            // A callback reporting success where an error was expected
+            // is a bug on the embedder side.
+            wasi_fs_error_t::WASI_FS_OK => FsError::UnknownError,
+            wasi_fs_error_t::WASI_FS_NOT_FOUND => FsError::EntryNotFound,
+            wasi_fs_error_t::WASI_FS_ALREADY_EXISTS => FsError::AlreadyExists,
+            wasi_fs_error_t::WASI_FS_PERMISSION_DENIED => FsError::PermissionDenied,
+            wasi_fs_error_t::WASI_FS_NOT_A_DIRECTORY => FsError::BaseNotDirectory,
+            wasi_fs_error_t::WASI_FS_NOT_A_FILE => FsError::NotAFile,
+            wasi_fs_error_t::WASI_FS_DIRECTORY_NOT_EMPTY => FsError::DirectoryNotEmpty,
+            wasi_fs_error_t::WASI_FS_INVALID_INPUT => FsError::InvalidInput,
+            wasi_fs_error_t::WASI_FS_STORAGE_FULL => FsError::StorageFull,
+            wasi_fs_error_t::WASI_FS_IO_ERROR => FsError::IOError,
+        }
+    }
+}
+
+fn check(status: wasi_fs_error_t) -> virtual_fs::Result<()> {
+    match status {
+        wasi_fs_error_t::WASI_FS_OK => Ok(()),
+        error => Err(error.into()),
+    }
+}
+
+/// The kind of an entry of a custom filesystem.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[repr(C)]
+#[allow(non_camel_case_types)]
+pub enum wasi_fs_file_type_t {
+    /// A regular file.
+    WASI_FS_FILE = 0,
+    /// A directory.
+    WASI_FS_DIRECTORY = 1,
+}
+
+/// Metadata of an entry of a custom filesystem. Times are in
+/// nanoseconds since the UNIX epoch.
+#[derive(Debug, Clone, Copy)]
+#[repr(C)]
+#[allow(non_camel_case_types)]
+pub struct wasi_fs_metadata_t {
+    pub file_type: wasi_fs_file_type_t,
+    pub len: u64,
+    pub accessed: u64,
+    pub created: u64,
+    pub modified: u64,
+}
+
+impl From<wasi_fs_metadata_t> for Metadata {
+    fn from(other: wasi_fs_metadata_t) -> Self {
+        Metadata {
+            ft: match other.file_type {
+                wasi_fs_file_type_t::WASI_FS_FILE => FileType::new_file(),
+                wasi_fs_file_type_t::WASI_FS_DIRECTORY => FileType::new_dir(),
+            },
+            accessed: other.accessed,
+            created: other.created,
+            modified: other.modified,
+            len: other.len,
+        }
+    }
+}
+
+/// How a file of a custom filesystem is opened, mirroring
+/// `std::fs::OpenOptions`.
+#[derive(Debug, Clone, Copy)]
+#[repr(C)]
+#[allow(non_camel_case_types)]
+pub struct wasi_fs_open_options_t {
+    pub read: bool,
+    pub write: bool,
+    pub append: bool,
+    pub truncate: bool,
+    pub create: bool,
+    pub create_new: bool,
+}
+
+impl From<&OpenOptionsConfig> for wasi_fs_open_options_t {
+    fn from(other: &OpenOptionsConfig) -> Self {
+        Self {
+            read: other.read(),
+            write: other.write(),
+            append: other.append(),
+            truncate: other.truncate(),
+            create: other.create(),
+            create_new: other.create_new(),
+        }
+    }
+}
+
+/// Origin of a `seek` callback of a [`wasi_fs_vtable_t`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[repr(C)]
+#[allow(non_camel_case_types)]
+pub enum wasi_fs_whence_t {
+    WASI_FS_SEEK_START = 0,
+    WASI_FS_SEEK_CURRENT = 1,
+    WASI_FS_SEEK_END = 2,
+}
+
+/// A directory listing being filled by the `readdir` callback of a
+/// [`wasi_fs_vtable_t`], see [`wasi_fs_dir_push`].
+#[allow(non_camel_case_types)]
+pub struct wasi_fs_dir_t {
+    path: PathBuf,
+    entries: Vec<DirEntry>,
+}
+
+/// Add the entry `name` to the directory listing `dir`.
+#[no_mangle]
+pub unsafe extern "C" fn wasi_fs_dir_push(
+    dir: &mut wasi_fs_dir_t,
+    name: *const c_char,
+    metadata: &wasi_fs_metadata_t,
+) {
+    debug_assert!(!name.is_null());
+
+    let name = CStr::from_ptr(name).to_string_lossy();
+
+    dir.entries.push(DirEntry {
+        path: dir.path.join(name.as_ref()),
+        metadata: Ok((*metadata).into()),
+    });
+}
+
+/// The callbacks implementing a custom filesystem.
+///
+/// Every callback receives the `user_data` given to [`wasi_fs_new`].
+/// Paths are absolute and NUL-terminated. Files are identified by the
+/// opaque handle produced by `open`, which is released with `close`.
+///
+/// `open`, `close`, `read`, `write`, `seek`, `stat`, `readdir` and
+/// `unlink` are required; the other callbacks may be `NULL`, in which
+/// case the corresponding operations are denied.
+#[derive(Clone, Copy)]
+#[repr(C)]
+#[allow(non_camel_case_types)]
+pub struct wasi_fs_vtable_t {
+    /// Open the file at `path`, storing its handle in `file`.
+    pub open: Option<
+        unsafe extern "C" fn(
+            user_data: *mut c_void,
+            path: *const c_char,
+            options: &wasi_fs_open_options_t,
+            file: &mut *mut c_void,
+        ) -> wasi_fs_error_t,
+    >,
+    /// Release a file handle.
+    pub close: Option<unsafe extern "C" fn(user_data: *mut c_void, file: *mut c_void)>,
+    /// Read at most `buffer_len` bytes into `buffer`, returning the
+    /// number of bytes read, `0` at the end of the file, or a
+    /// negative value on error.
+    pub read: Option<
+        unsafe extern "C" fn(
+            user_data: *mut c_void,
+            file: *mut c_void,
+            buffer: *mut c_char,
+            buffer_len: usize,
+        ) -> isize,
+    >,
+    /// Write `buffer_len` bytes from `buffer`, returning the number
+    /// of bytes written or a negative value on error.
+    pub write: Option<
+        unsafe extern "C" fn(
+            user_data: *mut c_void,
+            file: *mut c_void,
+            buffer: *const c_char,
+            buffer_len: usize,
+        ) -> isize,
+    >,
+    /// Move the cursor of the file, returning the new position from
+    /// the start of the file or a negative value on error.
+    pub seek: Option<
+        unsafe extern "C" fn(
+            user_data: *mut c_void,
+            file: *mut c_void,
+            offset: i64,
+            whence: wasi_fs_whence_t,
+        ) -> i64,
+    >,
+    /// Fill `metadata` for the entry at `path`.
+    pub stat: Option<
+        unsafe extern "C" fn(
+            user_data: *mut c_void,
+            path: *const c_char,
+            metadata: &mut wasi_fs_metadata_t,
+        ) -> wasi_fs_error_t,
+    >,
+    /// List the directory at `path` with [`wasi_fs_dir_push`].
+    pub readdir: Option<
+        unsafe extern "C" fn(
+            user_data: *mut c_void,
+            path: *const c_char,
+            dir: &mut wasi_fs_dir_t,
+        ) -> wasi_fs_error_t,
+    >,
+    /// Remove the file at `path`.
+    pub unlink: Option<
+        unsafe extern "C" fn(user_data: *mut c_void, path: *const c_char) -> wasi_fs_error_t,
+    >,
+    /// Create the directory at `path`.
+    pub mkdir: Option<
+        unsafe extern "C" fn(user_data: *mut c_void, path: *const c_char) -> wasi_fs_error_t,
+    >,
+    /// Remove the empty directory at `path`.
+    pub rmdir: Option<
+        unsafe extern "C" fn(user_data: *mut c_void, path: *const c_char) -> wasi_fs_error_t,
+    >,
+    /// Move the entry at `from` to `to`.
+    pub rename: Option<
+        unsafe extern "C" fn(
+            user_data: *mut c_void,
+            from: *const c_char,
+            to: *const c_char,
+        ) -> wasi_fs_error_t,
+    >,
+    /// Resize the file to `len` bytes.
+    pub set_len: Option<
+        unsafe extern "C" fn(
+            user_data: *mut c_void,
+            file: *mut c_void,
+            len: u64,
+        ) -> wasi_fs_error_t,
+    >,
+}
+
+/// The vtable and user data of a callback-based filesystem, shared by
+/// the filesystem and all the files it opened.
+struct VTable {
+    vtable: wasi_fs_vtable_t,
+    user_data: *mut c_void,
+}
+
+// SAFETY: the caller of `wasi_fs_new` promises that the callbacks and
+// their user data can be used from any thread.
+unsafe impl Send for VTable {}
+unsafe impl Sync for VTable {}
+
+impl fmt::Debug for VTable {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("VTable")
+            .field("user_data", &self.user_data)
+            .finish()
+    }
+}
+
+/// Converts `path` for the callbacks. WASIX may hand out paths
+/// relative to the root, which are made absolute here.
+fn c_path(path: &Path) -> virtual_fs::Result<CString> {
+    let path = Path::new("/").join(path);
+    let path = path.to_str().ok_or(FsError::InvalidInput)?;
+
+    CString::new(path).map_err(|_| FsError::InvalidInput)
+}
+
+impl VTable {
+    fn stat(&self, path: &Path) -> virtual_fs::Result<Metadata> {
+        let path = c_path(path)?;
+        let mut metadata = wasi_fs_metadata_t {
+            file_type: wasi_fs_file_type_t::WASI_FS_FILE,
+            len: 0,
+            accessed: 0,
+            created: 0,
+            modified: 0,
+        };
+
+        check(unsafe {
+            (self.vtable.stat.unwrap())(self.user_data, path.as_ptr(), &mut metadata)
+        })?;
+
+        Ok(metadata.into())
+    }
+
+    fn path_op(
+        &self,
+        callback: Option<
+            unsafe extern "C" fn(user_data: *mut c_void, path: *const c_char) -> wasi_fs_error_t,
+        >,
+        path: &Path,
+    ) -> virtual_fs::Result<()> {
+        let callback = callback.ok_or(FsError::PermissionDenied)?;
+        let path = c_path(path)?;
+
+        check(unsafe { callback(self.user_data, path.as_ptr()) })
+    }
+}
+
+/// A `FileSystem` forwarding every operation to a [`wasi_fs_vtable_t`].
+#[derive(Debug, Clone)]
+struct VTableFileSystem {
+    inner: Arc<VTable>,
+}
+
+impl FileSystem for VTableFileSystem {
+    fn readlink(&self, _path: &Path) -> virtual_fs::Result<PathBuf> {
+        Err(FsError::InvalidInput)
+    }
+
+    fn read_dir(&self, path: &Path) -> virtual_fs::Result<ReadDir> {
+        let c_path = c_path(path)?;
+        let mut dir = wasi_fs_dir_t {
+            path: path.to_owned(),
+            entries: Vec::new(),
+        };
+
+        check(unsafe {
+            (self.inner.vtable.readdir.unwrap())(self.inner.user_data, c_path.as_ptr(), &mut dir)
+        })?;
+
+        Ok(ReadDir::new(dir.entries))
+    }
+
+    fn create_dir(&self, path: &Path) -> virtual_fs::Result<()> {
+        self.inner.path_op(self.inner.vtable.mkdir, path)
+    }
+
+    fn remove_dir(&self, path: &Path) -> virtual_fs::Result<()> {
+        self.inner.path_op(self.inner.vtable.rmdir, path)
+    }
+
+    fn rename<'a>(
+        &'a self,
+        from: &'a Path,
+        to: &'a Path,
+    ) -> Pin<Box<dyn Future<Output = virtual_fs::Result<()>> + Send + 'a>> {
+        Box::pin(async move {
+            let rename = self.inner.vtable.rename.ok_or(FsError::PermissionDenied)?;
+            let from = c_path(from)?;
+            let to = c_path(to)?;
+
+            check(unsafe { rename(self.inner.user_data, from.as_ptr(), to.as_ptr()) })
+        })
+    }
+
+    fn metadata(&self, path: &Path) -> virtual_fs::Result<Metadata> {
+        self.inner.stat(path)
+    }
+
+    fn symlink_metadata(&self, path: &Path) -> virtual_fs::Result<Metadata> {
+        self.inner.stat(path)
+    }
+
+    fn remove_file(&self, path: &Path) -> virtual_fs::Result<()> {
+        self.inner.path_op(self.inner.vtable.unlink, path)
+    }
+
+    fn new_open_options(&self) -> OpenOptions {
+        OpenOptions::new(self)
+    }
+}
+
+impl FileOpener for VTableFileSystem {
+    fn open(
+        &self,
+        path: &Path,
+        conf: &OpenOptionsConfig,
+    ) -> virtual_fs::Result<Box<dyn VirtualFile + Send + Sync + 'static>> {
+        let c_path = c_path(path)?;
+        let options = wasi_fs_open_options_t::from(conf);
+        let mut handle = std::ptr::null_mut();
+
+        check(unsafe {
+            (self.inner.vtable.open.unwrap())(
+                self.inner.user_data,
+                c_path.as_ptr(),
+                &options,
+                &mut handle,
+            )
+        })?;
+
+        Ok(Box::new(VTableFile {
+            fs: self.inner.clone(),
+            path: path.to_owned(),
+            handle,
+            position: 0,
+        }))
+    }
+}
+
+/// A file opened by a [`VTableFileSystem`].
+#[derive(Debug)]
+struct VTableFile {
+    fs: Arc<VTable>,
+    path: PathBuf,
+    handle: *mut c_void,
+    /// Result of the last seek, reported by `poll_complete`.
+    position: u64,
+}
+
+// SAFETY: see `VTable`.
+unsafe impl Send for VTableFile {}
+unsafe impl Sync for VTableFile {}
+
+impl Drop for VTableFile {
+    fn drop(&mut self) {
+        unsafe { (self.fs.vtable.close.unwrap())(self.fs.user_data, self.handle) }
+    }
+}
+
+impl VTableFile {
+    fn metadata(&self) -> Metadata {
+        self.fs.stat(&self.path).unwrap_or_default()
+    }
+}
+
+fn io_result(result: isize) -> io::Result<usize> {
+    usize::try_from(result).map_err(|_| {
+        io::Error::new(
+            io::ErrorKind::Other,
+            "the filesystem callback reported an error",
+        )
+    })
+}
+
+impl tokio::io::AsyncRead for VTableFile {
+    fn poll_read(
+        self: Pin<&mut Self>,
+        _cx: &mut Context<'_>,
+        buf: &mut tokio::io::ReadBuf<'_>,
+    ) -> Poll<io::Result<()>> {
+        let unfilled = buf.initialize_unfilled();
+        let result = unsafe {
+            (self.fs.vtable.read.unwrap())(
+                self.fs.user_data,
+                self.handle,
+                unfilled.as_mut_ptr() as *mut c_char,
+                unfilled.len(),
+            )
+        };
+        let read = io_result(result)?.min(unfilled.len());
+        buf.advance(read);
+
+        Poll::Ready(Ok(()))
+    }
+}
+
+impl tokio::io::AsyncWrite for VTableFile {
+    fn poll_write(
+        self: Pin<&mut Self>,
+        _cx: &mut Context<'_>,
+        buf: &[u8],
+    ) -> Poll<io::Result<usize>> {
+        let result = unsafe {
+            (self.fs.vtable.write.unwrap())(
+                self.fs.user_data,
+                self.handle,
+                buf.as_ptr() as *const c_char,
+                buf.len(),
+            )
+        };
+
+        Poll::Ready(io_result(result).map(|written| written.min(buf.len())))
+    }
+
+    fn poll_flush(self: Pin<&mut Self>, _cx: &mut Context<'_>) -> Poll<io::Result<()>> {
+        Poll::Ready(Ok(()))
+    }
+
+    fn poll_shutdown(self: Pin<&mut Self>, _cx: &mut Context<'_>) -> Poll<io::Result<()>> {
+        Poll::Ready(Ok(()))
+    }
+}
+
+impl tokio::io::AsyncSeek for VTableFile {
+    fn start_seek(mut self: Pin<&mut Self>, position: SeekFrom) -> io::Result<()> {
+        let (offset, whence) = match position {
+            SeekFrom::Start(offset) => (
+                i64::try_from(offset).map_err(|_| io::ErrorKind::InvalidInput)?,
+                wasi_fs_whence_t::WASI_FS_SEEK_START,
+            ),
+            SeekFrom::Current(offset) => (offset, wasi_fs_whence_t::WASI_FS_SEEK_CURRENT),
+            SeekFrom::End(offset) => (offset, wasi_fs_whence_t::WASI_FS_SEEK_END),
+        };
+
+        let result = unsafe {
+            (self.fs.vtable.seek.unwrap())(self.fs.user_data, self.handle, offset, whence)
+        };
+        self.position = u64::try_from(result).map_err(|_| {
+            io::Error::new(
+                io::ErrorKind::Other,
+                "the filesystem callback reported an error",
+            )
+        })?;
+
+        Ok(())
+    }
+
+    fn poll_complete(self: Pin<&mut Self>, _cx: &mut Context<'_>) -> Poll<io::Result<u64>> {
+        Poll::Ready(Ok(self.position))
+    }
+}
+
+impl VirtualFile for VTableFile {
+    fn last_accessed(&self) -> u64 {
+        self.metadata().accessed
+    }
+
+    fn last_modified(&self) -> u64 {
+        self.metadata().modified
+    }
+
+    fn created_time(&self) -> u64 {
+        self.metadata().created
+    }
+
+    fn size(&self) -> u64 {
+        self.metadata().len
+    }
+
+    fn set_len(&mut self, new_size: u64) -> virtual_fs::Result<()> {
+        let set_len = self.fs.vtable.set_len.ok_or(FsError::PermissionDenied)?;
+
+        check(unsafe { set_len(self.fs.user_data, self.handle, new_size) })
+    }
+
+    fn unlink(&mut self) -> virtual_fs::Result<()> {
+        self.fs.path_op(self.fs.vtable.unlink, &self.path)
+    }
+
+    fn poll_read_ready(self: Pin<&mut Self>, _cx: &mut Context<'_>) -> Poll<io::Result<usize>> {
+        Poll::Ready(Ok(8192))
+    }
+
+    fn poll_write_ready(self: Pin<&mut Self>, _cx: &mut Context<'_>) -> Poll<io::Result<usize>> {
+        Poll::Ready(Ok(8192))
+    }
+}
+
+/// A custom filesystem, see [`wasi_fs_new`] and
+/// [`wasi_fs_new_memory`].
+#[allow(non_camel_case_types)]
+pub struct wasi_fs_t {
+    pub(super) inner: Arc<dyn FileSystem + Send + Sync>,
+}
+
+/// Create a filesystem backed by the callbacks of `vtable`, which are
+/// called with `user_data`.
+///
+/// The callbacks may be called from any thread, and `user_data` must
+/// outlive the filesystem and every WASI environment using it.
+///
+/// Returns `NULL` if a required callback is missing.
+#[no_mangle]
+pub extern "C" fn wasi_fs_new(
+    vtable: &wasi_fs_vtable_t,
+    user_data: *mut c_void,
+) -> Option<Box<wasi_fs_t>> {
+    let required = [
+        ("open", vtable.open.is_some()),
+        ("close", vtable.close.is_some()),
+        ("read", vtable.read.is_some()),
+        ("write", vtable.write.is_some()),
+        ("seek", vtable.seek.is_some()),
+        ("stat", vtable.stat.is_some()),
+        ("readdir", vtable.readdir.is_some()),
+        ("unlink", vtable.unlink.is_some()),
+    ];
+
+    if let Some((name, _)) = required.iter().find(|(_, present)| !present) {
+        update_last_error(format!("the `{}` callback is required", name));
+        return None;
+    }
+
+    let fs = VTableFileSystem {
+        inner: Arc::new(VTable {
+            vtable: *vtable,
+            user_data,
+        }),
+    };
+
+    Some(Box::new(wasi_fs_t {
+        inner: Arc::new(fs),
+    }))
+}
+
+/// Create an empty in-memory filesystem, to be populated with
+/// [`wasi_fs_create_dir`] and [`wasi_fs_write_file`].
+#[no_mangle]
+pub extern "C" fn wasi_fs_new_memory() -> Box<wasi_fs_t> {
+    Box::new(wasi_fs_t {
+        inner: Arc::new(TmpFileSystem::new()),
+    })
+}
+
+/// Delete a [`wasi_fs_t`]. WASI environments using it keep it alive.
+#[no_mangle]
+pub extern "C" fn wasi_fs_delete(_fs: Option<Box<wasi_fs_t>>) {}
+
+unsafe fn path_arg(path: *const c_char) -> Option<PathBuf> {
+    debug_assert!(!path.is_null());
+
+    let path = c_try!(CStr::from_ptr(path).to_str());
+
+    Some(PathBuf::from(path))
+}
+
+fn create_dir_all(fs: &dyn FileSystem, path: &Path) -> virtual_fs::Result<()> {
+    for dir in path.ancestors().collect::<Vec<_>>().into_iter().rev() {
+        if dir.parent().is_some() && fs.metadata(dir).is_err() {
+            fs.create_dir(dir)?;
+        }
+    }
+
+    Ok(())
+}
+
+/// Create the directory `path` in `fs`, along with its missing
+/// parents.
+#[no_mangle]
+pub unsafe extern "C" fn wasi_fs_create_dir(fs: &wasi_fs_t, path: *const c_char) -> bool {
+    let Some(path) = path_arg(path) else {
+        return false;
+    };
+
+    match create_dir_all(fs.inner.as_ref(), &path) {
+        Ok(()) => true,
+        Err(e) => {
+            update_last_error(format!("failed to create `{}`: {}", path.display(), e));
+            false
+        }
+    }
+}
+
+/// Create or replace the file `path` in `fs` with `content`, along
+/// with its missing parent directories.
+#[no_mangle]
+pub unsafe extern "C" fn wasi_fs_write_file(
+    fs: &wasi_fs_t,
+    path: *const c_char,
+    content: &wasm_byte_vec_t,
+) -> bool {
+    let Some(path) = path_arg(path) else {
+        return false;
+    };
+
+    if let Some(parent) = path.parent() {
+        if let Err(e) = create_dir_all(fs.inner.as_ref(), parent) {
+            update_last_error(format!("failed to create `{}`: {}", parent.display(), e));
+            return false;
+        }
+    }
+
+    let result = fs
+        .inner
+        .new_open_options()
+        .write(true)
+        .create(true)
+        .truncate(true)
+        .open(&path);
+    let mut file = c_try!(result; otherwise false);
+
+    match InlineWaker::block_on(file.write_all(content.as_slice())) {
+        Ok(()) => true,
+        Err(e) => {
+            update_last_error(format!("failed to write `{}`: {}", path.display(), e));
+            false
+        }
+    }
+}
+
+/// Read the file `path` of `fs` into `out`.
+#[no_mangle]
+pub unsafe extern "C" fn wasi_fs_read_file(
+    fs: &wasi_fs_t,
+    path: *const c_char,
+    out: &mut wasm_byte_vec_t,
+) -> bool {
+    let Some(path) = path_arg(path) else {
+        return false;
+    };
+
+    let result = fs.inner.new_open_options().read(true).open(&path);
+    let mut file = c_try!(result; otherwise false);
+    let mut content = Vec::new();
+
+    match InlineWaker::block_on(file.read_to_end(&mut content)) {
+        Ok(_) => {
+            out.set_buffer(content);
+            true
+        }
+        Err(e) => {
+            update_last_error(format!("failed to read `{}`: {}", path.display(), e));
+            false
+        }
+    }
+}
+
+/// Use `fs` as the root filesystem of the WASI program, instead of
+/// the host filesystem.
+#[no_mangle]
+pub extern "C" fn wasi_config_set_fs(config: &mut wasi_config_t, fs: &wasi_fs_t) {
+    config.builder.set_fs(Box::new(fs.inner.clone()));
+}
+
+#[cfg(test)]
+mod tests {
+    #[cfg(not(target_os = "windows"))]
+    use inline_c::assert_c;
+    #[cfg(target_os = "windows")]
+    use wasmer_inline_c::assert_c;
+
+    #[cfg_attr(coverage, ignore)]
+    #[test]
+    fn test_wasi_fs_vtable() {
+        (assert_c! {
+            #include "tests/wasmer.h"
+
+            typedef struct {
+                char path[32];
+                char data[64];
+                size_t len;
+                bool used;
+            } file_t;
+
+            typedef struct {
+                file_t* file;
+                size_t position;
+            } handle_t;
+
+            static file_t files[4];
+
+            file_t* find(const char* path) {
+                for (int i = 0; i < 4; ++i) {
+                    if (files[i].used && strcmp(files[i].path, path) == 0) {
+                        return &files[i];
+                    }
+                }
+
+                return NULL;
+            }
+
+            wasi_fs_error_t fs_open(void* user_data, const char* path, const wasi_fs_open_options_t* options, void** handle) {
+                (void) user_data;
+                file_t* file = find(path);
+
+                if (!file) {
+                    if (!options->create) {
+                        return WASI_FS_NOT_FOUND;
+                    }
+
+                    for (int i = 0; i < 4 && !file; ++i) {
+                        if (!files[i].used) {
+                            file = &files[i];
+                        }
+                    }
+
+                    if (!file) {
+                        return WASI_FS_STORAGE_FULL;
+                    }
+
+                    file->used = true;
+                    file->len = 0;
+                    strcpy(file->path, path);
+                }
+
+                if (options->truncate) {
+                    file->len = 0;
+                }
+
+                handle_t* new_handle = malloc(sizeof(handle_t));
+                new_handle->file = file;
+                new_handle->position = 0;
+                *handle = new_handle;
+
+                return WASI_FS_OK;
+            }
+
+            void fs_close(void* user_data, void* handle) {
+                (void) user_data;
+                free(handle);
+            }
+
+            intptr_t fs_read(void* user_data, void* handle, char* buffer, uintptr_t buffer_len) {
+                (void) user_data;
+                handle_t* h = handle;
+                size_t len = h->file->len - h->position;
+
+                if (len > buffer_len) {
+                    len = buffer_len;
+                }
+
+                memcpy(buffer, h->file->data + h->position, len);
+                h->position += len;
+
+                return len;
+            }
+
+            intptr_t fs_write(void* user_data, void* handle, const char* buffer, uintptr_t buffer_len) {
+                (void) user_data;
+                handle_t* h = handle;
+
+                if (h->position + buffer_len > sizeof(h->file->data)) {
+                    return -1;
+                }
+
+                memcpy(h->file->data + h->position, buffer, buffer_len);
+                h->position += buffer_len;
+
+                if (h->position > h->file->len) {
+                    h->file->len = h->position;
+                }
+
+                return buffer_len;
+            }
+
+            int64_t fs_seek(void* user_data, void* handle, int64_t offset, wasi_fs_whence_t whence) {
+                (void) user_data;
+                handle_t* h = handle;
+
+                switch (whence) {
+                    case WASI_FS_SEEK_START: h->position = offset; break;
+                    case WASI_FS_SEEK_CURRENT: h->position += offset; break;
+                    case WASI_FS_SEEK_END: h->position = h->file->len + offset; break;
+                }
+
+                return h->position;
+            }
+
+            wasi_fs_error_t fs_stat(void* user_data, const char* path, wasi_fs_metadata_t* metadata) {
+                (void) user_data;
+
+                if (strcmp(path, "/") == 0) {
+                    metadata->file_type = WASI_FS_DIRECTORY;
+                    return WASI_FS_OK;
+                }
+
+                file_t* file = find(path);
+
+                if (!file) {
+                    return WASI_FS_NOT_FOUND;
+                }
+
+                metadata->file_type = WASI_FS_FILE;
+                metadata->len = file->len;
+
+                return WASI_FS_OK;
+            }
+
+            wasi_fs_error_t fs_readdir(void* user_data, const char* path, wasi_fs_dir_t* dir) {
+                (void) user_data;
+                (void) path;
+
+                for (int i = 0; i < 4; ++i) {
+                    if (files[i].used) {
+                        wasi_fs_metadata_t metadata = { WASI_FS_FILE, files[i].len, 0, 0, 0 };
+                        wasi_fs_dir_push(dir, files[i].path + 1, &metadata);
+                    }
+                }
+
+                return WASI_FS_OK;
+            }
+
+            wasi_fs_error_t fs_unlink(void* user_data, const char* path) {
+                (void) user_data;
+                file_t* file = find(path);
+
+                if (!file) {
+                    return WASI_FS_NOT_FOUND;
+                }
+
+                file->used = false;
+
+                return WASI_FS_OK;
+            }
+
+            int main() {
+                wasm_engine_t* engine = wasm_engine_new();
+                wasm_store_t* store = wasm_store_new(engine);
+
+                // Copy `input.txt` to `output.txt`, relative to the
+                // root directory (fd 3).
+                wasm_byte_vec_t wat;
+                wasmer_byte_vec_new_from_string(
+                    &wat,
+                    "(module\n"
+                    "  (import \"wasi_snapshot_preview1\" \"path_open\" (func $path_open (param i32 i32 i32 i32 i32 i64 i64 i32 i32) (result i32)))\n"
+                    "  (import \"wasi_snapshot_preview1\" \"fd_read\" (func $fd_read (param i32 i32 i32 i32) (result i32)))\n"
+                    "  (import \"wasi_snapshot_preview1\" \"fd_write\" (func $fd_write (param i32 i32 i32 i32) (result i32)))\n"
+                    "  (import \"wasi_snapshot_preview1\" \"fd_close\" (func $fd_close (param i32) (result i32)))\n"
+                    "  (memory (export \"memory\") 1)\n"
+                    "  (data (i32.const 100) \"input.txt\")\n"
+                    "  (data (i32.const 120) \"output.txt\")\n"
+                    "  (func (export \"_start\")\n"
+                    "    (if (call $path_open (i32.const 3) (i32.const 0) (i32.const 100) (i32.const 9) (i32.const 0) (i64.const 66) (i64.const 66) (i32.const 0) (i32.const 12)) (then unreachable))\n"
+                    "    (i32.store (i32.const 0) (i32.const 200))\n"
+                    "    (i32.store (i32.const 4) (i32.const 64))\n"
+                    "    (if (call $fd_read (i32.load (i32.const 12)) (i32.const 0) (i32.const 1) (i32.const 8)) (then unreachable))\n"
+                    "    (i32.store (i32.const 4) (i32.load (i32.const 8)))\n"
+                    "    (if (call $path_open (i32.const 3) (i32.const 0) (i32.const 120) (i32.const 10) (i32.const 9) (i64.const 66) (i64.const 66) (i32.const 0) (i32.const 16)) (then unreachable))\n"
+                    "    (if (call $fd_write (i32.load (i32.const 16)) (i32.const 0) (i32.const 1) (i32.const 8)) (then unreachable))\n"
+                    "    (drop (call $fd_close (i32.load (i32.const 12))))\n"
+                    "    (drop (call $fd_close (i32.load (i32.const 16))))))"
+                );
+                wasm_byte_vec_t wasm;
+                wat2wasm(&wat, &wasm);
+
+                wasm_module_t* module = wasm_module_new(store, &wasm);
+                assert(module);
+
+                files[0].used = true;
+                strcpy(files[0].path, "/input.txt");
+                strcpy(files[0].data, "Hello, World!");
+                files[0].len = 13;
+
+                // `rename`, `mkdir`, `rmdir` and `set_len` are optional.
+                wasi_fs_vtable_t vtable = {
+                    fs_open, fs_close, fs_read, fs_write, fs_seek,
+                    fs_stat, fs_readdir, fs_unlink, NULL, NULL, NULL, NULL,
+                };
+                wasi_fs_t* fs = wasi_fs_new(&vtable, NULL);
+                assert(fs);
+
+                // `open` is required.
+                wasi_fs_vtable_t incomplete_vtable = vtable;
+                incomplete_vtable.open = NULL;
+                assert(wasi_fs_new(&incomplete_vtable, NULL) == NULL);
+
+                wasi_config_t* config = wasi_config_new("example_program");
+                wasi_config_set_fs(config, fs);
+                assert(wasi_config_preopen_dir(config, "/"));
+
+                wasi_env_t* wasi_env = wasi_env_new(store, config);
+                assert(wasi_env);
+
+                wasm_extern_vec_t imports;
+                assert(wasi_get_imports(store, wasi_env, module, &imports));
+
+                wasm_instance_t* instance = wasm_instance_new(store, module, &imports, NULL);
+                assert(instance);
+                assert(wasi_env_initialize_instance(wasi_env, store, instance));
+
+                wasm_func_t* start = wasi_get_start_function(instance);
+                assert(start);
+
+                wasm_val_vec_t args = WASM_EMPTY_VEC;
+                wasm_val_vec_t results = WASM_EMPTY_VEC;
+                assert(wasm_func_call(start, &args, &results) == NULL);
+
+                file_t* output = find("/output.txt");
+                assert(output);
+                assert(output->len == 13);
+                assert(strncmp(output->data, "Hello, World!", output->len) == 0);
+
+                wasm_func_delete(start);
+                wasm_instance_delete(instance);
+                wasm_extern_vec_delete(&imports);
+                wasi_env_delete(wasi_env);
+                wasi_fs_delete(fs);
+                wasm_module_delete(module);
+                wasm_byte_vec_delete(&wasm);
+                wasm_byte_vec_delete(&wat);
+                wasm_store_delete(store);
+                wasm_engine_delete(engine);
+
+                return 0;
+            }
+        })
+        .success();
+    }
+
+    #[cfg_attr(coverage, ignore)]
+    #[test]
+    fn test_wasi_fs_memory() {
+        (assert_c! {
+            #include "tests/wasmer.h"
+
+            int main() {
+                wasm_engine_t* engine = wasm_engine_new();
+                wasm_store_t* store = wasm_store_new(engine);
+
+                // Copy `input.txt` to `output.txt`, relative to the
+                // first preopened directory (fd 4).
+                wasm_byte_vec_t wat;
+                wasmer_byte_vec_new_from_string(
+                    &wat,
+                    "(module\n"
+                    "  (import \"wasi_snapshot_preview1\" \"path_open\" (func $path_open (param i32 i32 i32 i32 i32 i64 i64 i32 i32) (result i32)))\n"
+                    "  (import \"wasi_snapshot_preview1\" \"fd_read\" (func $fd_read (param i32 i32 i32 i32) (result i32)))\n"
+                    "  (import \"wasi_snapshot_preview1\" \"fd_write\" (func $fd_write (param i32 i32 i32 i32) (result i32)))\n"
+                    "  (import \"wasi_snapshot_preview1\" \"fd_close\" (func $fd_close (param i32) (result i32)))\n"
+                    "  (memory (export \"memory\") 1)\n"
+                    "  (data (i32.const 100) \"input.txt\")\n"
+                    "  (data (i32.const 120) \"output.txt\")\n"
+                    "  (func (export \"_start\")\n"
+                    "    (if (call $path_open (i32.const 4) (i32.const 0) (i32.const 100) (i32.const 9) (i32.const 0) (i64.const 66) (i64.const 66) (i32.const 0) (i32.const 12)) (then unreachable))\n"
+                    "    (i32.store (i32.const 0) (i32.const 200))\n"
+                    "    (i32.store (i32.const 4) (i32.const 64))\n"
+                    "    (if (call $fd_read (i32.load (i32.const 12)) (i32.const 0) (i32.const 1) (i32.const 8)) (then unreachable))\n"
+                    "    (i32.store (i32.const 4) (i32.load (i32.const 8)))\n"
+                    "    (if (call $path_open (i32.const 4) (i32.const 0) (i32.const 120) (i32.const 10) (i32.const 9) (i64.const 66) (i64.const 66) (i32.const 0) (i32.const 16)) (then unreachable))\n"
+                    "    (if (call $fd_write (i32.load (i32.const 16)) (i32.const 0) (i32.const 1) (i32.const 8)) (then unreachable))\n"
+                    "    (drop (call $fd_close (i32.load (i32.const 12))))\n"
+                    "    (drop (call $fd_close (i32.load (i32.const 16))))))"
+                );
+                wasm_byte_vec_t wasm;
+                wat2wasm(&wat, &wasm);
+
+                wasm_module_t* module = wasm_module_new(store, &wasm);
+                assert(module);
+
+                wasi_fs_t* fs = wasi_fs_new_memory();
+                assert(wasi_fs_create_dir(fs, "/data/empty"));
+
+                wasm_byte_vec_t input;
+                wasmer_byte_vec_new_from_string(&input, "Hello, World!");
+                assert(wasi_fs_write_file(fs, "/data/input.txt", &input));
+
+                wasm_byte_vec_t missing;
+                assert(!wasi_fs_read_file(fs, "/data/output.txt", &missing));
+
+                wasi_config_t* config = wasi_config_new("example_program");
+                wasi_config_set_fs(config, fs);
+                assert(wasi_config_preopen_dir(config, "/data"));
+
+                wasi_env_t* wasi_env = wasi_env_new(store, config);
+                assert(wasi_env);
+
+                wasm_extern_vec_t imports;
+                assert(wasi_get_imports(store, wasi_env, module, &imports));
+
+                wasm_instance_t* instance = wasm_instance_new(store, module, &imports, NULL);
+                assert(instance);
+                assert(wasi_env_initialize_instance(wasi_env, store, instance));
+
+                wasm_func_t* start = wasi_get_start_function(instance);
+                assert(start);
+
+                wasm_val_vec_t args = WASM_EMPTY_VEC;
+                wasm_val_vec_t results = WASM_EMPTY_VEC;
+                assert(wasm_func_call(start, &args, &results) == NULL);
+
+                wasm_byte_vec_t output;
+                assert(wasi_fs_read_file(fs, "/data/output.txt", &output));
+                assert(output.size == 13);
+                assert(strncmp(output.data, "Hello, World!", output.size) == 0);
+
+                wasm_byte_vec_delete(&output);
+                wasm_func_delete(start);
+                wasm_instance_delete(instance);
+                wasm_extern_vec_delete(&imports);
+                wasi_env_delete(wasi_env);
+                wasi_fs_delete(fs);
+                wasm_byte_vec_delete(&input);
+                wasm_module_delete(module);
+                wasm_byte_vec_delete(&wasm);
+                wasm_byte_vec_delete(&wat);
+                wasm_store_delete(store);
+                wasm_engine_delete(engine);
+
+                return 0;
+            }
+        })
+        .success();
+    }
+}
@@ -0,0 +1,250 @@
+//! Journaling of WASI programs.
+//!
+//! A journal is a log file recording the syscalls and the snapshots
+//! of a program. When a WASI environment is instantiated with
+//! [`super::wasi_env_instantiate`] and a journal that already has
+//! content, the program is restored from it, and resumes from its
+//! latest snapshot when its start function is called.
+
+use super::{wasi_config_t, wasi_env_t};
+use crate::error::update_last_error;
+use std::ffi::CStr;
+use std::os::raw::c_char;
+use std::sync::Arc;
+use wasmer_wasix::journal::{DynJournal, LogFileJournal, SnapshotTrigger};
+
+/// The situations in which a snapshot of the program is written to
+/// its journal, see [`wasi_config_add_snapshot_trigger`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[repr(C)]
+#[allow(non_camel_case_types)]
+pub enum wasi_snapshot_trigger_t {
+    /// All the threads of the program are idle.
+    WASI_SNAPSHOT_TRIGGER_IDLE = 0,
+    /// The program listens on a socket for the first time.
+    WASI_SNAPSHOT_TRIGGER_FIRST_LISTEN = 1,
+    /// The program reads its environment variables for the first time.
+    WASI_SNAPSHOT_TRIGGER_FIRST_ENVIRON = 2,
+    /// The program reads `stdin` for the first time.
+    WASI_SNAPSHOT_TRIGGER_FIRST_STDIN = 3,
+    /// The program receives its first interrupt signal.
+    WASI_SNAPSHOT_TRIGGER_FIRST_SIGINT = 4,
+    /// The program receives an interrupt signal.
+    WASI_SNAPSHOT_TRIGGER_SIGINT = 5,
+    /// The program receives an alarm signal.
+    WASI_SNAPSHOT_TRIGGER_SIGALRM = 6,
+    /// The program receives a terminal stop signal.
+    WASI_SNAPSHOT_TRIGGER_SIGTSTP = 7,
+    /// The program receives a stop signal.
+    WASI_SNAPSHOT_TRIGGER_SIGSTOP = 8,
+    /// The program makes a non-deterministic call.
+    WASI_SNAPSHOT_TRIGGER_NON_DETERMINISTIC_CALL = 9,
+}
+
+impl From<wasi_snapshot_trigger_t> for SnapshotTrigger {
+    fn from(other: wasi_snapshot_trigger_t) -> Self {
+        match other {
+            wasi_snapshot_trigger_t::WASI_SNAPSHOT_TRIGGER_IDLE => SnapshotTrigger::Idle,
+            wasi_snapshot_trigger_t::WASI_SNAPSHOT_TRIGGER_FIRST_LISTEN => {
+                SnapshotTrigger::FirstListen
+            }
+            wasi_snapshot_trigger_t::WASI_SNAPSHOT_TRIGGER_FIRST_ENVIRON => {
+                SnapshotTrigger::FirstEnviron
+            }
+            wasi_snapshot_trigger_t::WASI_SNAPSHOT_TRIGGER_FIRST_STDIN => {
+                SnapshotTrigger::FirstStdin
+            }
+            wasi_snapshot_trigger_t::WASI_SNAPSHOT_TRIGGER_FIRST_SIGINT => {
+                SnapshotTrigger::FirstSigint
+            }
+            wasi_snapshot_trigger_t::WASI_SNAPSHOT_TRIGGER_SIGINT => SnapshotTrigger::Sigint,
+            wasi_snapshot_trigger_t::WASI_SNAPSHOT_TRIGGER_SIGALRM => SnapshotTrigger::Sigalrm,
+            wasi_snapshot_trigger_t::WASI_SNAPSHOT_TRIGGER_SIGTSTP => SnapshotTrigger::Sigtstp,
+            wasi_snapshot_trigger_t::WASI_SNAPSHOT_TRIGGER_SIGSTOP => SnapshotTrigger::Sigstop,
+            wasi_snapshot_trigger_t::WASI_SNAPSHOT_TRIGGER_NON_DETERMINISTIC_CALL => {
+                SnapshotTrigger::NonDeterministicCall
+            }
+        }
+    }
+}
+
+/// Attach the journal stored in the log file at `path`, creating the
+/// file if it does not exist.
+///
+/// Journals are replayed in the order they are added when the
+/// environment is instantiated, and new events are written to the
+/// last one.
+#[no_mangle]
+pub unsafe extern "C" fn wasi_config_add_journal(
+    config: &mut wasi_config_t,
+    path: *const c_char,
+) -> bool {
+    debug_assert!(!path.is_null());
+
+    let path = c_try!(CStr::from_ptr(path).to_str(); otherwise false);
+    let journal = c_try!(LogFileJournal::new(path); otherwise false);
+
+    config.journals.push(Arc::new(journal) as Arc<DynJournal>);
+
+    true
+}
+
+/// Write a snapshot of the program to its journal whenever `trigger`
+/// happens.
+#[no_mangle]
+pub extern "C" fn wasi_config_add_snapshot_trigger(
+    config: &mut wasi_config_t,
+    trigger: wasi_snapshot_trigger_t,
+) {
+    config.builder.add_snapshot_trigger(trigger.into());
+}
+
+/// Request a snapshot of the program to be written to its journal.
+///
+/// The snapshot is taken the next time the threads of the program
+/// block in a syscall, e.g. while sleeping or polling. This only
+/// works for programs built with support for asynchronous threading
+/// (i.e. exporting the `asyncify_*` functions).
+///
+/// Returns `false` if the environment has no journal.
+#[no_mangle]
+pub extern "C" fn wasi_env_snapshot(env: &mut wasi_env_t) -> bool {
+    let store = c_try!(unsafe { env.store.store() }; otherwise false);
+    let data = env.inner.data(&store);
+
+    if data.runtime().active_journal().is_none() {
+        update_last_error("the WASI environment has no journal");
+        return false;
+    }
+
+    // Dropping the future does not cancel the snapshot.
+    drop(data.process.snapshot(SnapshotTrigger::Explicit));
+
+    true
+}
+
+#[cfg(test)]
+mod tests {
+    #[cfg(not(target_os = "windows"))]
+    use inline_c::assert_c;
+    #[cfg(target_os = "windows")]
+    use wasmer_inline_c::assert_c;
+
+    #[cfg_attr(coverage, ignore)]
+    #[test]
+    fn test_wasi_journal_restore() {
+        (assert_c! {
+            #include "tests/wasmer.h"
+
+            static char output[64];
+            static size_t output_len = 0;
+
+            intptr_t on_stdout(void* user_data, const char* buffer, uintptr_t buffer_len) {
+                (void) user_data;
+                memcpy(output + output_len, buffer, buffer_len);
+                output_len += buffer_len;
+                return buffer_len;
+            }
+
+            void run(wasm_store_t* store, wasm_module_t* module, const char* journal, bool snapshot) {
+                output_len = 0;
+
+                wasi_config_t* config = wasi_config_new("example_program");
+                wasi_config_set_stdout_callback(config, on_stdout, NULL);
+                assert(wasi_config_add_journal(config, journal));
+
+                wasm_instance_t* instance = NULL;
+                wasi_env_t* wasi_env = wasi_env_instantiate(store, config, module, &instance);
+                assert(wasi_env);
+
+                if (snapshot) {
+                    assert(wasi_env_snapshot(wasi_env));
+                }
+
+                wasm_func_t* start = wasi_get_start_function(instance);
+                assert(start);
+
+                wasm_val_vec_t args = WASM_EMPTY_VEC;
+                wasm_val_vec_t results = WASM_EMPTY_VEC;
+                assert(wasm_func_call(start, &args, &results) == NULL);
+
+                wasm_func_delete(start);
+                wasm_instance_delete(instance);
+                wasi_env_delete(wasi_env);
+            }
+
+            int main() {
+                wasm_engine_t* engine = wasm_engine_new();
+                wasm_store_t* store = wasm_store_new(engine);
+
+                // A hand-written stand-in for a program transformed by
+                // asyncify: it writes `Hello` into its memory, blocks
+                // in `thread_sleep` and prints what is in its memory.
+                // When it is rewound it skips writing `Hello`.
+                wasm_byte_vec_t wat;
+                wasmer_byte_vec_new_from_string(
+                    &wat,
+                    "(module\n"
+                    "  (import \"wasi_snapshot_preview1\" \"fd_write\" (func $fd_write (param i32 i32 i32 i32) (result i32)))\n"
+                    "  (import \"wasix_32v1\" \"thread_sleep\" (func $thread_sleep (param i64) (result i32)))\n"
+                    "  (memory (export \"memory\") 1)\n"
+                    "  (global (export \"__stack_pointer\") (mut i32) (i32.const 4096))\n"
+                    "  (global (export \"__stack_low\") i32 (i32.const 1024))\n"
+                    "  (global $state (mut i32) (i32.const 0))\n"
+                    "  (func (export \"asyncify_start_unwind\") (param i32) (global.set $state (i32.const 1)))\n"
+                    "  (func (export \"asyncify_stop_unwind\") (global.set $state (i32.const 0)))\n"
+                    "  (func (export \"asyncify_start_rewind\") (param i32) (global.set $state (i32.const 2)))\n"
+                    "  (func (export \"asyncify_stop_rewind\") (global.set $state (i32.const 0)))\n"
+                    "  (func (export \"asyncify_get_state\") (result i32) (global.get $state))\n"
+                    "  (func (export \"_start\")\n"
+                    "    (if (i32.ne (global.get $state) (i32.const 2)) (then\n"
+                    "      (i32.store (i32.const 200) (i32.const 0x6c6c6548))\n"
+                    "      (i32.store8 (i32.const 204) (i32.const 0x6f))))\n"
+                    "    (drop (call $thread_sleep (i64.const 0)))\n"
+                    "    (if (i32.eq (global.get $state) (i32.const 1)) (then return))\n"
+                    "    (i32.store (i32.const 100) (i32.const 200))\n"
+                    "    (i32.store (i32.const 104) (i32.const 5))\n"
+                    "    (drop (call $fd_write (i32.const 1) (i32.const 100) (i32.const 1) (i32.const 108)))))"
+                );
+                wasm_byte_vec_t wasm;
+                wat2wasm(&wat, &wasm);
+
+                wasm_module_t* module = wasm_module_new(store, &wasm);
+                assert(module);
+
+                const char* journal = "test_wasi_journal_restore.log";
+                remove(journal);
+
+                // Without a journal, no snapshot can be taken.
+                wasi_config_t* config = wasi_config_new("example_program");
+                wasm_instance_t* instance = NULL;
+                wasi_env_t* wasi_env = wasi_env_instantiate(store, config, module, &instance);
+                assert(wasi_env);
+                assert(!wasi_env_snapshot(wasi_env));
+                wasm_instance_delete(instance);
+                wasi_env_delete(wasi_env);
+
+                // Take a snapshot while the program sleeps.
+                run(store, module, journal, true);
+                assert(output_len == 5);
+                assert(strncmp(output, "Hello", output_len) == 0);
+
+                // Restore the memory from the snapshot and resume.
+                run(store, module, journal, false);
+                assert(output_len == 5);
+                assert(strncmp(output, "Hello", output_len) == 0);
+
+                remove(journal);
+
+                wasm_module_delete(module);
+                wasm_byte_vec_delete(&wasm);
+                wasm_byte_vec_delete(&wat);
+                wasm_store_delete(store);
+                wasm_engine_delete(engine);
+
+                return 0;
+            }
+        })
+        .success();
+    }
+}
@@ -2,6 +2,17 @@
 //!
 //! This API will be superseded by a standard WASI API when/if such a standard is created.
 
+mod callback_file;
+mod fs;
+mod journal;
+
+use self::callback_file::CallbackFile;
+pub use self::callback_file::{wasi_read_callback_t, wasi_write_callback_t};
+pub use self::fs::{
+    wasi_fs_dir_t, wasi_fs_error_t, wasi_fs_file_type_t, wasi_fs_metadata_t,
+    wasi_fs_open_options_t, wasi_fs_t, wasi_fs_vtable_t, wasi_fs_whence_t,
+};
+pub use self::journal::wasi_snapshot_trigger_t;
 pub use super::unstable::wasi::wasi_get_unordered_imports;
 use super::{
     externals::{wasm_extern_t, wasm_extern_vec_t, wasm_func_t, wasm_memory_t},
@@ -13,29 +24,60 @@ use super::{
 use crate::error::update_last_error;
 use std::convert::TryFrom;
 use std::ffi::CStr;
-use std::os::raw::c_char;
+use std::io::Write;
+use std::os::raw::{c_char, c_void};
 use std::slice;
 use std::sync::Arc;
+use wasmer_api::Engine;
 #[cfg(feature = "webc_runner")]
 use wasmer_api::{AsStoreMut, Imports, Module};
 use wasmer_wasix::{
     default_fs_backing, get_wasi_version,
+    journal::DynJournal,
+    rewind_ext2,
     runtime::task_manager::{tokio::TokioTaskManager, InlineWaker},
     virtual_fs::AsyncReadExt,
     virtual_fs::VirtualFile,
-    Pipe, PluggableRuntime, WasiEnv, WasiEnvBuilder, WasiFunctionEnv, WasiVersion,
+    virtual_net, Pipe, PluggableRuntime, UnsupportedVirtualNetworking, WasiEnv, WasiEnvBuilder,
+    WasiFunctionEnv, WasiVersion,
 };
 
+/// Where a standard stream of a WASI program is connected to.
 #[derive(Debug)]
+enum Stdio {
+    /// Use the host's stream.
+    Inherit,
+    /// Connect the stream to a pipe owned by the `wasi_env_t`.
+    Capture,
+    /// Forward the stream to a user-provided callback.
+    Callback(CallbackFile),
+}
+
 #[allow(non_camel_case_types)]
 pub struct wasi_config_t {
-    inherit_stdout: bool,
-    inherit_stderr: bool,
-    inherit_stdin: bool,
+    stdout: Stdio,
+    stderr: Stdio,
+    stdin: Stdio,
     builder: WasiEnvBuilder,
+    networking: wasi_networking_t,
+    journals: Vec<Arc<DynJournal>>,
     runtime: Option<tokio::runtime::Runtime>,
 }
 
+impl std::fmt::Debug for wasi_config_t {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("wasi_config_t")
+            .field("stdout", &self.stdout)
+            .field("stderr", &self.stderr)
+            .field("stdin", &self.stdin)
+            .field("builder", &self.builder)
+            .field("networking", &self.networking)
+            .field("journals", &self.journals.len())
+            .field("runtime", &self.runtime)
+            .finish()
+    }
+}
+
 #[no_mangle]
 pub unsafe extern "C" fn wasi_config_new(
     program_name: *const c_char,
@@ -52,10 +94,12 @@ pub unsafe extern "C" fn wasi_config_new(
     let _guard = runtime.enter();
 
     Some(Box::new(wasi_config_t {
-        inherit_stdout: true,
-        inherit_stderr: true,
-        inherit_stdin: true,
+        stdout: Stdio::Inherit,
+        stderr: Stdio::Inherit,
+        stdin: Stdio::Inherit,
         builder: WasiEnv::builder(prog_name).fs(default_fs_backing()),
+        networking: wasi_networking_t::WASI_NETWORKING_HOST,
+        journals: Vec::new(),
         runtime: Some(runtime),
     }))
 }
@@ -146,32 +190,194 @@ pub unsafe extern "C" fn wasi_config_mapdir(
 
 #[no_mangle]
 pub extern "C" fn wasi_config_capture_stdout(config: &mut wasi_config_t) {
-    config.inherit_stdout = false;
+    config.stdout = Stdio::Capture;
 }
 
 #[no_mangle]
 pub extern "C" fn wasi_config_inherit_stdout(config: &mut wasi_config_t) {
-    config.inherit_stdout = true;
+    config.stdout = Stdio::Inherit;
 }
 
 #[no_mangle]
 pub extern "C" fn wasi_config_capture_stderr(config: &mut wasi_config_t) {
-    config.inherit_stderr = false;
+    config.stderr = Stdio::Capture;
 }
 
 #[no_mangle]
 pub extern "C" fn wasi_config_inherit_stderr(config: &mut wasi_config_t) {
-    config.inherit_stderr = true;
+    config.stderr = Stdio::Inherit;
 }
 
-//#[no_mangle]
-//pub extern "C" fn wasi_config_capture_stdin(config: &mut wasi_config_t) {
-//    config.inherit_stdin = false;
-//}
+/// Capture `stdin`: the program reads whatever is written with
+/// [`wasi_env_write_stdin`], until [`wasi_env_close_stdin`] is
+/// called.
+#[no_mangle]
+pub extern "C" fn wasi_config_capture_stdin(config: &mut wasi_config_t) {
+    config.stdin = Stdio::Capture;
+}
 
 #[no_mangle]
 pub extern "C" fn wasi_config_inherit_stdin(config: &mut wasi_config_t) {
-    config.inherit_stdin = true;
+    config.stdin = Stdio::Inherit;
+}
+
+/// Feed `stdin` with `callback`, which is called with `user_data`
+/// every time the program reads from `stdin`.
+///
+/// The callback runs on the thread calling into the WebAssembly
+/// program, and `user_data` must outlive the `wasi_env_t`.
+#[no_mangle]
+pub extern "C" fn wasi_config_set_stdin_callback(
+    config: &mut wasi_config_t,
+    callback: wasi_read_callback_t,
+    user_data: *mut c_void,
+) {
+    config.stdin = Stdio::Callback(CallbackFile::reader(callback, user_data));
+}
+
+/// Send `stdout` to `callback`, which is called with `user_data`
+/// every time the program writes to `stdout`.
+///
+/// The callback runs on the thread calling into the WebAssembly
+/// program, and `user_data` must outlive the `wasi_env_t`.
+#[no_mangle]
+pub extern "C" fn wasi_config_set_stdout_callback(
+    config: &mut wasi_config_t,
+    callback: wasi_write_callback_t,
+    user_data: *mut c_void,
+) {
+    config.stdout = Stdio::Callback(CallbackFile::writer(callback, user_data));
+}
+
+/// Send `stderr` to `callback`, which is called with `user_data`
+/// every time the program writes to `stderr`.
+///
+/// The callback runs on the thread calling into the WebAssembly
+/// program, and `user_data` must outlive the `wasi_env_t`.
+#[no_mangle]
+pub extern "C" fn wasi_config_set_stderr_callback(
+    config: &mut wasi_config_t,
+    callback: wasi_write_callback_t,
+    user_data: *mut c_void,
+) {
+    config.stderr = Stdio::Callback(CallbackFile::writer(callback, user_data));
+}
+
+/// The network a WASI program is connected to.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[repr(C)]
+#[allow(non_camel_case_types)]
+pub enum wasi_networking_t {
+    /// No network: every socket operation fails.
+    WASI_NETWORKING_NONE = 0,
+    /// The network of the host (the default).
+    WASI_NETWORKING_HOST = 1,
+    /// A network isolated from the host, where the program can only
+    /// connect to the sockets it listens on itself.
+    WASI_NETWORKING_LOOPBACK = 2,
+}
+
+/// Choose the network the program is connected to.
+#[no_mangle]
+pub extern "C" fn wasi_config_set_networking(
+    config: &mut wasi_config_t,
+    networking: wasi_networking_t,
+) {
+    config.networking = networking;
+}
+
+/// Limit the number of threads the program can spawn, besides its
+/// main thread. There is no limit by default.
+#[no_mangle]
+pub extern "C" fn wasi_config_set_max_threads(config: &mut wasi_config_t, max_threads: usize) {
+    config.builder.capabilities_mut().threading.max_threads = Some(max_threads);
+}
+
+/// Splits `config` into a builder ready to be finalized and the
+/// handle of the tokio runtime driving its tasks, which must be
+/// entered while building the environment. Also returns our end of
+/// the `stdin` pipe when `stdin` is captured.
+fn prepare_builder(
+    config: wasi_config_t,
+    engine: &Engine,
+) -> (WasiEnvBuilder, tokio::runtime::Handle, Option<Pipe>) {
+    let wasi_config_t {
+        stdout,
+        stderr,
+        stdin,
+        mut builder,
+        networking,
+        journals,
+        runtime,
+    } = config;
+
+    let runtime = runtime.unwrap_or_else(|| {
+        tokio::runtime::Builder::new_multi_thread()
+            .enable_all()
+            .build()
+            .unwrap()
+    });
+
+    let handle = runtime.handle().clone();
+    let _guard = handle.enter();
+    let mut rt = PluggableRuntime::new(Arc::new(TokioTaskManager::new(runtime)));
+    rt.set_engine(Some(engine.clone()));
+
+    match networking {
+        wasi_networking_t::WASI_NETWORKING_NONE => {
+            rt.set_networking_implementation(UnsupportedVirtualNetworking::default())
+        }
+        wasi_networking_t::WASI_NETWORKING_HOST => {
+            rt.set_networking_implementation(virtual_net::host::LocalNetworking::default())
+        }
+        wasi_networking_t::WASI_NETWORKING_LOOPBACK => {
+            rt.set_networking_implementation(virtual_net::LoopbackNetworking::default())
+        }
+    };
+
+    for journal in journals {
+        rt.add_journal(journal.clone());
+        builder.add_journal(journal);
+    }
+
+    let mut builder = builder.runtime(Arc::new(rt));
+    let stdin = set_stdio(&mut builder, stdin, stdout, stderr);
+
+    (builder, handle, stdin)
+}
+
+/// Connects the standard streams of `builder` as configured,
+/// returning our end of the `stdin` pipe when `stdin` is captured.
+fn set_stdio(
+    builder: &mut WasiEnvBuilder,
+    stdin: Stdio,
+    stdout: Stdio,
+    stderr: Stdio,
+) -> Option<Pipe> {
+    match stdout {
+        Stdio::Inherit => {}
+        Stdio::Capture => builder.set_stdout(Box::new(Pipe::channel().0)),
+        Stdio::Callback(file) => builder.set_stdout(Box::new(file)),
+    }
+
+    match stderr {
+        Stdio::Inherit => {}
+        Stdio::Capture => builder.set_stderr(Box::new(Pipe::channel().0)),
+        Stdio::Callback(file) => builder.set_stderr(Box::new(file)),
+    }
+
+    match stdin {
+        Stdio::Inherit => None,
+        Stdio::Capture => {
+            let (host, guest) = Pipe::channel();
+            builder.set_stdin(Box::new(guest));
+            Some(host)
+        }
+        Stdio::Callback(file) => {
+            builder.set_stdin(Box::new(file));
+            None
+        }
+    }
 }
 
 #[repr(C)]
@@ -233,9 +439,9 @@ unsafe fn wasi_env_with_filesystem_inner(
     let module = &module.as_ref()?.inner;
     let imports = imports?;
 
-    let (wasi_env, import_object) = prepare_webc_env(
+    let (wasi_env, import_object, stdin) = prepare_webc_env(
         config,
-        &mut store.store_mut(),
+        &mut c_try!(store.store_mut()),
         module,
         &*(fs.ptr as *const u8), // cast wasi_filesystem_t.ptr as &'static [u8]
         fs.size,
@@ -247,35 +453,25 @@ unsafe fn wasi_env_with_filesystem_inner(
     Some(Box::new(wasi_env_t {
         inner: wasi_env,
         store: store.clone(),
+        stdin,
     }))
 }
 
 #[cfg(feature = "webc_runner")]
 fn prepare_webc_env(
-    mut config: Box<wasi_config_t>,
+    config: Box<wasi_config_t>,
     store: &mut impl AsStoreMut,
     module: &Module,
     bytes: &'static u8,
     len: usize,
     package_name: &str,
-) -> Option<(WasiFunctionEnv, Imports)> {
+) -> Option<(WasiFunctionEnv, Imports, Option<Pipe>)> {
     use virtual_fs::static_fs::StaticFileSystem;
     use webc::v1::{FsEntryType, WebC};
 
     let store_mut = store.as_store_mut();
-    let runtime = config.runtime.take();
-
-    let runtime = runtime.unwrap_or_else(|| {
-        tokio::runtime::Builder::new_multi_thread()
-            .enable_all()
-            .build()
-            .unwrap()
-    });
-
-    let handle = runtime.handle().clone();
+    let (mut builder, handle, stdin) = prepare_builder(*config, store_mut.engine());
     let _guard = handle.enter();
-    let mut rt = PluggableRuntime::new(Arc::new(TokioTaskManager::new(runtime)));
-    rt.set_engine(Some(store_mut.engine().clone()));
 
     let slice = unsafe { std::slice::from_raw_parts(bytes, len) };
     let volumes = WebC::parse_volumes_from_fileblock(slice).ok()?;
@@ -295,16 +491,6 @@ fn prepare_webc_env(
         .collect::<Vec<_>>();
 
     let filesystem = Box::new(StaticFileSystem::init(slice, package_name)?);
-    let mut builder = config.builder.runtime(Arc::new(rt));
-
-    if !config.inherit_stdout {
-        builder.set_stdout(Box::new(Pipe::channel().0));
-    }
-
-    if !config.inherit_stderr {
-        builder.set_stderr(Box::new(Pipe::channel().0));
-    }
-
     builder.set_fs(filesystem);
 
     for f_name in top_level_dirs.iter() {
@@ -315,7 +501,7 @@ fn prepare_webc_env(
     let env = builder.finalize(store).ok()?;
 
     let import_object = env.import_object(store, module).ok()?;
-    Some((env, import_object))
+    Some((env, import_object, stdin))
 }
 
 #[allow(non_camel_case_types)]
@@ -323,6 +509,9 @@ pub struct wasi_env_t {
     /// cbindgen:ignore
     pub(super) inner: WasiFunctionEnv,
     pub(super) store: StoreRef,
+    /// Our end of the `stdin` pipe, when `stdin` is captured.
+    /// cbindgen:ignore
+    stdin: Option<Pipe>,
 }
 
 /// Create a new WASI environment.
@@ -331,43 +520,70 @@ pub struct wasi_env_t {
 #[no_mangle]
 pub unsafe extern "C" fn wasi_env_new(
     store: Option<&mut wasm_store_t>,
-    mut config: Box<wasi_config_t>,
+    config: Box<wasi_config_t>,
 ) -> Option<Box<wasi_env_t>> {
     let store = &mut store?.inner;
-    let mut store_mut = store.store_mut();
+    let mut store_mut = c_try!(store.store_mut());
 
-    let runtime = config.runtime.take();
+    let (builder, handle, stdin) = prepare_builder(*config, store_mut.engine());
+    let _guard = handle.enter();
 
-    let runtime = runtime.unwrap_or_else(|| {
-        tokio::runtime::Builder::new_multi_thread()
-            .enable_all()
-            .build()
-            .unwrap()
-    });
+    let env = c_try!(builder.finalize(&mut store_mut));
 
-    let handle = runtime.handle().clone();
+    Some(Box::new(wasi_env_t {
+        inner: env,
+        store: store.clone(),
+        stdin,
+    }))
+}
+
+/// Create a new WASI environment and instantiate `module` with it,
+/// storing the instance in `instance`.
+///
+/// Unlike [`wasi_env_new`] followed by [`wasi_get_imports`] and
+/// `wasm_instance_new`, this supports WASIX programs importing their
+/// memory, which is how programs spawning threads are built. The
+/// instance is already initialized, so [`wasi_env_initialize_instance`]
+/// must not be called.
+///
+/// If the configuration has journals (see [`wasi_config_add_journal`]),
+/// the program is restored from them, and resumes from its latest
+/// snapshot when its start function is called. This must happen on
+/// the thread calling the start function.
+///
+/// It take ownership over the `wasi_config_t`.
+#[no_mangle]
+pub unsafe extern "C" fn wasi_env_instantiate(
+    store: Option<&mut wasm_store_t>,
+    config: Box<wasi_config_t>,
+    module: Option<&wasm_module_t>,
+    instance: &mut *mut wasm_instance_t,
+) -> Option<Box<wasi_env_t>> {
+    let store = &mut store?.inner;
+    let module = module?;
+    let mut store_mut = c_try!(store.store_mut());
+
+    let (builder, handle, stdin) = prepare_builder(*config, store_mut.engine());
     let _guard = handle.enter();
-    let mut rt = PluggableRuntime::new(Arc::new(TokioTaskManager::new(runtime)));
-    rt.set_engine(Some(store_mut.engine().clone()));
 
-    if !config.inherit_stdout {
-        config.builder.set_stdout(Box::new(Pipe::channel().0));
-    }
+    let (new_instance, env) = c_try!(builder.instantiate(module.inner.clone(), &mut store_mut));
 
-    if !config.inherit_stderr {
-        config.builder.set_stderr(Box::new(Pipe::channel().0));
+    let rewind_state = c_try!(env.bootstrap(&mut store_mut));
+    if rewind_state.is_some() {
+        let mut ctx = env.env.clone().into_mut(&mut store_mut);
+        c_try!(rewind_ext2(&mut ctx, rewind_state)
+            .map_err(|exit_code| format!("failed to resume the program: {}", exit_code)));
     }
 
-    // TODO: impl capturer for stdin
-
-    let env = c_try!(config
-        .builder
-        .runtime(Arc::new(rt))
-        .finalize(&mut store_mut));
+    *instance = Box::into_raw(Box::new(wasm_instance_t {
+        store: store.clone(),
+        inner: new_instance,
+    }));
 
     Some(Box::new(wasi_env_t {
         inner: env,
         store: store.clone(),
+        stdin,
     }))
 }
 
@@ -375,8 +591,8 @@ pub unsafe extern "C" fn wasi_env_new(
 #[no_mangle]
 pub extern "C" fn wasi_env_delete(state: Option<Box<wasi_env_t>>) {
     if let Some(mut env) = state {
-        env.inner
-            .on_exit(unsafe { &mut env.store.store_mut() }, None);
+        let mut store_mut = c_try!(unsafe { env.store.store_mut() }; otherwise ());
+        env.inner.on_exit(&mut store_mut, None);
     }
 }
 
@@ -397,7 +613,7 @@ pub unsafe extern "C" fn wasi_env_read_stdout(
     buffer_len: usize,
 ) -> isize {
     let inner_buffer = slice::from_raw_parts_mut(buffer as *mut _, buffer_len);
-    let store = env.store.store();
+    let store = c_try!(env.store.store(); otherwise -1);
 
     let stdout = {
         let data = env.inner.data(&store);
@@ -424,7 +640,7 @@ pub unsafe extern "C" fn wasi_env_read_stderr(
     buffer_len: usize,
 ) -> isize {
     let inner_buffer = slice::from_raw_parts_mut(buffer as *mut _, buffer_len);
-    let store = env.store.store();
+    let store = c_try!(env.store.store(); otherwise -1);
     let stderr = {
         let data = env.inner.data(&store);
         data.stderr()
@@ -442,6 +658,49 @@ pub unsafe extern "C" fn wasi_env_read_stderr(
     }
 }
 
+/// Write `buffer_len` bytes from `buffer` into the captured `stdin`
+/// of the program, see [`wasi_config_capture_stdin`].
+///
+/// Returns the number of bytes written, or `-1` on error.
+#[no_mangle]
+pub unsafe extern "C" fn wasi_env_write_stdin(
+    env: &mut wasi_env_t,
+    buffer: *const c_char,
+    buffer_len: usize,
+) -> isize {
+    let Some(stdin) = env.stdin.as_mut() else {
+        update_last_error("`stdin` is not captured");
+        return -1;
+    };
+
+    let inner_buffer = slice::from_raw_parts(buffer as *const u8, buffer_len);
+
+    match stdin.write_all(inner_buffer) {
+        Ok(()) => buffer_len as isize,
+        Err(err) => {
+            update_last_error(format!("failed to write to `stdin`: {}", err));
+            -1
+        }
+    }
+}
+
+/// Close the captured `stdin` of the program, see
+/// [`wasi_config_capture_stdin`]. The program reads end-of-file once
+/// it has consumed everything written before.
+#[no_mangle]
+pub extern "C" fn wasi_env_close_stdin(env: &mut wasi_env_t) -> bool {
+    match env.stdin.as_ref() {
+        Some(stdin) => {
+            stdin.close();
+            true
+        }
+        None => {
+            update_last_error("`stdin` is not captured");
+            false
+        }
+    }
+}
+
 fn read_inner(
     wasi_file: &mut Box<dyn VirtualFile + Send + Sync + 'static>,
     inner_buffer: &mut [u8],
@@ -544,7 +803,7 @@ unsafe fn wasi_get_imports_inner(
 ) -> Option<()> {
     let wasi_env = wasi_env?;
     let store = &mut wasi_env.store;
-    let mut store_mut = store.store_mut();
+    let mut store_mut = c_try!(store.store_mut());
     let module = module?;
 
     let import_object = c_try!(wasi_env.inner.import_object(&mut store_mut, &module.inner));
@@ -586,9 +845,10 @@ pub unsafe extern "C" fn wasi_env_initialize_instance(
     store: &mut wasm_store_t,
     instance: &mut wasm_instance_t,
 ) -> bool {
+    let mut store_mut = c_try!(store.inner.store_mut(); otherwise false);
     wasi_env
         .inner
-        .initialize(&mut store.inner.store_mut(), instance.inner.clone())
+        .initialize(&mut store_mut, instance.inner.clone())
         .unwrap();
     true
 }
@@ -679,6 +939,271 @@ mod tests {
         .success();
     }
 
+    #[cfg_attr(coverage, ignore)]
+    #[test]
+    fn test_wasi_capture_stdin() {
+        (assert_c! {
+            #include "tests/wasmer.h"
+
+            static char output[64];
+            static size_t output_len = 0;
+
+            intptr_t on_stdout(void* user_data, const char* buffer, uintptr_t buffer_len) {
+                assert(user_data == &output_len);
+                memcpy(output + output_len, buffer, buffer_len);
+                output_len += buffer_len;
+                return buffer_len;
+            }
+
+            int main() {
+                wasm_engine_t* engine = wasm_engine_new();
+                wasm_store_t* store = wasm_store_new(engine);
+
+                // Copy at most 64 bytes from `stdin` to `stdout`.
+                wasm_byte_vec_t wat;
+                wasmer_byte_vec_new_from_string(
+                    &wat,
+                    "(module\n"
+                    "  (import \"wasi_snapshot_preview1\" \"fd_read\" (func $fd_read (param i32 i32 i32 i32) (result i32)))\n"
+                    "  (import \"wasi_snapshot_preview1\" \"fd_write\" (func $fd_write (param i32 i32 i32 i32) (result i32)))\n"
+                    "  (memory (export \"memory\") 1)\n"
+                    "  (func (export \"_start\")\n"
+                    "    (i32.store (i32.const 0) (i32.const 16))\n"
+                    "    (i32.store (i32.const 4) (i32.const 64))\n"
+                    "    (drop (call $fd_read (i32.const 0) (i32.const 0) (i32.const 1) (i32.const 8)))\n"
+                    "    (i32.store (i32.const 4) (i32.load (i32.const 8)))\n"
+                    "    (drop (call $fd_write (i32.const 1) (i32.const 0) (i32.const 1) (i32.const 8)))))"
+                );
+                wasm_byte_vec_t wasm;
+                wat2wasm(&wat, &wasm);
+
+                wasm_module_t* module = wasm_module_new(store, &wasm);
+                assert(module);
+
+                wasi_config_t* config = wasi_config_new("example_program");
+                wasi_config_capture_stdin(config);
+                wasi_config_set_stdout_callback(config, on_stdout, &output_len);
+
+                wasi_env_t* wasi_env = wasi_env_new(store, config);
+                assert(wasi_env);
+
+                assert(wasi_env_write_stdin(wasi_env, "Hello, World!", 13) == 13);
+                assert(wasi_env_close_stdin(wasi_env));
+
+                wasm_extern_vec_t imports;
+                assert(wasi_get_imports(store, wasi_env, module, &imports));
+
+                wasm_instance_t* instance = wasm_instance_new(store, module, &imports, NULL);
+                assert(instance);
+                assert(wasi_env_initialize_instance(wasi_env, store, instance));
+
+                wasm_func_t* start = wasi_get_start_function(instance);
+                assert(start);
+
+                wasm_val_vec_t args = WASM_EMPTY_VEC;
+                wasm_val_vec_t results = WASM_EMPTY_VEC;
+                assert(wasm_func_call(start, &args, &results) == NULL);
+
+                assert(output_len == 13);
+                assert(strncmp(output, "Hello, World!", output_len) == 0);
+
+                wasm_func_delete(start);
+                wasm_instance_delete(instance);
+                wasm_extern_vec_delete(&imports);
+                wasi_env_delete(wasi_env);
+                wasm_module_delete(module);
+                wasm_byte_vec_delete(&wasm);
+                wasm_byte_vec_delete(&wat);
+                wasm_store_delete(store);
+                wasm_engine_delete(engine);
+
+                return 0;
+            }
+        })
+        .success();
+    }
+
+    #[cfg_attr(coverage, ignore)]
+    #[test]
+    fn test_wasi_stdio_callbacks() {
+        (assert_c! {
+            #include "tests/wasmer.h"
+
+            static char output[64];
+            static size_t output_len = 0;
+
+            intptr_t on_stdin(void* user_data, char* buffer, uintptr_t buffer_len) {
+                const char* input = (const char*) user_data;
+                size_t input_len = strlen(input);
+                assert(buffer_len >= input_len);
+                memcpy(buffer, input, input_len);
+                return input_len;
+            }
+
+            intptr_t on_stderr(void* user_data, const char* buffer, uintptr_t buffer_len) {
+                (void) user_data;
+                memcpy(output + output_len, buffer, buffer_len);
+                output_len += buffer_len;
+                return buffer_len;
+            }
+
+            int main() {
+                wasm_engine_t* engine = wasm_engine_new();
+                wasm_store_t* store = wasm_store_new(engine);
+
+                // Copy at most 64 bytes from `stdin` to `stderr`.
+                wasm_byte_vec_t wat;
+                wasmer_byte_vec_new_from_string(
+                    &wat,
+                    "(module\n"
+                    "  (import \"wasi_snapshot_preview1\" \"fd_read\" (func $fd_read (param i32 i32 i32 i32) (result i32)))\n"
+                    "  (import \"wasi_snapshot_preview1\" \"fd_write\" (func $fd_write (param i32 i32 i32 i32) (result i32)))\n"
+                    "  (memory (export \"memory\") 1)\n"
+                    "  (func (export \"_start\")\n"
+                    "    (i32.store (i32.const 0) (i32.const 16))\n"
+                    "    (i32.store (i32.const 4) (i32.const 64))\n"
+                    "    (drop (call $fd_read (i32.const 0) (i32.const 0) (i32.const 1) (i32.const 8)))\n"
+                    "    (i32.store (i32.const 4) (i32.load (i32.const 8)))\n"
+                    "    (drop (call $fd_write (i32.const 2) (i32.const 0) (i32.const 1) (i32.const 8)))))"
+                );
+                wasm_byte_vec_t wasm;
+                wat2wasm(&wat, &wasm);
+
+                wasm_module_t* module = wasm_module_new(store, &wasm);
+                assert(module);
+
+                wasi_config_t* config = wasi_config_new("example_program");
+                wasi_config_set_stdin_callback(config, on_stdin, "Hello, World!");
+                wasi_config_set_stderr_callback(config, on_stderr, NULL);
+
+                wasi_env_t* wasi_env = wasi_env_new(store, config);
+                assert(wasi_env);
+
+                // `stdin` is not captured.
+                assert(wasi_env_write_stdin(wasi_env, "nope", 4) == -1);
+
+                wasm_extern_vec_t imports;
+                assert(wasi_get_imports(store, wasi_env, module, &imports));
+
+                wasm_instance_t* instance = wasm_instance_new(store, module, &imports, NULL);
+                assert(instance);
+                assert(wasi_env_initialize_instance(wasi_env, store, instance));
+
+                wasm_func_t* start = wasi_get_start_function(instance);
+                assert(start);
+
+                wasm_val_vec_t args = WASM_EMPTY_VEC;
+                wasm_val_vec_t results = WASM_EMPTY_VEC;
+                assert(wasm_func_call(start, &args, &results) == NULL);
+
+                assert(output_len == 13);
+                assert(strncmp(output, "Hello, World!", output_len) == 0);
+
+                wasm_func_delete(start);
+                wasm_instance_delete(instance);
+                wasm_extern_vec_delete(&imports);
+                wasi_env_delete(wasi_env);
+                wasm_module_delete(module);
+                wasm_byte_vec_delete(&wasm);
+                wasm_byte_vec_delete(&wat);
+                wasm_store_delete(store);
+                wasm_engine_delete(engine);
+
+                return 0;
+            }
+        })
+        .success();
+    }
+
+    #[cfg_attr(coverage, ignore)]
+    #[test]
+    fn test_wasi_env_instantiate_threads() {
+        (assert_c! {
+            #include "tests/wasmer.h"
+
+            int main() {
+                wasm_engine_t* engine = wasm_engine_new();
+                wasm_store_t* store = wasm_store_new(engine);
+
+                // Spawn two threads, keeping the first one alive until
+                // the second spawn returned, and wait for the first one
+                // to write `42` at address 0.
+                wasm_byte_vec_t wat;
+                wasmer_byte_vec_new_from_string(
+                    &wat,
+                    "(module\n"
+                    "  (import \"wasi\" \"thread-spawn\" (func $thread_spawn (param i32) (result i32)))\n"
+                    "  (import \"env\" \"memory\" (memory 2 2 shared))\n"
+                    "  (export \"memory\" (memory 0))\n"
+                    "  (func (export \"wasi_thread_start\") (param i32 i32)\n"
+                    "    (block $done (loop $wait\n"
+                    "      (br_if $done (i32.atomic.load (i32.const 4)))\n"
+                    "      (drop (memory.atomic.wait32 (i32.const 4) (i32.const 0) (i64.const -1)))\n"
+                    "      (br $wait)))\n"
+                    "    (i32.atomic.store (i32.const 0) (i32.const 42))\n"
+                    "    (drop (memory.atomic.notify (i32.const 0) (i32.const 1))))\n"
+                    "  (func (export \"_start\")\n"
+                    "    (i32.store (i32.const 64) (i32.const 131072))\n"
+                    "    (i32.store (i32.const 120) (i32.const 4096))\n"
+                    "    (i32.store (i32.const 8) (call $thread_spawn (i32.const 64)))\n"
+                    "    (i32.store (i32.const 12) (call $thread_spawn (i32.const 64)))\n"
+                    "    (i32.atomic.store (i32.const 4) (i32.const 1))\n"
+                    "    (drop (memory.atomic.notify (i32.const 4) (i32.const 1)))\n"
+                    "    (block $done (loop $wait\n"
+                    "      (br_if $done (i32.atomic.load (i32.const 0)))\n"
+                    "      (drop (memory.atomic.wait32 (i32.const 0) (i32.const 0) (i64.const -1)))\n"
+                    "      (br $wait)))))"
+                );
+                wasm_byte_vec_t wasm;
+                wat2wasm(&wat, &wasm);
+
+                wasm_module_t* module = wasm_module_new(store, &wasm);
+                assert(module);
+
+                wasi_config_t* config = wasi_config_new("example_program");
+                wasi_config_set_networking(config, WASI_NETWORKING_NONE);
+                wasi_config_set_max_threads(config, 1);
+
+                wasm_instance_t* instance = NULL;
+                wasi_env_t* wasi_env = wasi_env_instantiate(store, config, module, &instance);
+                assert(wasi_env);
+                assert(instance);
+
+                wasm_func_t* start = wasi_get_start_function(instance);
+                assert(start);
+
+                wasm_val_vec_t args = WASM_EMPTY_VEC;
+                wasm_val_vec_t results = WASM_EMPTY_VEC;
+                assert(wasm_func_call(start, &args, &results) == NULL);
+
+                wasm_extern_vec_t exports;
+                wasm_instance_exports(instance, &exports);
+                wasm_memory_t* memory = wasm_extern_as_memory(exports.data[0]);
+                assert(memory);
+
+                int32_t* data = (int32_t*) wasm_memory_data(memory);
+                // The first thread ran…
+                assert(data[0] == 42);
+                assert(data[2] > 0);
+                // … but the second one exceeded the limit.
+                assert(data[3] < 0);
+
+                wasm_extern_vec_delete(&exports);
+                wasm_func_delete(start);
+                wasm_instance_delete(instance);
+                wasi_env_delete(wasi_env);
+                wasm_module_delete(module);
+                wasm_byte_vec_delete(&wasm);
+                wasm_byte_vec_delete(&wat);
+                wasm_store_delete(store);
+                wasm_engine_delete(engine);
+
+                return 0;
+            }
+        })
+        .success();
+    }
+
     #[cfg_attr(coverage, ignore)]
     #[test]
     fn test_wasi_get_wasi_version_invalid() {
@@ -64,8 +64,11 @@ impl SubsetTunables {
         // tunables make it static.
         //
         // If the module doesn't declare an explicit maximum treat it as 4GiB.
+        //
+        // Guard pages can't cover the range of 64-bit addresses, so 64-bit
+        // memories are always dynamic and get explicit bounds checks.
         let maximum = memory.maximum.unwrap_or_else(Pages::max_value);
-        if maximum <= self.static_memory_bound {
+        if maximum <= self.static_memory_bound && !memory.memory64 {
             MemoryStyle::Static {
                 // Bound can be larger than the maximum for performance reasons
                 bound: self.static_memory_bound,
@@ -235,6 +238,7 @@ impl CompilerOptions {
                             Type::F64 => "F".to_string(),
                             Type::V128 => "v".to_string(),
                             Type::ExternRef => "e".to_string(),
+                            Type::ExceptionRef => "x".to_string(),
                             Type::FuncRef => "r".to_string(),
                         })
                         .collect::<Vec<_>>()
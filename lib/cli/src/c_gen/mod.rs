@@ -3,6 +3,7 @@
 
 #![allow(dead_code)]
 
+pub mod shared_lib_header;
 pub mod staticlib_header;
 
 /// An identifier in C.
@@ -0,0 +1,52 @@
+//! Generate a header file for the shared library produced by
+//! `wasmer create-exe --shared`.
+
+/// Generate the header file declaring the entry points of a shared library
+/// holding the modules called `module_names`.
+///
+/// The entry points don't depend on the modules, so that a host can load
+/// any of these libraries with `dlopen` and look them up with `dlsym`.
+pub fn generate_header_file(module_names: &[String]) -> String {
+    let module_names = module_names
+        .iter()
+        .map(|name| format!("//   - \"{name}\""))
+        .collect::<Vec<_>>()
+        .join("\n");
+
+    format!(
+        r#"#ifndef WASMER_SHARED_MODULE_H
+#define WASMER_SHARED_MODULE_H
+
+#include "wasmer.h"
+
+#ifdef __cplusplus
+extern "C" {{
+#endif
+
+// The modules of the library:
+//
+{module_names}
+
+// Creates the module called `name` in `store`, or the first module of the
+// library when `name` is NULL. Returns NULL if there is no such module.
+//
+// The library must be loaded in a process using the same `libwasmer` as the
+// one it was linked against.
+wasm_module_t *wasmer_shared_module_new(wasm_store_t *store, const char *name);
+
+// Returns the names of the modules of the library, terminated by NULL.
+const char *const *wasmer_shared_module_names(void);
+
+// The types of the entry points, to look them up with `dlsym`.
+typedef wasm_module_t *(*wasmer_shared_module_new_t)(wasm_store_t *store,
+                                                     const char *name);
+typedef const char *const *(*wasmer_shared_module_names_t)(void);
+
+#ifdef __cplusplus
+}}
+#endif
+
+#endif // WASMER_SHARED_MODULE_H
+"#
+    )
+}
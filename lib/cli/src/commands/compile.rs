@@ -3,6 +3,7 @@ use std::path::PathBuf;
 use anyhow::{Context, Result};
 use clap::Parser;
 use wasmer::*;
+use wasmer_types::Aarch64Architecture;
 
 use crate::{common::HashAlgorithm, store::StoreOptions, warning};
 
@@ -13,7 +14,7 @@ pub struct Compile {
     #[clap(name = "FILE")]
     path: PathBuf,
 
-    /// Output file
+    /// Output file, or output directory with `--target-matrix`
     #[clap(name = "OUTPUT PATH", short = 'o')]
     output: PathBuf,
 
@@ -27,9 +28,32 @@ pub struct Compile {
     #[clap(short = 'm')]
     cpu_features: Vec<CpuFeature>,
 
+    /// Compile the module for a set of common CPUs (x86_64 baseline,
+    /// x86_64-v3 and aarch64) of the operating system of `--target`, into
+    /// one file per CPU in the output directory.
+    ///
+    /// `Module::deserialize_best` loads the best of them for the host.
+    #[cfg_attr(
+        feature = "signing",
+        clap(long, conflicts_with_all = &["cpu_features", "sign"])
+    )]
+    #[cfg_attr(
+        not(feature = "signing"),
+        clap(long, conflicts_with_all = &["cpu_features"])
+    )]
+    target_matrix: bool,
+
     /// Hashing algorithm to be used for module hash
     #[clap(long, value_enum)]
     hash_algorithm: Option<HashAlgorithm>,
+
+    /// Sign the compiled module with this ed25519 private key, in PKCS#8
+    /// DER form (e.g. from `openssl genpkey -algorithm ed25519 -outform DER`).
+    ///
+    /// Signed modules can be run with `wasmer run --require-signature`.
+    #[cfg(feature = "signing")]
+    #[clap(long, value_name = "KEY_FILE")]
+    sign: Option<PathBuf>,
 }
 
 impl Compile {
@@ -40,6 +64,10 @@ impl Compile {
     }
 
     fn inner_execute(&self) -> Result<()> {
+        if self.target_matrix {
+            return self.compile_target_matrix();
+        }
+
         let target = self
             .target_triple
             .as_ref()
@@ -84,6 +112,20 @@ impl Compile {
         println!("Target: {}", target.triple());
 
         let module = Module::from_file(&store, &self.path)?;
+        #[cfg(feature = "signing")]
+        match &self.sign {
+            Some(key_path) => {
+                let key = std::fs::read(key_path).with_context(|| {
+                    format!("Unable to read the signing key \"{}\"", key_path.display())
+                })?;
+                let signer = sys::ArtifactSigner::from_pkcs8(&key)?;
+                let signed = module.serialize_signed(&store, &signer)?;
+                std::fs::write(&self.output, signed)?;
+                println!("Public key: {}", hex::encode(signer.public_key()));
+            }
+            None => module.serialize_to_file(&self.output)?,
+        }
+        #[cfg(not(feature = "signing"))]
         module.serialize_to_file(&self.output)?;
         eprintln!(
             "✔ File compiled successfully to `{}`.",
@@ -92,4 +134,89 @@ impl Compile {
 
         Ok(())
     }
+
+    fn compile_target_matrix(&self) -> Result<()> {
+        let base = self.target_triple.clone().unwrap_or_else(Triple::host);
+        let (store, compiler_type) = self.store.get_store()?;
+        let mut engine = store.engine().clone();
+        let hash_algorithm = self.hash_algorithm.unwrap_or_default().into();
+        engine.set_hash_algorithm(Some(hash_algorithm));
+
+        let wasm = std::fs::read(&self.path)?;
+        std::fs::create_dir_all(&self.output).with_context(|| {
+            format!(
+                "Unable to create the directory \"{}\"",
+                self.output.display()
+            )
+        })?;
+        let stem = self
+            .path
+            .file_stem()
+            .map(|stem| stem.to_string_lossy().to_string())
+            .unwrap_or_else(|| "module".to_string());
+
+        println!("Compiler: {}", compiler_type.to_string());
+        for (name, architecture, cpu_features) in TARGET_MATRIX {
+            let mut triple = base.clone();
+            triple.architecture = *architecture;
+            let features = cpu_features
+                .iter()
+                .fold(CpuFeature::set(), |set, feature| set | *feature);
+            let target = Target::new(triple, features);
+
+            let bytes = match Module::precompile_for(&engine, &wasm, &target) {
+                Ok(bytes) => bytes,
+                // Not every compiler supports every CPU of the matrix.
+                Err(CompileError::UnsupportedTarget(reason)) => {
+                    warning!("skipping {name}: the target {reason} is not supported");
+                    continue;
+                }
+                Err(e) => {
+                    return Err(e).with_context(|| format!("failed to compile for {name}"));
+                }
+            };
+            let output = self.output.join(format!("{stem}.{name}.wasmu"));
+            std::fs::write(&output, bytes)?;
+            println!(
+                "Target: {} ({name}) → `{}`",
+                target.triple(),
+                output.display()
+            );
+        }
+        eprintln!(
+            "✔ File compiled successfully to `{}`.",
+            self.output.display(),
+        );
+
+        Ok(())
+    }
 }
+
+/// The CPUs `wasmer compile --target-matrix` compiles for: a name, used in
+/// the file names, the architecture and the CPU features.
+const TARGET_MATRIX: &[(&str, Architecture, &[CpuFeature])] = &[
+    ("x86_64", Architecture::X86_64, &[CpuFeature::SSE2]),
+    (
+        "x86_64-v3",
+        Architecture::X86_64,
+        &[
+            CpuFeature::SSE2,
+            CpuFeature::SSE3,
+            CpuFeature::SSSE3,
+            CpuFeature::SSE41,
+            CpuFeature::SSE42,
+            CpuFeature::POPCNT,
+            CpuFeature::AVX,
+            CpuFeature::AVX2,
+            CpuFeature::BMI1,
+            CpuFeature::BMI2,
+            CpuFeature::FMA,
+            CpuFeature::LZCNT,
+        ],
+    ),
+    (
+        "aarch64",
+        Architecture::Aarch64(Aarch64Architecture::Aarch64),
+        &[CpuFeature::NEON],
+    ),
+];
@@ -98,6 +98,14 @@ pub struct CreateExe {
     /// Hashing algorithm to be used for module hash
     #[clap(long, value_enum)]
     hash_algorithm: Option<HashAlgorithm>,
+
+    /// Emit a position-independent shared library instead of an executable,
+    /// for applications loading the module with `dlopen`.
+    ///
+    /// The library is linked against the shared libwasmer, and exports the
+    /// entry points declared by `wasmer gen-c-header --shared`.
+    #[clap(long)]
+    shared: bool,
 }
 
 /// Url or version to download the release from
@@ -221,7 +229,11 @@ impl CreateExe {
             return Err(anyhow::anyhow!("input path cannot be a directory"));
         }
 
-        let (store, compiler_type) = self.compiler.get_store_for_target(target.clone())?;
+        let mut compiler = self.compiler.clone();
+        if self.shared {
+            compiler.enable_pic();
+        }
+        let (store, compiler_type) = compiler.get_store_for_target(target.clone())?;
 
         let mut engine = store.engine().clone();
         let hash_algorithm = self.hash_algorithm.unwrap_or_default().into();
@@ -250,7 +262,7 @@ impl CreateExe {
             compile_pirita_into_directory(
                 &pirita,
                 &tempdir,
-                &self.compiler,
+                &compiler,
                 &self.cpu_features,
                 &cross_compilation.target,
                 &self.precompiled_atom,
@@ -262,7 +274,7 @@ impl CreateExe {
             prepare_directory_from_single_wasm_file(
                 &input_path,
                 &tempdir,
-                &self.compiler,
+                &compiler,
                 &cross_compilation.target,
                 &self.cpu_features,
                 &self.precompiled_atom,
@@ -273,6 +285,23 @@ impl CreateExe {
         get_module_infos(&store, &tempdir, &atoms)?;
         let mut entrypoint = get_entrypoint(&tempdir)?;
         create_header_files_in_dir(&tempdir, &mut entrypoint, &atoms, &self.precompiled_atom)?;
+
+        if self.shared {
+            link_shared_library_from_dir(
+                &tempdir,
+                &output_path,
+                &cross_compilation,
+                &self.libraries,
+                &atoms,
+                &self.precompiled_atom,
+            )?;
+            eprintln!(
+                "✔ Shared library compiled successfully to `{}`.",
+                self.output.display(),
+            );
+            return Ok(());
+        }
+
         link_exe_from_dir(
             &tempdir,
             output_path,
@@ -1369,6 +1398,101 @@ fn link_exe_from_dir(
     Ok(())
 }
 
+/// Given a directory, links the objects of the atoms into a shared library
+/// exporting the entry points of `wasmer_create_shared_lib.c`.
+///
+/// Unlike executables, shared libraries are linked against the shared
+/// libwasmer with the system linker, so that they use the same runtime as
+/// the application loading them.
+fn link_shared_library_from_dir(
+    directory: &Path,
+    output_path: &Path,
+    cross_compilation: &CrossCompileSetup,
+    additional_libraries: &[String],
+    atoms: &[(String, Vec<u8>)],
+    prefixes: &[String],
+) -> anyhow::Result<()> {
+    let target = &cross_compilation.target;
+    if target.operating_system == OperatingSystem::Windows {
+        bail!("Shared libraries can't be created for Windows targets yet");
+    }
+
+    let entrypoint = get_entrypoint(directory)
+        .with_context(|| anyhow::anyhow!("link shared library from dir"))?;
+
+    let prefixes = PrefixMapCompilation::from_input(atoms, prefixes, false)
+        .with_context(|| anyhow::anyhow!("link_shared_library_from_dir"))?;
+
+    let wasmer_shared_c = generate_wasmer_shared_c(&entrypoint, &prefixes).map_err(|e| {
+        anyhow::anyhow!(
+            "could not generate wasmer_shared.c in dir {}: {e}",
+            directory.display()
+        )
+    })?;
+    let wasmer_shared_c_path = directory.join("wasmer_shared.c");
+    std::fs::write(&wasmer_shared_c_path, wasmer_shared_c.as_bytes()).map_err(|e| {
+        anyhow::anyhow!(
+            "could not write wasmer_shared.c in dir {}: {e}",
+            directory.display()
+        )
+    })?;
+
+    let libwasmer_path = cross_compilation
+        .library
+        .canonicalize()
+        .context("Failed to find libwasmer")?;
+    let library_dir = libwasmer_path
+        .parent()
+        .context("Failed to find the directory of libwasmer")?;
+    let include_path = library_dir.with_file_name("include");
+    if !include_path.exists() {
+        // Can happen when we got the wrong library_path
+        return Err(anyhow::anyhow!("Wasmer include path {} does not exist, maybe library path {} is wrong (expected /lib/libwasmer.a)?", include_path.display(), libwasmer_path.display()));
+    }
+
+    let mut command = Command::new("cc");
+    command
+        .arg("-Wall")
+        .arg("-O2")
+        .arg("-shared")
+        .arg("-fPIC")
+        // Only the entry points are exported.
+        .arg("-fvisibility=hidden");
+    if *target != Triple::host() {
+        command.arg("-target").arg(format!("{}", target));
+    }
+    for atom in &entrypoint.atoms {
+        command.arg(directory.join(&atom.path).canonicalize()?);
+        if let Some(header) = &atom.header {
+            if let Some(include_dir) = directory.join(header).parent() {
+                command.arg("-I").arg(include_dir);
+            }
+        }
+    }
+    command
+        .arg(&wasmer_shared_c_path)
+        .arg("-I")
+        .arg(&include_path)
+        .arg("-L")
+        .arg(library_dir)
+        .arg("-lwasmer")
+        .args(additional_libraries.iter().map(|lib| format!("-l{}", lib)))
+        .arg("-o")
+        .arg(output_path);
+    log::debug!("{:?}", command);
+    let output = command.output()?;
+
+    if !output.status.success() {
+        bail!(
+            "linking failed with command line:{:#?} stdout: {}\n\nstderr: {}",
+            command,
+            String::from_utf8_lossy(&output.stdout),
+            String::from_utf8_lossy(&output.stderr),
+        );
+    }
+    Ok(())
+}
+
 /// Link compiled objects using the system linker
 #[allow(clippy::too_many_arguments)]
 fn link_objects_system_linker(
@@ -1567,6 +1691,47 @@ fn generate_wasmer_main_c(
     Ok(return_str.replace("// INSTANTIATE_MODULES", &c_code_to_instantiate))
 }
 
+/// Generate the wasmer_shared.c defining the entry points of a shared
+/// library, which create the modules of the atoms
+fn generate_wasmer_shared_c(
+    entrypoint: &Entrypoint,
+    prefixes: &PrefixMapCompilation,
+) -> Result<String, anyhow::Error> {
+    use std::fmt::Write;
+
+    const WASMER_SHARED_C_SOURCE: &str = include_str!("wasmer_create_shared_lib.c");
+
+    let mut extra_headers = Vec::new();
+    let mut c_code_to_instantiate = String::new();
+    let mut module_names = String::new();
+
+    for a in entrypoint.atoms.iter().map(|a| &a.command) {
+        let prefix = prefixes
+            .get_prefix_for_atom(&utils::normalize_atom_name(a))
+            .ok_or_else(|| {
+                anyhow::anyhow!(
+                    "cannot find prefix for atom {a} when generating wasmer_shared.c ({:#?})",
+                    prefixes
+                )
+            })?;
+
+        extra_headers.push(format!("#include \"static_defs_{prefix}.h\""));
+        write!(
+            c_code_to_instantiate,
+            "
+  if (!name || strcmp(name, \"{a}\") == 0) {{
+    return wasmer_object_module_new_{prefix}(store, \"{a}\");
+  }}"
+        )?;
+        write!(module_names, "\"{a}\", ")?;
+    }
+
+    Ok(WASMER_SHARED_C_SOURCE
+        .replace("// EXTRA_HEADERS", &extra_headers.join("\n"))
+        .replace("// INSTANTIATE_MODULES", &c_code_to_instantiate)
+        .replace("// MODULE_NAMES", &module_names))
+}
+
 #[allow(dead_code)]
 pub(super) mod utils {
 
@@ -53,6 +53,11 @@ pub struct CreateObj {
     #[clap(long, short = 'm', number_of_values = 1)]
     cpu_features: Vec<CpuFeature>,
 
+    /// Emit position-independent code, so that the object can be linked
+    /// into a shared library
+    #[clap(long)]
+    pic: bool,
+
     #[clap(flatten)]
     compiler: CompilerOptions,
 }
@@ -79,7 +84,11 @@ impl CreateObj {
             &target_triple,
             &self.cpu_features,
         );
-        let (_, compiler_type) = self.compiler.get_store_for_target(target.clone())?;
+        let mut compiler = self.compiler.clone();
+        if self.pic {
+            compiler.enable_pic();
+        }
+        let (_, compiler_type) = compiler.get_store_for_target(target.clone())?;
         println!("Compiler: {}", compiler_type.to_string());
         println!("Target: {}", target.triple());
 
@@ -87,7 +96,7 @@ impl CreateObj {
             crate::commands::create_exe::compile_pirita_into_directory(
                 &webc,
                 &output_directory_path,
-                &self.compiler,
+                &compiler,
                 &self.cpu_features,
                 &target_triple,
                 &prefix,
@@ -98,7 +107,7 @@ impl CreateObj {
             crate::commands::create_exe::prepare_directory_from_single_wasm_file(
                 &input_path,
                 &output_directory_path,
-                &self.compiler,
+                &compiler,
                 &target_triple,
                 &self.cpu_features,
                 &prefix,
@@ -47,6 +47,11 @@ pub struct GenCHeader {
 
     #[clap(long, short = 'm', number_of_values = 1)]
     cpu_features: Vec<CpuFeature>,
+
+    /// Generate the header of the shared library created by
+    /// `wasmer create-exe --shared`, instead of the header of the object
+    #[clap(long)]
+    shared: bool,
 }
 
 impl GenCHeader {
@@ -60,6 +65,10 @@ impl GenCHeader {
             None => crate::commands::PrefixMapCompilation::hash_for_bytes(&file),
         };
 
+        if self.shared {
+            return self.generate_shared_header(file);
+        }
+
         let atom = match Container::from_bytes(file.clone()) {
             Ok(webc) => self.get_atom(&webc)?,
             Err(webc::compat::ContainerError::Detect(DetectError::InvalidMagic { .. })) => {
@@ -117,6 +126,34 @@ impl GenCHeader {
         Ok(())
     }
 
+    fn generate_shared_header(&self, file: Bytes) -> Result<(), Error> {
+        let module_names = match Container::from_bytes(file) {
+            Ok(webc) => webc.atoms().into_keys().collect(),
+            Err(webc::compat::ContainerError::Detect(DetectError::InvalidMagic { .. })) => {
+                // we've probably got a WebAssembly file, which is named
+                // after the file like in `wasmer create-exe`
+                let name = self
+                    .path
+                    .file_stem()
+                    .and_then(|f| f.to_str())
+                    .unwrap_or("main");
+                vec![name.to_string()]
+            }
+            Err(other) => {
+                return Err(Error::new(other).context("Unable to parse the webc file"));
+            }
+        };
+
+        let header_file_src = crate::c_gen::shared_lib_header::generate_header_file(&module_names);
+        let output = crate::common::normalize_path(&self.output.display().to_string());
+
+        std::fs::write(&output, header_file_src)
+            .map_err(|e| anyhow::anyhow!("{e}"))
+            .with_context(|| anyhow::anyhow!("{output}"))?;
+
+        Ok(())
+    }
+
     fn get_atom(&self, pirita: &Container) -> Result<SharedBytes, Error> {
         let atoms = pirita.atoms();
         let atom_names: Vec<_> = atoms.keys().map(|s| s.as_str()).collect();
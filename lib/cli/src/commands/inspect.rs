@@ -1,9 +1,15 @@
+#[cfg(feature = "compiler")]
+use std::collections::HashMap;
+#[cfg(feature = "compiler")]
+use std::path::Path;
 use std::path::PathBuf;
 
 use anyhow::{Context, Result};
 use bytesize::ByteSize;
 use clap::Parser;
 use wasmer::*;
+#[cfg(feature = "compiler")]
+use wasmer_compiler::{ArtifactBuild, ArtifactSummary, CodeSummary, Features};
 
 use crate::store::StoreOptions;
 
@@ -14,6 +20,18 @@ pub struct Inspect {
     #[clap(name = "FILE")]
     path: PathBuf,
 
+    /// Inspect a module serialized with `wasmer compile` rather than a
+    /// WebAssembly file: how it was compiled, and the sizes of its code
+    #[cfg(feature = "compiler")]
+    #[clap(long)]
+    artifact: bool,
+
+    /// Compare the serialized module with this one, to explain how their
+    /// sizes differ
+    #[cfg(feature = "compiler")]
+    #[clap(long, value_name = "OTHER", requires = "artifact")]
+    diff: Option<PathBuf>,
+
     #[clap(flatten)]
     store: StoreOptions,
 }
@@ -26,6 +44,23 @@ impl Inspect {
     }
 
     fn inner_execute(&self) -> Result<()> {
+        #[cfg(feature = "compiler")]
+        if self.artifact {
+            let summary = summarize(&self.path)?;
+            return match &self.diff {
+                Some(other) => {
+                    let other = summarize(other)
+                        .with_context(|| format!("failed to inspect `{}`", other.display()))?;
+                    print_diff(&summary, &other);
+                    Ok(())
+                }
+                None => {
+                    print_summary(&summary);
+                    Ok(())
+                }
+            };
+        }
+
         let (store, _compiler_type) = self.store.get_store()?;
 
         let module_contents = std::fs::read(&self.path)?;
@@ -71,3 +106,161 @@ impl Inspect {
         Ok(())
     }
 }
+
+#[cfg(feature = "compiler")]
+fn summarize(path: &Path) -> Result<ArtifactSummary> {
+    let bytes = std::fs::read(path)?;
+    let artifact = ArtifactBuild::deserialize_checked(&bytes)?;
+    Ok(ArtifactSummary::new(&artifact))
+}
+
+#[cfg(feature = "compiler")]
+fn enabled_features(features: &Features) -> Vec<&'static str> {
+    features.enabled_names().collect()
+}
+
+#[cfg(feature = "compiler")]
+fn cpu_features(summary: &ArtifactSummary) -> String {
+    summary
+        .cpu_features
+        .iter()
+        .map(|feature| feature.to_string())
+        .collect::<Vec<_>>()
+        .join(", ")
+}
+
+#[cfg(feature = "compiler")]
+fn print_code(name: &str, code: &CodeSummary) {
+    println!("  {}: {} ({})", name, code.count, ByteSize(code.size as _));
+}
+
+#[cfg(feature = "compiler")]
+fn print_summary(summary: &ArtifactSummary) {
+    println!("Compiler: {}", summary.compiler);
+    println!("Target: {}", summary.triple);
+    println!("CPU features: {}", cpu_features(summary));
+    println!(
+        "Features: {}",
+        enabled_features(&summary.features).join(", ")
+    );
+    println!("Code:");
+    println!(
+        "  Functions: {} ({})",
+        summary.functions.len(),
+        ByteSize(summary.code_size() as _)
+    );
+    println!("  Relocations: {}", summary.relocations());
+    println!("  Frame info: {}", ByteSize(summary.frame_info_size() as _));
+    print_code("Call trampolines", &summary.function_call_trampolines);
+    print_code(
+        "Dynamic function trampolines",
+        &summary.dynamic_function_trampolines,
+    );
+    print_code("Custom sections", &summary.custom_sections);
+    println!(
+        "  Custom section relocations: {}",
+        summary.custom_section_relocations
+    );
+    println!("  Data: {}", ByteSize(summary.data_size as _));
+    println!("Functions:");
+    for function in &summary.functions {
+        println!(
+            "  {}: {} bytes, {} relocations, {} bytes of frame info",
+            function.display_name(),
+            function.code_size,
+            function.relocations,
+            function.frame_info_size
+        );
+    }
+}
+
+#[cfg(feature = "compiler")]
+fn print_change<T: PartialEq + std::fmt::Display>(name: &str, old: T, new: T) {
+    if old == new {
+        println!("{}: {}", name, old);
+    } else {
+        println!("{}: {} -> {}", name, old, new);
+    }
+}
+
+#[cfg(feature = "compiler")]
+fn print_size_change(name: &str, old: usize, new: usize) {
+    println!(
+        "  {}: {} -> {} ({:+})",
+        name,
+        old,
+        new,
+        new as i64 - old as i64
+    );
+}
+
+/// Prints how `new` differs from `old`, with the functions whose size
+/// changed the most first.
+#[cfg(feature = "compiler")]
+fn print_diff(old: &ArtifactSummary, new: &ArtifactSummary) {
+    print_change("Compiler", &old.compiler, &new.compiler);
+    print_change("Target", &old.triple, &new.triple);
+    print_change("CPU features", cpu_features(old), cpu_features(new));
+    print_change(
+        "Features",
+        enabled_features(&old.features).join(", "),
+        enabled_features(&new.features).join(", "),
+    );
+    println!("Sizes (bytes):");
+    print_size_change("Functions", old.code_size(), new.code_size());
+    print_size_change("Relocations", old.relocations(), new.relocations());
+    print_size_change("Frame info", old.frame_info_size(), new.frame_info_size());
+    print_size_change(
+        "Call trampolines",
+        old.function_call_trampolines.size,
+        new.function_call_trampolines.size,
+    );
+    print_size_change(
+        "Dynamic function trampolines",
+        old.dynamic_function_trampolines.size,
+        new.dynamic_function_trampolines.size,
+    );
+    print_size_change(
+        "Custom sections",
+        old.custom_sections.size,
+        new.custom_sections.size,
+    );
+    print_size_change("Data", old.data_size, new.data_size);
+
+    // Functions are matched by index, which only makes sense for two
+    // compilations of the same module.
+    let old_functions = old
+        .functions
+        .iter()
+        .map(|function| (function.index, function))
+        .collect::<HashMap<_, _>>();
+    let mut changes = new
+        .functions
+        .iter()
+        .filter_map(|function| {
+            let old_size = old_functions
+                .get(&function.index)
+                .map_or(0, |old| old.code_size);
+            let delta = function.code_size as i64 - old_size as i64;
+            (delta != 0).then_some((function, old_size, delta))
+        })
+        .collect::<Vec<_>>();
+    changes.sort_by_key(|(function, _, delta)| (-delta.abs(), function.index));
+    if old.functions.len() != new.functions.len() {
+        println!(
+            "Functions: {} -> {} (the artifacts may not be of the same module)",
+            old.functions.len(),
+            new.functions.len()
+        );
+    }
+    println!("Changed functions:");
+    for (function, old_size, delta) in changes {
+        println!(
+            "  {}: {} -> {} ({:+})",
+            function.display_name(),
+            old_size,
+            function.code_size,
+            delta
+        );
+    }
+}
@@ -25,6 +25,8 @@ mod journal;
 mod login;
 pub(crate) mod namespace;
 mod package;
+#[cfg(feature = "compiler")]
+mod preinit;
 mod run;
 mod self_update;
 pub mod ssh;
@@ -41,6 +43,8 @@ use clap::{CommandFactory, Parser};
 pub use compile::*;
 #[cfg(any(feature = "static-artifact-create", feature = "wasmer-artifact-create"))]
 pub use create_exe::*;
+#[cfg(feature = "compiler")]
+pub use preinit::*;
 #[cfg(feature = "wast")]
 pub use wast::*;
 #[cfg(feature = "static-artifact-create")]
@@ -126,6 +130,8 @@ impl WasmerCmd {
             Some(Cmd::Validate(validate)) => validate.execute(),
             #[cfg(feature = "compiler")]
             Some(Cmd::Compile(compile)) => compile.execute(),
+            #[cfg(feature = "compiler")]
+            Some(Cmd::PreInit(preinit)) => preinit.execute(),
             #[cfg(any(feature = "static-artifact-create", feature = "wasmer-artifact-create"))]
             Some(Cmd::CreateExe(create_exe)) => create_exe.execute(),
             #[cfg(feature = "static-artifact-create")]
@@ -251,6 +257,12 @@ enum Cmd {
     #[cfg(feature = "compiler")]
     Compile(Compile),
 
+    /// Pre-initialize a WebAssembly module by snapshotting its state after
+    /// running its initialization function
+    #[cfg(feature = "compiler")]
+    #[clap(name = "preinit")]
+    PreInit(PreInit),
+
     /// Compile a WebAssembly binary into a native executable
     ///
     /// To use, you need to set the `WASMER_DIR` environment variable
@@ -0,0 +1,155 @@
+//! The `wasmer preinit` subcommand: pre-initializes a module by running its
+//! initialization function ahead of time and snapshotting the resulting state.
+mod rewrite;
+
+use std::path::PathBuf;
+
+use anyhow::{bail, Context, Result};
+use clap::Parser;
+use wasmer::*;
+use wasmer_wasix::{is_wasi_module, WasiEnv};
+
+use self::rewrite::{Snapshot, SnapshotOptions};
+use crate::store::StoreOptions;
+
+#[derive(Debug, Parser)]
+/// The options for the `wasmer preinit` subcommand
+pub struct PreInit {
+    /// Input file
+    #[clap(name = "FILE")]
+    path: PathBuf,
+
+    /// Output file
+    #[clap(name = "OUTPUT PATH", short = 'o')]
+    output: PathBuf,
+
+    /// The exported function that initializes the module
+    #[clap(long = "init-func", default_value = "wasmer.initialize")]
+    init_func: String,
+
+    /// Keep the initialization function exported in the output module
+    #[clap(long)]
+    keep_init_func: bool,
+
+    /// Write the contents of the first memory to a separate image file
+    /// instead of data segments.
+    ///
+    /// The output module then starts with a zeroed memory and must be
+    /// instantiated with that memory mapped from the image (for instance
+    /// with `VMOwnedMemory::new_with_image`), which is near zero-copy.
+    #[clap(long = "memory-image")]
+    memory_image: Option<PathBuf>,
+
+    #[clap(flatten)]
+    store: StoreOptions,
+}
+
+impl PreInit {
+    /// Runs logic for the `preinit` subcommand
+    pub fn execute(&self) -> Result<()> {
+        self.inner_execute().context(format!(
+            "failed to pre-initialize `{}`",
+            self.path.display()
+        ))
+    }
+
+    fn inner_execute(&self) -> Result<()> {
+        let (mut store, _compiler_type) = self.store.get_store()?;
+
+        let contents = std::fs::read(&self.path)?;
+        let wasm = wat2wasm(&contents)?;
+        // Make sure the module is valid before touching its sections.
+        let module = Module::new(&store, &wasm)?;
+        if module
+            .exports()
+            .functions()
+            .all(|f| f.name() != self.init_func)
+        {
+            bail!(
+                "the module does not export an initialization function named `{}`",
+                self.init_func
+            );
+        }
+
+        let layout = rewrite::layout(&wasm)?;
+        let instrumented = Module::new(&store, rewrite::instrument(&wasm, &layout)?)?;
+
+        // WASI modules get a minimal environment, anything taken from the host
+        // (arguments, environment variables, ...) would end up in the snapshot.
+        let runtime = tokio::runtime::Runtime::new()?;
+        let _guard = runtime.enter();
+        let (instance, reactor_initialized) = if is_wasi_module(&module) {
+            let program = self
+                .path
+                .file_stem()
+                .map(|stem| stem.to_string_lossy().to_string())
+                .unwrap_or_default();
+            let (instance, _env) =
+                WasiEnv::builder(program).instantiate(instrumented, &mut store)?;
+            let reactor = instance.exports.get_function("_initialize").is_ok();
+            (instance, reactor)
+        } else {
+            (
+                Instance::new(&mut store, &instrumented, &imports! {})?,
+                false,
+            )
+        };
+
+        instance
+            .exports
+            .get_function(&self.init_func)?
+            .call(&mut store, &[])
+            .context("the initialization function failed")?;
+
+        let mut snapshot = Snapshot::default();
+        for index in 0..layout.num_globals() {
+            let global = instance.exports.get_global(&layout.global_export(index))?;
+            snapshot.globals.push(match global.get(&mut store) {
+                value @ (Value::I32(_)
+                | Value::I64(_)
+                | Value::F32(_)
+                | Value::F64(_)
+                | Value::V128(_)) => Some(value),
+                // References can't be expressed as constants, so these globals
+                // keep their original initializer.
+                _ => None,
+            });
+        }
+        for index in 0..layout.num_memories() {
+            let memory = instance.exports.get_memory(&layout.memory_export(index))?;
+            let view = memory.view(&store);
+            snapshot
+                .memories
+                .push((view.size().0 as u64, view.copy_to_vec()?));
+        }
+
+        let mut remove_exports = Vec::new();
+        if !self.keep_init_func {
+            remove_exports.push(self.init_func.as_str());
+        }
+        // The constructors of WASI reactors ran already and must not run twice.
+        if reactor_initialized {
+            remove_exports.push("_initialize");
+        }
+
+        if let Some(path) = &self.memory_image {
+            std::fs::write(path, rewrite::memory_image(&snapshot)?)
+                .with_context(|| format!("unable to write `{}`", path.display()))?;
+        }
+
+        let options = SnapshotOptions {
+            remove_exports: &remove_exports,
+            external_memory_image: self.memory_image.is_some(),
+        };
+        let output = rewrite::write_snapshot(&wasm, &layout, &snapshot, &options)?;
+        std::fs::write(&self.output, output)
+            .with_context(|| format!("unable to write `{}`", self.output.display()))?;
+
+        eprintln!(
+            "✔ Module pre-initialized successfully to `{}`.",
+            self.output.display(),
+        );
+
+        Ok(())
+    }
+}
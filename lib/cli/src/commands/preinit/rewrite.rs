@@ -0,0 +1,574 @@
+//! Section-level rewriting of WebAssembly modules used by `wasmer preinit`.
+//!
+//! Only the sections which carry the captured state (memories, globals,
+//! exports, start and data) are re-encoded, every other section is copied
+//! verbatim from the original module.
+
+use std::{collections::BTreeMap, ops::Range};
+
+use anyhow::{bail, Context, Result};
+use wasmer::Value;
+use wasmparser::{DataKind, ExternalKind, Parser, Payload, ValType};
+
+const CUSTOM_SECTION: u8 = 0;
+const EXPORT_SECTION: u8 = 7;
+const START_SECTION: u8 = 8;
+const MEMORY_SECTION: u8 = 5;
+const GLOBAL_SECTION: u8 = 6;
+const DATA_COUNT_SECTION: u8 = 12;
+const DATA_SECTION: u8 = 11;
+
+/// Zero gaps smaller than this are folded into the surrounding data segment
+/// rather than starting a new one, as every segment has an encoding overhead.
+const MIN_SEGMENT_GAP: usize = 64;
+
+/// Prefix of the exports added to the instrumented module.
+const EXPORT_PREFIX: &str = "__wasmer_preinit";
+
+/// The layout of a module, as relevant to the snapshot.
+#[derive(Debug, Default)]
+pub(super) struct ModuleLayout {
+    num_imported_globals: u32,
+    /// Content types of the globals defined by the module.
+    globals: Vec<ValType>,
+    /// Types of the memories defined by the module.
+    memories: Vec<wasmparser::MemoryType>,
+}
+
+impl ModuleLayout {
+    /// Name of the export under which the instrumented module exposes a defined global.
+    pub fn global_export(&self, defined_index: usize) -> String {
+        format!(
+            "{EXPORT_PREFIX}_global_{}",
+            self.num_imported_globals as usize + defined_index
+        )
+    }
+
+    /// Name of the export under which the instrumented module exposes a defined memory.
+    pub fn memory_export(&self, defined_index: usize) -> String {
+        // Modules importing memories are rejected, so the defined memories
+        // are the only ones.
+        format!("{EXPORT_PREFIX}_memory_{defined_index}")
+    }
+
+    /// Number of globals defined by the module.
+    pub fn num_globals(&self) -> usize {
+        self.globals.len()
+    }
+
+    /// Number of memories defined by the module.
+    pub fn num_memories(&self) -> usize {
+        self.memories.len()
+    }
+}
+
+/// The state captured from an initialized instance.
+#[derive(Debug, Default)]
+pub(super) struct Snapshot {
+    /// Values of the defined globals, `None` for the globals that can't be
+    /// snapshotted (reference types) and keep their original initializer.
+    pub globals: Vec<Option<Value>>,
+    /// Size in pages and contents of the defined memories.
+    pub memories: Vec<(u64, Vec<u8>)>,
+}
+
+/// Options for [`write_snapshot`].
+#[derive(Debug, Default)]
+pub(super) struct SnapshotOptions<'a> {
+    /// Exports removed from the output module.
+    pub remove_exports: &'a [&'a str],
+    /// Whether the first defined memory is provided by an external memory image,
+    /// in which case no data segments are emitted for it.
+    pub external_memory_image: bool,
+}
+
+/// Reads the layout of a module.
+pub(super) fn layout(wasm: &[u8]) -> Result<ModuleLayout> {
+    let mut layout = ModuleLayout::default();
+
+    for payload in Parser::new(0).parse_all(wasm) {
+        match payload? {
+            Payload::ImportSection(reader) => {
+                for import in reader {
+                    match import?.ty {
+                        wasmparser::TypeRef::Global(_) => layout.num_imported_globals += 1,
+                        // The contents of imported memories belong to the host, they
+                        // can't be recreated by the output module.
+                        wasmparser::TypeRef::Memory(_) => {
+                            bail!("modules importing memories can't be pre-initialized")
+                        }
+                        _ => {}
+                    }
+                }
+            }
+            Payload::GlobalSection(reader) => {
+                for global in reader {
+                    layout.globals.push(global?.ty.content_type);
+                }
+            }
+            Payload::MemorySection(reader) => {
+                for memory in reader {
+                    layout.memories.push(memory?);
+                }
+            }
+            _ => {}
+        }
+    }
+
+    Ok(layout)
+}
+
+/// Adds exports for every defined global and memory so that their state can be
+/// read back once the module has been initialized.
+pub(super) fn instrument(wasm: &[u8], layout: &ModuleLayout) -> Result<Vec<u8>> {
+    let mut exports = read_exports(wasm)?;
+
+    for index in 0..layout.num_globals() {
+        let global = layout.num_imported_globals + index as u32;
+        exports.push((layout.global_export(index), ExternalKind::Global, global));
+    }
+    for index in 0..layout.num_memories() {
+        let memory = index as u32;
+        exports.push((layout.memory_export(index), ExternalKind::Memory, memory));
+    }
+
+    let mut edits = BTreeMap::new();
+    edits.insert(EXPORT_SECTION, Some(encode_exports(&exports)));
+    apply_edits(wasm, edits)
+}
+
+/// Rewrites the original module so that it starts from the captured state.
+pub(super) fn write_snapshot(
+    wasm: &[u8],
+    layout: &ModuleLayout,
+    snapshot: &Snapshot,
+    options: &SnapshotOptions<'_>,
+) -> Result<Vec<u8>> {
+    let mut edits = BTreeMap::new();
+
+    // The initialization already happened, so the start function must not run again.
+    edits.insert(START_SECTION, None);
+
+    let exports = read_exports(wasm)?
+        .into_iter()
+        .filter(|(name, _, _)| !options.remove_exports.contains(&name.as_str()))
+        .collect::<Vec<_>>();
+    edits.insert(EXPORT_SECTION, Some(encode_exports(&exports)));
+
+    if !layout.memories.is_empty() {
+        let mut section = Vec::new();
+        write_u32(&mut section, layout.memories.len() as u32);
+        for (ty, (pages, _)) in layout.memories.iter().zip(&snapshot.memories) {
+            write_memory_type(&mut section, ty, *pages);
+        }
+        edits.insert(MEMORY_SECTION, Some(section));
+    }
+
+    if !layout.globals.is_empty() {
+        edits.insert(GLOBAL_SECTION, Some(encode_globals(wasm, snapshot)?));
+    }
+
+    let (data, count) = encode_data(wasm, layout, snapshot, options)?;
+    edits.insert(DATA_SECTION, Some(data));
+    if has_section(wasm, DATA_COUNT_SECTION)? {
+        let mut section = Vec::new();
+        write_u32(&mut section, count);
+        edits.insert(DATA_COUNT_SECTION, Some(section));
+    }
+
+    apply_edits(wasm, edits)
+}
+
+/// Writes the raw contents of the first defined memory, for use as a memory
+/// image mapped with `VMOwnedMemory::new_with_image`.
+pub(super) fn memory_image(snapshot: &Snapshot) -> Result<&[u8]> {
+    let (_, contents) = snapshot
+        .memories
+        .first()
+        .context("the module does not define a memory")?;
+    // Trailing zeros don't need to be stored, the mapping is zero-filled.
+    let len = contents
+        .iter()
+        .rposition(|b| *b != 0)
+        .map(|last| last + 1)
+        .unwrap_or(0);
+    Ok(&contents[..len])
+}
+
+/// Returns the non-zero ranges of a memory, merging the ranges separated by
+/// less than [`MIN_SEGMENT_GAP`] zero bytes.
+fn non_zero_ranges(data: &[u8]) -> Vec<Range<usize>> {
+    let mut ranges: Vec<Range<usize>> = Vec::new();
+    let mut offset = 0;
+
+    while let Some(start) = data[offset..].iter().position(|b| *b != 0) {
+        let start = offset + start;
+        let end = data[start..]
+            .iter()
+            .position(|b| *b == 0)
+            .map(|len| start + len)
+            .unwrap_or(data.len());
+
+        match ranges.last_mut() {
+            Some(last) if start - last.end < MIN_SEGMENT_GAP => last.end = end,
+            _ => ranges.push(start..end),
+        }
+        offset = end;
+    }
+
+    ranges
+}
+
+fn has_section(wasm: &[u8], id: u8) -> Result<bool> {
+    for payload in Parser::new(0).parse_all(wasm) {
+        if let Some((section, _)) = payload?.as_section() {
+            if section == id {
+                return Ok(true);
+            }
+        }
+    }
+    Ok(false)
+}
+
+fn read_exports(wasm: &[u8]) -> Result<Vec<(String, ExternalKind, u32)>> {
+    let mut exports = Vec::new();
+    for payload in Parser::new(0).parse_all(wasm) {
+        if let Payload::ExportSection(reader) = payload? {
+            for export in reader {
+                let export = export?;
+                exports.push((export.name.to_string(), export.kind, export.index));
+            }
+        }
+    }
+    Ok(exports)
+}
+
+fn encode_exports(exports: &[(String, ExternalKind, u32)]) -> Vec<u8> {
+    let mut section = Vec::new();
+    write_u32(&mut section, exports.len() as u32);
+    for (name, kind, index) in exports {
+        write_name(&mut section, name);
+        section.push(match kind {
+            ExternalKind::Func => 0x00,
+            ExternalKind::Table => 0x01,
+            ExternalKind::Memory => 0x02,
+            ExternalKind::Global => 0x03,
+            ExternalKind::Tag => 0x04,
+        });
+        write_u32(&mut section, *index);
+    }
+    section
+}
+
+fn encode_globals(wasm: &[u8], snapshot: &Snapshot) -> Result<Vec<u8>> {
+    let mut section = Vec::new();
+
+    for payload in Parser::new(0).parse_all(wasm) {
+        let Payload::GlobalSection(reader) = payload? else {
+            continue;
+        };
+        write_u32(&mut section, reader.count());
+        for (index, global) in reader.into_iter_with_offsets().enumerate() {
+            let (offset, global) = global?;
+            let init_expr = global.init_expr.get_binary_reader();
+            let init_start = init_expr.original_position();
+
+            // The global type is copied verbatim, only the initializer changes.
+            section.extend_from_slice(&wasm[offset..init_start]);
+            match snapshot.globals.get(index).cloned().flatten() {
+                Some(value) => write_const_expr(&mut section, &value)?,
+                None => section.extend_from_slice(&wasm[init_start..init_expr.range().end]),
+            }
+        }
+    }
+
+    Ok(section)
+}
+
+/// Encodes the data section, returning it along with the number of segments.
+fn encode_data(
+    wasm: &[u8],
+    layout: &ModuleLayout,
+    snapshot: &Snapshot,
+    options: &SnapshotOptions<'_>,
+) -> Result<(Vec<u8>, u32)> {
+    let keep_indices = has_section(wasm, DATA_COUNT_SECTION)?;
+    let mut section = Vec::new();
+    let mut count = 0;
+
+    for payload in Parser::new(0).parse_all(wasm) {
+        let Payload::DataSection(reader) = payload? else {
+            continue;
+        };
+        for data in reader {
+            let data = data?;
+            match data.kind {
+                // Passive segments are kept as they may be used by `memory.init`.
+                DataKind::Passive => section.extend_from_slice(&wasm[data.range]),
+                // The contents of active segments are part of the snapshot. When
+                // the module uses bulk memory instructions they are replaced by
+                // empty passive segments so the segment indices stay stable.
+                DataKind::Active { .. } if keep_indices => {
+                    section.push(0x01);
+                    write_u32(&mut section, 0);
+                }
+                DataKind::Active { .. } => continue,
+            }
+            count += 1;
+        }
+    }
+
+    for (index, (ty, (_, contents))) in layout.memories.iter().zip(&snapshot.memories).enumerate() {
+        if index == 0 && options.external_memory_image {
+            continue;
+        }
+        let memory = index as u32;
+        for range in non_zero_ranges(contents) {
+            if memory == 0 {
+                section.push(0x00);
+            } else {
+                section.push(0x02);
+                write_u32(&mut section, memory);
+            }
+            if ty.memory64 {
+                section.push(0x42);
+                write_i64(&mut section, range.start as i64);
+            } else {
+                section.push(0x41);
+                write_i64(&mut section, range.start as u32 as i32 as i64);
+            }
+            section.push(0x0b);
+            write_u32(&mut section, range.len() as u32);
+            section.extend_from_slice(&contents[range]);
+            count += 1;
+        }
+    }
+
+    let mut encoded = Vec::with_capacity(section.len() + 5);
+    write_u32(&mut encoded, count);
+    encoded.extend_from_slice(&section);
+    Ok((encoded, count))
+}
+
+/// Copies the module, replacing (`Some`) or removing (`None`) the sections
+/// in `edits`. Replacements for sections that don't exist in the module are
+/// inserted at their canonical position.
+fn apply_edits(wasm: &[u8], mut edits: BTreeMap<u8, Option<Vec<u8>>>) -> Result<Vec<u8>> {
+    let mut output = Vec::with_capacity(wasm.len());
+
+    for payload in Parser::new(0).parse_all(wasm) {
+        let payload = payload?;
+        if let Payload::Version { range, .. } = &payload {
+            output.extend_from_slice(&wasm[..range.end]);
+            continue;
+        }
+        let Some((id, range)) = payload.as_section() else {
+            continue;
+        };
+
+        // Custom sections can appear anywhere and don't affect the ordering.
+        if id != CUSTOM_SECTION {
+            let rank = section_rank(id)?;
+            let mut inserted = Vec::new();
+            for pending in edits.keys().copied().filter(|pending| *pending != id) {
+                if section_rank(pending)? < rank {
+                    inserted.push(pending);
+                }
+            }
+            for pending in inserted {
+                if let Some(Some(contents)) = edits.remove(&pending) {
+                    write_section(&mut output, pending, &contents);
+                }
+            }
+        }
+
+        match edits.remove(&id) {
+            Some(Some(contents)) => write_section(&mut output, id, &contents),
+            Some(None) => {}
+            None => write_section(&mut output, id, &wasm[range]),
+        }
+    }
+
+    // Whatever is left goes after all the existing sections.
+    for (id, contents) in edits {
+        if let Some(contents) = contents {
+            write_section(&mut output, id, &contents);
+        }
+    }
+
+    Ok(output)
+}
+
+/// The position of a (non-custom) section in a module.
+fn section_rank(id: u8) -> Result<u8> {
+    Ok(match id {
+        1..=5 => id,
+        // The tag section goes between the memory and global sections.
+        13 => 6,
+        6..=9 => id + 1,
+        // The data count section goes between the element and code sections.
+        12 => 11,
+        10 | 11 => id + 2,
+        _ => bail!("unknown section id {id}"),
+    })
+}
+
+fn write_section(output: &mut Vec<u8>, id: u8, contents: &[u8]) {
+    output.push(id);
+    write_u32(output, contents.len() as u32);
+    output.extend_from_slice(contents);
+}
+
+fn write_memory_type(output: &mut Vec<u8>, ty: &wasmparser::MemoryType, pages: u64) {
+    let mut flags = 0;
+    if ty.maximum.is_some() {
+        flags |= 0x01;
+    }
+    if ty.shared {
+        flags |= 0x02;
+    }
+    if ty.memory64 {
+        flags |= 0x04;
+    }
+    output.push(flags);
+    write_u64(output, pages.max(ty.initial));
+    if let Some(maximum) = ty.maximum {
+        write_u64(output, maximum);
+    }
+}
+
+fn write_const_expr(output: &mut Vec<u8>, value: &Value) -> Result<()> {
+    match value {
+        Value::I32(v) => {
+            output.push(0x41);
+            write_i64(output, *v as i64);
+        }
+        Value::I64(v) => {
+            output.push(0x42);
+            write_i64(output, *v);
+        }
+        Value::F32(v) => {
+            output.push(0x43);
+            output.extend_from_slice(&v.to_bits().to_le_bytes());
+        }
+        Value::F64(v) => {
+            output.push(0x44);
+            output.extend_from_slice(&v.to_bits().to_le_bytes());
+        }
+        Value::V128(v) => {
+            output.extend_from_slice(&[0xfd, 0x0c]);
+            output.extend_from_slice(&v.to_le_bytes());
+        }
+        other => bail!("unable to snapshot a global of type {:?}", other.ty()),
+    }
+    output.push(0x0b);
+    Ok(())
+}
+
+fn write_name(output: &mut Vec<u8>, name: &str) {
+    write_u32(output, name.len() as u32);
+    output.extend_from_slice(name.as_bytes());
+}
+
+fn write_u32(output: &mut Vec<u8>, value: u32) {
+    write_u64(output, value as u64)
+}
+
+fn write_u64(output: &mut Vec<u8>, mut value: u64) {
+    loop {
+        let byte = (value & 0x7f) as u8;
+        value >>= 7;
+        if value == 0 {
+            output.push(byte);
+            return;
+        }
+        output.push(byte | 0x80);
+    }
+}
+
+fn write_i64(output: &mut Vec<u8>, mut value: i64) {
+    loop {
+        let byte = (value & 0x7f) as u8;
+        value >>= 7;
+        let done = (value == 0 && byte & 0x40 == 0) || (value == -1 && byte & 0x40 != 0);
+        if done {
+            output.push(byte);
+            return;
+        }
+        output.push(byte | 0x80);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn non_zero_ranges_are_merged() {
+        let mut data = vec![0u8; 512];
+        data[10] = 1;
+        data[20] = 1;
+        data[300..310].fill(2);
+        assert_eq!(non_zero_ranges(&data), vec![10..21, 300..310]);
+        assert!(non_zero_ranges(&[0; 16]).is_empty());
+    }
+
+    #[test]
+    fn leb128() {
+        let mut out = Vec::new();
+        write_u32(&mut out, 624485);
+        assert_eq!(out, [0xe5, 0x8e, 0x26]);
+
+        out.clear();
+        write_i64(&mut out, -123456);
+        assert_eq!(out, [0xc0, 0xbb, 0x78]);
+    }
+
+    #[test]
+    fn snapshot_round_trip() {
+        let wasm = wasmer::wat2wasm(
+            br#"(module
+                (global $counter (mut i32) (i32.const 0))
+                (memory 1)
+                (data (i32.const 16) "before")
+                (func (export "wasmer.initialize")
+                    (global.set $counter (i32.const 42))
+                    (i32.store8 (i32.const 1024) (i32.const 7)))
+                (func (export "counter") (result i32) (global.get $counter)))"#,
+        )
+        .unwrap();
+
+        let layout = layout(&wasm).unwrap();
+        let instrumented = instrument(&wasm, &layout).unwrap();
+        wasmparser::validate(&instrumented).unwrap();
+
+        let mut memory = vec![0; 65536];
+        memory[16..22].copy_from_slice(b"before");
+        memory[1024] = 7;
+        let snapshot = Snapshot {
+            globals: vec![Some(Value::I32(42))],
+            memories: vec![(1, memory)],
+        };
+        let options = SnapshotOptions {
+            remove_exports: &["wasmer.initialize"],
+            external_memory_image: false,
+        };
+        let output = write_snapshot(&wasm, &layout, &snapshot, &options).unwrap();
+        wasmparser::validate(&output).unwrap();
+
+        let exports = read_exports(&output).unwrap();
+        assert_eq!(exports.len(), 1);
+        assert_eq!(exports[0].0, "counter");
+    }
+
+    #[test]
+    fn imported_memories_are_rejected() {
+        let wasm = wasmer::wat2wasm(
+            br#"(module
+                (import "env" "memory" (memory 1))
+                (func (export "wasmer.initialize")))"#,
+        )
+        .unwrap();
+        assert!(layout(&wasm).is_err());
+    }
+}
@@ -0,0 +1,563 @@
+//! A GDB remote protocol server to debug guests (`wasmer run --gdb`).
+//!
+//! The module is compiled for debugging, with Singlepass or the
+//! interpreter: a [`Debugger`] attached to it is called before every
+//! instruction and stops the guest thread when needed.
+//! While stopped, that thread serves the requests of the server thread,
+//! which owns the connection to the debugger and knows nothing about the
+//! guest state by itself.
+//!
+//! Addresses follow the convention of LLDB's WebAssembly support: the code
+//! of the module is mapped at [`CODE_BASE`], so the program counter is the
+//! offset of the next instruction in the module plus [`CODE_BASE`], and
+//! lower addresses refer to the linear memory.
+
+use std::{
+    collections::HashSet,
+    fmt::Write as _,
+    net::{Ipv4Addr, TcpListener, TcpStream},
+    ops::Range,
+    sync::{
+        mpsc::{self, Receiver, RecvTimeoutError, Sender},
+        Arc, Mutex,
+    },
+    thread::JoinHandle,
+    time::Duration,
+};
+
+use anyhow::{Context, Error};
+use gdbstub::{
+    arch::{Arch, Registers, SingleStepGdbBehavior},
+    common::Signal,
+    conn::{Connection, ConnectionExt},
+    outputln,
+    stub::{
+        run_blocking::{BlockingEventLoop, Event, WaitForStopReasonError},
+        DisconnectReason, GdbStub, SingleThreadStopReason,
+    },
+    target::{
+        ext::{
+            base::{
+                singlethread::{
+                    SingleThreadBase, SingleThreadRangeStepping, SingleThreadRangeSteppingOps,
+                    SingleThreadResume, SingleThreadResumeOps, SingleThreadSingleStep,
+                    SingleThreadSingleStepOps,
+                },
+                BaseOps,
+            },
+            breakpoints::{Breakpoints, BreakpointsOps, SwBreakpoint, SwBreakpointOps},
+            monitor_cmd::{ConsoleOutput, MonitorCmd, MonitorCmdOps},
+        },
+        Target, TargetError, TargetResult,
+    },
+};
+use wasmer::Module;
+use wasmer_types::{entity::EntityRef, GlobalIndex};
+use wasmer_vm::{attach_debugger, detach_debugger, DebugFrame, Debugger};
+
+/// Where the code of the module is mapped in the debugger address space.
+const CODE_BASE: u64 = 1 << 62;
+
+/// How often the server checks for an interrupt from the debugger while the
+/// guest is running.
+const POLL_INTERVAL: Duration = Duration::from_millis(50);
+
+type StopReason = SingleThreadStopReason<u64>;
+
+/// A debugging session, from the connection of the debugger to the end of
+/// the guest.
+pub(crate) struct DebugSession {
+    guest: Arc<Guest>,
+    server: JoinHandle<()>,
+}
+
+impl DebugSession {
+    /// Waits for a debugger to connect on `port`, then attaches to the
+    /// interpreter to stop at the first instruction of `module`.
+    pub(crate) fn start(port: u16, module: &Module, wasm: Vec<u8>) -> Result<Self, Error> {
+        let listener = TcpListener::bind((Ipv4Addr::LOCALHOST, port))
+            .with_context(|| format!("Unable to listen on port {port}"))?;
+        eprintln!("Waiting for a GDB connection on localhost:{port}...");
+        let (stream, peer) = listener
+            .accept()
+            .context("Unable to accept the GDB connection")?;
+        eprintln!("Debugger connected from {peer}");
+
+        let (requests, requests_rx) = mpsc::channel();
+        let (stops, stops_rx) = mpsc::channel();
+        let guest = Arc::new(Guest {
+            module: module.clone(),
+            control: Mutex::new(Control {
+                mode: Mode::Start,
+                breakpoints: HashSet::new(),
+                interrupted: false,
+            }),
+            requests: Mutex::new(Some(requests_rx)),
+            stops,
+        });
+        attach_debugger(guest.clone());
+
+        let mut target = Server {
+            guest: guest.clone(),
+            requests,
+            stops: stops_rx,
+            wasm,
+        };
+        let server = std::thread::spawn(move || {
+            match GdbStub::new(stream).run_blocking::<EventLoop>(&mut target) {
+                Ok(DisconnectReason::Kill) => {
+                    eprintln!("Killed by the debugger");
+                    std::process::exit(1);
+                }
+                Ok(_) => {}
+                Err(error) => tracing::warn!(%error, "The GDB session failed"),
+            }
+            // Let the guest run freely once the debugger is gone. Dropping
+            // the request channel releases it if it is stopped.
+            detach_debugger();
+        });
+
+        Ok(Self { guest, server })
+    }
+
+    /// Reports the end of the guest to the debugger, and waits for it to
+    /// disconnect.
+    pub(crate) fn finish(self, exit_code: i32) {
+        // Nothing will serve requests anymore.
+        self.guest.requests.lock().unwrap().take();
+        let _ = self.guest.stops.send(StopReason::Exited(exit_code as u8));
+        let _ = self.server.join();
+    }
+}
+
+/// How the guest should run.
+#[derive(Debug, Clone)]
+enum Mode {
+    /// Stop at the first instruction, which the debugger expects already.
+    Start,
+    /// Run until a breakpoint or an interrupt.
+    Continue,
+    /// Stop at the next instruction.
+    Step,
+    /// Run while the program counter is in the range.
+    RangeStep(Range<usize>),
+}
+
+#[derive(Debug)]
+struct Control {
+    mode: Mode,
+    /// Module offsets of the breakpoints.
+    breakpoints: HashSet<usize>,
+    /// Whether the debugger asked to stop.
+    interrupted: bool,
+}
+
+/// Something for a stopped guest thread to do.
+enum Request {
+    Inspect(Box<dyn FnOnce(&mut DebugFrame<'_>) + Send>),
+    Resume,
+}
+
+/// The debugger attached to the interpreter.
+struct Guest {
+    module: Module,
+    control: Mutex<Control>,
+    /// Requests from the server, taken by the thread stopped at a time.
+    requests: Mutex<Option<Receiver<Request>>>,
+    stops: Sender<StopReason>,
+}
+
+impl Guest {
+    /// Why the guest should stop at `offset`, if it should.
+    ///
+    /// The outer `Option` tells whether to stop, the inner one whether to
+    /// report it.
+    fn should_stop(&self, offset: usize) -> Option<Option<StopReason>> {
+        let mut control = self.control.lock().unwrap();
+        let reason = match &control.mode {
+            Mode::Start => None,
+            Mode::Step => Some(StopReason::DoneStep),
+            Mode::RangeStep(range) if !range.contains(&offset) => Some(StopReason::DoneStep),
+            Mode::Continue | Mode::RangeStep(_) => {
+                if control.interrupted {
+                    Some(StopReason::Signal(Signal::SIGINT))
+                } else if control.breakpoints.contains(&offset) {
+                    Some(StopReason::SwBreak(()))
+                } else {
+                    return None;
+                }
+            }
+        };
+        control.interrupted = false;
+        Some(reason)
+    }
+}
+
+impl Debugger for Guest {
+    fn on_instruction(&self, frame: &mut DebugFrame<'_>) {
+        if frame.module().id != self.module.info().id {
+            return;
+        }
+        let reason = match self.should_stop(frame.module_offset()) {
+            Some(reason) => reason,
+            None => return,
+        };
+
+        // Only one thread can be stopped at a time, others wait here.
+        let requests = self.requests.lock().unwrap();
+        let requests = match requests.as_ref() {
+            Some(requests) => requests,
+            None => return,
+        };
+        if let Some(reason) = reason {
+            let _ = self.stops.send(reason);
+        }
+        // The channel is closed when the debugger disconnects.
+        while let Ok(Request::Inspect(inspect)) = requests.recv() {
+            inspect(frame);
+        }
+    }
+}
+
+/// The GDB target, forwarding everything to the stopped guest.
+struct Server {
+    guest: Arc<Guest>,
+    requests: Sender<Request>,
+    stops: Receiver<StopReason>,
+    /// The Wasm module, for reads in the code address space.
+    wasm: Vec<u8>,
+}
+
+impl Server {
+    /// Runs `inspect` on the frame the guest is stopped at.
+    fn inspect<R: Send + 'static>(
+        &self,
+        inspect: impl FnOnce(&mut DebugFrame<'_>) -> R + Send + 'static,
+    ) -> Option<R> {
+        let (result, result_rx) = mpsc::channel();
+        let request = Request::Inspect(Box::new(move |frame| {
+            let _ = result.send(inspect(frame));
+        }));
+        self.requests.send(request).ok()?;
+        result_rx.recv().ok()
+    }
+
+    fn resume(&mut self, mode: Mode) -> Result<(), &'static str> {
+        self.guest.control.lock().unwrap().mode = mode;
+        self.requests
+            .send(Request::Resume)
+            .map_err(|_| "the guest is gone")
+    }
+}
+
+/// The WebAssembly "architecture", whose only register is the program
+/// counter.
+enum Wasm {}
+
+impl Arch for Wasm {
+    type Usize = u64;
+    type Registers = WasmRegisters;
+    type BreakpointKind = usize;
+    type RegId = ();
+
+    fn single_step_gdb_behavior() -> SingleStepGdbBehavior {
+        SingleStepGdbBehavior::Optional
+    }
+
+    fn target_description_xml() -> Option<&'static str> {
+        Some(
+            r#"<target version="1.0"><architecture>wasm32</architecture><feature name="org.gnu.gdb.wasm.core"><reg name="pc" bitsize="64" type="code_ptr" regnum="0"/></feature></target>"#,
+        )
+    }
+}
+
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+struct WasmRegisters {
+    pc: u64,
+}
+
+impl Registers for WasmRegisters {
+    type ProgramCounter = u64;
+
+    fn pc(&self) -> u64 {
+        self.pc
+    }
+
+    fn gdb_serialize(&self, mut write_byte: impl FnMut(Option<u8>)) {
+        for byte in self.pc.to_le_bytes() {
+            write_byte(Some(byte));
+        }
+    }
+
+    fn gdb_deserialize(&mut self, bytes: &[u8]) -> Result<(), ()> {
+        let pc = bytes.get(..8).ok_or(())?;
+        self.pc = u64::from_le_bytes(pc.try_into().unwrap());
+        Ok(())
+    }
+}
+
+impl Target for Server {
+    type Arch = Wasm;
+    type Error = &'static str;
+
+    fn base_ops(&mut self) -> BaseOps<'_, Self::Arch, Self::Error> {
+        BaseOps::SingleThread(self)
+    }
+
+    fn support_breakpoints(&mut self) -> Option<BreakpointsOps<'_, Self>> {
+        Some(self)
+    }
+
+    fn support_monitor_cmd(&mut self) -> Option<MonitorCmdOps<'_, Self>> {
+        Some(self)
+    }
+}
+
+impl SingleThreadBase for Server {
+    fn read_registers(&mut self, regs: &mut WasmRegisters) -> TargetResult<(), Self> {
+        let offset = self
+            .inspect(|frame| frame.module_offset())
+            .ok_or(TargetError::NonFatal)?;
+        regs.pc = CODE_BASE + offset as u64;
+        Ok(())
+    }
+
+    fn write_registers(&mut self, _regs: &WasmRegisters) -> TargetResult<(), Self> {
+        // Jumping around isn't supported.
+        Err(TargetError::NonFatal)
+    }
+
+    fn read_addrs(&mut self, start_addr: u64, data: &mut [u8]) -> TargetResult<(), Self> {
+        if start_addr >= CODE_BASE {
+            let start = (start_addr - CODE_BASE) as usize;
+            let code = self
+                .wasm
+                .get(start..start + data.len())
+                .ok_or(TargetError::NonFatal)?;
+            data.copy_from_slice(code);
+            return Ok(());
+        }
+        let range = start_addr as usize..start_addr as usize + data.len();
+        let bytes = self
+            .inspect(move |frame| Some(frame.memory()?.get(range)?.to_vec()))
+            .flatten()
+            .ok_or(TargetError::NonFatal)?;
+        data.copy_from_slice(&bytes);
+        Ok(())
+    }
+
+    fn write_addrs(&mut self, start_addr: u64, data: &[u8]) -> TargetResult<(), Self> {
+        if start_addr >= CODE_BASE {
+            return Err(TargetError::NonFatal);
+        }
+        let range = start_addr as usize..start_addr as usize + data.len();
+        let data = data.to_vec();
+        let written = self.inspect(move |frame| match frame.memory() {
+            Some(memory) if range.end <= memory.len() => {
+                memory[range].copy_from_slice(&data);
+                true
+            }
+            _ => false,
+        });
+        match written {
+            Some(true) => Ok(()),
+            _ => Err(TargetError::NonFatal),
+        }
+    }
+
+    fn support_resume(&mut self) -> Option<SingleThreadResumeOps<'_, Self>> {
+        Some(self)
+    }
+}
+
+impl SingleThreadResume for Server {
+    fn resume(&mut self, _signal: Option<Signal>) -> Result<(), Self::Error> {
+        Server::resume(self, Mode::Continue)
+    }
+
+    fn support_single_step(&mut self) -> Option<SingleThreadSingleStepOps<'_, Self>> {
+        Some(self)
+    }
+
+    fn support_range_step(&mut self) -> Option<SingleThreadRangeSteppingOps<'_, Self>> {
+        Some(self)
+    }
+}
+
+impl SingleThreadSingleStep for Server {
+    fn step(&mut self, _signal: Option<Signal>) -> Result<(), Self::Error> {
+        self.resume(Mode::Step)
+    }
+}
+
+impl SingleThreadRangeStepping for Server {
+    fn resume_range_step(&mut self, start: u64, end: u64) -> Result<(), Self::Error> {
+        let start = start.saturating_sub(CODE_BASE) as usize;
+        let end = end.saturating_sub(CODE_BASE) as usize;
+        self.resume(Mode::RangeStep(start..end))
+    }
+}
+
+impl Breakpoints for Server {
+    fn support_sw_breakpoint(&mut self) -> Option<SwBreakpointOps<'_, Self>> {
+        Some(self)
+    }
+}
+
+impl SwBreakpoint for Server {
+    fn add_sw_breakpoint(&mut self, addr: u64, _kind: usize) -> TargetResult<bool, Self> {
+        if addr < CODE_BASE {
+            return Ok(false);
+        }
+        let mut control = self.guest.control.lock().unwrap();
+        control.breakpoints.insert((addr - CODE_BASE) as usize);
+        Ok(true)
+    }
+
+    fn remove_sw_breakpoint(&mut self, addr: u64, _kind: usize) -> TargetResult<bool, Self> {
+        let mut control = self.guest.control.lock().unwrap();
+        Ok(control
+            .breakpoints
+            .remove(&(addr.wrapping_sub(CODE_BASE) as usize)))
+    }
+}
+
+const MONITOR_HELP: &str = "\
+Commands:
+  frame                  show the function and offset the guest is stopped at
+  locals                 show the parameters and locals of the function
+  local <index> <value>  set a parameter or local
+  globals                show the globals of the instance
+  global <index> <value> set a global
+Values are shown and parsed as raw bits, in decimal or in hexadecimal with 0x.";
+
+impl MonitorCmd for Server {
+    fn handle_monitor_cmd(
+        &mut self,
+        cmd: &[u8],
+        mut out: ConsoleOutput<'_>,
+    ) -> Result<(), Self::Error> {
+        let cmd = String::from_utf8_lossy(cmd);
+        let args = cmd.split_whitespace().collect::<Vec<_>>();
+        let output = match args[..] {
+            ["frame"] => self.inspect(|frame| {
+                let index = frame.function_index();
+                let name = frame.module().function_names.get(&index).cloned();
+                format!(
+                    "function {} ({}) at offset 0x{:x}",
+                    index.index(),
+                    name.as_deref().unwrap_or("<unnamed>"),
+                    frame.module_offset()
+                )
+            }),
+            ["locals"] => self.inspect(|frame| {
+                let mut output = String::new();
+                for (index, value) in frame.locals().iter().enumerate() {
+                    let _ = writeln!(output, "local {index} = 0x{value:x}");
+                }
+                output
+            }),
+            ["local", index, value] => match (index.parse::<usize>(), parse_value(value)) {
+                (Ok(index), Some(value)) => {
+                    self.inspect(move |frame| match frame.locals_mut().get_mut(index) {
+                        Some(local) => {
+                            *local = value;
+                            String::new()
+                        }
+                        None => format!("There is no local {index}"),
+                    })
+                }
+                _ => Some(MONITOR_HELP.to_string()),
+            },
+            ["globals"] => self.inspect(|frame| {
+                let mut output = String::new();
+                for index in frame.module().globals.keys() {
+                    if let Some(value) = frame.global(index) {
+                        let _ = writeln!(output, "global {} = 0x{:x}", index.index(), value);
+                    }
+                }
+                output
+            }),
+            ["global", index, value] => match (index.parse::<u32>(), parse_value(value)) {
+                (Ok(index), Some(value)) => self.inspect(move |frame| {
+                    if frame.set_global(GlobalIndex::from_u32(index), value) {
+                        String::new()
+                    } else {
+                        format!("There is no global {index}")
+                    }
+                }),
+                _ => Some(MONITOR_HELP.to_string()),
+            },
+            _ => Some(MONITOR_HELP.to_string()),
+        };
+        match output {
+            Some(output) => out.write_raw(output.as_bytes()),
+            None => outputln!(out, "The guest is gone"),
+        }
+        Ok(())
+    }
+}
+
+/// Parses a value given to a monitor command.
+fn parse_value(value: &str) -> Option<u64> {
+    if let Some(hex) = value.strip_prefix("0x") {
+        u64::from_str_radix(hex, 16).ok()
+    } else if value.starts_with('-') {
+        value.parse::<i64>().ok().map(|value| value as u64)
+    } else {
+        value.parse().ok()
+    }
+}
+
+enum EventLoop {}
+
+impl BlockingEventLoop for EventLoop {
+    type Target = Server;
+    type Connection = TcpStream;
+    type StopReason = StopReason;
+
+    fn wait_for_stop_reason(
+        target: &mut Server,
+        conn: &mut TcpStream,
+    ) -> Result<
+        Event<StopReason>,
+        WaitForStopReasonError<&'static str, <TcpStream as Connection>::Error>,
+    > {
+        loop {
+            match target.stops.recv_timeout(POLL_INTERVAL) {
+                Ok(reason) => return Ok(Event::TargetStopped(reason)),
+                Err(RecvTimeoutError::Timeout) => {}
+                Err(RecvTimeoutError::Disconnected) => {
+                    return Err(WaitForStopReasonError::Target("the guest is gone"))
+                }
+            }
+            if conn
+                .peek()
+                .map_err(WaitForStopReasonError::Connection)?
+                .is_some()
+            {
+                let byte = conn.read().map_err(WaitForStopReasonError::Connection)?;
+                return Ok(Event::IncomingData(byte));
+            }
+        }
+    }
+
+    fn on_interrupt(target: &mut Server) -> Result<Option<StopReason>, &'static str> {
+        // The guest reports the stop once it notices.
+        target.guest.control.lock().unwrap().interrupted = true;
+        Ok(None)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn monitor_values() {
+        assert_eq!(parse_value("42"), Some(42));
+        assert_eq!(parse_value("0x2a"), Some(42));
+        assert_eq!(parse_value("-1"), Some(u64::MAX));
+        assert_eq!(parse_value("forty-two"), None);
+    }
+}
@@ -1,5 +1,7 @@
 #![allow(missing_docs, unused)]
 
+#[cfg(feature = "gdb")]
+mod gdb;
 mod wasi;
 
 use std::{
@@ -8,6 +10,7 @@ use std::{
     fs::File,
     io::{ErrorKind, LineWriter, Read, Write},
     net::SocketAddr,
+    ops::Range,
     path::{Path, PathBuf},
     str::FromStr,
     sync::{Arc, Mutex},
@@ -22,6 +25,8 @@ use sha2::{Digest, Sha256};
 use tempfile::NamedTempFile;
 use url::Url;
 #[cfg(feature = "sys")]
+use wasmer::sys::{MemoryAccess, MemoryAccessKind, MemoryWatch};
+#[cfg(feature = "sys")]
 use wasmer::NativeEngineExt;
 use wasmer::{
     DeserializeError, Engine, Function, Imports, Instance, Module, Store, Type, TypedFunction,
@@ -81,6 +86,25 @@ pub struct Run {
     /// Generate a coredump at this path if a WebAssembly trap occurs
     #[clap(name = "COREDUMP_PATH", long)]
     coredump_on_trap: Option<PathBuf>,
+    /// Wait for a GDB connection on this port before running the module
+    #[cfg(feature = "gdb")]
+    #[clap(long, value_name = "PORT")]
+    gdb: Option<u16>,
+    /// Report the loads and stores of the module to this range of linear
+    /// memory (e.g. `0x1000..0x1010`). Can be given several times.
+    #[cfg(feature = "sys")]
+    #[clap(long, value_name = "START..END", value_parser = parse_memory_range)]
+    watch_memory: Vec<Range<u64>>,
+    /// Compile the module with profile instrumentation, and write the
+    /// profile it records to this file when it exits. The profile can be
+    /// given to `wasmer compile --llvm --profile`.
+    #[cfg(feature = "sys")]
+    #[clap(long, value_name = "FILE")]
+    profile_generate: Option<PathBuf>,
+    /// Only run pre-compiled modules signed with the key with this public
+    /// key, in hex form as printed by `wasmer compile --sign`.
+    #[clap(long, value_name = "PUBLIC_KEY", value_parser = parse_public_key)]
+    require_signature: Option<String>,
     /// The file, URL, or package to run.
     #[clap(value_parser = PackageSource::infer)]
     input: PackageSource,
@@ -99,7 +123,7 @@ impl Run {
     }
 
     #[tracing::instrument(level = "debug", name = "wasmer_run", skip_all)]
-    fn execute_inner(self, output: Output) -> Result<(), Error> {
+    fn execute_inner(mut self, output: Output) -> Result<(), Error> {
         let pb = ProgressBar::new_spinner();
         pb.set_draw_target(output.draw_target());
         pb.enable_steady_tick(TICK);
@@ -123,7 +147,19 @@ impl Run {
         };
 
         let _guard = handle.enter();
-        let (store, _) = self.store.get_store()?;
+        #[cfg(feature = "sys")]
+        if !self.watch_memory.is_empty() {
+            self.store.enable_memory_watch();
+        }
+        #[cfg(feature = "sys")]
+        if self.profile_generate.is_some() {
+            self.store.enable_profile_instrumentation();
+        }
+        #[cfg(feature = "gdb")]
+        if self.gdb.is_some() {
+            self.store.enable_debugging();
+        }
+        let (mut store, _) = self.store.get_store()?;
 
         #[cfg(feature = "sys")]
         let engine = {
@@ -131,6 +167,15 @@ impl Run {
             let hash_algorithm = self.hash_algorithm.unwrap_or_default().into();
             engine.set_hash_algorithm(Some(hash_algorithm));
 
+            if !self.watch_memory.is_empty() {
+                let memory_watch = MemoryWatch::new(print_memory_access);
+                for range in &self.watch_memory {
+                    memory_watch.watch(range.clone());
+                }
+                engine.set_memory_watch(Some(Arc::new(memory_watch)));
+                store = Store::new(engine.clone());
+            }
+
             engine
         };
         #[cfg(not(feature = "sys"))]
@@ -146,7 +191,11 @@ impl Run {
         let runtime: Arc<dyn Runtime + Send + Sync> = monitoring_runtime.runtime.clone();
         let monitoring_runtime: Arc<dyn Runtime + Send + Sync> = monitoring_runtime;
 
-        let target = self.input.resolve_target(&monitoring_runtime, &pb)?;
+        let target = self.input.resolve_target(
+            &monitoring_runtime,
+            &pb,
+            self.require_signature.as_deref(),
+        )?;
 
         pb.finish_and_clear();
 
@@ -160,6 +209,16 @@ impl Run {
                     module_hash,
                     path,
                 } => self.execute_wasm(&path, &module, module_hash, store, runtime.clone()),
+                #[cfg(feature = "gdb")]
+                ExecutableTarget::Package(_) if self.gdb.is_some() => Err(anyhow::anyhow!(
+                    "Only WebAssembly modules can be debugged with --gdb"
+                )),
+                #[cfg(feature = "sys")]
+                ExecutableTarget::Package(_) if self.profile_generate.is_some() => {
+                    Err(anyhow::anyhow!(
+                        "Only WebAssembly modules can be profiled with --profile-generate"
+                    ))
+                }
                 ExecutableTarget::Package(pkg) => self.execute_webc(&pkg, runtime.clone()),
             }
         };
@@ -180,6 +239,50 @@ impl Run {
 
     #[tracing::instrument(skip_all)]
     fn execute_wasm(
+        &self,
+        path: &Path,
+        module: &Module,
+        module_hash: ModuleHash,
+        store: Store,
+        runtime: Arc<dyn Runtime + Send + Sync>,
+    ) -> Result<(), Error> {
+        #[cfg(feature = "gdb")]
+        if let Some(port) = self.gdb {
+            let wasm = std::fs::read(path)
+                .with_context(|| format!("Unable to read \"{}\"", path.display()))?;
+            // Code addresses are offsets in the binary module.
+            let wasm = wasmer::wat2wasm(&wasm)?.into_owned();
+            let session = gdb::DebugSession::start(port, module, wasm)?;
+            let result = self.execute_module(path, module, module_hash, store, runtime);
+            let exit_code = match &result {
+                Ok(()) => 0,
+                Err(error) => error
+                    .chain()
+                    .find_map(get_exit_code)
+                    .map_or(1, |exit_code| exit_code.raw()),
+            };
+            session.finish(exit_code);
+            return result;
+        }
+
+        let result = self.execute_module(path, module, module_hash, store, runtime);
+        #[cfg(feature = "sys")]
+        if let Some(profile_path) = &self.profile_generate {
+            // The profile of a run that failed is still worth keeping.
+            let profile = module.profile().context(
+                "The compiler doesn't support profile instrumentation, use --llvm to record a profile",
+            )?;
+            std::fs::write(profile_path, profile.to_string()).with_context(|| {
+                format!(
+                    "Unable to write the profile to \"{}\"",
+                    profile_path.display()
+                )
+            })?;
+        }
+        result
+    }
+
+    fn execute_module(
         &self,
         path: &Path,
         module: &Module,
@@ -500,6 +603,13 @@ impl Run {
             stack_size: None,
             entrypoint: Some(original_executable.to_string()),
             coredump_on_trap: None,
+            #[cfg(feature = "gdb")]
+            gdb: None,
+            #[cfg(feature = "sys")]
+            watch_memory: Vec::new(),
+            #[cfg(feature = "sys")]
+            profile_generate: None,
+            require_signature: None,
             input: PackageSource::infer(executable)?,
             args: args.to_vec(),
             hash_algorithm: None,
@@ -607,9 +717,12 @@ impl PackageSource {
         &self,
         rt: &Arc<dyn Runtime + Send + Sync>,
         pb: &ProgressBar,
+        require_signature: Option<&str>,
     ) -> Result<ExecutableTarget, Error> {
         match self {
-            PackageSource::File(path) => ExecutableTarget::from_file(path, rt, pb),
+            PackageSource::File(path) => {
+                ExecutableTarget::from_file(path, rt, pb, require_signature)
+            }
             PackageSource::Dir(d) => ExecutableTarget::from_dir(d, rt, pb),
             PackageSource::Package(pkg) => {
                 pb.set_message("Loading from the registry");
@@ -718,6 +831,7 @@ impl ExecutableTarget {
         path: &Path,
         runtime: &Arc<dyn Runtime + Send + Sync>,
         pb: &ProgressBar,
+        require_signature: Option<&str>,
     ) -> Result<Self, Error> {
         pb.set_message(format!("Loading from \"{}\"", path.display()));
 
@@ -739,7 +853,21 @@ impl ExecutableTarget {
             TargetOnDisk::Artifact => {
                 let engine = runtime.engine();
                 pb.set_message("Deserializing pre-compiled WebAssembly module");
-                let module = unsafe { Module::deserialize_from_file(&engine, path)? };
+                let module = match require_signature {
+                    #[cfg(feature = "signing")]
+                    Some(public_key) => {
+                        let verifier =
+                            wasmer::sys::ArtifactVerifier::new(&hex::decode(public_key)?)?;
+                        let bytes = std::fs::read(path)?;
+                        Module::deserialize_checked(&engine, bytes, &verifier)
+                            .with_context(|| format!("Unable to load \"{}\"", path.display()))?
+                    }
+                    #[cfg(not(feature = "signing"))]
+                    Some(_) => {
+                        bail!("Signatures can only be checked with the `signing` feature enabled")
+                    }
+                    None => unsafe { Module::deserialize_from_file(&engine, path)? },
+                };
 
                 let module_hash = module.info().hash.ok_or_else(|| {
                     anyhow::Error::msg("module hash is not present in the artifact")
@@ -851,6 +979,54 @@ impl wasmer_wasix::runners::wcgi::Callbacks for Callbacks {
     }
 }
 
+/// Parses a range of linear memory given as `START..END`, in decimal or in
+/// hexadecimal with `0x`.
+fn parse_memory_range(s: &str) -> Result<Range<u64>, Error> {
+    fn parse_offset(offset: &str) -> Result<u64, Error> {
+        let offset = offset.trim();
+        let parsed = match offset.strip_prefix("0x") {
+            Some(hex) => u64::from_str_radix(hex, 16),
+            None => offset.parse(),
+        };
+        parsed.with_context(|| format!("Invalid memory offset \"{offset}\""))
+    }
+
+    let (start, end) = s
+        .split_once("..")
+        .context("Expected a range of memory like 0x1000..0x1010")?;
+    let range = parse_offset(start)?..parse_offset(end)?;
+    if range.is_empty() {
+        bail!("The memory range {s} is empty");
+    }
+    Ok(range)
+}
+
+/// Checks that a public key is given as 32 bytes in hexadecimal.
+fn parse_public_key(s: &str) -> Result<String, Error> {
+    let key = hex::decode(s.trim()).context("Expected a public key in hexadecimal")?;
+    if key.len() != 32 {
+        bail!("Expected a public key of 32 bytes, got {}", key.len());
+    }
+    Ok(s.trim().to_string())
+}
+
+/// Reports an access of the guest to watched memory.
+#[cfg(feature = "sys")]
+fn print_memory_access(access: &MemoryAccess) {
+    let kind = match access.kind {
+        MemoryAccessKind::Load => "load",
+        MemoryAccessKind::Store => "store",
+    };
+    eprintln!(
+        "[memory watch] function {} {kind}s {} bytes at 0x{:x} of memory {}: 0x{:x}",
+        access.function_index.as_u32(),
+        access.size,
+        access.offset,
+        access.memory_index.as_u32(),
+        access.value,
+    );
+}
+
 /// Exit the current process, using the WASI exit code if the error contains
 /// one.
 fn exit_with_wasi_exit_code(result: Result<(), Error>) -> ! {
@@ -0,0 +1,28 @@
+
+#include "wasmer.h"
+#include <stdlib.h>
+#include <string.h>
+// EXTRA_HEADERS
+
+#if defined(_WIN32)
+#define WASMER_SHARED_EXPORT __declspec(dllexport)
+#else
+#define WASMER_SHARED_EXPORT __attribute__((visibility("default")))
+#endif
+
+// Creates the module called `name` in `store`, or the first module of the
+// library when `name` is NULL. Returns NULL if there is no such module.
+WASMER_SHARED_EXPORT wasm_module_t *wasmer_shared_module_new(wasm_store_t *store,
+                                                             const char *name) {
+  // INSTANTIATE_MODULES
+  return NULL;
+}
+
+// Returns the names of the modules of the library, terminated by NULL.
+WASMER_SHARED_EXPORT const char *const *wasmer_shared_module_names(void) {
+  static const char *const names[] = {
+      // MODULE_NAMES
+      NULL,
+  };
+  return names;
+}
@@ -10,7 +10,7 @@ use std::path::PathBuf;
 use std::string::ToString;
 use std::sync::Arc;
 
-use anyhow::{bail, Result};
+use anyhow::{bail, Context, Result};
 #[cfg(feature = "sys")]
 use wasmer::sys::Features;
 use wasmer::*;
@@ -51,10 +51,23 @@ pub struct WasmFeatures {
     #[clap(long = "enable-multi-value")]
     pub multi_value: bool,
 
+    /// Enable support for the relaxed SIMD proposal.
+    #[clap(long = "enable-relaxed-simd")]
+    pub relaxed_simd: bool,
+
     /// Enable support for the bulk memory proposal.
     #[clap(long = "enable-bulk-memory")]
     pub bulk_memory: bool,
 
+    /// Enable support for the tail-call proposal.
+    #[clap(long = "enable-tail-call")]
+    pub tail_call: bool,
+
+    /// Enable support for the exception-handling proposal (Cranelift only,
+    /// so it isn't part of `--enable-all`).
+    #[clap(long = "enable-exceptions")]
+    pub exceptions: bool,
+
     /// Enable support for all pre-standard proposals.
     #[clap(long = "enable-all")]
     pub all: bool,
@@ -65,35 +78,74 @@ pub struct WasmFeatures {
 /// The compiler options
 pub struct CompilerOptions {
     /// Use Singlepass compiler.
-    #[clap(long, conflicts_with_all = &["cranelift", "llvm"])]
+    #[clap(long, conflicts_with_all = &["cranelift", "llvm", "interpreter"])]
     singlepass: bool,
 
     /// Use Cranelift compiler.
-    #[clap(long, conflicts_with_all = &["singlepass", "llvm"])]
+    #[clap(long, conflicts_with_all = &["singlepass", "llvm", "interpreter"])]
     cranelift: bool,
 
     /// Use LLVM compiler.
-    #[clap(long, conflicts_with_all = &["singlepass", "cranelift"])]
+    #[clap(long, conflicts_with_all = &["singlepass", "cranelift", "interpreter"])]
     llvm: bool,
 
+    /// Use the interpreter, which doesn't need executable memory.
+    #[clap(long, conflicts_with_all = &["singlepass", "cranelift", "llvm"])]
+    interpreter: bool,
+
     /// Enable compiler internal verification.
     ///
     /// Available for cranelift, LLVM and singlepass.
     #[clap(long)]
     enable_verifier: bool,
 
+    /// Give the relaxed SIMD operators the same results on every target.
+    ///
+    /// Available for cranelift and LLVM.
+    #[clap(long)]
+    relaxed_simd_deterministic: bool,
+
     /// LLVM debug directory, where IR and object files will be written to.
     ///
     /// Only available for the LLVM compiler.
     #[clap(long)]
     llvm_debug_dir: Option<PathBuf>,
 
+    /// Optimize the module for the profile recorded by
+    /// `wasmer run --profile-generate`.
+    ///
+    /// Only available for the LLVM compiler.
+    #[clap(long, value_name = "FILE")]
+    profile: Option<PathBuf>,
+
     #[clap(flatten)]
     features: WasmFeatures,
+
+    /// Route loads and stores through the memory watch.
+    #[clap(skip)]
+    memory_watch: bool,
+
+    /// Count the calls and branches of the functions in their profile.
+    #[clap(skip)]
+    profile_instrumentation: bool,
+
+    /// Emit position-independent code, to be linked into shared libraries.
+    #[clap(skip)]
+    pic: bool,
+
+    /// Let an attached debugger stop the compiled functions.
+    #[clap(skip)]
+    debugging: bool,
 }
 
 #[cfg(feature = "compiler")]
 impl CompilerOptions {
+    /// Compiles modules to position-independent code, so their objects can
+    /// be linked into shared libraries.
+    pub fn enable_pic(&mut self) {
+        self.pic = true;
+    }
+
     fn get_compiler(&self) -> Result<CompilerType> {
         if self.cranelift {
             Ok(CompilerType::Cranelift)
@@ -101,17 +153,22 @@ impl CompilerOptions {
             Ok(CompilerType::LLVM)
         } else if self.singlepass {
             Ok(CompilerType::Singlepass)
+        } else if self.interpreter {
+            Ok(CompilerType::Interpreter)
         } else {
             // Auto mode, we choose the best compiler for that platform
             cfg_if::cfg_if! {
                 if #[cfg(all(feature = "cranelift", any(target_arch = "x86_64", target_arch = "aarch64")))] {
                     Ok(CompilerType::Cranelift)
                 }
-                else if #[cfg(all(feature = "singlepass", any(target_arch = "x86_64", target_arch = "aarch64")))] {
+                else if #[cfg(all(feature = "singlepass", any(target_arch = "x86_64", target_arch = "aarch64", target_arch = "riscv64")))] {
                     Ok(CompilerType::Singlepass)
                 }
                 else if #[cfg(feature = "llvm")] {
                     Ok(CompilerType::LLVM)
+                }
+                else if #[cfg(feature = "interpreter")] {
+                    Ok(CompilerType::Interpreter)
                 } else {
                     bail!("There are no available compilers for your architecture");
                 }
@@ -133,12 +190,21 @@ impl CompilerOptions {
         if self.features.simd || self.features.all {
             features.simd(true);
         }
+        if self.features.relaxed_simd || self.features.all {
+            features.relaxed_simd(true);
+        }
         if self.features.bulk_memory || self.features.all {
             features.bulk_memory(true);
         }
         if self.features.reference_types || self.features.all {
             features.reference_types(true);
         }
+        if self.features.tail_call || self.features.all {
+            features.tail_call(true);
+        }
+        if self.features.exceptions {
+            features.exceptions(true);
+        }
         Ok(features)
     }
 
@@ -176,7 +242,7 @@ impl CompilerOptions {
     #[allow(unused_variables)]
     pub(crate) fn get_compiler_config(&self) -> Result<(Box<dyn CompilerConfig>, CompilerType)> {
         let compiler = self.get_compiler()?;
-        let compiler_config: Box<dyn CompilerConfig> = match compiler {
+        let mut compiler_config: Box<dyn CompilerConfig> = match compiler {
             CompilerType::Headless => bail!("The headless engine can't be chosen"),
             #[cfg(feature = "singlepass")]
             CompilerType::Singlepass => {
@@ -225,6 +291,7 @@ impl CompilerOptions {
                             Type::F64 => "F".to_string(),
                             Type::V128 => "v".to_string(),
                             Type::ExternRef => "e".to_string(),
+                            Type::ExceptionRef => "x".to_string(),
                             Type::FuncRef => "r".to_string(),
                         })
                         .collect::<Vec<_>>()
@@ -294,9 +361,22 @@ impl CompilerOptions {
                 if self.enable_verifier {
                     config.enable_verifier();
                 }
+                if let Some(ref profile) = self.profile {
+                    let profile = std::fs::read_to_string(profile).with_context(|| {
+                        format!("Unable to read the profile \"{}\"", profile.display())
+                    })?;
+                    config.profile(Some(Arc::new(profile.parse()?)));
+                }
                 Box::new(config)
             }
-            #[cfg(not(all(feature = "singlepass", feature = "cranelift", feature = "llvm",)))]
+            #[cfg(feature = "interpreter")]
+            CompilerType::Interpreter => Box::new(wasmer_compiler_interpreter::Interpreter::new()),
+            #[cfg(not(all(
+                feature = "singlepass",
+                feature = "cranelift",
+                feature = "llvm",
+                feature = "interpreter",
+            )))]
             compiler => {
                 bail!(
                     "The `{}` compiler is not included in this binary.",
@@ -305,6 +385,31 @@ impl CompilerOptions {
             }
         };
 
+        if self.profile.is_some() && compiler != CompilerType::LLVM {
+            bail!("Profiles can only be used with the LLVM compiler");
+        }
+        if self.debugging
+            && compiler != CompilerType::Singlepass
+            && compiler != CompilerType::Interpreter
+        {
+            bail!("Debugging can only be used with the Singlepass compiler or the interpreter");
+        }
+        if self.memory_watch {
+            compiler_config.enable_memory_watch();
+        }
+        if self.profile_instrumentation {
+            compiler_config.enable_profile_instrumentation();
+        }
+        if self.debugging {
+            compiler_config.enable_debugging();
+        }
+        if self.pic {
+            compiler_config.enable_pic();
+        }
+        if self.relaxed_simd_deterministic {
+            compiler_config.relaxed_simd_deterministic(true);
+        }
+
         #[allow(unreachable_code)]
         Ok((compiler_config, compiler))
     }
@@ -320,6 +425,8 @@ pub enum CompilerType {
     Cranelift,
     /// LLVM compiler
     LLVM,
+    /// Interpreter
+    Interpreter,
     /// Headless compiler
     #[allow(dead_code)]
     Headless,
@@ -336,6 +443,8 @@ impl CompilerType {
             Self::Cranelift,
             #[cfg(feature = "llvm")]
             Self::LLVM,
+            #[cfg(feature = "interpreter")]
+            Self::Interpreter,
         ]
     }
 }
@@ -346,6 +455,7 @@ impl ToString for CompilerType {
             Self::Singlepass => "singlepass".to_string(),
             Self::Cranelift => "cranelift".to_string(),
             Self::LLVM => "llvm".to_string(),
+            Self::Interpreter => "interpreter".to_string(),
             Self::Headless => "headless".to_string(),
         }
     }
@@ -353,6 +463,23 @@ impl ToString for CompilerType {
 
 #[cfg(feature = "compiler")]
 impl StoreOptions {
+    /// Compiles modules so their loads and stores are reported to the
+    /// memory watch of the engine.
+    pub fn enable_memory_watch(&mut self) {
+        self.compiler.memory_watch = true;
+    }
+
+    /// Compiles modules so they record a profile of their calls and
+    /// branches while they run.
+    pub fn enable_profile_instrumentation(&mut self) {
+        self.compiler.profile_instrumentation = true;
+    }
+
+    /// Compiles modules so an attached debugger can stop them.
+    pub fn enable_debugging(&mut self) {
+        self.compiler.debugging = true;
+    }
+
     /// Gets the store for the host target, with the compiler name selected
     pub fn get_store(&self) -> Result<(Store, CompilerType)> {
         let target = Target::default();
@@ -19,6 +19,7 @@ use cranelift_codegen::{Context, MachTrap};
 use gimli::write::{Address, EhFrame, FrameTable};
 #[cfg(feature = "rayon")]
 use rayon::prelude::{IntoParallelRefIterator, ParallelIterator};
+use std::num::NonZeroUsize;
 use std::sync::Arc;
 use wasmer_compiler::{
     Compiler, FunctionBinaryReader, FunctionBodyData, MiddlewareBinaryReader, ModuleMiddleware,
@@ -48,27 +49,22 @@ impl CraneliftCompiler {
     pub fn config(&self) -> &Cranelift {
         &self.config
     }
-}
-
-impl Compiler for CraneliftCompiler {
-    fn name(&self) -> &str {
-        "cranelift"
-    }
-
-    /// Get the middlewares for this compiler
-    fn get_middlewares(&self) -> &[Arc<dyn ModuleMiddleware>] {
-        &self.config.middlewares
-    }
 
-    /// Compile the module using Cranelift, producing a compilation result with
-    /// associated relocations.
-    fn compile_module(
+    /// Compiles the module with the threads of the current thread pool.
+    fn compile_module_in_thread_pool(
         &self,
         target: &Target,
         compile_info: &CompileModuleInfo,
         module_translation_state: &ModuleTranslationState,
         function_body_inputs: PrimaryMap<LocalFunctionIndex, FunctionBodyData<'_>>,
     ) -> Result<Compilation, CompileError> {
+        // Heaps are always indexed with 32-bit addresses.
+        if compile_info.module.memories.values().any(|m| m.memory64) {
+            return Err(CompileError::UnsupportedFeature(
+                "64-bit memories are not supported by the Cranelift compiler".to_string(),
+            ));
+        }
+
         let isa = self
             .config()
             .isa(target)
@@ -126,6 +122,18 @@ impl Compiler for CraneliftCompiler {
                     &memory_styles,
                     &table_styles,
                 );
+                if self.config.enable_memory_watch {
+                    func_env.watch_memory(func_index);
+                }
+                if compile_info.features.tail_call {
+                    func_env.enable_tail_calls();
+                }
+                if compile_info.features.exceptions {
+                    func_env.enable_exceptions();
+                }
+                if compile_info.features.relaxed_simd {
+                    func_env.enable_relaxed_simd(&*isa, self.config.relaxed_simd_deterministic);
+                }
                 context.func.name = match get_function_name(func_index) {
                     ExternalName::User(nameref) => {
                         if context.func.params.user_named_funcs().is_valid(nameref) {
@@ -238,6 +246,18 @@ impl Compiler for CraneliftCompiler {
                     memory_styles,
                     table_styles,
                 );
+                if self.config.enable_memory_watch {
+                    func_env.watch_memory(func_index);
+                }
+                if compile_info.features.tail_call {
+                    func_env.enable_tail_calls();
+                }
+                if compile_info.features.exceptions {
+                    func_env.enable_exceptions();
+                }
+                if compile_info.features.relaxed_simd {
+                    func_env.enable_relaxed_simd(&*isa, self.config.relaxed_simd_deterministic);
+                }
                 context.func.name = match get_function_name(func_index) {
                     ExternalName::User(nameref) => {
                         if context.func.params.user_named_funcs().is_valid(nameref) {
@@ -415,6 +435,76 @@ impl Compiler for CraneliftCompiler {
     }
 }
 
+impl Compiler for CraneliftCompiler {
+    fn name(&self) -> &str {
+        "cranelift"
+    }
+
+    fn deterministic_id(&self) -> String {
+        let mut id = self.name().to_string();
+        if self.config.enable_memory_watch {
+            id.push_str("-memory-watch");
+        }
+        if self.config.relaxed_simd_deterministic {
+            id.push_str("-relaxed-simd-deterministic");
+        }
+        id
+    }
+
+    /// Get the middlewares for this compiler
+    fn get_middlewares(&self) -> &[Arc<dyn ModuleMiddleware>] {
+        &self.config.middlewares
+    }
+
+    /// Compile the module using Cranelift, producing a compilation result with
+    /// associated relocations.
+    fn compile_module(
+        &self,
+        target: &Target,
+        compile_info: &CompileModuleInfo,
+        module_translation_state: &ModuleTranslationState,
+        function_body_inputs: PrimaryMap<LocalFunctionIndex, FunctionBodyData<'_>>,
+    ) -> Result<Compilation, CompileError> {
+        in_thread_pool(self.config.num_threads, || {
+            self.compile_module_in_thread_pool(
+                target,
+                compile_info,
+                module_translation_state,
+                function_body_inputs,
+            )
+        })
+    }
+}
+
+/// Runs `f` in a thread pool of `num_threads` threads, or in the global
+/// thread pool without a number of threads.
+#[cfg(feature = "rayon")]
+fn in_thread_pool<R: Send>(
+    num_threads: Option<NonZeroUsize>,
+    f: impl FnOnce() -> Result<R, CompileError> + Send,
+) -> Result<R, CompileError> {
+    match num_threads {
+        Some(num_threads) => {
+            let pool = rayon::ThreadPoolBuilder::new()
+                .num_threads(num_threads.get())
+                .build()
+                .map_err(|e| CompileError::Resource(e.to_string()))?;
+            pool.install(f)
+        }
+        None => f(),
+    }
+}
+
+/// Runs `f` on the current thread, as everything is compiled on it without
+/// rayon.
+#[cfg(not(feature = "rayon"))]
+fn in_thread_pool<R>(
+    _num_threads: Option<NonZeroUsize>,
+    f: impl FnOnce() -> Result<R, CompileError>,
+) -> Result<R, CompileError> {
+    f()
+}
+
 fn mach_reloc_to_reloc(module: &ModuleInfo, reloc: &MachReloc) -> Relocation {
     let &MachReloc {
         offset,
@@ -2,6 +2,7 @@ use crate::compiler::CraneliftCompiler;
 use cranelift_codegen::isa::{lookup, TargetIsa};
 use cranelift_codegen::settings::{self, Configurable};
 use cranelift_codegen::CodegenResult;
+use std::num::NonZeroUsize;
 use std::sync::Arc;
 use wasmer_compiler::{Compiler, CompilerConfig, Engine, EngineBuilder, ModuleMiddleware};
 use wasmer_types::{Architecture, CpuFeature, Target};
@@ -32,7 +33,10 @@ pub struct Cranelift {
     enable_nan_canonicalization: bool,
     enable_verifier: bool,
     enable_pic: bool,
+    pub(crate) enable_memory_watch: bool,
+    pub(crate) relaxed_simd_deterministic: bool,
     opt_level: CraneliftOptLevel,
+    pub(crate) num_threads: Option<NonZeroUsize>,
     /// The middleware chain.
     pub(crate) middlewares: Vec<Arc<dyn ModuleMiddleware>>,
 }
@@ -46,6 +50,9 @@ impl Cranelift {
             enable_verifier: false,
             opt_level: CraneliftOptLevel::Speed,
             enable_pic: false,
+            enable_memory_watch: false,
+            relaxed_simd_deterministic: false,
+            num_threads: None,
             middlewares: vec![],
         }
     }
@@ -116,6 +123,9 @@ impl Cranelift {
         if cpu_features.contains(CpuFeature::LZCNT) {
             builder.enable("has_lzcnt").expect("should be valid flag");
         }
+        if cpu_features.contains(CpuFeature::FMA) {
+            builder.enable("has_fma").expect("should be valid flag");
+        }
 
         builder.finish(self.flags(target))
     }
@@ -213,6 +223,18 @@ impl CompilerConfig for Cranelift {
         self.enable_nan_canonicalization = enable;
     }
 
+    fn relaxed_simd_deterministic(&mut self, enable: bool) {
+        self.relaxed_simd_deterministic = enable;
+    }
+
+    fn enable_memory_watch(&mut self) {
+        self.enable_memory_watch = true;
+    }
+
+    fn num_threads(&mut self, num_threads: Option<NonZeroUsize>) {
+        self.num_threads = num_threads;
+    }
+
     /// Transform it into the compiler
     fn compiler(self: Box<Self>) -> Box<dyn Compiler> {
         Box::new(CraneliftCompiler::new(*self))
@@ -2,7 +2,8 @@
 // Attributions: https://github.com/wasmerio/wasmer/blob/main/ATTRIBUTIONS.md
 
 use crate::translator::{
-    type_to_irtype, FuncEnvironment as BaseFuncEnvironment, GlobalVariable, TargetEnvironment,
+    type_to_irtype, zero_value, FuncEnvironment as BaseFuncEnvironment, FuncTranslationState,
+    GlobalVariable, TargetEnvironment,
 };
 use cranelift_codegen::cursor::FuncCursor;
 use cranelift_codegen::ir;
@@ -10,17 +11,18 @@ use cranelift_codegen::ir::condcodes::*;
 use cranelift_codegen::ir::immediates::{Offset32, Uimm64};
 use cranelift_codegen::ir::types::*;
 use cranelift_codegen::ir::{AbiParam, ArgumentPurpose, Function, InstBuilder, Signature};
-use cranelift_codegen::isa::TargetFrontendConfig;
-use cranelift_frontend::FunctionBuilder;
+use cranelift_codegen::isa::{TargetFrontendConfig, TargetIsa};
+use cranelift_frontend::{FunctionBuilder, Switch};
 use std::convert::TryFrom;
+use wasmer_compiler::wasm_unsupported;
 use wasmer_compiler::wasmparser::HeapType;
 use wasmer_types::entity::EntityRef;
 use wasmer_types::entity::PrimaryMap;
 use wasmer_types::VMBuiltinFunctionIndex;
 use wasmer_types::VMOffsets;
 use wasmer_types::{
-    FunctionIndex, FunctionType, GlobalIndex, LocalFunctionIndex, MemoryIndex, ModuleInfo,
-    SignatureIndex, TableIndex, Type as WasmerType,
+    Architecture, FunctionIndex, FunctionType, GlobalIndex, LocalFunctionIndex, MemoryIndex,
+    ModuleInfo, SignatureIndex, TableIndex, TagIndex, TagType, Type as WasmerType,
 };
 use wasmer_types::{MemoryStyle, TableStyle};
 use wasmer_types::{WasmError, WasmResult};
@@ -35,6 +37,9 @@ pub fn type_of_vmtable_definition_current_elements(vmoffsets: &VMOffsets) -> ir:
     ir::Type::int(u16::from(vmoffsets.size_of_vmtable_definition_current_elements()) * 8).unwrap()
 }
 
+/// The size of the `RawValue`s of the payload of exceptions.
+const EXCEPTION_PAYLOAD_VALUE_SIZE: usize = 16;
+
 /// The `FuncEnvironment` implementation for use by the `ModuleEnvironment`.
 pub struct FuncEnvironment<'module_environment> {
     /// Target-specified configuration.
@@ -113,6 +118,51 @@ pub struct FuncEnvironment<'module_environment> {
     /// The external function signature for implementing wasm's `memory32.atomic.notify`.
     memory32_atomic_notify_sig: Option<ir::SigRef>,
 
+    /// The external function signature for reporting loads to the memory watch.
+    memory_watch_load_sig: Option<ir::SigRef>,
+
+    /// The external function signature for reporting stores to the memory watch.
+    memory_watch_store_sig: Option<ir::SigRef>,
+
+    /// The external function signature for implementing wasm's `throw`.
+    throw_sig: Option<ir::SigRef>,
+
+    /// The external function signature for implementing wasm's `throw_ref`.
+    throw_ref_sig: Option<ir::SigRef>,
+
+    /// The external function signature for catching exceptions in a
+    /// `try_table`.
+    catch_sig: Option<ir::SigRef>,
+
+    /// The function being translated, if its memory accesses are reported
+    /// to the memory watch.
+    memory_watch: Option<FunctionIndex>,
+
+    /// Whether functions follow the tail call protocol of the `VMContext`,
+    /// when the tail-call proposal is enabled.
+    tail_calls: bool,
+
+    /// Whether the function was called by a function running tail calls,
+    /// loaded when entering the function.
+    tail_call_driven: Option<ir::Value>,
+
+    /// The block running the pending tail calls of the function, created by
+    /// its first tail call.
+    tail_call_block: Option<ir::Block>,
+
+    /// Whether the pending exception is checked after each call, when the
+    /// exception-handling proposal is enabled.
+    exceptions: bool,
+
+    /// Whether the relaxed-SIMD operators get their deterministic semantics.
+    relaxed_simd_deterministic: bool,
+
+    /// Whether the target is x86.
+    is_x86: bool,
+
+    /// Whether the target has vector fused multiply-add instructions.
+    has_native_fma: bool,
+
     /// Offsets to struct fields accessed by JIT code.
     offsets: VMOffsets,
 
@@ -155,12 +205,59 @@ impl<'module_environment> FuncEnvironment<'module_environment> {
             memory32_atomic_wait32_sig: None,
             memory32_atomic_wait64_sig: None,
             memory32_atomic_notify_sig: None,
+            memory_watch_load_sig: None,
+            memory_watch_store_sig: None,
+            throw_sig: None,
+            throw_ref_sig: None,
+            catch_sig: None,
+            memory_watch: None,
+            tail_calls: false,
+            tail_call_driven: None,
+            tail_call_block: None,
+            exceptions: false,
+            relaxed_simd_deterministic: true,
+            is_x86: false,
+            has_native_fma: false,
             offsets: VMOffsets::new(target_config.pointer_bytes(), module),
             memory_styles,
             table_styles,
         }
     }
 
+    /// Reports the memory accesses of the function being translated to the
+    /// memory watch of its instance.
+    pub fn watch_memory(&mut self, function_index: FunctionIndex) {
+        self.memory_watch = Some(function_index);
+    }
+
+    /// Makes the function follow the tail call protocol of the `VMContext`,
+    /// needed by modules using the tail-call proposal.
+    pub fn enable_tail_calls(&mut self) {
+        self.tail_calls = true;
+    }
+
+    /// Makes the function check the pending exception after each call,
+    /// needed by modules using the exception-handling proposal.
+    pub fn enable_exceptions(&mut self) {
+        self.exceptions = true;
+    }
+
+    /// Lowers the relaxed-SIMD operators to the fastest instructions of
+    /// `isa`, or with their deterministic semantics if `deterministic`.
+    pub fn enable_relaxed_simd(&mut self, isa: &dyn TargetIsa, deterministic: bool) {
+        let isa_flags = isa.isa_flags();
+        let has_flag = |name| {
+            isa_flags
+                .iter()
+                .any(|flag| flag.name == name && flag.as_bool() == Some(true))
+        };
+        self.relaxed_simd_deterministic = deterministic;
+        self.is_x86 = isa.triple().architecture == Architecture::X86_64;
+        // The vector FMA instructions are an extension on x86, and always
+        // there on the other targets supporting SIMD.
+        self.has_native_fma = !self.is_x86 || (has_flag("has_avx") && has_flag("has_fma"));
+    }
+
     fn pointer_type(&self) -> ir::Type {
         self.target_config.pointer_type()
     }
@@ -806,6 +903,125 @@ impl<'module_environment> FuncEnvironment<'module_environment> {
         sig
     }
 
+    fn get_memory_watch_load_sig(&mut self, func: &mut Function) -> ir::SigRef {
+        let sig = self.memory_watch_load_sig.unwrap_or_else(|| {
+            func.import_signature(Signature {
+                params: vec![
+                    AbiParam::special(self.pointer_type(), ArgumentPurpose::VMContext),
+                    // Function Index
+                    AbiParam::new(I32),
+                    // Memory Index
+                    AbiParam::new(I32),
+                    // Address
+                    AbiParam::new(I32),
+                    // Offset
+                    AbiParam::new(I32),
+                    // Size
+                    AbiParam::new(I32),
+                ],
+                returns: vec![],
+                call_conv: self.target_config.default_call_conv,
+            })
+        });
+        self.memory_watch_load_sig = Some(sig);
+        sig
+    }
+
+    fn get_throw_sig(&mut self, func: &mut Function) -> ir::SigRef {
+        let sig = self.throw_sig.unwrap_or_else(|| {
+            func.import_signature(Signature {
+                params: vec![
+                    AbiParam::special(self.pointer_type(), ArgumentPurpose::VMContext),
+                    // Tag Index
+                    AbiParam::new(I32),
+                    // Payload
+                    AbiParam::new(self.pointer_type()),
+                ],
+                returns: vec![],
+                call_conv: self.target_config.default_call_conv,
+            })
+        });
+        self.throw_sig = Some(sig);
+        sig
+    }
+
+    fn get_throw_ref_sig(&mut self, func: &mut Function) -> ir::SigRef {
+        let sig = self.throw_ref_sig.unwrap_or_else(|| {
+            func.import_signature(Signature {
+                params: vec![
+                    AbiParam::special(self.pointer_type(), ArgumentPurpose::VMContext),
+                    AbiParam::new(R64),
+                ],
+                returns: vec![],
+                call_conv: self.target_config.default_call_conv,
+            })
+        });
+        self.throw_ref_sig = Some(sig);
+        sig
+    }
+
+    fn get_catch_sig(&mut self, func: &mut Function) -> ir::SigRef {
+        let sig = self.catch_sig.unwrap_or_else(|| {
+            func.import_signature(Signature {
+                params: vec![
+                    AbiParam::special(self.pointer_type(), ArgumentPurpose::VMContext),
+                    // Tag Index
+                    AbiParam::new(I32),
+                    // Payload
+                    AbiParam::new(self.pointer_type()),
+                ],
+                returns: vec![AbiParam::new(R64)],
+                call_conv: self.target_config.default_call_conv,
+            })
+        });
+        self.catch_sig = Some(sig);
+        sig
+    }
+
+    /// Creates a stack slot for the payload of an exception carrying
+    /// `len` values, laid out as `RawValue`s, and returns its address.
+    fn exception_payload(
+        &mut self,
+        builder: &mut FunctionBuilder,
+        len: usize,
+    ) -> (Option<ir::StackSlot>, ir::Value) {
+        let pointer_type = self.pointer_type();
+        if len == 0 {
+            return (None, builder.ins().iconst(pointer_type, 0));
+        }
+        let slot = builder.create_sized_stack_slot(ir::StackSlotData::new(
+            ir::StackSlotKind::ExplicitSlot,
+            u32::try_from(len * EXCEPTION_PAYLOAD_VALUE_SIZE).unwrap(),
+        ));
+        (Some(slot), builder.ins().stack_addr(pointer_type, slot, 0))
+    }
+
+    fn get_memory_watch_store_sig(&mut self, func: &mut Function) -> ir::SigRef {
+        let sig = self.memory_watch_store_sig.unwrap_or_else(|| {
+            func.import_signature(Signature {
+                params: vec![
+                    AbiParam::special(self.pointer_type(), ArgumentPurpose::VMContext),
+                    // Function Index
+                    AbiParam::new(I32),
+                    // Memory Index
+                    AbiParam::new(I32),
+                    // Address
+                    AbiParam::new(I32),
+                    // Offset
+                    AbiParam::new(I32),
+                    // Size
+                    AbiParam::new(I32),
+                    // Value
+                    AbiParam::new(I64),
+                ],
+                returns: vec![],
+                call_conv: self.target_config.default_call_conv,
+            })
+        });
+        self.memory_watch_store_sig = Some(sig);
+        sig
+    }
+
     /// Return the memory.atomic.notify function signature to call for the given index,
     /// along with the translated index value to pass to it
     /// and its index in `VMBuiltinFunctionsArray`.
@@ -851,6 +1067,179 @@ impl<'module_environment> FuncEnvironment<'module_environment> {
 
         (base, func_addr)
     }
+
+    /// Loads the address and the vmctx of the imported function
+    /// `callee_index`.
+    fn load_imported_callee(
+        &mut self,
+        pos: &mut FuncCursor<'_>,
+        callee_index: FunctionIndex,
+    ) -> (ir::Value, ir::Value) {
+        let pointer_type = self.pointer_type();
+        let vmctx = self.vmctx(pos.func);
+        let base = pos.ins().global_value(pointer_type, vmctx);
+
+        let mem_flags = ir::MemFlags::trusted();
+
+        // Load the callee address.
+        let body_offset =
+            i32::try_from(self.offsets.vmctx_vmfunction_import_body(callee_index)).unwrap();
+        let func_addr = pos.ins().load(pointer_type, mem_flags, base, body_offset);
+
+        // Load the callee vmctx address.
+        let vmctx_offset =
+            i32::try_from(self.offsets.vmctx_vmfunction_import_vmctx(callee_index)).unwrap();
+        let vmctx = pos.ins().load(pointer_type, mem_flags, base, vmctx_offset);
+
+        (func_addr, vmctx)
+    }
+
+    /// Loads the address and the vmctx of the function `callee` in the table
+    /// `table_index`, trapping if there is none or if its signature isn't
+    /// `sig_index`.
+    fn load_indirect_callee(
+        &mut self,
+        pos: &mut FuncCursor<'_>,
+        table_index: TableIndex,
+        table: ir::Table,
+        sig_index: SignatureIndex,
+        callee: ir::Value,
+    ) -> (ir::Value, ir::Value) {
+        let pointer_type = self.pointer_type();
+
+        let table_entry_addr = pos.ins().table_addr(pointer_type, table, callee, 0);
+
+        // Dereference table_entry_addr to get the function address.
+        let mem_flags = ir::MemFlags::trusted();
+        let table_entry_addr = pos.ins().load(
+            pointer_type,
+            mem_flags,
+            table_entry_addr,
+            i32::from(self.offsets.vm_funcref_anyfunc_ptr()),
+        );
+
+        // check if the funcref is null
+        pos.ins()
+            .trapz(table_entry_addr, ir::TrapCode::IndirectCallToNull);
+
+        let func_addr = pos.ins().load(
+            pointer_type,
+            mem_flags,
+            table_entry_addr,
+            i32::from(self.offsets.vmcaller_checked_anyfunc_func_ptr()),
+        );
+
+        // If necessary, check the signature.
+        match self.table_styles[table_index] {
+            TableStyle::CallerChecksSignature => {
+                let sig_id_size = self.offsets.size_of_vmshared_signature_index();
+                let sig_id_type = ir::Type::int(u16::from(sig_id_size) * 8).unwrap();
+                let vmctx = self.vmctx(pos.func);
+                let base = pos.ins().global_value(pointer_type, vmctx);
+                let offset =
+                    i32::try_from(self.offsets.vmctx_vmshared_signature_id(sig_index)).unwrap();
+
+                // Load the caller ID.
+                let mut mem_flags = ir::MemFlags::trusted();
+                mem_flags.set_readonly();
+                let caller_sig_id = pos.ins().load(sig_id_type, mem_flags, base, offset);
+
+                // Load the callee ID.
+                let mem_flags = ir::MemFlags::trusted();
+                let callee_sig_id = pos.ins().load(
+                    sig_id_type,
+                    mem_flags,
+                    table_entry_addr,
+                    i32::from(self.offsets.vmcaller_checked_anyfunc_type_index()),
+                );
+
+                // Check that they match.
+                let cmp = pos.ins().icmp(IntCC::Equal, callee_sig_id, caller_sig_id);
+                pos.ins().trapz(cmp, ir::TrapCode::BadSignature);
+            }
+        }
+
+        // Load the callee vmctx address.
+        let vmctx = pos.ins().load(
+            pointer_type,
+            mem_flags,
+            table_entry_addr,
+            i32::from(self.offsets.vmcaller_checked_anyfunc_vmctx()),
+        );
+
+        (func_addr, vmctx)
+    }
+
+    /// Leaves a tail call to `func_addr` pending in the `VMContext`, ending
+    /// the current block. The pending tail call is run by the caller if it
+    /// runs tail calls itself, or by the tail call block of the function.
+    fn translate_tail_call(
+        &mut self,
+        builder: &mut FunctionBuilder,
+        sig_index: SignatureIndex,
+        func_addr: ir::Value,
+        callee_vmctx: ir::Value,
+        call_args: &[ir::Value],
+    ) -> WasmResult<()> {
+        let driven = self.tail_call_driven.ok_or_else(|| {
+            wasm_unsupported!("tail calls when the tail-call proposal is disabled")
+        })?;
+        let vmctx = builder
+            .func
+            .special_param(ArgumentPurpose::VMContext)
+            .unwrap();
+        let mem_flags = ir::MemFlags::trusted();
+
+        for (i, arg) in call_args.iter().enumerate() {
+            let offset = self.tail_call_arg_offset(i);
+            builder.ins().store(mem_flags, *arg, vmctx, offset);
+        }
+        let callee_offset = i32::try_from(self.offsets.vmctx_tail_call_callee()).unwrap();
+        builder
+            .ins()
+            .store(mem_flags, func_addr, vmctx, callee_offset);
+        let callee_vmctx_offset =
+            i32::try_from(self.offsets.vmctx_tail_call_callee_vmctx()).unwrap();
+        builder
+            .ins()
+            .store(mem_flags, callee_vmctx, vmctx, callee_vmctx_offset);
+        let signature = builder.ins().iconst(I32, i64::from(sig_index.as_u32()));
+        let signature_offset = i32::try_from(self.offsets.vmctx_tail_call_signature()).unwrap();
+        builder
+            .ins()
+            .store(mem_flags, signature, vmctx, signature_offset);
+
+        // If our caller runs tail calls, return to it and let it run this
+        // one. Its results are ignored.
+        let tail_call_block = *self
+            .tail_call_block
+            .get_or_insert_with(|| builder.create_block());
+        let return_block = builder.create_block();
+        builder.ins().brnz(driven, return_block, &[]);
+        builder.ins().jump(tail_call_block, &[]);
+        builder.seal_block(return_block);
+        builder.switch_to_block(return_block);
+        let returns = builder
+            .func
+            .signature
+            .returns
+            .iter()
+            .map(|ret| ret.value_type)
+            .collect::<Vec<_>>();
+        let results = returns
+            .into_iter()
+            .map(|ty| zero_value(builder, ty))
+            .collect::<Vec<_>>();
+        builder.ins().return_(&results);
+        Ok(())
+    }
+
+    /// The offset of the `index`th argument of the pending tail call.
+    fn tail_call_arg_offset(&self, index: usize) -> i32 {
+        let offset = self.offsets.vmctx_tail_call_args() as usize
+            + index * usize::from(self.offsets.size_of_vmtail_call_arg());
+        i32::try_from(offset).unwrap()
+    }
 }
 
 impl<'module_environment> TargetEnvironment for FuncEnvironment<'module_environment> {
@@ -1004,6 +1393,7 @@ impl<'module_environment> BaseFuncEnvironment for FuncEnvironment<'module_enviro
         Ok(match ty {
             HeapType::Func => pos.ins().null(self.reference_type()),
             HeapType::Extern => pos.ins().null(self.reference_type()),
+            HeapType::Exn => pos.ins().null(self.reference_type()),
             _ => {
                 return Err(WasmError::Unsupported(
                     "`ref.null T` that is not a `funcref` or an `externref`".into(),
@@ -1210,69 +1600,12 @@ impl<'module_environment> BaseFuncEnvironment for FuncEnvironment<'module_enviro
         callee: ir::Value,
         call_args: &[ir::Value],
     ) -> WasmResult<ir::Inst> {
-        let pointer_type = self.pointer_type();
-
-        let table_entry_addr = pos.ins().table_addr(pointer_type, table, callee, 0);
-
-        // Dereference table_entry_addr to get the function address.
-        let mem_flags = ir::MemFlags::trusted();
-        let table_entry_addr = pos.ins().load(
-            pointer_type,
-            mem_flags,
-            table_entry_addr,
-            i32::from(self.offsets.vm_funcref_anyfunc_ptr()),
-        );
-
-        // check if the funcref is null
-        pos.ins()
-            .trapz(table_entry_addr, ir::TrapCode::IndirectCallToNull);
-
-        let func_addr = pos.ins().load(
-            pointer_type,
-            mem_flags,
-            table_entry_addr,
-            i32::from(self.offsets.vmcaller_checked_anyfunc_func_ptr()),
-        );
-
-        // If necessary, check the signature.
-        match self.table_styles[table_index] {
-            TableStyle::CallerChecksSignature => {
-                let sig_id_size = self.offsets.size_of_vmshared_signature_index();
-                let sig_id_type = ir::Type::int(u16::from(sig_id_size) * 8).unwrap();
-                let vmctx = self.vmctx(pos.func);
-                let base = pos.ins().global_value(pointer_type, vmctx);
-                let offset =
-                    i32::try_from(self.offsets.vmctx_vmshared_signature_id(sig_index)).unwrap();
-
-                // Load the caller ID.
-                let mut mem_flags = ir::MemFlags::trusted();
-                mem_flags.set_readonly();
-                let caller_sig_id = pos.ins().load(sig_id_type, mem_flags, base, offset);
-
-                // Load the callee ID.
-                let mem_flags = ir::MemFlags::trusted();
-                let callee_sig_id = pos.ins().load(
-                    sig_id_type,
-                    mem_flags,
-                    table_entry_addr,
-                    i32::from(self.offsets.vmcaller_checked_anyfunc_type_index()),
-                );
-
-                // Check that they match.
-                let cmp = pos.ins().icmp(IntCC::Equal, callee_sig_id, caller_sig_id);
-                pos.ins().trapz(cmp, ir::TrapCode::BadSignature);
-            }
-        }
+        let (func_addr, vmctx) =
+            self.load_indirect_callee(&mut pos, table_index, table, sig_index, callee);
 
         let mut real_call_args = Vec::with_capacity(call_args.len() + 2);
 
         // First append the callee vmctx address.
-        let vmctx = pos.ins().load(
-            pointer_type,
-            mem_flags,
-            table_entry_addr,
-            i32::from(self.offsets.vmcaller_checked_anyfunc_vmctx()),
-        );
         real_call_args.push(vmctx);
 
         // Then append the regular call arguments.
@@ -1306,22 +1639,10 @@ impl<'module_environment> BaseFuncEnvironment for FuncEnvironment<'module_enviro
 
         // Handle direct calls to imported functions. We use an indirect call
         // so that we don't have to patch the code at runtime.
-        let pointer_type = self.pointer_type();
         let sig_ref = pos.func.dfg.ext_funcs[callee].signature;
-        let vmctx = self.vmctx(pos.func);
-        let base = pos.ins().global_value(pointer_type, vmctx);
-
-        let mem_flags = ir::MemFlags::trusted();
-
-        // Load the callee address.
-        let body_offset =
-            i32::try_from(self.offsets.vmctx_vmfunction_import_body(callee_index)).unwrap();
-        let func_addr = pos.ins().load(pointer_type, mem_flags, base, body_offset);
+        let (func_addr, vmctx) = self.load_imported_callee(&mut pos, callee_index);
 
         // First append the callee vmctx address.
-        let vmctx_offset =
-            i32::try_from(self.offsets.vmctx_vmfunction_import_vmctx(callee_index)).unwrap();
-        let vmctx = pos.ins().load(pointer_type, mem_flags, base, vmctx_offset);
         real_call_args.push(vmctx);
 
         // Then append the regular call arguments.
@@ -1330,6 +1651,154 @@ impl<'module_environment> BaseFuncEnvironment for FuncEnvironment<'module_enviro
         Ok(pos.ins().call_indirect(sig_ref, func_addr, &real_call_args))
     }
 
+    fn translate_return_call(
+        &mut self,
+        builder: &mut FunctionBuilder,
+        callee_index: FunctionIndex,
+        callee: ir::FuncRef,
+        call_args: &[ir::Value],
+    ) -> WasmResult<()> {
+        let (func_addr, callee_vmctx) = if self.module.is_imported_function(callee_index) {
+            self.load_imported_callee(&mut builder.cursor(), callee_index)
+        } else {
+            let vmctx = builder
+                .func
+                .special_param(ArgumentPurpose::VMContext)
+                .unwrap();
+            let func_addr = builder.ins().func_addr(self.pointer_type(), callee);
+            (func_addr, vmctx)
+        };
+        let sig_index = self.module.functions[callee_index];
+        self.translate_tail_call(builder, sig_index, func_addr, callee_vmctx, call_args)
+    }
+
+    fn translate_return_call_indirect(
+        &mut self,
+        builder: &mut FunctionBuilder,
+        table_index: TableIndex,
+        table: ir::Table,
+        sig_index: SignatureIndex,
+        _sig_ref: ir::SigRef,
+        callee: ir::Value,
+        call_args: &[ir::Value],
+    ) -> WasmResult<()> {
+        let (func_addr, callee_vmctx) =
+            self.load_indirect_callee(&mut builder.cursor(), table_index, table, sig_index, callee);
+        self.translate_tail_call(builder, sig_index, func_addr, callee_vmctx, call_args)
+    }
+
+    fn exceptions_enabled(&self) -> bool {
+        self.exceptions
+    }
+
+    fn relaxed_simd_deterministic(&self) -> bool {
+        self.relaxed_simd_deterministic
+    }
+
+    fn has_native_fma(&self) -> bool {
+        self.has_native_fma
+    }
+
+    fn is_x86(&self) -> bool {
+        self.is_x86
+    }
+
+    fn translate_exception_check(
+        &mut self,
+        builder: &mut FunctionBuilder,
+        handler: ir::Block,
+    ) -> WasmResult<()> {
+        let pointer_type = self.pointer_type();
+        let mem_flags = ir::MemFlags::trusted();
+        let vmctx = builder
+            .func
+            .special_param(ArgumentPurpose::VMContext)
+            .unwrap();
+        let offset = i32::try_from(self.offsets.vmctx_pending_exception_pointer()).unwrap();
+        let pending_exception = builder.ins().load(pointer_type, mem_flags, vmctx, offset);
+        let pending = builder
+            .ins()
+            .load(pointer_type, mem_flags, pending_exception, 0);
+        let next = builder.create_block();
+        builder.ins().brnz(pending, handler, &[]);
+        builder.ins().jump(next, &[]);
+        builder.seal_block(next);
+        builder.switch_to_block(next);
+        Ok(())
+    }
+
+    fn translate_throw(
+        &mut self,
+        builder: &mut FunctionBuilder,
+        tag_index: TagIndex,
+        args: &[ir::Value],
+    ) -> WasmResult<()> {
+        let (slot, payload) = self.exception_payload(builder, args.len());
+        if let Some(slot) = slot {
+            for (i, arg) in args.iter().enumerate() {
+                let offset = i32::try_from(i * EXCEPTION_PAYLOAD_VALUE_SIZE).unwrap();
+                builder.ins().stack_store(*arg, slot, offset);
+            }
+        }
+        let func_sig = self.get_throw_sig(builder.func);
+        let mut pos = builder.cursor();
+        let (vmctx, func_addr) = self.translate_load_builtin_function_address(
+            &mut pos,
+            VMBuiltinFunctionIndex::get_throw_index(),
+        );
+        let tag_index = pos.ins().iconst(I32, tag_index.as_u32() as i64);
+        pos.ins()
+            .call_indirect(func_sig, func_addr, &[vmctx, tag_index, payload]);
+        Ok(())
+    }
+
+    fn translate_throw_ref(&mut self, mut pos: FuncCursor, exnref: ir::Value) -> WasmResult<()> {
+        let func_sig = self.get_throw_ref_sig(pos.func);
+        let (vmctx, func_addr) = self.translate_load_builtin_function_address(
+            &mut pos,
+            VMBuiltinFunctionIndex::get_throw_ref_index(),
+        );
+        pos.ins()
+            .call_indirect(func_sig, func_addr, &[vmctx, exnref]);
+        Ok(())
+    }
+
+    fn translate_catch(
+        &mut self,
+        builder: &mut FunctionBuilder,
+        tag_index: Option<TagIndex>,
+    ) -> WasmResult<(ir::Value, Vec<ir::Value>)> {
+        let params = match tag_index {
+            Some(tag_index) => self.module.tag_type(tag_index).params().to_vec(),
+            None => vec![],
+        };
+        let (slot, payload) = self.exception_payload(builder, params.len());
+        let func_sig = self.get_catch_sig(builder.func);
+        let mut pos = builder.cursor();
+        let (vmctx, func_addr) = self.translate_load_builtin_function_address(
+            &mut pos,
+            VMBuiltinFunctionIndex::get_catch_index(),
+        );
+        // Any tag matches the `u32::MAX` index.
+        let tag_index = pos.ins().iconst(
+            I32,
+            tag_index.map_or(u32::MAX, |tag_index| tag_index.as_u32()) as i64,
+        );
+        let call_inst = pos
+            .ins()
+            .call_indirect(func_sig, func_addr, &[vmctx, tag_index, payload]);
+        let exnref = *pos.func.dfg.inst_results(call_inst).first().unwrap();
+        let mut values = Vec::with_capacity(params.len());
+        if let Some(slot) = slot {
+            for (i, param) in params.into_iter().enumerate() {
+                let ty = type_to_irtype(param, self.target_config)?;
+                let offset = i32::try_from(i * EXCEPTION_PAYLOAD_VALUE_SIZE).unwrap();
+                values.push(builder.ins().stack_load(ty, slot, offset));
+            }
+        }
+        Ok((exnref, values))
+    }
+
     fn translate_memory_grow(
         &mut self,
         mut pos: FuncCursor<'_>,
@@ -1573,6 +2042,186 @@ impl<'module_environment> BaseFuncEnvironment for FuncEnvironment<'module_enviro
         Ok(*pos.func.dfg.inst_results(call_inst).first().unwrap())
     }
 
+    fn translate_memory_watch(
+        &mut self,
+        mut pos: FuncCursor,
+        index: MemoryIndex,
+        addr: ir::Value,
+        offset: u64,
+        size: u32,
+        value: Option<ir::Value>,
+    ) -> WasmResult<()> {
+        let function_index = match self.memory_watch {
+            // Accesses to 64-bit memories aren't watched.
+            Some(function_index) if pos.func.dfg.value_type(addr) == I32 => function_index,
+            _ => return Ok(()),
+        };
+        let value = match value {
+            Some(value) => match pos.func.dfg.value_type(value) {
+                I64 => Some(value),
+                I32 => Some(pos.ins().uextend(I64, value)),
+                F32 => {
+                    let bits = pos.ins().bitcast(I32, ir::MemFlags::new(), value);
+                    Some(pos.ins().uextend(I64, bits))
+                }
+                F64 => Some(pos.ins().bitcast(I64, ir::MemFlags::new(), value)),
+                // Vectors don't fit in the reported value.
+                _ => return Ok(()),
+            },
+            None => None,
+        };
+        let function_index = pos.ins().iconst(I32, function_index.index() as i64);
+        let memory_index = pos.ins().iconst(I32, index.index() as i64);
+        let offset = pos.ins().iconst(I32, i64::from(offset as u32 as i32));
+        let size = pos.ins().iconst(I32, size as i64);
+        match value {
+            None => {
+                let func_sig = self.get_memory_watch_load_sig(pos.func);
+                let (vmctx, func_addr) = self.translate_load_builtin_function_address(
+                    &mut pos,
+                    VMBuiltinFunctionIndex::get_memory_watch_load_index(),
+                );
+                pos.ins().call_indirect(
+                    func_sig,
+                    func_addr,
+                    &[vmctx, function_index, memory_index, addr, offset, size],
+                );
+            }
+            Some(value) => {
+                let func_sig = self.get_memory_watch_store_sig(pos.func);
+                let (vmctx, func_addr) = self.translate_load_builtin_function_address(
+                    &mut pos,
+                    VMBuiltinFunctionIndex::get_memory_watch_store_index(),
+                );
+                pos.ins().call_indirect(
+                    func_sig,
+                    func_addr,
+                    &[
+                        vmctx,
+                        function_index,
+                        memory_index,
+                        addr,
+                        offset,
+                        size,
+                        value,
+                    ],
+                );
+            }
+        }
+        Ok(())
+    }
+
+    fn before_translate_function(
+        &mut self,
+        builder: &mut FunctionBuilder,
+        _state: &FuncTranslationState,
+    ) -> WasmResult<()> {
+        if self.tail_calls {
+            // Only the function called by a function running tail calls may
+            // leave its tail calls pending, so clear the flag on entry.
+            let vmctx = builder
+                .func
+                .special_param(ArgumentPurpose::VMContext)
+                .unwrap();
+            let mem_flags = ir::MemFlags::trusted();
+            let offset = i32::try_from(self.offsets.vmctx_tail_call_driven()).unwrap();
+            let driven = builder.ins().load(I32, mem_flags, vmctx, offset);
+            let zero = builder.ins().iconst(I32, 0);
+            builder.ins().store(mem_flags, zero, vmctx, offset);
+            self.tail_call_driven = Some(driven);
+        }
+        Ok(())
+    }
+
+    fn after_translate_function(
+        &mut self,
+        builder: &mut FunctionBuilder,
+        _state: &FuncTranslationState,
+    ) -> WasmResult<()> {
+        let tail_call_block = match self.tail_call_block {
+            Some(block) => block,
+            None => return Ok(()),
+        };
+        // Run the pending tail calls until one returns without leaving
+        // another pending, so that tail calls don't grow the stack.
+        let pointer_type = self.pointer_type();
+        let mem_flags = ir::MemFlags::trusted();
+        builder.switch_to_block(tail_call_block);
+        let vmctx = builder
+            .func
+            .special_param(ArgumentPurpose::VMContext)
+            .unwrap();
+        let callee_offset = i32::try_from(self.offsets.vmctx_tail_call_callee()).unwrap();
+        let func_addr = builder
+            .ins()
+            .load(pointer_type, mem_flags, vmctx, callee_offset);
+        let null = builder.ins().iconst(pointer_type, 0);
+        builder.ins().store(mem_flags, null, vmctx, callee_offset);
+        let callee_vmctx_offset =
+            i32::try_from(self.offsets.vmctx_tail_call_callee_vmctx()).unwrap();
+        let callee_vmctx = builder
+            .ins()
+            .load(pointer_type, mem_flags, vmctx, callee_vmctx_offset);
+        let signature_offset = i32::try_from(self.offsets.vmctx_tail_call_signature()).unwrap();
+        let signature = builder.ins().load(I32, mem_flags, vmctx, signature_offset);
+
+        // Functions of other instances, and host functions, don't know about
+        // our pending tail calls.
+        let same_instance = builder.ins().icmp(IntCC::Equal, callee_vmctx, vmctx);
+        let driven = builder.ins().uextend(I32, same_instance);
+        let driven_offset = i32::try_from(self.offsets.vmctx_tail_call_driven()).unwrap();
+        builder.ins().store(mem_flags, driven, vmctx, driven_offset);
+
+        // The callee has the same results as this function, but its
+        // parameters depend on its signature.
+        let returns = builder.func.signature.returns.clone();
+        let return_block = builder.create_block();
+        for ret in &returns {
+            builder.append_block_param(return_block, ret.value_type);
+        }
+        let mut switch = Switch::new();
+        let mut cases = Vec::new();
+        for (sig_index, signature) in self.signatures.iter() {
+            if signature.returns == returns {
+                let case = builder.create_block();
+                switch.set_entry(u128::from(sig_index.as_u32()), case);
+                cases.push((sig_index, case));
+            }
+        }
+        let unknown_signature = builder.create_block();
+        switch.emit(builder, signature, unknown_signature);
+        builder.switch_to_block(unknown_signature);
+        builder.ins().trap(ir::TrapCode::BadSignature);
+
+        for (sig_index, case) in cases {
+            builder.switch_to_block(case);
+            let signature = self.signatures[sig_index].clone();
+            let mut args = vec![callee_vmctx];
+            for (i, param) in signature.params.iter().skip(1).enumerate() {
+                let offset = self.tail_call_arg_offset(i);
+                args.push(
+                    builder
+                        .ins()
+                        .load(param.value_type, mem_flags, vmctx, offset),
+                );
+            }
+            let sig_ref = builder.import_signature(signature);
+            let call = builder.ins().call_indirect(sig_ref, func_addr, &args);
+            let results = builder.inst_results(call).to_vec();
+            let pending = builder
+                .ins()
+                .load(pointer_type, mem_flags, vmctx, callee_offset);
+            builder.ins().brnz(pending, tail_call_block, &[]);
+            builder.ins().jump(return_block, &results);
+        }
+
+        builder.switch_to_block(return_block);
+        let results = builder.block_params(return_block).to_vec();
+        builder.ins().return_(&results);
+        builder.seal_all_blocks();
+        Ok(())
+    }
+
     fn get_global_type(&self, global_index: GlobalIndex) -> Option<WasmerType> {
         Some(self.module.globals.get(global_index)?.ty)
     }
@@ -1606,4 +2255,9 @@ impl<'module_environment> BaseFuncEnvironment for FuncEnvironment<'module_enviro
     fn get_function_sig(&self, sig_index: SignatureIndex) -> Option<&FunctionType> {
         self.module.signatures.get(sig_index)
     }
+
+    fn get_tag_type(&self, tag_index: TagIndex) -> Option<TagType> {
+        self.module.tags.get(tag_index)?;
+        Some(self.module.tag_type(tag_index))
+    }
 }
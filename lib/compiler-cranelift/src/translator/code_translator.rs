@@ -92,10 +92,10 @@ use cranelift_frontend::{FunctionBuilder, Variable};
 use smallvec::SmallVec;
 use std::vec::Vec;
 
-use wasmer_compiler::wasmparser::{MemArg, Operator};
+use wasmer_compiler::wasmparser::{Catch, MemArg, Operator};
 use wasmer_compiler::{from_binaryreadererror_wasmerror, wasm_unsupported, ModuleTranslationState};
 use wasmer_types::{
-    FunctionIndex, GlobalIndex, MemoryIndex, SignatureIndex, TableIndex, WasmResult,
+    FunctionIndex, GlobalIndex, MemoryIndex, SignatureIndex, TableIndex, TagIndex, WasmResult,
 };
 
 // Clippy warns about "align: _" but its important to document that the align field is ignored
@@ -370,6 +370,7 @@ pub fn translate_operator<FE: FuncEnvironment + ?Sized>(
             }
         }
         Operator::End => {
+            pop_exception_handler(builder, state);
             let frame = state.control_stack.pop().unwrap();
             let next_block = frame.following_code();
             if !builder.is_unreachable() || builder.func.layout.first_inst(next_block).is_some() {
@@ -542,9 +543,78 @@ pub fn translate_operator<FE: FuncEnvironment + ?Sized>(
             state.reachable = false;
         }
         /********************************** Exception handing **********************************/
+        Operator::TryTable { try_table } => {
+            let (params, results) =
+                module_translation_state.blocktype_params_results(&try_table.ty)?;
+            let next = block_with_params(builder, results.iter(), environ)?;
+            let body = builder.create_block();
+            builder.ins().jump(body, &[]);
+            builder.seal_block(body);
+
+            // The handler of the `try_table` dispatches the exceptions left
+            // pending in its body to its catch clauses, in order. Their labels
+            // are relative to the blocks enclosing the `try_table`.
+            let handler = builder.create_block();
+            builder.switch_to_block(handler);
+            for catch in &try_table.catches {
+                let (tag_index, label, with_ref) = match *catch {
+                    Catch::One { tag, label } => (Some(TagIndex::from_u32(tag)), label, false),
+                    Catch::OneRef { tag, label } => (Some(TagIndex::from_u32(tag)), label, true),
+                    Catch::All { label } => (None, label, false),
+                    Catch::AllRef { label } => (None, label, true),
+                };
+                let (exnref, mut values) = environ.translate_catch(builder, tag_index)?;
+                if with_ref {
+                    values.push(exnref);
+                }
+                let br_destination = {
+                    let i = state.control_stack.len() - 1 - (label as usize);
+                    let frame = &mut state.control_stack[i];
+                    frame.set_branched_to_exit();
+                    frame.br_destination()
+                };
+                let caught = builder.create_block();
+                let next_catch = builder.create_block();
+                let is_null = builder.ins().is_null(exnref);
+                builder.ins().brnz(is_null, next_catch, &[]);
+                builder.ins().jump(caught, &[]);
+                builder.seal_block(caught);
+                builder.seal_block(next_catch);
+                builder.switch_to_block(caught);
+                canonicalise_then_jump(builder, br_destination, &values);
+                builder.switch_to_block(next_catch);
+            }
+            let outer_handler = state.exception_handler(builder);
+            builder.ins().jump(outer_handler, &[]);
+
+            builder.switch_to_block(body);
+            state.push_block(next, params.len(), results.len());
+            state
+                .exception_handlers
+                .push((state.control_stack.len(), handler));
+        }
+        Operator::Throw { tag_index } => {
+            let tag_index = TagIndex::from_u32(*tag_index);
+            let tag_type = environ
+                .get_tag_type(tag_index)
+                .expect("tags are checked by the validator");
+            let num_args = tag_type.params().len();
+            let args = state.peekn(num_args);
+            environ.translate_throw(builder, tag_index, args)?;
+            state.popn(num_args);
+            let handler = state.exception_handler(builder);
+            builder.ins().jump(handler, &[]);
+            state.reachable = false;
+        }
+        Operator::ThrowRef => {
+            let exnref = state.pop1();
+            environ.translate_throw_ref(builder.cursor(), exnref)?;
+            let handler = state.exception_handler(builder);
+            builder.ins().jump(handler, &[]);
+            state.reachable = false;
+        }
         Operator::Try { .. }
         | Operator::Catch { .. }
-        | Operator::Throw { .. }
         | Operator::Rethrow { .. }
         | Operator::Delegate { .. }
         | Operator::CatchAll => {
@@ -581,8 +651,10 @@ pub fn translate_operator<FE: FuncEnvironment + ?Sized>(
                     .len(),
                 "translate_call results should match the call signature"
             );
+            let inst_results = inst_results.to_vec();
             state.popn(num_args);
-            state.pushn(inst_results);
+            state.pushn(&inst_results);
+            translate_exception_check(builder, state, environ)?;
         }
         Operator::CallIndirect {
             type_index,
@@ -621,8 +693,57 @@ pub fn translate_operator<FE: FuncEnvironment + ?Sized>(
                 builder.func.dfg.signatures[sigref].returns.len(),
                 "translate_call_indirect results should match the call signature"
             );
+            let inst_results = inst_results.to_vec();
+            state.popn(num_args);
+            state.pushn(&inst_results);
+            translate_exception_check(builder, state, environ)?;
+        }
+        Operator::ReturnCall { function_index } => {
+            let (fref, num_args) = state.get_direct_func(builder.func, *function_index, environ)?;
+
+            let args = state.peekn_mut(num_args);
+
+            // Bitcast any vector arguments to their default type, I8X16, before calling.
+            let callee_signature =
+                &builder.func.dfg.signatures[builder.func.dfg.ext_funcs[fref].signature];
+            let types = wasm_param_types(&callee_signature.params, |i| {
+                environ.is_wasm_parameter(callee_signature, i)
+            });
+            bitcast_arguments(args, &types, builder);
+            let func_index = FunctionIndex::from_u32(*function_index);
+
+            environ.translate_return_call(builder, func_index, fref, args)?;
+            state.popn(num_args);
+            state.reachable = false;
+        }
+        Operator::ReturnCallIndirect {
+            type_index,
+            table_index,
+        } => {
+            let (sigref, num_args) = state.get_indirect_sig(builder.func, *type_index, environ)?;
+            let table = state.get_or_create_table(builder.func, *table_index, environ)?;
+            let callee = state.pop1();
+
+            // Bitcast any vector arguments to their default type, I8X16, before calling.
+            let callee_signature = &builder.func.dfg.signatures[sigref];
+            let args = state.peekn_mut(num_args);
+            let types = wasm_param_types(&callee_signature.params, |i| {
+                environ.is_wasm_parameter(callee_signature, i)
+            });
+            bitcast_arguments(args, &types, builder);
+
+            let args = state.peekn(num_args);
+            environ.translate_return_call_indirect(
+                builder,
+                TableIndex::from_u32(*table_index),
+                table,
+                SignatureIndex::from_u32(*type_index),
+                sigref,
+                callee,
+                args,
+            )?;
             state.popn(num_args);
-            state.pushn(inst_results);
+            state.reachable = false;
         }
         /******************************* Memory management ***********************************
          * Memory management is handled by environment. It is usually translated into calls to
@@ -1556,7 +1677,7 @@ pub fn translate_operator<FE: FuncEnvironment + ?Sized>(
             // to WASM using the less specific v128 type for certain operations and more specific
             // types (e.g. i8x16) for others.
         }
-        Operator::I8x16Swizzle => {
+        Operator::I8x16Swizzle | Operator::I8x16RelaxedSwizzle => {
             let (a, b) = pop2_with_bitcast(state, I8X16, builder);
             state.push1(builder.ins().swizzle(I8X16, a, b))
         }
@@ -1663,7 +1784,11 @@ pub fn translate_operator<FE: FuncEnvironment + ?Sized>(
             let b_mod_bitwidth = builder.ins().band_imm(b, bitwidth - 1);
             state.push1(builder.ins().sshr(bitcast_a, b_mod_bitwidth))
         }
-        Operator::V128Bitselect => {
+        Operator::V128Bitselect
+        | Operator::I8x16RelaxedLaneselect
+        | Operator::I16x8RelaxedLaneselect
+        | Operator::I32x4RelaxedLaneselect
+        | Operator::I64x2RelaxedLaneselect => {
             let (a, b, c) = state.pop3();
             let bitcast_a = optionally_bitcast_vector(a, I8X16, builder);
             let bitcast_b = optionally_bitcast_vector(b, I8X16, builder);
@@ -1812,11 +1937,11 @@ pub fn translate_operator<FE: FuncEnvironment + ?Sized>(
             let a = pop1_with_bitcast(state, F64X2, builder);
             state.push1(builder.ins().fvdemote(a));
         }
-        Operator::I32x4TruncSatF32x4S => {
+        Operator::I32x4TruncSatF32x4S | Operator::I32x4RelaxedTruncF32x4S => {
             let a = pop1_with_bitcast(state, F32X4, builder);
             state.push1(builder.ins().fcvt_to_sint_sat(I32X4, a))
         }
-        Operator::I32x4TruncSatF64x2SZero => {
+        Operator::I32x4TruncSatF64x2SZero | Operator::I32x4RelaxedTruncF64x2SZero => {
             let a = pop1_with_bitcast(state, F64X2, builder);
             let converted_a = builder.ins().fcvt_to_sint_sat(I64X2, a);
             let handle = builder.func.dfg.constants.insert(vec![0u8; 16].into());
@@ -1824,11 +1949,11 @@ pub fn translate_operator<FE: FuncEnvironment + ?Sized>(
 
             state.push1(builder.ins().snarrow(converted_a, zero));
         }
-        Operator::I32x4TruncSatF32x4U => {
+        Operator::I32x4TruncSatF32x4U | Operator::I32x4RelaxedTruncF32x4U => {
             let a = pop1_with_bitcast(state, F32X4, builder);
             state.push1(builder.ins().fcvt_to_uint_sat(I32X4, a))
         }
-        Operator::I32x4TruncSatF64x2UZero => {
+        Operator::I32x4TruncSatF64x2UZero | Operator::I32x4RelaxedTruncF64x2UZero => {
             let a = pop1_with_bitcast(state, F64X2, builder);
             let converted_a = builder.ins().fcvt_to_uint_sat(I64X2, a);
             let handle = builder.func.dfg.constants.insert(vec![0u8; 16].into());
@@ -1952,7 +2077,7 @@ pub fn translate_operator<FE: FuncEnvironment + ?Sized>(
             let arg = pop1_with_bitcast(state, type_of(op), builder);
             state.push1(builder.ins().popcnt(arg));
         }
-        Operator::I16x8Q15MulrSatS => {
+        Operator::I16x8Q15MulrSatS | Operator::I16x8RelaxedQ15mulrS => {
             let (a, b) = pop2_with_bitcast(state, I16X8, builder);
             state.push1(builder.ins().sqmul_round_sat(a, b))
         }
@@ -2028,35 +2153,64 @@ pub fn translate_operator<FE: FuncEnvironment + ?Sized>(
             let b_high = builder.ins().uwiden_high(b);
             state.push1(builder.ins().imul(a_high, b_high));
         }
-        Operator::ReturnCall { .. } | Operator::ReturnCallIndirect { .. } => {
-            return Err(wasm_unsupported!("proposed tail-call operator {:?}", op));
-        }
-        Operator::I8x16RelaxedSwizzle
-        | Operator::I32x4RelaxedTruncF32x4S
-        | Operator::I32x4RelaxedTruncF32x4U
-        | Operator::I32x4RelaxedTruncF64x2SZero
-        | Operator::I32x4RelaxedTruncF64x2UZero
+        Operator::F32x4RelaxedMadd
         | Operator::F32x4RelaxedNmadd
-        | Operator::F32x4RelaxedMadd
-        | Operator::I8x16RelaxedLaneselect
-        | Operator::I16x8RelaxedLaneselect
-        | Operator::I32x4RelaxedLaneselect
-        | Operator::I64x2RelaxedLaneselect
-        | Operator::F32x4RelaxedMin
-        | Operator::F32x4RelaxedMax
-        | Operator::F64x2RelaxedMin
-        | Operator::F64x2RelaxedMax
         | Operator::F64x2RelaxedMadd
-        | Operator::F64x2RelaxedNmadd
-        | Operator::I16x8RelaxedDotI8x16I7x16S
-        | Operator::I32x4RelaxedDotI8x16I7x16AddS
-        | Operator::I16x8RelaxedQ15mulrS => {
-            return Err(wasm_unsupported!("proposed relaxed-simd operator {:?}", op));
+        | Operator::F64x2RelaxedNmadd => {
+            let ty = type_of(op);
+            let (a, b, c) = state.pop3();
+            let a = optionally_bitcast_vector(a, ty, builder);
+            let b = optionally_bitcast_vector(b, ty, builder);
+            let c = optionally_bitcast_vector(c, ty, builder);
+            let a = match op {
+                Operator::F32x4RelaxedNmadd | Operator::F64x2RelaxedNmadd => builder.ins().fneg(a),
+                _ => a,
+            };
+            state.push1(if environ.has_native_fma() {
+                builder.ins().fma(a, b, c)
+            } else if environ.relaxed_simd_deterministic() {
+                fma_lanes(builder, ty, a, b, c)
+            } else {
+                let product = builder.ins().fmul(a, b);
+                builder.ins().fadd(product, c)
+            })
         }
-        Operator::TryTable { .. } | Operator::ThrowRef => {
-            return Err(wasm_unsupported!(
-                "exceptions are not supported (operator: {op:?})"
-            ));
+        Operator::F32x4RelaxedMin | Operator::F64x2RelaxedMin => {
+            let (a, b) = pop2_with_bitcast(state, type_of(op), builder);
+            state.push1(
+                if environ.relaxed_simd_deterministic() || !environ.is_x86() {
+                    builder.ins().fmin(a, b)
+                } else {
+                    // This is `minps a, b`, which returns `b` for NaNs and
+                    // zeros of either sign.
+                    builder.ins().fmin_pseudo(b, a)
+                },
+            )
+        }
+        Operator::F32x4RelaxedMax | Operator::F64x2RelaxedMax => {
+            let (a, b) = pop2_with_bitcast(state, type_of(op), builder);
+            state.push1(
+                if environ.relaxed_simd_deterministic() || !environ.is_x86() {
+                    builder.ins().fmax(a, b)
+                } else {
+                    // This is `maxps a, b`, which returns `b` for NaNs and
+                    // zeros of either sign.
+                    builder.ins().fmax_pseudo(b, a)
+                },
+            )
+        }
+        Operator::I16x8RelaxedDotI8x16I7x16S => {
+            let (a, b) = pop2_with_bitcast(state, I8X16, builder);
+            state.push1(relaxed_dot_i8x16(builder, a, b))
+        }
+        Operator::I32x4RelaxedDotI8x16I7x16AddS => {
+            let c = pop1_with_bitcast(state, I32X4, builder);
+            let (a, b) = pop2_with_bitcast(state, I8X16, builder);
+            let dot = relaxed_dot_i8x16(builder, a, b);
+            let dot_low = builder.ins().swiden_low(dot);
+            let dot_high = builder.ins().swiden_high(dot);
+            let dot = builder.ins().iadd_pairwise(dot_low, dot_high);
+            state.push1(builder.ins().iadd(dot, c))
         }
         Operator::RefEq
         | Operator::StructNew { .. }
@@ -2129,7 +2283,9 @@ fn translate_unreachable_operator<FE: FuncEnvironment + ?Sized>(
                 blockty,
             );
         }
-        Operator::Loop { blockty: _ } | Operator::Block { blockty: _ } => {
+        Operator::Loop { blockty: _ }
+        | Operator::Block { blockty: _ }
+        | Operator::TryTable { try_table: _ } => {
             state.push_block(ir::Block::reserved_value(), 0, 0);
         }
         Operator::Else => {
@@ -2182,6 +2338,7 @@ fn translate_unreachable_operator<FE: FuncEnvironment + ?Sized>(
             }
         }
         Operator::End => {
+            pop_exception_handler(builder, state);
             let stack = &mut state.stack;
             let control_stack = &mut state.control_stack;
             let frame = control_stack.pop().unwrap();
@@ -2236,6 +2393,31 @@ fn translate_unreachable_operator<FE: FuncEnvironment + ?Sized>(
     Ok(())
 }
 
+/// Branch to the exception handler if the call just translated left an
+/// exception pending.
+fn translate_exception_check<FE: FuncEnvironment + ?Sized>(
+    builder: &mut FunctionBuilder,
+    state: &mut FuncTranslationState,
+    environ: &mut FE,
+) -> WasmResult<()> {
+    if environ.exceptions_enabled() {
+        let handler = state.exception_handler(builder);
+        environ.translate_exception_check(builder, handler)?;
+    }
+    Ok(())
+}
+
+/// Pop the handler of the `try_table` whose frame is the innermost one, if
+/// any, sealing it now that all the code it handles is translated.
+fn pop_exception_handler(builder: &mut FunctionBuilder, state: &mut FuncTranslationState) {
+    if let Some(&(depth, handler)) = state.exception_handlers.last() {
+        if depth == state.control_stack.len() {
+            state.exception_handlers.pop();
+            builder.seal_block(handler);
+        }
+    }
+}
+
 /// Get the address+offset to use for a heap access.
 fn get_heap_addr(
     heap: ir::Heap,
@@ -2359,13 +2541,18 @@ fn translate_load<FE: FuncEnvironment + ?Sized>(
     state: &mut FuncTranslationState,
     environ: &mut FE,
 ) -> WasmResult<()> {
-    let (flags, base, offset) = prepare_load(
-        memarg,
-        mem_op_size(opcode, result_ty),
-        builder,
-        state,
-        environ,
-    )?;
+    let size = mem_op_size(opcode, result_ty);
+    if !result_ty.is_vector() {
+        environ.translate_memory_watch(
+            builder.cursor(),
+            MemoryIndex::from_u32(memarg.memory),
+            state.peek1(),
+            memarg.offset,
+            size,
+            None,
+        )?;
+    }
+    let (flags, base, offset) = prepare_load(memarg, size, builder, state, environ)?;
     let (load, dfg) = builder.ins().Load(opcode, result_ty, flags, offset, base);
     state.push1(dfg.first_result(load));
     Ok(())
@@ -2381,6 +2568,14 @@ fn translate_store<FE: FuncEnvironment + ?Sized>(
 ) -> WasmResult<()> {
     let (addr32, val) = state.pop2();
     let val_ty = builder.func.dfg.value_type(val);
+    environ.translate_memory_watch(
+        builder.cursor(),
+        MemoryIndex::from_u32(memarg.memory),
+        addr32,
+        memarg.offset,
+        mem_op_size(opcode, val_ty),
+        Some(val),
+    )?;
 
     let heap = state.get_heap(builder.func, memarg.memory, environ)?;
     let (base, offset) = get_heap_addr(
@@ -2756,7 +2951,11 @@ fn type_of(operator: &Operator) -> Type {
         | Operator::V128Or
         | Operator::V128Xor
         | Operator::V128AnyTrue
-        | Operator::V128Bitselect => I8X16, // default type representing V128
+        | Operator::V128Bitselect
+        | Operator::I8x16RelaxedLaneselect
+        | Operator::I16x8RelaxedLaneselect
+        | Operator::I32x4RelaxedLaneselect
+        | Operator::I64x2RelaxedLaneselect => I8X16, // default type representing V128
 
         Operator::I8x16Shuffle { .. }
         | Operator::I8x16Splat
@@ -2794,7 +2993,8 @@ fn type_of(operator: &Operator) -> Type {
         | Operator::I8x16MaxU
         | Operator::I8x16AvgrU
         | Operator::I8x16Bitmask
-        | Operator::I8x16Popcnt => I8X16,
+        | Operator::I8x16Popcnt
+        | Operator::I8x16RelaxedSwizzle => I8X16,
 
         Operator::I16x8Splat
         | Operator::V128Load16Splat { .. }
@@ -2831,7 +3031,9 @@ fn type_of(operator: &Operator) -> Type {
         | Operator::I16x8MaxU
         | Operator::I16x8AvgrU
         | Operator::I16x8Mul
-        | Operator::I16x8Bitmask => I16X8,
+        | Operator::I16x8Bitmask
+        | Operator::I16x8RelaxedQ15mulrS
+        | Operator::I16x8RelaxedDotI8x16I7x16S => I16X8,
 
         Operator::I32x4Splat
         | Operator::V128Load32Splat { .. }
@@ -2865,6 +3067,11 @@ fn type_of(operator: &Operator) -> Type {
         | Operator::I32x4Bitmask
         | Operator::I32x4TruncSatF32x4S
         | Operator::I32x4TruncSatF32x4U
+        | Operator::I32x4RelaxedTruncF32x4S
+        | Operator::I32x4RelaxedTruncF32x4U
+        | Operator::I32x4RelaxedTruncF64x2SZero
+        | Operator::I32x4RelaxedTruncF64x2UZero
+        | Operator::I32x4RelaxedDotI8x16I7x16AddS
         | Operator::V128Load32Zero { .. } => I32X4,
 
         Operator::I64x2Splat
@@ -2916,7 +3123,11 @@ fn type_of(operator: &Operator) -> Type {
         | Operator::F32x4Ceil
         | Operator::F32x4Floor
         | Operator::F32x4Trunc
-        | Operator::F32x4Nearest => F32X4,
+        | Operator::F32x4Nearest
+        | Operator::F32x4RelaxedMadd
+        | Operator::F32x4RelaxedNmadd
+        | Operator::F32x4RelaxedMin
+        | Operator::F32x4RelaxedMax => F32X4,
 
         Operator::F64x2Splat
         | Operator::F64x2ExtractLane { .. }
@@ -2941,7 +3152,11 @@ fn type_of(operator: &Operator) -> Type {
         | Operator::F64x2Ceil
         | Operator::F64x2Floor
         | Operator::F64x2Trunc
-        | Operator::F64x2Nearest => F64X2,
+        | Operator::F64x2Nearest
+        | Operator::F64x2RelaxedMadd
+        | Operator::F64x2RelaxedNmadd
+        | Operator::F64x2RelaxedMin
+        | Operator::F64x2RelaxedMax => F64X2,
 
         _ => unimplemented!(
             "Currently only SIMD instructions are mapped to their return type; the \
@@ -2951,6 +3166,51 @@ fn type_of(operator: &Operator) -> Type {
     }
 }
 
+/// Computes the fused multiply-add of `a`, `b` and `c` lane by lane, for targets without vector
+/// FMA instructions: the scalar ones fall back to a libcall if needed.
+fn fma_lanes(builder: &mut FunctionBuilder, ty: Type, a: Value, b: Value, c: Value) -> Value {
+    let mut result = c;
+    for lane in 0..ty.lane_count() as u8 {
+        let a = builder.ins().extractlane(a, lane);
+        let b = builder.ins().extractlane(b, lane);
+        let c = builder.ins().extractlane(c, lane);
+        let fma = builder.ins().fma(a, b, c);
+        result = builder.ins().insertlane(result, fma, lane);
+    }
+    result
+}
+
+/// Computes the dot products of the adjacent pairs of signed I8X16 lanes of `a` and `b`, wrapped
+/// to I16X8 lanes, which are the deterministic semantics of `i16x8.relaxed_dot_i8x16_i7x16_s`.
+fn relaxed_dot_i8x16(builder: &mut FunctionBuilder, a: Value, b: Value) -> Value {
+    let a_low = builder.ins().swiden_low(a);
+    let b_low = builder.ins().swiden_low(b);
+    let low = builder.ins().imul(a_low, b_low);
+    let a_high = builder.ins().swiden_high(a);
+    let b_high = builder.ins().swiden_high(b);
+    let high = builder.ins().imul(a_high, b_high);
+    // Gather the products of the even and odd lanes, to add them up.
+    let low = optionally_bitcast_vector(low, I8X16, builder);
+    let high = optionally_bitcast_vector(high, I8X16, builder);
+    let even_lanes: Vec<u8> = (0..8).flat_map(|lane| [4 * lane, 4 * lane + 1]).collect();
+    let odd_lanes: Vec<u8> = even_lanes.iter().map(|lane| lane + 2).collect();
+    let even_lanes = builder
+        .func
+        .dfg
+        .immediates
+        .push(ConstantData::from(even_lanes.as_ref()));
+    let odd_lanes = builder
+        .func
+        .dfg
+        .immediates
+        .push(ConstantData::from(odd_lanes.as_ref()));
+    let even = builder.ins().shuffle(low, high, even_lanes);
+    let odd = builder.ins().shuffle(low, high, odd_lanes);
+    let even = optionally_bitcast_vector(even, I16X8, builder);
+    let odd = optionally_bitcast_vector(odd, I16X8, builder);
+    builder.ins().iadd(even, odd)
+}
+
 /// Some SIMD operations only operate on I8X16 in CLIF; this will convert them to that type by
 /// adding a raw_bitcast if necessary.
 fn optionally_bitcast_vector(
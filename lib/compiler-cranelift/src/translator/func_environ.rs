@@ -12,10 +12,11 @@ use cranelift_codegen::ir::immediates::Offset32;
 use cranelift_codegen::ir::{self, InstBuilder};
 use cranelift_codegen::isa::TargetFrontendConfig;
 use cranelift_frontend::FunctionBuilder;
+use wasmer_compiler::wasm_unsupported;
 use wasmer_compiler::wasmparser::{HeapType, Operator};
 use wasmer_types::{
     FunctionIndex, FunctionType, GlobalIndex, LocalFunctionIndex, MemoryIndex, SignatureIndex,
-    TableIndex, Type as WasmerType, WasmResult,
+    TableIndex, TagIndex, TagType, Type as WasmerType, WasmResult,
 };
 
 /// The value of a WebAssembly global variable.
@@ -194,6 +195,118 @@ pub trait FuncEnvironment: TargetEnvironment {
         Ok(pos.ins().call(callee, call_args))
     }
 
+    /// Translate a `return_call` WebAssembly instruction at the current
+    /// block of `builder`.
+    ///
+    /// Insert instructions for a direct tail call to the function
+    /// `callee_index`, ending the current block. The function reference
+    /// `callee` was previously created by `make_direct_func()`.
+    fn translate_return_call(
+        &mut self,
+        _builder: &mut FunctionBuilder,
+        _callee_index: FunctionIndex,
+        _callee: ir::FuncRef,
+        _call_args: &[ir::Value],
+    ) -> WasmResult<()> {
+        Err(wasm_unsupported!("proposed tail-call operator return_call"))
+    }
+
+    /// Translate a `return_call_indirect` WebAssembly instruction at the
+    /// current block of `builder`.
+    ///
+    /// Insert instructions for a tail call to the function `callee` in the
+    /// table `table_index` with WebAssembly signature `sig_index`, ending the
+    /// current block. The `callee` value will have type `i32`.
+    #[allow(clippy::too_many_arguments)]
+    fn translate_return_call_indirect(
+        &mut self,
+        _builder: &mut FunctionBuilder,
+        _table_index: TableIndex,
+        _table: ir::Table,
+        _sig_index: SignatureIndex,
+        _sig_ref: ir::SigRef,
+        _callee: ir::Value,
+        _call_args: &[ir::Value],
+    ) -> WasmResult<()> {
+        Err(wasm_unsupported!(
+            "proposed tail-call operator return_call_indirect"
+        ))
+    }
+
+    /// Whether the pending exception of the store is checked after each
+    /// call, when the exception-handling proposal is enabled.
+    fn exceptions_enabled(&self) -> bool {
+        false
+    }
+
+    /// Insert instructions branching to `handler` if an exception is
+    /// pending after a call, continuing in a new block of `builder`
+    /// otherwise.
+    fn translate_exception_check(
+        &mut self,
+        _builder: &mut FunctionBuilder,
+        _handler: ir::Block,
+    ) -> WasmResult<()> {
+        // By default, exceptions aren't checked.
+        Ok(())
+    }
+
+    /// Translate a `throw` WebAssembly instruction, leaving an exception
+    /// of the tag `tag_index` carrying `args` pending. The caller branches
+    /// to the exception handler afterwards.
+    fn translate_throw(
+        &mut self,
+        _builder: &mut FunctionBuilder,
+        _tag_index: TagIndex,
+        _args: &[ir::Value],
+    ) -> WasmResult<()> {
+        Err(wasm_unsupported!(
+            "proposed exception-handling operator throw"
+        ))
+    }
+
+    /// Translate a `throw_ref` WebAssembly instruction, leaving the
+    /// exception `exnref` pending. The caller branches to the exception
+    /// handler afterwards.
+    fn translate_throw_ref(&mut self, _pos: FuncCursor, _exnref: ir::Value) -> WasmResult<()> {
+        Err(wasm_unsupported!(
+            "proposed exception-handling operator throw_ref"
+        ))
+    }
+
+    /// Insert instructions catching the pending exception if it has the tag
+    /// `tag_index`, or any tag if it is `None`.
+    ///
+    /// Return a reference to the caught exception, which is null if it
+    /// wasn't caught, and the values it carries.
+    fn translate_catch(
+        &mut self,
+        _builder: &mut FunctionBuilder,
+        _tag_index: Option<TagIndex>,
+    ) -> WasmResult<(ir::Value, Vec<ir::Value>)> {
+        Err(wasm_unsupported!(
+            "proposed exception-handling operator try_table"
+        ))
+    }
+
+    /// Whether the relaxed-SIMD operators get their deterministic
+    /// semantics, rather than the ones of the fastest instructions of the
+    /// target.
+    fn relaxed_simd_deterministic(&self) -> bool {
+        true
+    }
+
+    /// Whether the target has vector fused multiply-add instructions.
+    fn has_native_fma(&self) -> bool {
+        false
+    }
+
+    /// Whether the target is x86, whose SIMD instructions differ the most
+    /// from the deterministic semantics of the relaxed-SIMD operators.
+    fn is_x86(&self) -> bool {
+        false
+    }
+
     /// Translate a `memory.grow` WebAssembly instruction.
     ///
     /// The `index` provided identifies the linear memory to grow, and `heap` is the heap reference
@@ -439,6 +552,23 @@ pub trait FuncEnvironment: TargetEnvironment {
         count: ir::Value,
     ) -> WasmResult<ir::Value>;
 
+    /// Emit code reporting a memory access to the memory watch, before the
+    /// access. The `index` provided identifies the linear memory, `addr`
+    /// and `offset` the accessed address, and `value` is the value stored,
+    /// or `None` for loads.
+    fn translate_memory_watch(
+        &mut self,
+        _pos: FuncCursor,
+        _index: MemoryIndex,
+        _addr: ir::Value,
+        _offset: u64,
+        _size: u32,
+        _value: Option<ir::Value>,
+    ) -> WasmResult<()> {
+        // By default, memory accesses aren't watched.
+        Ok(())
+    }
+
     /// Emit code at the beginning of every wasm loop.
     ///
     /// This can be used to insert explicit interrupt or safepoint checking at
@@ -448,6 +578,26 @@ pub trait FuncEnvironment: TargetEnvironment {
         Ok(())
     }
 
+    /// Optional callback called before the body of the function is
+    /// translated, once its locals are declared.
+    fn before_translate_function(
+        &mut self,
+        _builder: &mut FunctionBuilder,
+        _state: &FuncTranslationState,
+    ) -> WasmResult<()> {
+        Ok(())
+    }
+
+    /// Optional callback called once the whole body of the function is
+    /// translated, before the function is finalized.
+    fn after_translate_function(
+        &mut self,
+        _builder: &mut FunctionBuilder,
+        _state: &FuncTranslationState,
+    ) -> WasmResult<()> {
+        Ok(())
+    }
+
     /// Optional callback for the `FunctionEnvMutironment` performing this translation to maintain
     /// internal state or prepare custom state for the operator to translate
     fn before_translate_operator(
@@ -495,4 +645,7 @@ pub trait FuncEnvironment: TargetEnvironment {
     /// Get the type of a function with the given signature index.
     #[allow(dead_code)]
     fn get_function_sig(&self, sig_index: SignatureIndex) -> Option<&FunctionType>;
+
+    /// Get the type of the tag with the given index.
+    fn get_tag_type(&self, tag_index: TagIndex) -> Option<TagType>;
 }
@@ -12,6 +12,7 @@
 use super::func_environ::{FuncEnvironment, GlobalVariable};
 use crate::{HashMap, Occupied, Vacant};
 use cranelift_codegen::ir::{self, Block, Inst, Value};
+use cranelift_frontend::FunctionBuilder;
 use std::vec::Vec;
 use wasmer_types::{
     FunctionIndex, GlobalIndex, MemoryIndex, SignatureIndex, TableIndex, WasmResult,
@@ -247,6 +248,14 @@ pub struct FuncTranslationState {
     // `FuncEnvironment::make_direct_func()`.
     // Stores both the function reference and the number of WebAssembly arguments
     functions: HashMap<FunctionIndex, (ir::FuncRef, usize)>,
+
+    /// The handlers of the enclosing `try_table`s, innermost last, with the height of the
+    /// control stack once their frame is pushed.
+    pub(crate) exception_handlers: Vec<(usize, Block)>,
+
+    /// The block returning from the function when a pending exception is not caught by any
+    /// enclosing `try_table`. Created on first use.
+    pub(crate) exception_exit: Option<Block>,
 }
 
 // Public methods that are exposed to non-`cranelift_wasm` API consumers.
@@ -273,6 +282,8 @@ impl FuncTranslationState {
             tables: HashMap::new(),
             signatures: HashMap::new(),
             functions: HashMap::new(),
+            exception_handlers: Vec::new(),
+            exception_exit: None,
         }
     }
 
@@ -285,6 +296,8 @@ impl FuncTranslationState {
         self.tables.clear();
         self.signatures.clear();
         self.functions.clear();
+        self.exception_handlers.clear();
+        self.exception_exit = None;
     }
 
     /// Initialize the state for compiling a function with the given signature.
@@ -303,6 +316,17 @@ impl FuncTranslationState {
         );
     }
 
+    /// Get the block a pending exception branches to: the handler of the innermost
+    /// `try_table`, or the block returning from the function.
+    pub(crate) fn exception_handler(&mut self, builder: &mut FunctionBuilder) -> Block {
+        match self.exception_handlers.last() {
+            Some((_, handler)) => *handler,
+            None => *self
+                .exception_exit
+                .get_or_insert_with(|| builder.create_block()),
+        }
+    }
+
     /// Push a value.
     pub(crate) fn push1(&mut self, val: Value) {
         self.stack.push(val);
@@ -10,7 +10,7 @@
 use super::code_translator::{bitcast_arguments, translate_operator, wasm_param_types};
 use super::func_environ::{FuncEnvironment, ReturnMode};
 use super::func_state::FuncTranslationState;
-use super::translation_utils::get_vmctx_value_label;
+use super::translation_utils::{get_vmctx_value_label, zero_value};
 use cranelift_codegen::entity::EntityRef;
 use cranelift_codegen::ir::{self, Block, InstBuilder, ValueLabel};
 use cranelift_codegen::timing;
@@ -107,6 +107,7 @@ impl FuncTranslator {
         self.state.initialize(&builder.func.signature, exit_block);
 
         parse_local_decls(reader, &mut builder, num_params, environ)?;
+        environ.before_translate_function(&mut builder, &self.state)?;
         parse_function_body(
             module_translation_state,
             reader,
@@ -114,6 +115,25 @@ impl FuncTranslator {
             &mut self.state,
             environ,
         )?;
+        if let Some(exception_exit) = self.state.exception_exit {
+            // An exception not caught in the function is left pending for
+            // the caller, which checks it in turn. The results are ignored.
+            builder.switch_to_block(exception_exit);
+            builder.seal_block(exception_exit);
+            let returns = builder
+                .func
+                .signature
+                .returns
+                .iter()
+                .map(|ret| ret.value_type)
+                .collect::<Vec<_>>();
+            let results = returns
+                .into_iter()
+                .map(|ty| zero_value(&mut builder, ty))
+                .collect::<Vec<_>>();
+            builder.ins().return_(&results);
+        }
+        environ.after_translate_function(&mut builder, &self.state)?;
 
         builder.finalize();
         Ok(())
@@ -195,7 +215,8 @@ fn declare_locals<FE: FuncEnvironment + ?Sized>(
             builder.ins().vconst(ir::types::I8X16, constant_handle)
         }
         Ref(ty) => {
-            if ty.is_func_ref() || ty.is_extern_ref() {
+            if ty.is_func_ref() || ty.is_extern_ref() || ty.heap_type() == wasmparser::HeapType::Exn
+            {
                 builder.ins().null(environ.reference_type())
             } else {
                 return Err(wasm_unsupported!("unsupported reference type: {:?}", ty));
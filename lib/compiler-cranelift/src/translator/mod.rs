@@ -8,7 +8,9 @@ mod translation_utils;
 mod unwind;
 
 pub use self::func_environ::{FuncEnvironment, GlobalVariable, TargetEnvironment};
+pub use self::func_state::FuncTranslationState;
 pub use self::func_translator::FuncTranslator;
+pub(crate) use self::translation_utils::zero_value;
 pub use self::translation_utils::{
     irlibcall_to_libcall, irreloc_to_relocationkind, signature_to_cranelift_ir, type_to_irtype,
 };
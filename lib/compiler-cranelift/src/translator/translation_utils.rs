@@ -4,7 +4,7 @@ use super::func_environ::TargetEnvironment;
 use crate::std::string::ToString;
 use core::u32;
 use cranelift_codegen::binemit::Reloc;
-use cranelift_codegen::ir::{self, AbiParam};
+use cranelift_codegen::ir::{self, AbiParam, InstBuilder};
 use cranelift_codegen::isa::TargetFrontendConfig;
 use cranelift_frontend::FunctionBuilder;
 use wasmer_compiler::wasmparser;
@@ -53,11 +53,31 @@ pub fn type_to_irtype(ty: Type, target_config: TargetFrontendConfig) -> WasmResu
         Type::F32 => Ok(ir::types::F32),
         Type::F64 => Ok(ir::types::F64),
         Type::V128 => Ok(ir::types::I8X16),
-        Type::ExternRef | Type::FuncRef => reference_type(target_config),
+        Type::ExternRef | Type::FuncRef | Type::ExceptionRef => reference_type(target_config),
         // ty => Err(wasm_unsupported!("type_to_type: wasm type {:?}", ty)),
     }
 }
 
+/// A zero value of type `ty`, or a null reference.
+pub(crate) fn zero_value(builder: &mut FunctionBuilder, ty: ir::Type) -> ir::Value {
+    if ty.is_ref() {
+        builder.ins().null(ty)
+    } else if ty.is_vector() {
+        let zero = builder
+            .func
+            .dfg
+            .constants
+            .insert(vec![0; ty.bytes() as usize].into());
+        builder.ins().vconst(ty, zero)
+    } else if ty == ir::types::F32 {
+        builder.ins().f32const(ir::immediates::Ieee32::with_bits(0))
+    } else if ty == ir::types::F64 {
+        builder.ins().f64const(ir::immediates::Ieee64::with_bits(0))
+    } else {
+        builder.ins().iconst(ty, 0)
+    }
+}
+
 /// Transform Cranelift LibCall into runtime LibCall
 pub fn irlibcall_to_libcall(libcall: ir::LibCall) -> LibCall {
     match libcall {
@@ -70,6 +90,8 @@ pub fn irlibcall_to_libcall(libcall: ir::LibCall) -> LibCall {
         ir::LibCall::TruncF64 => LibCall::TruncF64,
         ir::LibCall::NearestF32 => LibCall::NearestF32,
         ir::LibCall::NearestF64 => LibCall::NearestF64,
+        ir::LibCall::FmaF32 => LibCall::FmaF32,
+        ir::LibCall::FmaF64 => LibCall::FmaF64,
         _ => panic!("Unsupported libcall"),
     }
 }
@@ -111,7 +133,10 @@ pub fn block_with_params<'a, PE: TargetEnvironment + ?Sized>(
                 builder.append_block_param(block, ir::types::F64);
             }
             wasmparser::ValType::Ref(ty) => {
-                if ty.is_extern_ref() || ty.is_func_ref() {
+                if ty.is_extern_ref()
+                    || ty.is_func_ref()
+                    || ty.heap_type() == wasmparser::HeapType::Exn
+                {
                     builder.append_block_param(block, environ.reference_type());
                 } else {
                     return Err(WasmError::Unsupported(format!(
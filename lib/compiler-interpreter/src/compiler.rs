@@ -0,0 +1,312 @@
+//! Support for "compiling" to interpreter bytecode.
+
+use crate::config::Interpreter;
+use enumset::EnumSet;
+#[cfg(feature = "rayon")]
+use rayon::prelude::{IntoParallelIterator, ParallelIterator};
+use std::num::NonZeroUsize;
+use std::sync::Arc;
+use wasmer_compiler::wasmparser::{BinaryReader, BlockType, Operator};
+use wasmer_compiler::{
+    wptype_to_type, Compiler, FunctionBodyData, ModuleMiddleware, ModuleTranslationState,
+};
+use wasmer_types::entity::PrimaryMap;
+use wasmer_types::{
+    Compilation, CompileError, CompileModuleInfo, CompiledFunction, CompiledFunctionFrameInfo,
+    CpuFeature, FunctionAddressMap, FunctionBody, LocalFunctionIndex, ModuleInfo, SignatureIndex,
+    Target, Type, WasmError,
+};
+use wasmer_vm::interpreter::{encode_function, supports_operator, BlockInfo};
+
+/// A compiler that turns WebAssembly functions into bytecode for the
+/// interpreter in `wasmer-vm`.
+pub struct InterpreterCompiler {
+    config: Interpreter,
+}
+
+impl InterpreterCompiler {
+    /// Creates a new interpreter compiler
+    pub fn new(config: Interpreter) -> Self {
+        Self { config }
+    }
+}
+
+impl Compiler for InterpreterCompiler {
+    fn name(&self) -> &str {
+        "interpreter"
+    }
+
+    fn deterministic_id(&self) -> String {
+        let mut id = self.name().to_string();
+        if self.config.debugging {
+            id.push_str("-debugging");
+        }
+        id
+    }
+
+    /// Get the middlewares for this compiler
+    fn get_middlewares(&self) -> &[Arc<dyn ModuleMiddleware>] {
+        &self.config.middlewares
+    }
+
+    /// Compile the module to interpreter bytecode.
+    fn compile_module(
+        &self,
+        _target: &Target,
+        compile_info: &CompileModuleInfo,
+        _module_translation: &ModuleTranslationState,
+        function_body_inputs: PrimaryMap<LocalFunctionIndex, FunctionBodyData<'_>>,
+    ) -> Result<Compilation, CompileError> {
+        // The interpreter runs the original operator stream, so there is no
+        // way to apply the operators emitted by function middlewares.
+        if !self.config.middlewares.is_empty() {
+            return Err(CompileError::UnsupportedFeature(
+                "middlewares are not supported by the interpreter".to_string(),
+            ));
+        }
+        let module = &compile_info.module;
+        for signature in module.signatures.values() {
+            for ty in signature.params().iter().chain(signature.results()) {
+                check_type(*ty)?;
+            }
+        }
+        for global in module.globals.values() {
+            check_type(global.ty)?;
+        }
+
+        // Threads are left out: they are enabled by default in the CLI, and
+        // the atomic operators are rejected below like any other unsupported
+        // operator.
+        let features = &compile_info.features;
+        for (enabled, name) in [
+            (features.simd, "simd"),
+            (features.relaxed_simd, "relaxed simd"),
+            (features.memory64, "memory64"),
+            (features.exceptions, "exceptions"),
+            (features.tail_call, "tail calls"),
+        ] {
+            if enabled {
+                return Err(CompileError::UnsupportedFeature(format!(
+                    "{} is not supported by the interpreter",
+                    name
+                )));
+            }
+        }
+
+        let functions = in_thread_pool(self.config.num_threads, || {
+            function_body_inputs
+                .iter()
+                .collect::<Vec<(LocalFunctionIndex, &FunctionBodyData<'_>)>>()
+                .into_par_iter_if_rayon()
+                .map(|(i, input)| translate_function(module, i, input, self.config.debugging))
+                .collect::<Result<Vec<_>, CompileError>>()
+        })?
+        .into_iter()
+        .collect();
+
+        // Every call goes through the interpreter entry point, which the
+        // engine substitutes for these empty trampolines when loading.
+        let function_call_trampolines = module
+            .signatures
+            .keys()
+            .map(|_| empty_body())
+            .collect::<PrimaryMap<_, _>>();
+        let dynamic_function_trampolines = module
+            .imported_function_types()
+            .map(|_| empty_body())
+            .collect::<PrimaryMap<_, _>>();
+
+        Ok(Compilation {
+            functions,
+            custom_sections: PrimaryMap::new(),
+            function_call_trampolines,
+            dynamic_function_trampolines,
+            debug: None,
+        })
+    }
+
+    fn get_cpu_features_used(&self, _cpu_features: &EnumSet<CpuFeature>) -> EnumSet<CpuFeature> {
+        EnumSet::new()
+    }
+
+    fn is_interpreter(&self) -> bool {
+        true
+    }
+}
+
+fn empty_body() -> FunctionBody {
+    FunctionBody {
+        body: vec![],
+        unwind_info: None,
+    }
+}
+
+/// Rejects the types of values the interpreter can't hold in its 64-bit
+/// stack slots.
+fn check_type(ty: Type) -> Result<(), CompileError> {
+    match ty {
+        Type::V128 => Err(CompileError::UnsupportedFeature(
+            "v128 values are not supported by the interpreter".to_string(),
+        )),
+        Type::ExceptionRef => Err(CompileError::UnsupportedFeature(
+            "exnref values are not supported by the interpreter".to_string(),
+        )),
+        _ => Ok(()),
+    }
+}
+
+fn to_compile_error(error: wasmer_compiler::wasmparser::BinaryReaderError) -> CompileError {
+    CompileError::Wasm(WasmError::InvalidWebAssembly {
+        message: error.message().to_string(),
+        offset: error.offset(),
+    })
+}
+
+/// Returns the number of parameters and results of a block.
+fn block_arity(module: &ModuleInfo, ty: BlockType) -> (u16, u16) {
+    match ty {
+        BlockType::Empty => (0, 0),
+        BlockType::Type(_) => (0, 1),
+        BlockType::FuncType(index) => {
+            let signature = &module.signatures[SignatureIndex::from_u32(index)];
+            (
+                signature.params().len() as u16,
+                signature.results().len() as u16,
+            )
+        }
+    }
+}
+
+/// Validates the operators of a function and computes its block side table.
+fn translate_function(
+    module: &ModuleInfo,
+    local_index: LocalFunctionIndex,
+    input: &FunctionBodyData<'_>,
+    debugging: bool,
+) -> Result<CompiledFunction, CompileError> {
+    let mut reader = BinaryReader::new(input.data);
+    let mut num_locals = 0u32;
+    for _ in 0..reader.read_var_u32().map_err(to_compile_error)? {
+        num_locals += reader.read_var_u32().map_err(to_compile_error)?;
+        let ty = reader
+            .read::<wasmer_compiler::wasmparser::ValType>()
+            .map_err(to_compile_error)?;
+        check_type(wptype_to_type(ty)?)?;
+    }
+    let code = &input.data[reader.original_position()..];
+    let code_offset = input.module_offset + reader.original_position();
+    let mut reader = BinaryReader::new(code);
+
+    let mut blocks = Vec::new();
+    let mut open = Vec::new();
+    while !reader.eof() {
+        let position = reader.original_position() as u32;
+        let op = reader.read_operator().map_err(to_compile_error)?;
+        match op {
+            Operator::Block { blockty } | Operator::Loop { blockty } | Operator::If { blockty } => {
+                let (params, results) = block_arity(module, blockty);
+                open.push(blocks.len());
+                blocks.push(BlockInfo {
+                    start: position,
+                    else_: None,
+                    end: 0,
+                    params,
+                    results,
+                });
+            }
+            Operator::Else => {
+                if let Some(&block) = open.last() {
+                    blocks[block].else_ = Some(position);
+                }
+            }
+            Operator::End => {
+                // The last `end` closes the function itself, which has no
+                // entry in the side table.
+                if let Some(block) = open.pop() {
+                    blocks[block].end = reader.original_position() as u32;
+                }
+            }
+            op if !supports_operator(&op) => {
+                return Err(CompileError::UnsupportedFeature(format!(
+                    "operator {:?} at offset {} is not supported by the interpreter",
+                    op,
+                    code_offset + position as usize
+                )))
+            }
+            _ => {}
+        }
+    }
+
+    let index = module.func_index(local_index);
+    let body = encode_function(
+        index.as_u32(),
+        num_locals,
+        code_offset as u32,
+        debugging,
+        &blocks,
+        code,
+    );
+    let body_len = body.len();
+    Ok(CompiledFunction {
+        body: FunctionBody {
+            body,
+            unwind_info: None,
+        },
+        relocations: vec![],
+        frame_info: CompiledFunctionFrameInfo {
+            traps: vec![],
+            address_map: FunctionAddressMap {
+                body_len,
+                ..Default::default()
+            },
+        },
+    })
+}
+
+/// Runs `f` in a thread pool of `num_threads` threads, or in the global
+/// thread pool without a number of threads.
+#[cfg(feature = "rayon")]
+fn in_thread_pool<R: Send>(
+    num_threads: Option<NonZeroUsize>,
+    f: impl FnOnce() -> Result<R, CompileError> + Send,
+) -> Result<R, CompileError> {
+    match num_threads {
+        Some(num_threads) => {
+            let pool = rayon::ThreadPoolBuilder::new()
+                .num_threads(num_threads.get())
+                .build()
+                .map_err(|e| CompileError::Resource(e.to_string()))?;
+            pool.install(f)
+        }
+        None => f(),
+    }
+}
+
+/// Runs `f` on the current thread, as everything is compiled on it without
+/// rayon.
+#[cfg(not(feature = "rayon"))]
+fn in_thread_pool<R>(
+    _num_threads: Option<NonZeroUsize>,
+    f: impl FnOnce() -> Result<R, CompileError>,
+) -> Result<R, CompileError> {
+    f()
+}
+
+trait IntoParIterIfRayon {
+    type Output;
+    fn into_par_iter_if_rayon(self) -> Self::Output;
+}
+
+impl<T: Send> IntoParIterIfRayon for Vec<T> {
+    #[cfg(not(feature = "rayon"))]
+    type Output = std::vec::IntoIter<T>;
+    #[cfg(feature = "rayon")]
+    type Output = rayon::vec::IntoIter<T>;
+
+    fn into_par_iter_if_rayon(self) -> Self::Output {
+        #[cfg(not(feature = "rayon"))]
+        return self.into_iter();
+        #[cfg(feature = "rayon")]
+        return self.into_par_iter();
+    }
+}
@@ -0,0 +1,77 @@
+use crate::compiler::InterpreterCompiler;
+use std::num::NonZeroUsize;
+use std::sync::Arc;
+use wasmer_compiler::{Compiler, CompilerConfig, Engine, EngineBuilder, ModuleMiddleware};
+use wasmer_types::{Features, Target};
+
+/// Configuration for the interpreter backend.
+#[derive(Debug, Clone)]
+pub struct Interpreter {
+    pub(crate) num_threads: Option<NonZeroUsize>,
+    /// The middleware chain.
+    pub(crate) middlewares: Vec<Arc<dyn ModuleMiddleware>>,
+    pub(crate) debugging: bool,
+}
+
+impl Interpreter {
+    /// Creates a new configuration object with the default configuration
+    /// specified.
+    pub fn new() -> Self {
+        Self {
+            num_threads: None,
+            middlewares: vec![],
+            debugging: false,
+        }
+    }
+
+    /// Check for an attached debugger before each instruction of the
+    /// compiled functions. Other functions run without the check.
+    pub fn debugging(&mut self, enable: bool) -> &mut Self {
+        self.debugging = enable;
+        self
+    }
+}
+
+impl CompilerConfig for Interpreter {
+    fn enable_pic(&mut self) {
+        // Do nothing, the interpreter bytecode has no relocations.
+    }
+
+    fn enable_debugging(&mut self) {
+        self.debugging = true;
+    }
+
+    fn num_threads(&mut self, num_threads: Option<NonZeroUsize>) {
+        self.num_threads = num_threads;
+    }
+
+    /// Transform it into the compiler
+    fn compiler(self: Box<Self>) -> Box<dyn Compiler> {
+        Box::new(InterpreterCompiler::new(*self))
+    }
+
+    /// Gets the default features for this compiler in the given target
+    fn default_features_for_target(&self, _target: &Target) -> Features {
+        let mut features = Features::default();
+        features.simd(false);
+        features.threads(false);
+        features
+    }
+
+    /// Pushes a middleware onto the back of the middleware chain.
+    fn push_middleware(&mut self, middleware: Arc<dyn ModuleMiddleware>) {
+        self.middlewares.push(middleware);
+    }
+}
+
+impl Default for Interpreter {
+    fn default() -> Interpreter {
+        Self::new()
+    }
+}
+
+impl From<Interpreter> for Engine {
+    fn from(config: Interpreter) -> Self {
+        EngineBuilder::new(config).engine()
+    }
+}
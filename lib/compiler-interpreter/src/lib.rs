@@ -0,0 +1,14 @@
+//! A WebAssembly `Compiler` implementation that targets an interpreter.
+//!
+//! Instead of native code, the "compiled" functions are a bytecode run by
+//! the interpreter in `wasmer-vm`, so no memory ever needs to be executable.
+//! This makes it usable where JIT compilation is forbidden, at the cost of a
+//! much slower execution.
+
+#![cfg_attr(docsrs, feature(doc_cfg, doc_auto_cfg))]
+
+mod compiler;
+mod config;
+
+pub use crate::compiler::InterpreterCompiler;
+pub use crate::config::Interpreter;
@@ -0,0 +1,263 @@
+use wasmer::{
+    imports, Function, FunctionEnv, FunctionEnvMut, Instance, Module, Store, TypedFunction, Value,
+};
+use wasmer_compiler_interpreter::Interpreter;
+use wasmer_types::{CompileError, Features, TrapCode};
+
+fn store() -> Store {
+    Store::new(Interpreter::new())
+}
+
+#[test]
+fn control_flow_and_calls() -> anyhow::Result<()> {
+    let mut store = store();
+    let module = Module::new(
+        &store,
+        r#"
+(module
+  (func $fac (export "fac") (param i64) (result i64)
+    (if (result i64) (i64.eqz (local.get 0))
+      (then (i64.const 1))
+      (else
+        (i64.mul (local.get 0) (call $fac (i64.sub (local.get 0) (i64.const 1)))))))
+  (func (export "sum") (param $n i32) (result i32)
+    (local $acc i32)
+    (block $done
+      (loop $next
+        (br_if $done (i32.eqz (local.get $n)))
+        (local.set $acc (i32.add (local.get $acc) (local.get $n)))
+        (local.set $n (i32.sub (local.get $n) (i32.const 1)))
+        (br $next)))
+    (local.get $acc))
+  (func (export "pick") (param i32) (result i32)
+    (block (block (block
+      (br_table 0 1 2 (local.get 0)))
+      (return (i32.const 10)))
+      (return (i32.const 20)))
+    (i32.const 30))
+  (func (export "swap") (param i32 i64) (result i64 i32)
+    (local.get 1) (local.get 0)))
+"#,
+    )?;
+    let instance = Instance::new(&mut store, &module, &imports! {})?;
+
+    let fac: TypedFunction<i64, i64> = instance.exports.get_typed_function(&store, "fac")?;
+    assert_eq!(fac.call(&mut store, 20)?, 2432902008176640000);
+    let sum: TypedFunction<i32, i32> = instance.exports.get_typed_function(&store, "sum")?;
+    assert_eq!(sum.call(&mut store, 100)?, 5050);
+    let pick: TypedFunction<i32, i32> = instance.exports.get_typed_function(&store, "pick")?;
+    assert_eq!(pick.call(&mut store, 0)?, 10);
+    assert_eq!(pick.call(&mut store, 1)?, 20);
+    assert_eq!(pick.call(&mut store, 2)?, 30);
+    assert_eq!(pick.call(&mut store, 7)?, 30);
+    let swap = instance.exports.get_function("swap")?;
+    assert_eq!(
+        &*swap.call(&mut store, &[Value::I32(1), Value::I64(2)])?,
+        &[Value::I64(2), Value::I32(1)]
+    );
+    Ok(())
+}
+
+#[test]
+fn host_functions_and_memory() -> anyhow::Result<()> {
+    let mut store = store();
+    let module = Module::new(
+        &store,
+        r#"
+(module
+  (import "env" "add" (func $add (param i32 i32) (result i32)))
+  (import "env" "log" (func $log (param i32)))
+  (memory (export "memory") 1)
+  (data (i32.const 16) "\01\02\03\04")
+  (func (export "run") (result i32)
+    (call $log (i32.load (i32.const 16)))
+    (i32.store8 (i32.const 0) (i32.const 42))
+    (drop (memory.grow (i32.const 1)))
+    (call $add (memory.size) (i32.load8_u (i32.const 0)))))
+"#,
+    )?;
+    let env = FunctionEnv::new(&mut store, Vec::<i32>::new());
+    let imports = imports! {
+        "env" => {
+            "add" => Function::new_typed(&mut store, |a: i32, b: i32| a + b),
+            "log" => Function::new_typed_with_env(
+                &mut store,
+                &env,
+                |mut env: FunctionEnvMut<Vec<i32>>, value: i32| env.data_mut().push(value),
+            ),
+        }
+    };
+    let instance = Instance::new(&mut store, &module, &imports)?;
+
+    let run: TypedFunction<(), i32> = instance.exports.get_typed_function(&store, "run")?;
+    assert_eq!(run.call(&mut store)?, 44);
+    assert_eq!(env.as_ref(&store), &[0x04030201]);
+    let memory = instance.exports.get_memory("memory")?;
+    assert_eq!(memory.view(&store).size().0, 2);
+    Ok(())
+}
+
+#[test]
+fn traps() -> anyhow::Result<()> {
+    let mut store = store();
+    let module = Module::new(
+        &store,
+        r#"
+(module
+  (type $ii (func (param i32) (result i32)))
+  (memory 1)
+  (table 2 funcref)
+  (elem (i32.const 0) $id)
+  (func $id (param i32) (result i32) (local.get 0))
+  (func (export "div") (param i32 i32) (result i32)
+    (i32.div_s (local.get 0) (local.get 1)))
+  (func (export "load") (param i32) (result i32)
+    (i32.load (local.get 0)))
+  (func (export "call") (param i32) (result i32)
+    (call_indirect (type $ii) (i32.const 5) (local.get 0)))
+  (func $recurse (export "recurse") (result i32)
+    (i32.add (call $recurse) (i32.const 1))))
+"#,
+    )?;
+    let instance = Instance::new(&mut store, &module, &imports! {})?;
+
+    let div: TypedFunction<(i32, i32), i32> = instance.exports.get_typed_function(&store, "div")?;
+    assert_eq!(div.call(&mut store, 7, 2)?, 3);
+    let error = div.call(&mut store, 1, 0).unwrap_err();
+    assert_eq!(error.to_trap(), Some(TrapCode::IntegerDivisionByZero));
+    let error = div.call(&mut store, i32::MIN, -1).unwrap_err();
+    assert_eq!(error.to_trap(), Some(TrapCode::IntegerOverflow));
+
+    let load: TypedFunction<i32, i32> = instance.exports.get_typed_function(&store, "load")?;
+    let error = load.call(&mut store, 65534).unwrap_err();
+    assert_eq!(error.to_trap(), Some(TrapCode::HeapAccessOutOfBounds));
+
+    let call: TypedFunction<i32, i32> = instance.exports.get_typed_function(&store, "call")?;
+    assert_eq!(call.call(&mut store, 0)?, 5);
+    let error = call.call(&mut store, 1).unwrap_err();
+    assert_eq!(error.to_trap(), Some(TrapCode::IndirectCallToNull));
+    let error = call.call(&mut store, 2).unwrap_err();
+    assert_eq!(error.to_trap(), Some(TrapCode::TableAccessOutOfBounds));
+
+    let recurse: TypedFunction<(), i32> = instance.exports.get_typed_function(&store, "recurse")?;
+    let error = recurse.call(&mut store).unwrap_err();
+    assert_eq!(error.to_trap(), Some(TrapCode::StackOverflow));
+
+    // The instance is still usable after a trap.
+    assert_eq!(div.call(&mut store, 9, 3)?, 3);
+    Ok(())
+}
+
+#[test]
+fn serialized_artifacts_are_interpreted() -> anyhow::Result<()> {
+    let store = store();
+    let module = Module::new(
+        &store,
+        r#"(module (func (export "answer") (result i32) (i32.const 42)))"#,
+    )?;
+    let bytes = module.serialize()?;
+
+    let mut store = Store::new(wasmer::sys::EngineBuilder::headless());
+    let module = unsafe { Module::deserialize(&store, bytes)? };
+    let instance = Instance::new(&mut store, &module, &imports! {})?;
+    let answer: TypedFunction<(), i32> = instance.exports.get_typed_function(&store, "answer")?;
+    assert_eq!(answer.call(&mut store)?, 42);
+    Ok(())
+}
+
+#[test]
+fn unsupported_features_are_rejected() {
+    let store = store();
+    let error = Module::new(
+        &store,
+        r#"(module (func (param i32) (result i32) (return_call 0 (local.get 0))))"#,
+    )
+    .unwrap_err();
+    assert!(error.to_string().contains("tail"), "{}", error);
+}
+
+#[test]
+fn v128_values_are_rejected() {
+    let mut features = Features::default();
+    features.simd(true);
+    let engine = wasmer::sys::EngineBuilder::new(Interpreter::new()).set_features(Some(features));
+    let store = Store::new(engine);
+    for wat in [
+        r#"(module (func (param v128)))"#,
+        r#"(module (func (local v128)))"#,
+        r#"(module (global v128 (v128.const i64x2 0 0)))"#,
+    ] {
+        let error = Module::new(&store, wat).unwrap_err();
+        assert!(
+            matches!(error, CompileError::UnsupportedFeature(_)),
+            "{}: {}",
+            wat,
+            error
+        );
+    }
+}
+
+#[test]
+fn debugger_sees_and_changes_locals() -> anyhow::Result<()> {
+    use std::sync::{Arc, Mutex};
+    use wasmer_vm::{attach_debugger, detach_debugger, DebugFrame, Debugger};
+
+    struct Recorder {
+        module: Module,
+        offsets: Mutex<Vec<usize>>,
+    }
+
+    impl Debugger for Recorder {
+        fn on_instruction(&self, frame: &mut DebugFrame<'_>) {
+            // Other tests may run in the interpreter at the same time.
+            if frame.module().id != self.module.info().id {
+                return;
+            }
+            let mut offsets = self.offsets.lock().unwrap();
+            if offsets.is_empty() {
+                frame.locals_mut()[0] = 5;
+            }
+            offsets.push(frame.module_offset());
+        }
+    }
+
+    let wat = r#"(module (func (export "double") (param i32) (result i32)
+                   (i32.add (local.get 0) (local.get 0))))"#;
+    let mut debug_store = Store::new(Interpreter::new().debugging(true).clone());
+    let module = Module::new(&debug_store, wat)?;
+    let instance = Instance::new(&mut debug_store, &module, &imports! {})?;
+    let double: TypedFunction<i32, i32> = instance
+        .exports
+        .get_typed_function(&debug_store, "double")?;
+
+    let recorder = Arc::new(Recorder {
+        module: module.clone(),
+        offsets: Mutex::new(Vec::new()),
+    });
+    attach_debugger(recorder.clone());
+    let result = double.call(&mut debug_store, 1);
+    detach_debugger();
+
+    assert_eq!(result?, 10);
+    let offsets = recorder.offsets.lock().unwrap();
+    // Both `local.get`, `i32.add` and the final `end`.
+    assert_eq!(offsets.len(), 4);
+    assert!(offsets.windows(2).all(|pair| pair[0] < pair[1]));
+
+    // Functions compiled without debugging never call the debugger.
+    let mut store = store();
+    let module = Module::new(&store, wat)?;
+    let instance = Instance::new(&mut store, &module, &imports! {})?;
+    let double: TypedFunction<i32, i32> = instance.exports.get_typed_function(&store, "double")?;
+    let recorder = Arc::new(Recorder {
+        module: module.clone(),
+        offsets: Mutex::new(Vec::new()),
+    });
+    attach_debugger(recorder.clone());
+    let result = double.call(&mut store, 1);
+    detach_debugger();
+
+    assert_eq!(result?, 2);
+    assert!(recorder.offsets.lock().unwrap().is_empty());
+    Ok(())
+}
@@ -7,15 +7,15 @@ use inkwell::memory_buffer::MemoryBuffer;
 use inkwell::module::{Linkage, Module};
 use inkwell::targets::FileType;
 use inkwell::DLLStorageClass;
-use rayon::iter::ParallelBridge;
 use rayon::prelude::{IntoParallelIterator, IntoParallelRefIterator, ParallelIterator};
+use std::num::NonZeroUsize;
 use std::sync::Arc;
 use wasmer_compiler::{Compiler, FunctionBodyData, ModuleMiddleware, ModuleTranslationState};
 use wasmer_types::entity::{EntityRef, PrimaryMap};
 use wasmer_types::{
     Compilation, CompileError, CompileModuleInfo, CustomSection, CustomSectionProtection, Dwarf,
-    FunctionIndex, LocalFunctionIndex, RelocationTarget, SectionBody, SectionIndex, SignatureIndex,
-    Symbol, SymbolRegistry, Target,
+    FunctionIndex, LocalFunctionIndex, ModuleHash, RelocationTarget, SectionBody, SectionIndex,
+    SignatureIndex, Symbol, SymbolRegistry, Target,
 };
 
 //use std::sync::Mutex;
@@ -88,42 +88,58 @@ impl LLVMCompiler {
         let target_machine = self.config().target_machine(target);
         let ctx = Context::create();
 
-        // TODO: https:/github.com/rayon-rs/rayon/issues/822
-
-        let merged_bitcode = function_body_inputs.into_iter().par_bridge().map_init(
-            || {
-                let target_machine = self.config().target_machine(target);
-                FuncTranslator::new(target_machine)
-            },
-            |func_translator, (i, input)| {
-                let module = func_translator.translate_to_module(
-                    &compile_info.module,
-                    module_translation,
-                    &i,
-                    input,
-                    self.config(),
-                    &compile_info.memory_styles,
-                    &compile_info.table_styles,
-                    symbol_registry,
-                )?;
-                Ok(module.write_bitcode_to_memory().as_slice().to_vec())
-            },
-        );
+        // The iterators are indexed, rather than bridged with `par_bridge`,
+        // so that the modules are linked in the same order whatever the
+        // number of threads, and the object file is the same.
+        let merged_bitcode = function_body_inputs
+            .iter()
+            .collect::<Vec<_>>()
+            .into_par_iter()
+            .map_init(
+                || {
+                    let target_machine = self.config().target_machine(target);
+                    FuncTranslator::new(target_machine)
+                },
+                |func_translator, (i, input)| {
+                    let module = func_translator.translate_to_module(
+                        &compile_info.module,
+                        module_translation,
+                        &i,
+                        input,
+                        self.config(),
+                        &compile_info.memory_styles,
+                        &compile_info.table_styles,
+                        symbol_registry,
+                    )?;
+                    Ok(module.write_bitcode_to_memory().as_slice().to_vec())
+                },
+            );
 
-        let trampolines_bitcode = compile_info.module.signatures.iter().par_bridge().map_init(
-            || {
-                let target_machine = self.config().target_machine(target);
-                FuncTrampoline::new(target_machine)
-            },
-            |func_trampoline, (i, sig)| {
-                let name = symbol_registry.symbol_to_name(Symbol::FunctionCallTrampoline(i));
-                let module = func_trampoline.trampoline_to_module(sig, self.config(), &name)?;
-                Ok(module.write_bitcode_to_memory().as_slice().to_vec())
-            },
-        );
+        let trampolines_bitcode = compile_info
+            .module
+            .signatures
+            .iter()
+            .collect::<Vec<_>>()
+            .into_par_iter()
+            .map_init(
+                || {
+                    let target_machine = self.config().target_machine(target);
+                    FuncTrampoline::new(target_machine)
+                },
+                |func_trampoline, (i, sig)| {
+                    let name = symbol_registry.symbol_to_name(Symbol::FunctionCallTrampoline(i));
+                    let module = func_trampoline.trampoline_to_module(sig, self.config(), &name)?;
+                    Ok(module.write_bitcode_to_memory().as_slice().to_vec())
+                },
+            );
 
-        let dynamic_trampolines_bitcode =
-            compile_info.module.functions.iter().par_bridge().map_init(
+        let dynamic_trampolines_bitcode = compile_info
+            .module
+            .functions
+            .iter()
+            .collect::<Vec<_>>()
+            .into_par_iter()
+            .map_init(
                 || {
                     let target_machine = self.config().target_machine(target);
                     (
@@ -192,48 +208,29 @@ impl LLVMCompiler {
 
         Ok(memory_buffer.as_slice().to_vec())
     }
-}
-
-impl Compiler for LLVMCompiler {
-    fn name(&self) -> &str {
-        "llvm"
-    }
-
-    /// Get the middlewares for this compiler
-    fn get_middlewares(&self) -> &[Arc<dyn ModuleMiddleware>] {
-        &self.config.middlewares
-    }
 
-    fn experimental_native_compile_module(
-        &self,
-        target: &Target,
-        compile_info: &CompileModuleInfo,
-        module_translation: &ModuleTranslationState,
-        // The list of function bodies
-        function_body_inputs: &PrimaryMap<LocalFunctionIndex, FunctionBodyData<'_>>,
-        symbol_registry: &dyn SymbolRegistry,
-        // The metadata to inject into the wasmer_metadata section of the object file.
-        wasmer_metadata: &[u8],
-    ) -> Option<Result<Vec<u8>, CompileError>> {
-        Some(self.compile_native_object(
-            target,
-            compile_info,
-            module_translation,
-            function_body_inputs,
-            symbol_registry,
-            wasmer_metadata,
-        ))
-    }
-
-    /// Compile the module using LLVM, producing a compilation result with
-    /// associated relocations.
-    fn compile_module(
+    /// Compiles the module with the threads of the current thread pool.
+    fn compile_module_in_thread_pool(
         &self,
         target: &Target,
         compile_info: &CompileModuleInfo,
         module_translation: &ModuleTranslationState,
         function_body_inputs: PrimaryMap<LocalFunctionIndex, FunctionBodyData<'_>>,
     ) -> Result<Compilation, CompileError> {
+        // Exceptions thrown by callees must be checked after each call,
+        // which the generated code doesn't do.
+        if compile_info.features.exceptions {
+            return Err(CompileError::UnsupportedFeature(
+                "exceptions are not supported by the LLVM compiler".to_string(),
+            ));
+        }
+        // Memory accesses are always lowered with 32-bit addresses.
+        if compile_info.module.memories.values().any(|m| m.memory64) {
+            return Err(CompileError::UnsupportedFeature(
+                "64-bit memories are not supported by the LLVM compiler".to_string(),
+            ));
+        }
+
         //let data = Arc::new(Mutex::new(0));
         let memory_styles = &compile_info.memory_styles;
         let table_styles = &compile_info.table_styles;
@@ -373,3 +370,93 @@ impl Compiler for LLVMCompiler {
         })
     }
 }
+
+impl Compiler for LLVMCompiler {
+    fn name(&self) -> &str {
+        "llvm"
+    }
+
+    fn deterministic_id(&self) -> String {
+        let mut id = if self.config.relaxed_simd_deterministic {
+            format!("{}-relaxed-simd-deterministic", self.name())
+        } else {
+            self.name().to_string()
+        };
+        if self.config.enable_profile_instrumentation {
+            id.push_str("-instrumented");
+        }
+        if let Some(profile) = &self.config.profile {
+            id.push_str(&format!("-pgo-{}", ModuleHash::xxhash(profile.to_string())));
+        }
+        id
+    }
+
+    fn instruments_profile(&self) -> bool {
+        self.config.enable_profile_instrumentation
+    }
+
+    /// Get the middlewares for this compiler
+    fn get_middlewares(&self) -> &[Arc<dyn ModuleMiddleware>] {
+        &self.config.middlewares
+    }
+
+    fn experimental_native_compile_module(
+        &self,
+        target: &Target,
+        compile_info: &CompileModuleInfo,
+        module_translation: &ModuleTranslationState,
+        // The list of function bodies
+        function_body_inputs: &PrimaryMap<LocalFunctionIndex, FunctionBodyData<'_>>,
+        symbol_registry: &dyn SymbolRegistry,
+        // The metadata to inject into the wasmer_metadata section of the object file.
+        wasmer_metadata: &[u8],
+    ) -> Option<Result<Vec<u8>, CompileError>> {
+        Some(in_thread_pool(self.config.num_threads, || {
+            self.compile_native_object(
+                target,
+                compile_info,
+                module_translation,
+                function_body_inputs,
+                symbol_registry,
+                wasmer_metadata,
+            )
+        }))
+    }
+
+    /// Compile the module using LLVM, producing a compilation result with
+    /// associated relocations.
+    fn compile_module(
+        &self,
+        target: &Target,
+        compile_info: &CompileModuleInfo,
+        module_translation: &ModuleTranslationState,
+        function_body_inputs: PrimaryMap<LocalFunctionIndex, FunctionBodyData<'_>>,
+    ) -> Result<Compilation, CompileError> {
+        in_thread_pool(self.config.num_threads, || {
+            self.compile_module_in_thread_pool(
+                target,
+                compile_info,
+                module_translation,
+                function_body_inputs,
+            )
+        })
+    }
+}
+
+/// Runs `f` in a thread pool of `num_threads` threads, or in the global
+/// thread pool without a number of threads.
+fn in_thread_pool<R: Send>(
+    num_threads: Option<NonZeroUsize>,
+    f: impl FnOnce() -> Result<R, CompileError> + Send,
+) -> Result<R, CompileError> {
+    match num_threads {
+        Some(num_threads) => {
+            let pool = rayon::ThreadPoolBuilder::new()
+                .num_threads(num_threads.get())
+                .build()
+                .map_err(|e| CompileError::Resource(e.to_string()))?;
+            pool.install(f)
+        }
+        None => f(),
+    }
+}
@@ -6,10 +6,11 @@ use inkwell::targets::{
 pub use inkwell::OptimizationLevel as LLVMOptLevel;
 use itertools::Itertools;
 use std::fmt::Debug;
+use std::num::NonZeroUsize;
 use std::sync::Arc;
 use target_lexicon::Architecture;
 use wasmer_compiler::{Compiler, CompilerConfig, Engine, EngineBuilder, ModuleMiddleware};
-use wasmer_types::{FunctionType, LocalFunctionIndex, Target, Triple};
+use wasmer_types::{FunctionType, LocalFunctionIndex, Profile, Target, Triple};
 
 /// The InkWell ModuleInfo type
 pub type InkwellModule<'ctx> = inkwell::module::Module<'ctx>;
@@ -42,8 +43,12 @@ pub struct LLVM {
     pub(crate) enable_nan_canonicalization: bool,
     pub(crate) enable_verifier: bool,
     pub(crate) opt_level: LLVMOptLevel,
+    pub(crate) relaxed_simd_deterministic: bool,
     is_pic: bool,
     pub(crate) callbacks: Option<Arc<dyn LLVMCallbacks>>,
+    pub(crate) num_threads: Option<NonZeroUsize>,
+    pub(crate) enable_profile_instrumentation: bool,
+    pub(crate) profile: Option<Arc<Profile>>,
     /// The middleware chain.
     pub(crate) middlewares: Vec<Arc<dyn ModuleMiddleware>>,
 }
@@ -56,8 +61,12 @@ impl LLVM {
             enable_nan_canonicalization: false,
             enable_verifier: false,
             opt_level: LLVMOptLevel::Aggressive,
+            relaxed_simd_deterministic: false,
             is_pic: false,
             callbacks: None,
+            num_threads: None,
+            enable_profile_instrumentation: false,
+            profile: None,
             middlewares: vec![],
         }
     }
@@ -75,6 +84,18 @@ impl LLVM {
         self
     }
 
+    /// The profile recorded by a build of the module with profile
+    /// instrumentation, to optimize the module for the same workload.
+    ///
+    /// The call counts of the functions become their entry counts, and the
+    /// counts of the conditional branches their branch weights. Functions
+    /// that were never called are optimized for size and moved away from
+    /// the hot code.
+    pub fn profile(&mut self, profile: Option<Arc<Profile>>) -> &mut Self {
+        self.profile = profile;
+        self
+    }
+
     fn reloc_mode(&self) -> RelocMode {
         if self.is_pic {
             RelocMode::PIC
@@ -263,6 +284,18 @@ impl CompilerConfig for LLVM {
         self.enable_nan_canonicalization = enable;
     }
 
+    fn relaxed_simd_deterministic(&mut self, enable: bool) {
+        self.relaxed_simd_deterministic = enable;
+    }
+
+    fn enable_profile_instrumentation(&mut self) {
+        self.enable_profile_instrumentation = true;
+    }
+
+    fn num_threads(&mut self, num_threads: Option<NonZeroUsize>) {
+        self.num_threads = num_threads;
+    }
+
     /// Transform it into the compiler.
     fn compiler(self: Box<Self>) -> Box<dyn Compiler> {
         Box::new(LLVMCompiler::new(*self))
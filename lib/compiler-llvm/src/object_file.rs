@@ -45,6 +45,8 @@ where
     libcalls.insert("nearbyint".to_string(), LibCall::NearestF64);
     libcalls.insert("truncf".to_string(), LibCall::TruncF32);
     libcalls.insert("trunc".to_string(), LibCall::TruncF64);
+    libcalls.insert("fmaf".to_string(), LibCall::FmaF32);
+    libcalls.insert("fma".to_string(), LibCall::FmaF64);
     libcalls.insert("wasmer_vm_f32_ceil".to_string(), LibCall::CeilF32);
     libcalls.insert("wasmer_vm_f64_ceil".to_string(), LibCall::CeilF64);
     libcalls.insert("wasmer_vm_f32_floor".to_string(), LibCall::FloorF32);
@@ -53,6 +55,8 @@ where
     libcalls.insert("wasmer_vm_f64_nearest".to_string(), LibCall::NearestF64);
     libcalls.insert("wasmer_vm_f32_trunc".to_string(), LibCall::TruncF32);
     libcalls.insert("wasmer_vm_f64_trunc".to_string(), LibCall::TruncF64);
+    libcalls.insert("wasmer_vm_f32_fma".to_string(), LibCall::FmaF32);
+    libcalls.insert("wasmer_vm_f64_fma".to_string(), LibCall::FmaF64);
     libcalls.insert("wasmer_vm_memory32_size".to_string(), LibCall::Memory32Size);
     libcalls.insert(
         "wasmer_vm_imported_memory32_size".to_string(),
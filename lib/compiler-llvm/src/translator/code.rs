@@ -7,6 +7,7 @@ use super::{
 };
 use inkwell::{
     attributes::AttributeLoc,
+    basic_block::BasicBlock,
     builder::Builder,
     context::Context,
     module::{Linkage, Module},
@@ -204,6 +205,9 @@ impl FuncTranslator {
             symbol_registry,
             abi: &*self.abi,
             config,
+            local_func_index: *local_func_index,
+            profile_counts: vec![],
+            conditional_branches: vec![],
         };
         fcg.ctx.add_func(
             func_index,
@@ -212,6 +216,9 @@ impl FuncTranslator {
             fcg.ctx.basic(),
             &func_attrs,
         );
+        if config.enable_profile_instrumentation {
+            fcg.build_profile_count(fcg.intrinsics.i32_zero);
+        }
 
         while fcg.state.has_control_frames() {
             let pos = reader.current_position() as u32;
@@ -220,6 +227,7 @@ impl FuncTranslator {
         }
 
         fcg.finalize(wasm_fn_type)?;
+        fcg.finalize_profile();
 
         if let Some(ref callbacks) = config.callbacks {
             callbacks.preopt_ir(&function, &module);
@@ -828,6 +836,36 @@ impl<'ctx, 'a> LLVMFunctionCodeGenerator<'ctx, 'a> {
         self.v128_into_int_vec(value, info, self.intrinsics.i32x4_ty)
     }
 
+    /// Computes the dot products of the adjacent pairs of signed lanes of
+    /// `v1` and `v2`, wrapped to the lanes of `ty`, which are twice as wide.
+    fn pairwise_dot_product(
+        &self,
+        v1: VectorValue<'ctx>,
+        v2: VectorValue<'ctx>,
+        ty: VectorType<'ctx>,
+    ) -> VectorValue<'ctx> {
+        let lanes = ty.get_size() as usize;
+        let even_lanes = (0..lanes)
+            .map(|lane| self.intrinsics.i32_consts[2 * lane])
+            .collect::<Vec<_>>();
+        let odd_lanes = (0..lanes)
+            .map(|lane| self.intrinsics.i32_consts[2 * lane + 1])
+            .collect::<Vec<_>>();
+        let [even, odd] = [even_lanes, odd_lanes].map(|lanes| {
+            let [v1, v2] = [v1, v2].map(|v| {
+                let v = self.builder.build_shuffle_vector(
+                    v,
+                    v.get_type().get_undef(),
+                    VectorType::const_vector(&lanes),
+                    "",
+                );
+                self.builder.build_int_s_extend(v, ty, "")
+            });
+            self.builder.build_int_mul(v1, v2, "")
+        });
+        self.builder.build_int_add(even, odd, "")
+    }
+
     fn v128_into_i64x2(
         &self,
         value: BasicValueEnum<'ctx>,
@@ -1226,6 +1264,116 @@ impl<'ctx, 'a> LLVMFunctionCodeGenerator<'ctx, 'a> {
         self.builder.position_at_end(continue_block);
     }
 
+    /// Counts an event of the function in its profile. The number of
+    /// counters is set by `finalize_profile`.
+    fn build_profile_count(&mut self, counter: IntValue<'ctx>) {
+        let count_fn_ptr = self.ctx.profile_count(self.intrinsics);
+        let call = self.builder.build_indirect_call(
+            self.intrinsics.profile_count_ty,
+            count_fn_ptr,
+            &[
+                self.ctx.basic().into(),
+                self.intrinsics
+                    .i32_ty
+                    .const_int(self.local_func_index.as_u32().into(), false)
+                    .into(),
+                counter.into(),
+                self.intrinsics.i32_zero.into(),
+            ],
+            "",
+        );
+        self.profile_counts
+            .push(call.try_as_basic_value().right().unwrap());
+    }
+
+    /// Builds the conditional branch of an `if` or `br_if`, counting which
+    /// way it goes with profile instrumentation enabled.
+    fn build_profiled_conditional_branch(
+        &mut self,
+        cond_value: IntValue<'ctx>,
+        then_block: BasicBlock<'ctx>,
+        else_block: BasicBlock<'ctx>,
+    ) {
+        if self.config.enable_profile_instrumentation {
+            let branch = self.conditional_branches.len() as u64;
+            let counter = self.builder.build_select(
+                cond_value,
+                self.intrinsics.i32_ty.const_int(1 + 2 * branch, false),
+                self.intrinsics.i32_ty.const_int(2 + 2 * branch, false),
+                "",
+            );
+            self.build_profile_count(counter.into_int_value());
+        }
+        let branch = self
+            .builder
+            .build_conditional_branch(cond_value, then_block, else_block);
+        self.conditional_branches.push(branch);
+    }
+
+    /// Sets the number of counters of the profile instrumentation, and
+    /// annotates the function with the counts of the profile it is
+    /// optimized for, if any.
+    ///
+    /// Functions that were never called are marked cold, so that they are
+    /// optimized for size, and the ones called the most hot. The branch
+    /// weights move the blocks that were rarely run away from the hot path.
+    /// A profile that doesn't match the branches of the function, recorded
+    /// for another version of the module, is ignored.
+    fn finalize_profile(&self) {
+        let num_counters = 1 + 2 * self.conditional_branches.len() as u64;
+        let num_counters = self.intrinsics.i32_ty.const_int(num_counters, false);
+        for call in &self.profile_counts {
+            call.set_operand(3, num_counters);
+        }
+
+        let Some(profile) = &self.config.profile else {
+            return;
+        };
+        let prof = self.context.get_kind_id("prof");
+        let entry_count = match profile.function(self.local_func_index) {
+            Some(function) if function.num_branches() == self.conditional_branches.len() => {
+                for (index, branch) in self.conditional_branches.iter().enumerate() {
+                    let (taken, not_taken) = function.branch(index).unwrap();
+                    if taken == 0 && not_taken == 0 {
+                        continue;
+                    }
+                    // Branch weights are 32 bits, only their ratio matters.
+                    let scale = (taken.max(not_taken) >> 32) + 1;
+                    let weights = self.context.metadata_node(&[
+                        self.context.metadata_string("branch_weights").into(),
+                        self.intrinsics
+                            .i32_ty
+                            .const_int(taken / scale, false)
+                            .into(),
+                        self.intrinsics
+                            .i32_ty
+                            .const_int(not_taken / scale, false)
+                            .into(),
+                    ]);
+                    branch.set_metadata(weights, prof).unwrap();
+                }
+                function.entry_count()
+            }
+            Some(_) => return,
+            None => 0,
+        };
+
+        let entry_count_node = self.context.metadata_node(&[
+            self.context.metadata_string("function_entry_count").into(),
+            self.intrinsics.i64_ty.const_int(entry_count, false).into(),
+        ]);
+        self.function
+            .as_global_value()
+            .set_metadata(entry_count_node, prof);
+        if entry_count == 0 {
+            self.function
+                .add_attribute(AttributeLoc::Function, self.intrinsics.cold);
+        } else if entry_count.saturating_mul(100) >= profile.max_entry_count() {
+            self.function
+                .add_attribute(AttributeLoc::Function, self.intrinsics.hot);
+        }
+    }
+
     fn finalize(&mut self, wasm_fn_type: &FunctionType) -> Result<(), CompileError> {
         let func_type = self.function.get_type();
 
@@ -1388,6 +1536,13 @@ pub struct LLVMFunctionCodeGenerator<'ctx, 'a> {
     symbol_registry: &'a dyn SymbolRegistry,
     abi: &'a dyn Abi,
     config: &'a LLVM,
+    local_func_index: LocalFunctionIndex,
+    // The calls counting the events of the function in its profile, whose
+    // number of counters is only known once the function is translated.
+    profile_counts: Vec<InstructionValue<'ctx>>,
+    // The conditional branches of `if` and `br_if`, in the order of the
+    // code, matching the branch counters of the profile.
+    conditional_branches: Vec<InstructionValue<'ctx>>,
 }
 
 impl<'ctx, 'a> LLVMFunctionCodeGenerator<'ctx, 'a> {
@@ -1423,6 +1578,34 @@ impl<'ctx, 'a> LLVMFunctionCodeGenerator<'ctx, 'a> {
             }
         }
 
+        // These relaxed-SIMD operators are lowered with their deterministic
+        // semantics, which are the ones of other SIMD operators.
+        let op = match op {
+            Operator::I8x16RelaxedSwizzle => Operator::I8x16Swizzle,
+            Operator::I32x4RelaxedTruncF32x4S => Operator::I32x4TruncSatF32x4S,
+            Operator::I32x4RelaxedTruncF32x4U => Operator::I32x4TruncSatF32x4U,
+            Operator::I32x4RelaxedTruncF64x2SZero => Operator::I32x4TruncSatF64x2SZero,
+            Operator::I32x4RelaxedTruncF64x2UZero => Operator::I32x4TruncSatF64x2UZero,
+            Operator::I8x16RelaxedLaneselect
+            | Operator::I16x8RelaxedLaneselect
+            | Operator::I32x4RelaxedLaneselect
+            | Operator::I64x2RelaxedLaneselect => Operator::V128Bitselect,
+            Operator::I16x8RelaxedQ15mulrS => Operator::I16x8Q15MulrSatS,
+            Operator::F32x4RelaxedMin if self.config.relaxed_simd_deterministic => {
+                Operator::F32x4Min
+            }
+            Operator::F32x4RelaxedMax if self.config.relaxed_simd_deterministic => {
+                Operator::F32x4Max
+            }
+            Operator::F64x2RelaxedMin if self.config.relaxed_simd_deterministic => {
+                Operator::F64x2Min
+            }
+            Operator::F64x2RelaxedMax if self.config.relaxed_simd_deterministic => {
+                Operator::F64x2Max
+            }
+            op => op,
+        };
+
         match op {
             /***************************
              * Control Flow instructions.
@@ -1596,8 +1779,8 @@ impl<'ctx, 'a> LLVMFunctionCodeGenerator<'ctx, 'a> {
                     self.intrinsics.i32_zero,
                     "",
                 );
-                self.builder
-                    .build_conditional_branch(cond_value, *frame.br_dest(), else_block);
+                let br_dest = *frame.br_dest();
+                self.build_profiled_conditional_branch(cond_value, br_dest, else_block);
                 self.builder.position_at_end(else_block);
             }
             Operator::BrTable { ref targets } => {
@@ -1697,8 +1880,7 @@ impl<'ctx, 'a> LLVMFunctionCodeGenerator<'ctx, 'a> {
                     "",
                 );
 
-                self.builder
-                    .build_conditional_branch(cond_value, if_then_block, if_else_block);
+                self.build_profiled_conditional_branch(cond_value, if_then_block, if_else_block);
                 self.builder.position_at_end(if_else_block);
                 let block_param_types = self
                     .module_translation
@@ -3107,6 +3289,33 @@ impl<'ctx, 'a> LLVMFunctionCodeGenerator<'ctx, 'a> {
                 let res = self.builder.build_bitcast(res, self.intrinsics.i128_ty, "");
                 self.state.push1(res);
             }
+            Operator::I16x8RelaxedDotI8x16I7x16S => {
+                let ((v1, i1), (v2, i2)) = self.state.pop2_extra()?;
+                let (v1, _) = self.v128_into_i8x16(v1, i1);
+                let (v2, _) = self.v128_into_i8x16(v2, i2);
+                let res = self.pairwise_dot_product(v1, v2, self.intrinsics.i16x8_ty);
+                let res = self.builder.build_bitcast(res, self.intrinsics.i128_ty, "");
+                self.state.push1(res);
+            }
+            Operator::I32x4RelaxedDotI8x16I7x16AddS => {
+                let ((v1, i1), (v2, i2), (v3, i3)) = self.state.pop3_extra()?;
+                let (v1, _) = self.v128_into_i8x16(v1, i1);
+                let (v2, _) = self.v128_into_i8x16(v2, i2);
+                let (v3, _) = self.v128_into_i32x4(v3, i3);
+                let dot = self.pairwise_dot_product(v1, v2, self.intrinsics.i16x8_ty);
+                // Add up the adjacent pairs of lanes by multiplying them by one.
+                let ones = self.splat_vector(
+                    self.intrinsics
+                        .i16_ty
+                        .const_int(1, false)
+                        .as_basic_value_enum(),
+                    self.intrinsics.i16x8_ty,
+                );
+                let dot = self.pairwise_dot_product(dot, ones, self.intrinsics.i32x4_ty);
+                let res = self.builder.build_int_add(dot, v3, "");
+                let res = self.builder.build_bitcast(res, self.intrinsics.i128_ty, "");
+                self.state.push1(res);
+            }
             Operator::I32x4DotI16x8S => {
                 let ((v1, i1), (v2, i2)) = self.state.pop2_extra()?;
                 let (v1, _) = self.v128_into_i16x8(v1, i1);
@@ -4799,6 +5008,92 @@ impl<'ctx, 'a> LLVMFunctionCodeGenerator<'ctx, 'a> {
                 let res = self.builder.build_bitcast(res, self.intrinsics.i128_ty, "");
                 self.state.push1(res);
             }
+            Operator::F32x4RelaxedMadd | Operator::F32x4RelaxedNmadd => {
+                let ((v1, i1), (v2, i2), (v3, i3)) = self.state.pop3_extra()?;
+                let (v1, i1) = self.v128_into_f32x4(v1, i1);
+                let (v2, i2) = self.v128_into_f32x4(v2, i2);
+                let (v3, i3) = self.v128_into_f32x4(v3, i3);
+                let v1 = if matches!(op, Operator::F32x4RelaxedNmadd) {
+                    self.builder.build_float_neg(v1, "")
+                } else {
+                    v1
+                };
+                // `fmuladd` is only fused where it's faster.
+                let intrinsic = if self.config.relaxed_simd_deterministic {
+                    self.intrinsics.fma_f32x4
+                } else {
+                    self.intrinsics.fmuladd_f32x4
+                };
+                let res = self
+                    .builder
+                    .build_call(intrinsic, &[v1.into(), v2.into(), v3.into()], "")
+                    .try_as_basic_value()
+                    .left()
+                    .unwrap();
+                let res = self.builder.build_bitcast(res, self.intrinsics.i128_ty, "");
+                self.state.push1_extra(
+                    res,
+                    (i1.strip_pending() & i2.strip_pending() & i3.strip_pending())
+                        | ExtraInfo::pending_f32_nan(),
+                );
+            }
+            Operator::F64x2RelaxedMadd | Operator::F64x2RelaxedNmadd => {
+                let ((v1, i1), (v2, i2), (v3, i3)) = self.state.pop3_extra()?;
+                let (v1, i1) = self.v128_into_f64x2(v1, i1);
+                let (v2, i2) = self.v128_into_f64x2(v2, i2);
+                let (v3, i3) = self.v128_into_f64x2(v3, i3);
+                let v1 = if matches!(op, Operator::F64x2RelaxedNmadd) {
+                    self.builder.build_float_neg(v1, "")
+                } else {
+                    v1
+                };
+                let intrinsic = if self.config.relaxed_simd_deterministic {
+                    self.intrinsics.fma_f64x2
+                } else {
+                    self.intrinsics.fmuladd_f64x2
+                };
+                let res = self
+                    .builder
+                    .build_call(intrinsic, &[v1.into(), v2.into(), v3.into()], "")
+                    .try_as_basic_value()
+                    .left()
+                    .unwrap();
+                let res = self.builder.build_bitcast(res, self.intrinsics.i128_ty, "");
+                self.state.push1_extra(
+                    res,
+                    (i1.strip_pending() & i2.strip_pending() & i3.strip_pending())
+                        | ExtraInfo::pending_f64_nan(),
+                );
+            }
+            Operator::F32x4RelaxedMin | Operator::F32x4RelaxedMax => {
+                // a < b ? a : b, as `minps`, and a > b ? a : b, as `maxps`.
+                let ((v1, i1), (v2, i2)) = self.state.pop2_extra()?;
+                let (v1, _i1) = self.v128_into_f32x4(v1, i1);
+                let (v2, _i2) = self.v128_into_f32x4(v2, i2);
+                let predicate = if matches!(op, Operator::F32x4RelaxedMin) {
+                    FloatPredicate::OLT
+                } else {
+                    FloatPredicate::OGT
+                };
+                let cmp = self.builder.build_float_compare(predicate, v1, v2, "");
+                let res = self.builder.build_select(cmp, v1, v2, "");
+                let res = self.builder.build_bitcast(res, self.intrinsics.i128_ty, "");
+                self.state.push1(res);
+            }
+            Operator::F64x2RelaxedMin | Operator::F64x2RelaxedMax => {
+                let ((v1, i1), (v2, i2)) = self.state.pop2_extra()?;
+                let (v1, _i1) = self.v128_into_f64x2(v1, i1);
+                let (v2, _i2) = self.v128_into_f64x2(v2, i2);
+                let predicate = if matches!(op, Operator::F64x2RelaxedMin) {
+                    FloatPredicate::OLT
+                } else {
+                    FloatPredicate::OGT
+                };
+                let cmp = self.builder.build_float_compare(predicate, v1, v2, "");
+                let res = self.builder.build_select(cmp, v1, v2, "");
+                let res = self.builder.build_bitcast(res, self.intrinsics.i128_ty, "");
+                self.state.push1(res);
+            }
             Operator::F32x4PMin => {
                 // Pseudo-min: b < a ? b : a
                 let ((v1, i1), (v2, i2)) = self.state.pop2_extra()?;
@@ -41,7 +41,9 @@ pub fn type_to_llvm_ptr<'ctx>(
         Type::F64 => Ok(intrinsics.f64_ptr_ty),
         Type::V128 => Ok(intrinsics.i128_ptr_ty),
         Type::FuncRef => Ok(intrinsics.funcref_ty.ptr_type(AddressSpace::default())),
-        Type::ExternRef => Ok(intrinsics.externref_ty.ptr_type(AddressSpace::default())),
+        Type::ExternRef | Type::ExceptionRef => {
+            Ok(intrinsics.externref_ty.ptr_type(AddressSpace::default()))
+        }
     }
 }
 
@@ -56,7 +58,7 @@ pub fn type_to_llvm<'ctx>(
         Type::F64 => Ok(intrinsics.f64_ty.as_basic_type_enum()),
         Type::V128 => Ok(intrinsics.i128_ty.as_basic_type_enum()),
         Type::FuncRef => Ok(intrinsics.funcref_ty.as_basic_type_enum()),
-        Type::ExternRef => Ok(intrinsics.externref_ty.as_basic_type_enum()),
+        Type::ExternRef | Type::ExceptionRef => Ok(intrinsics.externref_ty.as_basic_type_enum()),
     }
 }
 
@@ -103,6 +105,11 @@ pub struct Intrinsics<'ctx> {
     pub sqrt_f32x4: FunctionValue<'ctx>,
     pub sqrt_f64x2: FunctionValue<'ctx>,
 
+    pub fma_f32x4: FunctionValue<'ctx>,
+    pub fma_f64x2: FunctionValue<'ctx>,
+    pub fmuladd_f32x4: FunctionValue<'ctx>,
+    pub fmuladd_f64x2: FunctionValue<'ctx>,
+
     pub cmp_f32: FunctionValue<'ctx>,
     pub cmp_f64: FunctionValue<'ctx>,
     pub cmp_f32x4: FunctionValue<'ctx>,
@@ -158,6 +165,8 @@ pub struct Intrinsics<'ctx> {
     pub personality: FunctionValue<'ctx>,
     pub readonly: Attribute,
     pub stack_probe: Attribute,
+    pub cold: Attribute,
+    pub hot: Attribute,
 
     pub void_ty: VoidType<'ctx>,
     pub i1_ty: IntType<'ctx>,
@@ -242,6 +251,7 @@ pub struct Intrinsics<'ctx> {
     pub imported_memory_fill: FunctionValue<'ctx>,
     pub memory_size_ty: FunctionType<'ctx>,
     pub memory_grow_ty: FunctionType<'ctx>,
+    pub profile_count_ty: FunctionType<'ctx>,
     pub memory_wait32: FunctionValue<'ctx>,
     pub memory_wait32_ty: FunctionType<'ctx>,
     pub imported_memory_wait32: FunctionValue<'ctx>,
@@ -275,6 +285,7 @@ pub struct Intrinsics<'ctx> {
     pub imported_memory32_wait64_ptr_ty: PointerType<'ctx>,
     pub memory32_notify_ptr_ty: PointerType<'ctx>,
     pub imported_memory32_notify_ptr_ty: PointerType<'ctx>,
+    pub profile_count_ptr_ty: PointerType<'ctx>,
 
     // Pointer to the VM.
     pub ctx_ptr_ty: PointerType<'ctx>,
@@ -403,6 +414,14 @@ impl<'ctx> Intrinsics<'ctx> {
             f32x4_ty.fn_type(&[f32x4_ty_basic_md, f32x4_ty_basic_md], false);
         let ret_f64x2_take_f64x2_f64x2 =
             f64x2_ty.fn_type(&[f64x2_ty_basic_md, f64x2_ty_basic_md], false);
+        let ret_f32x4_take_f32x4_f32x4_f32x4 = f32x4_ty.fn_type(
+            &[f32x4_ty_basic_md, f32x4_ty_basic_md, f32x4_ty_basic_md],
+            false,
+        );
+        let ret_f64x2_take_f64x2_f64x2_f64x2 = f64x2_ty.fn_type(
+            &[f64x2_ty_basic_md, f64x2_ty_basic_md, f64x2_ty_basic_md],
+            false,
+        );
 
         let ret_f64_take_f32_md = f64_ty.fn_type(&[f32_ty_basic_md, md_ty_basic_md], false);
         let ret_f32_take_f64_md_md =
@@ -507,6 +526,27 @@ impl<'ctx> Intrinsics<'ctx> {
             sqrt_f32x4: module.add_function("llvm.sqrt.v4f32", ret_f32x4_take_f32x4, None),
             sqrt_f64x2: module.add_function("llvm.sqrt.v2f64", ret_f64x2_take_f64x2, None),
 
+            fma_f32x4: module.add_function(
+                "llvm.fma.v4f32",
+                ret_f32x4_take_f32x4_f32x4_f32x4,
+                None,
+            ),
+            fma_f64x2: module.add_function(
+                "llvm.fma.v2f64",
+                ret_f64x2_take_f64x2_f64x2_f64x2,
+                None,
+            ),
+            fmuladd_f32x4: module.add_function(
+                "llvm.fmuladd.v4f32",
+                ret_f32x4_take_f32x4_f32x4_f32x4,
+                None,
+            ),
+            fmuladd_f64x2: module.add_function(
+                "llvm.fmuladd.v2f64",
+                ret_f64x2_take_f64x2_f64x2_f64x2,
+                None,
+            ),
+
             ceil_f32: module.add_function("llvm.ceil.f32", ret_f32_take_f32, None),
             ceil_f64: module.add_function("llvm.ceil.f64", ret_f64_take_f64, None),
             ceil_f32x4: module.add_function("llvm.ceil.v4f32", ret_f32x4_take_f32x4, None),
@@ -722,6 +762,8 @@ impl<'ctx> Intrinsics<'ctx> {
             readonly: context
                 .create_enum_attribute(Attribute::get_named_enum_kind_id("readonly"), 0),
             stack_probe: context.create_string_attribute("probe-stack", "inline-asm"),
+            cold: context.create_enum_attribute(Attribute::get_named_enum_kind_id("cold"), 0),
+            hot: context.create_enum_attribute(Attribute::get_named_enum_kind_id("hot"), 0),
 
             void_ty,
             i1_ty,
@@ -1011,6 +1053,15 @@ impl<'ctx> Intrinsics<'ctx> {
                 &[ctx_ptr_ty_basic_md, i32_ty_basic_md, i32_ty_basic_md],
                 false,
             ),
+            profile_count_ty: void_ty.fn_type(
+                &[
+                    ctx_ptr_ty_basic_md,
+                    i32_ty_basic_md,
+                    i32_ty_basic_md,
+                    i32_ty_basic_md,
+                ],
+                false,
+            ),
             data_drop: module.add_function(
                 "wasmer_vm_data_drop",
                 void_ty.fn_type(&[ctx_ptr_ty_basic_md, i32_ty_basic_md], false),
@@ -1245,6 +1296,17 @@ impl<'ctx> Intrinsics<'ctx> {
                     false,
                 )
                 .ptr_type(AddressSpace::default()),
+            profile_count_ptr_ty: void_ty
+                .fn_type(
+                    &[
+                        ctx_ptr_ty_basic_md,
+                        i32_ty_basic_md,
+                        i32_ty_basic_md,
+                        i32_ty_basic_md,
+                    ],
+                    false,
+                )
+                .ptr_type(AddressSpace::default()),
 
             ctx_ptr_ty,
         };
@@ -1311,6 +1373,7 @@ pub struct CtxType<'ctx, 'a> {
     cached_functions: HashMap<FunctionIndex, FunctionCache<'ctx>>,
     cached_memory_grow: HashMap<MemoryIndex, PointerValue<'ctx>>,
     cached_memory_size: HashMap<MemoryIndex, PointerValue<'ctx>>,
+    cached_profile_count: Option<PointerValue<'ctx>>,
 
     offsets: VMOffsets,
 }
@@ -1336,6 +1399,7 @@ impl<'ctx, 'a> CtxType<'ctx, 'a> {
             cached_functions: HashMap::new(),
             cached_memory_grow: HashMap::new(),
             cached_memory_size: HashMap::new(),
+            cached_profile_count: None,
 
             // TODO: pointer width
             offsets: VMOffsets::new(8, wasm_module),
@@ -1863,6 +1927,35 @@ impl<'ctx, 'a> CtxType<'ctx, 'a> {
         })
     }
 
+    pub fn profile_count(&mut self, intrinsics: &Intrinsics<'ctx>) -> PointerValue<'ctx> {
+        let (cached_profile_count, offsets, cache_builder, ctx_ptr_value) = (
+            &mut self.cached_profile_count,
+            &self.offsets,
+            &self.cache_builder,
+            &self.ctx_ptr_value,
+        );
+        *cached_profile_count.get_or_insert_with(|| {
+            let offset =
+                offsets.vmctx_builtin_function(VMBuiltinFunctionIndex::get_profile_count_index());
+            let offset = intrinsics.i32_ty.const_int(offset.into(), false);
+            let count_fn_ptr_ptr =
+                unsafe { cache_builder.build_gep(intrinsics.i8_ty, *ctx_ptr_value, &[offset], "") };
+
+            let count_fn_ptr_ptr = cache_builder
+                .build_bitcast(
+                    count_fn_ptr_ptr,
+                    intrinsics
+                        .profile_count_ptr_ty
+                        .ptr_type(AddressSpace::default()),
+                    "",
+                )
+                .into_pointer_value();
+            cache_builder
+                .build_load(intrinsics.profile_count_ptr_ty, count_fn_ptr_ptr, "")
+                .into_pointer_value()
+        })
+    }
+
     pub fn memory_size(
         &mut self,
         memory_index: MemoryIndex,
@@ -12,7 +12,7 @@ use smallvec::{smallvec, SmallVec};
 use std::cmp;
 use std::iter;
 use wasmer_compiler::wasmparser::{
-    BlockType as WpTypeOrFuncType, HeapType as WpHeapType, Operator, RefType as WpRefType,
+    BlockType as WpTypeOrFuncType, HeapType as WpHeapType, MemArg, Operator, RefType as WpRefType,
     ValType as WpType,
 };
 use wasmer_compiler::FunctionBodyData;
@@ -54,12 +54,20 @@ pub struct FuncGen<'a, M: Machine> {
     /// Types of local variables, including arguments.
     local_types: Vec<WpType>,
 
+    /// Index in `locals` of the first location of each local variable. A v128
+    /// local takes two locations, for its low and high 64-bit halves.
+    local_slots: Vec<usize>,
+
     /// Value stack.
     value_stack: Vec<Location<M::GPR, M::SIMD>>,
 
     /// Metadata about floating point values on the stack.
     fp_stack: Vec<FloatValue>,
 
+    /// Depths in the main value stack of the v128 values. A v128 value takes
+    /// two entries, its low 64-bit half and then its high one.
+    v128_stack: Vec<usize>,
+
     /// A list of frames describing the current control stack.
     control_stack: Vec<ControlFrame>,
 
@@ -88,6 +96,13 @@ pub struct FuncGen<'a, M: Machine> {
 
     /// Calling convention to use.
     calling_convention: CallingConvention,
+
+    /// Offset in the module of the operator being compiled.
+    srcloc: u32,
+
+    /// Distance below the local pointer of the copy of the locals passed to
+    /// the debugger, when compiling for debugging.
+    debug_area_offset: usize,
 }
 
 struct SpecialLabelSet {
@@ -204,12 +219,21 @@ impl<T> PopMany<T> for Vec<T> {
 
 trait WpTypeExt {
     fn is_float(&self) -> bool;
+    fn slots(&self) -> usize;
 }
 
 impl WpTypeExt for WpType {
     fn is_float(&self) -> bool {
         matches!(self, WpType::F32 | WpType::F64)
     }
+
+    /// Number of value stack entries (and local locations) taken by a value.
+    fn slots(&self) -> usize {
+        match self {
+            WpType::V128 => 2,
+            _ => 1,
+        }
+    }
 }
 
 #[derive(Debug, Clone)]
@@ -240,6 +264,7 @@ fn type_to_wp_type(ty: Type) -> WpType {
         Type::V128 => WpType::V128,
         Type::ExternRef => WpType::Ref(WpRefType::new(true, WpHeapType::Extern).unwrap()),
         Type::FuncRef => WpType::Ref(WpRefType::new(true, WpHeapType::Func).unwrap()),
+        Type::ExceptionRef => WpType::Ref(WpRefType::EXNREF),
     }
 }
 
@@ -553,19 +578,40 @@ impl<'a, M: Machine> FuncGen<'a, M> {
         // Add size of locals on stack.
         static_area_size += num_mem_slots * 8;
 
+        // The debugger gets a copy of all the locals, right below them.
+        if self.config.enable_debugging {
+            static_area_size += n * 8;
+            self.debug_area_offset = static_area_size;
+        }
+
         // Allocate save area, without actually writing to it.
         static_area_size = self.machine.round_stack_adjust(static_area_size);
 
+        // Number of locations taken by the parameters.
+        let num_param_slots: usize = sig
+            .params()
+            .iter()
+            .map(|&x| type_to_wp_type(x).slots())
+            .sum();
+
         // Stack probe.
         //
         // `rep stosq` writes data from low address to high address and may skip the stack guard page.
         // so here we probe it explicitly when needed.
-        for i in (sig.params().len()..n)
-            .step_by(NATIVE_PAGE_SIZE / 8)
-            .skip(1)
-        {
+        for i in (num_param_slots..n).step_by(NATIVE_PAGE_SIZE / 8).skip(1) {
             self.machine.zero_location(Size::S64, locations[i])?;
         }
+        if self.config.enable_debugging {
+            for i in (0..n).rev().step_by(NATIVE_PAGE_SIZE / 8) {
+                self.machine.zero_location(
+                    Size::S64,
+                    Location::Memory(
+                        self.machine.local_pointer(),
+                        (i * 8) as i32 - self.debug_area_offset as i32,
+                    ),
+                )?;
+            }
+        }
 
         self.machine.adjust_stack(static_area_size as _)?;
 
@@ -617,22 +663,28 @@ impl<'a, M: Machine> FuncGen<'a, M> {
         // Load in-register parameters into the allocated locations.
         // Locals are allocated on the stack from higher address to lower address,
         // so we won't skip the stack guard page here.
+        // A v128 parameter is passed as two 64-bit parameters, its low half first.
         let mut stack_offset: usize = 0;
-        for (i, param) in sig.params().iter().enumerate() {
-            let sz = match *param {
-                Type::I32 | Type::F32 => Size::S32,
-                Type::I64 | Type::F64 => Size::S64,
-                Type::ExternRef | Type::FuncRef => Size::S64,
+        let mut slot = 0;
+        for param in sig.params().iter() {
+            let (sz, slots) = match *param {
+                Type::I32 | Type::F32 => (Size::S32, 1),
+                Type::I64 | Type::F64 => (Size::S64, 1),
+                Type::ExternRef | Type::FuncRef => (Size::S64, 1),
+                Type::V128 => (Size::S64, 2),
                 _ => codegen_error!("singlepass init_local unimplemented"),
             };
-            let loc = self.machine.get_call_param_location(
-                i + 1,
-                sz,
-                &mut stack_offset,
-                calling_convention,
-            );
-            self.machine
-                .move_location_extend(sz, false, loc, Size::S64, locations[i])?;
+            for _ in 0..slots {
+                let loc = self.machine.get_call_param_location(
+                    slot + 1,
+                    sz,
+                    &mut stack_offset,
+                    calling_convention,
+                );
+                self.machine
+                    .move_location_extend(sz, false, loc, Size::S64, locations[slot])?;
+                slot += 1;
+            }
         }
 
         // Load vmctx into it's GPR.
@@ -646,7 +698,7 @@ impl<'a, M: Machine> FuncGen<'a, M> {
         // Initialize all normal locals to zero.
         let mut init_stack_loc_cnt = 0;
         let mut last_stack_loc = Location::Memory(self.machine.local_pointer(), i32::MAX);
-        for location in locations.iter().take(n).skip(sig.params().len()) {
+        for location in locations.iter().take(n).skip(num_param_slots) {
             match location {
                 Location::Memory(_, _) => {
                     init_stack_loc_cnt += 1;
@@ -697,6 +749,7 @@ impl<'a, M: Machine> FuncGen<'a, M> {
 
     /// Set the source location of the Wasm to the given offset.
     pub fn set_srcloc(&mut self, offset: u32) {
+        self.srcloc = offset;
         self.machine.set_srcloc(offset);
     }
 
@@ -715,6 +768,100 @@ impl<'a, M: Machine> FuncGen<'a, M> {
         self.get_location_released(loc)
     }
 
+    /// Acquires the locations of a new v128 value, and pushes it onto the value stack.
+    #[allow(clippy::type_complexity)]
+    fn push_v128(&mut self) -> Result<[Location<M::GPR, M::SIMD>; 2], CompileError> {
+        let depth = self.value_stack.len();
+        let locs = self.acquire_locations(
+            &[
+                (WpType::I64, MachineValue::WasmStack(depth)),
+                (WpType::I64, MachineValue::WasmStack(depth + 1)),
+            ],
+            false,
+        )?;
+        self.value_stack.extend_from_slice(&locs);
+        self.v128_stack.push(depth);
+        Ok([locs[0], locs[1]])
+    }
+
+    /// Is the value on top of the value stack a v128?
+    fn v128_on_top(&self) -> bool {
+        self.value_stack.len() >= 2 && self.v128_stack.last() == Some(&(self.value_stack.len() - 2))
+    }
+
+    /// Forgets the v128 values that have been popped from the value stack.
+    fn forget_popped_v128(&mut self) {
+        while let Some(&depth) = self.v128_stack.last() {
+            if depth + 2 <= self.value_stack.len() {
+                break;
+            }
+            self.v128_stack.pop();
+        }
+    }
+
+    /// Moves the value on top of the stack to the return registers, for a
+    /// branch or the end of a block.
+    fn emit_return_value(&mut self, ty: WpType) -> Result<(), CompileError> {
+        if ty == WpType::V128 {
+            let lo = self.value_stack[self.value_stack.len() - 2];
+            let hi = self.value_stack[self.value_stack.len() - 1];
+            self.machine.emit_relaxed_mov(
+                Size::S64,
+                lo,
+                Location::GPR(self.machine.get_gpr_for_ret()),
+            )?;
+            return self.machine.emit_relaxed_mov(
+                Size::S64,
+                hi,
+                Location::GPR(self.machine.get_gpr_for_ret_high()),
+            );
+        }
+        let loc = *self.value_stack.last().unwrap();
+        let canonicalize = if ty.is_float() {
+            let fp = self.fp_stack.peek1()?;
+            self.machine.arch_supports_canonicalize_nan()
+                && self.config.enable_nan_canonicalization
+                && fp.canonicalization.is_some()
+        } else {
+            false
+        };
+        self.machine
+            .emit_function_return_value(ty, canonicalize, loc)
+    }
+
+    /// Pushes the value returned by a call, or by a block, from the return registers.
+    fn push_return_value(&mut self, ty: WpType, from_call: bool) -> Result<(), CompileError> {
+        if ty == WpType::V128 {
+            let [lo, hi] = self.push_v128()?;
+            self.machine.move_location(
+                Size::S64,
+                Location::GPR(self.machine.get_gpr_for_ret()),
+                lo,
+            )?;
+            return self.machine.move_location(
+                Size::S64,
+                Location::GPR(self.machine.get_gpr_for_ret_high()),
+                hi,
+            );
+        }
+        let ret = self.acquire_locations(
+            &[(ty, MachineValue::WasmStack(self.value_stack.len()))],
+            false,
+        )?[0];
+        self.value_stack.push(ret);
+        if ty.is_float() {
+            self.fp_stack
+                .push(FloatValue::new(self.value_stack.len() - 1));
+        }
+        // Callees return floats in a SIMD register too.
+        let src = if from_call && ty.is_float() {
+            Location::SIMD(self.machine.get_simd_for_ret())
+        } else {
+            Location::GPR(self.machine.get_gpr_for_ret())
+        };
+        self.machine.move_location(Size::S64, src, ret)
+    }
+
     /// Prepare data for binary operator with 2 inputs and 1 output.
     fn i2o1_prepare(&mut self, ty: WpType) -> Result<I2O1<M::GPR, M::SIMD>, CompileError> {
         let loc_b = self.pop_value_released()?;
@@ -777,11 +924,14 @@ impl<'a, M: Machine> FuncGen<'a, M> {
         self.state.stack_values.push(MachineValue::ExplicitShadow);
 
         let params: Vec<_> = params.collect();
+        // A v128 parameter is passed as two 64-bit parameters, its low half first.
         let params_size: Vec<_> = params_type
-            .map(|x| match x {
-                WpType::F32 | WpType::I32 => Size::S32,
-                WpType::V128 => unimplemented!(),
-                _ => Size::S64,
+            .flat_map(|x| -> SmallVec<[Size; 2]> {
+                match x {
+                    WpType::F32 | WpType::I32 => smallvec![Size::S32],
+                    WpType::V128 => smallvec![Size::S64, Size::S64],
+                    _ => smallvec![Size::S64],
+                }
             })
             .collect();
 
@@ -999,6 +1149,194 @@ impl<'a, M: Machine> FuncGen<'a, M> {
         Ok(())
     }
 
+    /// Reports a load or a store to the memory watch, before the access.
+    ///
+    /// The address, and the value of stores, are still on the value stack.
+    /// Only accesses within the bounds of the watched ranges call into the
+    /// VM. Modules with 64-bit memories are rejected with the memory watch
+    /// enabled, so the address always has 32 bits.
+    fn emit_memory_watch(
+        &mut self,
+        memarg: &MemArg,
+        size: u32,
+        store: bool,
+    ) -> Result<(), CompileError> {
+        let addr = if store {
+            self.value_stack[self.value_stack.len() - 2]
+        } else {
+            self.value_stack[self.value_stack.len() - 1]
+        };
+
+        // Skip the call unless `address + offset .. + size` overlaps the
+        // bounds.
+        let skip = self.machine.get_label();
+        let tmp_access = self.machine.acquire_temp_gpr().unwrap();
+        let tmp_bound = self.machine.acquire_temp_gpr().unwrap();
+        let tmp_bounds = self.machine.acquire_temp_gpr().unwrap();
+        self.machine
+            .move_location(Size::S32, addr, Location::GPR(tmp_access))?;
+        if memarg.offset != 0 {
+            self.machine.move_location(
+                Size::S64,
+                Location::Imm64(memarg.offset),
+                Location::GPR(tmp_bound),
+            )?;
+            self.machine.location_add(
+                Size::S64,
+                Location::GPR(tmp_bound),
+                Location::GPR(tmp_access),
+                false,
+            )?;
+        }
+        self.machine.move_location(
+            Size::S64,
+            Location::Memory(
+                self.machine.get_vmctx_reg(),
+                self.vmoffsets.vmctx_memory_watch_pointer() as i32,
+            ),
+            Location::GPR(tmp_bounds),
+        )?;
+        self.machine.move_location(
+            Size::S64,
+            Location::Memory(tmp_bounds, 8),
+            Location::GPR(tmp_bound),
+        )?;
+        self.machine.location_cmp(
+            Size::S64,
+            Location::GPR(tmp_bound),
+            Location::GPR(tmp_access),
+        )?;
+        self.machine.jmp_on_aboveequal(skip)?;
+        self.machine.move_location(
+            Size::S64,
+            Location::Memory(tmp_bounds, 0),
+            Location::GPR(tmp_bound),
+        )?;
+        self.machine.location_add(
+            Size::S64,
+            Location::Imm32(size),
+            Location::GPR(tmp_access),
+            false,
+        )?;
+        self.machine.location_cmp(
+            Size::S64,
+            Location::GPR(tmp_bound),
+            Location::GPR(tmp_access),
+        )?;
+        self.machine.jmp_on_belowequal(skip)?;
+        self.machine.release_gpr(tmp_bounds);
+        self.machine.release_gpr(tmp_bound);
+        self.machine.release_gpr(tmp_access);
+
+        let function_index = self
+            .module
+            .func_index(LocalFunctionIndex::new(self.fsm.local_function_id));
+        let mut params = vec![
+            Location::Imm32(function_index.as_u32()),
+            Location::Imm32(memarg.memory),
+        ];
+        let mut params_type = vec![WpType::I32, WpType::I32];
+        let builtin = if store {
+            let value = self.value_stack[self.value_stack.len() - 1];
+            params.extend([addr, Location::Imm32(memarg.offset as u32)]);
+            params.extend([Location::Imm32(size), value]);
+            params_type.extend([WpType::I32, WpType::I32, WpType::I32, WpType::I64]);
+            VMBuiltinFunctionIndex::get_memory_watch_store_index()
+        } else {
+            params.extend([addr, Location::Imm32(memarg.offset as u32)]);
+            params.push(Location::Imm32(size));
+            params_type.extend([WpType::I32, WpType::I32, WpType::I32]);
+            VMBuiltinFunctionIndex::get_memory_watch_load_index()
+        };
+
+        self.machine.move_location(
+            Size::S64,
+            Location::Memory(
+                self.machine.get_vmctx_reg(),
+                self.vmoffsets.vmctx_builtin_function(builtin) as i32,
+            ),
+            Location::GPR(self.machine.get_grp_for_call()),
+        )?;
+        self.emit_call_native(
+            |this| {
+                this.machine
+                    .emit_call_register(this.machine.get_grp_for_call())
+            },
+            // [vmctx, function_index, memory_index, address, offset, size, value]
+            params.into_iter(),
+            params_type.into_iter(),
+        )?;
+        self.machine.emit_label(skip)
+    }
+
+    /// Calls the debugger hook before the current operator.
+    ///
+    /// The locals are copied to the debug area of the frame for the call,
+    /// and back after it, since the debugger may modify them. The copy is
+    /// written from its highest address down, so its pages are touched in
+    /// order and the stack guard page can't be skipped.
+    fn emit_debug_hook(&mut self) -> Result<(), CompileError> {
+        let local_pointer = self.machine.local_pointer();
+        let debug_area_offset = self.debug_area_offset as i32;
+        let slot = |i: usize| Location::Memory(local_pointer, (i * 8) as i32 - debug_area_offset);
+
+        let tmp = self.machine.acquire_temp_gpr().unwrap();
+        for (i, local) in self.locals.iter().enumerate().rev() {
+            self.machine
+                .move_location(Size::S64, *local, Location::GPR(tmp))?;
+            self.machine
+                .move_location(Size::S64, Location::GPR(tmp), slot(i))?;
+        }
+        self.machine.release_gpr(tmp);
+
+        let function_index = self
+            .module
+            .func_index(LocalFunctionIndex::new(self.fsm.local_function_id));
+        self.machine.move_location(
+            Size::S64,
+            Location::Memory(
+                self.machine.get_vmctx_reg(),
+                self.vmoffsets
+                    .vmctx_builtin_function(VMBuiltinFunctionIndex::get_debug_hook_index())
+                    as i32,
+            ),
+            Location::GPR(self.machine.get_grp_for_call()),
+        )?;
+        self.emit_call_native(
+            |this| {
+                this.machine
+                    .emit_call_register(this.machine.get_grp_for_call())
+            },
+            // [vmctx, function_index, module_offset, frame, locals_offset, num_locals]
+            [
+                Location::Imm32(function_index.as_u32()),
+                Location::Imm32(self.srcloc),
+                Location::GPR(local_pointer),
+                Location::Imm32(self.debug_area_offset as u32),
+                Location::Imm32(self.locals.len() as u32),
+            ]
+            .into_iter(),
+            [
+                WpType::I32,
+                WpType::I32,
+                WpType::I64,
+                WpType::I32,
+                WpType::I32,
+            ]
+            .into_iter(),
+        )?;
+
+        let tmp = self.machine.acquire_temp_gpr().unwrap();
+        for (i, local) in self.locals.iter().enumerate() {
+            self.machine
+                .move_location(Size::S64, slot(i), Location::GPR(tmp))?;
+            self.machine
+                .move_location(Size::S64, Location::GPR(tmp), *local)?;
+        }
+        self.machine.release_gpr(tmp);
+        Ok(())
+    }
+
     /// Emits a Native ABI call sequence, specialized for labels as the call target.
     fn _emit_call_native_label<
         I: Iterator<Item = Location<M::GPR, M::SIMD>>,
@@ -1017,9 +1355,14 @@ impl<'a, M: Machine> FuncGen<'a, M> {
         Ok(())
     }
 
+    /// Whether the given memory is indexed with 64-bit addresses.
+    fn memory64(&self, memory_index: MemoryIndex) -> bool {
+        self.module.memories[memory_index].memory64
+    }
+
     /// Emits a memory operation.
     fn op_memory<
-        F: FnOnce(&mut Self, bool, bool, i32, Label, Label) -> Result<(), CompileError>,
+        F: FnOnce(&mut Self, bool, bool, bool, i32, Label, Label) -> Result<(), CompileError>,
     >(
         &mut self,
         cb: F,
@@ -1028,6 +1371,7 @@ impl<'a, M: Machine> FuncGen<'a, M> {
             MemoryStyle::Static { .. } => false,
             MemoryStyle::Dynamic { .. } => true,
         };
+        let memory64 = self.memory64(MemoryIndex::new(0));
 
         let offset = if self.module.num_imported_memories != 0 {
             self.vmoffsets
@@ -1039,6 +1383,7 @@ impl<'a, M: Machine> FuncGen<'a, M> {
         cb(
             self,
             need_check,
+            memory64,
             self.module.num_imported_memories != 0,
             offset as i32,
             self.special_labels.heap_access_oob,
@@ -1064,8 +1409,9 @@ impl<'a, M: Machine> FuncGen<'a, M> {
         self.machine.emit_function_prolog()?;
 
         // Initialize locals.
+        let num_local_slots = self.local_types.iter().map(|x| x.slots()).sum();
         self.locals = self.init_locals(
-            self.local_types.len(),
+            num_local_slots,
             self.signature.clone(),
             self.calling_convention,
         )?;
@@ -1134,6 +1480,14 @@ impl<'a, M: Machine> FuncGen<'a, M> {
             .map(|&x| type_to_wp_type(x))
             .collect();
         local_types.extend_from_slice(local_types_excluding_arguments);
+        let local_slots = local_types
+            .iter()
+            .scan(0, |slot, ty| {
+                let first = *slot;
+                *slot += ty.slots();
+                Some(first)
+            })
+            .collect();
 
         let mut machine = machine;
         let special_labels = SpecialLabelSet {
@@ -1164,8 +1518,10 @@ impl<'a, M: Machine> FuncGen<'a, M> {
             signature,
             locals: vec![], // initialization deferred to emit_head
             local_types,
+            local_slots,
             value_stack: vec![],
             fp_stack: vec![],
+            v128_stack: vec![],
             control_stack: vec![],
             stack_offset: MachineStackOffset(0),
             save_area_offset: None,
@@ -1177,11 +1533,61 @@ impl<'a, M: Machine> FuncGen<'a, M> {
             relocations: vec![],
             special_labels,
             calling_convention,
+            srcloc: 0,
+            debug_area_offset: 0,
         };
+        // The stubs of lazily compiled modules jump to the compiled function
+        // through the same entry sequence.
+        if config.enable_tier_up || config.enable_lazy_compilation {
+            let tier = u32::from(vmoffsets.size_of_vmfunction_tier()) * local_func_index.as_u32();
+            let vmctx = fg.machine.get_simple_param_location(0, calling_convention);
+            fg.machine.emit_tier_up_entry(
+                vmctx,
+                vmoffsets.vmctx_function_tiers_pointer(),
+                tier + u32::from(vmoffsets.vmfunction_tier_redirect()),
+                tier + u32::from(vmoffsets.vmfunction_tier_calls()),
+            )?;
+        }
         fg.emit_head()?;
         Ok(fg)
     }
 
+    /// Emits the body of the stub of a lazily compiled function: it has the
+    /// function compiled, then calls it through its own entry, which jumps
+    /// to the compiled function from then on.
+    pub fn emit_lazy_stub(&mut self) -> Result<(), CompileError> {
+        self.machine.move_location(
+            Size::S64,
+            Location::Memory(
+                self.machine.get_vmctx_reg(),
+                self.vmoffsets
+                    .vmctx_builtin_function(VMBuiltinFunctionIndex::get_lazy_compile_index())
+                    as i32,
+            ),
+            Location::GPR(self.machine.get_grp_for_call()),
+        )?;
+        self.emit_call_native(
+            |this| {
+                this.machine
+                    .emit_call_register(this.machine.get_grp_for_call())
+            },
+            // [vmctx, local_function_index]
+            iter::once(Location::Imm32(self.fsm.local_function_id as u32)),
+            iter::once(WpType::I32),
+        )?;
+
+        let function_index = self
+            .module
+            .func_index(LocalFunctionIndex::new(self.fsm.local_function_id));
+        for local_index in 0..self.signature.params().len() as u32 {
+            self.feed_operator(Operator::LocalGet { local_index })?;
+        }
+        self.feed_operator(Operator::Call {
+            function_index: function_index.as_u32(),
+        })?;
+        self.feed_operator(Operator::End)
+    }
+
     pub fn has_control_frames(&self) -> bool {
         !self.control_stack.is_empty()
     }
@@ -1223,6 +1629,16 @@ impl<'a, M: Machine> FuncGen<'a, M> {
             was_unreachable = false;
         }
 
+        if self.config.enable_debugging {
+            self.emit_debug_hook()?;
+        }
+
+        if self.config.enable_memory_watch {
+            if let Some((memarg, size, store)) = memory_access(&op) {
+                self.emit_memory_watch(memarg, size, store)?;
+            }
+        }
+
         match op {
             Operator::GlobalGet { global_index } => {
                 let global_index = GlobalIndex::from_u32(global_index);
@@ -1231,11 +1647,18 @@ impl<'a, M: Machine> FuncGen<'a, M> {
                 if ty.is_float() {
                     self.fp_stack.push(FloatValue::new(self.value_stack.len()));
                 }
-                let loc = self.acquire_locations(
-                    &[(ty, MachineValue::WasmStack(self.value_stack.len()))],
-                    false,
-                )?[0];
-                self.value_stack.push(loc);
+                // The high half of a v128 global is right after its low half.
+                let (loc, loc_high) = if ty == WpType::V128 {
+                    let [lo, hi] = self.push_v128()?;
+                    (lo, Some(hi))
+                } else {
+                    let loc = self.acquire_locations(
+                        &[(ty, MachineValue::WasmStack(self.value_stack.len()))],
+                        false,
+                    )?[0];
+                    self.value_stack.push(loc);
+                    (loc, None)
+                };
 
                 let tmp = self.machine.acquire_temp_gpr().unwrap();
 
@@ -1263,6 +1686,10 @@ impl<'a, M: Machine> FuncGen<'a, M> {
                 };
 
                 self.machine.emit_relaxed_mov(Size::S64, src, loc)?;
+                if let Some(loc_high) = loc_high {
+                    self.machine
+                        .emit_relaxed_mov(Size::S64, Location::Memory(tmp, 8), loc_high)?;
+                }
 
                 self.machine.release_gpr(tmp);
             }
@@ -1292,6 +1719,12 @@ impl<'a, M: Machine> FuncGen<'a, M> {
                     Location::Memory(tmp, 0)
                 };
                 let ty = type_to_wp_type(self.module.globals[global_index].ty);
+                if ty == WpType::V128 {
+                    let hi = self.pop_value_released()?;
+                    self.forget_popped_v128();
+                    self.machine
+                        .emit_relaxed_mov(Size::S64, hi, Location::Memory(tmp, 8))?;
+                }
                 let loc = self.pop_value_released()?;
                 if ty.is_float() {
                     let fp = self.fp_stack.pop1()?;
@@ -1318,12 +1751,21 @@ impl<'a, M: Machine> FuncGen<'a, M> {
             }
             Operator::LocalGet { local_index } => {
                 let local_index = local_index as usize;
+                let slot = self.local_slots[local_index];
+                if self.local_types[local_index] == WpType::V128 {
+                    let [lo, hi] = self.push_v128()?;
+                    self.machine
+                        .emit_relaxed_mov(Size::S64, self.locals[slot], lo)?;
+                    self.machine
+                        .emit_relaxed_mov(Size::S64, self.locals[slot + 1], hi)?;
+                    return Ok(());
+                }
                 let ret = self.acquire_locations(
                     &[(WpType::I64, MachineValue::WasmStack(self.value_stack.len()))],
                     false,
                 )?[0];
                 self.machine
-                    .emit_relaxed_mov(Size::S64, self.locals[local_index], ret)?;
+                    .emit_relaxed_mov(Size::S64, self.locals[slot], ret)?;
                 self.value_stack.push(ret);
                 if self.local_types[local_index].is_float() {
                     self.fp_stack
@@ -1332,6 +1774,17 @@ impl<'a, M: Machine> FuncGen<'a, M> {
             }
             Operator::LocalSet { local_index } => {
                 let local_index = local_index as usize;
+                let slot = self.local_slots[local_index];
+                if self.local_types[local_index] == WpType::V128 {
+                    let hi = self.pop_value_released()?;
+                    let lo = self.pop_value_released()?;
+                    self.forget_popped_v128();
+                    self.machine
+                        .emit_relaxed_mov(Size::S64, lo, self.locals[slot])?;
+                    self.machine
+                        .emit_relaxed_mov(Size::S64, hi, self.locals[slot + 1])?;
+                    return Ok(());
+                }
                 let loc = self.pop_value_released()?;
 
                 if self.local_types[local_index].is_float() {
@@ -1347,19 +1800,29 @@ impl<'a, M: Machine> FuncGen<'a, M> {
                                 _ => codegen_error!("singlepass Operator::LocalSet unreachable"),
                             },
                             loc,
-                            self.locals[local_index],
+                            self.locals[slot],
                         )
                     } else {
                         self.machine
-                            .emit_relaxed_mov(Size::S64, loc, self.locals[local_index])
+                            .emit_relaxed_mov(Size::S64, loc, self.locals[slot])
                     }
                 } else {
                     self.machine
-                        .emit_relaxed_mov(Size::S64, loc, self.locals[local_index])
+                        .emit_relaxed_mov(Size::S64, loc, self.locals[slot])
                 }?;
             }
             Operator::LocalTee { local_index } => {
                 let local_index = local_index as usize;
+                let slot = self.local_slots[local_index];
+                if self.local_types[local_index] == WpType::V128 {
+                    let lo = self.value_stack[self.value_stack.len() - 2];
+                    let hi = self.value_stack[self.value_stack.len() - 1];
+                    self.machine
+                        .emit_relaxed_mov(Size::S64, lo, self.locals[slot])?;
+                    self.machine
+                        .emit_relaxed_mov(Size::S64, hi, self.locals[slot + 1])?;
+                    return Ok(());
+                }
                 let loc = *self.value_stack.last().unwrap();
 
                 if self.local_types[local_index].is_float() {
@@ -1375,15 +1838,15 @@ impl<'a, M: Machine> FuncGen<'a, M> {
                                 _ => codegen_error!("singlepass Operator::LocalTee unreachable"),
                             },
                             loc,
-                            self.locals[local_index],
+                            self.locals[slot],
                         )
                     } else {
                         self.machine
-                            .emit_relaxed_mov(Size::S64, loc, self.locals[local_index])
+                            .emit_relaxed_mov(Size::S64, loc, self.locals[slot])
                     }
                 } else {
                     self.machine
-                        .emit_relaxed_mov(Size::S64, loc, self.locals[local_index])
+                        .emit_relaxed_mov(Size::S64, loc, self.locals[slot])
                 }?;
             }
             Operator::I32Const { value } => {
@@ -2633,10 +3096,12 @@ impl<'a, M: Machine> FuncGen<'a, M> {
                 let return_types: SmallVec<[WpType; 1]> =
                     sig.results().iter().cloned().map(type_to_wp_type).collect();
 
+                let param_slots: usize = param_types.iter().map(|x| x.slots()).sum();
                 let params: SmallVec<[_; 8]> = self
                     .value_stack
-                    .drain(self.value_stack.len() - param_types.len()..)
+                    .drain(self.value_stack.len() - param_slots..)
                     .collect();
+                self.forget_popped_v128();
                 self.release_locations_only_regs(&params)?;
 
                 self.release_locations_only_osr_state(params.len())?;
@@ -2691,29 +3156,7 @@ impl<'a, M: Machine> FuncGen<'a, M> {
                 self.release_locations_only_stack(&params)?;
 
                 if !return_types.is_empty() {
-                    let ret = self.acquire_locations(
-                        &[(
-                            return_types[0],
-                            MachineValue::WasmStack(self.value_stack.len()),
-                        )],
-                        false,
-                    )?[0];
-                    self.value_stack.push(ret);
-                    if return_types[0].is_float() {
-                        self.machine.move_location(
-                            Size::S64,
-                            Location::SIMD(self.machine.get_simd_for_ret()),
-                            ret,
-                        )?;
-                        self.fp_stack
-                            .push(FloatValue::new(self.value_stack.len() - 1));
-                    } else {
-                        self.machine.move_location(
-                            Size::S64,
-                            Location::GPR(self.machine.get_gpr_for_ret()),
-                            ret,
-                        )?;
-                    }
+                    self.push_return_value(return_types[0], true)?;
                 }
             }
             Operator::CallIndirect {
@@ -2733,10 +3176,12 @@ impl<'a, M: Machine> FuncGen<'a, M> {
 
                 let func_index = self.pop_value_released()?;
 
+                let param_slots: usize = param_types.iter().map(|x| x.slots()).sum();
                 let params: SmallVec<[_; 8]> = self
                     .value_stack
-                    .drain(self.value_stack.len() - param_types.len()..)
+                    .drain(self.value_stack.len() - param_slots..)
                     .collect();
+                self.forget_popped_v128();
                 self.release_locations_only_regs(&params)?;
 
                 // Pop arguments off the FP stack and canonicalize them if needed.
@@ -2920,29 +3365,7 @@ impl<'a, M: Machine> FuncGen<'a, M> {
                 self.release_locations_only_stack(&params)?;
 
                 if !return_types.is_empty() {
-                    let ret = self.acquire_locations(
-                        &[(
-                            return_types[0],
-                            MachineValue::WasmStack(self.value_stack.len()),
-                        )],
-                        false,
-                    )?[0];
-                    self.value_stack.push(ret);
-                    if return_types[0].is_float() {
-                        self.machine.move_location(
-                            Size::S64,
-                            Location::SIMD(self.machine.get_simd_for_ret()),
-                            ret,
-                        )?;
-                        self.fp_stack
-                            .push(FloatValue::new(self.value_stack.len() - 1));
-                    } else {
-                        self.machine.move_location(
-                            Size::S64,
-                            Location::GPR(self.machine.get_gpr_for_ret()),
-                            ret,
-                        )?;
-                    }
+                    self.push_return_value(return_types[0], true)?;
                 }
             }
             Operator::If { blockty } => {
@@ -2979,17 +3402,7 @@ impl<'a, M: Machine> FuncGen<'a, M> {
 
                 if !was_unreachable && !frame.returns.is_empty() {
                     let first_return = frame.returns[0];
-                    let loc = *self.value_stack.last().unwrap();
-                    let canonicalize = if first_return.is_float() {
-                        let fp = self.fp_stack.peek1()?;
-                        self.machine.arch_supports_canonicalize_nan()
-                            && self.config.enable_nan_canonicalization
-                            && fp.canonicalization.is_some()
-                    } else {
-                        false
-                    };
-                    self.machine
-                        .emit_function_return_value(first_return, canonicalize, loc)?;
+                    self.emit_return_value(first_return)?;
                 }
 
                 let frame = &self.control_stack.last_mut().unwrap();
@@ -2998,6 +3411,7 @@ impl<'a, M: Machine> FuncGen<'a, M> {
                 self.release_locations_value(stack_depth)?;
                 self.value_stack.truncate(stack_depth);
                 self.fp_stack.truncate(fp_depth);
+                self.forget_popped_v128();
                 let frame = &mut self.control_stack.last_mut().unwrap();
 
                 match frame.if_else {
@@ -3017,6 +3431,35 @@ impl<'a, M: Machine> FuncGen<'a, M> {
             // be done with TypedSelect. But otherwise they're the same.
             Operator::TypedSelect { .. } | Operator::Select => {
                 let cond = self.pop_value_released()?;
+                if self.v128_on_top() {
+                    let b_hi = self.pop_value_released()?;
+                    let b_lo = self.pop_value_released()?;
+                    let a_hi = self.pop_value_released()?;
+                    let a_lo = self.pop_value_released()?;
+                    self.forget_popped_v128();
+                    let [lo, hi] = self.push_v128()?;
+
+                    let end_label = self.machine.get_label();
+                    let zero_label = self.machine.get_label();
+
+                    self.machine
+                        .emit_relaxed_cmp(Size::S32, Location::Imm32(0), cond)?;
+                    self.machine.jmp_on_equal(zero_label)?;
+                    for (src, dst) in [(a_lo, lo), (a_hi, hi)] {
+                        if src != dst {
+                            self.machine.emit_relaxed_mov(Size::S64, src, dst)?;
+                        }
+                    }
+                    self.machine.jmp_unconditionnal(end_label)?;
+                    self.machine.emit_label(zero_label)?;
+                    for (src, dst) in [(b_lo, lo), (b_hi, hi)] {
+                        if src != dst {
+                            self.machine.emit_relaxed_mov(Size::S64, src, dst)?;
+                        }
+                    }
+                    self.machine.emit_label(end_label)?;
+                    return Ok(());
+                }
                 let v_b = self.pop_value_released()?;
                 let v_a = self.pop_value_released()?;
                 let cncl: Option<(Option<CanonicalizeType>, Option<CanonicalizeType>)> =
@@ -3130,10 +3573,18 @@ impl<'a, M: Machine> FuncGen<'a, M> {
                     Location::Memory(
                         self.machine.get_vmctx_reg(),
                         self.vmoffsets.vmctx_builtin_function(
-                            if self.module.local_memory_index(memory_index).is_some() {
-                                VMBuiltinFunctionIndex::get_memory32_size_index()
-                            } else {
-                                VMBuiltinFunctionIndex::get_imported_memory32_size_index()
+                            match (
+                                self.module.local_memory_index(memory_index).is_some(),
+                                self.memory64(memory_index),
+                            ) {
+                                (true, false) => VMBuiltinFunctionIndex::get_memory32_size_index(),
+                                (false, false) => {
+                                    VMBuiltinFunctionIndex::get_imported_memory32_size_index()
+                                }
+                                (true, true) => VMBuiltinFunctionIndex::get_memory64_size_index(),
+                                (false, true) => {
+                                    VMBuiltinFunctionIndex::get_imported_memory64_size_index()
+                                }
                             },
                         ) as i32,
                     ),
@@ -3169,9 +3620,13 @@ impl<'a, M: Machine> FuncGen<'a, M> {
                     Size::S64,
                     Location::Memory(
                         self.machine.get_vmctx_reg(),
-                        self.vmoffsets
-                            .vmctx_builtin_function(VMBuiltinFunctionIndex::get_memory_init_index())
-                            as i32,
+                        self.vmoffsets.vmctx_builtin_function(
+                            if self.memory64(MemoryIndex::from_u32(mem)) {
+                                VMBuiltinFunctionIndex::get_memory64_init_index()
+                            } else {
+                                VMBuiltinFunctionIndex::get_memory_init_index()
+                            },
+                        ) as i32,
                     ),
                     Location::GPR(self.machine.get_grp_for_call()),
                 )?;
@@ -3237,18 +3692,15 @@ impl<'a, M: Machine> FuncGen<'a, M> {
                 self.release_locations_only_regs(&[len, src_pos, dst_pos])?;
 
                 let memory_index = MemoryIndex::new(src_mem as usize);
-                let (memory_copy_index, memory_index) =
-                    if self.module.local_memory_index(memory_index).is_some() {
-                        (
-                            VMBuiltinFunctionIndex::get_memory_copy_index(),
-                            memory_index,
-                        )
-                    } else {
-                        (
-                            VMBuiltinFunctionIndex::get_imported_memory_copy_index(),
-                            memory_index,
-                        )
-                    };
+                let memory_copy_index = match (
+                    self.module.local_memory_index(memory_index).is_some(),
+                    self.memory64(memory_index),
+                ) {
+                    (true, false) => VMBuiltinFunctionIndex::get_memory_copy_index(),
+                    (false, false) => VMBuiltinFunctionIndex::get_imported_memory_copy_index(),
+                    (true, true) => VMBuiltinFunctionIndex::get_memory64_copy_index(),
+                    (false, true) => VMBuiltinFunctionIndex::get_imported_memory64_copy_index(),
+                };
 
                 self.machine.move_location(
                     Size::S64,
@@ -3289,18 +3741,15 @@ impl<'a, M: Machine> FuncGen<'a, M> {
                 self.release_locations_only_regs(&[len, val, dst])?;
 
                 let memory_index = MemoryIndex::new(mem as usize);
-                let (memory_fill_index, memory_index) =
-                    if self.module.local_memory_index(memory_index).is_some() {
-                        (
-                            VMBuiltinFunctionIndex::get_memory_fill_index(),
-                            memory_index,
-                        )
-                    } else {
-                        (
-                            VMBuiltinFunctionIndex::get_imported_memory_fill_index(),
-                            memory_index,
-                        )
-                    };
+                let memory_fill_index = match (
+                    self.module.local_memory_index(memory_index).is_some(),
+                    self.memory64(memory_index),
+                ) {
+                    (true, false) => VMBuiltinFunctionIndex::get_memory_fill_index(),
+                    (false, false) => VMBuiltinFunctionIndex::get_imported_memory_fill_index(),
+                    (true, true) => VMBuiltinFunctionIndex::get_memory64_fill_index(),
+                    (false, true) => VMBuiltinFunctionIndex::get_imported_memory64_fill_index(),
+                };
 
                 self.machine.move_location(
                     Size::S64,
@@ -3340,10 +3789,18 @@ impl<'a, M: Machine> FuncGen<'a, M> {
                     Location::Memory(
                         self.machine.get_vmctx_reg(),
                         self.vmoffsets.vmctx_builtin_function(
-                            if self.module.local_memory_index(memory_index).is_some() {
-                                VMBuiltinFunctionIndex::get_memory32_grow_index()
-                            } else {
-                                VMBuiltinFunctionIndex::get_imported_memory32_grow_index()
+                            match (
+                                self.module.local_memory_index(memory_index).is_some(),
+                                self.memory64(memory_index),
+                            ) {
+                                (true, false) => VMBuiltinFunctionIndex::get_memory32_grow_index(),
+                                (false, false) => {
+                                    VMBuiltinFunctionIndex::get_imported_memory32_grow_index()
+                                }
+                                (true, true) => VMBuiltinFunctionIndex::get_memory64_grow_index(),
+                                (false, true) => {
+                                    VMBuiltinFunctionIndex::get_imported_memory64_grow_index()
+                                }
                             },
                         ) as i32,
                     ),
@@ -3386,6 +3843,7 @@ impl<'a, M: Machine> FuncGen<'a, M> {
                 self.op_memory(
                     |this,
                      need_check,
+                     memory64,
                      imported_memories,
                      offset,
                      heap_access_oob,
@@ -3395,6 +3853,7 @@ impl<'a, M: Machine> FuncGen<'a, M> {
                             memarg,
                             ret,
                             need_check,
+                            memory64,
                             imported_memories,
                             offset,
                             heap_access_oob,
@@ -3415,6 +3874,7 @@ impl<'a, M: Machine> FuncGen<'a, M> {
                 self.op_memory(
                     |this,
                      need_check,
+                     memory64,
                      imported_memories,
                      offset,
                      heap_access_oob,
@@ -3424,6 +3884,7 @@ impl<'a, M: Machine> FuncGen<'a, M> {
                             memarg,
                             ret,
                             need_check,
+                            memory64,
                             imported_memories,
                             offset,
                             heap_access_oob,
@@ -3442,6 +3903,7 @@ impl<'a, M: Machine> FuncGen<'a, M> {
                 self.op_memory(
                     |this,
                      need_check,
+                     memory64,
                      imported_memories,
                      offset,
                      heap_access_oob,
@@ -3451,6 +3913,7 @@ impl<'a, M: Machine> FuncGen<'a, M> {
                             memarg,
                             ret,
                             need_check,
+                            memory64,
                             imported_memories,
                             offset,
                             heap_access_oob,
@@ -3469,6 +3932,7 @@ impl<'a, M: Machine> FuncGen<'a, M> {
                 self.op_memory(
                     |this,
                      need_check,
+                     memory64,
                      imported_memories,
                      offset,
                      heap_access_oob,
@@ -3478,6 +3942,7 @@ impl<'a, M: Machine> FuncGen<'a, M> {
                             memarg,
                             ret,
                             need_check,
+                            memory64,
                             imported_memories,
                             offset,
                             heap_access_oob,
@@ -3496,6 +3961,7 @@ impl<'a, M: Machine> FuncGen<'a, M> {
                 self.op_memory(
                     |this,
                      need_check,
+                     memory64,
                      imported_memories,
                      offset,
                      heap_access_oob,
@@ -3505,6 +3971,7 @@ impl<'a, M: Machine> FuncGen<'a, M> {
                             memarg,
                             ret,
                             need_check,
+                            memory64,
                             imported_memories,
                             offset,
                             heap_access_oob,
@@ -3523,6 +3990,7 @@ impl<'a, M: Machine> FuncGen<'a, M> {
                 self.op_memory(
                     |this,
                      need_check,
+                     memory64,
                      imported_memories,
                      offset,
                      heap_access_oob,
@@ -3532,6 +4000,7 @@ impl<'a, M: Machine> FuncGen<'a, M> {
                             memarg,
                             ret,
                             need_check,
+                            memory64,
                             imported_memories,
                             offset,
                             heap_access_oob,
@@ -3546,6 +4015,7 @@ impl<'a, M: Machine> FuncGen<'a, M> {
                 self.op_memory(
                     |this,
                      need_check,
+                     memory64,
                      imported_memories,
                      offset,
                      heap_access_oob,
@@ -3555,6 +4025,7 @@ impl<'a, M: Machine> FuncGen<'a, M> {
                             memarg,
                             target_addr,
                             need_check,
+                            memory64,
                             imported_memories,
                             offset,
                             heap_access_oob,
@@ -3571,6 +4042,7 @@ impl<'a, M: Machine> FuncGen<'a, M> {
                 self.op_memory(
                     |this,
                      need_check,
+                     memory64,
                      imported_memories,
                      offset,
                      heap_access_oob,
@@ -3581,6 +4053,7 @@ impl<'a, M: Machine> FuncGen<'a, M> {
                             target_addr,
                             config_nan_canonicalization && fp.canonicalization.is_some(),
                             need_check,
+                            memory64,
                             imported_memories,
                             offset,
                             heap_access_oob,
@@ -3595,6 +4068,7 @@ impl<'a, M: Machine> FuncGen<'a, M> {
                 self.op_memory(
                     |this,
                      need_check,
+                     memory64,
                      imported_memories,
                      offset,
                      heap_access_oob,
@@ -3604,6 +4078,7 @@ impl<'a, M: Machine> FuncGen<'a, M> {
                             memarg,
                             target_addr,
                             need_check,
+                            memory64,
                             imported_memories,
                             offset,
                             heap_access_oob,
@@ -3618,6 +4093,7 @@ impl<'a, M: Machine> FuncGen<'a, M> {
                 self.op_memory(
                     |this,
                      need_check,
+                     memory64,
                      imported_memories,
                      offset,
                      heap_access_oob,
@@ -3627,6 +4103,7 @@ impl<'a, M: Machine> FuncGen<'a, M> {
                             memarg,
                             target_addr,
                             need_check,
+                            memory64,
                             imported_memories,
                             offset,
                             heap_access_oob,
@@ -3645,6 +4122,7 @@ impl<'a, M: Machine> FuncGen<'a, M> {
                 self.op_memory(
                     |this,
                      need_check,
+                     memory64,
                      imported_memories,
                      offset,
                      heap_access_oob,
@@ -3654,6 +4132,7 @@ impl<'a, M: Machine> FuncGen<'a, M> {
                             memarg,
                             ret,
                             need_check,
+                            memory64,
                             imported_memories,
                             offset,
                             heap_access_oob,
@@ -3674,6 +4153,7 @@ impl<'a, M: Machine> FuncGen<'a, M> {
                 self.op_memory(
                     |this,
                      need_check,
+                     memory64,
                      imported_memories,
                      offset,
                      heap_access_oob,
@@ -3683,6 +4163,7 @@ impl<'a, M: Machine> FuncGen<'a, M> {
                             memarg,
                             ret,
                             need_check,
+                            memory64,
                             imported_memories,
                             offset,
                             heap_access_oob,
@@ -3701,6 +4182,7 @@ impl<'a, M: Machine> FuncGen<'a, M> {
                 self.op_memory(
                     |this,
                      need_check,
+                     memory64,
                      imported_memories,
                      offset,
                      heap_access_oob,
@@ -3710,6 +4192,7 @@ impl<'a, M: Machine> FuncGen<'a, M> {
                             memarg,
                             ret,
                             need_check,
+                            memory64,
                             imported_memories,
                             offset,
                             heap_access_oob,
@@ -3728,6 +4211,7 @@ impl<'a, M: Machine> FuncGen<'a, M> {
                 self.op_memory(
                     |this,
                      need_check,
+                     memory64,
                      imported_memories,
                      offset,
                      heap_access_oob,
@@ -3737,6 +4221,7 @@ impl<'a, M: Machine> FuncGen<'a, M> {
                             memarg,
                             ret,
                             need_check,
+                            memory64,
                             imported_memories,
                             offset,
                             heap_access_oob,
@@ -3755,6 +4240,7 @@ impl<'a, M: Machine> FuncGen<'a, M> {
                 self.op_memory(
                     |this,
                      need_check,
+                     memory64,
                      imported_memories,
                      offset,
                      heap_access_oob,
@@ -3764,6 +4250,7 @@ impl<'a, M: Machine> FuncGen<'a, M> {
                             memarg,
                             ret,
                             need_check,
+                            memory64,
                             imported_memories,
                             offset,
                             heap_access_oob,
@@ -3782,6 +4269,7 @@ impl<'a, M: Machine> FuncGen<'a, M> {
                 self.op_memory(
                     |this,
                      need_check,
+                     memory64,
                      imported_memories,
                      offset,
                      heap_access_oob,
@@ -3791,6 +4279,7 @@ impl<'a, M: Machine> FuncGen<'a, M> {
                             memarg,
                             ret,
                             need_check,
+                            memory64,
                             imported_memories,
                             offset,
                             heap_access_oob,
@@ -3809,6 +4298,7 @@ impl<'a, M: Machine> FuncGen<'a, M> {
                 self.op_memory(
                     |this,
                      need_check,
+                     memory64,
                      imported_memories,
                      offset,
                      heap_access_oob,
@@ -3818,6 +4308,7 @@ impl<'a, M: Machine> FuncGen<'a, M> {
                             memarg,
                             ret,
                             need_check,
+                            memory64,
                             imported_memories,
                             offset,
                             heap_access_oob,
@@ -3836,6 +4327,7 @@ impl<'a, M: Machine> FuncGen<'a, M> {
                 self.op_memory(
                     |this,
                      need_check,
+                     memory64,
                      imported_memories,
                      offset,
                      heap_access_oob,
@@ -3845,6 +4337,7 @@ impl<'a, M: Machine> FuncGen<'a, M> {
                             memarg,
                             ret,
                             need_check,
+                            memory64,
                             imported_memories,
                             offset,
                             heap_access_oob,
@@ -3860,6 +4353,7 @@ impl<'a, M: Machine> FuncGen<'a, M> {
                 self.op_memory(
                     |this,
                      need_check,
+                     memory64,
                      imported_memories,
                      offset,
                      heap_access_oob,
@@ -3869,6 +4363,7 @@ impl<'a, M: Machine> FuncGen<'a, M> {
                             memarg,
                             target_addr,
                             need_check,
+                            memory64,
                             imported_memories,
                             offset,
                             heap_access_oob,
@@ -3885,6 +4380,7 @@ impl<'a, M: Machine> FuncGen<'a, M> {
                 self.op_memory(
                     |this,
                      need_check,
+                     memory64,
                      imported_memories,
                      offset,
                      heap_access_oob,
@@ -3895,6 +4391,7 @@ impl<'a, M: Machine> FuncGen<'a, M> {
                             target_addr,
                             config_nan_canonicalization && fp.canonicalization.is_some(),
                             need_check,
+                            memory64,
                             imported_memories,
                             offset,
                             heap_access_oob,
@@ -3909,6 +4406,7 @@ impl<'a, M: Machine> FuncGen<'a, M> {
                 self.op_memory(
                     |this,
                      need_check,
+                     memory64,
                      imported_memories,
                      offset,
                      heap_access_oob,
@@ -3918,6 +4416,7 @@ impl<'a, M: Machine> FuncGen<'a, M> {
                             memarg,
                             target_addr,
                             need_check,
+                            memory64,
                             imported_memories,
                             offset,
                             heap_access_oob,
@@ -3932,6 +4431,7 @@ impl<'a, M: Machine> FuncGen<'a, M> {
                 self.op_memory(
                     |this,
                      need_check,
+                     memory64,
                      imported_memories,
                      offset,
                      heap_access_oob,
@@ -3941,6 +4441,7 @@ impl<'a, M: Machine> FuncGen<'a, M> {
                             memarg,
                             target_addr,
                             need_check,
+                            memory64,
                             imported_memories,
                             offset,
                             heap_access_oob,
@@ -3955,6 +4456,7 @@ impl<'a, M: Machine> FuncGen<'a, M> {
                 self.op_memory(
                     |this,
                      need_check,
+                     memory64,
                      imported_memories,
                      offset,
                      heap_access_oob,
@@ -3964,6 +4466,7 @@ impl<'a, M: Machine> FuncGen<'a, M> {
                             memarg,
                             target_addr,
                             need_check,
+                            memory64,
                             imported_memories,
                             offset,
                             heap_access_oob,
@@ -3987,17 +4490,7 @@ impl<'a, M: Machine> FuncGen<'a, M> {
                         ));
                     }
                     let first_return = frame.returns[0];
-                    let loc = *self.value_stack.last().unwrap();
-                    let canonicalize = if first_return.is_float() {
-                        let fp = self.fp_stack.peek1()?;
-                        self.machine.arch_supports_canonicalize_nan()
-                            && self.config.enable_nan_canonicalization
-                            && fp.canonicalization.is_some()
-                    } else {
-                        false
-                    };
-                    self.machine
-                        .emit_function_return_value(first_return, canonicalize, loc)?;
+                    self.emit_return_value(first_return)?;
                 }
                 let frame = &self.control_stack[0];
                 let frame_depth = frame.value_stack_depth;
@@ -4016,17 +4509,7 @@ impl<'a, M: Machine> FuncGen<'a, M> {
                         ));
                     }
                     let first_return = frame.returns[0];
-                    let loc = *self.value_stack.last().unwrap();
-                    let canonicalize = if first_return.is_float() {
-                        let fp = self.fp_stack.peek1()?;
-                        self.machine.arch_supports_canonicalize_nan()
-                            && self.config.enable_nan_canonicalization
-                            && fp.canonicalization.is_some()
-                    } else {
-                        false
-                    };
-                    self.machine
-                        .emit_function_return_value(first_return, canonicalize, loc)?;
+                    self.emit_return_value(first_return)?;
                 }
                 let stack_len = self.control_stack.len();
                 let frame = &mut self.control_stack[stack_len - 1 - (relative_depth as usize)];
@@ -4054,17 +4537,7 @@ impl<'a, M: Machine> FuncGen<'a, M> {
                     }
 
                     let first_return = frame.returns[0];
-                    let loc = *self.value_stack.last().unwrap();
-                    let canonicalize = if first_return.is_float() {
-                        let fp = self.fp_stack.peek1()?;
-                        self.machine.arch_supports_canonicalize_nan()
-                            && self.config.enable_nan_canonicalization
-                            && fp.canonicalization.is_some()
-                    } else {
-                        false
-                    };
-                    self.machine
-                        .emit_function_return_value(first_return, canonicalize, loc)?;
+                    self.emit_return_value(first_return)?;
                 }
                 let stack_len = self.control_stack.len();
                 let frame = &mut self.control_stack[stack_len - 1 - (relative_depth as usize)];
@@ -4109,17 +4582,7 @@ impl<'a, M: Machine> FuncGen<'a, M> {
                         }
 
                         let first_return = frame.returns[0];
-                        let loc = *self.value_stack.last().unwrap();
-                        let canonicalize = if first_return.is_float() {
-                            let fp = self.fp_stack.peek1()?;
-                            self.machine.arch_supports_canonicalize_nan()
-                                && self.config.enable_nan_canonicalization
-                                && fp.canonicalization.is_some()
-                        } else {
-                            false
-                        };
-                        self.machine
-                            .emit_function_return_value(first_return, canonicalize, loc)?;
+                        self.emit_return_value(first_return)?;
                     }
                     let frame =
                         &self.control_stack[self.control_stack.len() - 1 - (*target as usize)];
@@ -4141,17 +4604,7 @@ impl<'a, M: Machine> FuncGen<'a, M> {
                         }
 
                         let first_return = frame.returns[0];
-                        let loc = *self.value_stack.last().unwrap();
-                        let canonicalize = if first_return.is_float() {
-                            let fp = self.fp_stack.peek1()?;
-                            self.machine.arch_supports_canonicalize_nan()
-                                && self.config.enable_nan_canonicalization
-                                && fp.canonicalization.is_some()
-                        } else {
-                            false
-                        };
-                        self.machine
-                            .emit_function_return_value(first_return, canonicalize, loc)?;
+                        self.emit_return_value(first_return)?;
                     }
                     let frame = &self.control_stack
                         [self.control_stack.len() - 1 - (default_target as usize)];
@@ -4168,6 +4621,12 @@ impl<'a, M: Machine> FuncGen<'a, M> {
                 self.unreachable_depth = 1;
             }
             Operator::Drop => {
+                if self.v128_on_top() {
+                    self.pop_value_released()?;
+                    self.pop_value_released()?;
+                    self.forget_popped_v128();
+                    return Ok(());
+                }
                 self.pop_value_released()?;
                 if let Some(x) = self.fp_stack.last() {
                     if x.depth == self.value_stack.len() {
@@ -4179,17 +4638,7 @@ impl<'a, M: Machine> FuncGen<'a, M> {
                 let frame = self.control_stack.pop().unwrap();
 
                 if !was_unreachable && !frame.returns.is_empty() {
-                    let loc = *self.value_stack.last().unwrap();
-                    let canonicalize = if frame.returns[0].is_float() {
-                        let fp = self.fp_stack.peek1()?;
-                        self.machine.arch_supports_canonicalize_nan()
-                            && self.config.enable_nan_canonicalization
-                            && fp.canonicalization.is_some()
-                    } else {
-                        false
-                    };
-                    self.machine
-                        .emit_function_return_value(frame.returns[0], canonicalize, loc)?;
+                    self.emit_return_value(frame.returns[0])?;
                 }
 
                 if self.control_stack.is_empty() {
@@ -4210,6 +4659,7 @@ impl<'a, M: Machine> FuncGen<'a, M> {
                     self.release_locations(released)?;
                     self.value_stack.truncate(frame.value_stack_depth);
                     self.fp_stack.truncate(frame.fp_stack_depth);
+                    self.forget_popped_v128();
 
                     if !frame.loop_like {
                         self.machine.emit_label(frame.label)?;
@@ -4225,24 +4675,8 @@ impl<'a, M: Machine> FuncGen<'a, M> {
                                 "End: incorrect frame.returns".to_owned(),
                             ));
                         }
-                        let loc = self.acquire_locations(
-                            &[(
-                                frame.returns[0],
-                                MachineValue::WasmStack(self.value_stack.len()),
-                            )],
-                            false,
-                        )?[0];
-                        self.machine.move_location(
-                            Size::S64,
-                            Location::GPR(self.machine.get_gpr_for_ret()),
-                            loc,
-                        )?;
-                        self.value_stack.push(loc);
-                        if frame.returns[0].is_float() {
-                            self.fp_stack
-                                .push(FloatValue::new(self.value_stack.len() - 1));
-                            // we already canonicalized at the `Br*` instruction or here previously.
-                        }
+                        // Floats were already canonicalized at the `Br*` instruction or here previously.
+                        self.push_return_value(frame.returns[0], false)?;
                     }
                 }
             }
@@ -4266,6 +4700,7 @@ impl<'a, M: Machine> FuncGen<'a, M> {
                 self.op_memory(
                     |this,
                      need_check,
+                     memory64,
                      imported_memories,
                      offset,
                      heap_access_oob,
@@ -4275,6 +4710,7 @@ impl<'a, M: Machine> FuncGen<'a, M> {
                             memarg,
                             ret,
                             need_check,
+                            memory64,
                             imported_memories,
                             offset,
                             heap_access_oob,
@@ -4293,6 +4729,7 @@ impl<'a, M: Machine> FuncGen<'a, M> {
                 self.op_memory(
                     |this,
                      need_check,
+                     memory64,
                      imported_memories,
                      offset,
                      heap_access_oob,
@@ -4302,6 +4739,7 @@ impl<'a, M: Machine> FuncGen<'a, M> {
                             memarg,
                             ret,
                             need_check,
+                            memory64,
                             imported_memories,
                             offset,
                             heap_access_oob,
@@ -4320,6 +4758,7 @@ impl<'a, M: Machine> FuncGen<'a, M> {
                 self.op_memory(
                     |this,
                      need_check,
+                     memory64,
                      imported_memories,
                      offset,
                      heap_access_oob,
@@ -4329,6 +4768,7 @@ impl<'a, M: Machine> FuncGen<'a, M> {
                             memarg,
                             ret,
                             need_check,
+                            memory64,
                             imported_memories,
                             offset,
                             heap_access_oob,
@@ -4343,6 +4783,7 @@ impl<'a, M: Machine> FuncGen<'a, M> {
                 self.op_memory(
                     |this,
                      need_check,
+                     memory64,
                      imported_memories,
                      offset,
                      heap_access_oob,
@@ -4352,6 +4793,7 @@ impl<'a, M: Machine> FuncGen<'a, M> {
                             memarg,
                             target_addr,
                             need_check,
+                            memory64,
                             imported_memories,
                             offset,
                             heap_access_oob,
@@ -4366,6 +4808,7 @@ impl<'a, M: Machine> FuncGen<'a, M> {
                 self.op_memory(
                     |this,
                      need_check,
+                     memory64,
                      imported_memories,
                      offset,
                      heap_access_oob,
@@ -4375,6 +4818,7 @@ impl<'a, M: Machine> FuncGen<'a, M> {
                             memarg,
                             target_addr,
                             need_check,
+                            memory64,
                             imported_memories,
                             offset,
                             heap_access_oob,
@@ -4389,6 +4833,7 @@ impl<'a, M: Machine> FuncGen<'a, M> {
                 self.op_memory(
                     |this,
                      need_check,
+                     memory64,
                      imported_memories,
                      offset,
                      heap_access_oob,
@@ -4398,6 +4843,7 @@ impl<'a, M: Machine> FuncGen<'a, M> {
                             memarg,
                             target_addr,
                             need_check,
+                            memory64,
                             imported_memories,
                             offset,
                             heap_access_oob,
@@ -4416,6 +4862,7 @@ impl<'a, M: Machine> FuncGen<'a, M> {
                 self.op_memory(
                     |this,
                      need_check,
+                     memory64,
                      imported_memories,
                      offset,
                      heap_access_oob,
@@ -4425,6 +4872,7 @@ impl<'a, M: Machine> FuncGen<'a, M> {
                             memarg,
                             ret,
                             need_check,
+                            memory64,
                             imported_memories,
                             offset,
                             heap_access_oob,
@@ -4443,6 +4891,7 @@ impl<'a, M: Machine> FuncGen<'a, M> {
                 self.op_memory(
                     |this,
                      need_check,
+                     memory64,
                      imported_memories,
                      offset,
                      heap_access_oob,
@@ -4452,6 +4901,7 @@ impl<'a, M: Machine> FuncGen<'a, M> {
                             memarg,
                             ret,
                             need_check,
+                            memory64,
                             imported_memories,
                             offset,
                             heap_access_oob,
@@ -4470,6 +4920,7 @@ impl<'a, M: Machine> FuncGen<'a, M> {
                 self.op_memory(
                     |this,
                      need_check,
+                     memory64,
                      imported_memories,
                      offset,
                      heap_access_oob,
@@ -4479,6 +4930,7 @@ impl<'a, M: Machine> FuncGen<'a, M> {
                             memarg,
                             ret,
                             need_check,
+                            memory64,
                             imported_memories,
                             offset,
                             heap_access_oob,
@@ -4497,6 +4949,7 @@ impl<'a, M: Machine> FuncGen<'a, M> {
                 self.op_memory(
                     |this,
                      need_check,
+                     memory64,
                      imported_memories,
                      offset,
                      heap_access_oob,
@@ -4506,6 +4959,7 @@ impl<'a, M: Machine> FuncGen<'a, M> {
                             memarg,
                             ret,
                             need_check,
+                            memory64,
                             imported_memories,
                             offset,
                             heap_access_oob,
@@ -4520,6 +4974,7 @@ impl<'a, M: Machine> FuncGen<'a, M> {
                 self.op_memory(
                     |this,
                      need_check,
+                     memory64,
                      imported_memories,
                      offset,
                      heap_access_oob,
@@ -4529,6 +4984,7 @@ impl<'a, M: Machine> FuncGen<'a, M> {
                             memarg,
                             target_addr,
                             need_check,
+                            memory64,
                             imported_memories,
                             offset,
                             heap_access_oob,
@@ -4543,6 +4999,7 @@ impl<'a, M: Machine> FuncGen<'a, M> {
                 self.op_memory(
                     |this,
                      need_check,
+                     memory64,
                      imported_memories,
                      offset,
                      heap_access_oob,
@@ -4552,6 +5009,7 @@ impl<'a, M: Machine> FuncGen<'a, M> {
                             memarg,
                             target_addr,
                             need_check,
+                            memory64,
                             imported_memories,
                             offset,
                             heap_access_oob,
@@ -4566,6 +5024,7 @@ impl<'a, M: Machine> FuncGen<'a, M> {
                 self.op_memory(
                     |this,
                      need_check,
+                     memory64,
                      imported_memories,
                      offset,
                      heap_access_oob,
@@ -4575,6 +5034,7 @@ impl<'a, M: Machine> FuncGen<'a, M> {
                             memarg,
                             target_addr,
                             need_check,
+                            memory64,
                             imported_memories,
                             offset,
                             heap_access_oob,
@@ -4589,6 +5049,7 @@ impl<'a, M: Machine> FuncGen<'a, M> {
                 self.op_memory(
                     |this,
                      need_check,
+                     memory64,
                      imported_memories,
                      offset,
                      heap_access_oob,
@@ -4598,6 +5059,7 @@ impl<'a, M: Machine> FuncGen<'a, M> {
                             memarg,
                             target_addr,
                             need_check,
+                            memory64,
                             imported_memories,
                             offset,
                             heap_access_oob,
@@ -4617,6 +5079,7 @@ impl<'a, M: Machine> FuncGen<'a, M> {
                 self.op_memory(
                     |this,
                      need_check,
+                     memory64,
                      imported_memories,
                      offset,
                      heap_access_oob,
@@ -4627,6 +5090,7 @@ impl<'a, M: Machine> FuncGen<'a, M> {
                             memarg,
                             ret,
                             need_check,
+                            memory64,
                             imported_memories,
                             offset,
                             heap_access_oob,
@@ -4646,6 +5110,7 @@ impl<'a, M: Machine> FuncGen<'a, M> {
                 self.op_memory(
                     |this,
                      need_check,
+                     memory64,
                      imported_memories,
                      offset,
                      heap_access_oob,
@@ -4656,6 +5121,7 @@ impl<'a, M: Machine> FuncGen<'a, M> {
                             memarg,
                             ret,
                             need_check,
+                            memory64,
                             imported_memories,
                             offset,
                             heap_access_oob,
@@ -4675,6 +5141,7 @@ impl<'a, M: Machine> FuncGen<'a, M> {
                 self.op_memory(
                     |this,
                      need_check,
+                     memory64,
                      imported_memories,
                      offset,
                      heap_access_oob,
@@ -4685,6 +5152,7 @@ impl<'a, M: Machine> FuncGen<'a, M> {
                             memarg,
                             ret,
                             need_check,
+                            memory64,
                             imported_memories,
                             offset,
                             heap_access_oob,
@@ -4704,6 +5172,7 @@ impl<'a, M: Machine> FuncGen<'a, M> {
                 self.op_memory(
                     |this,
                      need_check,
+                     memory64,
                      imported_memories,
                      offset,
                      heap_access_oob,
@@ -4714,6 +5183,7 @@ impl<'a, M: Machine> FuncGen<'a, M> {
                             memarg,
                             ret,
                             need_check,
+                            memory64,
                             imported_memories,
                             offset,
                             heap_access_oob,
@@ -4733,6 +5203,7 @@ impl<'a, M: Machine> FuncGen<'a, M> {
                 self.op_memory(
                     |this,
                      need_check,
+                     memory64,
                      imported_memories,
                      offset,
                      heap_access_oob,
@@ -4743,6 +5214,7 @@ impl<'a, M: Machine> FuncGen<'a, M> {
                             memarg,
                             ret,
                             need_check,
+                            memory64,
                             imported_memories,
                             offset,
                             heap_access_oob,
@@ -4762,6 +5234,7 @@ impl<'a, M: Machine> FuncGen<'a, M> {
                 self.op_memory(
                     |this,
                      need_check,
+                     memory64,
                      imported_memories,
                      offset,
                      heap_access_oob,
@@ -4772,6 +5245,7 @@ impl<'a, M: Machine> FuncGen<'a, M> {
                             memarg,
                             ret,
                             need_check,
+                            memory64,
                             imported_memories,
                             offset,
                             heap_access_oob,
@@ -4791,6 +5265,7 @@ impl<'a, M: Machine> FuncGen<'a, M> {
                 self.op_memory(
                     |this,
                      need_check,
+                     memory64,
                      imported_memories,
                      offset,
                      heap_access_oob,
@@ -4801,6 +5276,7 @@ impl<'a, M: Machine> FuncGen<'a, M> {
                             memarg,
                             ret,
                             need_check,
+                            memory64,
                             imported_memories,
                             offset,
                             heap_access_oob,
@@ -4820,6 +5296,7 @@ impl<'a, M: Machine> FuncGen<'a, M> {
                 self.op_memory(
                     |this,
                      need_check,
+                     memory64,
                      imported_memories,
                      offset,
                      heap_access_oob,
@@ -4830,6 +5307,7 @@ impl<'a, M: Machine> FuncGen<'a, M> {
                             memarg,
                             ret,
                             need_check,
+                            memory64,
                             imported_memories,
                             offset,
                             heap_access_oob,
@@ -4849,6 +5327,7 @@ impl<'a, M: Machine> FuncGen<'a, M> {
                 self.op_memory(
                     |this,
                      need_check,
+                     memory64,
                      imported_memories,
                      offset,
                      heap_access_oob,
@@ -4859,6 +5338,7 @@ impl<'a, M: Machine> FuncGen<'a, M> {
                             memarg,
                             ret,
                             need_check,
+                            memory64,
                             imported_memories,
                             offset,
                             heap_access_oob,
@@ -4878,6 +5358,7 @@ impl<'a, M: Machine> FuncGen<'a, M> {
                 self.op_memory(
                     |this,
                      need_check,
+                     memory64,
                      imported_memories,
                      offset,
                      heap_access_oob,
@@ -4888,6 +5369,7 @@ impl<'a, M: Machine> FuncGen<'a, M> {
                             memarg,
                             ret,
                             need_check,
+                            memory64,
                             imported_memories,
                             offset,
                             heap_access_oob,
@@ -4907,6 +5389,7 @@ impl<'a, M: Machine> FuncGen<'a, M> {
                 self.op_memory(
                     |this,
                      need_check,
+                     memory64,
                      imported_memories,
                      offset,
                      heap_access_oob,
@@ -4917,6 +5400,7 @@ impl<'a, M: Machine> FuncGen<'a, M> {
                             memarg,
                             ret,
                             need_check,
+                            memory64,
                             imported_memories,
                             offset,
                             heap_access_oob,
@@ -4936,6 +5420,7 @@ impl<'a, M: Machine> FuncGen<'a, M> {
                 self.op_memory(
                     |this,
                      need_check,
+                     memory64,
                      imported_memories,
                      offset,
                      heap_access_oob,
@@ -4946,6 +5431,7 @@ impl<'a, M: Machine> FuncGen<'a, M> {
                             memarg,
                             ret,
                             need_check,
+                            memory64,
                             imported_memories,
                             offset,
                             heap_access_oob,
@@ -4965,6 +5451,7 @@ impl<'a, M: Machine> FuncGen<'a, M> {
                 self.op_memory(
                     |this,
                      need_check,
+                     memory64,
                      imported_memories,
                      offset,
                      heap_access_oob,
@@ -4975,6 +5462,7 @@ impl<'a, M: Machine> FuncGen<'a, M> {
                             memarg,
                             ret,
                             need_check,
+                            memory64,
                             imported_memories,
                             offset,
                             heap_access_oob,
@@ -4994,6 +5482,7 @@ impl<'a, M: Machine> FuncGen<'a, M> {
                 self.op_memory(
                     |this,
                      need_check,
+                     memory64,
                      imported_memories,
                      offset,
                      heap_access_oob,
@@ -5004,6 +5493,7 @@ impl<'a, M: Machine> FuncGen<'a, M> {
                             memarg,
                             ret,
                             need_check,
+                            memory64,
                             imported_memories,
                             offset,
                             heap_access_oob,
@@ -5023,6 +5513,7 @@ impl<'a, M: Machine> FuncGen<'a, M> {
                 self.op_memory(
                     |this,
                      need_check,
+                     memory64,
                      imported_memories,
                      offset,
                      heap_access_oob,
@@ -5033,6 +5524,7 @@ impl<'a, M: Machine> FuncGen<'a, M> {
                             memarg,
                             ret,
                             need_check,
+                            memory64,
                             imported_memories,
                             offset,
                             heap_access_oob,
@@ -5052,6 +5544,7 @@ impl<'a, M: Machine> FuncGen<'a, M> {
                 self.op_memory(
                     |this,
                      need_check,
+                     memory64,
                      imported_memories,
                      offset,
                      heap_access_oob,
@@ -5062,6 +5555,7 @@ impl<'a, M: Machine> FuncGen<'a, M> {
                             memarg,
                             ret,
                             need_check,
+                            memory64,
                             imported_memories,
                             offset,
                             heap_access_oob,
@@ -5081,6 +5575,7 @@ impl<'a, M: Machine> FuncGen<'a, M> {
                 self.op_memory(
                     |this,
                      need_check,
+                     memory64,
                      imported_memories,
                      offset,
                      heap_access_oob,
@@ -5091,6 +5586,7 @@ impl<'a, M: Machine> FuncGen<'a, M> {
                             memarg,
                             ret,
                             need_check,
+                            memory64,
                             imported_memories,
                             offset,
                             heap_access_oob,
@@ -5110,6 +5606,7 @@ impl<'a, M: Machine> FuncGen<'a, M> {
                 self.op_memory(
                     |this,
                      need_check,
+                     memory64,
                      imported_memories,
                      offset,
                      heap_access_oob,
@@ -5120,6 +5617,7 @@ impl<'a, M: Machine> FuncGen<'a, M> {
                             memarg,
                             ret,
                             need_check,
+                            memory64,
                             imported_memories,
                             offset,
                             heap_access_oob,
@@ -5139,6 +5637,7 @@ impl<'a, M: Machine> FuncGen<'a, M> {
                 self.op_memory(
                     |this,
                      need_check,
+                     memory64,
                      imported_memories,
                      offset,
                      heap_access_oob,
@@ -5149,6 +5648,7 @@ impl<'a, M: Machine> FuncGen<'a, M> {
                             memarg,
                             ret,
                             need_check,
+                            memory64,
                             imported_memories,
                             offset,
                             heap_access_oob,
@@ -5168,6 +5668,7 @@ impl<'a, M: Machine> FuncGen<'a, M> {
                 self.op_memory(
                     |this,
                      need_check,
+                     memory64,
                      imported_memories,
                      offset,
                      heap_access_oob,
@@ -5178,6 +5679,7 @@ impl<'a, M: Machine> FuncGen<'a, M> {
                             memarg,
                             ret,
                             need_check,
+                            memory64,
                             imported_memories,
                             offset,
                             heap_access_oob,
@@ -5197,6 +5699,7 @@ impl<'a, M: Machine> FuncGen<'a, M> {
                 self.op_memory(
                     |this,
                      need_check,
+                     memory64,
                      imported_memories,
                      offset,
                      heap_access_oob,
@@ -5207,6 +5710,7 @@ impl<'a, M: Machine> FuncGen<'a, M> {
                             memarg,
                             ret,
                             need_check,
+                            memory64,
                             imported_memories,
                             offset,
                             heap_access_oob,
@@ -5226,6 +5730,7 @@ impl<'a, M: Machine> FuncGen<'a, M> {
                 self.op_memory(
                     |this,
                      need_check,
+                     memory64,
                      imported_memories,
                      offset,
                      heap_access_oob,
@@ -5236,6 +5741,7 @@ impl<'a, M: Machine> FuncGen<'a, M> {
                             memarg,
                             ret,
                             need_check,
+                            memory64,
                             imported_memories,
                             offset,
                             heap_access_oob,
@@ -5255,6 +5761,7 @@ impl<'a, M: Machine> FuncGen<'a, M> {
                 self.op_memory(
                     |this,
                      need_check,
+                     memory64,
                      imported_memories,
                      offset,
                      heap_access_oob,
@@ -5265,6 +5772,7 @@ impl<'a, M: Machine> FuncGen<'a, M> {
                             memarg,
                             ret,
                             need_check,
+                            memory64,
                             imported_memories,
                             offset,
                             heap_access_oob,
@@ -5284,6 +5792,7 @@ impl<'a, M: Machine> FuncGen<'a, M> {
                 self.op_memory(
                     |this,
                      need_check,
+                     memory64,
                      imported_memories,
                      offset,
                      heap_access_oob,
@@ -5294,6 +5803,7 @@ impl<'a, M: Machine> FuncGen<'a, M> {
                             memarg,
                             ret,
                             need_check,
+                            memory64,
                             imported_memories,
                             offset,
                             heap_access_oob,
@@ -5313,6 +5823,7 @@ impl<'a, M: Machine> FuncGen<'a, M> {
                 self.op_memory(
                     |this,
                      need_check,
+                     memory64,
                      imported_memories,
                      offset,
                      heap_access_oob,
@@ -5323,6 +5834,7 @@ impl<'a, M: Machine> FuncGen<'a, M> {
                             memarg,
                             ret,
                             need_check,
+                            memory64,
                             imported_memories,
                             offset,
                             heap_access_oob,
@@ -5342,6 +5854,7 @@ impl<'a, M: Machine> FuncGen<'a, M> {
                 self.op_memory(
                     |this,
                      need_check,
+                     memory64,
                      imported_memories,
                      offset,
                      heap_access_oob,
@@ -5352,6 +5865,7 @@ impl<'a, M: Machine> FuncGen<'a, M> {
                             memarg,
                             ret,
                             need_check,
+                            memory64,
                             imported_memories,
                             offset,
                             heap_access_oob,
@@ -5371,6 +5885,7 @@ impl<'a, M: Machine> FuncGen<'a, M> {
                 self.op_memory(
                     |this,
                      need_check,
+                     memory64,
                      imported_memories,
                      offset,
                      heap_access_oob,
@@ -5381,6 +5896,7 @@ impl<'a, M: Machine> FuncGen<'a, M> {
                             memarg,
                             ret,
                             need_check,
+                            memory64,
                             imported_memories,
                             offset,
                             heap_access_oob,
@@ -5400,6 +5916,7 @@ impl<'a, M: Machine> FuncGen<'a, M> {
                 self.op_memory(
                     |this,
                      need_check,
+                     memory64,
                      imported_memories,
                      offset,
                      heap_access_oob,
@@ -5410,6 +5927,7 @@ impl<'a, M: Machine> FuncGen<'a, M> {
                             memarg,
                             ret,
                             need_check,
+                            memory64,
                             imported_memories,
                             offset,
                             heap_access_oob,
@@ -5429,6 +5947,7 @@ impl<'a, M: Machine> FuncGen<'a, M> {
                 self.op_memory(
                     |this,
                      need_check,
+                     memory64,
                      imported_memories,
                      offset,
                      heap_access_oob,
@@ -5439,6 +5958,7 @@ impl<'a, M: Machine> FuncGen<'a, M> {
                             memarg,
                             ret,
                             need_check,
+                            memory64,
                             imported_memories,
                             offset,
                             heap_access_oob,
@@ -5458,6 +5978,7 @@ impl<'a, M: Machine> FuncGen<'a, M> {
                 self.op_memory(
                     |this,
                      need_check,
+                     memory64,
                      imported_memories,
                      offset,
                      heap_access_oob,
@@ -5468,6 +5989,7 @@ impl<'a, M: Machine> FuncGen<'a, M> {
                             memarg,
                             ret,
                             need_check,
+                            memory64,
                             imported_memories,
                             offset,
                             heap_access_oob,
@@ -5487,6 +6009,7 @@ impl<'a, M: Machine> FuncGen<'a, M> {
                 self.op_memory(
                     |this,
                      need_check,
+                     memory64,
                      imported_memories,
                      offset,
                      heap_access_oob,
@@ -5497,6 +6020,7 @@ impl<'a, M: Machine> FuncGen<'a, M> {
                             memarg,
                             ret,
                             need_check,
+                            memory64,
                             imported_memories,
                             offset,
                             heap_access_oob,
@@ -5516,6 +6040,7 @@ impl<'a, M: Machine> FuncGen<'a, M> {
                 self.op_memory(
                     |this,
                      need_check,
+                     memory64,
                      imported_memories,
                      offset,
                      heap_access_oob,
@@ -5526,6 +6051,7 @@ impl<'a, M: Machine> FuncGen<'a, M> {
                             memarg,
                             ret,
                             need_check,
+                            memory64,
                             imported_memories,
                             offset,
                             heap_access_oob,
@@ -5545,6 +6071,7 @@ impl<'a, M: Machine> FuncGen<'a, M> {
                 self.op_memory(
                     |this,
                      need_check,
+                     memory64,
                      imported_memories,
                      offset,
                      heap_access_oob,
@@ -5555,6 +6082,7 @@ impl<'a, M: Machine> FuncGen<'a, M> {
                             memarg,
                             ret,
                             need_check,
+                            memory64,
                             imported_memories,
                             offset,
                             heap_access_oob,
@@ -5574,6 +6102,7 @@ impl<'a, M: Machine> FuncGen<'a, M> {
                 self.op_memory(
                     |this,
                      need_check,
+                     memory64,
                      imported_memories,
                      offset,
                      heap_access_oob,
@@ -5584,6 +6113,7 @@ impl<'a, M: Machine> FuncGen<'a, M> {
                             memarg,
                             ret,
                             need_check,
+                            memory64,
                             imported_memories,
                             offset,
                             heap_access_oob,
@@ -5603,6 +6133,7 @@ impl<'a, M: Machine> FuncGen<'a, M> {
                 self.op_memory(
                     |this,
                      need_check,
+                     memory64,
                      imported_memories,
                      offset,
                      heap_access_oob,
@@ -5613,6 +6144,7 @@ impl<'a, M: Machine> FuncGen<'a, M> {
                             memarg,
                             ret,
                             need_check,
+                            memory64,
                             imported_memories,
                             offset,
                             heap_access_oob,
@@ -5632,6 +6164,7 @@ impl<'a, M: Machine> FuncGen<'a, M> {
                 self.op_memory(
                     |this,
                      need_check,
+                     memory64,
                      imported_memories,
                      offset,
                      heap_access_oob,
@@ -5642,6 +6175,7 @@ impl<'a, M: Machine> FuncGen<'a, M> {
                             memarg,
                             ret,
                             need_check,
+                            memory64,
                             imported_memories,
                             offset,
                             heap_access_oob,
@@ -5661,6 +6195,7 @@ impl<'a, M: Machine> FuncGen<'a, M> {
                 self.op_memory(
                     |this,
                      need_check,
+                     memory64,
                      imported_memories,
                      offset,
                      heap_access_oob,
@@ -5671,6 +6206,7 @@ impl<'a, M: Machine> FuncGen<'a, M> {
                             memarg,
                             ret,
                             need_check,
+                            memory64,
                             imported_memories,
                             offset,
                             heap_access_oob,
@@ -5690,6 +6226,7 @@ impl<'a, M: Machine> FuncGen<'a, M> {
                 self.op_memory(
                     |this,
                      need_check,
+                     memory64,
                      imported_memories,
                      offset,
                      heap_access_oob,
@@ -5700,6 +6237,7 @@ impl<'a, M: Machine> FuncGen<'a, M> {
                             memarg,
                             ret,
                             need_check,
+                            memory64,
                             imported_memories,
                             offset,
                             heap_access_oob,
@@ -5719,6 +6257,7 @@ impl<'a, M: Machine> FuncGen<'a, M> {
                 self.op_memory(
                     |this,
                      need_check,
+                     memory64,
                      imported_memories,
                      offset,
                      heap_access_oob,
@@ -5729,6 +6268,7 @@ impl<'a, M: Machine> FuncGen<'a, M> {
                             memarg,
                             ret,
                             need_check,
+                            memory64,
                             imported_memories,
                             offset,
                             heap_access_oob,
@@ -5748,6 +6288,7 @@ impl<'a, M: Machine> FuncGen<'a, M> {
                 self.op_memory(
                     |this,
                      need_check,
+                     memory64,
                      imported_memories,
                      offset,
                      heap_access_oob,
@@ -5758,6 +6299,7 @@ impl<'a, M: Machine> FuncGen<'a, M> {
                             memarg,
                             ret,
                             need_check,
+                            memory64,
                             imported_memories,
                             offset,
                             heap_access_oob,
@@ -5777,6 +6319,7 @@ impl<'a, M: Machine> FuncGen<'a, M> {
                 self.op_memory(
                     |this,
                      need_check,
+                     memory64,
                      imported_memories,
                      offset,
                      heap_access_oob,
@@ -5787,6 +6330,7 @@ impl<'a, M: Machine> FuncGen<'a, M> {
                             memarg,
                             ret,
                             need_check,
+                            memory64,
                             imported_memories,
                             offset,
                             heap_access_oob,
@@ -5806,6 +6350,7 @@ impl<'a, M: Machine> FuncGen<'a, M> {
                 self.op_memory(
                     |this,
                      need_check,
+                     memory64,
                      imported_memories,
                      offset,
                      heap_access_oob,
@@ -5816,6 +6361,7 @@ impl<'a, M: Machine> FuncGen<'a, M> {
                             memarg,
                             ret,
                             need_check,
+                            memory64,
                             imported_memories,
                             offset,
                             heap_access_oob,
@@ -5836,6 +6382,7 @@ impl<'a, M: Machine> FuncGen<'a, M> {
                 self.op_memory(
                     |this,
                      need_check,
+                     memory64,
                      imported_memories,
                      offset,
                      heap_access_oob,
@@ -5847,6 +6394,7 @@ impl<'a, M: Machine> FuncGen<'a, M> {
                             memarg,
                             ret,
                             need_check,
+                            memory64,
                             imported_memories,
                             offset,
                             heap_access_oob,
@@ -5867,6 +6415,7 @@ impl<'a, M: Machine> FuncGen<'a, M> {
                 self.op_memory(
                     |this,
                      need_check,
+                     memory64,
                      imported_memories,
                      offset,
                      heap_access_oob,
@@ -5878,6 +6427,7 @@ impl<'a, M: Machine> FuncGen<'a, M> {
                             memarg,
                             ret,
                             need_check,
+                            memory64,
                             imported_memories,
                             offset,
                             heap_access_oob,
@@ -5898,6 +6448,7 @@ impl<'a, M: Machine> FuncGen<'a, M> {
                 self.op_memory(
                     |this,
                      need_check,
+                     memory64,
                      imported_memories,
                      offset,
                      heap_access_oob,
@@ -5909,6 +6460,7 @@ impl<'a, M: Machine> FuncGen<'a, M> {
                             memarg,
                             ret,
                             need_check,
+                            memory64,
                             imported_memories,
                             offset,
                             heap_access_oob,
@@ -5929,6 +6481,7 @@ impl<'a, M: Machine> FuncGen<'a, M> {
                 self.op_memory(
                     |this,
                      need_check,
+                     memory64,
                      imported_memories,
                      offset,
                      heap_access_oob,
@@ -5940,6 +6493,7 @@ impl<'a, M: Machine> FuncGen<'a, M> {
                             memarg,
                             ret,
                             need_check,
+                            memory64,
                             imported_memories,
                             offset,
                             heap_access_oob,
@@ -5960,6 +6514,7 @@ impl<'a, M: Machine> FuncGen<'a, M> {
                 self.op_memory(
                     |this,
                      need_check,
+                     memory64,
                      imported_memories,
                      offset,
                      heap_access_oob,
@@ -5971,6 +6526,7 @@ impl<'a, M: Machine> FuncGen<'a, M> {
                             memarg,
                             ret,
                             need_check,
+                            memory64,
                             imported_memories,
                             offset,
                             heap_access_oob,
@@ -5991,6 +6547,7 @@ impl<'a, M: Machine> FuncGen<'a, M> {
                 self.op_memory(
                     |this,
                      need_check,
+                     memory64,
                      imported_memories,
                      offset,
                      heap_access_oob,
@@ -6002,6 +6559,7 @@ impl<'a, M: Machine> FuncGen<'a, M> {
                             memarg,
                             ret,
                             need_check,
+                            memory64,
                             imported_memories,
                             offset,
                             heap_access_oob,
@@ -6022,6 +6580,7 @@ impl<'a, M: Machine> FuncGen<'a, M> {
                 self.op_memory(
                     |this,
                      need_check,
+                     memory64,
                      imported_memories,
                      offset,
                      heap_access_oob,
@@ -6033,6 +6592,7 @@ impl<'a, M: Machine> FuncGen<'a, M> {
                             memarg,
                             ret,
                             need_check,
+                            memory64,
                             imported_memories,
                             offset,
                             heap_access_oob,
@@ -6427,6 +6987,12 @@ impl<'a, M: Machine> FuncGen<'a, M> {
                 )?;
             }
             Operator::MemoryAtomicWait32 { ref memarg } => {
+                // The builtin only takes 32-bit addresses.
+                if self.memory64(MemoryIndex::new(memarg.memory as usize)) {
+                    codegen_error!(
+                        "singlepass memory.atomic.wait32 on a 64-bit memory is not supported"
+                    );
+                }
                 let timeout = self.value_stack.pop().unwrap();
                 let val = self.value_stack.pop().unwrap();
                 let dst = self.value_stack.pop().unwrap();
@@ -6489,6 +7055,12 @@ impl<'a, M: Machine> FuncGen<'a, M> {
                 )?;
             }
             Operator::MemoryAtomicWait64 { ref memarg } => {
+                // The builtin only takes 32-bit addresses.
+                if self.memory64(MemoryIndex::new(memarg.memory as usize)) {
+                    codegen_error!(
+                        "singlepass memory.atomic.wait64 on a 64-bit memory is not supported"
+                    );
+                }
                 let timeout = self.value_stack.pop().unwrap();
                 let val = self.value_stack.pop().unwrap();
                 let dst = self.value_stack.pop().unwrap();
@@ -6551,6 +7123,12 @@ impl<'a, M: Machine> FuncGen<'a, M> {
                 )?;
             }
             Operator::MemoryAtomicNotify { ref memarg } => {
+                // The builtin only takes 32-bit addresses.
+                if self.memory64(MemoryIndex::new(memarg.memory as usize)) {
+                    codegen_error!(
+                        "singlepass memory.atomic.notify on a 64-bit memory is not supported"
+                    );
+                }
                 let cnt = self.value_stack.pop().unwrap();
                 let dst = self.value_stack.pop().unwrap();
                 self.release_locations_only_regs(&[cnt, dst])?;
@@ -6604,17 +7182,107 @@ impl<'a, M: Machine> FuncGen<'a, M> {
                     ret,
                 )?;
             }
+            Operator::V128Const { value } => {
+                let bits = value.i128() as u128;
+                for half in [bits as u64, (bits >> 64) as u64] {
+                    self.value_stack.push(Location::Imm64(half));
+                    self.state.wasm_stack.push(WasmAbstractValue::Const(half));
+                }
+                self.v128_stack.push(self.value_stack.len() - 2);
+            }
             _ => {
-                return Err(CompileError::Codegen(format!(
-                    "not yet implemented: {:?}",
-                    op
-                )));
+                if let Some((params, result)) = simd_signature(&op) {
+                    self.emit_simd_op(&op, params, result)?;
+                } else {
+                    return Err(CompileError::Codegen(format!(
+                        "not yet implemented: {:?}",
+                        op
+                    )));
+                }
             }
         }
 
         Ok(())
     }
 
+    /// Emits a fixed-width SIMD operator, with the given parameter and result types.
+    fn emit_simd_op(
+        &mut self,
+        op: &Operator,
+        params: &[WpType],
+        result: Option<WpType>,
+    ) -> Result<(), CompileError> {
+        let param_slots: usize = params.iter().map(|x| x.slots()).sum();
+        let args: SmallVec<[_; 8]> = self
+            .value_stack
+            .drain(self.value_stack.len() - param_slots..)
+            .collect();
+        self.forget_popped_v128();
+
+        // Pop the float operands off the FP stack, canonicalizing them if needed
+        // since their bit patterns end up in a lane.
+        while let Some(fp) = self.fp_stack.last() {
+            if fp.depth < self.value_stack.len() {
+                break;
+            }
+            let index = fp.depth - self.value_stack.len();
+            if self.machine.arch_supports_canonicalize_nan()
+                && self.config.enable_nan_canonicalization
+                && fp.canonicalization.is_some()
+            {
+                let size = fp.canonicalization.unwrap().to_size();
+                self.machine
+                    .canonicalize_nan(size, args[index], args[index])?;
+            }
+            self.fp_stack.pop().unwrap();
+        }
+        self.release_locations(&args)?;
+
+        let ret: SmallVec<[_; 2]> = match result {
+            Some(WpType::V128) => SmallVec::from_slice(&self.push_v128()?),
+            Some(ty) => {
+                let loc = self.acquire_locations(
+                    &[(ty, MachineValue::WasmStack(self.value_stack.len()))],
+                    false,
+                )?[0];
+                self.value_stack.push(loc);
+                if ty.is_float() {
+                    self.fp_stack
+                        .push(FloatValue::new(self.value_stack.len() - 1));
+                }
+                smallvec![loc]
+            }
+            None => smallvec![],
+        };
+
+        if is_simd_memory_op(op) {
+            self.op_memory(
+                |this,
+                 need_check,
+                 memory64,
+                 imported_memories,
+                 offset,
+                 heap_access_oob,
+                 unaligned_atomic| {
+                    this.machine.emit_simd_memory(
+                        op,
+                        args[0],
+                        &args[1..],
+                        &ret,
+                        need_check,
+                        memory64,
+                        imported_memories,
+                        offset,
+                        heap_access_oob,
+                        unaligned_atomic,
+                    )
+                },
+            )
+        } else {
+            self.machine.emit_simd(op, &args, &ret)
+        }
+    }
+
     pub fn finalize(
         mut self,
         data: &FunctionBodyData,
@@ -6743,3 +7411,312 @@ impl<'a, M: Machine> FuncGen<'a, M> {
     }
     */
 }
+
+/// The memory argument and the size of the plain loads and stores, and
+/// whether they are stores.
+fn memory_access<'a>(op: &'a Operator) -> Option<(&'a MemArg, u32, bool)> {
+    let access = match op {
+        Operator::I32Load8S { memarg }
+        | Operator::I32Load8U { memarg }
+        | Operator::I64Load8S { memarg }
+        | Operator::I64Load8U { memarg } => (memarg, 1, false),
+        Operator::I32Load16S { memarg }
+        | Operator::I32Load16U { memarg }
+        | Operator::I64Load16S { memarg }
+        | Operator::I64Load16U { memarg } => (memarg, 2, false),
+        Operator::I32Load { memarg }
+        | Operator::F32Load { memarg }
+        | Operator::I64Load32S { memarg }
+        | Operator::I64Load32U { memarg } => (memarg, 4, false),
+        Operator::I64Load { memarg } | Operator::F64Load { memarg } => (memarg, 8, false),
+        Operator::I32Store8 { memarg } | Operator::I64Store8 { memarg } => (memarg, 1, true),
+        Operator::I32Store16 { memarg } | Operator::I64Store16 { memarg } => (memarg, 2, true),
+        Operator::I32Store { memarg }
+        | Operator::F32Store { memarg }
+        | Operator::I64Store32 { memarg } => (memarg, 4, true),
+        Operator::I64Store { memarg } | Operator::F64Store { memarg } => (memarg, 8, true),
+        _ => return None,
+    };
+    Some(access)
+}
+
+/// Whether a fixed-width SIMD operator accesses the linear memory.
+fn is_simd_memory_op(op: &Operator) -> bool {
+    matches!(
+        op,
+        Operator::V128Load { .. }
+            | Operator::V128Load8x8S { .. }
+            | Operator::V128Load8x8U { .. }
+            | Operator::V128Load16x4S { .. }
+            | Operator::V128Load16x4U { .. }
+            | Operator::V128Load32x2S { .. }
+            | Operator::V128Load32x2U { .. }
+            | Operator::V128Load8Splat { .. }
+            | Operator::V128Load16Splat { .. }
+            | Operator::V128Load32Splat { .. }
+            | Operator::V128Load64Splat { .. }
+            | Operator::V128Load32Zero { .. }
+            | Operator::V128Load64Zero { .. }
+            | Operator::V128Store { .. }
+            | Operator::V128Load8Lane { .. }
+            | Operator::V128Load16Lane { .. }
+            | Operator::V128Load32Lane { .. }
+            | Operator::V128Load64Lane { .. }
+            | Operator::V128Store8Lane { .. }
+            | Operator::V128Store16Lane { .. }
+            | Operator::V128Store32Lane { .. }
+            | Operator::V128Store64Lane { .. }
+    )
+}
+
+/// The parameter and result types of the fixed-width SIMD operators, other
+/// than `v128.const`.
+fn simd_signature(op: &Operator) -> Option<(&'static [WpType], Option<WpType>)> {
+    use wasmer_compiler::wasmparser::ValType::{F32, F64, I32, I64, V128};
+
+    let signature: (&'static [WpType], Option<WpType>) = match op {
+        Operator::V128Load { .. }
+        | Operator::V128Load8x8S { .. }
+        | Operator::V128Load8x8U { .. }
+        | Operator::V128Load16x4S { .. }
+        | Operator::V128Load16x4U { .. }
+        | Operator::V128Load32x2S { .. }
+        | Operator::V128Load32x2U { .. }
+        | Operator::V128Load8Splat { .. }
+        | Operator::V128Load16Splat { .. }
+        | Operator::V128Load32Splat { .. }
+        | Operator::V128Load64Splat { .. }
+        | Operator::V128Load32Zero { .. }
+        | Operator::V128Load64Zero { .. } => (&[I32], Some(V128)),
+        Operator::V128Store { .. }
+        | Operator::V128Store8Lane { .. }
+        | Operator::V128Store16Lane { .. }
+        | Operator::V128Store32Lane { .. }
+        | Operator::V128Store64Lane { .. } => (&[I32, V128], None),
+        Operator::V128Load8Lane { .. }
+        | Operator::V128Load16Lane { .. }
+        | Operator::V128Load32Lane { .. }
+        | Operator::V128Load64Lane { .. } => (&[I32, V128], Some(V128)),
+
+        Operator::I8x16Splat | Operator::I16x8Splat | Operator::I32x4Splat => (&[I32], Some(V128)),
+        Operator::I64x2Splat => (&[I64], Some(V128)),
+        Operator::F32x4Splat => (&[F32], Some(V128)),
+        Operator::F64x2Splat => (&[F64], Some(V128)),
+
+        Operator::I8x16ExtractLaneS { .. }
+        | Operator::I8x16ExtractLaneU { .. }
+        | Operator::I16x8ExtractLaneS { .. }
+        | Operator::I16x8ExtractLaneU { .. }
+        | Operator::I32x4ExtractLane { .. } => (&[V128], Some(I32)),
+        Operator::I64x2ExtractLane { .. } => (&[V128], Some(I64)),
+        Operator::F32x4ExtractLane { .. } => (&[V128], Some(F32)),
+        Operator::F64x2ExtractLane { .. } => (&[V128], Some(F64)),
+        Operator::I8x16ReplaceLane { .. }
+        | Operator::I16x8ReplaceLane { .. }
+        | Operator::I32x4ReplaceLane { .. } => (&[V128, I32], Some(V128)),
+        Operator::I64x2ReplaceLane { .. } => (&[V128, I64], Some(V128)),
+        Operator::F32x4ReplaceLane { .. } => (&[V128, F32], Some(V128)),
+        Operator::F64x2ReplaceLane { .. } => (&[V128, F64], Some(V128)),
+
+        Operator::V128AnyTrue
+        | Operator::I8x16AllTrue
+        | Operator::I8x16Bitmask
+        | Operator::I16x8AllTrue
+        | Operator::I16x8Bitmask
+        | Operator::I32x4AllTrue
+        | Operator::I32x4Bitmask
+        | Operator::I64x2AllTrue
+        | Operator::I64x2Bitmask => (&[V128], Some(I32)),
+
+        Operator::I8x16Shl
+        | Operator::I8x16ShrS
+        | Operator::I8x16ShrU
+        | Operator::I16x8Shl
+        | Operator::I16x8ShrS
+        | Operator::I16x8ShrU
+        | Operator::I32x4Shl
+        | Operator::I32x4ShrS
+        | Operator::I32x4ShrU
+        | Operator::I64x2Shl
+        | Operator::I64x2ShrS
+        | Operator::I64x2ShrU => (&[V128, I32], Some(V128)),
+
+        Operator::V128Bitselect => (&[V128, V128, V128], Some(V128)),
+
+        Operator::V128Not
+        | Operator::I8x16Abs
+        | Operator::I8x16Neg
+        | Operator::I8x16Popcnt
+        | Operator::I16x8Abs
+        | Operator::I16x8Neg
+        | Operator::I32x4Abs
+        | Operator::I32x4Neg
+        | Operator::I64x2Abs
+        | Operator::I64x2Neg
+        | Operator::I16x8ExtAddPairwiseI8x16S
+        | Operator::I16x8ExtAddPairwiseI8x16U
+        | Operator::I32x4ExtAddPairwiseI16x8S
+        | Operator::I32x4ExtAddPairwiseI16x8U
+        | Operator::I16x8ExtendLowI8x16S
+        | Operator::I16x8ExtendHighI8x16S
+        | Operator::I16x8ExtendLowI8x16U
+        | Operator::I16x8ExtendHighI8x16U
+        | Operator::I32x4ExtendLowI16x8S
+        | Operator::I32x4ExtendHighI16x8S
+        | Operator::I32x4ExtendLowI16x8U
+        | Operator::I32x4ExtendHighI16x8U
+        | Operator::I64x2ExtendLowI32x4S
+        | Operator::I64x2ExtendHighI32x4S
+        | Operator::I64x2ExtendLowI32x4U
+        | Operator::I64x2ExtendHighI32x4U
+        | Operator::F32x4Ceil
+        | Operator::F32x4Floor
+        | Operator::F32x4Trunc
+        | Operator::F32x4Nearest
+        | Operator::F32x4Abs
+        | Operator::F32x4Neg
+        | Operator::F32x4Sqrt
+        | Operator::F64x2Ceil
+        | Operator::F64x2Floor
+        | Operator::F64x2Trunc
+        | Operator::F64x2Nearest
+        | Operator::F64x2Abs
+        | Operator::F64x2Neg
+        | Operator::F64x2Sqrt
+        | Operator::I32x4TruncSatF32x4S
+        | Operator::I32x4TruncSatF32x4U
+        | Operator::F32x4ConvertI32x4S
+        | Operator::F32x4ConvertI32x4U
+        | Operator::I32x4TruncSatF64x2SZero
+        | Operator::I32x4TruncSatF64x2UZero
+        | Operator::F64x2ConvertLowI32x4S
+        | Operator::F64x2ConvertLowI32x4U
+        | Operator::F32x4DemoteF64x2Zero
+        | Operator::F64x2PromoteLowF32x4 => (&[V128], Some(V128)),
+
+        Operator::I8x16Shuffle { .. }
+        | Operator::I8x16Swizzle
+        | Operator::I8x16Eq
+        | Operator::I8x16Ne
+        | Operator::I8x16LtS
+        | Operator::I8x16LtU
+        | Operator::I8x16GtS
+        | Operator::I8x16GtU
+        | Operator::I8x16LeS
+        | Operator::I8x16LeU
+        | Operator::I8x16GeS
+        | Operator::I8x16GeU
+        | Operator::I16x8Eq
+        | Operator::I16x8Ne
+        | Operator::I16x8LtS
+        | Operator::I16x8LtU
+        | Operator::I16x8GtS
+        | Operator::I16x8GtU
+        | Operator::I16x8LeS
+        | Operator::I16x8LeU
+        | Operator::I16x8GeS
+        | Operator::I16x8GeU
+        | Operator::I32x4Eq
+        | Operator::I32x4Ne
+        | Operator::I32x4LtS
+        | Operator::I32x4LtU
+        | Operator::I32x4GtS
+        | Operator::I32x4GtU
+        | Operator::I32x4LeS
+        | Operator::I32x4LeU
+        | Operator::I32x4GeS
+        | Operator::I32x4GeU
+        | Operator::I64x2Eq
+        | Operator::I64x2Ne
+        | Operator::I64x2LtS
+        | Operator::I64x2GtS
+        | Operator::I64x2LeS
+        | Operator::I64x2GeS
+        | Operator::F32x4Eq
+        | Operator::F32x4Ne
+        | Operator::F32x4Lt
+        | Operator::F32x4Gt
+        | Operator::F32x4Le
+        | Operator::F32x4Ge
+        | Operator::F64x2Eq
+        | Operator::F64x2Ne
+        | Operator::F64x2Lt
+        | Operator::F64x2Gt
+        | Operator::F64x2Le
+        | Operator::F64x2Ge
+        | Operator::V128And
+        | Operator::V128AndNot
+        | Operator::V128Or
+        | Operator::V128Xor
+        | Operator::I8x16NarrowI16x8S
+        | Operator::I8x16NarrowI16x8U
+        | Operator::I8x16Add
+        | Operator::I8x16AddSatS
+        | Operator::I8x16AddSatU
+        | Operator::I8x16Sub
+        | Operator::I8x16SubSatS
+        | Operator::I8x16SubSatU
+        | Operator::I8x16MinS
+        | Operator::I8x16MinU
+        | Operator::I8x16MaxS
+        | Operator::I8x16MaxU
+        | Operator::I8x16AvgrU
+        | Operator::I16x8Q15MulrSatS
+        | Operator::I16x8NarrowI32x4S
+        | Operator::I16x8NarrowI32x4U
+        | Operator::I16x8Add
+        | Operator::I16x8AddSatS
+        | Operator::I16x8AddSatU
+        | Operator::I16x8Sub
+        | Operator::I16x8SubSatS
+        | Operator::I16x8SubSatU
+        | Operator::I16x8Mul
+        | Operator::I16x8MinS
+        | Operator::I16x8MinU
+        | Operator::I16x8MaxS
+        | Operator::I16x8MaxU
+        | Operator::I16x8AvgrU
+        | Operator::I16x8ExtMulLowI8x16S
+        | Operator::I16x8ExtMulHighI8x16S
+        | Operator::I16x8ExtMulLowI8x16U
+        | Operator::I16x8ExtMulHighI8x16U
+        | Operator::I32x4Add
+        | Operator::I32x4Sub
+        | Operator::I32x4Mul
+        | Operator::I32x4MinS
+        | Operator::I32x4MinU
+        | Operator::I32x4MaxS
+        | Operator::I32x4MaxU
+        | Operator::I32x4DotI16x8S
+        | Operator::I32x4ExtMulLowI16x8S
+        | Operator::I32x4ExtMulHighI16x8S
+        | Operator::I32x4ExtMulLowI16x8U
+        | Operator::I32x4ExtMulHighI16x8U
+        | Operator::I64x2Add
+        | Operator::I64x2Sub
+        | Operator::I64x2Mul
+        | Operator::I64x2ExtMulLowI32x4S
+        | Operator::I64x2ExtMulHighI32x4S
+        | Operator::I64x2ExtMulLowI32x4U
+        | Operator::I64x2ExtMulHighI32x4U
+        | Operator::F32x4Add
+        | Operator::F32x4Sub
+        | Operator::F32x4Mul
+        | Operator::F32x4Div
+        | Operator::F32x4Min
+        | Operator::F32x4Max
+        | Operator::F32x4PMin
+        | Operator::F32x4PMax
+        | Operator::F64x2Add
+        | Operator::F64x2Sub
+        | Operator::F64x2Mul
+        | Operator::F64x2Div
+        | Operator::F64x2Min
+        | Operator::F64x2Max
+        | Operator::F64x2PMin
+        | Operator::F64x2PMax => (&[V128, V128], Some(V128)),
+
+        _ => return None,
+    };
+    Some(signature)
+}
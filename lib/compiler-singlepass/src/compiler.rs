@@ -11,14 +11,17 @@ use crate::machine::{
     gen_import_call_trampoline, gen_std_dynamic_import_trampoline, gen_std_trampoline,
 };
 use crate::machine_arm64::MachineARM64;
+use crate::machine_riscv::MachineRiscv;
 use crate::machine_x64::MachineX86_64;
 #[cfg(feature = "unwind")]
-use crate::unwind::{create_systemv_cie, UnwindFrame};
+use crate::unwind::create_systemv_cie;
+use crate::unwind::UnwindFrame;
 use enumset::EnumSet;
 #[cfg(feature = "unwind")]
 use gimli::write::{EhFrame, FrameTable};
 #[cfg(feature = "rayon")]
 use rayon::prelude::{IntoParallelIterator, ParallelIterator};
+use std::num::NonZeroUsize;
 use std::sync::Arc;
 use wasmer_compiler::{
     Compiler, CompilerConfig, FunctionBinaryReader, FunctionBodyData, MiddlewareBinaryReader,
@@ -27,7 +30,7 @@ use wasmer_compiler::{
 use wasmer_types::entity::{EntityRef, PrimaryMap};
 use wasmer_types::{
     Architecture, CallingConvention, Compilation, CompileError, CompileModuleInfo,
-    CompiledFunction, CpuFeature, Dwarf, FunctionBody, FunctionIndex, FunctionType,
+    CompiledFunction, CpuFeature, CustomSection, Dwarf, FunctionBody, FunctionIndex, FunctionType,
     LocalFunctionIndex, MemoryIndex, ModuleInfo, OperatingSystem, SectionIndex, TableIndex, Target,
     TrapCode, TrapInformation, VMOffsets,
 };
@@ -48,47 +51,23 @@ impl SinglepassCompiler {
     fn config(&self) -> &Singlepass {
         &self.config
     }
-}
-
-impl Compiler for SinglepassCompiler {
-    fn name(&self) -> &str {
-        "singlepass"
-    }
-
-    /// Get the middlewares for this compiler
-    fn get_middlewares(&self) -> &[Arc<dyn ModuleMiddleware>] {
-        &self.config.middlewares
-    }
 
-    /// Compile the module using Singlepass, producing a compilation result with
-    /// associated relocations.
-    fn compile_module(
+    /// Compiles the module with the threads of the current thread pool.
+    fn compile_module_in_thread_pool(
         &self,
         target: &Target,
         compile_info: &CompileModuleInfo,
-        _module_translation: &ModuleTranslationState,
         function_body_inputs: PrimaryMap<LocalFunctionIndex, FunctionBodyData<'_>>,
     ) -> Result<Compilation, CompileError> {
-        match target.triple().architecture {
-            Architecture::X86_64 => {}
-            Architecture::Aarch64(_) => {}
-            _ => {
-                return Err(CompileError::UnsupportedTarget(
-                    target.triple().architecture.to_string(),
-                ))
-            }
+        // The memory watch reports 32-bit offsets.
+        if self.config.enable_memory_watch
+            && compile_info.module.memories.values().any(|m| m.memory64)
+        {
+            return Err(CompileError::UnsupportedFeature(
+                "the memory watch doesn't support 64-bit memories".to_string(),
+            ));
         }
-
-        let calling_convention = match target.triple().default_calling_convention() {
-            Ok(CallingConvention::WindowsFastcall) => CallingConvention::WindowsFastcall,
-            Ok(CallingConvention::SystemV) => CallingConvention::SystemV,
-            Ok(CallingConvention::AppleAarch64) => CallingConvention::AppleAarch64,
-            _ => {
-                return Err(CompileError::UnsupportedTarget(
-                    "Unsupported Calling convention for Singlepass compiler".to_string(),
-                ))
-            }
-        };
+        let calling_convention = calling_convention(target, compile_info)?;
 
         // Generate the frametable
         #[cfg(feature = "unwind")]
@@ -113,8 +92,6 @@ impl Compiler for SinglepassCompiler {
             }
         };
 
-        let memory_styles = &compile_info.memory_styles;
-        let table_styles = &compile_info.table_styles;
         let vmoffsets = VMOffsets::new(8, &compile_info.module);
         let module = &compile_info.module;
         let mut custom_sections: PrimaryMap<SectionIndex, _> = (0..module.num_imported_functions)
@@ -139,69 +116,15 @@ impl Compiler for SinglepassCompiler {
             .collect::<Vec<(LocalFunctionIndex, &FunctionBodyData<'_>)>>()
             .into_par_iter_if_rayon()
             .map(|(i, input)| {
-                let middleware_chain = self
-                    .config
-                    .middlewares
-                    .generate_function_middleware_chain(i);
-                let mut reader =
-                    MiddlewareBinaryReader::new_with_offset(input.data, input.module_offset);
-                reader.set_middleware_chain(middleware_chain);
-
-                // This local list excludes arguments.
-                let mut locals = vec![];
-                let num_locals = reader.read_local_count()?;
-                for _ in 0..num_locals {
-                    let (count, ty) = reader.read_local_decl()?;
-                    for _ in 0..count {
-                        locals.push(ty);
-                    }
-                }
-
-                match target.triple().architecture {
-                    Architecture::X86_64 => {
-                        let machine = MachineX86_64::new(Some(target.clone()))?;
-                        let mut generator = FuncGen::new(
-                            module,
-                            &self.config,
-                            &vmoffsets,
-                            memory_styles,
-                            table_styles,
-                            i,
-                            &locals,
-                            machine,
-                            calling_convention,
-                        )?;
-                        while generator.has_control_frames() {
-                            generator.set_srcloc(reader.original_position() as u32);
-                            let op = reader.read_operator()?;
-                            generator.feed_operator(op)?;
-                        }
-
-                        generator.finalize(input)
-                    }
-                    Architecture::Aarch64(_) => {
-                        let machine = MachineARM64::new(Some(target.clone()));
-                        let mut generator = FuncGen::new(
-                            module,
-                            &self.config,
-                            &vmoffsets,
-                            memory_styles,
-                            table_styles,
-                            i,
-                            &locals,
-                            machine,
-                            calling_convention,
-                        )?;
-                        while generator.has_control_frames() {
-                            generator.set_srcloc(reader.original_position() as u32);
-                            let op = reader.read_operator()?;
-                            generator.feed_operator(op)?;
-                        }
-
-                        generator.finalize(input)
-                    }
-                    _ => unimplemented!(),
-                }
+                compile_function_body(
+                    &self.config,
+                    target,
+                    compile_info,
+                    &vmoffsets,
+                    calling_convention,
+                    i,
+                    input,
+                )
             })
             .collect::<Result<Vec<_>, CompileError>>()?
             .into_iter()
@@ -261,6 +184,108 @@ impl Compiler for SinglepassCompiler {
             debug: dwarf,
         })
     }
+}
+
+impl Compiler for SinglepassCompiler {
+    fn name(&self) -> &str {
+        "singlepass"
+    }
+
+    fn deterministic_id(&self) -> String {
+        let mut id = self.name().to_string();
+        if self.config.enable_memory_watch {
+            id.push_str("-memory-watch");
+        }
+        if self.config.enable_tier_up {
+            id.push_str("-tier-up");
+        }
+        if self.config.enable_lazy_compilation {
+            id.push_str("-lazy");
+        }
+        if self.config.enable_debugging {
+            id.push_str("-debugging");
+        }
+        id
+    }
+
+    fn tiers_up(&self) -> bool {
+        // The stubs of lazily compiled functions jump to them like tiered
+        // up functions.
+        self.config.enable_tier_up || self.config.enable_lazy_compilation
+    }
+
+    fn compiles_lazily(&self) -> bool {
+        self.config.enable_lazy_compilation
+    }
+
+    /// Get the middlewares for this compiler
+    fn get_middlewares(&self) -> &[Arc<dyn ModuleMiddleware>] {
+        &self.config.middlewares
+    }
+
+    /// Compile the module using Singlepass, producing a compilation result with
+    /// associated relocations.
+    fn compile_module(
+        &self,
+        target: &Target,
+        compile_info: &CompileModuleInfo,
+        _module_translation: &ModuleTranslationState,
+        function_body_inputs: PrimaryMap<LocalFunctionIndex, FunctionBodyData<'_>>,
+    ) -> Result<Compilation, CompileError> {
+        in_thread_pool(self.config.num_threads, || {
+            self.compile_module_in_thread_pool(target, compile_info, function_body_inputs)
+        })
+    }
+
+    fn compile_function(
+        &self,
+        target: &Target,
+        compile_info: &CompileModuleInfo,
+        _module_translation: &ModuleTranslationState,
+        index: LocalFunctionIndex,
+        function_body_input: &FunctionBodyData<'_>,
+    ) -> Result<(CompiledFunction, Option<CustomSection>), CompileError> {
+        let calling_convention = calling_convention(target, compile_info)?;
+        // The function is called through its stub, so it has no entry
+        // sequence of its own.
+        let config = Singlepass {
+            enable_tier_up: false,
+            enable_lazy_compilation: false,
+            ..self.config.clone()
+        };
+        let vmoffsets = VMOffsets::new(8, &compile_info.module);
+        #[cfg_attr(not(feature = "unwind"), allow(unused_variables))]
+        let (function, fde) = compile_function_body(
+            &config,
+            target,
+            compile_info,
+            &vmoffsets,
+            calling_convention,
+            index,
+            function_body_input,
+        )?;
+
+        // The unwind info of the function gets its own frame table.
+        #[cfg(feature = "unwind")]
+        let eh_frame = match (calling_convention, fde) {
+            (CallingConvention::SystemV, Some(UnwindFrame::SystemV(fde))) => {
+                create_systemv_cie(target.triple().architecture).map(|cie| {
+                    let mut dwarf_frametable = FrameTable::default();
+                    let cie_id = dwarf_frametable.add_cie(cie);
+                    dwarf_frametable.add_fde(cie_id, fde);
+                    let mut eh_frame =
+                        EhFrame(WriterRelocate::new(target.triple().endianness().ok()));
+                    dwarf_frametable.write_eh_frame(&mut eh_frame).unwrap();
+                    eh_frame.0.into_section()
+                })
+            }
+            _ => None,
+        };
+        #[cfg(not(feature = "unwind"))]
+        let eh_frame = None;
+
+        Ok((function, eh_frame))
+    }
 
     fn get_cpu_features_used(&self, cpu_features: &EnumSet<CpuFeature>) -> EnumSet<CpuFeature> {
         let used = CpuFeature::AVX | CpuFeature::SSE42 | CpuFeature::LZCNT | CpuFeature::BMI1;
@@ -268,6 +293,184 @@ impl Compiler for SinglepassCompiler {
     }
 }
 
+/// Checks that Singlepass can compile for `target`, and gets the calling
+/// convention of the compiled functions.
+fn calling_convention(
+    target: &Target,
+    compile_info: &CompileModuleInfo,
+) -> Result<CallingConvention, CompileError> {
+    match target.triple().architecture {
+        Architecture::X86_64 => {}
+        Architecture::Aarch64(_) => {}
+        Architecture::Riscv64(_) => {}
+        _ => {
+            return Err(CompileError::UnsupportedTarget(
+                target.triple().architecture.to_string(),
+            ))
+        }
+    }
+
+    // Exceptions thrown by callees must be checked after each call,
+    // which the generated code doesn't do.
+    if compile_info.features.exceptions {
+        return Err(CompileError::UnsupportedFeature(
+            "exceptions are not supported by the singlepass compiler".to_string(),
+        ));
+    }
+
+    match target.triple().default_calling_convention() {
+        Ok(CallingConvention::WindowsFastcall) => Ok(CallingConvention::WindowsFastcall),
+        Ok(CallingConvention::SystemV) => Ok(CallingConvention::SystemV),
+        Ok(CallingConvention::AppleAarch64) => Ok(CallingConvention::AppleAarch64),
+        _ => Err(CompileError::UnsupportedTarget(
+            "Unsupported Calling convention for Singlepass compiler".to_string(),
+        )),
+    }
+}
+
+/// Compiles the function at `i`, or its stub when compiling lazily.
+fn compile_function_body(
+    config: &Singlepass,
+    target: &Target,
+    compile_info: &CompileModuleInfo,
+    vmoffsets: &VMOffsets,
+    calling_convention: CallingConvention,
+    i: LocalFunctionIndex,
+    input: &FunctionBodyData<'_>,
+) -> Result<(CompiledFunction, Option<UnwindFrame>), CompileError> {
+    let module = &compile_info.module;
+    let memory_styles = &compile_info.memory_styles;
+    let table_styles = &compile_info.table_styles;
+    let middleware_chain = config.middlewares.generate_function_middleware_chain(i);
+    let mut reader = MiddlewareBinaryReader::new_with_offset(input.data, input.module_offset);
+    reader.set_middleware_chain(middleware_chain);
+    reader.allow_memarg64(compile_info.features.memory64);
+
+    // This local list excludes arguments. Stubs don't have any locals.
+    let mut locals = vec![];
+    if !config.enable_lazy_compilation {
+        let num_locals = reader.read_local_count()?;
+        for _ in 0..num_locals {
+            let (count, ty) = reader.read_local_decl()?;
+            for _ in 0..count {
+                locals.push(ty);
+            }
+        }
+    }
+
+    match target.triple().architecture {
+        Architecture::X86_64 => {
+            let machine = MachineX86_64::new(Some(target.clone()))?;
+            let generator = FuncGen::new(
+                module,
+                config,
+                vmoffsets,
+                memory_styles,
+                table_styles,
+                i,
+                &locals,
+                machine,
+                calling_convention,
+            )?;
+            feed_function(
+                generator,
+                &mut reader,
+                input,
+                config.enable_lazy_compilation,
+            )
+        }
+        Architecture::Aarch64(_) => {
+            let machine = MachineARM64::new(Some(target.clone()));
+            let generator = FuncGen::new(
+                module,
+                config,
+                vmoffsets,
+                memory_styles,
+                table_styles,
+                i,
+                &locals,
+                machine,
+                calling_convention,
+            )?;
+            feed_function(
+                generator,
+                &mut reader,
+                input,
+                config.enable_lazy_compilation,
+            )
+        }
+        Architecture::Riscv64(_) => {
+            let machine = MachineRiscv::new(Some(target.clone()));
+            let generator = FuncGen::new(
+                module,
+                config,
+                vmoffsets,
+                memory_styles,
+                table_styles,
+                i,
+                &locals,
+                machine,
+                calling_convention,
+            )?;
+            feed_function(
+                generator,
+                &mut reader,
+                input,
+                config.enable_lazy_compilation,
+            )
+        }
+        _ => unimplemented!(),
+    }
+}
+
+/// Feeds the operators of a function to its generator, or emits its stub.
+fn feed_function<M: Machine>(
+    mut generator: FuncGen<'_, M>,
+    reader: &mut MiddlewareBinaryReader<'_>,
+    input: &FunctionBodyData<'_>,
+    stub: bool,
+) -> Result<(CompiledFunction, Option<UnwindFrame>), CompileError> {
+    if stub {
+        generator.emit_lazy_stub()?;
+    } else {
+        while generator.has_control_frames() {
+            generator.set_srcloc(reader.original_position() as u32);
+            let op = reader.read_operator()?;
+            generator.feed_operator(op)?;
+        }
+    }
+    generator.finalize(input)
+}
+
+/// Runs `f` in a thread pool of `num_threads` threads, or in the global
+/// thread pool without a number of threads.
+#[cfg(feature = "rayon")]
+fn in_thread_pool<R: Send>(
+    num_threads: Option<NonZeroUsize>,
+    f: impl FnOnce() -> Result<R, CompileError> + Send,
+) -> Result<R, CompileError> {
+    match num_threads {
+        Some(num_threads) => {
+            let pool = rayon::ThreadPoolBuilder::new()
+                .num_threads(num_threads.get())
+                .build()
+                .map_err(|e| CompileError::Resource(e.to_string()))?;
+            pool.install(f)
+        }
+        None => f(),
+    }
+}
+
+/// Runs `f` on the current thread, as everything is compiled on it without
+/// rayon.
+#[cfg(not(feature = "rayon"))]
+fn in_thread_pool<R>(
+    _num_threads: Option<NonZeroUsize>,
+    f: impl FnOnce() -> Result<R, CompileError>,
+) -> Result<R, CompileError> {
+    f()
+}
+
 trait IntoParIterIfRayon {
     type Output;
     fn into_par_iter_if_rayon(self) -> Self::Output;
@@ -2,6 +2,7 @@
 #![allow(unused_imports, dead_code)]
 
 use crate::compiler::SinglepassCompiler;
+use std::num::NonZeroUsize;
 use std::sync::Arc;
 use wasmer_compiler::{Compiler, CompilerConfig, Engine, EngineBuilder, ModuleMiddleware};
 use wasmer_types::{CpuFeature, Features, Target};
@@ -9,6 +10,11 @@ use wasmer_types::{CpuFeature, Features, Target};
 #[derive(Debug, Clone)]
 pub struct Singlepass {
     pub(crate) enable_nan_canonicalization: bool,
+    pub(crate) enable_memory_watch: bool,
+    pub(crate) enable_tier_up: bool,
+    pub(crate) enable_lazy_compilation: bool,
+    pub(crate) enable_debugging: bool,
+    pub(crate) num_threads: Option<NonZeroUsize>,
     /// The middleware chain.
     pub(crate) middlewares: Vec<Arc<dyn ModuleMiddleware>>,
 }
@@ -19,6 +25,11 @@ impl Singlepass {
     pub fn new() -> Self {
         Self {
             enable_nan_canonicalization: true,
+            enable_memory_watch: false,
+            enable_tier_up: false,
+            enable_lazy_compilation: false,
+            enable_debugging: false,
+            num_threads: None,
             middlewares: vec![],
         }
     }
@@ -35,6 +46,26 @@ impl CompilerConfig for Singlepass {
         // PIC code.
     }
 
+    fn enable_memory_watch(&mut self) {
+        self.enable_memory_watch = true;
+    }
+
+    fn enable_tier_up(&mut self) {
+        self.enable_tier_up = true;
+    }
+
+    fn enable_lazy_compilation(&mut self) {
+        self.enable_lazy_compilation = true;
+    }
+
+    fn enable_debugging(&mut self) {
+        self.enable_debugging = true;
+    }
+
+    fn num_threads(&mut self, num_threads: Option<NonZeroUsize>) {
+        self.num_threads = num_threads;
+    }
+
     /// Transform it into the compiler
     fn compiler(self: Box<Self>) -> Box<dyn Compiler> {
         Box::new(SinglepassCompiler::new(*self))
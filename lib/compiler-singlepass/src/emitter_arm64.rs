@@ -84,6 +84,108 @@ pub enum GPROrMemory {
     Memory(GPR, i32),
 }
 
+/// The arrangement of the lanes of a 128-bit NEON register.
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+pub enum Lanes {
+    B16,
+    H8,
+    S4,
+    D2,
+}
+
+/// NEON instructions on three registers with the same arrangement.
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+pub enum Neon3Op {
+    Add,
+    Sub,
+    Mul,
+    Sqadd,
+    Uqadd,
+    Sqsub,
+    Uqsub,
+    Smin,
+    Umin,
+    Smax,
+    Umax,
+    Urhadd,
+    Cmeq,
+    Cmgt,
+    Cmge,
+    Cmhi,
+    Cmhs,
+    Sshl,
+    Ushl,
+    Sqrdmulh,
+    Addp,
+    Zip1,
+    Fadd,
+    Fsub,
+    Fmul,
+    Fdiv,
+    Fmin,
+    Fmax,
+    Fcmeq,
+    Fcmgt,
+    Fcmge,
+    And,
+    Orr,
+    Eor,
+    Bic,
+    Bsl,
+}
+
+/// NEON instructions on two registers with the same arrangement, the
+/// `Zero` ones comparing against zero.
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+pub enum Neon2Op {
+    Abs,
+    Neg,
+    Cnt,
+    Not,
+    Fabs,
+    Fneg,
+    Fsqrt,
+    Frintn,
+    Frintm,
+    Frintp,
+    Frintz,
+    Scvtf,
+    Ucvtf,
+    Fcvtzs,
+    Fcvtzu,
+    CmeqZero,
+    CmltZero,
+}
+
+/// NEON instructions widening or narrowing their lanes, which are given
+/// by the arrangement of the narrow lanes.
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+pub enum NeonWidthOp {
+    Sxtl,
+    Sxtl2,
+    Uxtl,
+    Uxtl2,
+    Saddlp,
+    Uaddlp,
+    Sqxtn,
+    Sqxtn2,
+    Sqxtun,
+    Sqxtun2,
+    Uqxtn,
+    Fcvtl,
+    Fcvtn,
+}
+
+/// NEON multiplications widening their lanes, which are given by the
+/// arrangement of the narrow lanes.
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+pub enum NeonLongOp {
+    Smull,
+    Smull2,
+    Umull,
+    Umull2,
+}
+
 pub trait EmitterARM64 {
     fn get_label(&mut self) -> Label;
     fn get_offset(&self) -> Offset;
@@ -481,6 +583,86 @@ pub trait EmitterARM64 {
     ) -> Result<(), CompileError>;
     fn emit_cnt(&mut self, src: NEON, dst: NEON) -> Result<(), CompileError>;
     fn emit_addv(&mut self, src: NEON, dst: NEON) -> Result<(), CompileError>;
+    fn emit_neon3(
+        &mut self,
+        op: Neon3Op,
+        lanes: Lanes,
+        src1: NEON,
+        src2: NEON,
+        dst: NEON,
+    ) -> Result<(), CompileError>;
+    fn emit_neon2(
+        &mut self,
+        op: Neon2Op,
+        lanes: Lanes,
+        src: NEON,
+        dst: NEON,
+    ) -> Result<(), CompileError>;
+    fn emit_neon_width(
+        &mut self,
+        op: NeonWidthOp,
+        lanes: Lanes,
+        src: NEON,
+        dst: NEON,
+    ) -> Result<(), CompileError>;
+    fn emit_neon_long(
+        &mut self,
+        op: NeonLongOp,
+        lanes: Lanes,
+        src1: NEON,
+        src2: NEON,
+        dst: NEON,
+    ) -> Result<(), CompileError>;
+    fn emit_neon_addv(&mut self, lanes: Lanes, src: NEON, dst: NEON) -> Result<(), CompileError>;
+    fn emit_neon_umaxv(&mut self, src: NEON, dst: NEON) -> Result<(), CompileError>;
+    fn emit_neon_dup(&mut self, lanes: Lanes, src: GPR, dst: NEON) -> Result<(), CompileError>;
+    fn emit_neon_ins(
+        &mut self,
+        lanes: Lanes,
+        src: GPR,
+        lane: u32,
+        dst: NEON,
+    ) -> Result<(), CompileError>;
+    fn emit_neon_umov(
+        &mut self,
+        lanes: Lanes,
+        signed: bool,
+        src: NEON,
+        lane: u32,
+        dst: GPR,
+    ) -> Result<(), CompileError>;
+    fn emit_neon_ext(
+        &mut self,
+        src1: NEON,
+        src2: NEON,
+        imm: u32,
+        dst: NEON,
+    ) -> Result<(), CompileError>;
+    /// Looks `idx` up in the table made of `table` and the register after it.
+    fn emit_neon_tbl(
+        &mut self,
+        table: NEON,
+        two_registers: bool,
+        idx: NEON,
+        dst: NEON,
+    ) -> Result<(), CompileError>;
+    fn emit_neon_ldr_q(&mut self, dst: NEON, addr: GPR) -> Result<(), CompileError>;
+    fn emit_neon_str_q(&mut self, src: NEON, addr: GPR) -> Result<(), CompileError>;
+    fn emit_neon_ld1r(&mut self, lanes: Lanes, dst: NEON, addr: GPR) -> Result<(), CompileError>;
+    fn emit_neon_ld1_lane(
+        &mut self,
+        lanes: Lanes,
+        dst: NEON,
+        lane: u32,
+        addr: GPR,
+    ) -> Result<(), CompileError>;
+    fn emit_neon_st1_lane(
+        &mut self,
+        lanes: Lanes,
+        src: NEON,
+        lane: u32,
+        addr: GPR,
+    ) -> Result<(), CompileError>;
     fn emit_read_fpcr(&mut self, reg: GPR) -> Result<(), CompileError>;
     fn emit_write_fpcr(&mut self, reg: GPR) -> Result<(), CompileError>;
     fn emit_read_fpsr(&mut self, reg: GPR) -> Result<(), CompileError>;
@@ -3272,6 +3454,440 @@ impl EmitterARM64 for Assembler {
         dynasm!(self ; addv B(dst.into_index() as u32), V(src.into_index() as u32).B8);
         Ok(())
     }
+    fn emit_neon3(
+        &mut self,
+        op: Neon3Op,
+        lanes: Lanes,
+        src1: NEON,
+        src2: NEON,
+        dst: NEON,
+    ) -> Result<(), CompileError> {
+        let (src1, src2, dst) = (src1 as u32, src2 as u32, dst as u32);
+        match (op, lanes) {
+            (Neon3Op::Add, Lanes::B16) => dynasm!(self ; add V(dst).B16, V(src1).B16, V(src2).B16),
+            (Neon3Op::Add, Lanes::H8) => dynasm!(self ; add V(dst).H8, V(src1).H8, V(src2).H8),
+            (Neon3Op::Add, Lanes::S4) => dynasm!(self ; add V(dst).S4, V(src1).S4, V(src2).S4),
+            (Neon3Op::Add, Lanes::D2) => dynasm!(self ; add V(dst).D2, V(src1).D2, V(src2).D2),
+            (Neon3Op::Sub, Lanes::B16) => dynasm!(self ; sub V(dst).B16, V(src1).B16, V(src2).B16),
+            (Neon3Op::Sub, Lanes::H8) => dynasm!(self ; sub V(dst).H8, V(src1).H8, V(src2).H8),
+            (Neon3Op::Sub, Lanes::S4) => dynasm!(self ; sub V(dst).S4, V(src1).S4, V(src2).S4),
+            (Neon3Op::Sub, Lanes::D2) => dynasm!(self ; sub V(dst).D2, V(src1).D2, V(src2).D2),
+            (Neon3Op::Mul, Lanes::B16) => dynasm!(self ; mul V(dst).B16, V(src1).B16, V(src2).B16),
+            (Neon3Op::Mul, Lanes::H8) => dynasm!(self ; mul V(dst).H8, V(src1).H8, V(src2).H8),
+            (Neon3Op::Mul, Lanes::S4) => dynasm!(self ; mul V(dst).S4, V(src1).S4, V(src2).S4),
+            (Neon3Op::Sqadd, Lanes::B16) => {
+                dynasm!(self ; sqadd V(dst).B16, V(src1).B16, V(src2).B16)
+            }
+            (Neon3Op::Sqadd, Lanes::H8) => dynasm!(self ; sqadd V(dst).H8, V(src1).H8, V(src2).H8),
+            (Neon3Op::Uqadd, Lanes::B16) => {
+                dynasm!(self ; uqadd V(dst).B16, V(src1).B16, V(src2).B16)
+            }
+            (Neon3Op::Uqadd, Lanes::H8) => dynasm!(self ; uqadd V(dst).H8, V(src1).H8, V(src2).H8),
+            (Neon3Op::Sqsub, Lanes::B16) => {
+                dynasm!(self ; sqsub V(dst).B16, V(src1).B16, V(src2).B16)
+            }
+            (Neon3Op::Sqsub, Lanes::H8) => dynasm!(self ; sqsub V(dst).H8, V(src1).H8, V(src2).H8),
+            (Neon3Op::Uqsub, Lanes::B16) => {
+                dynasm!(self ; uqsub V(dst).B16, V(src1).B16, V(src2).B16)
+            }
+            (Neon3Op::Uqsub, Lanes::H8) => dynasm!(self ; uqsub V(dst).H8, V(src1).H8, V(src2).H8),
+            (Neon3Op::Smin, Lanes::B16) => {
+                dynasm!(self ; smin V(dst).B16, V(src1).B16, V(src2).B16)
+            }
+            (Neon3Op::Smin, Lanes::H8) => dynasm!(self ; smin V(dst).H8, V(src1).H8, V(src2).H8),
+            (Neon3Op::Smin, Lanes::S4) => dynasm!(self ; smin V(dst).S4, V(src1).S4, V(src2).S4),
+            (Neon3Op::Umin, Lanes::B16) => {
+                dynasm!(self ; umin V(dst).B16, V(src1).B16, V(src2).B16)
+            }
+            (Neon3Op::Umin, Lanes::H8) => dynasm!(self ; umin V(dst).H8, V(src1).H8, V(src2).H8),
+            (Neon3Op::Umin, Lanes::S4) => dynasm!(self ; umin V(dst).S4, V(src1).S4, V(src2).S4),
+            (Neon3Op::Smax, Lanes::B16) => {
+                dynasm!(self ; smax V(dst).B16, V(src1).B16, V(src2).B16)
+            }
+            (Neon3Op::Smax, Lanes::H8) => dynasm!(self ; smax V(dst).H8, V(src1).H8, V(src2).H8),
+            (Neon3Op::Smax, Lanes::S4) => dynasm!(self ; smax V(dst).S4, V(src1).S4, V(src2).S4),
+            (Neon3Op::Umax, Lanes::B16) => {
+                dynasm!(self ; umax V(dst).B16, V(src1).B16, V(src2).B16)
+            }
+            (Neon3Op::Umax, Lanes::H8) => dynasm!(self ; umax V(dst).H8, V(src1).H8, V(src2).H8),
+            (Neon3Op::Umax, Lanes::S4) => dynasm!(self ; umax V(dst).S4, V(src1).S4, V(src2).S4),
+            (Neon3Op::Urhadd, Lanes::B16) => {
+                dynasm!(self ; urhadd V(dst).B16, V(src1).B16, V(src2).B16)
+            }
+            (Neon3Op::Urhadd, Lanes::H8) => {
+                dynasm!(self ; urhadd V(dst).H8, V(src1).H8, V(src2).H8)
+            }
+            (Neon3Op::Cmeq, Lanes::B16) => {
+                dynasm!(self ; cmeq V(dst).B16, V(src1).B16, V(src2).B16)
+            }
+            (Neon3Op::Cmeq, Lanes::H8) => dynasm!(self ; cmeq V(dst).H8, V(src1).H8, V(src2).H8),
+            (Neon3Op::Cmeq, Lanes::S4) => dynasm!(self ; cmeq V(dst).S4, V(src1).S4, V(src2).S4),
+            (Neon3Op::Cmeq, Lanes::D2) => dynasm!(self ; cmeq V(dst).D2, V(src1).D2, V(src2).D2),
+            (Neon3Op::Cmgt, Lanes::B16) => {
+                dynasm!(self ; cmgt V(dst).B16, V(src1).B16, V(src2).B16)
+            }
+            (Neon3Op::Cmgt, Lanes::H8) => dynasm!(self ; cmgt V(dst).H8, V(src1).H8, V(src2).H8),
+            (Neon3Op::Cmgt, Lanes::S4) => dynasm!(self ; cmgt V(dst).S4, V(src1).S4, V(src2).S4),
+            (Neon3Op::Cmgt, Lanes::D2) => dynasm!(self ; cmgt V(dst).D2, V(src1).D2, V(src2).D2),
+            (Neon3Op::Cmge, Lanes::B16) => {
+                dynasm!(self ; cmge V(dst).B16, V(src1).B16, V(src2).B16)
+            }
+            (Neon3Op::Cmge, Lanes::H8) => dynasm!(self ; cmge V(dst).H8, V(src1).H8, V(src2).H8),
+            (Neon3Op::Cmge, Lanes::S4) => dynasm!(self ; cmge V(dst).S4, V(src1).S4, V(src2).S4),
+            (Neon3Op::Cmge, Lanes::D2) => dynasm!(self ; cmge V(dst).D2, V(src1).D2, V(src2).D2),
+            (Neon3Op::Cmhi, Lanes::B16) => {
+                dynasm!(self ; cmhi V(dst).B16, V(src1).B16, V(src2).B16)
+            }
+            (Neon3Op::Cmhi, Lanes::H8) => dynasm!(self ; cmhi V(dst).H8, V(src1).H8, V(src2).H8),
+            (Neon3Op::Cmhi, Lanes::S4) => dynasm!(self ; cmhi V(dst).S4, V(src1).S4, V(src2).S4),
+            (Neon3Op::Cmhi, Lanes::D2) => dynasm!(self ; cmhi V(dst).D2, V(src1).D2, V(src2).D2),
+            (Neon3Op::Cmhs, Lanes::B16) => {
+                dynasm!(self ; cmhs V(dst).B16, V(src1).B16, V(src2).B16)
+            }
+            (Neon3Op::Cmhs, Lanes::H8) => dynasm!(self ; cmhs V(dst).H8, V(src1).H8, V(src2).H8),
+            (Neon3Op::Cmhs, Lanes::S4) => dynasm!(self ; cmhs V(dst).S4, V(src1).S4, V(src2).S4),
+            (Neon3Op::Cmhs, Lanes::D2) => dynasm!(self ; cmhs V(dst).D2, V(src1).D2, V(src2).D2),
+            (Neon3Op::Sshl, Lanes::B16) => {
+                dynasm!(self ; sshl V(dst).B16, V(src1).B16, V(src2).B16)
+            }
+            (Neon3Op::Sshl, Lanes::H8) => dynasm!(self ; sshl V(dst).H8, V(src1).H8, V(src2).H8),
+            (Neon3Op::Sshl, Lanes::S4) => dynasm!(self ; sshl V(dst).S4, V(src1).S4, V(src2).S4),
+            (Neon3Op::Sshl, Lanes::D2) => dynasm!(self ; sshl V(dst).D2, V(src1).D2, V(src2).D2),
+            (Neon3Op::Ushl, Lanes::B16) => {
+                dynasm!(self ; ushl V(dst).B16, V(src1).B16, V(src2).B16)
+            }
+            (Neon3Op::Ushl, Lanes::H8) => dynasm!(self ; ushl V(dst).H8, V(src1).H8, V(src2).H8),
+            (Neon3Op::Ushl, Lanes::S4) => dynasm!(self ; ushl V(dst).S4, V(src1).S4, V(src2).S4),
+            (Neon3Op::Ushl, Lanes::D2) => dynasm!(self ; ushl V(dst).D2, V(src1).D2, V(src2).D2),
+            (Neon3Op::Sqrdmulh, Lanes::H8) => {
+                dynasm!(self ; sqrdmulh V(dst).H8, V(src1).H8, V(src2).H8)
+            }
+            (Neon3Op::Addp, Lanes::B16) => {
+                dynasm!(self ; addp V(dst).B16, V(src1).B16, V(src2).B16)
+            }
+            (Neon3Op::Addp, Lanes::H8) => dynasm!(self ; addp V(dst).H8, V(src1).H8, V(src2).H8),
+            (Neon3Op::Addp, Lanes::S4) => dynasm!(self ; addp V(dst).S4, V(src1).S4, V(src2).S4),
+            (Neon3Op::Addp, Lanes::D2) => dynasm!(self ; addp V(dst).D2, V(src1).D2, V(src2).D2),
+            (Neon3Op::Zip1, Lanes::B16) => {
+                dynasm!(self ; zip1 V(dst).B16, V(src1).B16, V(src2).B16)
+            }
+            (Neon3Op::Zip1, Lanes::H8) => dynasm!(self ; zip1 V(dst).H8, V(src1).H8, V(src2).H8),
+            (Neon3Op::Zip1, Lanes::S4) => dynasm!(self ; zip1 V(dst).S4, V(src1).S4, V(src2).S4),
+            (Neon3Op::Zip1, Lanes::D2) => dynasm!(self ; zip1 V(dst).D2, V(src1).D2, V(src2).D2),
+            (Neon3Op::Fadd, Lanes::S4) => dynasm!(self ; fadd V(dst).S4, V(src1).S4, V(src2).S4),
+            (Neon3Op::Fadd, Lanes::D2) => dynasm!(self ; fadd V(dst).D2, V(src1).D2, V(src2).D2),
+            (Neon3Op::Fsub, Lanes::S4) => dynasm!(self ; fsub V(dst).S4, V(src1).S4, V(src2).S4),
+            (Neon3Op::Fsub, Lanes::D2) => dynasm!(self ; fsub V(dst).D2, V(src1).D2, V(src2).D2),
+            (Neon3Op::Fmul, Lanes::S4) => dynasm!(self ; fmul V(dst).S4, V(src1).S4, V(src2).S4),
+            (Neon3Op::Fmul, Lanes::D2) => dynasm!(self ; fmul V(dst).D2, V(src1).D2, V(src2).D2),
+            (Neon3Op::Fdiv, Lanes::S4) => dynasm!(self ; fdiv V(dst).S4, V(src1).S4, V(src2).S4),
+            (Neon3Op::Fdiv, Lanes::D2) => dynasm!(self ; fdiv V(dst).D2, V(src1).D2, V(src2).D2),
+            (Neon3Op::Fmin, Lanes::S4) => dynasm!(self ; fmin V(dst).S4, V(src1).S4, V(src2).S4),
+            (Neon3Op::Fmin, Lanes::D2) => dynasm!(self ; fmin V(dst).D2, V(src1).D2, V(src2).D2),
+            (Neon3Op::Fmax, Lanes::S4) => dynasm!(self ; fmax V(dst).S4, V(src1).S4, V(src2).S4),
+            (Neon3Op::Fmax, Lanes::D2) => dynasm!(self ; fmax V(dst).D2, V(src1).D2, V(src2).D2),
+            (Neon3Op::Fcmeq, Lanes::S4) => dynasm!(self ; fcmeq V(dst).S4, V(src1).S4, V(src2).S4),
+            (Neon3Op::Fcmeq, Lanes::D2) => dynasm!(self ; fcmeq V(dst).D2, V(src1).D2, V(src2).D2),
+            (Neon3Op::Fcmgt, Lanes::S4) => dynasm!(self ; fcmgt V(dst).S4, V(src1).S4, V(src2).S4),
+            (Neon3Op::Fcmgt, Lanes::D2) => dynasm!(self ; fcmgt V(dst).D2, V(src1).D2, V(src2).D2),
+            (Neon3Op::Fcmge, Lanes::S4) => dynasm!(self ; fcmge V(dst).S4, V(src1).S4, V(src2).S4),
+            (Neon3Op::Fcmge, Lanes::D2) => dynasm!(self ; fcmge V(dst).D2, V(src1).D2, V(src2).D2),
+            (Neon3Op::And, _) => dynasm!(self ; and V(dst).B16, V(src1).B16, V(src2).B16),
+            (Neon3Op::Orr, _) => dynasm!(self ; orr V(dst).B16, V(src1).B16, V(src2).B16),
+            (Neon3Op::Eor, _) => dynasm!(self ; eor V(dst).B16, V(src1).B16, V(src2).B16),
+            (Neon3Op::Bic, _) => dynasm!(self ; bic V(dst).B16, V(src1).B16, V(src2).B16),
+            (Neon3Op::Bsl, _) => dynasm!(self ; bsl V(dst).B16, V(src1).B16, V(src2).B16),
+            _ => codegen_error!("singlepass can't emit {:?} {:?}", op, lanes),
+        }
+        Ok(())
+    }
+    fn emit_neon2(
+        &mut self,
+        op: Neon2Op,
+        lanes: Lanes,
+        src: NEON,
+        dst: NEON,
+    ) -> Result<(), CompileError> {
+        let (src, dst) = (src as u32, dst as u32);
+        match (op, lanes) {
+            (Neon2Op::Abs, Lanes::B16) => dynasm!(self ; abs V(dst).B16, V(src).B16),
+            (Neon2Op::Abs, Lanes::H8) => dynasm!(self ; abs V(dst).H8, V(src).H8),
+            (Neon2Op::Abs, Lanes::S4) => dynasm!(self ; abs V(dst).S4, V(src).S4),
+            (Neon2Op::Abs, Lanes::D2) => dynasm!(self ; abs V(dst).D2, V(src).D2),
+            (Neon2Op::Neg, Lanes::B16) => dynasm!(self ; neg V(dst).B16, V(src).B16),
+            (Neon2Op::Neg, Lanes::H8) => dynasm!(self ; neg V(dst).H8, V(src).H8),
+            (Neon2Op::Neg, Lanes::S4) => dynasm!(self ; neg V(dst).S4, V(src).S4),
+            (Neon2Op::Neg, Lanes::D2) => dynasm!(self ; neg V(dst).D2, V(src).D2),
+            (Neon2Op::Cnt, _) => dynasm!(self ; cnt V(dst).B16, V(src).B16),
+            (Neon2Op::Not, _) => dynasm!(self ; not V(dst).B16, V(src).B16),
+            (Neon2Op::Fabs, Lanes::S4) => dynasm!(self ; fabs V(dst).S4, V(src).S4),
+            (Neon2Op::Fabs, Lanes::D2) => dynasm!(self ; fabs V(dst).D2, V(src).D2),
+            (Neon2Op::Fneg, Lanes::S4) => dynasm!(self ; fneg V(dst).S4, V(src).S4),
+            (Neon2Op::Fneg, Lanes::D2) => dynasm!(self ; fneg V(dst).D2, V(src).D2),
+            (Neon2Op::Fsqrt, Lanes::S4) => dynasm!(self ; fsqrt V(dst).S4, V(src).S4),
+            (Neon2Op::Fsqrt, Lanes::D2) => dynasm!(self ; fsqrt V(dst).D2, V(src).D2),
+            (Neon2Op::Frintn, Lanes::S4) => dynasm!(self ; frintn V(dst).S4, V(src).S4),
+            (Neon2Op::Frintn, Lanes::D2) => dynasm!(self ; frintn V(dst).D2, V(src).D2),
+            (Neon2Op::Frintm, Lanes::S4) => dynasm!(self ; frintm V(dst).S4, V(src).S4),
+            (Neon2Op::Frintm, Lanes::D2) => dynasm!(self ; frintm V(dst).D2, V(src).D2),
+            (Neon2Op::Frintp, Lanes::S4) => dynasm!(self ; frintp V(dst).S4, V(src).S4),
+            (Neon2Op::Frintp, Lanes::D2) => dynasm!(self ; frintp V(dst).D2, V(src).D2),
+            (Neon2Op::Frintz, Lanes::S4) => dynasm!(self ; frintz V(dst).S4, V(src).S4),
+            (Neon2Op::Frintz, Lanes::D2) => dynasm!(self ; frintz V(dst).D2, V(src).D2),
+            (Neon2Op::Scvtf, Lanes::S4) => dynasm!(self ; scvtf V(dst).S4, V(src).S4),
+            (Neon2Op::Scvtf, Lanes::D2) => dynasm!(self ; scvtf V(dst).D2, V(src).D2),
+            (Neon2Op::Ucvtf, Lanes::S4) => dynasm!(self ; ucvtf V(dst).S4, V(src).S4),
+            (Neon2Op::Ucvtf, Lanes::D2) => dynasm!(self ; ucvtf V(dst).D2, V(src).D2),
+            (Neon2Op::Fcvtzs, Lanes::S4) => dynasm!(self ; fcvtzs V(dst).S4, V(src).S4),
+            (Neon2Op::Fcvtzs, Lanes::D2) => dynasm!(self ; fcvtzs V(dst).D2, V(src).D2),
+            (Neon2Op::Fcvtzu, Lanes::S4) => dynasm!(self ; fcvtzu V(dst).S4, V(src).S4),
+            (Neon2Op::Fcvtzu, Lanes::D2) => dynasm!(self ; fcvtzu V(dst).D2, V(src).D2),
+            (Neon2Op::CmeqZero, Lanes::B16) => dynasm!(self ; cmeq V(dst).B16, V(src).B16, 0),
+            (Neon2Op::CmeqZero, Lanes::H8) => dynasm!(self ; cmeq V(dst).H8, V(src).H8, 0),
+            (Neon2Op::CmeqZero, Lanes::S4) => dynasm!(self ; cmeq V(dst).S4, V(src).S4, 0),
+            (Neon2Op::CmeqZero, Lanes::D2) => dynasm!(self ; cmeq V(dst).D2, V(src).D2, 0),
+            (Neon2Op::CmltZero, Lanes::B16) => dynasm!(self ; cmlt V(dst).B16, V(src).B16, 0),
+            (Neon2Op::CmltZero, Lanes::H8) => dynasm!(self ; cmlt V(dst).H8, V(src).H8, 0),
+            (Neon2Op::CmltZero, Lanes::S4) => dynasm!(self ; cmlt V(dst).S4, V(src).S4, 0),
+            (Neon2Op::CmltZero, Lanes::D2) => dynasm!(self ; cmlt V(dst).D2, V(src).D2, 0),
+            _ => codegen_error!("singlepass can't emit {:?} {:?}", op, lanes),
+        }
+        Ok(())
+    }
+    fn emit_neon_width(
+        &mut self,
+        op: NeonWidthOp,
+        lanes: Lanes,
+        src: NEON,
+        dst: NEON,
+    ) -> Result<(), CompileError> {
+        let (src, dst) = (src as u32, dst as u32);
+        match (op, lanes) {
+            (NeonWidthOp::Sxtl, Lanes::B16) => dynasm!(self ; sxtl V(dst).H8, V(src).B8),
+            (NeonWidthOp::Sxtl, Lanes::H8) => dynasm!(self ; sxtl V(dst).S4, V(src).H4),
+            (NeonWidthOp::Sxtl, Lanes::S4) => dynasm!(self ; sxtl V(dst).D2, V(src).S2),
+            (NeonWidthOp::Sxtl2, Lanes::B16) => dynasm!(self ; sxtl2 V(dst).H8, V(src).B16),
+            (NeonWidthOp::Sxtl2, Lanes::H8) => dynasm!(self ; sxtl2 V(dst).S4, V(src).H8),
+            (NeonWidthOp::Sxtl2, Lanes::S4) => dynasm!(self ; sxtl2 V(dst).D2, V(src).S4),
+            (NeonWidthOp::Uxtl, Lanes::B16) => dynasm!(self ; uxtl V(dst).H8, V(src).B8),
+            (NeonWidthOp::Uxtl, Lanes::H8) => dynasm!(self ; uxtl V(dst).S4, V(src).H4),
+            (NeonWidthOp::Uxtl, Lanes::S4) => dynasm!(self ; uxtl V(dst).D2, V(src).S2),
+            (NeonWidthOp::Uxtl2, Lanes::B16) => dynasm!(self ; uxtl2 V(dst).H8, V(src).B16),
+            (NeonWidthOp::Uxtl2, Lanes::H8) => dynasm!(self ; uxtl2 V(dst).S4, V(src).H8),
+            (NeonWidthOp::Uxtl2, Lanes::S4) => dynasm!(self ; uxtl2 V(dst).D2, V(src).S4),
+            (NeonWidthOp::Saddlp, Lanes::B16) => dynasm!(self ; saddlp V(dst).H8, V(src).B16),
+            (NeonWidthOp::Saddlp, Lanes::H8) => dynasm!(self ; saddlp V(dst).S4, V(src).H8),
+            (NeonWidthOp::Saddlp, Lanes::S4) => dynasm!(self ; saddlp V(dst).D2, V(src).S4),
+            (NeonWidthOp::Uaddlp, Lanes::B16) => dynasm!(self ; uaddlp V(dst).H8, V(src).B16),
+            (NeonWidthOp::Uaddlp, Lanes::H8) => dynasm!(self ; uaddlp V(dst).S4, V(src).H8),
+            (NeonWidthOp::Uaddlp, Lanes::S4) => dynasm!(self ; uaddlp V(dst).D2, V(src).S4),
+            (NeonWidthOp::Sqxtn, Lanes::B16) => dynasm!(self ; sqxtn V(dst).B8, V(src).H8),
+            (NeonWidthOp::Sqxtn, Lanes::H8) => dynasm!(self ; sqxtn V(dst).H4, V(src).S4),
+            (NeonWidthOp::Sqxtn, Lanes::S4) => dynasm!(self ; sqxtn V(dst).S2, V(src).D2),
+            (NeonWidthOp::Sqxtn2, Lanes::B16) => dynasm!(self ; sqxtn2 V(dst).B16, V(src).H8),
+            (NeonWidthOp::Sqxtn2, Lanes::H8) => dynasm!(self ; sqxtn2 V(dst).H8, V(src).S4),
+            (NeonWidthOp::Sqxtn2, Lanes::S4) => dynasm!(self ; sqxtn2 V(dst).S4, V(src).D2),
+            (NeonWidthOp::Sqxtun, Lanes::B16) => dynasm!(self ; sqxtun V(dst).B8, V(src).H8),
+            (NeonWidthOp::Sqxtun, Lanes::H8) => dynasm!(self ; sqxtun V(dst).H4, V(src).S4),
+            (NeonWidthOp::Sqxtun, Lanes::S4) => dynasm!(self ; sqxtun V(dst).S2, V(src).D2),
+            (NeonWidthOp::Sqxtun2, Lanes::B16) => dynasm!(self ; sqxtun2 V(dst).B16, V(src).H8),
+            (NeonWidthOp::Sqxtun2, Lanes::H8) => dynasm!(self ; sqxtun2 V(dst).H8, V(src).S4),
+            (NeonWidthOp::Sqxtun2, Lanes::S4) => dynasm!(self ; sqxtun2 V(dst).S4, V(src).D2),
+            (NeonWidthOp::Uqxtn, Lanes::B16) => dynasm!(self ; uqxtn V(dst).B8, V(src).H8),
+            (NeonWidthOp::Uqxtn, Lanes::H8) => dynasm!(self ; uqxtn V(dst).H4, V(src).S4),
+            (NeonWidthOp::Uqxtn, Lanes::S4) => dynasm!(self ; uqxtn V(dst).S2, V(src).D2),
+            (NeonWidthOp::Fcvtl, Lanes::S4) => dynasm!(self ; fcvtl V(dst).D2, V(src).S2),
+            (NeonWidthOp::Fcvtn, Lanes::S4) => dynasm!(self ; fcvtn V(dst).S2, V(src).D2),
+            _ => codegen_error!("singlepass can't emit {:?} {:?}", op, lanes),
+        }
+        Ok(())
+    }
+    fn emit_neon_long(
+        &mut self,
+        op: NeonLongOp,
+        lanes: Lanes,
+        src1: NEON,
+        src2: NEON,
+        dst: NEON,
+    ) -> Result<(), CompileError> {
+        let (src1, src2, dst) = (src1 as u32, src2 as u32, dst as u32);
+        match (op, lanes) {
+            (NeonLongOp::Smull, Lanes::B16) => {
+                dynasm!(self ; smull V(dst).H8, V(src1).B8, V(src2).B8)
+            }
+            (NeonLongOp::Smull, Lanes::H8) => {
+                dynasm!(self ; smull V(dst).S4, V(src1).H4, V(src2).H4)
+            }
+            (NeonLongOp::Smull, Lanes::S4) => {
+                dynasm!(self ; smull V(dst).D2, V(src1).S2, V(src2).S2)
+            }
+            (NeonLongOp::Smull2, Lanes::B16) => {
+                dynasm!(self ; smull2 V(dst).H8, V(src1).B16, V(src2).B16)
+            }
+            (NeonLongOp::Smull2, Lanes::H8) => {
+                dynasm!(self ; smull2 V(dst).S4, V(src1).H8, V(src2).H8)
+            }
+            (NeonLongOp::Smull2, Lanes::S4) => {
+                dynasm!(self ; smull2 V(dst).D2, V(src1).S4, V(src2).S4)
+            }
+            (NeonLongOp::Umull, Lanes::B16) => {
+                dynasm!(self ; umull V(dst).H8, V(src1).B8, V(src2).B8)
+            }
+            (NeonLongOp::Umull, Lanes::H8) => {
+                dynasm!(self ; umull V(dst).S4, V(src1).H4, V(src2).H4)
+            }
+            (NeonLongOp::Umull, Lanes::S4) => {
+                dynasm!(self ; umull V(dst).D2, V(src1).S2, V(src2).S2)
+            }
+            (NeonLongOp::Umull2, Lanes::B16) => {
+                dynasm!(self ; umull2 V(dst).H8, V(src1).B16, V(src2).B16)
+            }
+            (NeonLongOp::Umull2, Lanes::H8) => {
+                dynasm!(self ; umull2 V(dst).S4, V(src1).H8, V(src2).H8)
+            }
+            (NeonLongOp::Umull2, Lanes::S4) => {
+                dynasm!(self ; umull2 V(dst).D2, V(src1).S4, V(src2).S4)
+            }
+            _ => codegen_error!("singlepass can't emit {:?} {:?}", op, lanes),
+        }
+        Ok(())
+    }
+    fn emit_neon_addv(&mut self, lanes: Lanes, src: NEON, dst: NEON) -> Result<(), CompileError> {
+        let (src, dst) = (src as u32, dst as u32);
+        match lanes {
+            Lanes::B16 => dynasm!(self ; addv B(dst), V(src).B16),
+            Lanes::H8 => dynasm!(self ; addv H(dst), V(src).H8),
+            Lanes::S4 => dynasm!(self ; addv S(dst), V(src).S4),
+            Lanes::D2 => dynasm!(self ; addp D(dst), V(src).D2),
+        }
+        Ok(())
+    }
+    fn emit_neon_umaxv(&mut self, src: NEON, dst: NEON) -> Result<(), CompileError> {
+        dynasm!(self ; umaxv S(dst as u32), V(src as u32).S4);
+        Ok(())
+    }
+    fn emit_neon_dup(&mut self, lanes: Lanes, src: GPR, dst: NEON) -> Result<(), CompileError> {
+        let (src, dst) = (src.into_index() as u32, dst as u32);
+        match lanes {
+            Lanes::B16 => dynasm!(self ; dup V(dst).B16, W(src)),
+            Lanes::H8 => dynasm!(self ; dup V(dst).H8, W(src)),
+            Lanes::S4 => dynasm!(self ; dup V(dst).S4, W(src)),
+            Lanes::D2 => dynasm!(self ; dup V(dst).D2, X(src)),
+        }
+        Ok(())
+    }
+    fn emit_neon_ins(
+        &mut self,
+        lanes: Lanes,
+        src: GPR,
+        lane: u32,
+        dst: NEON,
+    ) -> Result<(), CompileError> {
+        let (src, dst) = (src.into_index() as u32, dst as u32);
+        match lanes {
+            Lanes::B16 => dynasm!(self ; ins V(dst).B[lane], W(src)),
+            Lanes::H8 => dynasm!(self ; ins V(dst).H[lane], W(src)),
+            Lanes::S4 => dynasm!(self ; ins V(dst).S[lane], W(src)),
+            Lanes::D2 => dynasm!(self ; ins V(dst).D[lane], X(src)),
+        }
+        Ok(())
+    }
+    fn emit_neon_umov(
+        &mut self,
+        lanes: Lanes,
+        signed: bool,
+        src: NEON,
+        lane: u32,
+        dst: GPR,
+    ) -> Result<(), CompileError> {
+        let (src, dst) = (src as u32, dst.into_index() as u32);
+        match (lanes, signed) {
+            (Lanes::B16, false) => dynasm!(self ; umov W(dst), V(src).B[lane]),
+            (Lanes::B16, true) => dynasm!(self ; smov W(dst), V(src).B[lane]),
+            (Lanes::H8, false) => dynasm!(self ; umov W(dst), V(src).H[lane]),
+            (Lanes::H8, true) => dynasm!(self ; smov W(dst), V(src).H[lane]),
+            (Lanes::S4, false) => dynasm!(self ; umov W(dst), V(src).S[lane]),
+            (Lanes::D2, false) => dynasm!(self ; umov X(dst), V(src).D[lane]),
+            _ => codegen_error!("singlepass can't emit SMOV {:?}", lanes),
+        }
+        Ok(())
+    }
+    fn emit_neon_ext(
+        &mut self,
+        src1: NEON,
+        src2: NEON,
+        imm: u32,
+        dst: NEON,
+    ) -> Result<(), CompileError> {
+        let (src1, src2, dst) = (src1 as u32, src2 as u32, dst as u32);
+        dynasm!(self ; ext V(dst).B16, V(src1).B16, V(src2).B16, imm);
+        Ok(())
+    }
+    fn emit_neon_tbl(
+        &mut self,
+        table: NEON,
+        two_registers: bool,
+        idx: NEON,
+        dst: NEON,
+    ) -> Result<(), CompileError> {
+        let (table, idx, dst) = (table as u32, idx as u32, dst as u32);
+        if two_registers {
+            dynasm!(self ; tbl V(dst).B16, {V(table).B16 * 2}, V(idx).B16);
+        } else {
+            dynasm!(self ; tbl V(dst).B16, {V(table).B16 * 1}, V(idx).B16);
+        }
+        Ok(())
+    }
+    fn emit_neon_ldr_q(&mut self, dst: NEON, addr: GPR) -> Result<(), CompileError> {
+        dynasm!(self ; ldr Q(dst as u32), [X(addr.into_index() as u32)]);
+        Ok(())
+    }
+    fn emit_neon_str_q(&mut self, src: NEON, addr: GPR) -> Result<(), CompileError> {
+        dynasm!(self ; str Q(src as u32), [X(addr.into_index() as u32)]);
+        Ok(())
+    }
+    fn emit_neon_ld1r(&mut self, lanes: Lanes, dst: NEON, addr: GPR) -> Result<(), CompileError> {
+        let (dst, addr) = (dst as u32, addr.into_index() as u32);
+        match lanes {
+            Lanes::B16 => dynasm!(self ; ld1r {V(dst).B16 * 1}, [X(addr)]),
+            Lanes::H8 => dynasm!(self ; ld1r {V(dst).H8 * 1}, [X(addr)]),
+            Lanes::S4 => dynasm!(self ; ld1r {V(dst).S4 * 1}, [X(addr)]),
+            Lanes::D2 => dynasm!(self ; ld1r {V(dst).D2 * 1}, [X(addr)]),
+        }
+        Ok(())
+    }
+    fn emit_neon_ld1_lane(
+        &mut self,
+        lanes: Lanes,
+        dst: NEON,
+        lane: u32,
+        addr: GPR,
+    ) -> Result<(), CompileError> {
+        let (dst, addr) = (dst as u32, addr.into_index() as u32);
+        match lanes {
+            Lanes::B16 => dynasm!(self ; ld1 {V(dst).B * 1}[lane], [X(addr)]),
+            Lanes::H8 => dynasm!(self ; ld1 {V(dst).H * 1}[lane], [X(addr)]),
+            Lanes::S4 => dynasm!(self ; ld1 {V(dst).S * 1}[lane], [X(addr)]),
+            Lanes::D2 => dynasm!(self ; ld1 {V(dst).D * 1}[lane], [X(addr)]),
+        }
+        Ok(())
+    }
+    fn emit_neon_st1_lane(
+        &mut self,
+        lanes: Lanes,
+        src: NEON,
+        lane: u32,
+        addr: GPR,
+    ) -> Result<(), CompileError> {
+        let (src, addr) = (src as u32, addr.into_index() as u32);
+        match lanes {
+            Lanes::B16 => dynasm!(self ; st1 {V(src).B * 1}[lane], [X(addr)]),
+            Lanes::H8 => dynasm!(self ; st1 {V(src).H * 1}[lane], [X(addr)]),
+            Lanes::S4 => dynasm!(self ; st1 {V(src).S * 1}[lane], [X(addr)]),
+            Lanes::D2 => dynasm!(self ; st1 {V(src).D * 1}[lane], [X(addr)]),
+        }
+        Ok(())
+    }
 
     fn emit_fmov(
         &mut self,
@@ -3332,7 +3948,28 @@ pub fn gen_std_trampoline_arm64(
         ; mov X(args as u32), x2
     );
 
-    let stack_args = sig.params().len().saturating_sub(7); //1st arg is ctx, not an actual arg
+    // The offset in `args_rets` of each argument, a v128 being passed as
+    // two 64-bit halves.
+    let arg_offsets: Vec<usize> = sig
+        .params()
+        .iter()
+        .enumerate()
+        .flat_map(|(i, param)| match param {
+            Type::V128 => vec![i * 16, i * 16 + 8],
+            _ => vec![i * 16],
+        })
+        .collect();
+    let arg_sizes: Vec<Size> = sig
+        .params()
+        .iter()
+        .flat_map(|param| match param {
+            Type::V128 => vec![Size::S64, Size::S64],
+            Type::I32 | Type::F32 => vec![Size::S32],
+            _ => vec![Size::S64],
+        })
+        .collect();
+
+    let stack_args = arg_offsets.len().saturating_sub(7); //1st arg is ctx, not an actual arg
     let mut stack_offset = stack_args as u32 * 8;
     if stack_args > 0 {
         if stack_offset % 16 != 0 {
@@ -3345,23 +3982,13 @@ pub fn gen_std_trampoline_arm64(
     // Move arguments to their locations.
     // `callee_vmctx` is already in the first argument register, so no need to move.
     let mut caller_stack_offset: i32 = 0;
-    for (i, param) in sig.params().iter().enumerate() {
-        let sz = match *param {
-            Type::I32 | Type::F32 => Size::S32,
-            Type::I64 | Type::F64 => Size::S64,
-            Type::ExternRef => Size::S64,
-            Type::FuncRef => Size::S64,
-            _ => codegen_error!(
-                "singlepass unsupported param type for trampoline {:?}",
-                *param
-            ),
-        };
+    for (i, (arg_offset, sz)) in arg_offsets.iter().zip(arg_sizes).enumerate() {
         match i {
             0..=6 => {
                 a.emit_ldr(
                     sz,
                     Location::GPR(GPR::from_index(i + 1).unwrap()),
-                    Location::Memory(args, (i * 16) as i32),
+                    Location::Memory(args, *arg_offset as i32),
                 )?;
             }
             _ => {
@@ -3386,7 +4013,7 @@ pub fn gen_std_trampoline_arm64(
                 a.emit_ldr(
                     sz,
                     Location::GPR(GPR::X16),
-                    Location::Memory(args, (i * 16) as i32),
+                    Location::Memory(args, *arg_offset as i32),
                 )?;
                 a.emit_str(
                     sz,
@@ -3416,6 +4043,9 @@ pub fn gen_std_trampoline_arm64(
     // Write return value.
     if !sig.results().is_empty() {
         a.emit_str(Size::S64, Location::GPR(GPR::X0), Location::Memory(args, 0))?;
+        if sig.results()[0] == Type::V128 {
+            a.emit_str(Size::S64, Location::GPR(GPR::X1), Location::Memory(args, 8))?;
+        }
     }
 
     // Restore stack.
@@ -3478,49 +4108,61 @@ pub fn gen_std_dynamic_import_trampoline_arm64(
         let mut stack_param_count: usize = 0;
 
         for (i, ty) in sig.params().iter().enumerate() {
-            let source_loc = match argalloc.next(*ty, calling_convention) {
-                Some(ARM64Register::GPR(gpr)) => Location::GPR(gpr),
-                Some(ARM64Register::NEON(neon)) => Location::SIMD(neon),
-                None => {
-                    let sz = match calling_convention {
-                        CallingConvention::AppleAarch64 => match *ty {
-                            Type::I32 | Type::F32 => Size::S32,
-                            _ => {
-                                if stack_param_count & 7 != 0 {
-                                    stack_param_count = (stack_param_count + 7) & !7;
-                                };
-                                Size::S64
-                            }
-                        },
-                        _ => Size::S64,
-                    };
-                    a.emit_ldr(
-                        sz,
-                        Location::GPR(GPR::X26),
-                        Location::Memory(GPR::XzrSp, (stack_offset + 16 + stack_param_count) as _),
-                    )?;
-                    stack_param_count += match sz {
-                        Size::S32 => 4,
-                        Size::S64 => 8,
-                        _ => codegen_error!(
-                            "singlepass unreachable in gen_std_dynamic_import_trampoline_arm64"
-                        ),
-                    };
-                    Location::GPR(GPR::X26)
-                }
+            // A v128 comes as two 64-bit halves.
+            let (arg_ty, halves) = match ty {
+                Type::V128 => (Type::I64, 2),
+                _ => (*ty, 1),
             };
-            a.emit_str(
-                Size::S64,
-                source_loc,
-                Location::Memory(GPR::XzrSp, (i * 16) as _),
-            )?;
+            for half in 0..halves {
+                let source_loc = match argalloc.next(arg_ty, calling_convention) {
+                    Some(ARM64Register::GPR(gpr)) => Location::GPR(gpr),
+                    Some(ARM64Register::NEON(neon)) => Location::SIMD(neon),
+                    None => {
+                        let sz = match calling_convention {
+                            CallingConvention::AppleAarch64 => match arg_ty {
+                                Type::I32 | Type::F32 => Size::S32,
+                                _ => {
+                                    if stack_param_count & 7 != 0 {
+                                        stack_param_count = (stack_param_count + 7) & !7;
+                                    };
+                                    Size::S64
+                                }
+                            },
+                            _ => Size::S64,
+                        };
+                        a.emit_ldr(
+                            sz,
+                            Location::GPR(GPR::X26),
+                            Location::Memory(
+                                GPR::XzrSp,
+                                (stack_offset + 16 + stack_param_count) as _,
+                            ),
+                        )?;
+                        stack_param_count += match sz {
+                            Size::S32 => 4,
+                            Size::S64 => 8,
+                            _ => codegen_error!(
+                                "singlepass unreachable in gen_std_dynamic_import_trampoline_arm64"
+                            ),
+                        };
+                        Location::GPR(GPR::X26)
+                    }
+                };
+                a.emit_str(
+                    Size::S64,
+                    source_loc,
+                    Location::Memory(GPR::XzrSp, (i * 16 + half * 8) as _),
+                )?;
+            }
 
-            // Zero upper 64 bits.
-            a.emit_str(
-                Size::S64,
-                Location::GPR(GPR::XzrSp),                       // XZR here
-                Location::Memory(GPR::XzrSp, (i * 16 + 8) as _), // XSP here
-            )?;
+            if halves == 1 {
+                // Zero upper 64 bits.
+                a.emit_str(
+                    Size::S64,
+                    Location::GPR(GPR::XzrSp), // XZR here
+                    Location::Memory(GPR::XzrSp, (i * 16 + 8) as _), // XSP here
+                )?;
+            }
         }
     }
 
@@ -3551,6 +4193,13 @@ pub fn gen_std_dynamic_import_trampoline_arm64(
             Location::GPR(GPR::X0),
             Location::Memory(GPR::XzrSp, 0),
         )?;
+        if sig.results()[0] == Type::V128 {
+            a.emit_ldr(
+                Size::S64,
+                Location::GPR(GPR::X1),
+                Location::Memory(GPR::XzrSp, 8),
+            )?;
+        }
     }
 
     // Release values array.
@@ -3603,19 +4252,24 @@ pub fn gen_import_call_trampoline_arm64(
     // For the standard System V calling convention requires
     //  floating point arguments to be passed in NEON registers.
     //  Translation is expensive, so only do it if needed.
-    if sig
+    // A v128 is passed as two 64-bit halves.
+    let params: Vec<Type> = sig
         .params()
         .iter()
-        .any(|&x| x == Type::F32 || x == Type::F64)
-    {
+        .flat_map(|&ty| match ty {
+            Type::V128 => vec![Type::I64, Type::I64],
+            _ => vec![ty],
+        })
+        .collect();
+    if params.iter().any(|&x| x == Type::F32 || x == Type::F64) {
         #[allow(clippy::match_single_binding)]
         match calling_convention {
             _ => {
                 // Allocate stack space for arguments.
-                let stack_offset: i32 = if sig.params().len() > 7 {
+                let stack_offset: i32 = if params.len() > 7 {
                     7 * 8
                 } else {
-                    (sig.params().len() as i32) * 8
+                    (params.len() as i32) * 8
                 };
                 let stack_offset = if stack_offset & 15 != 0 {
                     stack_offset + 8
@@ -3654,7 +4308,7 @@ pub fn gen_import_call_trampoline_arm64(
                 let mut param_locations = vec![];
                 /* Clippy is wrong about using `i` to index `PARAM_REGS` here. */
                 #[allow(clippy::needless_range_loop)]
-                for i in 0..sig.params().len() {
+                for i in 0..params.len() {
                     let loc = match i {
                         0..=6 => {
                             let loc = Location::Memory(GPR::XzrSp, (i * 8) as i32);
@@ -3670,7 +4324,7 @@ pub fn gen_import_call_trampoline_arm64(
                 let mut caller_stack_offset: i32 = 0;
                 let mut argalloc = ArgumentRegisterAllocator::default();
                 argalloc.next(Type::I64, calling_convention).unwrap(); // skip VMContext
-                for (i, ty) in sig.params().iter().enumerate() {
+                for (i, ty) in params.iter().enumerate() {
                     let prev_loc = param_locations[i];
                     let targ = match argalloc.next(*ty, calling_convention) {
                         Some(ARM64Register::GPR(gpr)) => Location::GPR(gpr),
@@ -0,0 +1,1407 @@
+use crate::codegen_error;
+pub use crate::common_decl::Size;
+use crate::location::Location as AbstractLocation;
+pub use crate::location::Reg;
+pub use crate::machine::{Label, Offset};
+pub use crate::riscv_decl::{ArgumentRegisterAllocator, RiscvRegister, FPR, GPR};
+use dynasmrt::relocations::{ImpossibleRelocation, Relocation, RelocationKind, RelocationSize};
+use dynasmrt::{AssemblyOffset, DynamicLabel, DynasmApi, DynasmLabelApi, VecAssembler};
+use wasmer_types::{
+    CallingConvention, CompileError, CustomSection, CustomSectionProtection, FunctionBody,
+    FunctionIndex, FunctionType, SectionBody, Type, VMOffsets,
+};
+
+/// `dynasm` has no RISC-V backend, so instructions are encoded by hand and
+/// label references are patched through this relocation type.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+pub enum RiscvRelocation {
+    /// The 13-bit offset of a conditional branch.
+    Branch,
+    /// The 21-bit offset of a `jal`.
+    Jal,
+    /// A 32-bit offset split between an `auipc` and the I-type instruction following it.
+    AuipcI,
+}
+
+impl RiscvRelocation {
+    fn write_word(buf: &mut [u8], index: usize, word: u32) {
+        buf[index * 4..index * 4 + 4].copy_from_slice(&word.to_le_bytes());
+    }
+    fn read_word(buf: &[u8], index: usize) -> u32 {
+        let mut word = [0; 4];
+        word.copy_from_slice(&buf[index * 4..index * 4 + 4]);
+        u32::from_le_bytes(word)
+    }
+}
+
+impl Relocation for RiscvRelocation {
+    type Encoding = u8;
+    fn from_encoding(encoding: Self::Encoding) -> Self {
+        match encoding {
+            0 => Self::Branch,
+            1 => Self::Jal,
+            2 => Self::AuipcI,
+            x => panic!("Unsupported RISC-V relocation {}", x),
+        }
+    }
+    fn from_size(_size: RelocationSize) -> Self {
+        panic!("RISC-V relocations are not size based")
+    }
+    fn size(&self) -> usize {
+        match self {
+            Self::Branch | Self::Jal => 4,
+            Self::AuipcI => 8,
+        }
+    }
+    fn write_value(&self, buf: &mut [u8], value: isize) -> Result<(), ImpossibleRelocation> {
+        let value = value as i64;
+        match self {
+            Self::Branch => {
+                if value & 1 != 0 || !(-(1 << 12)..(1 << 12)).contains(&value) {
+                    return Err(ImpossibleRelocation {});
+                }
+                let word = Self::read_word(buf, 0) & !0xfe00_0f80;
+                Self::write_word(buf, 0, word | encode_b_imm(value as i32));
+            }
+            Self::Jal => {
+                if value & 1 != 0 || !(-(1 << 20)..(1 << 20)).contains(&value) {
+                    return Err(ImpossibleRelocation {});
+                }
+                let word = Self::read_word(buf, 0) & 0xfff;
+                Self::write_word(buf, 0, word | encode_j_imm(value as i32));
+            }
+            Self::AuipcI => {
+                let hi = (value + 0x800) >> 12;
+                if !(-(1 << 19)..(1 << 19)).contains(&hi) {
+                    return Err(ImpossibleRelocation {});
+                }
+                let lo = value - (hi << 12);
+                let auipc = Self::read_word(buf, 0) & 0xfff;
+                Self::write_word(buf, 0, auipc | ((hi as u32) << 12));
+                let itype = Self::read_word(buf, 1) & 0x000f_ffff;
+                Self::write_word(buf, 1, itype | ((lo as u32) << 20));
+            }
+        }
+        Ok(())
+    }
+    fn read_value(&self, buf: &[u8]) -> isize {
+        let word = Self::read_word(buf, 0);
+        (match self {
+            Self::Branch => {
+                let imm = ((word >> 31) & 1) << 12
+                    | ((word >> 25) & 0x3f) << 5
+                    | ((word >> 8) & 0xf) << 1
+                    | ((word >> 7) & 1) << 11;
+                ((imm << 19) as i32) >> 19
+            }
+            Self::Jal => {
+                let imm = ((word >> 31) & 1) << 20
+                    | ((word >> 21) & 0x3ff) << 1
+                    | ((word >> 20) & 1) << 11
+                    | ((word >> 12) & 0xff) << 12;
+                ((imm << 11) as i32) >> 11
+            }
+            Self::AuipcI => {
+                let lo = (Self::read_word(buf, 1) as i32) >> 20;
+                ((word & 0xffff_f000) as i32).wrapping_add(lo)
+            }
+        }) as isize
+    }
+    fn kind(&self) -> RelocationKind {
+        RelocationKind::Relative
+    }
+    fn page_size() -> usize {
+        4096
+    }
+}
+
+fn encode_b_imm(imm: i32) -> u32 {
+    let imm = imm as u32;
+    ((imm >> 12) & 1) << 31
+        | ((imm >> 5) & 0x3f) << 25
+        | ((imm >> 1) & 0xf) << 8
+        | ((imm >> 11) & 1) << 7
+}
+
+fn encode_j_imm(imm: i32) -> u32 {
+    let imm = imm as u32;
+    ((imm >> 20) & 1) << 31
+        | ((imm >> 1) & 0x3ff) << 21
+        | ((imm >> 11) & 1) << 20
+        | ((imm >> 12) & 0xff) << 12
+}
+
+type Assembler = VecAssembler<RiscvRelocation>;
+
+pub type Location = AbstractLocation<GPR, FPR>;
+
+/// Scratch register used by the far jumps and the trampolines.
+pub const SCRATCH: GPR = GPR::X31;
+
+/// Comparisons of the conditional branches.
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+#[allow(dead_code)]
+pub enum Condition {
+    Eq = 0,
+    Ne = 1,
+    Lt = 4,
+    Ge = 5,
+    Ltu = 6,
+    Geu = 7,
+}
+
+impl Condition {
+    pub fn invert(self) -> Self {
+        match self {
+            Condition::Eq => Condition::Ne,
+            Condition::Ne => Condition::Eq,
+            Condition::Lt => Condition::Ge,
+            Condition::Ge => Condition::Lt,
+            Condition::Ltu => Condition::Geu,
+            Condition::Geu => Condition::Ltu,
+        }
+    }
+}
+
+/// Register-register integer operations, the `W` form being used for 32-bit
+/// operands when one exists.
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+#[allow(dead_code)]
+pub enum AluOp {
+    Add,
+    Sub,
+    Sll,
+    Slt,
+    Sltu,
+    Xor,
+    Srl,
+    Sra,
+    Or,
+    And,
+    Mul,
+    Mulh,
+    Mulhu,
+    Div,
+    Divu,
+    Rem,
+    Remu,
+}
+
+/// Register-immediate integer operations.
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+#[allow(dead_code)]
+pub enum AluImmOp {
+    Addi,
+    Slti,
+    Sltiu,
+    Xori,
+    Ori,
+    Andi,
+    Slli,
+    Srli,
+    Srai,
+}
+
+/// Floating-point operations with two operands.
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+#[allow(dead_code)]
+pub enum FpOp {
+    Add,
+    Sub,
+    Mul,
+    Div,
+    Min,
+    Max,
+    Sgnj,
+    Sgnjn,
+    Sgnjx,
+}
+
+/// Floating-point comparisons, writing 0 or 1 to a GPR.
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+pub enum FpCmp {
+    Eq,
+    Lt,
+    Le,
+}
+
+/// Atomic memory operations.
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+pub enum AmoOp {
+    Add,
+    Swap,
+    Xor,
+    Or,
+    And,
+}
+
+/// Static rounding modes of the floating-point instructions.
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+pub enum RoundingMode {
+    /// Round to nearest, ties to even.
+    Rne = 0,
+    /// Round towards zero.
+    Rtz = 1,
+    /// Round down.
+    Rdn = 2,
+    /// Round up.
+    Rup = 3,
+}
+
+const OPC_LOAD: u32 = 0x03;
+const OPC_LOAD_FP: u32 = 0x07;
+const OPC_MISC_MEM: u32 = 0x0f;
+const OPC_OP_IMM: u32 = 0x13;
+const OPC_AUIPC: u32 = 0x17;
+const OPC_OP_IMM_32: u32 = 0x1b;
+const OPC_STORE: u32 = 0x23;
+const OPC_STORE_FP: u32 = 0x27;
+const OPC_AMO: u32 = 0x2f;
+const OPC_OP: u32 = 0x33;
+const OPC_LUI: u32 = 0x37;
+const OPC_OP_32: u32 = 0x3b;
+const OPC_OP_FP: u32 = 0x53;
+const OPC_BRANCH: u32 = 0x63;
+const OPC_JALR: u32 = 0x67;
+const OPC_JAL: u32 = 0x6f;
+const OPC_SYSTEM: u32 = 0x73;
+
+/// The `fflags` CSR.
+const CSR_FFLAGS: u32 = 0x001;
+
+fn r_type(opcode: u32, rd: u32, funct3: u32, rs1: u32, rs2: u32, funct7: u32) -> u32 {
+    funct7 << 25 | rs2 << 20 | rs1 << 15 | funct3 << 12 | rd << 7 | opcode
+}
+
+fn i_type(opcode: u32, rd: u32, funct3: u32, rs1: u32, imm: i32) -> u32 {
+    ((imm as u32) & 0xfff) << 20 | rs1 << 15 | funct3 << 12 | rd << 7 | opcode
+}
+
+fn s_type(opcode: u32, funct3: u32, rs1: u32, rs2: u32, imm: i32) -> u32 {
+    let imm = imm as u32;
+    ((imm >> 5) & 0x7f) << 25 | rs2 << 20 | rs1 << 15 | funct3 << 12 | (imm & 0x1f) << 7 | opcode
+}
+
+fn fmt(sz: Size) -> Result<u32, CompileError> {
+    match sz {
+        Size::S32 => Ok(0),
+        Size::S64 => Ok(1),
+        _ => codegen_error!("singlepass can't use a {:?} floating-point format", sz),
+    }
+}
+
+/// Whether `imm` fits a 12-bit signed immediate.
+pub fn imm12(imm: i64) -> bool {
+    (-2048..2048).contains(&imm)
+}
+
+pub trait EmitterRiscv {
+    fn get_label(&mut self) -> Label;
+    fn get_offset(&self) -> Offset;
+    fn get_jmp_instr_size(&self) -> u8;
+
+    fn finalize_function(&mut self);
+
+    fn emit_u32(&mut self, word: u32);
+
+    fn emit_load(
+        &mut self,
+        sz: Size,
+        signed: bool,
+        reg: GPR,
+        addr: GPR,
+        offset: i32,
+    ) -> Result<(), CompileError>;
+    fn emit_store(
+        &mut self,
+        sz: Size,
+        reg: GPR,
+        addr: GPR,
+        offset: i32,
+    ) -> Result<(), CompileError>;
+    fn emit_fload(
+        &mut self,
+        sz: Size,
+        reg: FPR,
+        addr: GPR,
+        offset: i32,
+    ) -> Result<(), CompileError>;
+    fn emit_fstore(
+        &mut self,
+        sz: Size,
+        reg: FPR,
+        addr: GPR,
+        offset: i32,
+    ) -> Result<(), CompileError>;
+
+    fn emit_alu(
+        &mut self,
+        sz: Size,
+        op: AluOp,
+        dst: GPR,
+        src1: GPR,
+        src2: GPR,
+    ) -> Result<(), CompileError>;
+    fn emit_alu_imm(
+        &mut self,
+        sz: Size,
+        op: AluImmOp,
+        dst: GPR,
+        src: GPR,
+        imm: i32,
+    ) -> Result<(), CompileError>;
+    fn emit_lui(&mut self, dst: GPR, imm20: u32) -> Result<(), CompileError>;
+    fn emit_mov(&mut self, dst: GPR, src: GPR) -> Result<(), CompileError>;
+    fn emit_mov_imm(&mut self, dst: GPR, val: u64) -> Result<(), CompileError>;
+    /// Zero-extends the low 32 bits of `src` into `dst`.
+    fn emit_zext32(&mut self, dst: GPR, src: GPR) -> Result<(), CompileError>;
+    /// Sign-extends the low 32 bits of `src` into `dst`.
+    fn emit_sext32(&mut self, dst: GPR, src: GPR) -> Result<(), CompileError>;
+
+    fn emit_fop(
+        &mut self,
+        sz: Size,
+        op: FpOp,
+        dst: FPR,
+        src1: FPR,
+        src2: FPR,
+    ) -> Result<(), CompileError>;
+    fn emit_fsqrt(&mut self, sz: Size, dst: FPR, src: FPR) -> Result<(), CompileError>;
+    fn emit_fcmp(
+        &mut self,
+        sz: Size,
+        cmp: FpCmp,
+        dst: GPR,
+        src1: FPR,
+        src2: FPR,
+    ) -> Result<(), CompileError>;
+    fn emit_fcvt_to_int(
+        &mut self,
+        sz_in: Size,
+        sz_out: Size,
+        signed: bool,
+        dst: GPR,
+        src: FPR,
+        rm: RoundingMode,
+    ) -> Result<(), CompileError>;
+    fn emit_fcvt_from_int(
+        &mut self,
+        sz_in: Size,
+        signed: bool,
+        sz_out: Size,
+        dst: FPR,
+        src: GPR,
+    ) -> Result<(), CompileError>;
+    fn emit_fcvt_float(
+        &mut self,
+        sz_in: Size,
+        sz_out: Size,
+        dst: FPR,
+        src: FPR,
+    ) -> Result<(), CompileError>;
+    fn emit_fmv_to_gpr(&mut self, sz: Size, dst: GPR, src: FPR) -> Result<(), CompileError>;
+    fn emit_fmv_from_gpr(&mut self, sz: Size, dst: FPR, src: GPR) -> Result<(), CompileError>;
+    fn emit_read_fflags(&mut self, dst: GPR) -> Result<(), CompileError>;
+    fn emit_clear_fflags(&mut self) -> Result<(), CompileError>;
+
+    fn emit_amo(
+        &mut self,
+        sz: Size,
+        op: AmoOp,
+        dst: GPR,
+        addr: GPR,
+        src: GPR,
+    ) -> Result<(), CompileError>;
+    fn emit_lr(&mut self, sz: Size, dst: GPR, addr: GPR) -> Result<(), CompileError>;
+    fn emit_sc(&mut self, sz: Size, dst: GPR, addr: GPR, src: GPR) -> Result<(), CompileError>;
+    fn emit_fence(&mut self) -> Result<(), CompileError>;
+
+    fn emit_label(&mut self, label: Label) -> Result<(), CompileError>;
+    fn emit_load_label(&mut self, reg: GPR, label: Label) -> Result<(), CompileError>;
+    fn emit_j_label(&mut self, label: Label) -> Result<(), CompileError>;
+    fn emit_j_label_far(&mut self, label: Label) -> Result<(), CompileError>;
+    fn emit_bcond_label(
+        &mut self,
+        cond: Condition,
+        src1: GPR,
+        src2: GPR,
+        label: Label,
+    ) -> Result<(), CompileError>;
+    fn emit_bcond_label_far(
+        &mut self,
+        cond: Condition,
+        src1: GPR,
+        src2: GPR,
+        label: Label,
+    ) -> Result<(), CompileError>;
+    fn emit_call_label(&mut self, label: Label) -> Result<(), CompileError>;
+    fn emit_call_register(&mut self, reg: GPR) -> Result<(), CompileError>;
+    fn emit_j_register(&mut self, reg: GPR) -> Result<(), CompileError>;
+    fn emit_ret(&mut self) -> Result<(), CompileError>;
+
+    fn emit_udf(&mut self, payload: u8) -> Result<(), CompileError>;
+    fn emit_ebreak(&mut self) -> Result<(), CompileError>;
+
+    fn arch_supports_canonicalize_nan(&self) -> bool {
+        true
+    }
+
+    fn arch_requires_indirect_call_trampoline(&self) -> bool {
+        false
+    }
+
+    fn arch_emit_indirect_call_with_trampoline(
+        &mut self,
+        _loc: Location,
+    ) -> Result<(), CompileError> {
+        codegen_error!("singlepass arch_emit_indirect_call_with_trampoline unimplemented")
+    }
+}
+
+impl EmitterRiscv for Assembler {
+    fn get_label(&mut self) -> DynamicLabel {
+        self.new_dynamic_label()
+    }
+
+    fn get_offset(&self) -> AssemblyOffset {
+        self.offset()
+    }
+
+    fn get_jmp_instr_size(&self) -> u8 {
+        8 // auipc + jalr
+    }
+
+    fn finalize_function(&mut self) {}
+
+    fn emit_u32(&mut self, word: u32) {
+        self.push_u32(word);
+    }
+
+    fn emit_load(
+        &mut self,
+        sz: Size,
+        signed: bool,
+        reg: GPR,
+        addr: GPR,
+        offset: i32,
+    ) -> Result<(), CompileError> {
+        if !imm12(offset as i64) {
+            codegen_error!("singlepass can't emit load with offset {}", offset);
+        }
+        let funct3 = match (sz, signed) {
+            (Size::S8, true) => 0,
+            (Size::S16, true) => 1,
+            (Size::S32, true) => 2,
+            (Size::S64, _) => 3,
+            (Size::S8, false) => 4,
+            (Size::S16, false) => 5,
+            (Size::S32, false) => 6,
+        };
+        self.emit_u32(i_type(OPC_LOAD, reg as u32, funct3, addr as u32, offset));
+        Ok(())
+    }
+    fn emit_store(
+        &mut self,
+        sz: Size,
+        reg: GPR,
+        addr: GPR,
+        offset: i32,
+    ) -> Result<(), CompileError> {
+        if !imm12(offset as i64) {
+            codegen_error!("singlepass can't emit store with offset {}", offset);
+        }
+        let funct3 = match sz {
+            Size::S8 => 0,
+            Size::S16 => 1,
+            Size::S32 => 2,
+            Size::S64 => 3,
+        };
+        self.emit_u32(s_type(OPC_STORE, funct3, addr as u32, reg as u32, offset));
+        Ok(())
+    }
+    fn emit_fload(
+        &mut self,
+        sz: Size,
+        reg: FPR,
+        addr: GPR,
+        offset: i32,
+    ) -> Result<(), CompileError> {
+        if !imm12(offset as i64) {
+            codegen_error!("singlepass can't emit fload with offset {}", offset);
+        }
+        let funct3 = 2 + fmt(sz)?;
+        self.emit_u32(i_type(OPC_LOAD_FP, reg as u32, funct3, addr as u32, offset));
+        Ok(())
+    }
+    fn emit_fstore(
+        &mut self,
+        sz: Size,
+        reg: FPR,
+        addr: GPR,
+        offset: i32,
+    ) -> Result<(), CompileError> {
+        if !imm12(offset as i64) {
+            codegen_error!("singlepass can't emit fstore with offset {}", offset);
+        }
+        let funct3 = 2 + fmt(sz)?;
+        self.emit_u32(s_type(
+            OPC_STORE_FP,
+            funct3,
+            addr as u32,
+            reg as u32,
+            offset,
+        ));
+        Ok(())
+    }
+
+    fn emit_alu(
+        &mut self,
+        sz: Size,
+        op: AluOp,
+        dst: GPR,
+        src1: GPR,
+        src2: GPR,
+    ) -> Result<(), CompileError> {
+        let (funct3, funct7, has_w) = match op {
+            AluOp::Add => (0, 0x00, true),
+            AluOp::Sub => (0, 0x20, true),
+            AluOp::Sll => (1, 0x00, true),
+            AluOp::Slt => (2, 0x00, false),
+            AluOp::Sltu => (3, 0x00, false),
+            AluOp::Xor => (4, 0x00, false),
+            AluOp::Srl => (5, 0x00, true),
+            AluOp::Sra => (5, 0x20, true),
+            AluOp::Or => (6, 0x00, false),
+            AluOp::And => (7, 0x00, false),
+            AluOp::Mul => (0, 0x01, true),
+            AluOp::Mulh => (1, 0x01, false),
+            AluOp::Mulhu => (3, 0x01, false),
+            AluOp::Div => (4, 0x01, true),
+            AluOp::Divu => (5, 0x01, true),
+            AluOp::Rem => (6, 0x01, true),
+            AluOp::Remu => (7, 0x01, true),
+        };
+        let opcode = if sz == Size::S32 && has_w {
+            OPC_OP_32
+        } else {
+            OPC_OP
+        };
+        self.emit_u32(r_type(
+            opcode,
+            dst as u32,
+            funct3,
+            src1 as u32,
+            src2 as u32,
+            funct7,
+        ));
+        Ok(())
+    }
+    fn emit_alu_imm(
+        &mut self,
+        sz: Size,
+        op: AluImmOp,
+        dst: GPR,
+        src: GPR,
+        imm: i32,
+    ) -> Result<(), CompileError> {
+        let word = sz == Size::S32;
+        let (funct3, imm, has_w) = match op {
+            AluImmOp::Slli | AluImmOp::Srli | AluImmOp::Srai => {
+                let max = if word { 31 } else { 63 };
+                if !(0..=max).contains(&imm) {
+                    codegen_error!("singlepass can't emit shift by {}", imm);
+                }
+                match op {
+                    AluImmOp::Slli => (1, imm, true),
+                    AluImmOp::Srli => (5, imm, true),
+                    _ => (5, imm | 0x400, true),
+                }
+            }
+            _ => {
+                if !imm12(imm as i64) {
+                    codegen_error!("singlepass can't emit {:?} with immediate {}", op, imm);
+                }
+                match op {
+                    AluImmOp::Addi => (0, imm, true),
+                    AluImmOp::Slti => (2, imm, false),
+                    AluImmOp::Sltiu => (3, imm, false),
+                    AluImmOp::Xori => (4, imm, false),
+                    AluImmOp::Ori => (6, imm, false),
+                    _ => (7, imm, false),
+                }
+            }
+        };
+        let opcode = if word && has_w {
+            OPC_OP_IMM_32
+        } else {
+            OPC_OP_IMM
+        };
+        self.emit_u32(i_type(opcode, dst as u32, funct3, src as u32, imm));
+        Ok(())
+    }
+    fn emit_lui(&mut self, dst: GPR, imm20: u32) -> Result<(), CompileError> {
+        self.emit_u32((imm20 & 0xfffff) << 12 | (dst as u32) << 7 | OPC_LUI);
+        Ok(())
+    }
+    fn emit_mov(&mut self, dst: GPR, src: GPR) -> Result<(), CompileError> {
+        self.emit_alu_imm(Size::S64, AluImmOp::Addi, dst, src, 0)
+    }
+    fn emit_mov_imm(&mut self, dst: GPR, val: u64) -> Result<(), CompileError> {
+        let val = val as i64;
+        if imm12(val) {
+            self.emit_alu_imm(Size::S64, AluImmOp::Addi, dst, GPR::X0, val as i32)
+        } else if val == val as i32 as i64 {
+            let lo = (val << 52) >> 52;
+            let hi = (val.wrapping_sub(lo) >> 12) as u32;
+            self.emit_lui(dst, hi)?;
+            if lo != 0 {
+                self.emit_alu_imm(Size::S32, AluImmOp::Addi, dst, dst, lo as i32)?;
+            }
+            Ok(())
+        } else {
+            // Materialize the upper bits, then shift them in place and add the low 12 bits.
+            let lo = (val << 52) >> 52;
+            let mut hi = val.wrapping_sub(lo) >> 12;
+            let shift = hi.trailing_zeros();
+            hi >>= shift;
+            self.emit_mov_imm(dst, hi as u64)?;
+            self.emit_alu_imm(Size::S64, AluImmOp::Slli, dst, dst, 12 + shift as i32)?;
+            if lo != 0 {
+                self.emit_alu_imm(Size::S64, AluImmOp::Addi, dst, dst, lo as i32)?;
+            }
+            Ok(())
+        }
+    }
+    fn emit_zext32(&mut self, dst: GPR, src: GPR) -> Result<(), CompileError> {
+        self.emit_alu_imm(Size::S64, AluImmOp::Slli, dst, src, 32)?;
+        self.emit_alu_imm(Size::S64, AluImmOp::Srli, dst, dst, 32)
+    }
+    fn emit_sext32(&mut self, dst: GPR, src: GPR) -> Result<(), CompileError> {
+        self.emit_alu_imm(Size::S32, AluImmOp::Addi, dst, src, 0)
+    }
+
+    fn emit_fop(
+        &mut self,
+        sz: Size,
+        op: FpOp,
+        dst: FPR,
+        src1: FPR,
+        src2: FPR,
+    ) -> Result<(), CompileError> {
+        let rne = RoundingMode::Rne as u32;
+        let (funct5, funct3) = match op {
+            FpOp::Add => (0x00, rne),
+            FpOp::Sub => (0x01, rne),
+            FpOp::Mul => (0x02, rne),
+            FpOp::Div => (0x03, rne),
+            FpOp::Sgnj => (0x04, 0),
+            FpOp::Sgnjn => (0x04, 1),
+            FpOp::Sgnjx => (0x04, 2),
+            FpOp::Min => (0x05, 0),
+            FpOp::Max => (0x05, 1),
+        };
+        self.emit_u32(r_type(
+            OPC_OP_FP,
+            dst as u32,
+            funct3,
+            src1 as u32,
+            src2 as u32,
+            funct5 << 2 | fmt(sz)?,
+        ));
+        Ok(())
+    }
+    fn emit_fsqrt(&mut self, sz: Size, dst: FPR, src: FPR) -> Result<(), CompileError> {
+        self.emit_u32(r_type(
+            OPC_OP_FP,
+            dst as u32,
+            RoundingMode::Rne as u32,
+            src as u32,
+            0,
+            0x0b << 2 | fmt(sz)?,
+        ));
+        Ok(())
+    }
+    fn emit_fcmp(
+        &mut self,
+        sz: Size,
+        cmp: FpCmp,
+        dst: GPR,
+        src1: FPR,
+        src2: FPR,
+    ) -> Result<(), CompileError> {
+        let funct3 = match cmp {
+            FpCmp::Le => 0,
+            FpCmp::Lt => 1,
+            FpCmp::Eq => 2,
+        };
+        self.emit_u32(r_type(
+            OPC_OP_FP,
+            dst as u32,
+            funct3,
+            src1 as u32,
+            src2 as u32,
+            0x14 << 2 | fmt(sz)?,
+        ));
+        Ok(())
+    }
+    fn emit_fcvt_to_int(
+        &mut self,
+        sz_in: Size,
+        sz_out: Size,
+        signed: bool,
+        dst: GPR,
+        src: FPR,
+        rm: RoundingMode,
+    ) -> Result<(), CompileError> {
+        let rs2 = match (sz_out, signed) {
+            (Size::S32, true) => 0,
+            (Size::S32, false) => 1,
+            (Size::S64, true) => 2,
+            (Size::S64, false) => 3,
+            _ => codegen_error!("singlepass can't convert to a {:?} integer", sz_out),
+        };
+        self.emit_u32(r_type(
+            OPC_OP_FP,
+            dst as u32,
+            rm as u32,
+            src as u32,
+            rs2,
+            0x18 << 2 | fmt(sz_in)?,
+        ));
+        Ok(())
+    }
+    fn emit_fcvt_from_int(
+        &mut self,
+        sz_in: Size,
+        signed: bool,
+        sz_out: Size,
+        dst: FPR,
+        src: GPR,
+    ) -> Result<(), CompileError> {
+        let rs2 = match (sz_in, signed) {
+            (Size::S32, true) => 0,
+            (Size::S32, false) => 1,
+            (Size::S64, true) => 2,
+            (Size::S64, false) => 3,
+            _ => codegen_error!("singlepass can't convert from a {:?} integer", sz_in),
+        };
+        self.emit_u32(r_type(
+            OPC_OP_FP,
+            dst as u32,
+            RoundingMode::Rne as u32,
+            src as u32,
+            rs2,
+            0x1a << 2 | fmt(sz_out)?,
+        ));
+        Ok(())
+    }
+    fn emit_fcvt_float(
+        &mut self,
+        sz_in: Size,
+        sz_out: Size,
+        dst: FPR,
+        src: FPR,
+    ) -> Result<(), CompileError> {
+        self.emit_u32(r_type(
+            OPC_OP_FP,
+            dst as u32,
+            RoundingMode::Rne as u32,
+            src as u32,
+            fmt(sz_in)?,
+            0x08 << 2 | fmt(sz_out)?,
+        ));
+        Ok(())
+    }
+    fn emit_fmv_to_gpr(&mut self, sz: Size, dst: GPR, src: FPR) -> Result<(), CompileError> {
+        self.emit_u32(r_type(
+            OPC_OP_FP,
+            dst as u32,
+            0,
+            src as u32,
+            0,
+            0x1c << 2 | fmt(sz)?,
+        ));
+        Ok(())
+    }
+    fn emit_fmv_from_gpr(&mut self, sz: Size, dst: FPR, src: GPR) -> Result<(), CompileError> {
+        self.emit_u32(r_type(
+            OPC_OP_FP,
+            dst as u32,
+            0,
+            src as u32,
+            0,
+            0x1e << 2 | fmt(sz)?,
+        ));
+        Ok(())
+    }
+    fn emit_read_fflags(&mut self, dst: GPR) -> Result<(), CompileError> {
+        // csrrs dst, fflags, zero
+        self.emit_u32(i_type(OPC_SYSTEM, dst as u32, 2, 0, CSR_FFLAGS as i32));
+        Ok(())
+    }
+    fn emit_clear_fflags(&mut self) -> Result<(), CompileError> {
+        // csrrw zero, fflags, zero
+        self.emit_u32(i_type(OPC_SYSTEM, 0, 1, 0, CSR_FFLAGS as i32));
+        Ok(())
+    }
+
+    fn emit_amo(
+        &mut self,
+        sz: Size,
+        op: AmoOp,
+        dst: GPR,
+        addr: GPR,
+        src: GPR,
+    ) -> Result<(), CompileError> {
+        let funct5 = match op {
+            AmoOp::Add => 0x00,
+            AmoOp::Swap => 0x01,
+            AmoOp::Xor => 0x04,
+            AmoOp::Or => 0x08,
+            AmoOp::And => 0x0c,
+        };
+        let funct3 = match sz {
+            Size::S32 => 2,
+            Size::S64 => 3,
+            _ => codegen_error!("singlepass can't emit a {:?} amo", sz),
+        };
+        // Sequentially consistent: both aq and rl are set.
+        self.emit_u32(r_type(
+            OPC_AMO,
+            dst as u32,
+            funct3,
+            addr as u32,
+            src as u32,
+            funct5 << 2 | 0x3,
+        ));
+        Ok(())
+    }
+    fn emit_lr(&mut self, sz: Size, dst: GPR, addr: GPR) -> Result<(), CompileError> {
+        let funct3 = match sz {
+            Size::S32 => 2,
+            Size::S64 => 3,
+            _ => codegen_error!("singlepass can't emit a {:?} lr", sz),
+        };
+        self.emit_u32(r_type(
+            OPC_AMO,
+            dst as u32,
+            funct3,
+            addr as u32,
+            0,
+            0x02 << 2 | 0x3,
+        ));
+        Ok(())
+    }
+    fn emit_sc(&mut self, sz: Size, dst: GPR, addr: GPR, src: GPR) -> Result<(), CompileError> {
+        let funct3 = match sz {
+            Size::S32 => 2,
+            Size::S64 => 3,
+            _ => codegen_error!("singlepass can't emit a {:?} sc", sz),
+        };
+        self.emit_u32(r_type(
+            OPC_AMO,
+            dst as u32,
+            funct3,
+            addr as u32,
+            src as u32,
+            0x03 << 2 | 0x3,
+        ));
+        Ok(())
+    }
+    fn emit_fence(&mut self) -> Result<(), CompileError> {
+        // fence rw, rw
+        self.emit_u32(0x0330_000f | OPC_MISC_MEM);
+        Ok(())
+    }
+
+    fn emit_label(&mut self, label: Label) -> Result<(), CompileError> {
+        self.dynamic_label(label);
+        Ok(())
+    }
+    fn emit_load_label(&mut self, reg: GPR, label: Label) -> Result<(), CompileError> {
+        // auipc reg, 0; addi reg, reg, 0
+        self.emit_u32((reg as u32) << 7 | OPC_AUIPC);
+        self.emit_u32(i_type(OPC_OP_IMM, reg as u32, 0, reg as u32, 0));
+        self.dynamic_relocation(label, 0, 8, 8, RiscvRelocation::AuipcI);
+        Ok(())
+    }
+    fn emit_j_label(&mut self, label: Label) -> Result<(), CompileError> {
+        // jal zero, 0
+        self.emit_u32(OPC_JAL);
+        self.dynamic_relocation(label, 0, 4, 4, RiscvRelocation::Jal);
+        Ok(())
+    }
+    fn emit_j_label_far(&mut self, label: Label) -> Result<(), CompileError> {
+        // auipc scratch, 0; jalr zero, 0(scratch)
+        self.emit_u32((SCRATCH as u32) << 7 | OPC_AUIPC);
+        self.emit_u32(i_type(OPC_JALR, 0, 0, SCRATCH as u32, 0));
+        self.dynamic_relocation(label, 0, 8, 8, RiscvRelocation::AuipcI);
+        Ok(())
+    }
+    fn emit_bcond_label(
+        &mut self,
+        cond: Condition,
+        src1: GPR,
+        src2: GPR,
+        label: Label,
+    ) -> Result<(), CompileError> {
+        self.emit_u32(r_type(
+            OPC_BRANCH,
+            0,
+            cond as u32,
+            src1 as u32,
+            src2 as u32,
+            0,
+        ));
+        self.dynamic_relocation(label, 0, 4, 4, RiscvRelocation::Branch);
+        Ok(())
+    }
+    fn emit_bcond_label_far(
+        &mut self,
+        cond: Condition,
+        src1: GPR,
+        src2: GPR,
+        label: Label,
+    ) -> Result<(), CompileError> {
+        // Skip the far jump when the inverted condition holds.
+        self.emit_u32(
+            r_type(
+                OPC_BRANCH,
+                0,
+                cond.invert() as u32,
+                src1 as u32,
+                src2 as u32,
+                0,
+            ) | encode_b_imm(12),
+        );
+        self.emit_j_label_far(label)
+    }
+    fn emit_call_label(&mut self, label: Label) -> Result<(), CompileError> {
+        // auipc ra, 0; jalr ra, 0(ra)
+        self.emit_u32((GPR::X1 as u32) << 7 | OPC_AUIPC);
+        self.emit_u32(i_type(OPC_JALR, GPR::X1 as u32, 0, GPR::X1 as u32, 0));
+        self.dynamic_relocation(label, 0, 8, 8, RiscvRelocation::AuipcI);
+        Ok(())
+    }
+    fn emit_call_register(&mut self, reg: GPR) -> Result<(), CompileError> {
+        self.emit_u32(i_type(OPC_JALR, GPR::X1 as u32, 0, reg as u32, 0));
+        Ok(())
+    }
+    fn emit_j_register(&mut self, reg: GPR) -> Result<(), CompileError> {
+        self.emit_u32(i_type(OPC_JALR, 0, 0, reg as u32, 0));
+        Ok(())
+    }
+    fn emit_ret(&mut self) -> Result<(), CompileError> {
+        self.emit_j_register(GPR::X1)
+    }
+
+    fn emit_udf(&mut self, payload: u8) -> Result<(), CompileError> {
+        // An all-zero halfword is a defined illegal instruction, the trap
+        // handler reads the payload from the halfword following it.
+        self.emit_u32((payload as u32) << 16);
+        Ok(())
+    }
+    fn emit_ebreak(&mut self) -> Result<(), CompileError> {
+        self.emit_u32(0x0010_0073);
+        Ok(())
+    }
+}
+
+/// Loads `sz` bytes at `base + offset` into `dst`, going through [`SCRATCH`]
+/// when the offset doesn't fit an immediate.
+fn emit_load_from(
+    a: &mut Assembler,
+    sz: Size,
+    dst: Location,
+    base: GPR,
+    offset: i32,
+) -> Result<(), CompileError> {
+    let (base, offset) = if imm12(offset as i64) {
+        (base, offset)
+    } else {
+        a.emit_mov_imm(SCRATCH, offset as i64 as u64)?;
+        a.emit_alu(Size::S64, AluOp::Add, SCRATCH, base, SCRATCH)?;
+        (SCRATCH, 0)
+    };
+    match dst {
+        Location::GPR(dst) => a.emit_load(sz, true, dst, base, offset),
+        Location::SIMD(dst) => a.emit_fload(sz, dst, base, offset),
+        _ => codegen_error!("singlepass can't load into {:?}", dst),
+    }
+}
+
+/// Stores `sz` bytes of `src` at `base + offset`, using `tmp` to compute the
+/// address when the offset doesn't fit an immediate.
+fn emit_store_to(
+    a: &mut Assembler,
+    sz: Size,
+    src: Location,
+    base: GPR,
+    offset: i32,
+    tmp: GPR,
+) -> Result<(), CompileError> {
+    let (base, offset) = if imm12(offset as i64) {
+        (base, offset)
+    } else {
+        a.emit_mov_imm(tmp, offset as i64 as u64)?;
+        a.emit_alu(Size::S64, AluOp::Add, tmp, base, tmp)?;
+        (tmp, 0)
+    };
+    match src {
+        Location::GPR(src) => a.emit_store(sz, src, base, offset),
+        Location::SIMD(src) => a.emit_fstore(sz, src, base, offset),
+        _ => codegen_error!("singlepass can't store {:?}", src),
+    }
+}
+
+/// Adds `delta` to the stack pointer, using `tmp` for large adjustments.
+fn emit_adjust_sp(a: &mut Assembler, delta: i64, tmp: GPR) -> Result<(), CompileError> {
+    if delta == 0 {
+        Ok(())
+    } else if imm12(delta) {
+        a.emit_alu_imm(Size::S64, AluImmOp::Addi, GPR::X2, GPR::X2, delta as i32)
+    } else {
+        a.emit_mov_imm(tmp, delta as u64)?;
+        a.emit_alu(Size::S64, AluOp::Add, GPR::X2, GPR::X2, tmp)
+    }
+}
+
+// Generates a trampoline for calling a function of type `sig` from the host.
+pub fn gen_std_trampoline_riscv(
+    sig: &FunctionType,
+    _calling_convention: CallingConvention,
+) -> Result<FunctionBody, CompileError> {
+    let mut a = Assembler::new(0);
+
+    let fptr = GPR::X9;
+    let args = GPR::X18;
+
+    a.emit_alu_imm(Size::S64, AluImmOp::Addi, GPR::X2, GPR::X2, -32)?;
+    a.emit_store(Size::S64, GPR::X1, GPR::X2, 24)?;
+    a.emit_store(Size::S64, GPR::X8, GPR::X2, 16)?;
+    a.emit_store(Size::S64, fptr, GPR::X2, 8)?;
+    a.emit_store(Size::S64, args, GPR::X2, 0)?;
+    a.emit_mov(GPR::X8, GPR::X2)?;
+    a.emit_mov(fptr, GPR::X11)?;
+    a.emit_mov(args, GPR::X12)?;
+
+    // The offset in `args_rets` of each argument, a v128 being passed as
+    // two 64-bit halves.
+    let arg_offsets: Vec<usize> = sig
+        .params()
+        .iter()
+        .enumerate()
+        .flat_map(|(i, param)| match param {
+            Type::V128 => vec![i * 16, i * 16 + 8],
+            _ => vec![i * 16],
+        })
+        .collect();
+    let arg_sizes: Vec<Size> = sig
+        .params()
+        .iter()
+        .flat_map(|param| match param {
+            Type::V128 => vec![Size::S64, Size::S64],
+            Type::I32 | Type::F32 => vec![Size::S32],
+            _ => vec![Size::S64],
+        })
+        .collect();
+
+    let stack_args = arg_offsets.len().saturating_sub(7); //1st arg is ctx, not an actual arg
+    let mut stack_offset = stack_args as i64 * 8;
+    if stack_offset % 16 != 0 {
+        stack_offset += 8;
+    }
+    emit_adjust_sp(&mut a, -stack_offset, GPR::X30)?;
+
+    // Move arguments to their locations.
+    // `callee_vmctx` is already in the first argument register, so no need to move.
+    let mut caller_stack_offset: i32 = 0;
+    for (i, (arg_offset, sz)) in arg_offsets.iter().zip(arg_sizes).enumerate() {
+        match i {
+            0..=6 => {
+                emit_load_from(
+                    &mut a,
+                    sz,
+                    Location::GPR(GPR::from_index(i + 11).unwrap()),
+                    args,
+                    *arg_offset as i32,
+                )?;
+            }
+            _ => {
+                // using X31 as scratch reg
+                emit_load_from(
+                    &mut a,
+                    Size::S64,
+                    Location::GPR(SCRATCH),
+                    args,
+                    *arg_offset as i32,
+                )?;
+                emit_store_to(
+                    &mut a,
+                    Size::S64,
+                    Location::GPR(SCRATCH),
+                    GPR::X2,
+                    caller_stack_offset,
+                    GPR::X30,
+                )?;
+                caller_stack_offset += 8;
+            }
+        }
+    }
+
+    a.emit_call_register(fptr)?;
+
+    // Write return value.
+    if !sig.results().is_empty() {
+        a.emit_store(Size::S64, GPR::X10, args, 0)?;
+        if sig.results()[0] == Type::V128 {
+            a.emit_store(Size::S64, GPR::X11, args, 8)?;
+        }
+    }
+
+    // Restore stack.
+    a.emit_mov(GPR::X2, GPR::X8)?;
+    a.emit_load(Size::S64, false, args, GPR::X2, 0)?;
+    a.emit_load(Size::S64, false, fptr, GPR::X2, 8)?;
+    a.emit_load(Size::S64, false, GPR::X8, GPR::X2, 16)?;
+    a.emit_load(Size::S64, false, GPR::X1, GPR::X2, 24)?;
+    a.emit_alu_imm(Size::S64, AluImmOp::Addi, GPR::X2, GPR::X2, 32)?;
+    a.emit_ret()?;
+
+    let mut body = a.finalize().unwrap();
+    body.shrink_to_fit();
+    Ok(FunctionBody {
+        body,
+        unwind_info: None,
+    })
+}
+
+// Generates dynamic import function call trampoline for a function type.
+pub fn gen_std_dynamic_import_trampoline_riscv(
+    vmoffsets: &VMOffsets,
+    sig: &FunctionType,
+    calling_convention: CallingConvention,
+) -> Result<FunctionBody, CompileError> {
+    let mut a = Assembler::new(0);
+    // Allocate argument array.
+    let stack_offset: usize = 16 * std::cmp::max(sig.params().len(), sig.results().len());
+    // Save RA and X9, as scratch register
+    a.emit_alu_imm(Size::S64, AluImmOp::Addi, GPR::X2, GPR::X2, -16)?;
+    a.emit_store(Size::S64, GPR::X1, GPR::X2, 8)?;
+    a.emit_store(Size::S64, GPR::X9, GPR::X2, 0)?;
+    emit_adjust_sp(&mut a, -(stack_offset as i64), GPR::X9)?;
+
+    // Copy arguments.
+    if !sig.params().is_empty() {
+        let mut argalloc = ArgumentRegisterAllocator::default();
+        argalloc.next(Type::I64, calling_convention).unwrap(); // skip VMContext
+
+        let mut stack_param_count: usize = 0;
+
+        for (i, ty) in sig.params().iter().enumerate() {
+            // A v128 comes as two 64-bit halves.
+            let (arg_ty, halves) = match ty {
+                Type::V128 => (Type::I64, 2),
+                _ => (*ty, 1),
+            };
+            for half in 0..halves {
+                let source_loc = match argalloc.next(arg_ty, calling_convention) {
+                    Some(RiscvRegister::GPR(gpr)) => Location::GPR(gpr),
+                    Some(RiscvRegister::FPR(fpr)) => Location::SIMD(fpr),
+                    None => {
+                        emit_load_from(
+                            &mut a,
+                            Size::S64,
+                            Location::GPR(GPR::X9),
+                            GPR::X2,
+                            (stack_offset + 16 + stack_param_count) as _,
+                        )?;
+                        stack_param_count += 8;
+                        Location::GPR(GPR::X9)
+                    }
+                };
+                emit_store_to(
+                    &mut a,
+                    Size::S64,
+                    source_loc,
+                    GPR::X2,
+                    (i * 16 + half * 8) as _,
+                    SCRATCH,
+                )?;
+            }
+
+            if halves == 1 {
+                // Zero upper 64 bits.
+                emit_store_to(
+                    &mut a,
+                    Size::S64,
+                    Location::GPR(GPR::X0),
+                    GPR::X2,
+                    (i * 16 + 8) as _,
+                    SCRATCH,
+                )?;
+            }
+        }
+    }
+
+    // Load target address.
+    let offset = vmoffsets.vmdynamicfunction_import_context_address();
+    emit_load_from(
+        &mut a,
+        Size::S64,
+        Location::GPR(GPR::X9),
+        GPR::X10,
+        offset as i32,
+    )?;
+    // Load values array.
+    a.emit_mov(GPR::X11, GPR::X2)?;
+
+    // Call target.
+    a.emit_call_register(GPR::X9)?;
+
+    // Fetch return value.
+    if !sig.results().is_empty() {
+        assert_eq!(sig.results().len(), 1);
+        a.emit_load(Size::S64, false, GPR::X10, GPR::X2, 0)?;
+        match sig.results()[0] {
+            Type::V128 => a.emit_load(Size::S64, false, GPR::X11, GPR::X2, 8)?,
+            // Native callers expect floats in `fa0`.
+            Type::F32 => a.emit_fload(Size::S32, FPR::F10, GPR::X2, 0)?,
+            Type::F64 => a.emit_fload(Size::S64, FPR::F10, GPR::X2, 0)?,
+            _ => {}
+        }
+    }
+
+    // Release values array.
+    emit_adjust_sp(&mut a, stack_offset as i64, GPR::X9)?;
+    a.emit_load(Size::S64, false, GPR::X9, GPR::X2, 0)?;
+    a.emit_load(Size::S64, false, GPR::X1, GPR::X2, 8)?;
+    a.emit_alu_imm(Size::S64, AluImmOp::Addi, GPR::X2, GPR::X2, 16)?;
+
+    // Return.
+    a.emit_ret()?;
+
+    let mut body = a.finalize().unwrap();
+    body.shrink_to_fit();
+    Ok(FunctionBody {
+        body,
+        unwind_info: None,
+    })
+}
+
+// Singlepass calls import functions through a trampoline.
+pub fn gen_import_call_trampoline_riscv(
+    vmoffsets: &VMOffsets,
+    index: FunctionIndex,
+    sig: &FunctionType,
+    calling_convention: CallingConvention,
+) -> Result<CustomSection, CompileError> {
+    let mut a = Assembler::new(0);
+
+    // Singlepass internally treats all arguments as integers, with the upper
+    // half of a 32-bit integer left undefined.
+    // The LP64D ABI passes floating point arguments in FP registers, and
+    // expects 32-bit integers to be sign-extended to 64 bits.
+    // A v128 is passed as two 64-bit halves.
+    let params: Vec<Type> = sig
+        .params()
+        .iter()
+        .flat_map(|&ty| match ty {
+            Type::V128 => vec![Type::I64, Type::I64],
+            _ => vec![ty],
+        })
+        .collect();
+    if params.iter().any(|&x| x == Type::F32 || x == Type::F64) {
+        // Allocate stack space for arguments.
+        let stack_offset: i32 = if params.len() > 7 {
+            7 * 8
+        } else {
+            (params.len() as i32) * 8
+        };
+        let stack_offset = if stack_offset & 15 != 0 {
+            stack_offset + 8
+        } else {
+            stack_offset
+        };
+        emit_adjust_sp(&mut a, -(stack_offset as i64), SCRATCH)?;
+
+        // Store all arguments to the stack to prevent overwrite.
+        let mut param_locations = vec![];
+        for i in 0..params.len() {
+            let loc = match i {
+                0..=6 => {
+                    let offset = (i * 8) as i32;
+                    a.emit_store(Size::S64, GPR::from_index(i + 11).unwrap(), GPR::X2, offset)?;
+                    offset
+                }
+                _ => stack_offset + ((i - 7) * 8) as i32,
+            };
+            param_locations.push(loc);
+        }
+
+        // Copy arguments.
+        let mut caller_stack_offset: i32 = 0;
+        let mut argalloc = ArgumentRegisterAllocator::default();
+        argalloc.next(Type::I64, calling_convention).unwrap(); // skip VMContext
+        for (i, ty) in params.iter().enumerate() {
+            let prev_loc = param_locations[i];
+            let sz = match ty {
+                Type::I32 | Type::F32 => Size::S32,
+                _ => Size::S64,
+            };
+            let targ = match argalloc.next(*ty, calling_convention) {
+                Some(RiscvRegister::GPR(gpr)) => Location::GPR(gpr),
+                Some(RiscvRegister::FPR(fpr)) => Location::SIMD(fpr),
+                None => {
+                    // No register can be allocated. Put this argument on the stack.
+                    emit_load_from(&mut a, sz, Location::GPR(SCRATCH), GPR::X2, prev_loc)?;
+                    emit_store_to(
+                        &mut a,
+                        Size::S64,
+                        Location::GPR(SCRATCH),
+                        GPR::X2,
+                        stack_offset + caller_stack_offset,
+                        GPR::X30,
+                    )?;
+                    caller_stack_offset += 8;
+                    continue;
+                }
+            };
+            emit_load_from(&mut a, sz, targ, GPR::X2, prev_loc)?;
+        }
+
+        // Restore stack pointer.
+        emit_adjust_sp(&mut a, stack_offset as i64, SCRATCH)?;
+    } else {
+        for (i, ty) in params.iter().take(7).enumerate() {
+            if *ty == Type::I32 {
+                let reg = GPR::from_index(i + 11).unwrap();
+                a.emit_sext32(reg, reg)?;
+            }
+        }
+    }
+
+    // Emits a tail call trampoline that loads the address of the target import function
+    // from Ctx and jumps to it.
+    let offset = vmoffsets.vmctx_vmfunction_import(index) as i32;
+    let offset = if imm12(offset as i64 + 8) {
+        offset
+    } else {
+        a.emit_mov_imm(SCRATCH, offset as i64 as u64)?;
+        a.emit_alu(Size::S64, AluOp::Add, GPR::X10, GPR::X10, SCRATCH)?;
+        0
+    };
+    a.emit_load(Size::S64, false, SCRATCH, GPR::X10, offset)?; // function pointer
+    a.emit_load(Size::S64, false, GPR::X10, GPR::X10, offset + 8)?; // target vmctx
+    a.emit_j_register(SCRATCH)?;
+
+    let mut contents = a.finalize().unwrap();
+    contents.shrink_to_fit();
+    let section_body = SectionBody::new_with_vec(contents);
+
+    Ok(CustomSection {
+        protection: CustomSectionProtection::ReadExecute,
+        bytes: section_body,
+        relocations: vec![],
+    })
+}
@@ -20,11 +20,14 @@ mod config;
 #[cfg(feature = "unwind")]
 mod dwarf;
 mod emitter_arm64;
+mod emitter_riscv;
 mod emitter_x64;
 mod location;
 mod machine;
 mod machine_arm64;
+mod machine_riscv;
 mod machine_x64;
+mod riscv_decl;
 mod unwind;
 #[cfg(feature = "unwind")]
 mod unwind_winx64;
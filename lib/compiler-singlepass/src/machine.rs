@@ -1,13 +1,14 @@
 use crate::common_decl::*;
 use crate::location::{Location, Reg};
 use crate::machine_arm64::MachineARM64;
+use crate::machine_riscv::MachineRiscv;
 use crate::machine_x64::MachineX86_64;
 use crate::unwind::UnwindInstructions;
 use dynasmrt::{AssemblyOffset, DynamicLabel};
 use std::collections::BTreeMap;
 use std::fmt::Debug;
 pub use wasmer_compiler::wasmparser::MemArg;
-use wasmer_compiler::wasmparser::ValType as WpType;
+use wasmer_compiler::wasmparser::{Operator, ValType as WpType};
 use wasmer_types::{
     Architecture, CallingConvention, CompileError, CustomSection, FunctionBody, FunctionIndex,
     FunctionType, InstructionAddressMap, Relocation, RelocationTarget, Target, TrapCode,
@@ -241,6 +242,17 @@ pub trait Machine {
     /// finalize a function
     fn finalize_function(&mut self) -> Result<(), CompileError>;
 
+    /// emit the tier-up entry of a function, before its prolog: increment the call count
+    /// at offset `calls` of the `VMFunctionTier` array pointed at offset `tiers` of `vmctx`,
+    /// and jump to the redirect at offset `redirect` of the array if not null.
+    /// Only registers not used to pass the parameters can be clobbered.
+    fn emit_tier_up_entry(
+        &mut self,
+        vmctx: Location<Self::GPR, Self::SIMD>,
+        tiers: u32,
+        redirect: u32,
+        calls: u32,
+    ) -> Result<(), CompileError>;
     /// emit native function prolog (depending on the calling Convention, like "PUSH RBP / MOV RSP, RBP")
     fn emit_function_prolog(&mut self) -> Result<(), CompileError>;
     /// emit native function epilog (depending on the calling Convention, like "MOV RBP, RSP / POP RBP")
@@ -293,6 +305,8 @@ pub trait Machine {
     fn get_gpr_for_ret(&self) -> Self::GPR;
     /// get the simd for the return of float/double values
     fn get_simd_for_ret(&self) -> Self::SIMD;
+    /// get the gpr for the return of the high half of v128 values
+    fn get_gpr_for_ret_high(&self) -> Self::GPR;
 
     /// Emit a debug breakpoint
     fn emit_debug_breakpoint(&mut self) -> Result<(), CompileError>;
@@ -666,6 +680,7 @@ pub trait Machine {
         memarg: &MemArg,
         ret: Location<Self::GPR, Self::SIMD>,
         need_check: bool,
+        memory64: bool,
         imported_memories: bool,
         offset: i32,
         heap_access_oob: Label,
@@ -679,6 +694,7 @@ pub trait Machine {
         memarg: &MemArg,
         ret: Location<Self::GPR, Self::SIMD>,
         need_check: bool,
+        memory64: bool,
         imported_memories: bool,
         offset: i32,
         heap_access_oob: Label,
@@ -692,6 +708,7 @@ pub trait Machine {
         memarg: &MemArg,
         ret: Location<Self::GPR, Self::SIMD>,
         need_check: bool,
+        memory64: bool,
         imported_memories: bool,
         offset: i32,
         heap_access_oob: Label,
@@ -705,6 +722,7 @@ pub trait Machine {
         memarg: &MemArg,
         ret: Location<Self::GPR, Self::SIMD>,
         need_check: bool,
+        memory64: bool,
         imported_memories: bool,
         offset: i32,
         heap_access_oob: Label,
@@ -718,6 +736,7 @@ pub trait Machine {
         memarg: &MemArg,
         ret: Location<Self::GPR, Self::SIMD>,
         need_check: bool,
+        memory64: bool,
         imported_memories: bool,
         offset: i32,
         heap_access_oob: Label,
@@ -731,6 +750,7 @@ pub trait Machine {
         memarg: &MemArg,
         ret: Location<Self::GPR, Self::SIMD>,
         need_check: bool,
+        memory64: bool,
         imported_memories: bool,
         offset: i32,
         heap_access_oob: Label,
@@ -744,6 +764,7 @@ pub trait Machine {
         memarg: &MemArg,
         ret: Location<Self::GPR, Self::SIMD>,
         need_check: bool,
+        memory64: bool,
         imported_memories: bool,
         offset: i32,
         heap_access_oob: Label,
@@ -757,6 +778,7 @@ pub trait Machine {
         memarg: &MemArg,
         ret: Location<Self::GPR, Self::SIMD>,
         need_check: bool,
+        memory64: bool,
         imported_memories: bool,
         offset: i32,
         heap_access_oob: Label,
@@ -770,6 +792,7 @@ pub trait Machine {
         memarg: &MemArg,
         addr: Location<Self::GPR, Self::SIMD>,
         need_check: bool,
+        memory64: bool,
         imported_memories: bool,
         offset: i32,
         heap_access_oob: Label,
@@ -783,6 +806,7 @@ pub trait Machine {
         memarg: &MemArg,
         addr: Location<Self::GPR, Self::SIMD>,
         need_check: bool,
+        memory64: bool,
         imported_memories: bool,
         offset: i32,
         heap_access_oob: Label,
@@ -796,6 +820,7 @@ pub trait Machine {
         memarg: &MemArg,
         addr: Location<Self::GPR, Self::SIMD>,
         need_check: bool,
+        memory64: bool,
         imported_memories: bool,
         offset: i32,
         heap_access_oob: Label,
@@ -809,6 +834,7 @@ pub trait Machine {
         memarg: &MemArg,
         addr: Location<Self::GPR, Self::SIMD>,
         need_check: bool,
+        memory64: bool,
         imported_memories: bool,
         offset: i32,
         heap_access_oob: Label,
@@ -822,6 +848,7 @@ pub trait Machine {
         memarg: &MemArg,
         addr: Location<Self::GPR, Self::SIMD>,
         need_check: bool,
+        memory64: bool,
         imported_memories: bool,
         offset: i32,
         heap_access_oob: Label,
@@ -835,6 +862,7 @@ pub trait Machine {
         memarg: &MemArg,
         addr: Location<Self::GPR, Self::SIMD>,
         need_check: bool,
+        memory64: bool,
         imported_memories: bool,
         offset: i32,
         heap_access_oob: Label,
@@ -849,6 +877,7 @@ pub trait Machine {
         memarg: &MemArg,
         ret: Location<Self::GPR, Self::SIMD>,
         need_check: bool,
+        memory64: bool,
         imported_memories: bool,
         offset: i32,
         heap_access_oob: Label,
@@ -863,6 +892,7 @@ pub trait Machine {
         memarg: &MemArg,
         ret: Location<Self::GPR, Self::SIMD>,
         need_check: bool,
+        memory64: bool,
         imported_memories: bool,
         offset: i32,
         heap_access_oob: Label,
@@ -877,6 +907,7 @@ pub trait Machine {
         memarg: &MemArg,
         ret: Location<Self::GPR, Self::SIMD>,
         need_check: bool,
+        memory64: bool,
         imported_memories: bool,
         offset: i32,
         heap_access_oob: Label,
@@ -891,6 +922,7 @@ pub trait Machine {
         memarg: &MemArg,
         ret: Location<Self::GPR, Self::SIMD>,
         need_check: bool,
+        memory64: bool,
         imported_memories: bool,
         offset: i32,
         heap_access_oob: Label,
@@ -905,6 +937,7 @@ pub trait Machine {
         memarg: &MemArg,
         ret: Location<Self::GPR, Self::SIMD>,
         need_check: bool,
+        memory64: bool,
         imported_memories: bool,
         offset: i32,
         heap_access_oob: Label,
@@ -919,6 +952,7 @@ pub trait Machine {
         memarg: &MemArg,
         ret: Location<Self::GPR, Self::SIMD>,
         need_check: bool,
+        memory64: bool,
         imported_memories: bool,
         offset: i32,
         heap_access_oob: Label,
@@ -933,6 +967,7 @@ pub trait Machine {
         memarg: &MemArg,
         ret: Location<Self::GPR, Self::SIMD>,
         need_check: bool,
+        memory64: bool,
         imported_memories: bool,
         offset: i32,
         heap_access_oob: Label,
@@ -947,6 +982,7 @@ pub trait Machine {
         memarg: &MemArg,
         ret: Location<Self::GPR, Self::SIMD>,
         need_check: bool,
+        memory64: bool,
         imported_memories: bool,
         offset: i32,
         heap_access_oob: Label,
@@ -961,6 +997,7 @@ pub trait Machine {
         memarg: &MemArg,
         ret: Location<Self::GPR, Self::SIMD>,
         need_check: bool,
+        memory64: bool,
         imported_memories: bool,
         offset: i32,
         heap_access_oob: Label,
@@ -975,6 +1012,7 @@ pub trait Machine {
         memarg: &MemArg,
         ret: Location<Self::GPR, Self::SIMD>,
         need_check: bool,
+        memory64: bool,
         imported_memories: bool,
         offset: i32,
         heap_access_oob: Label,
@@ -989,6 +1027,7 @@ pub trait Machine {
         memarg: &MemArg,
         ret: Location<Self::GPR, Self::SIMD>,
         need_check: bool,
+        memory64: bool,
         imported_memories: bool,
         offset: i32,
         heap_access_oob: Label,
@@ -1003,6 +1042,7 @@ pub trait Machine {
         memarg: &MemArg,
         ret: Location<Self::GPR, Self::SIMD>,
         need_check: bool,
+        memory64: bool,
         imported_memories: bool,
         offset: i32,
         heap_access_oob: Label,
@@ -1017,6 +1057,7 @@ pub trait Machine {
         memarg: &MemArg,
         ret: Location<Self::GPR, Self::SIMD>,
         need_check: bool,
+        memory64: bool,
         imported_memories: bool,
         offset: i32,
         heap_access_oob: Label,
@@ -1031,6 +1072,7 @@ pub trait Machine {
         memarg: &MemArg,
         ret: Location<Self::GPR, Self::SIMD>,
         need_check: bool,
+        memory64: bool,
         imported_memories: bool,
         offset: i32,
         heap_access_oob: Label,
@@ -1045,6 +1087,7 @@ pub trait Machine {
         memarg: &MemArg,
         ret: Location<Self::GPR, Self::SIMD>,
         need_check: bool,
+        memory64: bool,
         imported_memories: bool,
         offset: i32,
         heap_access_oob: Label,
@@ -1059,6 +1102,7 @@ pub trait Machine {
         memarg: &MemArg,
         ret: Location<Self::GPR, Self::SIMD>,
         need_check: bool,
+        memory64: bool,
         imported_memories: bool,
         offset: i32,
         heap_access_oob: Label,
@@ -1073,6 +1117,7 @@ pub trait Machine {
         memarg: &MemArg,
         ret: Location<Self::GPR, Self::SIMD>,
         need_check: bool,
+        memory64: bool,
         imported_memories: bool,
         offset: i32,
         heap_access_oob: Label,
@@ -1087,6 +1132,7 @@ pub trait Machine {
         memarg: &MemArg,
         ret: Location<Self::GPR, Self::SIMD>,
         need_check: bool,
+        memory64: bool,
         imported_memories: bool,
         offset: i32,
         heap_access_oob: Label,
@@ -1102,6 +1148,7 @@ pub trait Machine {
         memarg: &MemArg,
         ret: Location<Self::GPR, Self::SIMD>,
         need_check: bool,
+        memory64: bool,
         imported_memories: bool,
         offset: i32,
         heap_access_oob: Label,
@@ -1117,6 +1164,7 @@ pub trait Machine {
         memarg: &MemArg,
         ret: Location<Self::GPR, Self::SIMD>,
         need_check: bool,
+        memory64: bool,
         imported_memories: bool,
         offset: i32,
         heap_access_oob: Label,
@@ -1132,6 +1180,7 @@ pub trait Machine {
         memarg: &MemArg,
         ret: Location<Self::GPR, Self::SIMD>,
         need_check: bool,
+        memory64: bool,
         imported_memories: bool,
         offset: i32,
         heap_access_oob: Label,
@@ -1353,6 +1402,7 @@ pub trait Machine {
         memarg: &MemArg,
         ret: Location<Self::GPR, Self::SIMD>,
         need_check: bool,
+        memory64: bool,
         imported_memories: bool,
         offset: i32,
         heap_access_oob: Label,
@@ -1366,6 +1416,7 @@ pub trait Machine {
         memarg: &MemArg,
         ret: Location<Self::GPR, Self::SIMD>,
         need_check: bool,
+        memory64: bool,
         imported_memories: bool,
         offset: i32,
         heap_access_oob: Label,
@@ -1379,6 +1430,7 @@ pub trait Machine {
         memarg: &MemArg,
         ret: Location<Self::GPR, Self::SIMD>,
         need_check: bool,
+        memory64: bool,
         imported_memories: bool,
         offset: i32,
         heap_access_oob: Label,
@@ -1392,6 +1444,7 @@ pub trait Machine {
         memarg: &MemArg,
         ret: Location<Self::GPR, Self::SIMD>,
         need_check: bool,
+        memory64: bool,
         imported_memories: bool,
         offset: i32,
         heap_access_oob: Label,
@@ -1405,6 +1458,7 @@ pub trait Machine {
         memarg: &MemArg,
         ret: Location<Self::GPR, Self::SIMD>,
         need_check: bool,
+        memory64: bool,
         imported_memories: bool,
         offset: i32,
         heap_access_oob: Label,
@@ -1418,6 +1472,7 @@ pub trait Machine {
         memarg: &MemArg,
         ret: Location<Self::GPR, Self::SIMD>,
         need_check: bool,
+        memory64: bool,
         imported_memories: bool,
         offset: i32,
         heap_access_oob: Label,
@@ -1431,6 +1486,7 @@ pub trait Machine {
         memarg: &MemArg,
         ret: Location<Self::GPR, Self::SIMD>,
         need_check: bool,
+        memory64: bool,
         imported_memories: bool,
         offset: i32,
         heap_access_oob: Label,
@@ -1444,6 +1500,7 @@ pub trait Machine {
         memarg: &MemArg,
         ret: Location<Self::GPR, Self::SIMD>,
         need_check: bool,
+        memory64: bool,
         imported_memories: bool,
         offset: i32,
         heap_access_oob: Label,
@@ -1457,6 +1514,7 @@ pub trait Machine {
         memarg: &MemArg,
         ret: Location<Self::GPR, Self::SIMD>,
         need_check: bool,
+        memory64: bool,
         imported_memories: bool,
         offset: i32,
         heap_access_oob: Label,
@@ -1470,6 +1528,7 @@ pub trait Machine {
         memarg: &MemArg,
         ret: Location<Self::GPR, Self::SIMD>,
         need_check: bool,
+        memory64: bool,
         imported_memories: bool,
         offset: i32,
         heap_access_oob: Label,
@@ -1483,6 +1542,7 @@ pub trait Machine {
         memarg: &MemArg,
         ret: Location<Self::GPR, Self::SIMD>,
         need_check: bool,
+        memory64: bool,
         imported_memories: bool,
         offset: i32,
         heap_access_oob: Label,
@@ -1496,6 +1556,7 @@ pub trait Machine {
         memarg: &MemArg,
         addr: Location<Self::GPR, Self::SIMD>,
         need_check: bool,
+        memory64: bool,
         imported_memories: bool,
         offset: i32,
         heap_access_oob: Label,
@@ -1509,6 +1570,7 @@ pub trait Machine {
         memarg: &MemArg,
         addr: Location<Self::GPR, Self::SIMD>,
         need_check: bool,
+        memory64: bool,
         imported_memories: bool,
         offset: i32,
         heap_access_oob: Label,
@@ -1522,6 +1584,7 @@ pub trait Machine {
         memarg: &MemArg,
         addr: Location<Self::GPR, Self::SIMD>,
         need_check: bool,
+        memory64: bool,
         imported_memories: bool,
         offset: i32,
         heap_access_oob: Label,
@@ -1535,6 +1598,7 @@ pub trait Machine {
         memarg: &MemArg,
         addr: Location<Self::GPR, Self::SIMD>,
         need_check: bool,
+        memory64: bool,
         imported_memories: bool,
         offset: i32,
         heap_access_oob: Label,
@@ -1548,6 +1612,7 @@ pub trait Machine {
         memarg: &MemArg,
         addr: Location<Self::GPR, Self::SIMD>,
         need_check: bool,
+        memory64: bool,
         imported_memories: bool,
         offset: i32,
         heap_access_oob: Label,
@@ -1561,6 +1626,7 @@ pub trait Machine {
         memarg: &MemArg,
         addr: Location<Self::GPR, Self::SIMD>,
         need_check: bool,
+        memory64: bool,
         imported_memories: bool,
         offset: i32,
         heap_access_oob: Label,
@@ -1574,6 +1640,7 @@ pub trait Machine {
         memarg: &MemArg,
         addr: Location<Self::GPR, Self::SIMD>,
         need_check: bool,
+        memory64: bool,
         imported_memories: bool,
         offset: i32,
         heap_access_oob: Label,
@@ -1587,6 +1654,7 @@ pub trait Machine {
         memarg: &MemArg,
         addr: Location<Self::GPR, Self::SIMD>,
         need_check: bool,
+        memory64: bool,
         imported_memories: bool,
         offset: i32,
         heap_access_oob: Label,
@@ -1601,6 +1669,7 @@ pub trait Machine {
         memarg: &MemArg,
         ret: Location<Self::GPR, Self::SIMD>,
         need_check: bool,
+        memory64: bool,
         imported_memories: bool,
         offset: i32,
         heap_access_oob: Label,
@@ -1615,6 +1684,7 @@ pub trait Machine {
         memarg: &MemArg,
         ret: Location<Self::GPR, Self::SIMD>,
         need_check: bool,
+        memory64: bool,
         imported_memories: bool,
         offset: i32,
         heap_access_oob: Label,
@@ -1629,6 +1699,7 @@ pub trait Machine {
         memarg: &MemArg,
         ret: Location<Self::GPR, Self::SIMD>,
         need_check: bool,
+        memory64: bool,
         imported_memories: bool,
         offset: i32,
         heap_access_oob: Label,
@@ -1643,6 +1714,7 @@ pub trait Machine {
         memarg: &MemArg,
         ret: Location<Self::GPR, Self::SIMD>,
         need_check: bool,
+        memory64: bool,
         imported_memories: bool,
         offset: i32,
         heap_access_oob: Label,
@@ -1657,6 +1729,7 @@ pub trait Machine {
         memarg: &MemArg,
         ret: Location<Self::GPR, Self::SIMD>,
         need_check: bool,
+        memory64: bool,
         imported_memories: bool,
         offset: i32,
         heap_access_oob: Label,
@@ -1671,6 +1744,7 @@ pub trait Machine {
         memarg: &MemArg,
         ret: Location<Self::GPR, Self::SIMD>,
         need_check: bool,
+        memory64: bool,
         imported_memories: bool,
         offset: i32,
         heap_access_oob: Label,
@@ -1685,6 +1759,7 @@ pub trait Machine {
         memarg: &MemArg,
         ret: Location<Self::GPR, Self::SIMD>,
         need_check: bool,
+        memory64: bool,
         imported_memories: bool,
         offset: i32,
         heap_access_oob: Label,
@@ -1699,6 +1774,7 @@ pub trait Machine {
         memarg: &MemArg,
         ret: Location<Self::GPR, Self::SIMD>,
         need_check: bool,
+        memory64: bool,
         imported_memories: bool,
         offset: i32,
         heap_access_oob: Label,
@@ -1713,6 +1789,7 @@ pub trait Machine {
         memarg: &MemArg,
         ret: Location<Self::GPR, Self::SIMD>,
         need_check: bool,
+        memory64: bool,
         imported_memories: bool,
         offset: i32,
         heap_access_oob: Label,
@@ -1727,6 +1804,7 @@ pub trait Machine {
         memarg: &MemArg,
         ret: Location<Self::GPR, Self::SIMD>,
         need_check: bool,
+        memory64: bool,
         imported_memories: bool,
         offset: i32,
         heap_access_oob: Label,
@@ -1741,6 +1819,7 @@ pub trait Machine {
         memarg: &MemArg,
         ret: Location<Self::GPR, Self::SIMD>,
         need_check: bool,
+        memory64: bool,
         imported_memories: bool,
         offset: i32,
         heap_access_oob: Label,
@@ -1755,6 +1834,7 @@ pub trait Machine {
         memarg: &MemArg,
         ret: Location<Self::GPR, Self::SIMD>,
         need_check: bool,
+        memory64: bool,
         imported_memories: bool,
         offset: i32,
         heap_access_oob: Label,
@@ -1769,6 +1849,7 @@ pub trait Machine {
         memarg: &MemArg,
         ret: Location<Self::GPR, Self::SIMD>,
         need_check: bool,
+        memory64: bool,
         imported_memories: bool,
         offset: i32,
         heap_access_oob: Label,
@@ -1783,6 +1864,7 @@ pub trait Machine {
         memarg: &MemArg,
         ret: Location<Self::GPR, Self::SIMD>,
         need_check: bool,
+        memory64: bool,
         imported_memories: bool,
         offset: i32,
         heap_access_oob: Label,
@@ -1797,6 +1879,7 @@ pub trait Machine {
         memarg: &MemArg,
         ret: Location<Self::GPR, Self::SIMD>,
         need_check: bool,
+        memory64: bool,
         imported_memories: bool,
         offset: i32,
         heap_access_oob: Label,
@@ -1811,6 +1894,7 @@ pub trait Machine {
         memarg: &MemArg,
         ret: Location<Self::GPR, Self::SIMD>,
         need_check: bool,
+        memory64: bool,
         imported_memories: bool,
         offset: i32,
         heap_access_oob: Label,
@@ -1825,6 +1909,7 @@ pub trait Machine {
         memarg: &MemArg,
         ret: Location<Self::GPR, Self::SIMD>,
         need_check: bool,
+        memory64: bool,
         imported_memories: bool,
         offset: i32,
         heap_access_oob: Label,
@@ -1839,6 +1924,7 @@ pub trait Machine {
         memarg: &MemArg,
         ret: Location<Self::GPR, Self::SIMD>,
         need_check: bool,
+        memory64: bool,
         imported_memories: bool,
         offset: i32,
         heap_access_oob: Label,
@@ -1853,6 +1939,7 @@ pub trait Machine {
         memarg: &MemArg,
         ret: Location<Self::GPR, Self::SIMD>,
         need_check: bool,
+        memory64: bool,
         imported_memories: bool,
         offset: i32,
         heap_access_oob: Label,
@@ -1867,6 +1954,7 @@ pub trait Machine {
         memarg: &MemArg,
         ret: Location<Self::GPR, Self::SIMD>,
         need_check: bool,
+        memory64: bool,
         imported_memories: bool,
         offset: i32,
         heap_access_oob: Label,
@@ -1881,6 +1969,7 @@ pub trait Machine {
         memarg: &MemArg,
         ret: Location<Self::GPR, Self::SIMD>,
         need_check: bool,
+        memory64: bool,
         imported_memories: bool,
         offset: i32,
         heap_access_oob: Label,
@@ -1895,6 +1984,7 @@ pub trait Machine {
         memarg: &MemArg,
         ret: Location<Self::GPR, Self::SIMD>,
         need_check: bool,
+        memory64: bool,
         imported_memories: bool,
         offset: i32,
         heap_access_oob: Label,
@@ -1909,6 +1999,7 @@ pub trait Machine {
         memarg: &MemArg,
         ret: Location<Self::GPR, Self::SIMD>,
         need_check: bool,
+        memory64: bool,
         imported_memories: bool,
         offset: i32,
         heap_access_oob: Label,
@@ -1923,6 +2014,7 @@ pub trait Machine {
         memarg: &MemArg,
         ret: Location<Self::GPR, Self::SIMD>,
         need_check: bool,
+        memory64: bool,
         imported_memories: bool,
         offset: i32,
         heap_access_oob: Label,
@@ -1938,6 +2030,7 @@ pub trait Machine {
         memarg: &MemArg,
         ret: Location<Self::GPR, Self::SIMD>,
         need_check: bool,
+        memory64: bool,
         imported_memories: bool,
         offset: i32,
         heap_access_oob: Label,
@@ -1953,6 +2046,7 @@ pub trait Machine {
         memarg: &MemArg,
         ret: Location<Self::GPR, Self::SIMD>,
         need_check: bool,
+        memory64: bool,
         imported_memories: bool,
         offset: i32,
         heap_access_oob: Label,
@@ -1968,6 +2062,7 @@ pub trait Machine {
         memarg: &MemArg,
         ret: Location<Self::GPR, Self::SIMD>,
         need_check: bool,
+        memory64: bool,
         imported_memories: bool,
         offset: i32,
         heap_access_oob: Label,
@@ -1983,6 +2078,7 @@ pub trait Machine {
         memarg: &MemArg,
         ret: Location<Self::GPR, Self::SIMD>,
         need_check: bool,
+        memory64: bool,
         imported_memories: bool,
         offset: i32,
         heap_access_oob: Label,
@@ -1997,6 +2093,7 @@ pub trait Machine {
         memarg: &MemArg,
         ret: Location<Self::GPR, Self::SIMD>,
         need_check: bool,
+        memory64: bool,
         imported_memories: bool,
         offset: i32,
         heap_access_oob: Label,
@@ -2011,6 +2108,7 @@ pub trait Machine {
         addr: Location<Self::GPR, Self::SIMD>,
         canonicalize: bool,
         need_check: bool,
+        memory64: bool,
         imported_memories: bool,
         offset: i32,
         heap_access_oob: Label,
@@ -2024,6 +2122,7 @@ pub trait Machine {
         memarg: &MemArg,
         ret: Location<Self::GPR, Self::SIMD>,
         need_check: bool,
+        memory64: bool,
         imported_memories: bool,
         offset: i32,
         heap_access_oob: Label,
@@ -2038,6 +2137,7 @@ pub trait Machine {
         addr: Location<Self::GPR, Self::SIMD>,
         canonicalize: bool,
         need_check: bool,
+        memory64: bool,
         imported_memories: bool,
         offset: i32,
         heap_access_oob: Label,
@@ -2372,6 +2472,31 @@ pub trait Machine {
         ret: Location<Self::GPR, Self::SIMD>,
     ) -> Result<(), CompileError>;
 
+    /// Emit a fixed-width SIMD operator. Each v128 operand, and the v128
+    /// result, takes two locations: its low 64-bit half, then its high one.
+    fn emit_simd(
+        &mut self,
+        op: &Operator,
+        args: &[Location<Self::GPR, Self::SIMD>],
+        ret: &[Location<Self::GPR, Self::SIMD>],
+    ) -> Result<(), CompileError>;
+    /// Emit a fixed-width SIMD load or store, with the operands other than
+    /// the address laid out as for `emit_simd`.
+    #[allow(clippy::too_many_arguments)]
+    fn emit_simd_memory(
+        &mut self,
+        op: &Operator,
+        addr: Location<Self::GPR, Self::SIMD>,
+        args: &[Location<Self::GPR, Self::SIMD>],
+        ret: &[Location<Self::GPR, Self::SIMD>],
+        need_check: bool,
+        memory64: bool,
+        imported_memories: bool,
+        offset: i32,
+        heap_access_oob: Label,
+        unaligned_atomic: Label,
+    ) -> Result<(), CompileError>;
+
     /// Standard function Trampoline generation
     fn gen_std_trampoline(
         &self,
@@ -2414,6 +2539,10 @@ pub fn gen_std_trampoline(
             let machine = MachineARM64::new(Some(target.clone()));
             machine.gen_std_trampoline(sig, calling_convention)
         }
+        Architecture::Riscv64(_) => {
+            let machine = MachineRiscv::new(Some(target.clone()));
+            machine.gen_std_trampoline(sig, calling_convention)
+        }
         _ => Err(CompileError::UnsupportedTarget(
             "singlepass unimplemented arch for gen_std_trampoline".to_owned(),
         )),
@@ -2436,6 +2565,10 @@ pub fn gen_std_dynamic_import_trampoline(
             let machine = MachineARM64::new(Some(target.clone()));
             machine.gen_std_dynamic_import_trampoline(vmoffsets, sig, calling_convention)
         }
+        Architecture::Riscv64(_) => {
+            let machine = MachineRiscv::new(Some(target.clone()));
+            machine.gen_std_dynamic_import_trampoline(vmoffsets, sig, calling_convention)
+        }
         _ => Err(CompileError::UnsupportedTarget(
             "singlepass unimplemented arch for gen_std_dynamic_import_trampoline".to_owned(),
         )),
@@ -2458,6 +2591,10 @@ pub fn gen_import_call_trampoline(
             let machine = MachineARM64::new(Some(target.clone()));
             machine.gen_import_call_trampoline(vmoffsets, index, sig, calling_convention)
         }
+        Architecture::Riscv64(_) => {
+            let machine = MachineRiscv::new(Some(target.clone()));
+            machine.gen_import_call_trampoline(vmoffsets, index, sig, calling_convention)
+        }
         _ => Err(CompileError::UnsupportedTarget(
             "singlepass unimplemented arch for gen_import_call_trampoline".to_owned(),
         )),
@@ -2,7 +2,7 @@ use dynasmrt::{aarch64::Aarch64Relocation, VecAssembler};
 #[cfg(feature = "unwind")]
 use gimli::{write::CallFrameInstruction, AArch64};
 
-use wasmer_compiler::wasmparser::ValType as WpType;
+use wasmer_compiler::wasmparser::{Operator, ValType as WpType};
 use wasmer_types::{
     CallingConvention, CompileError, CpuFeature, CustomSection, FunctionBody, FunctionIndex,
     FunctionType, InstructionAddressMap, Relocation, RelocationKind, RelocationTarget, SourceLoc,
@@ -976,6 +976,7 @@ impl MachineARM64 {
         check_alignment: bool,
         value_size: usize,
         need_check: bool,
+        memory64: bool,
         imported_memories: bool,
         offset: i32,
         heap_access_oob: Label,
@@ -1053,13 +1054,14 @@ impl MachineARM64 {
         // Load effective address.
         // `base_loc` and `bound_loc` becomes INVALID after this line, because `tmp_addr`
         // might be reused.
-        self.move_location(Size::S32, addr, Location::GPR(tmp_addr))?;
+        let addr_size = if memory64 { Size::S64 } else { Size::S32 };
+        self.move_location(addr_size, addr, Location::GPR(tmp_addr))?;
 
         // Add offset to memory address.
         if memarg.offset != 0 {
             if self.compatible_imm(memarg.offset as _, ImmType::Bits12) {
                 self.assembler.emit_adds(
-                    Size::S32,
+                    addr_size,
                     Location::Imm32(memarg.offset as u32),
                     Location::GPR(tmp_addr),
                     Location::GPR(tmp_addr),
@@ -1071,7 +1073,7 @@ impl MachineARM64 {
                 self.assembler
                     .emit_mov_imm(Location::GPR(tmp), memarg.offset as _)?;
                 self.assembler.emit_adds(
-                    Size::S32,
+                    addr_size,
                     Location::GPR(tmp_addr),
                     Location::GPR(tmp),
                     Location::GPR(tmp_addr),
@@ -1085,12 +1087,25 @@ impl MachineARM64 {
         }
 
         // Wasm linear memory -> real memory
-        self.assembler.emit_add(
-            Size::S64,
-            Location::GPR(tmp_base),
-            Location::GPR(tmp_addr),
-            Location::GPR(tmp_addr),
-        )?;
+        if memory64 && need_check {
+            // A 64-bit address can wrap around when added to the base,
+            // which the bound check below wouldn't see.
+            self.assembler.emit_adds(
+                Size::S64,
+                Location::GPR(tmp_base),
+                Location::GPR(tmp_addr),
+                Location::GPR(tmp_addr),
+            )?;
+            self.assembler
+                .emit_bcond_label_far(Condition::Cs, heap_access_oob)?;
+        } else {
+            self.assembler.emit_add(
+                Size::S64,
+                Location::GPR(tmp_base),
+                Location::GPR(tmp_addr),
+                Location::GPR(tmp_addr),
+            )?;
+        }
 
         if need_check {
             // Trap if the end address of the requested area is above that of the linear memory.
@@ -1409,6 +1424,91 @@ impl MachineARM64 {
     fn emit_illegal_op_internal(&mut self, trap: TrapCode) -> Result<(), CompileError> {
         self.assembler.emit_udf(0xc0 | (trap as u8) as u16)
     }
+
+    /// Acquires the temporary NEON registers used to lower a SIMD operator,
+    /// the first two being consecutive so they can be a `tbl` table.
+    fn acquire_simd_temps(&mut self) -> Result<[NEON; 3], CompileError> {
+        let mut temps = [NEON::V0; 3];
+        for temp in temps.iter_mut() {
+            *temp = self.acquire_temp_simd().ok_or_else(|| {
+                CompileError::Codegen("singlepass cannot acquire temp simd".to_owned())
+            })?;
+        }
+        if temps[1] as u32 != temps[0] as u32 + 1 {
+            codegen_error!("singlepass cannot acquire consecutive temp simd");
+        }
+        Ok(temps)
+    }
+    fn release_simd_temps(&mut self, temps: [NEON; 3]) {
+        for temp in temps.iter().rev() {
+            self.release_simd(*temp);
+        }
+    }
+    /// Moves the scalar `src` to a temporary GPR, which the caller releases.
+    fn simd_scalar(&mut self, sz: Size, src: Location) -> Result<GPR, CompileError> {
+        let tmp = self.acquire_temp_gpr().ok_or_else(|| {
+            CompileError::Codegen("singlepass cannot acquire temp gpr".to_owned())
+        })?;
+        self.move_location(sz, src, Location::GPR(tmp))?;
+        Ok(tmp)
+    }
+    /// Loads a v128 value, held as its low and high 64-bit halves, into `dst`.
+    fn simd_gather(&mut self, lo: Location, hi: Location, dst: NEON) -> Result<(), CompileError> {
+        for (lane, half) in [(0, lo), (1, hi)] {
+            let tmp = self.simd_scalar(Size::S64, half)?;
+            self.assembler.emit_neon_ins(Lanes::D2, tmp, lane, dst)?;
+            self.release_gpr(tmp);
+        }
+        Ok(())
+    }
+    /// Stores `src` into the low and high 64-bit halves of a v128 value.
+    fn simd_scatter(&mut self, src: NEON, lo: Location, hi: Location) -> Result<(), CompileError> {
+        for (lane, half) in [(0, lo), (1, hi)] {
+            match half {
+                Location::GPR(dst) => {
+                    self.assembler
+                        .emit_neon_umov(Lanes::D2, false, src, lane, dst)?
+                }
+                _ => {
+                    let tmp = self.acquire_temp_gpr().ok_or_else(|| {
+                        CompileError::Codegen("singlepass cannot acquire temp gpr".to_owned())
+                    })?;
+                    self.assembler
+                        .emit_neon_umov(Lanes::D2, false, src, lane, tmp)?;
+                    self.move_location(Size::S64, Location::GPR(tmp), half)?;
+                    self.release_gpr(tmp);
+                }
+            }
+        }
+        Ok(())
+    }
+    /// Materializes a 128-bit constant in `dst`.
+    fn simd_const(&mut self, value: u128, dst: NEON) -> Result<(), CompileError> {
+        if value == 0 {
+            return self
+                .assembler
+                .emit_neon3(Neon3Op::Eor, Lanes::B16, dst, dst, dst);
+        }
+        if value == u128::MAX {
+            return self
+                .assembler
+                .emit_neon3(Neon3Op::Cmeq, Lanes::B16, dst, dst, dst);
+        }
+        let (lo, hi) = (value as u64, (value >> 64) as u64);
+        let tmp = self.acquire_temp_gpr().ok_or_else(|| {
+            CompileError::Codegen("singlepass cannot acquire temp gpr".to_owned())
+        })?;
+        self.assembler.emit_mov_imm(Location::GPR(tmp), lo)?;
+        if hi == lo {
+            self.assembler.emit_neon_dup(Lanes::D2, tmp, dst)?;
+        } else {
+            self.assembler.emit_neon_ins(Lanes::D2, tmp, 0, dst)?;
+            self.assembler.emit_mov_imm(Location::GPR(tmp), hi)?;
+            self.assembler.emit_neon_ins(Lanes::D2, tmp, 1, dst)?;
+        }
+        self.release_gpr(tmp);
+        Ok(())
+    }
 }
 
 impl Machine for MachineARM64 {
@@ -2315,6 +2415,62 @@ impl Machine for MachineARM64 {
         Ok(())
     }
 
+    fn emit_tier_up_entry(
+        &mut self,
+        vmctx: Location,
+        tiers: u32,
+        redirect: u32,
+        calls: u32,
+    ) -> Result<(), CompileError> {
+        // X16 and X17 are the intra-procedure-call scratch registers.
+        let vmctx = match vmctx {
+            Location::GPR(vmctx) => vmctx,
+            _ => codegen_error!("singlepass emit_tier_up_entry unreachable"),
+        };
+        let run = self.get_label();
+        self.assembler
+            .emit_mov_imm(Location::GPR(GPR::X17), tiers as u64)?;
+        self.assembler.emit_ldr(
+            Size::S64,
+            Location::GPR(GPR::X16),
+            Location::Memory2(vmctx, GPR::X17, Multiplier::One, 0),
+        )?;
+        self.assembler
+            .emit_mov_imm(Location::GPR(GPR::X17), redirect as u64)?;
+        self.assembler.emit_add(
+            Size::S64,
+            Location::GPR(GPR::X16),
+            Location::GPR(GPR::X17),
+            Location::GPR(GPR::X16),
+        )?;
+        let calls = (calls - redirect) as i32;
+        self.assembler.emit_ldr(
+            Size::S64,
+            Location::GPR(GPR::X17),
+            Location::Memory(GPR::X16, calls),
+        )?;
+        self.assembler.emit_add(
+            Size::S64,
+            Location::GPR(GPR::X17),
+            Location::Imm8(1),
+            Location::GPR(GPR::X17),
+        )?;
+        self.assembler.emit_str(
+            Size::S64,
+            Location::GPR(GPR::X17),
+            Location::Memory(GPR::X16, calls),
+        )?;
+        self.assembler.emit_ldr(
+            Size::S64,
+            Location::GPR(GPR::X16),
+            Location::Memory(GPR::X16, 0),
+        )?;
+        self.assembler
+            .emit_cbz_label(Size::S64, Location::GPR(GPR::X16), run)?;
+        self.assembler.emit_b_register(GPR::X16)?;
+        self.emit_label(run)
+    }
+
     fn emit_function_prolog(&mut self) -> Result<(), CompileError> {
         self.emit_double_push(Size::S64, Location::GPR(GPR::X29), Location::GPR(GPR::X30))?; // save LR too
         self.emit_unwind_op(UnwindOps::Push2Regs {
@@ -2458,6 +2614,9 @@ impl Machine for MachineARM64 {
     fn get_simd_for_ret(&self) -> NEON {
         NEON::V0
     }
+    fn get_gpr_for_ret_high(&self) -> GPR {
+        GPR::X1
+    }
 
     fn arch_requires_indirect_call_trampoline(&self) -> bool {
         self.assembler.arch_requires_indirect_call_trampoline()
@@ -3266,6 +3425,7 @@ impl Machine for MachineARM64 {
         memarg: &MemArg,
         ret: Location,
         need_check: bool,
+        memory64: bool,
         imported_memories: bool,
         offset: i32,
         heap_access_oob: Label,
@@ -3277,6 +3437,7 @@ impl Machine for MachineARM64 {
             false,
             4,
             need_check,
+            memory64,
             imported_memories,
             offset,
             heap_access_oob,
@@ -3290,6 +3451,7 @@ impl Machine for MachineARM64 {
         memarg: &MemArg,
         ret: Location,
         need_check: bool,
+        memory64: bool,
         imported_memories: bool,
         offset: i32,
         heap_access_oob: Label,
@@ -3301,6 +3463,7 @@ impl Machine for MachineARM64 {
             false,
             1,
             need_check,
+            memory64,
             imported_memories,
             offset,
             heap_access_oob,
@@ -3314,6 +3477,7 @@ impl Machine for MachineARM64 {
         memarg: &MemArg,
         ret: Location,
         need_check: bool,
+        memory64: bool,
         imported_memories: bool,
         offset: i32,
         heap_access_oob: Label,
@@ -3325,6 +3489,7 @@ impl Machine for MachineARM64 {
             false,
             1,
             need_check,
+            memory64,
             imported_memories,
             offset,
             heap_access_oob,
@@ -3338,6 +3503,7 @@ impl Machine for MachineARM64 {
         memarg: &MemArg,
         ret: Location,
         need_check: bool,
+        memory64: bool,
         imported_memories: bool,
         offset: i32,
         heap_access_oob: Label,
@@ -3349,6 +3515,7 @@ impl Machine for MachineARM64 {
             false,
             2,
             need_check,
+            memory64,
             imported_memories,
             offset,
             heap_access_oob,
@@ -3362,6 +3529,7 @@ impl Machine for MachineARM64 {
         memarg: &MemArg,
         ret: Location,
         need_check: bool,
+        memory64: bool,
         imported_memories: bool,
         offset: i32,
         heap_access_oob: Label,
@@ -3373,6 +3541,7 @@ impl Machine for MachineARM64 {
             false,
             2,
             need_check,
+            memory64,
             imported_memories,
             offset,
             heap_access_oob,
@@ -3386,6 +3555,7 @@ impl Machine for MachineARM64 {
         memarg: &MemArg,
         ret: Location,
         need_check: bool,
+        memory64: bool,
         imported_memories: bool,
         offset: i32,
         heap_access_oob: Label,
@@ -3397,6 +3567,7 @@ impl Machine for MachineARM64 {
             true,
             4,
             need_check,
+            memory64,
             imported_memories,
             offset,
             heap_access_oob,
@@ -3410,6 +3581,7 @@ impl Machine for MachineARM64 {
         memarg: &MemArg,
         ret: Location,
         need_check: bool,
+        memory64: bool,
         imported_memories: bool,
         offset: i32,
         heap_access_oob: Label,
@@ -3421,6 +3593,7 @@ impl Machine for MachineARM64 {
             true,
             1,
             need_check,
+            memory64,
             imported_memories,
             offset,
             heap_access_oob,
@@ -3434,6 +3607,7 @@ impl Machine for MachineARM64 {
         memarg: &MemArg,
         ret: Location,
         need_check: bool,
+        memory64: bool,
         imported_memories: bool,
         offset: i32,
         heap_access_oob: Label,
@@ -3445,6 +3619,7 @@ impl Machine for MachineARM64 {
             true,
             2,
             need_check,
+            memory64,
             imported_memories,
             offset,
             heap_access_oob,
@@ -3458,6 +3633,7 @@ impl Machine for MachineARM64 {
         memarg: &MemArg,
         target_addr: Location,
         need_check: bool,
+        memory64: bool,
         imported_memories: bool,
         offset: i32,
         heap_access_oob: Label,
@@ -3469,6 +3645,7 @@ impl Machine for MachineARM64 {
             false,
             4,
             need_check,
+            memory64,
             imported_memories,
             offset,
             heap_access_oob,
@@ -3482,6 +3659,7 @@ impl Machine for MachineARM64 {
         memarg: &MemArg,
         target_addr: Location,
         need_check: bool,
+        memory64: bool,
         imported_memories: bool,
         offset: i32,
         heap_access_oob: Label,
@@ -3493,6 +3671,7 @@ impl Machine for MachineARM64 {
             false,
             4,
             need_check,
+            memory64,
             imported_memories,
             offset,
             heap_access_oob,
@@ -3506,6 +3685,7 @@ impl Machine for MachineARM64 {
         memarg: &MemArg,
         target_addr: Location,
         need_check: bool,
+        memory64: bool,
         imported_memories: bool,
         offset: i32,
         heap_access_oob: Label,
@@ -3517,6 +3697,7 @@ impl Machine for MachineARM64 {
             false,
             4,
             need_check,
+            memory64,
             imported_memories,
             offset,
             heap_access_oob,
@@ -3530,6 +3711,7 @@ impl Machine for MachineARM64 {
         memarg: &MemArg,
         target_addr: Location,
         need_check: bool,
+        memory64: bool,
         imported_memories: bool,
         offset: i32,
         heap_access_oob: Label,
@@ -3541,6 +3723,7 @@ impl Machine for MachineARM64 {
             true,
             4,
             need_check,
+            memory64,
             imported_memories,
             offset,
             heap_access_oob,
@@ -3555,6 +3738,7 @@ impl Machine for MachineARM64 {
         memarg: &MemArg,
         target_addr: Location,
         need_check: bool,
+        memory64: bool,
         imported_memories: bool,
         offset: i32,
         heap_access_oob: Label,
@@ -3566,6 +3750,7 @@ impl Machine for MachineARM64 {
             true,
             1,
             need_check,
+            memory64,
             imported_memories,
             offset,
             heap_access_oob,
@@ -3580,6 +3765,7 @@ impl Machine for MachineARM64 {
         memarg: &MemArg,
         target_addr: Location,
         need_check: bool,
+        memory64: bool,
         imported_memories: bool,
         offset: i32,
         heap_access_oob: Label,
@@ -3591,6 +3777,7 @@ impl Machine for MachineARM64 {
             true,
             2,
             need_check,
+            memory64,
             imported_memories,
             offset,
             heap_access_oob,
@@ -3607,6 +3794,7 @@ impl Machine for MachineARM64 {
         memarg: &MemArg,
         ret: Location,
         need_check: bool,
+        memory64: bool,
         imported_memories: bool,
         offset: i32,
         heap_access_oob: Label,
@@ -3618,6 +3806,7 @@ impl Machine for MachineARM64 {
             true,
             4,
             need_check,
+            memory64,
             imported_memories,
             offset,
             heap_access_oob,
@@ -3668,6 +3857,7 @@ impl Machine for MachineARM64 {
         memarg: &MemArg,
         ret: Location,
         need_check: bool,
+        memory64: bool,
         imported_memories: bool,
         offset: i32,
         heap_access_oob: Label,
@@ -3679,6 +3869,7 @@ impl Machine for MachineARM64 {
             true,
             1,
             need_check,
+            memory64,
             imported_memories,
             offset,
             heap_access_oob,
@@ -3729,6 +3920,7 @@ impl Machine for MachineARM64 {
         memarg: &MemArg,
         ret: Location,
         need_check: bool,
+        memory64: bool,
         imported_memories: bool,
         offset: i32,
         heap_access_oob: Label,
@@ -3740,6 +3932,7 @@ impl Machine for MachineARM64 {
             true,
             2,
             need_check,
+            memory64,
             imported_memories,
             offset,
             heap_access_oob,
@@ -3790,6 +3983,7 @@ impl Machine for MachineARM64 {
         memarg: &MemArg,
         ret: Location,
         need_check: bool,
+        memory64: bool,
         imported_memories: bool,
         offset: i32,
         heap_access_oob: Label,
@@ -3801,6 +3995,7 @@ impl Machine for MachineARM64 {
             true,
             4,
             need_check,
+            memory64,
             imported_memories,
             offset,
             heap_access_oob,
@@ -3851,6 +4046,7 @@ impl Machine for MachineARM64 {
         memarg: &MemArg,
         ret: Location,
         need_check: bool,
+        memory64: bool,
         imported_memories: bool,
         offset: i32,
         heap_access_oob: Label,
@@ -3862,6 +4058,7 @@ impl Machine for MachineARM64 {
             true,
             1,
             need_check,
+            memory64,
             imported_memories,
             offset,
             heap_access_oob,
@@ -3912,6 +4109,7 @@ impl Machine for MachineARM64 {
         memarg: &MemArg,
         ret: Location,
         need_check: bool,
+        memory64: bool,
         imported_memories: bool,
         offset: i32,
         heap_access_oob: Label,
@@ -3923,6 +4121,7 @@ impl Machine for MachineARM64 {
             true,
             2,
             need_check,
+            memory64,
             imported_memories,
             offset,
             heap_access_oob,
@@ -3973,6 +4172,7 @@ impl Machine for MachineARM64 {
         memarg: &MemArg,
         ret: Location,
         need_check: bool,
+        memory64: bool,
         imported_memories: bool,
         offset: i32,
         heap_access_oob: Label,
@@ -3984,6 +4184,7 @@ impl Machine for MachineARM64 {
             true,
             4,
             need_check,
+            memory64,
             imported_memories,
             offset,
             heap_access_oob,
@@ -4034,6 +4235,7 @@ impl Machine for MachineARM64 {
         memarg: &MemArg,
         ret: Location,
         need_check: bool,
+        memory64: bool,
         imported_memories: bool,
         offset: i32,
         heap_access_oob: Label,
@@ -4045,6 +4247,7 @@ impl Machine for MachineARM64 {
             true,
             1,
             need_check,
+            memory64,
             imported_memories,
             offset,
             heap_access_oob,
@@ -4095,6 +4298,7 @@ impl Machine for MachineARM64 {
         memarg: &MemArg,
         ret: Location,
         need_check: bool,
+        memory64: bool,
         imported_memories: bool,
         offset: i32,
         heap_access_oob: Label,
@@ -4106,6 +4310,7 @@ impl Machine for MachineARM64 {
             true,
             2,
             need_check,
+            memory64,
             imported_memories,
             offset,
             heap_access_oob,
@@ -4156,6 +4361,7 @@ impl Machine for MachineARM64 {
         memarg: &MemArg,
         ret: Location,
         need_check: bool,
+        memory64: bool,
         imported_memories: bool,
         offset: i32,
         heap_access_oob: Label,
@@ -4167,6 +4373,7 @@ impl Machine for MachineARM64 {
             true,
             4,
             need_check,
+            memory64,
             imported_memories,
             offset,
             heap_access_oob,
@@ -4217,6 +4424,7 @@ impl Machine for MachineARM64 {
         memarg: &MemArg,
         ret: Location,
         need_check: bool,
+        memory64: bool,
         imported_memories: bool,
         offset: i32,
         heap_access_oob: Label,
@@ -4228,6 +4436,7 @@ impl Machine for MachineARM64 {
             true,
             1,
             need_check,
+            memory64,
             imported_memories,
             offset,
             heap_access_oob,
@@ -4278,6 +4487,7 @@ impl Machine for MachineARM64 {
         memarg: &MemArg,
         ret: Location,
         need_check: bool,
+        memory64: bool,
         imported_memories: bool,
         offset: i32,
         heap_access_oob: Label,
@@ -4289,6 +4499,7 @@ impl Machine for MachineARM64 {
             true,
             2,
             need_check,
+            memory64,
             imported_memories,
             offset,
             heap_access_oob,
@@ -4339,6 +4550,7 @@ impl Machine for MachineARM64 {
         memarg: &MemArg,
         ret: Location,
         need_check: bool,
+        memory64: bool,
         imported_memories: bool,
         offset: i32,
         heap_access_oob: Label,
@@ -4350,6 +4562,7 @@ impl Machine for MachineARM64 {
             true,
             4,
             need_check,
+            memory64,
             imported_memories,
             offset,
             heap_access_oob,
@@ -4400,6 +4613,7 @@ impl Machine for MachineARM64 {
         memarg: &MemArg,
         ret: Location,
         need_check: bool,
+        memory64: bool,
         imported_memories: bool,
         offset: i32,
         heap_access_oob: Label,
@@ -4411,6 +4625,7 @@ impl Machine for MachineARM64 {
             true,
             1,
             need_check,
+            memory64,
             imported_memories,
             offset,
             heap_access_oob,
@@ -4461,6 +4676,7 @@ impl Machine for MachineARM64 {
         memarg: &MemArg,
         ret: Location,
         need_check: bool,
+        memory64: bool,
         imported_memories: bool,
         offset: i32,
         heap_access_oob: Label,
@@ -4472,6 +4688,7 @@ impl Machine for MachineARM64 {
             true,
             2,
             need_check,
+            memory64,
             imported_memories,
             offset,
             heap_access_oob,
@@ -4522,6 +4739,7 @@ impl Machine for MachineARM64 {
         memarg: &MemArg,
         ret: Location,
         need_check: bool,
+        memory64: bool,
         imported_memories: bool,
         offset: i32,
         heap_access_oob: Label,
@@ -4533,6 +4751,7 @@ impl Machine for MachineARM64 {
             true,
             4,
             need_check,
+            memory64,
             imported_memories,
             offset,
             heap_access_oob,
@@ -4580,6 +4799,7 @@ impl Machine for MachineARM64 {
         memarg: &MemArg,
         ret: Location,
         need_check: bool,
+        memory64: bool,
         imported_memories: bool,
         offset: i32,
         heap_access_oob: Label,
@@ -4591,6 +4811,7 @@ impl Machine for MachineARM64 {
             true,
             1,
             need_check,
+            memory64,
             imported_memories,
             offset,
             heap_access_oob,
@@ -4638,6 +4859,7 @@ impl Machine for MachineARM64 {
         memarg: &MemArg,
         ret: Location,
         need_check: bool,
+        memory64: bool,
         imported_memories: bool,
         offset: i32,
         heap_access_oob: Label,
@@ -4649,6 +4871,7 @@ impl Machine for MachineARM64 {
             true,
             2,
             need_check,
+            memory64,
             imported_memories,
             offset,
             heap_access_oob,
@@ -4697,6 +4920,7 @@ impl Machine for MachineARM64 {
         memarg: &MemArg,
         ret: Location,
         need_check: bool,
+        memory64: bool,
         imported_memories: bool,
         offset: i32,
         heap_access_oob: Label,
@@ -4708,6 +4932,7 @@ impl Machine for MachineARM64 {
             true,
             4,
             need_check,
+            memory64,
             imported_memories,
             offset,
             heap_access_oob,
@@ -4760,6 +4985,7 @@ impl Machine for MachineARM64 {
         memarg: &MemArg,
         ret: Location,
         need_check: bool,
+        memory64: bool,
         imported_memories: bool,
         offset: i32,
         heap_access_oob: Label,
@@ -4771,6 +4997,7 @@ impl Machine for MachineARM64 {
             true,
             1,
             need_check,
+            memory64,
             imported_memories,
             offset,
             heap_access_oob,
@@ -4823,6 +5050,7 @@ impl Machine for MachineARM64 {
         memarg: &MemArg,
         ret: Location,
         need_check: bool,
+        memory64: bool,
         imported_memories: bool,
         offset: i32,
         heap_access_oob: Label,
@@ -4834,6 +5062,7 @@ impl Machine for MachineARM64 {
             true,
             2,
             need_check,
+            memory64,
             imported_memories,
             offset,
             heap_access_oob,
@@ -5414,6 +5643,7 @@ impl Machine for MachineARM64 {
         memarg: &MemArg,
         ret: Location,
         need_check: bool,
+        memory64: bool,
         imported_memories: bool,
         offset: i32,
         heap_access_oob: Label,
@@ -5425,6 +5655,7 @@ impl Machine for MachineARM64 {
             false,
             8,
             need_check,
+            memory64,
             imported_memories,
             offset,
             heap_access_oob,
@@ -5438,6 +5669,7 @@ impl Machine for MachineARM64 {
         memarg: &MemArg,
         ret: Location,
         need_check: bool,
+        memory64: bool,
         imported_memories: bool,
         offset: i32,
         heap_access_oob: Label,
@@ -5449,6 +5681,7 @@ impl Machine for MachineARM64 {
             false,
             1,
             need_check,
+            memory64,
             imported_memories,
             offset,
             heap_access_oob,
@@ -5462,6 +5695,7 @@ impl Machine for MachineARM64 {
         memarg: &MemArg,
         ret: Location,
         need_check: bool,
+        memory64: bool,
         imported_memories: bool,
         offset: i32,
         heap_access_oob: Label,
@@ -5473,6 +5707,7 @@ impl Machine for MachineARM64 {
             false,
             1,
             need_check,
+            memory64,
             imported_memories,
             offset,
             heap_access_oob,
@@ -5486,6 +5721,7 @@ impl Machine for MachineARM64 {
         memarg: &MemArg,
         ret: Location,
         need_check: bool,
+        memory64: bool,
         imported_memories: bool,
         offset: i32,
         heap_access_oob: Label,
@@ -5497,6 +5733,7 @@ impl Machine for MachineARM64 {
             false,
             2,
             need_check,
+            memory64,
             imported_memories,
             offset,
             heap_access_oob,
@@ -5510,6 +5747,7 @@ impl Machine for MachineARM64 {
         memarg: &MemArg,
         ret: Location,
         need_check: bool,
+        memory64: bool,
         imported_memories: bool,
         offset: i32,
         heap_access_oob: Label,
@@ -5521,6 +5759,7 @@ impl Machine for MachineARM64 {
             false,
             2,
             need_check,
+            memory64,
             imported_memories,
             offset,
             heap_access_oob,
@@ -5534,6 +5773,7 @@ impl Machine for MachineARM64 {
         memarg: &MemArg,
         ret: Location,
         need_check: bool,
+        memory64: bool,
         imported_memories: bool,
         offset: i32,
         heap_access_oob: Label,
@@ -5545,6 +5785,7 @@ impl Machine for MachineARM64 {
             false,
             4,
             need_check,
+            memory64,
             imported_memories,
             offset,
             heap_access_oob,
@@ -5558,6 +5799,7 @@ impl Machine for MachineARM64 {
         memarg: &MemArg,
         ret: Location,
         need_check: bool,
+        memory64: bool,
         imported_memories: bool,
         offset: i32,
         heap_access_oob: Label,
@@ -5569,6 +5811,7 @@ impl Machine for MachineARM64 {
             false,
             4,
             need_check,
+            memory64,
             imported_memories,
             offset,
             heap_access_oob,
@@ -5582,6 +5825,7 @@ impl Machine for MachineARM64 {
         memarg: &MemArg,
         ret: Location,
         need_check: bool,
+        memory64: bool,
         imported_memories: bool,
         offset: i32,
         heap_access_oob: Label,
@@ -5593,6 +5837,7 @@ impl Machine for MachineARM64 {
             true,
             8,
             need_check,
+            memory64,
             imported_memories,
             offset,
             heap_access_oob,
@@ -5606,6 +5851,7 @@ impl Machine for MachineARM64 {
         memarg: &MemArg,
         ret: Location,
         need_check: bool,
+        memory64: bool,
         imported_memories: bool,
         offset: i32,
         heap_access_oob: Label,
@@ -5617,6 +5863,7 @@ impl Machine for MachineARM64 {
             true,
             1,
             need_check,
+            memory64,
             imported_memories,
             offset,
             heap_access_oob,
@@ -5630,6 +5877,7 @@ impl Machine for MachineARM64 {
         memarg: &MemArg,
         ret: Location,
         need_check: bool,
+        memory64: bool,
         imported_memories: bool,
         offset: i32,
         heap_access_oob: Label,
@@ -5641,6 +5889,7 @@ impl Machine for MachineARM64 {
             true,
             2,
             need_check,
+            memory64,
             imported_memories,
             offset,
             heap_access_oob,
@@ -5654,6 +5903,7 @@ impl Machine for MachineARM64 {
         memarg: &MemArg,
         ret: Location,
         need_check: bool,
+        memory64: bool,
         imported_memories: bool,
         offset: i32,
         heap_access_oob: Label,
@@ -5665,6 +5915,7 @@ impl Machine for MachineARM64 {
             true,
             4,
             need_check,
+            memory64,
             imported_memories,
             offset,
             heap_access_oob,
@@ -5678,6 +5929,7 @@ impl Machine for MachineARM64 {
         memarg: &MemArg,
         target_addr: Location,
         need_check: bool,
+        memory64: bool,
         imported_memories: bool,
         offset: i32,
         heap_access_oob: Label,
@@ -5689,6 +5941,7 @@ impl Machine for MachineARM64 {
             false,
             8,
             need_check,
+            memory64,
             imported_memories,
             offset,
             heap_access_oob,
@@ -5702,6 +5955,7 @@ impl Machine for MachineARM64 {
         memarg: &MemArg,
         target_addr: Location,
         need_check: bool,
+        memory64: bool,
         imported_memories: bool,
         offset: i32,
         heap_access_oob: Label,
@@ -5713,6 +5967,7 @@ impl Machine for MachineARM64 {
             false,
             1,
             need_check,
+            memory64,
             imported_memories,
             offset,
             heap_access_oob,
@@ -5726,6 +5981,7 @@ impl Machine for MachineARM64 {
         memarg: &MemArg,
         target_addr: Location,
         need_check: bool,
+        memory64: bool,
         imported_memories: bool,
         offset: i32,
         heap_access_oob: Label,
@@ -5737,6 +5993,7 @@ impl Machine for MachineARM64 {
             false,
             2,
             need_check,
+            memory64,
             imported_memories,
             offset,
             heap_access_oob,
@@ -5750,6 +6007,7 @@ impl Machine for MachineARM64 {
         memarg: &MemArg,
         target_addr: Location,
         need_check: bool,
+        memory64: bool,
         imported_memories: bool,
         offset: i32,
         heap_access_oob: Label,
@@ -5761,6 +6019,7 @@ impl Machine for MachineARM64 {
             false,
             4,
             need_check,
+            memory64,
             imported_memories,
             offset,
             heap_access_oob,
@@ -5774,6 +6033,7 @@ impl Machine for MachineARM64 {
         memarg: &MemArg,
         target_addr: Location,
         need_check: bool,
+        memory64: bool,
         imported_memories: bool,
         offset: i32,
         heap_access_oob: Label,
@@ -5785,6 +6045,7 @@ impl Machine for MachineARM64 {
             true,
             8,
             need_check,
+            memory64,
             imported_memories,
             offset,
             heap_access_oob,
@@ -5799,6 +6060,7 @@ impl Machine for MachineARM64 {
         memarg: &MemArg,
         target_addr: Location,
         need_check: bool,
+        memory64: bool,
         imported_memories: bool,
         offset: i32,
         heap_access_oob: Label,
@@ -5810,6 +6072,7 @@ impl Machine for MachineARM64 {
             true,
             1,
             need_check,
+            memory64,
             imported_memories,
             offset,
             heap_access_oob,
@@ -5824,6 +6087,7 @@ impl Machine for MachineARM64 {
         memarg: &MemArg,
         target_addr: Location,
         need_check: bool,
+        memory64: bool,
         imported_memories: bool,
         offset: i32,
         heap_access_oob: Label,
@@ -5835,6 +6099,7 @@ impl Machine for MachineARM64 {
             true,
             2,
             need_check,
+            memory64,
             imported_memories,
             offset,
             heap_access_oob,
@@ -5849,6 +6114,7 @@ impl Machine for MachineARM64 {
         memarg: &MemArg,
         target_addr: Location,
         need_check: bool,
+        memory64: bool,
         imported_memories: bool,
         offset: i32,
         heap_access_oob: Label,
@@ -5860,6 +6126,7 @@ impl Machine for MachineARM64 {
             true,
             4,
             need_check,
+            memory64,
             imported_memories,
             offset,
             heap_access_oob,
@@ -5876,6 +6143,7 @@ impl Machine for MachineARM64 {
         memarg: &MemArg,
         ret: Location,
         need_check: bool,
+        memory64: bool,
         imported_memories: bool,
         offset: i32,
         heap_access_oob: Label,
@@ -5887,6 +6155,7 @@ impl Machine for MachineARM64 {
             true,
             8,
             need_check,
+            memory64,
             imported_memories,
             offset,
             heap_access_oob,
@@ -5937,6 +6206,7 @@ impl Machine for MachineARM64 {
         memarg: &MemArg,
         ret: Location,
         need_check: bool,
+        memory64: bool,
         imported_memories: bool,
         offset: i32,
         heap_access_oob: Label,
@@ -5948,6 +6218,7 @@ impl Machine for MachineARM64 {
             true,
             1,
             need_check,
+            memory64,
             imported_memories,
             offset,
             heap_access_oob,
@@ -5998,6 +6269,7 @@ impl Machine for MachineARM64 {
         memarg: &MemArg,
         ret: Location,
         need_check: bool,
+        memory64: bool,
         imported_memories: bool,
         offset: i32,
         heap_access_oob: Label,
@@ -6009,6 +6281,7 @@ impl Machine for MachineARM64 {
             true,
             2,
             need_check,
+            memory64,
             imported_memories,
             offset,
             heap_access_oob,
@@ -6059,6 +6332,7 @@ impl Machine for MachineARM64 {
         memarg: &MemArg,
         ret: Location,
         need_check: bool,
+        memory64: bool,
         imported_memories: bool,
         offset: i32,
         heap_access_oob: Label,
@@ -6070,6 +6344,7 @@ impl Machine for MachineARM64 {
             true,
             4,
             need_check,
+            memory64,
             imported_memories,
             offset,
             heap_access_oob,
@@ -6120,6 +6395,7 @@ impl Machine for MachineARM64 {
         memarg: &MemArg,
         ret: Location,
         need_check: bool,
+        memory64: bool,
         imported_memories: bool,
         offset: i32,
         heap_access_oob: Label,
@@ -6131,6 +6407,7 @@ impl Machine for MachineARM64 {
             true,
             8,
             need_check,
+            memory64,
             imported_memories,
             offset,
             heap_access_oob,
@@ -6181,6 +6458,7 @@ impl Machine for MachineARM64 {
         memarg: &MemArg,
         ret: Location,
         need_check: bool,
+        memory64: bool,
         imported_memories: bool,
         offset: i32,
         heap_access_oob: Label,
@@ -6192,6 +6470,7 @@ impl Machine for MachineARM64 {
             true,
             1,
             need_check,
+            memory64,
             imported_memories,
             offset,
             heap_access_oob,
@@ -6242,6 +6521,7 @@ impl Machine for MachineARM64 {
         memarg: &MemArg,
         ret: Location,
         need_check: bool,
+        memory64: bool,
         imported_memories: bool,
         offset: i32,
         heap_access_oob: Label,
@@ -6253,6 +6533,7 @@ impl Machine for MachineARM64 {
             true,
             2,
             need_check,
+            memory64,
             imported_memories,
             offset,
             heap_access_oob,
@@ -6303,6 +6584,7 @@ impl Machine for MachineARM64 {
         memarg: &MemArg,
         ret: Location,
         need_check: bool,
+        memory64: bool,
         imported_memories: bool,
         offset: i32,
         heap_access_oob: Label,
@@ -6314,6 +6596,7 @@ impl Machine for MachineARM64 {
             true,
             4,
             need_check,
+            memory64,
             imported_memories,
             offset,
             heap_access_oob,
@@ -6364,6 +6647,7 @@ impl Machine for MachineARM64 {
         memarg: &MemArg,
         ret: Location,
         need_check: bool,
+        memory64: bool,
         imported_memories: bool,
         offset: i32,
         heap_access_oob: Label,
@@ -6375,6 +6659,7 @@ impl Machine for MachineARM64 {
             true,
             8,
             need_check,
+            memory64,
             imported_memories,
             offset,
             heap_access_oob,
@@ -6425,6 +6710,7 @@ impl Machine for MachineARM64 {
         memarg: &MemArg,
         ret: Location,
         need_check: bool,
+        memory64: bool,
         imported_memories: bool,
         offset: i32,
         heap_access_oob: Label,
@@ -6436,6 +6722,7 @@ impl Machine for MachineARM64 {
             true,
             1,
             need_check,
+            memory64,
             imported_memories,
             offset,
             heap_access_oob,
@@ -6486,6 +6773,7 @@ impl Machine for MachineARM64 {
         memarg: &MemArg,
         ret: Location,
         need_check: bool,
+        memory64: bool,
         imported_memories: bool,
         offset: i32,
         heap_access_oob: Label,
@@ -6497,6 +6785,7 @@ impl Machine for MachineARM64 {
             true,
             2,
             need_check,
+            memory64,
             imported_memories,
             offset,
             heap_access_oob,
@@ -6547,6 +6836,7 @@ impl Machine for MachineARM64 {
         memarg: &MemArg,
         ret: Location,
         need_check: bool,
+        memory64: bool,
         imported_memories: bool,
         offset: i32,
         heap_access_oob: Label,
@@ -6558,6 +6848,7 @@ impl Machine for MachineARM64 {
             true,
             4,
             need_check,
+            memory64,
             imported_memories,
             offset,
             heap_access_oob,
@@ -6608,6 +6899,7 @@ impl Machine for MachineARM64 {
         memarg: &MemArg,
         ret: Location,
         need_check: bool,
+        memory64: bool,
         imported_memories: bool,
         offset: i32,
         heap_access_oob: Label,
@@ -6619,6 +6911,7 @@ impl Machine for MachineARM64 {
             true,
             8,
             need_check,
+            memory64,
             imported_memories,
             offset,
             heap_access_oob,
@@ -6669,6 +6962,7 @@ impl Machine for MachineARM64 {
         memarg: &MemArg,
         ret: Location,
         need_check: bool,
+        memory64: bool,
         imported_memories: bool,
         offset: i32,
         heap_access_oob: Label,
@@ -6680,6 +6974,7 @@ impl Machine for MachineARM64 {
             true,
             1,
             need_check,
+            memory64,
             imported_memories,
             offset,
             heap_access_oob,
@@ -6730,6 +7025,7 @@ impl Machine for MachineARM64 {
         memarg: &MemArg,
         ret: Location,
         need_check: bool,
+        memory64: bool,
         imported_memories: bool,
         offset: i32,
         heap_access_oob: Label,
@@ -6741,6 +7037,7 @@ impl Machine for MachineARM64 {
             true,
             2,
             need_check,
+            memory64,
             imported_memories,
             offset,
             heap_access_oob,
@@ -6791,6 +7088,7 @@ impl Machine for MachineARM64 {
         memarg: &MemArg,
         ret: Location,
         need_check: bool,
+        memory64: bool,
         imported_memories: bool,
         offset: i32,
         heap_access_oob: Label,
@@ -6802,6 +7100,7 @@ impl Machine for MachineARM64 {
             true,
             4,
             need_check,
+            memory64,
             imported_memories,
             offset,
             heap_access_oob,
@@ -6852,6 +7151,7 @@ impl Machine for MachineARM64 {
         memarg: &MemArg,
         ret: Location,
         need_check: bool,
+        memory64: bool,
         imported_memories: bool,
         offset: i32,
         heap_access_oob: Label,
@@ -6863,6 +7163,7 @@ impl Machine for MachineARM64 {
             true,
             8,
             need_check,
+            memory64,
             imported_memories,
             offset,
             heap_access_oob,
@@ -6913,6 +7214,7 @@ impl Machine for MachineARM64 {
         memarg: &MemArg,
         ret: Location,
         need_check: bool,
+        memory64: bool,
         imported_memories: bool,
         offset: i32,
         heap_access_oob: Label,
@@ -6924,6 +7226,7 @@ impl Machine for MachineARM64 {
             true,
             1,
             need_check,
+            memory64,
             imported_memories,
             offset,
             heap_access_oob,
@@ -6974,6 +7277,7 @@ impl Machine for MachineARM64 {
         memarg: &MemArg,
         ret: Location,
         need_check: bool,
+        memory64: bool,
         imported_memories: bool,
         offset: i32,
         heap_access_oob: Label,
@@ -6985,6 +7289,7 @@ impl Machine for MachineARM64 {
             true,
             2,
             need_check,
+            memory64,
             imported_memories,
             offset,
             heap_access_oob,
@@ -7035,6 +7340,7 @@ impl Machine for MachineARM64 {
         memarg: &MemArg,
         ret: Location,
         need_check: bool,
+        memory64: bool,
         imported_memories: bool,
         offset: i32,
         heap_access_oob: Label,
@@ -7046,6 +7352,7 @@ impl Machine for MachineARM64 {
             true,
             4,
             need_check,
+            memory64,
             imported_memories,
             offset,
             heap_access_oob,
@@ -7096,6 +7403,7 @@ impl Machine for MachineARM64 {
         memarg: &MemArg,
         ret: Location,
         need_check: bool,
+        memory64: bool,
         imported_memories: bool,
         offset: i32,
         heap_access_oob: Label,
@@ -7107,6 +7415,7 @@ impl Machine for MachineARM64 {
             true,
             8,
             need_check,
+            memory64,
             imported_memories,
             offset,
             heap_access_oob,
@@ -7154,6 +7463,7 @@ impl Machine for MachineARM64 {
         memarg: &MemArg,
         ret: Location,
         need_check: bool,
+        memory64: bool,
         imported_memories: bool,
         offset: i32,
         heap_access_oob: Label,
@@ -7165,6 +7475,7 @@ impl Machine for MachineARM64 {
             true,
             1,
             need_check,
+            memory64,
             imported_memories,
             offset,
             heap_access_oob,
@@ -7212,6 +7523,7 @@ impl Machine for MachineARM64 {
         memarg: &MemArg,
         ret: Location,
         need_check: bool,
+        memory64: bool,
         imported_memories: bool,
         offset: i32,
         heap_access_oob: Label,
@@ -7223,6 +7535,7 @@ impl Machine for MachineARM64 {
             true,
             2,
             need_check,
+            memory64,
             imported_memories,
             offset,
             heap_access_oob,
@@ -7270,6 +7583,7 @@ impl Machine for MachineARM64 {
         memarg: &MemArg,
         ret: Location,
         need_check: bool,
+        memory64: bool,
         imported_memories: bool,
         offset: i32,
         heap_access_oob: Label,
@@ -7281,6 +7595,7 @@ impl Machine for MachineARM64 {
             true,
             4,
             need_check,
+            memory64,
             imported_memories,
             offset,
             heap_access_oob,
@@ -7329,6 +7644,7 @@ impl Machine for MachineARM64 {
         memarg: &MemArg,
         ret: Location,
         need_check: bool,
+        memory64: bool,
         imported_memories: bool,
         offset: i32,
         heap_access_oob: Label,
@@ -7340,6 +7656,7 @@ impl Machine for MachineARM64 {
             true,
             8,
             need_check,
+            memory64,
             imported_memories,
             offset,
             heap_access_oob,
@@ -7392,6 +7709,7 @@ impl Machine for MachineARM64 {
         memarg: &MemArg,
         ret: Location,
         need_check: bool,
+        memory64: bool,
         imported_memories: bool,
         offset: i32,
         heap_access_oob: Label,
@@ -7403,6 +7721,7 @@ impl Machine for MachineARM64 {
             true,
             1,
             need_check,
+            memory64,
             imported_memories,
             offset,
             heap_access_oob,
@@ -7455,6 +7774,7 @@ impl Machine for MachineARM64 {
         memarg: &MemArg,
         ret: Location,
         need_check: bool,
+        memory64: bool,
         imported_memories: bool,
         offset: i32,
         heap_access_oob: Label,
@@ -7466,6 +7786,7 @@ impl Machine for MachineARM64 {
             true,
             2,
             need_check,
+            memory64,
             imported_memories,
             offset,
             heap_access_oob,
@@ -7518,6 +7839,7 @@ impl Machine for MachineARM64 {
         memarg: &MemArg,
         ret: Location,
         need_check: bool,
+        memory64: bool,
         imported_memories: bool,
         offset: i32,
         heap_access_oob: Label,
@@ -7529,6 +7851,7 @@ impl Machine for MachineARM64 {
             true,
             4,
             need_check,
+            memory64,
             imported_memories,
             offset,
             heap_access_oob,
@@ -7579,6 +7902,7 @@ impl Machine for MachineARM64 {
         memarg: &MemArg,
         ret: Location,
         need_check: bool,
+        memory64: bool,
         imported_memories: bool,
         offset: i32,
         heap_access_oob: Label,
@@ -7590,6 +7914,7 @@ impl Machine for MachineARM64 {
             false,
             4,
             need_check,
+            memory64,
             imported_memories,
             offset,
             heap_access_oob,
@@ -7604,6 +7929,7 @@ impl Machine for MachineARM64 {
         target_addr: Location,
         canonicalize: bool,
         need_check: bool,
+        memory64: bool,
         imported_memories: bool,
         offset: i32,
         heap_access_oob: Label,
@@ -7616,6 +7942,7 @@ impl Machine for MachineARM64 {
             false,
             4,
             need_check,
+            memory64,
             imported_memories,
             offset,
             heap_access_oob,
@@ -7635,6 +7962,7 @@ impl Machine for MachineARM64 {
         memarg: &MemArg,
         ret: Location,
         need_check: bool,
+        memory64: bool,
         imported_memories: bool,
         offset: i32,
         heap_access_oob: Label,
@@ -7646,6 +7974,7 @@ impl Machine for MachineARM64 {
             false,
             8,
             need_check,
+            memory64,
             imported_memories,
             offset,
             heap_access_oob,
@@ -7660,6 +7989,7 @@ impl Machine for MachineARM64 {
         target_addr: Location,
         canonicalize: bool,
         need_check: bool,
+        memory64: bool,
         imported_memories: bool,
         offset: i32,
         heap_access_oob: Label,
@@ -7672,6 +8002,7 @@ impl Machine for MachineARM64 {
             false,
             8,
             need_check,
+            memory64,
             imported_memories,
             offset,
             heap_access_oob,
@@ -8483,6 +8814,735 @@ impl Machine for MachineARM64 {
         )
     }
 
+    fn emit_simd(
+        &mut self,
+        op: &Operator,
+        args: &[Location],
+        ret: &[Location],
+    ) -> Result<(), CompileError> {
+        let [x, y, z] = self.acquire_simd_temps()?;
+        // Load the first two v128 operands, the others are read by each operator.
+        if !matches!(*op, Operator::I64x2Mul) {
+            if args.len() >= 2 {
+                self.simd_gather(args[0], args[1], x)?;
+            }
+            if args.len() >= 4 {
+                self.simd_gather(args[2], args[3], y)?;
+            }
+        }
+        let result = match *op {
+            Operator::I8x16Splat
+            | Operator::I16x8Splat
+            | Operator::I32x4Splat
+            | Operator::I64x2Splat
+            | Operator::F32x4Splat
+            | Operator::F64x2Splat => {
+                let (sz, lanes) = match *op {
+                    Operator::I8x16Splat => (Size::S32, Lanes::B16),
+                    Operator::I16x8Splat => (Size::S32, Lanes::H8),
+                    Operator::I32x4Splat | Operator::F32x4Splat => (Size::S32, Lanes::S4),
+                    _ => (Size::S64, Lanes::D2),
+                };
+                let tmp = self.simd_scalar(sz, args[0])?;
+                self.assembler.emit_neon_dup(lanes, tmp, x)?;
+                self.release_gpr(tmp);
+                Some(x)
+            }
+
+            Operator::I8x16ExtractLaneS { lane }
+            | Operator::I8x16ExtractLaneU { lane }
+            | Operator::I16x8ExtractLaneS { lane }
+            | Operator::I16x8ExtractLaneU { lane }
+            | Operator::I32x4ExtractLane { lane }
+            | Operator::I64x2ExtractLane { lane }
+            | Operator::F32x4ExtractLane { lane }
+            | Operator::F64x2ExtractLane { lane } => {
+                let (lanes, signed) = match *op {
+                    Operator::I8x16ExtractLaneS { .. } => (Lanes::B16, true),
+                    Operator::I8x16ExtractLaneU { .. } => (Lanes::B16, false),
+                    Operator::I16x8ExtractLaneS { .. } => (Lanes::H8, true),
+                    Operator::I16x8ExtractLaneU { .. } => (Lanes::H8, false),
+                    Operator::I32x4ExtractLane { .. } | Operator::F32x4ExtractLane { .. } => {
+                        (Lanes::S4, false)
+                    }
+                    _ => (Lanes::D2, false),
+                };
+                let tmp = self.acquire_temp_gpr().ok_or_else(|| {
+                    CompileError::Codegen("singlepass cannot acquire temp gpr".to_owned())
+                })?;
+                self.assembler
+                    .emit_neon_umov(lanes, signed, x, lane as u32, tmp)?;
+                let ret_sz = if lanes == Lanes::D2 {
+                    Size::S64
+                } else {
+                    Size::S32
+                };
+                self.move_location(ret_sz, Location::GPR(tmp), ret[0])?;
+                self.release_gpr(tmp);
+                None
+            }
+            Operator::I8x16ReplaceLane { lane }
+            | Operator::I16x8ReplaceLane { lane }
+            | Operator::I32x4ReplaceLane { lane }
+            | Operator::I64x2ReplaceLane { lane }
+            | Operator::F32x4ReplaceLane { lane }
+            | Operator::F64x2ReplaceLane { lane } => {
+                let (sz, lanes) = match *op {
+                    Operator::I8x16ReplaceLane { .. } => (Size::S32, Lanes::B16),
+                    Operator::I16x8ReplaceLane { .. } => (Size::S32, Lanes::H8),
+                    Operator::I32x4ReplaceLane { .. } | Operator::F32x4ReplaceLane { .. } => {
+                        (Size::S32, Lanes::S4)
+                    }
+                    _ => (Size::S64, Lanes::D2),
+                };
+                let tmp = self.simd_scalar(sz, args[2])?;
+                self.assembler.emit_neon_ins(lanes, tmp, lane as u32, x)?;
+                self.release_gpr(tmp);
+                Some(x)
+            }
+
+            Operator::V128AnyTrue
+            | Operator::I8x16AllTrue
+            | Operator::I16x8AllTrue
+            | Operator::I32x4AllTrue
+            | Operator::I64x2AllTrue => {
+                let condition = match *op {
+                    Operator::V128AnyTrue => Condition::Ne,
+                    _ => {
+                        let lanes = match *op {
+                            Operator::I8x16AllTrue => Lanes::B16,
+                            Operator::I16x8AllTrue => Lanes::H8,
+                            Operator::I32x4AllTrue => Lanes::S4,
+                            _ => Lanes::D2,
+                        };
+                        // Set the lanes that are zero, and check that there is none.
+                        self.assembler.emit_neon2(Neon2Op::CmeqZero, lanes, x, x)?;
+                        Condition::Eq
+                    }
+                };
+                let tmp = self.acquire_temp_gpr().ok_or_else(|| {
+                    CompileError::Codegen("singlepass cannot acquire temp gpr".to_owned())
+                })?;
+                self.assembler.emit_neon_umaxv(x, y)?;
+                self.assembler.emit_neon_umov(Lanes::S4, false, y, 0, tmp)?;
+                self.assembler
+                    .emit_cmp(Size::S32, Location::Imm32(0), Location::GPR(tmp))?;
+                self.assembler
+                    .emit_cset(Size::S32, Location::GPR(tmp), condition)?;
+                self.move_location(Size::S32, Location::GPR(tmp), ret[0])?;
+                self.release_gpr(tmp);
+                None
+            }
+            Operator::I8x16Bitmask
+            | Operator::I16x8Bitmask
+            | Operator::I32x4Bitmask
+            | Operator::I64x2Bitmask => {
+                // Set the negative lanes, keep a distinct bit of each and sum them.
+                let (lanes, bits) = match *op {
+                    Operator::I8x16Bitmask => (Lanes::B16, 8),
+                    Operator::I16x8Bitmask => (Lanes::H8, 16),
+                    Operator::I32x4Bitmask => (Lanes::S4, 32),
+                    _ => (Lanes::D2, 64),
+                };
+                let weights =
+                    (0..128 / bits).fold(0u128, |acc, i| acc | (1u128 << (i % 8)) << (i * bits));
+                self.assembler.emit_neon2(Neon2Op::CmltZero, lanes, x, x)?;
+                self.simd_const(weights, y)?;
+                self.assembler
+                    .emit_neon3(Neon3Op::And, Lanes::B16, x, y, x)?;
+                let sum_lanes = if let Operator::I8x16Bitmask = *op {
+                    // Interleave the bytes of both halves, so that each 16-bit
+                    // lane holds the bits of a byte from each.
+                    self.assembler.emit_neon_ext(x, x, 8, y)?;
+                    self.assembler
+                        .emit_neon3(Neon3Op::Zip1, Lanes::B16, x, y, x)?;
+                    Lanes::H8
+                } else {
+                    lanes
+                };
+                self.assembler.emit_neon_addv(sum_lanes, x, x)?;
+                let tmp = self.acquire_temp_gpr().ok_or_else(|| {
+                    CompileError::Codegen("singlepass cannot acquire temp gpr".to_owned())
+                })?;
+                self.assembler.emit_neon_umov(sum_lanes, false, x, 0, tmp)?;
+                self.move_location(Size::S32, Location::GPR(tmp), ret[0])?;
+                self.release_gpr(tmp);
+                None
+            }
+
+            Operator::I8x16Shl
+            | Operator::I8x16ShrS
+            | Operator::I8x16ShrU
+            | Operator::I16x8Shl
+            | Operator::I16x8ShrS
+            | Operator::I16x8ShrU
+            | Operator::I32x4Shl
+            | Operator::I32x4ShrS
+            | Operator::I32x4ShrU
+            | Operator::I64x2Shl
+            | Operator::I64x2ShrS
+            | Operator::I64x2ShrU => {
+                let (lanes, bits) = match *op {
+                    Operator::I8x16Shl | Operator::I8x16ShrS | Operator::I8x16ShrU => {
+                        (Lanes::B16, 8)
+                    }
+                    Operator::I16x8Shl | Operator::I16x8ShrS | Operator::I16x8ShrU => {
+                        (Lanes::H8, 16)
+                    }
+                    Operator::I32x4Shl | Operator::I32x4ShrS | Operator::I32x4ShrU => {
+                        (Lanes::S4, 32)
+                    }
+                    _ => (Lanes::D2, 64),
+                };
+                // The vector shifts go right by negative amounts.
+                let (shift, right) = match *op {
+                    Operator::I8x16Shl
+                    | Operator::I16x8Shl
+                    | Operator::I32x4Shl
+                    | Operator::I64x2Shl => (Neon3Op::Ushl, false),
+                    Operator::I8x16ShrU
+                    | Operator::I16x8ShrU
+                    | Operator::I32x4ShrU
+                    | Operator::I64x2ShrU => (Neon3Op::Ushl, true),
+                    _ => (Neon3Op::Sshl, true),
+                };
+                let tmp = self.simd_scalar(Size::S32, args[2])?;
+                self.assembler.emit_and(
+                    Size::S64,
+                    Location::GPR(tmp),
+                    Location::Imm64(bits - 1),
+                    Location::GPR(tmp),
+                )?;
+                self.assembler.emit_neon_dup(lanes, tmp, y)?;
+                self.release_gpr(tmp);
+                if right {
+                    self.assembler.emit_neon2(Neon2Op::Neg, lanes, y, y)?;
+                }
+                self.assembler.emit_neon3(shift, lanes, x, y, x)?;
+                Some(x)
+            }
+
+            Operator::V128Bitselect => {
+                self.simd_gather(args[4], args[5], z)?;
+                self.assembler
+                    .emit_neon3(Neon3Op::Bsl, Lanes::B16, x, y, z)?;
+                Some(z)
+            }
+            Operator::V128AndNot => {
+                self.assembler
+                    .emit_neon3(Neon3Op::Bic, Lanes::B16, x, y, x)?;
+                Some(x)
+            }
+            Operator::I8x16Ne
+            | Operator::I16x8Ne
+            | Operator::I32x4Ne
+            | Operator::I64x2Ne
+            | Operator::F32x4Ne
+            | Operator::F64x2Ne => {
+                let (eq, lanes) = match *op {
+                    Operator::I8x16Ne => (Neon3Op::Cmeq, Lanes::B16),
+                    Operator::I16x8Ne => (Neon3Op::Cmeq, Lanes::H8),
+                    Operator::I32x4Ne => (Neon3Op::Cmeq, Lanes::S4),
+                    Operator::I64x2Ne => (Neon3Op::Cmeq, Lanes::D2),
+                    Operator::F32x4Ne => (Neon3Op::Fcmeq, Lanes::S4),
+                    _ => (Neon3Op::Fcmeq, Lanes::D2),
+                };
+                self.assembler.emit_neon3(eq, lanes, x, y, x)?;
+                self.assembler.emit_neon2(Neon2Op::Not, Lanes::B16, x, x)?;
+                Some(x)
+            }
+            Operator::I8x16NarrowI16x8S
+            | Operator::I8x16NarrowI16x8U
+            | Operator::I16x8NarrowI32x4S
+            | Operator::I16x8NarrowI32x4U => {
+                let (low, high, lanes) = match *op {
+                    Operator::I8x16NarrowI16x8S => {
+                        (NeonWidthOp::Sqxtn, NeonWidthOp::Sqxtn2, Lanes::B16)
+                    }
+                    Operator::I8x16NarrowI16x8U => {
+                        (NeonWidthOp::Sqxtun, NeonWidthOp::Sqxtun2, Lanes::B16)
+                    }
+                    Operator::I16x8NarrowI32x4S => {
+                        (NeonWidthOp::Sqxtn, NeonWidthOp::Sqxtn2, Lanes::H8)
+                    }
+                    _ => (NeonWidthOp::Sqxtun, NeonWidthOp::Sqxtun2, Lanes::H8),
+                };
+                self.assembler.emit_neon_width(low, lanes, x, z)?;
+                self.assembler.emit_neon_width(high, lanes, y, z)?;
+                Some(z)
+            }
+            Operator::I16x8ExtendLowI8x16S
+            | Operator::I16x8ExtendHighI8x16S
+            | Operator::I16x8ExtendLowI8x16U
+            | Operator::I16x8ExtendHighI8x16U
+            | Operator::I32x4ExtendLowI16x8S
+            | Operator::I32x4ExtendHighI16x8S
+            | Operator::I32x4ExtendLowI16x8U
+            | Operator::I32x4ExtendHighI16x8U
+            | Operator::I64x2ExtendLowI32x4S
+            | Operator::I64x2ExtendHighI32x4S
+            | Operator::I64x2ExtendLowI32x4U
+            | Operator::I64x2ExtendHighI32x4U
+            | Operator::I16x8ExtAddPairwiseI8x16S
+            | Operator::I16x8ExtAddPairwiseI8x16U
+            | Operator::I32x4ExtAddPairwiseI16x8S
+            | Operator::I32x4ExtAddPairwiseI16x8U => {
+                let (extend, lanes) = match *op {
+                    Operator::I16x8ExtendLowI8x16S => (NeonWidthOp::Sxtl, Lanes::B16),
+                    Operator::I16x8ExtendHighI8x16S => (NeonWidthOp::Sxtl2, Lanes::B16),
+                    Operator::I16x8ExtendLowI8x16U => (NeonWidthOp::Uxtl, Lanes::B16),
+                    Operator::I16x8ExtendHighI8x16U => (NeonWidthOp::Uxtl2, Lanes::B16),
+                    Operator::I32x4ExtendLowI16x8S => (NeonWidthOp::Sxtl, Lanes::H8),
+                    Operator::I32x4ExtendHighI16x8S => (NeonWidthOp::Sxtl2, Lanes::H8),
+                    Operator::I32x4ExtendLowI16x8U => (NeonWidthOp::Uxtl, Lanes::H8),
+                    Operator::I32x4ExtendHighI16x8U => (NeonWidthOp::Uxtl2, Lanes::H8),
+                    Operator::I64x2ExtendLowI32x4S => (NeonWidthOp::Sxtl, Lanes::S4),
+                    Operator::I64x2ExtendHighI32x4S => (NeonWidthOp::Sxtl2, Lanes::S4),
+                    Operator::I64x2ExtendLowI32x4U => (NeonWidthOp::Uxtl, Lanes::S4),
+                    Operator::I64x2ExtendHighI32x4U => (NeonWidthOp::Uxtl2, Lanes::S4),
+                    Operator::I16x8ExtAddPairwiseI8x16S => (NeonWidthOp::Saddlp, Lanes::B16),
+                    Operator::I16x8ExtAddPairwiseI8x16U => (NeonWidthOp::Uaddlp, Lanes::B16),
+                    Operator::I32x4ExtAddPairwiseI16x8S => (NeonWidthOp::Saddlp, Lanes::H8),
+                    _ => (NeonWidthOp::Uaddlp, Lanes::H8),
+                };
+                self.assembler.emit_neon_width(extend, lanes, x, x)?;
+                Some(x)
+            }
+            Operator::I16x8ExtMulLowI8x16S
+            | Operator::I16x8ExtMulHighI8x16S
+            | Operator::I16x8ExtMulLowI8x16U
+            | Operator::I16x8ExtMulHighI8x16U
+            | Operator::I32x4ExtMulLowI16x8S
+            | Operator::I32x4ExtMulHighI16x8S
+            | Operator::I32x4ExtMulLowI16x8U
+            | Operator::I32x4ExtMulHighI16x8U
+            | Operator::I64x2ExtMulLowI32x4S
+            | Operator::I64x2ExtMulHighI32x4S
+            | Operator::I64x2ExtMulLowI32x4U
+            | Operator::I64x2ExtMulHighI32x4U => {
+                let (mul, lanes) = match *op {
+                    Operator::I16x8ExtMulLowI8x16S => (NeonLongOp::Smull, Lanes::B16),
+                    Operator::I16x8ExtMulHighI8x16S => (NeonLongOp::Smull2, Lanes::B16),
+                    Operator::I16x8ExtMulLowI8x16U => (NeonLongOp::Umull, Lanes::B16),
+                    Operator::I16x8ExtMulHighI8x16U => (NeonLongOp::Umull2, Lanes::B16),
+                    Operator::I32x4ExtMulLowI16x8S => (NeonLongOp::Smull, Lanes::H8),
+                    Operator::I32x4ExtMulHighI16x8S => (NeonLongOp::Smull2, Lanes::H8),
+                    Operator::I32x4ExtMulLowI16x8U => (NeonLongOp::Umull, Lanes::H8),
+                    Operator::I32x4ExtMulHighI16x8U => (NeonLongOp::Umull2, Lanes::H8),
+                    Operator::I64x2ExtMulLowI32x4S => (NeonLongOp::Smull, Lanes::S4),
+                    Operator::I64x2ExtMulHighI32x4S => (NeonLongOp::Smull2, Lanes::S4),
+                    Operator::I64x2ExtMulLowI32x4U => (NeonLongOp::Umull, Lanes::S4),
+                    _ => (NeonLongOp::Umull2, Lanes::S4),
+                };
+                self.assembler.emit_neon_long(mul, lanes, x, y, x)?;
+                Some(x)
+            }
+            Operator::I32x4DotI16x8S => {
+                // Multiply the low and the high lanes, then sum adjacent products.
+                self.assembler
+                    .emit_neon_long(NeonLongOp::Smull, Lanes::H8, x, y, z)?;
+                self.assembler
+                    .emit_neon_long(NeonLongOp::Smull2, Lanes::H8, x, y, x)?;
+                self.assembler
+                    .emit_neon3(Neon3Op::Addp, Lanes::S4, z, x, x)?;
+                Some(x)
+            }
+            Operator::I64x2Mul => {
+                // There's no 64-bit lane multiplication, so multiply each half
+                // in general purpose registers.
+                let mut temps = [GPR::X8; 3];
+                for temp in temps.iter_mut() {
+                    *temp = self.acquire_temp_gpr().ok_or_else(|| {
+                        CompileError::Codegen("singlepass cannot acquire temp gpr".to_owned())
+                    })?;
+                }
+                let [lo, hi, tmp] = temps;
+                for (half, dst) in [(0, lo), (1, hi)] {
+                    self.move_location(Size::S64, args[half], Location::GPR(dst))?;
+                    self.move_location(Size::S64, args[half + 2], Location::GPR(tmp))?;
+                    self.assembler.emit_mul(
+                        Size::S64,
+                        Location::GPR(dst),
+                        Location::GPR(tmp),
+                        Location::GPR(dst),
+                    )?;
+                }
+                self.move_location(Size::S64, Location::GPR(lo), ret[0])?;
+                self.move_location(Size::S64, Location::GPR(hi), ret[1])?;
+                for temp in temps.iter().rev() {
+                    self.release_gpr(*temp);
+                }
+                None
+            }
+            Operator::I32x4TruncSatF64x2SZero | Operator::I32x4TruncSatF64x2UZero => {
+                // Both conversions saturate, and the narrowing zeroes the high lanes.
+                let (convert, narrow) = match *op {
+                    Operator::I32x4TruncSatF64x2SZero => (Neon2Op::Fcvtzs, NeonWidthOp::Sqxtn),
+                    _ => (Neon2Op::Fcvtzu, NeonWidthOp::Uqxtn),
+                };
+                self.assembler.emit_neon2(convert, Lanes::D2, x, x)?;
+                self.assembler.emit_neon_width(narrow, Lanes::S4, x, x)?;
+                Some(x)
+            }
+            Operator::F64x2ConvertLowI32x4S | Operator::F64x2ConvertLowI32x4U => {
+                let (extend, convert) = match *op {
+                    Operator::F64x2ConvertLowI32x4S => (NeonWidthOp::Sxtl, Neon2Op::Scvtf),
+                    _ => (NeonWidthOp::Uxtl, Neon2Op::Ucvtf),
+                };
+                self.assembler.emit_neon_width(extend, Lanes::S4, x, x)?;
+                self.assembler.emit_neon2(convert, Lanes::D2, x, x)?;
+                Some(x)
+            }
+            Operator::F32x4DemoteF64x2Zero => {
+                self.assembler
+                    .emit_neon_width(NeonWidthOp::Fcvtn, Lanes::S4, x, x)?;
+                Some(x)
+            }
+            Operator::F64x2PromoteLowF32x4 => {
+                self.assembler
+                    .emit_neon_width(NeonWidthOp::Fcvtl, Lanes::S4, x, x)?;
+                Some(x)
+            }
+            Operator::F32x4PMin
+            | Operator::F32x4PMax
+            | Operator::F64x2PMin
+            | Operator::F64x2PMax => {
+                // `pmin(a, b)` is `a > b ? b : a` and `pmax(a, b)` is `b > a ? b : a`.
+                let (lanes, swap) = match *op {
+                    Operator::F32x4PMin => (Lanes::S4, false),
+                    Operator::F32x4PMax => (Lanes::S4, true),
+                    Operator::F64x2PMin => (Lanes::D2, false),
+                    _ => (Lanes::D2, true),
+                };
+                let (src1, src2) = if swap { (y, x) } else { (x, y) };
+                self.assembler
+                    .emit_neon3(Neon3Op::Fcmgt, lanes, src1, src2, z)?;
+                self.assembler
+                    .emit_neon3(Neon3Op::Bsl, Lanes::B16, y, x, z)?;
+                Some(z)
+            }
+
+            Operator::I8x16Shuffle { lanes } => {
+                // Look the lanes up in the table made of both operands.
+                let indices = lanes
+                    .iter()
+                    .enumerate()
+                    .fold(0u128, |acc, (i, lane)| acc | (*lane as u128) << (i * 8));
+                self.simd_const(indices, z)?;
+                self.assembler.emit_neon_tbl(x, true, z, z)?;
+                Some(z)
+            }
+            Operator::I8x16Swizzle => {
+                // The out of range indices select zero, as in `tbl`.
+                self.assembler.emit_neon_tbl(x, false, y, x)?;
+                Some(x)
+            }
+
+            _ => {
+                let unop = match *op {
+                    Operator::V128Not => Some((Neon2Op::Not, Lanes::B16)),
+                    Operator::I8x16Abs => Some((Neon2Op::Abs, Lanes::B16)),
+                    Operator::I16x8Abs => Some((Neon2Op::Abs, Lanes::H8)),
+                    Operator::I32x4Abs => Some((Neon2Op::Abs, Lanes::S4)),
+                    Operator::I64x2Abs => Some((Neon2Op::Abs, Lanes::D2)),
+                    Operator::I8x16Neg => Some((Neon2Op::Neg, Lanes::B16)),
+                    Operator::I16x8Neg => Some((Neon2Op::Neg, Lanes::H8)),
+                    Operator::I32x4Neg => Some((Neon2Op::Neg, Lanes::S4)),
+                    Operator::I64x2Neg => Some((Neon2Op::Neg, Lanes::D2)),
+                    Operator::I8x16Popcnt => Some((Neon2Op::Cnt, Lanes::B16)),
+                    Operator::F32x4Abs => Some((Neon2Op::Fabs, Lanes::S4)),
+                    Operator::F32x4Neg => Some((Neon2Op::Fneg, Lanes::S4)),
+                    Operator::F32x4Sqrt => Some((Neon2Op::Fsqrt, Lanes::S4)),
+                    Operator::F32x4Ceil => Some((Neon2Op::Frintp, Lanes::S4)),
+                    Operator::F32x4Floor => Some((Neon2Op::Frintm, Lanes::S4)),
+                    Operator::F32x4Trunc => Some((Neon2Op::Frintz, Lanes::S4)),
+                    Operator::F32x4Nearest => Some((Neon2Op::Frintn, Lanes::S4)),
+                    Operator::F64x2Abs => Some((Neon2Op::Fabs, Lanes::D2)),
+                    Operator::F64x2Neg => Some((Neon2Op::Fneg, Lanes::D2)),
+                    Operator::F64x2Sqrt => Some((Neon2Op::Fsqrt, Lanes::D2)),
+                    Operator::F64x2Ceil => Some((Neon2Op::Frintp, Lanes::D2)),
+                    Operator::F64x2Floor => Some((Neon2Op::Frintm, Lanes::D2)),
+                    Operator::F64x2Trunc => Some((Neon2Op::Frintz, Lanes::D2)),
+                    Operator::F64x2Nearest => Some((Neon2Op::Frintn, Lanes::D2)),
+                    Operator::I32x4TruncSatF32x4S => Some((Neon2Op::Fcvtzs, Lanes::S4)),
+                    Operator::I32x4TruncSatF32x4U => Some((Neon2Op::Fcvtzu, Lanes::S4)),
+                    Operator::F32x4ConvertI32x4S => Some((Neon2Op::Scvtf, Lanes::S4)),
+                    Operator::F32x4ConvertI32x4U => Some((Neon2Op::Ucvtf, Lanes::S4)),
+                    _ => None,
+                };
+                if let Some((unop, lanes)) = unop {
+                    self.assembler.emit_neon2(unop, lanes, x, x)?;
+                    Some(x)
+                } else {
+                    // The comparisons missing from NEON swap their operands.
+                    let (binop, lanes, swap) = match *op {
+                        Operator::V128And => (Neon3Op::And, Lanes::B16, false),
+                        Operator::V128Or => (Neon3Op::Orr, Lanes::B16, false),
+                        Operator::V128Xor => (Neon3Op::Eor, Lanes::B16, false),
+                        Operator::I8x16Eq => (Neon3Op::Cmeq, Lanes::B16, false),
+                        Operator::I8x16LtS => (Neon3Op::Cmgt, Lanes::B16, true),
+                        Operator::I8x16LtU => (Neon3Op::Cmhi, Lanes::B16, true),
+                        Operator::I8x16GtS => (Neon3Op::Cmgt, Lanes::B16, false),
+                        Operator::I8x16GtU => (Neon3Op::Cmhi, Lanes::B16, false),
+                        Operator::I8x16LeS => (Neon3Op::Cmge, Lanes::B16, true),
+                        Operator::I8x16LeU => (Neon3Op::Cmhs, Lanes::B16, true),
+                        Operator::I8x16GeS => (Neon3Op::Cmge, Lanes::B16, false),
+                        Operator::I8x16GeU => (Neon3Op::Cmhs, Lanes::B16, false),
+                        Operator::I16x8Eq => (Neon3Op::Cmeq, Lanes::H8, false),
+                        Operator::I16x8LtS => (Neon3Op::Cmgt, Lanes::H8, true),
+                        Operator::I16x8LtU => (Neon3Op::Cmhi, Lanes::H8, true),
+                        Operator::I16x8GtS => (Neon3Op::Cmgt, Lanes::H8, false),
+                        Operator::I16x8GtU => (Neon3Op::Cmhi, Lanes::H8, false),
+                        Operator::I16x8LeS => (Neon3Op::Cmge, Lanes::H8, true),
+                        Operator::I16x8LeU => (Neon3Op::Cmhs, Lanes::H8, true),
+                        Operator::I16x8GeS => (Neon3Op::Cmge, Lanes::H8, false),
+                        Operator::I16x8GeU => (Neon3Op::Cmhs, Lanes::H8, false),
+                        Operator::I32x4Eq => (Neon3Op::Cmeq, Lanes::S4, false),
+                        Operator::I32x4LtS => (Neon3Op::Cmgt, Lanes::S4, true),
+                        Operator::I32x4LtU => (Neon3Op::Cmhi, Lanes::S4, true),
+                        Operator::I32x4GtS => (Neon3Op::Cmgt, Lanes::S4, false),
+                        Operator::I32x4GtU => (Neon3Op::Cmhi, Lanes::S4, false),
+                        Operator::I32x4LeS => (Neon3Op::Cmge, Lanes::S4, true),
+                        Operator::I32x4LeU => (Neon3Op::Cmhs, Lanes::S4, true),
+                        Operator::I32x4GeS => (Neon3Op::Cmge, Lanes::S4, false),
+                        Operator::I32x4GeU => (Neon3Op::Cmhs, Lanes::S4, false),
+                        Operator::I64x2Eq => (Neon3Op::Cmeq, Lanes::D2, false),
+                        Operator::I64x2LtS => (Neon3Op::Cmgt, Lanes::D2, true),
+                        Operator::I64x2GtS => (Neon3Op::Cmgt, Lanes::D2, false),
+                        Operator::I64x2LeS => (Neon3Op::Cmge, Lanes::D2, true),
+                        Operator::I64x2GeS => (Neon3Op::Cmge, Lanes::D2, false),
+                        Operator::F32x4Eq => (Neon3Op::Fcmeq, Lanes::S4, false),
+                        Operator::F32x4Lt => (Neon3Op::Fcmgt, Lanes::S4, true),
+                        Operator::F32x4Gt => (Neon3Op::Fcmgt, Lanes::S4, false),
+                        Operator::F32x4Le => (Neon3Op::Fcmge, Lanes::S4, true),
+                        Operator::F32x4Ge => (Neon3Op::Fcmge, Lanes::S4, false),
+                        Operator::F64x2Eq => (Neon3Op::Fcmeq, Lanes::D2, false),
+                        Operator::F64x2Lt => (Neon3Op::Fcmgt, Lanes::D2, true),
+                        Operator::F64x2Gt => (Neon3Op::Fcmgt, Lanes::D2, false),
+                        Operator::F64x2Le => (Neon3Op::Fcmge, Lanes::D2, true),
+                        Operator::F64x2Ge => (Neon3Op::Fcmge, Lanes::D2, false),
+                        Operator::I8x16Add => (Neon3Op::Add, Lanes::B16, false),
+                        Operator::I8x16AddSatS => (Neon3Op::Sqadd, Lanes::B16, false),
+                        Operator::I8x16AddSatU => (Neon3Op::Uqadd, Lanes::B16, false),
+                        Operator::I8x16Sub => (Neon3Op::Sub, Lanes::B16, false),
+                        Operator::I8x16SubSatS => (Neon3Op::Sqsub, Lanes::B16, false),
+                        Operator::I8x16SubSatU => (Neon3Op::Uqsub, Lanes::B16, false),
+                        Operator::I8x16MinS => (Neon3Op::Smin, Lanes::B16, false),
+                        Operator::I8x16MinU => (Neon3Op::Umin, Lanes::B16, false),
+                        Operator::I8x16MaxS => (Neon3Op::Smax, Lanes::B16, false),
+                        Operator::I8x16MaxU => (Neon3Op::Umax, Lanes::B16, false),
+                        Operator::I8x16AvgrU => (Neon3Op::Urhadd, Lanes::B16, false),
+                        Operator::I16x8Add => (Neon3Op::Add, Lanes::H8, false),
+                        Operator::I16x8AddSatS => (Neon3Op::Sqadd, Lanes::H8, false),
+                        Operator::I16x8AddSatU => (Neon3Op::Uqadd, Lanes::H8, false),
+                        Operator::I16x8Sub => (Neon3Op::Sub, Lanes::H8, false),
+                        Operator::I16x8SubSatS => (Neon3Op::Sqsub, Lanes::H8, false),
+                        Operator::I16x8SubSatU => (Neon3Op::Uqsub, Lanes::H8, false),
+                        Operator::I16x8Mul => (Neon3Op::Mul, Lanes::H8, false),
+                        Operator::I16x8MinS => (Neon3Op::Smin, Lanes::H8, false),
+                        Operator::I16x8MinU => (Neon3Op::Umin, Lanes::H8, false),
+                        Operator::I16x8MaxS => (Neon3Op::Smax, Lanes::H8, false),
+                        Operator::I16x8MaxU => (Neon3Op::Umax, Lanes::H8, false),
+                        Operator::I16x8AvgrU => (Neon3Op::Urhadd, Lanes::H8, false),
+                        Operator::I16x8Q15MulrSatS => (Neon3Op::Sqrdmulh, Lanes::H8, false),
+                        Operator::I32x4Add => (Neon3Op::Add, Lanes::S4, false),
+                        Operator::I32x4Sub => (Neon3Op::Sub, Lanes::S4, false),
+                        Operator::I32x4Mul => (Neon3Op::Mul, Lanes::S4, false),
+                        Operator::I32x4MinS => (Neon3Op::Smin, Lanes::S4, false),
+                        Operator::I32x4MinU => (Neon3Op::Umin, Lanes::S4, false),
+                        Operator::I32x4MaxS => (Neon3Op::Smax, Lanes::S4, false),
+                        Operator::I32x4MaxU => (Neon3Op::Umax, Lanes::S4, false),
+                        Operator::I64x2Add => (Neon3Op::Add, Lanes::D2, false),
+                        Operator::I64x2Sub => (Neon3Op::Sub, Lanes::D2, false),
+                        Operator::F32x4Add => (Neon3Op::Fadd, Lanes::S4, false),
+                        Operator::F32x4Sub => (Neon3Op::Fsub, Lanes::S4, false),
+                        Operator::F32x4Mul => (Neon3Op::Fmul, Lanes::S4, false),
+                        Operator::F32x4Div => (Neon3Op::Fdiv, Lanes::S4, false),
+                        Operator::F32x4Min => (Neon3Op::Fmin, Lanes::S4, false),
+                        Operator::F32x4Max => (Neon3Op::Fmax, Lanes::S4, false),
+                        Operator::F64x2Add => (Neon3Op::Fadd, Lanes::D2, false),
+                        Operator::F64x2Sub => (Neon3Op::Fsub, Lanes::D2, false),
+                        Operator::F64x2Mul => (Neon3Op::Fmul, Lanes::D2, false),
+                        Operator::F64x2Div => (Neon3Op::Fdiv, Lanes::D2, false),
+                        Operator::F64x2Min => (Neon3Op::Fmin, Lanes::D2, false),
+                        Operator::F64x2Max => (Neon3Op::Fmax, Lanes::D2, false),
+                        _ => codegen_error!("singlepass can't emit SIMD operator {:?}", op),
+                    };
+                    let (src1, src2) = if swap { (y, x) } else { (x, y) };
+                    self.assembler.emit_neon3(binop, lanes, src1, src2, x)?;
+                    Some(x)
+                }
+            }
+        };
+        if let Some(result) = result {
+            self.simd_scatter(result, ret[0], ret[1])?;
+        }
+        self.release_simd_temps([x, y, z]);
+        Ok(())
+    }
+
+    fn emit_simd_memory(
+        &mut self,
+        op: &Operator,
+        addr: Location,
+        args: &[Location],
+        ret: &[Location],
+        need_check: bool,
+        memory64: bool,
+        imported_memories: bool,
+        offset: i32,
+        heap_access_oob: Label,
+        unaligned_atomic: Label,
+    ) -> Result<(), CompileError> {
+        let [x, y, z] = self.acquire_simd_temps()?;
+        match *op {
+            Operator::V128Store { ref memarg } => {
+                self.simd_gather(args[0], args[1], x)?;
+                self.memory_op(
+                    addr,
+                    memarg,
+                    false,
+                    16,
+                    need_check,
+                    memory64,
+                    imported_memories,
+                    offset,
+                    heap_access_oob,
+                    unaligned_atomic,
+                    |this, addr| this.assembler.emit_neon_str_q(x, addr),
+                )?;
+            }
+            Operator::V128Store8Lane { ref memarg, lane }
+            | Operator::V128Store16Lane { ref memarg, lane }
+            | Operator::V128Store32Lane { ref memarg, lane }
+            | Operator::V128Store64Lane { ref memarg, lane } => {
+                let (lanes, value_size) = match *op {
+                    Operator::V128Store8Lane { .. } => (Lanes::B16, 1),
+                    Operator::V128Store16Lane { .. } => (Lanes::H8, 2),
+                    Operator::V128Store32Lane { .. } => (Lanes::S4, 4),
+                    _ => (Lanes::D2, 8),
+                };
+                self.simd_gather(args[0], args[1], x)?;
+                self.memory_op(
+                    addr,
+                    memarg,
+                    false,
+                    value_size,
+                    need_check,
+                    memory64,
+                    imported_memories,
+                    offset,
+                    heap_access_oob,
+                    unaligned_atomic,
+                    |this, addr| {
+                        this.assembler
+                            .emit_neon_st1_lane(lanes, x, lane as u32, addr)
+                    },
+                )?;
+            }
+            Operator::V128Load8Lane { ref memarg, lane }
+            | Operator::V128Load16Lane { ref memarg, lane }
+            | Operator::V128Load32Lane { ref memarg, lane }
+            | Operator::V128Load64Lane { ref memarg, lane } => {
+                let (lanes, value_size) = match *op {
+                    Operator::V128Load8Lane { .. } => (Lanes::B16, 1),
+                    Operator::V128Load16Lane { .. } => (Lanes::H8, 2),
+                    Operator::V128Load32Lane { .. } => (Lanes::S4, 4),
+                    _ => (Lanes::D2, 8),
+                };
+                self.simd_gather(args[0], args[1], x)?;
+                self.memory_op(
+                    addr,
+                    memarg,
+                    false,
+                    value_size,
+                    need_check,
+                    memory64,
+                    imported_memories,
+                    offset,
+                    heap_access_oob,
+                    unaligned_atomic,
+                    |this, addr| {
+                        this.assembler
+                            .emit_neon_ld1_lane(lanes, x, lane as u32, addr)
+                    },
+                )?;
+                self.simd_scatter(x, ret[0], ret[1])?;
+            }
+            Operator::V128Load { ref memarg }
+            | Operator::V128Load8x8S { ref memarg }
+            | Operator::V128Load8x8U { ref memarg }
+            | Operator::V128Load16x4S { ref memarg }
+            | Operator::V128Load16x4U { ref memarg }
+            | Operator::V128Load32x2S { ref memarg }
+            | Operator::V128Load32x2U { ref memarg }
+            | Operator::V128Load8Splat { ref memarg }
+            | Operator::V128Load16Splat { ref memarg }
+            | Operator::V128Load32Splat { ref memarg }
+            | Operator::V128Load64Splat { ref memarg }
+            | Operator::V128Load32Zero { ref memarg }
+            | Operator::V128Load64Zero { ref memarg } => {
+                let value_size = match *op {
+                    Operator::V128Load { .. } => 16,
+                    Operator::V128Load8Splat { .. } => 1,
+                    Operator::V128Load16Splat { .. } => 2,
+                    Operator::V128Load32Splat { .. } | Operator::V128Load32Zero { .. } => 4,
+                    _ => 8,
+                };
+                // The lanes loaded and repeated by the splats.
+                let splat = match *op {
+                    Operator::V128Load8Splat { .. } => Some(Lanes::B16),
+                    Operator::V128Load16Splat { .. } => Some(Lanes::H8),
+                    Operator::V128Load32Splat { .. } => Some(Lanes::S4),
+                    Operator::V128Load64Splat { .. } => Some(Lanes::D2),
+                    _ => None,
+                };
+                self.memory_op(
+                    addr,
+                    memarg,
+                    false,
+                    value_size,
+                    need_check,
+                    memory64,
+                    imported_memories,
+                    offset,
+                    heap_access_oob,
+                    unaligned_atomic,
+                    |this, addr| match (splat, value_size) {
+                        (Some(lanes), _) => this.assembler.emit_neon_ld1r(lanes, x, addr),
+                        (None, 16) => this.assembler.emit_neon_ldr_q(x, addr),
+                        (None, 4) => this.assembler.emit_ldr(
+                            Size::S32,
+                            Location::SIMD(x),
+                            Location::Memory(addr, 0),
+                        ),
+                        _ => this.assembler.emit_ldr(
+                            Size::S64,
+                            Location::SIMD(x),
+                            Location::Memory(addr, 0),
+                        ),
+                    },
+                )?;
+                let extend = match *op {
+                    Operator::V128Load8x8S { .. } => Some((NeonWidthOp::Sxtl, Lanes::B16)),
+                    Operator::V128Load8x8U { .. } => Some((NeonWidthOp::Uxtl, Lanes::B16)),
+                    Operator::V128Load16x4S { .. } => Some((NeonWidthOp::Sxtl, Lanes::H8)),
+                    Operator::V128Load16x4U { .. } => Some((NeonWidthOp::Uxtl, Lanes::H8)),
+                    Operator::V128Load32x2S { .. } => Some((NeonWidthOp::Sxtl, Lanes::S4)),
+                    Operator::V128Load32x2U { .. } => Some((NeonWidthOp::Uxtl, Lanes::S4)),
+                    _ => None,
+                };
+                if let Some((extend, lanes)) = extend {
+                    self.assembler.emit_neon_width(extend, lanes, x, x)?;
+                }
+                self.simd_scatter(x, ret[0], ret[1])?;
+            }
+            _ => codegen_error!("singlepass can't emit SIMD memory operator {:?}", op),
+        }
+        self.release_simd_temps([x, y, z]);
+        Ok(())
+    }
+
     fn gen_std_trampoline(
         &self,
         sig: &FunctionType,
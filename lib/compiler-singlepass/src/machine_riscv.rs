@@ -0,0 +1,5958 @@
+use dynasmrt::VecAssembler;
+#[cfg(feature = "unwind")]
+use gimli::{write::CallFrameInstruction, RiscV};
+
+use wasmer_compiler::wasmparser::{Operator, ValType as WpType};
+use wasmer_types::{
+    CallingConvention, CompileError, CustomSection, FunctionBody, FunctionIndex, FunctionType,
+    InstructionAddressMap, Relocation, RelocationKind, RelocationTarget, SourceLoc, Target,
+    TrapCode, TrapInformation, VMOffsets,
+};
+
+use crate::codegen_error;
+use crate::common_decl::*;
+use crate::emitter_riscv::*;
+use crate::location::Location as AbstractLocation;
+use crate::location::Reg;
+use crate::machine::*;
+use crate::riscv_decl::new_machine_state;
+use crate::riscv_decl::{FPR, GPR};
+use crate::unwind::{UnwindInstructions, UnwindOps};
+
+type Assembler = VecAssembler<RiscvRelocation>;
+type Location = AbstractLocation<GPR, FPR>;
+
+/// RISC-V DWARF register numbers match the register indices, the FPRs
+/// being numbered from 32.
+#[cfg(feature = "unwind")]
+fn dwarf_index(reg: u16) -> gimli::Register {
+    match reg {
+        0..=63 => gimli::Register(reg),
+        _ => panic!("Unknown register index {}", reg),
+    }
+}
+
+pub struct MachineRiscv {
+    assembler: Assembler,
+    used_gprs: u32,
+    used_fprs: u32,
+    trap_table: TrapTable,
+    /// Map from byte offset into wasm function to range of native instructions.
+    // Ordered by increasing InstructionAddressMap::srcloc.
+    instructions_address_map: Vec<InstructionAddressMap>,
+    /// The source location for the current operator.
+    src_loc: u32,
+    /// is last push on a 8byte multiple or 16bytes?
+    pushed: bool,
+    /// Vector of unwind operations with offset
+    unwind_ops: Vec<(usize, UnwindOps)>,
+    /// Operands of the last comparison. RISC-V has no flags register, so
+    /// comparisons are folded into the conditional jump following them.
+    cmp_operands: Option<(Size, Location, Location)>,
+}
+
+/// Atomic read-modify-write operations.
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+enum AtomicOp {
+    Add,
+    Sub,
+    And,
+    Or,
+    Xor,
+    Xchg,
+}
+
+impl MachineRiscv {
+    pub fn new(_target: Option<Target>) -> Self {
+        MachineRiscv {
+            assembler: Assembler::new(0),
+            used_gprs: 0,
+            used_fprs: 0,
+            trap_table: TrapTable::default(),
+            instructions_address_map: vec![],
+            src_loc: 0,
+            pushed: false,
+            unwind_ops: vec![],
+            cmp_operands: None,
+        }
+    }
+
+    fn acquire_temp(&mut self) -> Result<GPR, CompileError> {
+        self.acquire_temp_gpr()
+            .ok_or_else(|| CompileError::Codegen("singlepass cannot acquire temp gpr".to_owned()))
+    }
+    fn acquire_temp_fpr(&mut self) -> Result<FPR, CompileError> {
+        self.acquire_temp_simd()
+            .ok_or_else(|| CompileError::Codegen("singlepass cannot acquire temp simd".to_owned()))
+    }
+    fn release_temps(&mut self, gprs: Vec<GPR>, fprs: Vec<FPR>) {
+        for r in gprs {
+            self.release_gpr(r);
+        }
+        for r in fprs {
+            self.release_simd(r);
+        }
+    }
+    /// Returns `reg` if it is one of `temps`, or a new temporary register otherwise,
+    /// so it can be overwritten.
+    fn own_temp(&mut self, reg: GPR, temps: &mut Vec<GPR>) -> Result<GPR, CompileError> {
+        if temps.contains(&reg) {
+            Ok(reg)
+        } else {
+            let tmp = self.acquire_temp()?;
+            temps.push(tmp);
+            Ok(tmp)
+        }
+    }
+
+    /// Returns a base register and an immediate offset addressing `base + offset`,
+    /// computing the address in the scratch register when the offset is out of range.
+    fn mem_addr(&mut self, base: GPR, offset: i32) -> Result<(GPR, i32), CompileError> {
+        if imm12(offset as i64) {
+            Ok((base, offset))
+        } else {
+            self.assembler.emit_mov_imm(SCRATCH, offset as i64 as u64)?;
+            self.assembler
+                .emit_alu(Size::S64, AluOp::Add, SCRATCH, base, SCRATCH)?;
+            Ok((SCRATCH, 0))
+        }
+    }
+    fn emit_relaxed_load(
+        &mut self,
+        sz: Size,
+        signed: bool,
+        dst: GPR,
+        base: GPR,
+        offset: i32,
+    ) -> Result<(), CompileError> {
+        let (base, offset) = self.mem_addr(base, offset)?;
+        self.assembler.emit_load(sz, signed, dst, base, offset)
+    }
+    fn emit_relaxed_store(
+        &mut self,
+        sz: Size,
+        src: GPR,
+        base: GPR,
+        offset: i32,
+    ) -> Result<(), CompileError> {
+        let (base, offset) = self.mem_addr(base, offset)?;
+        self.assembler.emit_store(sz, src, base, offset)
+    }
+    fn emit_relaxed_fload(
+        &mut self,
+        sz: Size,
+        dst: FPR,
+        base: GPR,
+        offset: i32,
+    ) -> Result<(), CompileError> {
+        let (base, offset) = self.mem_addr(base, offset)?;
+        self.assembler.emit_fload(sz, dst, base, offset)
+    }
+    fn emit_relaxed_fstore(
+        &mut self,
+        sz: Size,
+        src: FPR,
+        base: GPR,
+        offset: i32,
+    ) -> Result<(), CompileError> {
+        let (base, offset) = self.mem_addr(base, offset)?;
+        self.assembler.emit_fstore(sz, src, base, offset)
+    }
+    /// Emits `dst = src + imm`, going through the scratch register for large immediates.
+    fn emit_add_imm(&mut self, dst: GPR, src: GPR, imm: i64) -> Result<(), CompileError> {
+        if imm12(imm) {
+            self.assembler
+                .emit_alu_imm(Size::S64, AluImmOp::Addi, dst, src, imm as i32)
+        } else {
+            self.assembler.emit_mov_imm(SCRATCH, imm as u64)?;
+            self.assembler
+                .emit_alu(Size::S64, AluOp::Add, dst, src, SCRATCH)
+        }
+    }
+    /// Moves `sp` by `delta` bytes.
+    fn emit_adjust_sp(&mut self, delta: i64) -> Result<(), CompileError> {
+        self.emit_add_imm(GPR::X2, GPR::X2, delta)
+    }
+    /// Extends the `sz` low bits of `src` into `dst`.
+    fn emit_extend(
+        &mut self,
+        sz: Size,
+        signed: bool,
+        dst: GPR,
+        src: GPR,
+    ) -> Result<(), CompileError> {
+        let shift = match sz {
+            Size::S8 => 56,
+            Size::S16 => 48,
+            Size::S32 if signed => return self.assembler.emit_sext32(dst, src),
+            Size::S32 => 32,
+            Size::S64 => return self.assembler.emit_mov(dst, src),
+        };
+        self.assembler
+            .emit_alu_imm(Size::S64, AluImmOp::Slli, dst, src, shift)?;
+        let op = if signed {
+            AluImmOp::Srai
+        } else {
+            AluImmOp::Srli
+        };
+        self.assembler.emit_alu_imm(Size::S64, op, dst, dst, shift)
+    }
+
+    fn imm_value(src: Location) -> Option<u64> {
+        match src {
+            Location::Imm8(val) => Some(val as u64),
+            Location::Imm32(val) => Some(val as u64),
+            Location::Imm64(val) => Some(val),
+            _ => None,
+        }
+    }
+
+    /// Gets `src` in a GPR, loading it in a temporary register when it isn't one.
+    /// A constant zero is read from `x0`.
+    fn location_to_reg(
+        &mut self,
+        sz: Size,
+        src: Location,
+        temps: &mut Vec<GPR>,
+        read_val: bool,
+    ) -> Result<GPR, CompileError> {
+        match src {
+            Location::GPR(x) => Ok(x),
+            Location::Imm8(_) | Location::Imm32(_) | Location::Imm64(_) => {
+                if !read_val {
+                    codegen_error!("singlepass can't write to {:?}", src);
+                }
+                let val = Self::imm_value(src).unwrap();
+                if val == 0 {
+                    return Ok(GPR::X0);
+                }
+                let tmp = self.acquire_temp()?;
+                temps.push(tmp);
+                self.assembler.emit_mov_imm(tmp, val)?;
+                Ok(tmp)
+            }
+            Location::SIMD(x) => {
+                let tmp = self.acquire_temp()?;
+                temps.push(tmp);
+                if read_val {
+                    self.assembler.emit_fmv_to_gpr(Size::S64, tmp, x)?;
+                }
+                Ok(tmp)
+            }
+            Location::Memory(base, offset) => {
+                let tmp = self.acquire_temp()?;
+                temps.push(tmp);
+                if read_val {
+                    self.emit_relaxed_load(sz, false, tmp, base, offset)?;
+                }
+                Ok(tmp)
+            }
+            _ => codegen_error!("singlepass can't emit location_to_reg {:?} {:?}", sz, src),
+        }
+    }
+    /// Gets `src` in an FPR, the value being converted from its raw bits when
+    /// it lives in a GPR or in memory.
+    fn location_to_freg(
+        &mut self,
+        sz: Size,
+        src: Location,
+        temps: &mut Vec<FPR>,
+        read_val: bool,
+    ) -> Result<FPR, CompileError> {
+        if let Location::SIMD(x) = src {
+            return Ok(x);
+        }
+        let tmp = self.acquire_temp_fpr()?;
+        temps.push(tmp);
+        if read_val {
+            match src {
+                Location::GPR(x) => self.assembler.emit_fmv_from_gpr(sz, tmp, x)?,
+                Location::Memory(base, offset) => self.emit_relaxed_fload(sz, tmp, base, offset)?,
+                Location::Imm8(_) | Location::Imm32(_) | Location::Imm64(_) => {
+                    let val = Self::imm_value(src).unwrap();
+                    self.assembler.emit_mov_imm(SCRATCH, val)?;
+                    self.assembler.emit_fmv_from_gpr(sz, tmp, SCRATCH)?;
+                }
+                _ => codegen_error!("singlepass can't emit location_to_freg {:?} {:?}", sz, src),
+            }
+        }
+        Ok(tmp)
+    }
+    /// Sign-extends a 32-bit value held in `reg` into a register that can be overwritten.
+    fn sext_temp(&mut self, reg: GPR, temps: &mut Vec<GPR>) -> Result<GPR, CompileError> {
+        if reg == GPR::X0 {
+            return Ok(reg);
+        }
+        let tmp = self.own_temp(reg, temps)?;
+        self.assembler.emit_sext32(tmp, reg)?;
+        Ok(tmp)
+    }
+
+    /// Emits `dst = src1 op src2`, using the immediate form of `op` when `src2` is a small constant.
+    fn emit_relaxed_alu3(
+        &mut self,
+        sz: Size,
+        op: AluOp,
+        src1: Location,
+        src2: Location,
+        dst: Location,
+    ) -> Result<(), CompileError> {
+        let mut temps = vec![];
+        let a = self.location_to_reg(sz, src1, &mut temps, true)?;
+        let imm = match src2 {
+            Location::Imm32(val) if sz == Size::S32 => Some(val as i32 as i64),
+            _ => Self::imm_value(src2).map(|val| val as i64),
+        };
+        let shift_mask = if sz == Size::S32 { 31 } else { 63 };
+        let imm_op = match (op, imm) {
+            (AluOp::Add, Some(val)) if imm12(val) => Some((AluImmOp::Addi, val)),
+            (AluOp::Sub, Some(val)) if imm12(-val) => Some((AluImmOp::Addi, -val)),
+            (AluOp::And, Some(val)) if imm12(val) => Some((AluImmOp::Andi, val)),
+            (AluOp::Or, Some(val)) if imm12(val) => Some((AluImmOp::Ori, val)),
+            (AluOp::Xor, Some(val)) if imm12(val) => Some((AluImmOp::Xori, val)),
+            (AluOp::Sll, Some(val)) => Some((AluImmOp::Slli, val & shift_mask)),
+            (AluOp::Srl, Some(val)) => Some((AluImmOp::Srli, val & shift_mask)),
+            (AluOp::Sra, Some(val)) => Some((AluImmOp::Srai, val & shift_mask)),
+            _ => None,
+        };
+        let dest = self.location_to_reg(sz, dst, &mut temps, false)?;
+        if let Some((imm_op, val)) = imm_op {
+            self.assembler
+                .emit_alu_imm(sz, imm_op, dest, a, val as i32)?;
+        } else {
+            let b = self.location_to_reg(sz, src2, &mut temps, true)?;
+            self.assembler.emit_alu(sz, op, dest, a, b)?;
+        }
+        if dst != Location::GPR(dest) {
+            self.move_location(sz, Location::GPR(dest), dst)?;
+        }
+        self.release_temps(temps, vec![]);
+        Ok(())
+    }
+    /// Sets `ret` to 1 if `a cond b`, to 0 otherwise.
+    fn emit_cmpop(
+        &mut self,
+        sz: Size,
+        cond: Condition,
+        loc_a: Location,
+        loc_b: Location,
+        ret: Location,
+    ) -> Result<(), CompileError> {
+        let mut temps = vec![];
+        let mut a = self.location_to_reg(sz, loc_a, &mut temps, true)?;
+        let mut b = self.location_to_reg(sz, loc_b, &mut temps, true)?;
+        if sz == Size::S32 {
+            a = self.sext_temp(a, &mut temps)?;
+            b = self.sext_temp(b, &mut temps)?;
+        }
+        let dest = self.location_to_reg(sz, ret, &mut temps, false)?;
+        match cond {
+            Condition::Eq | Condition::Ne => {
+                self.assembler.emit_alu(Size::S64, AluOp::Xor, dest, a, b)?;
+                if cond == Condition::Eq {
+                    self.assembler
+                        .emit_alu_imm(Size::S64, AluImmOp::Sltiu, dest, dest, 1)?;
+                } else {
+                    self.assembler
+                        .emit_alu(Size::S64, AluOp::Sltu, dest, GPR::X0, dest)?;
+                }
+            }
+            Condition::Lt | Condition::Ge => {
+                self.assembler.emit_alu(Size::S64, AluOp::Slt, dest, a, b)?;
+            }
+            Condition::Ltu | Condition::Geu => {
+                self.assembler
+                    .emit_alu(Size::S64, AluOp::Sltu, dest, a, b)?;
+            }
+        }
+        if cond == Condition::Ge || cond == Condition::Geu {
+            self.assembler
+                .emit_alu_imm(Size::S64, AluImmOp::Xori, dest, dest, 1)?;
+        }
+        if ret != Location::GPR(dest) {
+            self.move_location(Size::S32, Location::GPR(dest), ret)?;
+        }
+        self.release_temps(temps, vec![]);
+        Ok(())
+    }
+    /// Jumps to `label` if the operands of the last comparison satisfy
+    /// `dst cond src`, or `src cond dst` when `swap` is set.
+    fn emit_cmp_branch(
+        &mut self,
+        cond: Condition,
+        swap: bool,
+        label: Label,
+    ) -> Result<(), CompileError> {
+        let (sz, src, dst) = self.cmp_operands.take().ok_or_else(|| {
+            CompileError::Codegen("singlepass conditional jump without comparison".to_owned())
+        })?;
+        let mut temps = vec![];
+        let mut s = self.location_to_reg(sz, src, &mut temps, true)?;
+        let mut d = self.location_to_reg(sz, dst, &mut temps, true)?;
+        if sz == Size::S32 {
+            s = self.sext_temp(s, &mut temps)?;
+            d = self.sext_temp(d, &mut temps)?;
+        }
+        let (x, y) = if swap { (s, d) } else { (d, s) };
+        self.assembler.emit_bcond_label_far(cond, x, y, label)?;
+        self.release_temps(temps, vec![]);
+        Ok(())
+    }
+
+    /// Emits a division or remainder, trapping on a zero divisor and, for `sdiv`,
+    /// on overflow.
+    #[allow(clippy::too_many_arguments)]
+    fn emit_divop(
+        &mut self,
+        sz: Size,
+        op: AluOp,
+        loc_a: Location,
+        loc_b: Location,
+        ret: Location,
+        integer_division_by_zero: Label,
+        integer_overflow: Option<Label>,
+    ) -> Result<usize, CompileError> {
+        let mut temps = vec![];
+        let mut a = self.location_to_reg(sz, loc_a, &mut temps, true)?;
+        let mut b = self.location_to_reg(sz, loc_b, &mut temps, true)?;
+        if sz == Size::S32 {
+            a = self.sext_temp(a, &mut temps)?;
+            b = self.sext_temp(b, &mut temps)?;
+        }
+        let dest = self.location_to_reg(sz, ret, &mut temps, false)?;
+
+        self.assembler
+            .emit_bcond_label_far(Condition::Eq, b, GPR::X0, integer_division_by_zero)?;
+        if let Some(integer_overflow) = integer_overflow {
+            let tmp = self.acquire_temp()?;
+            temps.push(tmp);
+            self.emit_jmp_on_sdiv_overflow(sz, a, b, tmp, integer_overflow)?;
+        }
+        let offset = self.mark_instruction_with_trap_code(TrapCode::IntegerOverflow);
+        self.assembler.emit_alu(sz, op, dest, a, b)?;
+        if ret != Location::GPR(dest) {
+            self.move_location(sz, Location::GPR(dest), ret)?;
+        }
+        self.release_temps(temps, vec![]);
+        Ok(offset)
+    }
+
+    /// Jumps to `integer_overflow` if the signed division of `a` by `b`
+    /// overflows, i.e. for `INT_MIN / -1`. The operands are sign-extended to
+    /// 64 bits, and `tmp` is clobbered.
+    ///
+    /// There are no flags to test on RISC-V, and its `div` doesn't trap but
+    /// returns `INT_MIN`, so the operands are compared explicitly.
+    fn emit_jmp_on_sdiv_overflow(
+        &mut self,
+        sz: Size,
+        a: GPR,
+        b: GPR,
+        tmp: GPR,
+        integer_overflow: Label,
+    ) -> Result<(), CompileError> {
+        let label_nooverflow = self.assembler.get_label();
+        let min = if sz == Size::S32 {
+            i32::MIN as i64
+        } else {
+            i64::MIN
+        };
+        self.assembler.emit_mov_imm(tmp, min as u64)?;
+        self.assembler
+            .emit_bcond_label(Condition::Ne, a, tmp, label_nooverflow)?;
+        self.assembler.emit_mov_imm(tmp, -1i64 as u64)?;
+        self.assembler
+            .emit_bcond_label_far(Condition::Eq, b, tmp, integer_overflow)?;
+        self.assembler.emit_label(label_nooverflow)
+    }
+
+    /// Counts the set bits of `x` in place, using `t1` and `t2` as temporaries.
+    fn emit_popcount(&mut self, x: GPR, t1: GPR, t2: GPR) -> Result<(), CompileError> {
+        let a = &mut self.assembler;
+        // x -= (x >> 1) & 0x5555...
+        a.emit_alu_imm(Size::S64, AluImmOp::Srli, t1, x, 1)?;
+        a.emit_mov_imm(t2, 0x5555_5555_5555_5555)?;
+        a.emit_alu(Size::S64, AluOp::And, t1, t1, t2)?;
+        a.emit_alu(Size::S64, AluOp::Sub, x, x, t1)?;
+        // x = (x & 0x3333...) + ((x >> 2) & 0x3333...)
+        a.emit_mov_imm(t2, 0x3333_3333_3333_3333)?;
+        a.emit_alu_imm(Size::S64, AluImmOp::Srli, t1, x, 2)?;
+        a.emit_alu(Size::S64, AluOp::And, t1, t1, t2)?;
+        a.emit_alu(Size::S64, AluOp::And, x, x, t2)?;
+        a.emit_alu(Size::S64, AluOp::Add, x, x, t1)?;
+        // x = (x + (x >> 4)) & 0x0f0f...
+        a.emit_alu_imm(Size::S64, AluImmOp::Srli, t1, x, 4)?;
+        a.emit_alu(Size::S64, AluOp::Add, x, x, t1)?;
+        a.emit_mov_imm(t2, 0x0f0f_0f0f_0f0f_0f0f)?;
+        a.emit_alu(Size::S64, AluOp::And, x, x, t2)?;
+        // x = (x * 0x0101...) >> 56
+        a.emit_mov_imm(t2, 0x0101_0101_0101_0101)?;
+        a.emit_alu(Size::S64, AluOp::Mul, x, x, t2)?;
+        a.emit_alu_imm(Size::S64, AluImmOp::Srli, x, x, 56)
+    }
+    /// Emits clz, ctz or popcnt, there being no bit manipulation extension in RV64GC.
+    fn emit_bitcount(
+        &mut self,
+        sz: Size,
+        op: Operator,
+        loc: Location,
+        ret: Location,
+    ) -> Result<(), CompileError> {
+        let mut temps = vec![];
+        let src = self.location_to_reg(sz, loc, &mut temps, true)?;
+        let x = self.acquire_temp()?;
+        let t1 = self.acquire_temp()?;
+        let t2 = self.acquire_temp()?;
+        temps.extend([x, t1, t2]);
+        if sz == Size::S32 {
+            self.assembler.emit_zext32(x, src)?;
+        } else {
+            self.assembler.emit_mov(x, src)?;
+        }
+        match op {
+            Operator::I32Clz | Operator::I64Clz => {
+                // Smear the highest set bit to the right, and count the zeros left.
+                for shift in [1, 2, 4, 8, 16, 32] {
+                    self.assembler
+                        .emit_alu_imm(Size::S64, AluImmOp::Srli, t1, x, shift)?;
+                    self.assembler.emit_alu(Size::S64, AluOp::Or, x, x, t1)?;
+                }
+                self.assembler
+                    .emit_alu_imm(Size::S64, AluImmOp::Xori, x, x, -1)?;
+                self.emit_popcount(x, t1, t2)?;
+                if sz == Size::S32 {
+                    self.assembler
+                        .emit_alu_imm(Size::S64, AluImmOp::Addi, x, x, -32)?;
+                }
+            }
+            Operator::I32Ctz | Operator::I64Ctz => {
+                if sz == Size::S32 {
+                    // Bound the count to 32 for a zero input.
+                    self.assembler.emit_mov_imm(t1, 1 << 32)?;
+                    self.assembler.emit_alu(Size::S64, AluOp::Or, x, x, t1)?;
+                }
+                // Count the trailing zeros as the bits set in `!x & (x - 1)`.
+                self.assembler
+                    .emit_alu_imm(Size::S64, AluImmOp::Addi, t1, x, -1)?;
+                self.assembler
+                    .emit_alu_imm(Size::S64, AluImmOp::Xori, x, x, -1)?;
+                self.assembler.emit_alu(Size::S64, AluOp::And, x, x, t1)?;
+                self.emit_popcount(x, t1, t2)?;
+            }
+            _ => self.emit_popcount(x, t1, t2)?,
+        }
+        self.move_location(sz, Location::GPR(x), ret)?;
+        self.release_temps(temps, vec![]);
+        Ok(())
+    }
+    /// Emits a rotation, as the combination of two shifts.
+    fn emit_rotate(
+        &mut self,
+        sz: Size,
+        left: bool,
+        loc_a: Location,
+        loc_b: Location,
+        ret: Location,
+    ) -> Result<(), CompileError> {
+        let mut temps = vec![];
+        let a = self.location_to_reg(sz, loc_a, &mut temps, true)?;
+        let b = self.location_to_reg(sz, loc_b, &mut temps, true)?;
+        let t1 = self.acquire_temp()?;
+        let t2 = self.acquire_temp()?;
+        temps.extend([t1, t2]);
+        let (first, second) = if left {
+            (AluOp::Sll, AluOp::Srl)
+        } else {
+            (AluOp::Srl, AluOp::Sll)
+        };
+        // The shift amounts are taken modulo the operand width.
+        self.assembler.emit_alu(sz, first, t1, a, b)?;
+        self.assembler
+            .emit_alu(Size::S64, AluOp::Sub, t2, GPR::X0, b)?;
+        self.assembler.emit_alu(sz, second, t2, a, t2)?;
+        self.assembler.emit_alu(Size::S64, AluOp::Or, t1, t1, t2)?;
+        self.move_location(sz, Location::GPR(t1), ret)?;
+        self.release_temps(temps, vec![]);
+        Ok(())
+    }
+
+    #[allow(clippy::too_many_arguments)]
+    fn memory_op<F: FnOnce(&mut Self, GPR) -> Result<(), CompileError>>(
+        &mut self,
+        addr: Location,
+        memarg: &MemArg,
+        check_alignment: bool,
+        value_size: usize,
+        need_check: bool,
+        memory64: bool,
+        imported_memories: bool,
+        offset: i32,
+        heap_access_oob: Label,
+        unaligned_atomic: Label,
+        cb: F,
+    ) -> Result<(), CompileError> {
+        let tmp_addr = self.acquire_temp()?;
+
+        // Reusing `tmp_addr` for temporary indirection here, since it's not used before the last reference to `{base,bound}_loc`.
+        let (base_loc, bound_loc) = if imported_memories {
+            // Imported memories require one level of indirection.
+            self.emit_relaxed_load(Size::S64, false, tmp_addr, self.get_vmctx_reg(), offset)?;
+            ((tmp_addr, 0), (tmp_addr, 8))
+        } else {
+            (
+                (self.get_vmctx_reg(), offset),
+                (self.get_vmctx_reg(), offset + 8),
+            )
+        };
+
+        let tmp_base = self.acquire_temp()?;
+        let tmp_bound = self.acquire_temp()?;
+
+        // Load base into temporary register.
+        self.emit_relaxed_load(Size::S64, false, tmp_base, base_loc.0, base_loc.1)?;
+
+        // Load bound into temporary register, if needed.
+        if need_check {
+            self.emit_relaxed_load(Size::S64, false, tmp_bound, bound_loc.0, bound_loc.1)?;
+
+            // Wasm -> Effective.
+            // Assuming we never underflow - should always be true on Linux/macOS and Windows >=8,
+            // since the first page from 0x0 to 0x1000 is not accepted by mmap.
+            self.assembler
+                .emit_alu(Size::S64, AluOp::Add, tmp_bound, tmp_base, tmp_bound)?;
+            self.emit_add_imm(tmp_bound, tmp_bound, -(value_size as i64))?;
+        }
+
+        // Load effective address.
+        // `base_loc` and `bound_loc` becomes INVALID after this line, because `tmp_addr`
+        // might be reused.
+        // A 32-bit address is zero-extended by the move.
+        let addr_size = if memory64 { Size::S64 } else { Size::S32 };
+        self.move_location(addr_size, addr, Location::GPR(tmp_addr))?;
+
+        // Add offset to memory address.
+        if memarg.offset != 0 {
+            if memory64 {
+                let tmp = self.acquire_temp()?;
+                self.emit_add_imm(tmp, tmp_addr, memarg.offset as i64)?;
+                // Trap if offset calculation overflowed.
+                self.assembler.emit_bcond_label_far(
+                    Condition::Ltu,
+                    tmp,
+                    tmp_addr,
+                    heap_access_oob,
+                )?;
+                self.assembler.emit_mov(tmp_addr, tmp)?;
+                self.release_gpr(tmp);
+            } else {
+                self.emit_add_imm(tmp_addr, tmp_addr, memarg.offset as i64)?;
+                // Trap if the address doesn't fit in 32 bits anymore.
+                self.assembler
+                    .emit_alu_imm(Size::S64, AluImmOp::Srli, SCRATCH, tmp_addr, 32)?;
+                self.assembler.emit_bcond_label_far(
+                    Condition::Ne,
+                    SCRATCH,
+                    GPR::X0,
+                    heap_access_oob,
+                )?;
+            }
+        }
+
+        // Wasm linear memory -> real memory
+        self.assembler
+            .emit_alu(Size::S64, AluOp::Add, tmp_addr, tmp_base, tmp_addr)?;
+        if memory64 && need_check {
+            // A 64-bit address can wrap around when added to the base,
+            // which the bound check below wouldn't see.
+            self.assembler.emit_bcond_label_far(
+                Condition::Ltu,
+                tmp_addr,
+                tmp_base,
+                heap_access_oob,
+            )?;
+        }
+
+        if need_check {
+            // `tmp_bound` is inclusive. So trap only if `tmp_addr > tmp_bound`.
+            self.assembler.emit_bcond_label_far(
+                Condition::Ltu,
+                tmp_bound,
+                tmp_addr,
+                heap_access_oob,
+            )?;
+        }
+
+        self.release_gpr(tmp_bound);
+        self.release_gpr(tmp_base);
+
+        let align = value_size as u32;
+        if check_alignment && align != 1 {
+            self.assembler.emit_alu_imm(
+                Size::S64,
+                AluImmOp::Andi,
+                SCRATCH,
+                tmp_addr,
+                (align - 1) as i32,
+            )?;
+            self.assembler.emit_bcond_label_far(
+                Condition::Ne,
+                SCRATCH,
+                GPR::X0,
+                unaligned_atomic,
+            )?;
+        }
+        let begin = self.assembler.get_offset().0;
+        cb(self, tmp_addr)?;
+        let end = self.assembler.get_offset().0;
+        self.mark_address_range_with_trap_code(TrapCode::HeapAccessOutOfBounds, begin, end);
+
+        self.release_gpr(tmp_addr);
+        Ok(())
+    }
+    /// Loads `sz` bytes at `addr` into `ret`, extended to 64 bits.
+    fn emit_load_to(
+        &mut self,
+        sz: Size,
+        signed: bool,
+        ret: Location,
+        addr: GPR,
+    ) -> Result<(), CompileError> {
+        let mut temps = vec![];
+        let dest = self.location_to_reg(Size::S64, ret, &mut temps, false)?;
+        self.assembler.emit_load(sz, signed, dest, addr, 0)?;
+        if ret != Location::GPR(dest) {
+            self.move_location(Size::S64, Location::GPR(dest), ret)?;
+        }
+        self.release_temps(temps, vec![]);
+        Ok(())
+    }
+    /// Stores the `sz` low bytes of `value` at `addr`.
+    fn emit_store_from(
+        &mut self,
+        sz: Size,
+        value: Location,
+        addr: GPR,
+    ) -> Result<(), CompileError> {
+        let mut temps = vec![];
+        let src = self.location_to_reg(sz, value, &mut temps, true)?;
+        self.assembler.emit_store(sz, src, addr, 0)?;
+        self.release_temps(temps, vec![]);
+        Ok(())
+    }
+    /// Computes the bit offset of a sub-word value at `addr` into `shift`, the
+    /// mask of the value within its aligned word into `mask`, and aligns `addr`.
+    fn emit_subword_setup(
+        &mut self,
+        sz: Size,
+        addr: GPR,
+        shift: GPR,
+        mask: GPR,
+    ) -> Result<(), CompileError> {
+        self.assembler
+            .emit_alu_imm(Size::S64, AluImmOp::Andi, shift, addr, 3)?;
+        self.assembler
+            .emit_alu_imm(Size::S64, AluImmOp::Slli, shift, shift, 3)?;
+        self.assembler
+            .emit_alu_imm(Size::S64, AluImmOp::Andi, addr, addr, -4)?;
+        let bits = if sz == Size::S8 { 0xff } else { 0xffff };
+        self.assembler.emit_mov_imm(mask, bits)
+    }
+    /// Extracts the sub-word value at bit `shift` of `word` into `dst`.
+    fn emit_subword_extract(
+        &mut self,
+        sz: Size,
+        dst: GPR,
+        word: GPR,
+        shift: GPR,
+    ) -> Result<(), CompileError> {
+        self.assembler
+            .emit_alu(Size::S64, AluOp::Srl, dst, word, shift)?;
+        self.emit_extend(sz, false, dst, dst)
+    }
+    /// Emits an atomic read-modify-write of `sz` bytes at `addr`, the old value
+    /// being zero-extended into `ret`. Sub-word operations use a LR/SC loop on
+    /// the aligned word.
+    fn emit_atomic_rmw(
+        &mut self,
+        sz: Size,
+        op: AtomicOp,
+        loc: Location,
+        addr: GPR,
+        ret: Location,
+    ) -> Result<(), CompileError> {
+        let mut temps = vec![];
+        let val = self.location_to_reg(Size::S64, loc, &mut temps, true)?;
+        let old = self.acquire_temp()?;
+        temps.push(old);
+        match sz {
+            Size::S32 | Size::S64 => {
+                let (amo, val) = match op {
+                    AtomicOp::Add => (AmoOp::Add, val),
+                    AtomicOp::Sub => {
+                        let neg = self.own_temp(val, &mut temps)?;
+                        self.assembler
+                            .emit_alu(Size::S64, AluOp::Sub, neg, GPR::X0, val)?;
+                        (AmoOp::Add, neg)
+                    }
+                    AtomicOp::And => (AmoOp::And, val),
+                    AtomicOp::Or => (AmoOp::Or, val),
+                    AtomicOp::Xor => (AmoOp::Xor, val),
+                    AtomicOp::Xchg => (AmoOp::Swap, val),
+                };
+                self.assembler.emit_amo(sz, amo, old, addr, val)?;
+                if sz == Size::S32 {
+                    self.assembler.emit_zext32(old, old)?;
+                }
+            }
+            _ => {
+                let shift = self.acquire_temp()?;
+                let mask = self.acquire_temp()?;
+                let new = self.acquire_temp()?;
+                temps.extend([shift, mask, new]);
+                let val_sh = self.own_temp(val, &mut temps)?;
+                self.emit_subword_setup(sz, addr, shift, mask)?;
+                self.assembler
+                    .emit_alu(Size::S64, AluOp::And, val_sh, val, mask)?;
+                self.assembler
+                    .emit_alu(Size::S64, AluOp::Sll, val_sh, val_sh, shift)?;
+                self.assembler
+                    .emit_alu(Size::S64, AluOp::Sll, mask, mask, shift)?;
+                if op == AtomicOp::And {
+                    // Keep the bits outside of the value.
+                    self.assembler
+                        .emit_alu_imm(Size::S64, AluImmOp::Xori, new, mask, -1)?;
+                    self.assembler
+                        .emit_alu(Size::S64, AluOp::Or, val_sh, val_sh, new)?;
+                }
+                let retry = self.assembler.get_label();
+                self.assembler.emit_label(retry)?;
+                self.assembler.emit_lr(Size::S32, old, addr)?;
+                let (alu, merge) = match op {
+                    AtomicOp::Add => (AluOp::Add, true),
+                    AtomicOp::Sub => (AluOp::Sub, true),
+                    AtomicOp::And => (AluOp::And, false),
+                    AtomicOp::Or => (AluOp::Or, false),
+                    AtomicOp::Xor => (AluOp::Xor, false),
+                    AtomicOp::Xchg => (AluOp::Xor, true),
+                };
+                if op == AtomicOp::Xchg {
+                    self.assembler.emit_mov(new, val_sh)?;
+                } else {
+                    self.assembler.emit_alu(Size::S64, alu, new, old, val_sh)?;
+                }
+                if merge {
+                    // new = ((new ^ old) & mask) ^ old
+                    self.assembler
+                        .emit_alu(Size::S64, AluOp::Xor, new, new, old)?;
+                    self.assembler
+                        .emit_alu(Size::S64, AluOp::And, new, new, mask)?;
+                    self.assembler
+                        .emit_alu(Size::S64, AluOp::Xor, new, new, old)?;
+                }
+                self.assembler.emit_sc(Size::S32, SCRATCH, addr, new)?;
+                self.assembler
+                    .emit_bcond_label(Condition::Ne, SCRATCH, GPR::X0, retry)?;
+                self.emit_subword_extract(sz, old, old, shift)?;
+            }
+        }
+        self.move_location(Size::S64, Location::GPR(old), ret)?;
+        self.release_temps(temps, vec![]);
+        Ok(())
+    }
+    /// Emits an atomic compare-and-exchange of `sz` bytes at `addr`, the old
+    /// value being zero-extended into `ret`.
+    fn emit_atomic_cmpxchg(
+        &mut self,
+        sz: Size,
+        new: Location,
+        cmp: Location,
+        addr: GPR,
+        ret: Location,
+    ) -> Result<(), CompileError> {
+        let mut temps = vec![];
+        let new = self.location_to_reg(Size::S64, new, &mut temps, true)?;
+        let cmp = self.location_to_reg(Size::S64, cmp, &mut temps, true)?;
+        let old = self.acquire_temp()?;
+        temps.push(old);
+        let retry = self.assembler.get_label();
+        let done = self.assembler.get_label();
+        match sz {
+            Size::S32 | Size::S64 => {
+                // `lr.w` sign-extends the loaded value.
+                let cmp = if sz == Size::S32 {
+                    self.sext_temp(cmp, &mut temps)?
+                } else {
+                    cmp
+                };
+                self.assembler.emit_label(retry)?;
+                self.assembler.emit_lr(sz, old, addr)?;
+                self.assembler
+                    .emit_bcond_label(Condition::Ne, old, cmp, done)?;
+                self.assembler.emit_sc(sz, SCRATCH, addr, new)?;
+                self.assembler
+                    .emit_bcond_label(Condition::Ne, SCRATCH, GPR::X0, retry)?;
+                self.assembler.emit_label(done)?;
+                if sz == Size::S32 {
+                    self.assembler.emit_zext32(old, old)?;
+                }
+            }
+            _ => {
+                let shift = self.acquire_temp()?;
+                let mask = self.acquire_temp()?;
+                let tmp = self.acquire_temp()?;
+                temps.extend([shift, mask, tmp]);
+                let new_sh = self.own_temp(new, &mut temps)?;
+                let cmp_sh = self.own_temp(cmp, &mut temps)?;
+                self.emit_subword_setup(sz, addr, shift, mask)?;
+                self.assembler
+                    .emit_alu(Size::S64, AluOp::And, new_sh, new, mask)?;
+                self.assembler
+                    .emit_alu(Size::S64, AluOp::Sll, new_sh, new_sh, shift)?;
+                self.assembler
+                    .emit_alu(Size::S64, AluOp::And, cmp_sh, cmp, mask)?;
+                self.assembler
+                    .emit_alu(Size::S64, AluOp::Sll, cmp_sh, cmp_sh, shift)?;
+                self.assembler
+                    .emit_alu(Size::S64, AluOp::Sll, mask, mask, shift)?;
+                self.assembler.emit_label(retry)?;
+                self.assembler.emit_lr(Size::S32, old, addr)?;
+                self.assembler
+                    .emit_alu(Size::S64, AluOp::And, tmp, old, mask)?;
+                self.assembler
+                    .emit_bcond_label(Condition::Ne, tmp, cmp_sh, done)?;
+                // tmp = ((old ^ new) & mask) ^ old
+                self.assembler
+                    .emit_alu(Size::S64, AluOp::Xor, tmp, old, new_sh)?;
+                self.assembler
+                    .emit_alu(Size::S64, AluOp::And, tmp, tmp, mask)?;
+                self.assembler
+                    .emit_alu(Size::S64, AluOp::Xor, tmp, tmp, old)?;
+                self.assembler.emit_sc(Size::S32, SCRATCH, addr, tmp)?;
+                self.assembler
+                    .emit_bcond_label(Condition::Ne, SCRATCH, GPR::X0, retry)?;
+                self.assembler.emit_label(done)?;
+                self.emit_subword_extract(sz, old, old, shift)?;
+            }
+        }
+        self.move_location(Size::S64, Location::GPR(old), ret)?;
+        self.release_temps(temps, vec![]);
+        Ok(())
+    }
+
+    /// Emits a floating-point operation on `loc_a` and `loc_b`.
+    fn emit_relaxed_fop3(
+        &mut self,
+        sz: Size,
+        op: FpOp,
+        loc_a: Location,
+        loc_b: Location,
+        ret: Location,
+    ) -> Result<(), CompileError> {
+        let mut fprs = vec![];
+        let a = self.location_to_freg(sz, loc_a, &mut fprs, true)?;
+        let b = if loc_b == loc_a {
+            a
+        } else {
+            self.location_to_freg(sz, loc_b, &mut fprs, true)?
+        };
+        let dest = self.location_to_freg(sz, ret, &mut fprs, false)?;
+        self.assembler.emit_fop(sz, op, dest, a, b)?;
+        if ret != Location::SIMD(dest) {
+            self.move_location(sz, Location::SIMD(dest), ret)?;
+        }
+        self.release_temps(vec![], fprs);
+        Ok(())
+    }
+    /// Emits a floating-point minimum or maximum, NaN operands giving the canonical NaN.
+    fn emit_fminmax(
+        &mut self,
+        sz: Size,
+        op: FpOp,
+        loc_a: Location,
+        loc_b: Location,
+        ret: Location,
+    ) -> Result<(), CompileError> {
+        let mut fprs = vec![];
+        let a = self.location_to_freg(sz, loc_a, &mut fprs, true)?;
+        let b = self.location_to_freg(sz, loc_b, &mut fprs, true)?;
+        let dest = self.location_to_freg(sz, ret, &mut fprs, false)?;
+        let t1 = self.acquire_temp()?;
+        let t2 = self.acquire_temp()?;
+        let nan = self.assembler.get_label();
+        let end = self.assembler.get_label();
+        self.assembler.emit_fcmp(sz, FpCmp::Eq, t1, a, a)?;
+        self.assembler.emit_fcmp(sz, FpCmp::Eq, t2, b, b)?;
+        self.assembler.emit_alu(Size::S64, AluOp::And, t1, t1, t2)?;
+        self.assembler
+            .emit_bcond_label(Condition::Eq, t1, GPR::X0, nan)?;
+        self.assembler.emit_fop(sz, op, dest, a, b)?;
+        self.assembler.emit_j_label(end)?;
+        self.assembler.emit_label(nan)?;
+        // `fmin` and `fmax` return the other operand when one is NaN.
+        self.assembler.emit_fop(sz, FpOp::Add, dest, a, b)?;
+        self.assembler.emit_label(end)?;
+        if ret != Location::SIMD(dest) {
+            self.move_location(sz, Location::SIMD(dest), ret)?;
+        }
+        self.release_gpr(t2);
+        self.release_gpr(t1);
+        self.release_temps(vec![], fprs);
+        Ok(())
+    }
+    fn emit_fsqrt(&mut self, sz: Size, loc: Location, ret: Location) -> Result<(), CompileError> {
+        let mut fprs = vec![];
+        let src = self.location_to_freg(sz, loc, &mut fprs, true)?;
+        let dest = self.location_to_freg(sz, ret, &mut fprs, false)?;
+        self.assembler.emit_fsqrt(sz, dest, src)?;
+        if ret != Location::SIMD(dest) {
+            self.move_location(sz, Location::SIMD(dest), ret)?;
+        }
+        self.release_temps(vec![], fprs);
+        Ok(())
+    }
+    /// Sets `ret` to the result of the floating-point comparison of `loc_a` and
+    /// `loc_b`, inverted when `negate` is set.
+    fn emit_fcmpop(
+        &mut self,
+        sz: Size,
+        cmp: FpCmp,
+        negate: bool,
+        loc_a: Location,
+        loc_b: Location,
+        ret: Location,
+    ) -> Result<(), CompileError> {
+        let mut temps = vec![];
+        let mut fprs = vec![];
+        let a = self.location_to_freg(sz, loc_a, &mut fprs, true)?;
+        let b = self.location_to_freg(sz, loc_b, &mut fprs, true)?;
+        let dest = self.location_to_reg(Size::S32, ret, &mut temps, false)?;
+        self.assembler.emit_fcmp(sz, cmp, dest, a, b)?;
+        if negate {
+            self.assembler
+                .emit_alu_imm(Size::S64, AluImmOp::Xori, dest, dest, 1)?;
+        }
+        if ret != Location::GPR(dest) {
+            self.move_location(Size::S32, Location::GPR(dest), ret)?;
+        }
+        self.release_temps(temps, fprs);
+        Ok(())
+    }
+    /// Rounds to an integral value with the rounding mode `rm`, by converting
+    /// to an integer and back when the value isn't already integral.
+    fn emit_fround(
+        &mut self,
+        sz: Size,
+        rm: RoundingMode,
+        loc: Location,
+        ret: Location,
+    ) -> Result<(), CompileError> {
+        let mut fprs = vec![];
+        let src = self.location_to_freg(sz, loc, &mut fprs, true)?;
+        let dest = self.location_to_freg(sz, ret, &mut fprs, false)?;
+        let limit = self.acquire_temp_fpr()?;
+        let abs = self.acquire_temp_fpr()?;
+        fprs.extend([limit, abs]);
+        let tmp = self.acquire_temp()?;
+        let large = self.assembler.get_label();
+        let end = self.assembler.get_label();
+
+        // Values from 2^52 (2^23 for f32) up are integral.
+        let limit_bits: u64 = if sz == Size::S32 {
+            0x4b00_0000
+        } else {
+            0x4330_0000_0000_0000
+        };
+        self.assembler.emit_mov_imm(tmp, limit_bits)?;
+        self.assembler.emit_fmv_from_gpr(sz, limit, tmp)?;
+        self.assembler.emit_fop(sz, FpOp::Sgnjx, abs, src, src)?;
+        self.assembler.emit_fcmp(sz, FpCmp::Lt, tmp, abs, limit)?;
+        self.assembler
+            .emit_bcond_label(Condition::Eq, tmp, GPR::X0, large)?;
+        self.assembler
+            .emit_fcvt_to_int(sz, Size::S64, true, tmp, src, rm)?;
+        self.assembler
+            .emit_fcvt_from_int(Size::S64, true, sz, dest, tmp)?;
+        // Keep the sign of the input, for results rounded to zero.
+        self.assembler.emit_fop(sz, FpOp::Sgnj, dest, dest, src)?;
+        self.assembler.emit_j_label(end)?;
+        self.assembler.emit_label(large)?;
+        // Integral value, infinity or NaN, which gets canonicalized.
+        self.assembler.emit_fop(sz, FpOp::Min, dest, src, src)?;
+        self.assembler.emit_label(end)?;
+        if ret != Location::SIMD(dest) {
+            self.move_location(sz, Location::SIMD(dest), ret)?;
+        }
+        self.release_gpr(tmp);
+        self.release_temps(vec![], fprs);
+        Ok(())
+    }
+    /// Converts the `sz_in` integer `loc` to a `sz_out` float.
+    fn emit_convert_from_int(
+        &mut self,
+        sz_in: Size,
+        signed: bool,
+        sz_out: Size,
+        loc: Location,
+        ret: Location,
+    ) -> Result<(), CompileError> {
+        let mut temps = vec![];
+        let mut fprs = vec![];
+        let src = self.location_to_reg(sz_in, loc, &mut temps, true)?;
+        let dest = self.location_to_freg(sz_out, ret, &mut fprs, false)?;
+        self.assembler
+            .emit_fcvt_from_int(sz_in, signed, sz_out, dest, src)?;
+        if ret != Location::SIMD(dest) {
+            self.move_location(sz_out, Location::SIMD(dest), ret)?;
+        }
+        self.release_temps(temps, fprs);
+        Ok(())
+    }
+    /// Truncates the `sz_in` float `loc` to a `sz_out` integer. Invalid
+    /// conversions trap, or saturate when `sat` is set.
+    fn emit_convert_to_int(
+        &mut self,
+        sz_in: Size,
+        sz_out: Size,
+        signed: bool,
+        sat: bool,
+        loc: Location,
+        ret: Location,
+    ) -> Result<(), CompileError> {
+        let mut temps = vec![];
+        let mut fprs = vec![];
+        let src = self.location_to_freg(sz_in, loc, &mut fprs, true)?;
+        let dest = self.location_to_reg(sz_out, ret, &mut temps, false)?;
+        let tmp = self.acquire_temp()?;
+        temps.push(tmp);
+        if !sat {
+            self.assembler.emit_clear_fflags()?;
+        }
+        // Out of range values are saturated by the conversion.
+        self.assembler
+            .emit_fcvt_to_int(sz_in, sz_out, signed, dest, src, RoundingMode::Rtz)?;
+        self.assembler.emit_fcmp(sz_in, FpCmp::Eq, tmp, src, src)?;
+        if sat {
+            // NaN gives 0.
+            self.assembler
+                .emit_alu(Size::S64, AluOp::Sub, tmp, GPR::X0, tmp)?;
+            self.assembler
+                .emit_alu(Size::S64, AluOp::And, dest, dest, tmp)?;
+        } else {
+            let trap_badconv = self.assembler.get_label();
+            let end = self.assembler.get_label();
+            self.assembler
+                .emit_bcond_label(Condition::Eq, tmp, GPR::X0, trap_badconv)?;
+            // Check the invalid operation flag.
+            self.assembler.emit_read_fflags(tmp)?;
+            self.assembler
+                .emit_alu_imm(Size::S64, AluImmOp::Andi, tmp, tmp, 0x10)?;
+            self.assembler
+                .emit_bcond_label(Condition::Eq, tmp, GPR::X0, end)?;
+            self.emit_illegal_op_internal(TrapCode::IntegerOverflow)?;
+            self.emit_label(trap_badconv)?;
+            self.emit_illegal_op_internal(TrapCode::BadConversionToInteger)?;
+            self.emit_label(end)?;
+        }
+        if ret != Location::GPR(dest) {
+            self.move_location(sz_out, Location::GPR(dest), ret)?;
+        }
+        self.release_temps(temps, fprs);
+        Ok(())
+    }
+    fn emit_convert_float(
+        &mut self,
+        sz_in: Size,
+        sz_out: Size,
+        loc: Location,
+        ret: Location,
+    ) -> Result<(), CompileError> {
+        let mut fprs = vec![];
+        let src = self.location_to_freg(sz_in, loc, &mut fprs, true)?;
+        let dest = self.location_to_freg(sz_out, ret, &mut fprs, false)?;
+        self.assembler.emit_fcvt_float(sz_in, sz_out, dest, src)?;
+        if ret != Location::SIMD(dest) {
+            self.move_location(sz_out, Location::SIMD(dest), ret)?;
+        }
+        self.release_temps(vec![], fprs);
+        Ok(())
+    }
+
+    fn emit_push(&mut self, sz: Size, src: Location) -> Result<(), CompileError> {
+        if sz != Size::S64 {
+            codegen_error!("singlepass can't emit PUSH {:?} {:?}", sz, src);
+        }
+        let src = match src {
+            Location::GPR(_) | Location::SIMD(_) => src,
+            _ => {
+                self.move_location(Size::S64, src, Location::GPR(SCRATCH))?;
+                Location::GPR(SCRATCH)
+            }
+        };
+        let offset = if self.pushed {
+            0
+        } else {
+            self.emit_adjust_sp(-16)?;
+            8
+        };
+        match src {
+            Location::GPR(r) => self.assembler.emit_store(Size::S64, r, GPR::X2, offset)?,
+            Location::SIMD(r) => self.assembler.emit_fstore(Size::S64, r, GPR::X2, offset)?,
+            _ => unreachable!(),
+        }
+        self.pushed = !self.pushed;
+        Ok(())
+    }
+    fn emit_double_push(
+        &mut self,
+        sz: Size,
+        src1: Location,
+        src2: Location,
+    ) -> Result<(), CompileError> {
+        match (self.pushed, sz, src1, src2) {
+            (false, Size::S64, Location::GPR(r1), Location::GPR(r2)) => {
+                self.emit_adjust_sp(-16)?;
+                self.assembler.emit_store(Size::S64, r1, GPR::X2, 0)?;
+                self.assembler.emit_store(Size::S64, r2, GPR::X2, 8)?;
+            }
+            _ => {
+                self.emit_push(sz, src1)?;
+                self.emit_push(sz, src2)?;
+            }
+        }
+        Ok(())
+    }
+    fn emit_pop(&mut self, sz: Size, dst: Location) -> Result<(), CompileError> {
+        let offset = if self.pushed { 8 } else { 0 };
+        match (sz, dst) {
+            (Size::S64, Location::GPR(r)) => {
+                self.assembler
+                    .emit_load(Size::S64, false, r, GPR::X2, offset)?
+            }
+            (Size::S64, Location::SIMD(r)) => {
+                self.assembler.emit_fload(Size::S64, r, GPR::X2, offset)?
+            }
+            _ => codegen_error!("singlepass can't emit POP {:?} {:?}", sz, dst),
+        }
+        if self.pushed {
+            self.emit_adjust_sp(16)?;
+        }
+        self.pushed = !self.pushed;
+        Ok(())
+    }
+    fn emit_double_pop(
+        &mut self,
+        sz: Size,
+        dst1: Location,
+        dst2: Location,
+    ) -> Result<(), CompileError> {
+        match (self.pushed, sz, dst1, dst2) {
+            (false, Size::S64, Location::GPR(r1), Location::GPR(r2)) => {
+                self.assembler.emit_load(Size::S64, false, r1, GPR::X2, 0)?;
+                self.assembler.emit_load(Size::S64, false, r2, GPR::X2, 8)?;
+                self.emit_adjust_sp(16)?;
+            }
+            _ => {
+                self.emit_pop(sz, dst2)?;
+                self.emit_pop(sz, dst1)?;
+            }
+        }
+        Ok(())
+    }
+
+    fn used_gprs_contains(&self, r: &GPR) -> bool {
+        self.used_gprs & (1 << r.into_index()) != 0
+    }
+    fn used_fprs_contains(&self, r: &FPR) -> bool {
+        self.used_fprs & (1 << r.into_index()) != 0
+    }
+    fn used_gprs_insert(&mut self, r: GPR) {
+        self.used_gprs |= 1 << r.into_index();
+    }
+    fn used_fprs_insert(&mut self, r: FPR) {
+        self.used_fprs |= 1 << r.into_index();
+    }
+    fn used_gprs_remove(&mut self, r: &GPR) -> bool {
+        let ret = self.used_gprs_contains(r);
+        self.used_gprs &= !(1 << r.into_index());
+        ret
+    }
+    fn used_fprs_remove(&mut self, r: &FPR) -> bool {
+        let ret = self.used_fprs_contains(r);
+        self.used_fprs &= !(1 << r.into_index());
+        ret
+    }
+    fn emit_unwind_op(&mut self, op: UnwindOps) {
+        self.unwind_ops.push((self.get_offset().0, op));
+    }
+    fn emit_illegal_op_internal(&mut self, trap: TrapCode) -> Result<(), CompileError> {
+        self.assembler.emit_udf(0xc0 | (trap as u8))
+    }
+}
+
+impl Machine for MachineRiscv {
+    type GPR = GPR;
+    type SIMD = FPR;
+    fn assembler_get_offset(&self) -> Offset {
+        self.assembler.get_offset()
+    }
+    fn index_from_gpr(&self, x: GPR) -> RegisterIndex {
+        RegisterIndex(x as usize)
+    }
+    fn index_from_simd(&self, x: FPR) -> RegisterIndex {
+        RegisterIndex(x as usize + 32)
+    }
+
+    fn get_vmctx_reg(&self) -> GPR {
+        GPR::X27
+    }
+
+    fn get_used_gprs(&self) -> Vec<GPR> {
+        GPR::iterator()
+            .filter(|x| self.used_gprs & (1 << x.into_index()) != 0)
+            .cloned()
+            .collect()
+    }
+
+    fn get_used_simd(&self) -> Vec<FPR> {
+        FPR::iterator()
+            .filter(|x| self.used_fprs & (1 << x.into_index()) != 0)
+            .cloned()
+            .collect()
+    }
+
+    fn pick_gpr(&self) -> Option<GPR> {
+        use GPR::*;
+        static REGS: &[GPR] = &[X5, X6, X7, X28, X29, X30];
+        for r in REGS {
+            if !self.used_gprs_contains(r) {
+                return Some(*r);
+            }
+        }
+        None
+    }
+
+    // Picks an unused general purpose register for internal temporary use.
+    fn pick_temp_gpr(&self) -> Option<GPR> {
+        use GPR::*;
+        static REGS: &[GPR] = &[X17, X16, X15, X14, X13, X12, X11];
+        for r in REGS {
+            if !self.used_gprs_contains(r) {
+                return Some(*r);
+            }
+        }
+        None
+    }
+
+    fn acquire_temp_gpr(&mut self) -> Option<GPR> {
+        let gpr = self.pick_temp_gpr();
+        if let Some(x) = gpr {
+            self.used_gprs_insert(x);
+        }
+        gpr
+    }
+
+    fn release_gpr(&mut self, gpr: GPR) {
+        assert!(self.used_gprs_remove(&gpr));
+    }
+
+    fn reserve_unused_temp_gpr(&mut self, gpr: GPR) -> GPR {
+        assert!(!self.used_gprs_contains(&gpr));
+        self.used_gprs_insert(gpr);
+        gpr
+    }
+
+    fn reserve_gpr(&mut self, gpr: GPR) {
+        self.used_gprs_insert(gpr);
+    }
+
+    fn push_used_gpr(&mut self, used_gprs: &[GPR]) -> Result<usize, CompileError> {
+        if used_gprs.len() % 2 == 1 {
+            self.emit_push(Size::S64, Location::GPR(GPR::X0))?;
+        }
+        for r in used_gprs.iter() {
+            self.emit_push(Size::S64, Location::GPR(*r))?;
+        }
+        Ok(((used_gprs.len() + 1) / 2) * 16)
+    }
+    fn pop_used_gpr(&mut self, used_gprs: &[GPR]) -> Result<(), CompileError> {
+        for r in used_gprs.iter().rev() {
+            self.emit_pop(Size::S64, Location::GPR(*r))?;
+        }
+        if used_gprs.len() % 2 == 1 {
+            self.emit_pop(Size::S64, Location::GPR(GPR::X0))?;
+        }
+        Ok(())
+    }
+
+    // Values are never allocated to FPRs, floats live in GPRs or on the stack.
+    fn pick_simd(&self) -> Option<FPR> {
+        None
+    }
+
+    // Picks an unused FPR for internal temporary use.
+    fn pick_temp_simd(&self) -> Option<FPR> {
+        use FPR::*;
+        static REGS: &[FPR] = &[F0, F1, F2, F3, F4, F5, F6, F7, F28, F29, F30, F31];
+        for r in REGS {
+            if !self.used_fprs_contains(r) {
+                return Some(*r);
+            }
+        }
+        None
+    }
+
+    // Acquires a temporary FPR.
+    fn acquire_temp_simd(&mut self) -> Option<FPR> {
+        let fpr = self.pick_temp_simd();
+        if let Some(x) = fpr {
+            self.used_fprs_insert(x);
+        }
+        fpr
+    }
+
+    fn reserve_simd(&mut self, fpr: FPR) {
+        self.used_fprs_insert(fpr);
+    }
+
+    // Releases a temporary FPR.
+    fn release_simd(&mut self, fpr: FPR) {
+        assert!(self.used_fprs_remove(&fpr));
+    }
+
+    fn push_used_simd(&mut self, used_fprs: &[FPR]) -> Result<usize, CompileError> {
+        let stack_adjust = if used_fprs.len() & 1 == 1 {
+            (used_fprs.len() * 8) as u32 + 8
+        } else {
+            (used_fprs.len() * 8) as u32
+        };
+        self.adjust_stack(stack_adjust)?;
+
+        for (i, r) in used_fprs.iter().enumerate() {
+            self.emit_relaxed_fstore(Size::S64, *r, GPR::X2, (i * 8) as i32)?;
+        }
+        Ok(stack_adjust as usize)
+    }
+    fn pop_used_simd(&mut self, used_fprs: &[FPR]) -> Result<(), CompileError> {
+        for (i, r) in used_fprs.iter().enumerate() {
+            self.emit_relaxed_fload(Size::S64, *r, GPR::X2, (i * 8) as i32)?;
+        }
+        let stack_adjust = if used_fprs.len() & 1 == 1 {
+            (used_fprs.len() * 8) as u32 + 8
+        } else {
+            (used_fprs.len() * 8) as u32
+        };
+        self.restore_stack(stack_adjust)
+    }
+
+    /// Set the source location of the Wasm to the given offset.
+    fn set_srcloc(&mut self, offset: u32) {
+        self.src_loc = offset;
+    }
+    /// Marks each address in the code range emitted by `f` with the trap code `code`.
+    fn mark_address_range_with_trap_code(&mut self, code: TrapCode, begin: usize, end: usize) {
+        for i in begin..end {
+            self.trap_table.offset_to_code.insert(i, code);
+        }
+        self.mark_instruction_address_end(begin);
+    }
+
+    /// Marks one address as trappable with trap code `code`.
+    fn mark_address_with_trap_code(&mut self, code: TrapCode) {
+        let offset = self.assembler.get_offset().0;
+        self.trap_table.offset_to_code.insert(offset, code);
+        self.mark_instruction_address_end(offset);
+    }
+    /// Marks the instruction as trappable with trap code `code`. return "begin" offset
+    fn mark_instruction_with_trap_code(&mut self, code: TrapCode) -> usize {
+        let offset = self.assembler.get_offset().0;
+        self.trap_table.offset_to_code.insert(offset, code);
+        offset
+    }
+    /// Pushes the instruction to the address map, calculating the offset from a
+    /// provided beginning address.
+    fn mark_instruction_address_end(&mut self, begin: usize) {
+        self.instructions_address_map.push(InstructionAddressMap {
+            srcloc: SourceLoc::new(self.src_loc),
+            code_offset: begin,
+            code_len: self.assembler.get_offset().0 - begin,
+        });
+    }
+
+    /// Insert a StackOverflow (at offset 0)
+    fn insert_stackoverflow(&mut self) {
+        let offset = 0;
+        self.trap_table
+            .offset_to_code
+            .insert(offset, TrapCode::StackOverflow);
+        self.mark_instruction_address_end(offset);
+    }
+
+    /// Get all current TrapInformation
+    fn collect_trap_information(&self) -> Vec<TrapInformation> {
+        self.trap_table
+            .offset_to_code
+            .clone()
+            .into_iter()
+            .map(|(offset, code)| TrapInformation {
+                code_offset: offset as u32,
+                trap_code: code,
+            })
+            .collect()
+    }
+
+    fn instructions_address_map(&self) -> Vec<InstructionAddressMap> {
+        self.instructions_address_map.clone()
+    }
+
+    // Return a rounded stack adjustement value (the stack is 16 bytes aligned on RISC-V)
+    fn round_stack_adjust(&self, value: usize) -> usize {
+        if value & 0xf != 0 {
+            ((value >> 4) + 1) << 4
+        } else {
+            value
+        }
+    }
+
+    // Memory location for a local on the stack
+    fn local_on_stack(&mut self, stack_offset: i32) -> Location {
+        Location::Memory(GPR::X8, -stack_offset)
+    }
+
+    // Adjust stack for locals
+    fn adjust_stack(&mut self, delta_stack_offset: u32) -> Result<(), CompileError> {
+        self.emit_adjust_sp(-(delta_stack_offset as i64))
+    }
+    // restore stack
+    fn restore_stack(&mut self, delta_stack_offset: u32) -> Result<(), CompileError> {
+        self.emit_adjust_sp(delta_stack_offset as i64)
+    }
+    fn pop_stack_locals(&mut self, delta_stack_offset: u32) -> Result<(), CompileError> {
+        let real_delta = if delta_stack_offset & 15 != 0 {
+            delta_stack_offset + 8
+        } else {
+            delta_stack_offset
+        };
+        self.emit_adjust_sp(real_delta as i64)
+    }
+    // push a value on the stack for a native call
+    fn move_location_for_native(
+        &mut self,
+        size: Size,
+        loc: Location,
+        dest: Location,
+    ) -> Result<(), CompileError> {
+        match loc {
+            Location::Imm64(_)
+            | Location::Imm32(_)
+            | Location::Imm8(_)
+            | Location::Memory(_, _)
+            | Location::Memory2(_, _, _, _) => {
+                let tmp = self.acquire_temp()?;
+                self.move_location(size, loc, Location::GPR(tmp))?;
+                self.move_location(size, Location::GPR(tmp), dest)?;
+                self.release_gpr(tmp);
+                Ok(())
+            }
+            _ => self.move_location(size, loc, dest),
+        }
+    }
+
+    // Zero a location that is 32bits
+    fn zero_location(&mut self, size: Size, location: Location) -> Result<(), CompileError> {
+        self.move_location(size, Location::GPR(GPR::X0), location)
+    }
+
+    // GPR Reg used for local pointer on the stack
+    fn local_pointer(&self) -> GPR {
+        GPR::X8
+    }
+
+    // Determine whether a local should be allocated on the stack.
+    fn is_local_on_stack(&self, idx: usize) -> bool {
+        idx > 7
+    }
+
+    // Determine a local's location.
+    fn get_local_location(&self, idx: usize, callee_saved_regs_size: usize) -> Location {
+        // Use callee-saved registers for the first locals.
+        match idx {
+            0 => Location::GPR(GPR::X18),
+            1 => Location::GPR(GPR::X19),
+            2 => Location::GPR(GPR::X20),
+            3 => Location::GPR(GPR::X21),
+            4 => Location::GPR(GPR::X22),
+            5 => Location::GPR(GPR::X23),
+            6 => Location::GPR(GPR::X24),
+            7 => Location::GPR(GPR::X25),
+            _ => Location::Memory(GPR::X8, -(((idx - 7) * 8 + callee_saved_regs_size) as i32)),
+        }
+    }
+    // Move a local to the stack
+    fn move_local(&mut self, stack_offset: i32, location: Location) -> Result<(), CompileError> {
+        match location {
+            Location::GPR(x) => {
+                self.emit_relaxed_store(Size::S64, x, GPR::X8, -stack_offset)?;
+                self.emit_unwind_op(UnwindOps::SaveRegister {
+                    reg: x.to_dwarf(),
+                    bp_neg_offset: stack_offset,
+                });
+            }
+            Location::SIMD(x) => {
+                self.emit_relaxed_fstore(Size::S64, x, GPR::X8, -stack_offset)?;
+                self.emit_unwind_op(UnwindOps::SaveRegister {
+                    reg: x.to_dwarf(),
+                    bp_neg_offset: stack_offset,
+                });
+            }
+            _ => codegen_error!("singlepass can't emit move_local {:?}", location),
+        }
+        Ok(())
+    }
+
+    // List of register to save, depending on the CallingConvention
+    fn list_to_save(&self, _calling_convention: CallingConvention) -> Vec<Location> {
+        vec![]
+    }
+
+    // Get param location, MUST be called in order!
+    fn get_param_location(
+        &self,
+        idx: usize,
+        _sz: Size,
+        stack_args: &mut usize,
+        _calling_convention: CallingConvention,
+    ) -> Location {
+        match idx {
+            0 => Location::GPR(GPR::X10),
+            1 => Location::GPR(GPR::X11),
+            2 => Location::GPR(GPR::X12),
+            3 => Location::GPR(GPR::X13),
+            4 => Location::GPR(GPR::X14),
+            5 => Location::GPR(GPR::X15),
+            6 => Location::GPR(GPR::X16),
+            7 => Location::GPR(GPR::X17),
+            _ => {
+                let loc = Location::Memory(GPR::X2, *stack_args as i32);
+                *stack_args += 8;
+                loc
+            }
+        }
+    }
+    // Get call param location, MUST be called in order!
+    fn get_call_param_location(
+        &self,
+        idx: usize,
+        _sz: Size,
+        stack_args: &mut usize,
+        _calling_convention: CallingConvention,
+    ) -> Location {
+        match idx {
+            0 => Location::GPR(GPR::X10),
+            1 => Location::GPR(GPR::X11),
+            2 => Location::GPR(GPR::X12),
+            3 => Location::GPR(GPR::X13),
+            4 => Location::GPR(GPR::X14),
+            5 => Location::GPR(GPR::X15),
+            6 => Location::GPR(GPR::X16),
+            7 => Location::GPR(GPR::X17),
+            _ => {
+                let loc = Location::Memory(GPR::X8, 16 * 2 + *stack_args as i32);
+                *stack_args += 8;
+                loc
+            }
+        }
+    }
+    // Get simple param location
+    fn get_simple_param_location(
+        &self,
+        idx: usize,
+        _calling_convention: CallingConvention,
+    ) -> Location {
+        match idx {
+            0 => Location::GPR(GPR::X10),
+            1 => Location::GPR(GPR::X11),
+            2 => Location::GPR(GPR::X12),
+            3 => Location::GPR(GPR::X13),
+            4 => Location::GPR(GPR::X14),
+            5 => Location::GPR(GPR::X15),
+            6 => Location::GPR(GPR::X16),
+            7 => Location::GPR(GPR::X17),
+            _ => Location::Memory(GPR::X8, (16 * 2 + (idx - 8) * 8) as i32),
+        }
+    }
+    // move a location to another
+    fn move_location(
+        &mut self,
+        size: Size,
+        source: Location,
+        dest: Location,
+    ) -> Result<(), CompileError> {
+        match (source, dest) {
+            (Location::GPR(src), Location::GPR(dst)) => {
+                // 32-bit values are zero-extended when moved to a register.
+                if size == Size::S32 {
+                    self.assembler.emit_zext32(dst, src)
+                } else if src != dst {
+                    self.assembler.emit_mov(dst, src)
+                } else {
+                    Ok(())
+                }
+            }
+            (Location::Imm8(_), Location::GPR(dst))
+            | (Location::Imm32(_), Location::GPR(dst))
+            | (Location::Imm64(_), Location::GPR(dst)) => {
+                let mut val = Self::imm_value(source).unwrap();
+                if size == Size::S32 {
+                    val &= 0xffff_ffff;
+                }
+                self.assembler.emit_mov_imm(dst, val)
+            }
+            (Location::Memory(base, offset), Location::GPR(dst)) => {
+                self.emit_relaxed_load(size, false, dst, base, offset)
+            }
+            (Location::GPR(src), Location::Memory(base, offset)) => {
+                self.emit_relaxed_store(size, src, base, offset)
+            }
+            (Location::Imm8(_), Location::Memory(base, offset))
+            | (Location::Imm32(_), Location::Memory(base, offset))
+            | (Location::Imm64(_), Location::Memory(base, offset))
+            | (Location::Memory(_, _), Location::Memory(base, offset)) => {
+                if Self::imm_value(source) == Some(0) {
+                    return self.emit_relaxed_store(size, GPR::X0, base, offset);
+                }
+                let tmp = self.acquire_temp()?;
+                self.move_location(size, source, Location::GPR(tmp))?;
+                self.emit_relaxed_store(size, tmp, base, offset)?;
+                self.release_gpr(tmp);
+                Ok(())
+            }
+            (Location::SIMD(src), Location::SIMD(dst)) => {
+                if src != dst {
+                    self.assembler
+                        .emit_fop(Size::S64, FpOp::Sgnj, dst, src, src)?;
+                }
+                Ok(())
+            }
+            (Location::SIMD(src), Location::GPR(dst)) => {
+                let sz = if size == Size::S32 {
+                    Size::S32
+                } else {
+                    Size::S64
+                };
+                self.assembler.emit_fmv_to_gpr(sz, dst, src)?;
+                if sz == Size::S32 {
+                    self.assembler.emit_zext32(dst, dst)?;
+                }
+                Ok(())
+            }
+            (Location::GPR(src), Location::SIMD(dst)) => {
+                let sz = if size == Size::S32 {
+                    Size::S32
+                } else {
+                    Size::S64
+                };
+                self.assembler.emit_fmv_from_gpr(sz, dst, src)
+            }
+            (Location::SIMD(src), Location::Memory(base, offset)) => match size {
+                Size::S32 | Size::S64 => self.emit_relaxed_fstore(size, src, base, offset),
+                _ => codegen_error!(
+                    "singlepass can't emit move_location {:?} {:?} => {:?}",
+                    size,
+                    source,
+                    dest
+                ),
+            },
+            (Location::Memory(base, offset), Location::SIMD(dst)) => match size {
+                Size::S32 | Size::S64 => self.emit_relaxed_fload(size, dst, base, offset),
+                _ => codegen_error!(
+                    "singlepass can't emit move_location {:?} {:?} => {:?}",
+                    size,
+                    source,
+                    dest
+                ),
+            },
+            (Location::Imm8(_), Location::SIMD(dst))
+            | (Location::Imm32(_), Location::SIMD(dst))
+            | (Location::Imm64(_), Location::SIMD(dst)) => {
+                let sz = if size == Size::S32 {
+                    Size::S32
+                } else {
+                    Size::S64
+                };
+                self.assembler
+                    .emit_mov_imm(SCRATCH, Self::imm_value(source).unwrap())?;
+                self.assembler.emit_fmv_from_gpr(sz, dst, SCRATCH)
+            }
+            _ => codegen_error!(
+                "singlepass can't emit move_location {:?} {:?} => {:?}",
+                size,
+                source,
+                dest
+            ),
+        }
+    }
+    // move a location to another
+    fn move_location_extend(
+        &mut self,
+        size_val: Size,
+        signed: bool,
+        source: Location,
+        size_op: Size,
+        dest: Location,
+    ) -> Result<(), CompileError> {
+        if size_op != Size::S64 {
+            codegen_error!("singlepass move_location_extend unreachable");
+        }
+        let mut temps = vec![];
+        let src = self.location_to_reg(size_val, source, &mut temps, true)?;
+        let dst = self.location_to_reg(size_op, dest, &mut temps, false)?;
+        self.emit_extend(size_val, signed, dst, src)?;
+        if dest != Location::GPR(dst) {
+            self.move_location(size_op, Location::GPR(dst), dest)?;
+        }
+        self.release_temps(temps, vec![]);
+        Ok(())
+    }
+    fn load_address(
+        &mut self,
+        _size: Size,
+        _reg: Location,
+        _mem: Location,
+    ) -> Result<(), CompileError> {
+        codegen_error!("singlepass load_address unimplemented");
+    }
+    // Init the stack loc counter
+    fn init_stack_loc(
+        &mut self,
+        init_stack_loc_cnt: u64,
+        last_stack_loc: Location,
+    ) -> Result<(), CompileError> {
+        let label = self.assembler.get_label();
+        let dest = self.acquire_temp()?;
+        let cnt = self.acquire_temp()?;
+        match last_stack_loc {
+            Location::Memory(reg, offset) => self.emit_add_imm(dest, reg, offset as i64)?,
+            _ => codegen_error!("singlepass can't emit init_stack_loc {:?}", last_stack_loc),
+        }
+        self.assembler.emit_mov_imm(cnt, init_stack_loc_cnt)?;
+        self.assembler.emit_label(label)?;
+        self.assembler.emit_store(Size::S64, GPR::X0, dest, 0)?;
+        self.assembler
+            .emit_alu_imm(Size::S64, AluImmOp::Addi, dest, dest, 8)?;
+        self.assembler
+            .emit_alu_imm(Size::S64, AluImmOp::Addi, cnt, cnt, -1)?;
+        self.assembler
+            .emit_bcond_label(Condition::Ne, cnt, GPR::X0, label)?;
+        self.release_gpr(cnt);
+        self.release_gpr(dest);
+        Ok(())
+    }
+    // Restore save_area
+    fn restore_saved_area(&mut self, saved_area_offset: i32) -> Result<(), CompileError> {
+        let real_delta = if saved_area_offset & 15 != 0 {
+            self.pushed = true;
+            saved_area_offset + 8
+        } else {
+            self.pushed = false;
+            saved_area_offset
+        };
+        self.emit_add_imm(GPR::X2, GPR::X8, -(real_delta as i64))
+    }
+    // Pop a location
+    fn pop_location(&mut self, location: Location) -> Result<(), CompileError> {
+        self.emit_pop(Size::S64, location)
+    }
+    // Create a new `MachineState` with default values.
+    fn new_machine_state(&self) -> MachineState {
+        new_machine_state()
+    }
+
+    // assembler finalize
+    fn assembler_finalize(self) -> Result<Vec<u8>, CompileError> {
+        self.assembler.finalize().map_err(|e| {
+            CompileError::Codegen(format!("Assembler failed finalization with: {:?}", e))
+        })
+    }
+
+    fn get_offset(&self) -> Offset {
+        self.assembler.get_offset()
+    }
+
+    fn finalize_function(&mut self) -> Result<(), CompileError> {
+        self.assembler.finalize_function();
+        Ok(())
+    }
+
+    fn emit_tier_up_entry(
+        &mut self,
+        vmctx: Location,
+        tiers: u32,
+        redirect: u32,
+        calls: u32,
+    ) -> Result<(), CompileError> {
+        // T0 and T1 pass no parameter.
+        let vmctx = match vmctx {
+            Location::GPR(vmctx) => vmctx,
+            _ => codegen_error!("singlepass emit_tier_up_entry unreachable"),
+        };
+        let run = self.get_label();
+        self.assembler.emit_mov_imm(GPR::X6, tiers as u64)?;
+        self.assembler
+            .emit_alu(Size::S64, AluOp::Add, GPR::X6, vmctx, GPR::X6)?;
+        self.assembler
+            .emit_load(Size::S64, false, GPR::X5, GPR::X6, 0)?;
+        self.assembler.emit_mov_imm(GPR::X6, redirect as u64)?;
+        self.assembler
+            .emit_alu(Size::S64, AluOp::Add, GPR::X5, GPR::X5, GPR::X6)?;
+        let calls = (calls - redirect) as i32;
+        self.assembler
+            .emit_load(Size::S64, false, GPR::X6, GPR::X5, calls)?;
+        self.assembler
+            .emit_alu_imm(Size::S64, AluImmOp::Addi, GPR::X6, GPR::X6, 1)?;
+        self.assembler
+            .emit_store(Size::S64, GPR::X6, GPR::X5, calls)?;
+        self.assembler
+            .emit_load(Size::S64, false, GPR::X5, GPR::X5, 0)?;
+        self.assembler
+            .emit_bcond_label(Condition::Eq, GPR::X5, GPR::X0, run)?;
+        self.assembler.emit_j_register(GPR::X5)?;
+        self.emit_label(run)
+    }
+
+    fn emit_function_prolog(&mut self) -> Result<(), CompileError> {
+        self.emit_double_push(Size::S64, Location::GPR(GPR::X8), Location::GPR(GPR::X1))?; // save RA too
+        self.emit_unwind_op(UnwindOps::Push2Regs {
+            reg1: GPR::X8.to_dwarf(),
+            reg2: GPR::X1.to_dwarf(),
+            up_to_sp: 16,
+        });
+        self.emit_double_push(Size::S64, Location::GPR(GPR::X9), Location::GPR(GPR::X27))?;
+        self.emit_unwind_op(UnwindOps::Push2Regs {
+            reg1: GPR::X9.to_dwarf(),
+            reg2: GPR::X27.to_dwarf(),
+            up_to_sp: 32,
+        });
+        self.assembler.emit_mov(GPR::X8, GPR::X2)?;
+        self.emit_unwind_op(UnwindOps::DefineNewFrame);
+        Ok(())
+    }
+
+    fn emit_function_epilog(&mut self) -> Result<(), CompileError> {
+        self.assembler.emit_mov(GPR::X2, GPR::X8)?;
+        self.pushed = false; // SP is restored, consider it aligned
+        self.emit_double_pop(Size::S64, Location::GPR(GPR::X9), Location::GPR(GPR::X27))?;
+        self.emit_double_pop(Size::S64, Location::GPR(GPR::X8), Location::GPR(GPR::X1))?;
+        Ok(())
+    }
+
+    fn emit_function_return_value(
+        &mut self,
+        ty: WpType,
+        canonicalize: bool,
+        loc: Location,
+    ) -> Result<(), CompileError> {
+        if canonicalize {
+            self.canonicalize_nan(
+                match ty {
+                    WpType::F32 => Size::S32,
+                    WpType::F64 => Size::S64,
+                    _ => unreachable!(),
+                },
+                loc,
+                Location::GPR(GPR::X10),
+            )?;
+        } else {
+            self.emit_relaxed_mov(Size::S64, loc, Location::GPR(GPR::X10))?;
+        }
+        Ok(())
+    }
+
+    fn emit_function_return_float(&mut self) -> Result<(), CompileError> {
+        self.assembler
+            .emit_fmv_from_gpr(Size::S64, FPR::F10, GPR::X10)
+    }
+
+    fn arch_supports_canonicalize_nan(&self) -> bool {
+        self.assembler.arch_supports_canonicalize_nan()
+    }
+    fn canonicalize_nan(
+        &mut self,
+        sz: Size,
+        input: Location,
+        output: Location,
+    ) -> Result<(), CompileError> {
+        // fmin(x, x) gives x, or the canonical NaN for a NaN input.
+        self.emit_relaxed_fop3(sz, FpOp::Min, input, input, output)
+    }
+
+    fn emit_illegal_op(&mut self, trap: TrapCode) -> Result<(), CompileError> {
+        let offset = self.assembler.get_offset().0;
+        self.emit_illegal_op_internal(trap)?;
+        self.mark_instruction_address_end(offset);
+        Ok(())
+    }
+    fn get_label(&mut self) -> Label {
+        self.assembler.new_dynamic_label()
+    }
+    fn emit_label(&mut self, label: Label) -> Result<(), CompileError> {
+        self.assembler.emit_label(label)
+    }
+    fn get_grp_for_call(&self) -> GPR {
+        GPR::X9
+    }
+    fn emit_call_register(&mut self, reg: GPR) -> Result<(), CompileError> {
+        self.assembler.emit_call_register(reg)
+    }
+    fn emit_call_label(&mut self, label: Label) -> Result<(), CompileError> {
+        self.assembler.emit_call_label(label)
+    }
+    fn get_gpr_for_ret(&self) -> GPR {
+        GPR::X10
+    }
+    fn get_simd_for_ret(&self) -> FPR {
+        FPR::F10
+    }
+    fn get_gpr_for_ret_high(&self) -> GPR {
+        GPR::X11
+    }
+
+    fn arch_requires_indirect_call_trampoline(&self) -> bool {
+        self.assembler.arch_requires_indirect_call_trampoline()
+    }
+
+    fn arch_emit_indirect_call_with_trampoline(
+        &mut self,
+        location: Location,
+    ) -> Result<(), CompileError> {
+        self.assembler
+            .arch_emit_indirect_call_with_trampoline(location)
+    }
+
+    fn emit_debug_breakpoint(&mut self) -> Result<(), CompileError> {
+        self.assembler.emit_ebreak()
+    }
+
+    fn emit_call_location(&mut self, location: Location) -> Result<(), CompileError> {
+        // The argument registers are set up already, go through the scratch register.
+        let reg = match location {
+            Location::GPR(reg) => reg,
+            _ => {
+                self.move_location(Size::S64, location, Location::GPR(SCRATCH))?;
+                SCRATCH
+            }
+        };
+        self.assembler.emit_call_register(reg)
+    }
+
+    fn location_address(
+        &mut self,
+        _size: Size,
+        _source: Location,
+        _dest: Location,
+    ) -> Result<(), CompileError> {
+        codegen_error!("singlepass location_address not implemented")
+    }
+    // logic
+    fn location_and(
+        &mut self,
+        _size: Size,
+        _source: Location,
+        _dest: Location,
+        _flags: bool,
+    ) -> Result<(), CompileError> {
+        codegen_error!("singlepass location_and not implemented")
+    }
+    fn location_xor(
+        &mut self,
+        _size: Size,
+        _source: Location,
+        _dest: Location,
+        _flags: bool,
+    ) -> Result<(), CompileError> {
+        codegen_error!("singlepass location_xor not implemented")
+    }
+    fn location_or(
+        &mut self,
+        _size: Size,
+        _source: Location,
+        _dest: Location,
+        _flags: bool,
+    ) -> Result<(), CompileError> {
+        codegen_error!("singlepass location_or not implemented")
+    }
+    fn location_test(
+        &mut self,
+        _size: Size,
+        _source: Location,
+        _dest: Location,
+    ) -> Result<(), CompileError> {
+        codegen_error!("singlepass location_test not implemented")
+    }
+    // math
+    fn location_add(
+        &mut self,
+        size: Size,
+        source: Location,
+        dest: Location,
+        flags: bool,
+    ) -> Result<(), CompileError> {
+        if flags {
+            codegen_error!("singlepass location_add with flags not implemented");
+        }
+        self.emit_relaxed_alu3(size, AluOp::Add, dest, source, dest)
+    }
+    fn location_sub(
+        &mut self,
+        size: Size,
+        source: Location,
+        dest: Location,
+        flags: bool,
+    ) -> Result<(), CompileError> {
+        if flags {
+            codegen_error!("singlepass location_sub with flags not implemented");
+        }
+        self.emit_relaxed_alu3(size, AluOp::Sub, dest, source, dest)
+    }
+    fn location_cmp(
+        &mut self,
+        size: Size,
+        source: Location,
+        dest: Location,
+    ) -> Result<(), CompileError> {
+        self.cmp_operands = Some((size, source, dest));
+        Ok(())
+    }
+    fn jmp_unconditionnal(&mut self, label: Label) -> Result<(), CompileError> {
+        self.assembler.emit_j_label_far(label)
+    }
+    fn jmp_on_equal(&mut self, label: Label) -> Result<(), CompileError> {
+        self.emit_cmp_branch(Condition::Eq, false, label)
+    }
+    fn jmp_on_different(&mut self, label: Label) -> Result<(), CompileError> {
+        self.emit_cmp_branch(Condition::Ne, false, label)
+    }
+    fn jmp_on_above(&mut self, label: Label) -> Result<(), CompileError> {
+        self.emit_cmp_branch(Condition::Ltu, true, label)
+    }
+    fn jmp_on_aboveequal(&mut self, label: Label) -> Result<(), CompileError> {
+        self.emit_cmp_branch(Condition::Geu, false, label)
+    }
+    fn jmp_on_belowequal(&mut self, label: Label) -> Result<(), CompileError> {
+        self.emit_cmp_branch(Condition::Geu, true, label)
+    }
+    // Carry set, like on the other backends: the signed division overflow
+    // is tested explicitly by `emit_jmp_on_sdiv_overflow`.
+    fn jmp_on_overflow(&mut self, label: Label) -> Result<(), CompileError> {
+        self.emit_cmp_branch(Condition::Ltu, false, label)
+    }
+
+    // jmp table
+    fn emit_jmp_to_jumptable(&mut self, label: Label, cond: Location) -> Result<(), CompileError> {
+        let tmp1 = self.acquire_temp()?;
+        let tmp2 = self.acquire_temp()?;
+
+        self.assembler.emit_load_label(tmp1, label)?;
+        self.move_location(Size::S32, cond, Location::GPR(tmp2))?;
+
+        // Each entry of the table is a far jump of 8 bytes.
+        self.assembler
+            .emit_alu_imm(Size::S64, AluImmOp::Slli, tmp2, tmp2, 3)?;
+        self.assembler
+            .emit_alu(Size::S64, AluOp::Add, tmp2, tmp1, tmp2)?;
+        self.assembler.emit_j_register(tmp2)?;
+        self.release_gpr(tmp2);
+        self.release_gpr(tmp1);
+        Ok(())
+    }
+
+    fn align_for_loop(&mut self) -> Result<(), CompileError> {
+        // noting to do on RISC-V
+        Ok(())
+    }
+
+    fn emit_ret(&mut self) -> Result<(), CompileError> {
+        self.assembler.emit_ret()
+    }
+
+    fn emit_push(&mut self, size: Size, loc: Location) -> Result<(), CompileError> {
+        self.emit_push(size, loc)
+    }
+    fn emit_pop(&mut self, size: Size, loc: Location) -> Result<(), CompileError> {
+        self.emit_pop(size, loc)
+    }
+
+    fn emit_memory_fence(&mut self) -> Result<(), CompileError> {
+        self.assembler.emit_fence()
+    }
+
+    fn location_neg(
+        &mut self,
+        _size_val: Size, // size of src
+        _signed: bool,
+        _source: Location,
+        _size_op: Size,
+        _dest: Location,
+    ) -> Result<(), CompileError> {
+        codegen_error!("singlepass location_neg unimplemented");
+    }
+
+    fn emit_imul_imm32(&mut self, size: Size, imm32: u32, gpr: GPR) -> Result<(), CompileError> {
+        let tmp = self.acquire_temp()?;
+        self.assembler.emit_mov_imm(tmp, imm32 as u64)?;
+        self.assembler.emit_alu(size, AluOp::Mul, gpr, gpr, tmp)?;
+        self.release_gpr(tmp);
+        Ok(())
+    }
+
+    // relaxed binop based...
+    fn emit_relaxed_mov(
+        &mut self,
+        sz: Size,
+        src: Location,
+        dst: Location,
+    ) -> Result<(), CompileError> {
+        self.move_location(sz, src, dst)
+    }
+    fn emit_relaxed_cmp(
+        &mut self,
+        sz: Size,
+        src: Location,
+        dst: Location,
+    ) -> Result<(), CompileError> {
+        self.location_cmp(sz, src, dst)
+    }
+    fn emit_relaxed_zero_extension(
+        &mut self,
+        _sz_src: Size,
+        _src: Location,
+        _sz_dst: Size,
+        _dst: Location,
+    ) -> Result<(), CompileError> {
+        codegen_error!("singlepass emit_relaxed_zero_extension unimplemented");
+    }
+    fn emit_relaxed_sign_extension(
+        &mut self,
+        sz_src: Size,
+        src: Location,
+        sz_dst: Size,
+        dst: Location,
+    ) -> Result<(), CompileError> {
+        let mut temps = vec![];
+        let src = self.location_to_reg(sz_src, src, &mut temps, true)?;
+        let dest = self.location_to_reg(sz_dst, dst, &mut temps, false)?;
+        self.emit_extend(sz_src, true, dest, src)?;
+        if dst != Location::GPR(dest) {
+            self.move_location(Size::S64, Location::GPR(dest), dst)?;
+        }
+        self.release_temps(temps, vec![]);
+        Ok(())
+    }
+    fn emit_binop_add32(
+        &mut self,
+        loc_a: Location,
+        loc_b: Location,
+        ret: Location,
+    ) -> Result<(), CompileError> {
+        self.emit_relaxed_alu3(Size::S32, AluOp::Add, loc_a, loc_b, ret)
+    }
+    fn emit_binop_sub32(
+        &mut self,
+        loc_a: Location,
+        loc_b: Location,
+        ret: Location,
+    ) -> Result<(), CompileError> {
+        self.emit_relaxed_alu3(Size::S32, AluOp::Sub, loc_a, loc_b, ret)
+    }
+    fn emit_binop_mul32(
+        &mut self,
+        loc_a: Location,
+        loc_b: Location,
+        ret: Location,
+    ) -> Result<(), CompileError> {
+        self.emit_relaxed_alu3(Size::S32, AluOp::Mul, loc_a, loc_b, ret)
+    }
+    fn emit_binop_udiv32(
+        &mut self,
+        loc_a: Location,
+        loc_b: Location,
+        ret: Location,
+        integer_division_by_zero: Label,
+        _integer_overflow: Label,
+    ) -> Result<usize, CompileError> {
+        self.emit_divop(
+            Size::S32,
+            AluOp::Divu,
+            loc_a,
+            loc_b,
+            ret,
+            integer_division_by_zero,
+            None,
+        )
+    }
+    fn emit_binop_sdiv32(
+        &mut self,
+        loc_a: Location,
+        loc_b: Location,
+        ret: Location,
+        integer_division_by_zero: Label,
+        integer_overflow: Label,
+    ) -> Result<usize, CompileError> {
+        self.emit_divop(
+            Size::S32,
+            AluOp::Div,
+            loc_a,
+            loc_b,
+            ret,
+            integer_division_by_zero,
+            Some(integer_overflow),
+        )
+    }
+    fn emit_binop_urem32(
+        &mut self,
+        loc_a: Location,
+        loc_b: Location,
+        ret: Location,
+        integer_division_by_zero: Label,
+        _integer_overflow: Label,
+    ) -> Result<usize, CompileError> {
+        self.emit_divop(
+            Size::S32,
+            AluOp::Remu,
+            loc_a,
+            loc_b,
+            ret,
+            integer_division_by_zero,
+            None,
+        )
+    }
+    fn emit_binop_srem32(
+        &mut self,
+        loc_a: Location,
+        loc_b: Location,
+        ret: Location,
+        integer_division_by_zero: Label,
+        _integer_overflow: Label,
+    ) -> Result<usize, CompileError> {
+        self.emit_divop(
+            Size::S32,
+            AluOp::Rem,
+            loc_a,
+            loc_b,
+            ret,
+            integer_division_by_zero,
+            None,
+        )
+    }
+    fn emit_binop_and32(
+        &mut self,
+        loc_a: Location,
+        loc_b: Location,
+        ret: Location,
+    ) -> Result<(), CompileError> {
+        self.emit_relaxed_alu3(Size::S32, AluOp::And, loc_a, loc_b, ret)
+    }
+    fn emit_binop_or32(
+        &mut self,
+        loc_a: Location,
+        loc_b: Location,
+        ret: Location,
+    ) -> Result<(), CompileError> {
+        self.emit_relaxed_alu3(Size::S32, AluOp::Or, loc_a, loc_b, ret)
+    }
+    fn emit_binop_xor32(
+        &mut self,
+        loc_a: Location,
+        loc_b: Location,
+        ret: Location,
+    ) -> Result<(), CompileError> {
+        self.emit_relaxed_alu3(Size::S32, AluOp::Xor, loc_a, loc_b, ret)
+    }
+    fn i32_cmp_ge_s(
+        &mut self,
+        loc_a: Location,
+        loc_b: Location,
+        ret: Location,
+    ) -> Result<(), CompileError> {
+        self.emit_cmpop(Size::S32, Condition::Ge, loc_a, loc_b, ret)
+    }
+    fn i32_cmp_gt_s(
+        &mut self,
+        loc_a: Location,
+        loc_b: Location,
+        ret: Location,
+    ) -> Result<(), CompileError> {
+        self.emit_cmpop(Size::S32, Condition::Lt, loc_b, loc_a, ret)
+    }
+    fn i32_cmp_le_s(
+        &mut self,
+        loc_a: Location,
+        loc_b: Location,
+        ret: Location,
+    ) -> Result<(), CompileError> {
+        self.emit_cmpop(Size::S32, Condition::Ge, loc_b, loc_a, ret)
+    }
+    fn i32_cmp_lt_s(
+        &mut self,
+        loc_a: Location,
+        loc_b: Location,
+        ret: Location,
+    ) -> Result<(), CompileError> {
+        self.emit_cmpop(Size::S32, Condition::Lt, loc_a, loc_b, ret)
+    }
+    fn i32_cmp_ge_u(
+        &mut self,
+        loc_a: Location,
+        loc_b: Location,
+        ret: Location,
+    ) -> Result<(), CompileError> {
+        self.emit_cmpop(Size::S32, Condition::Geu, loc_a, loc_b, ret)
+    }
+    fn i32_cmp_gt_u(
+        &mut self,
+        loc_a: Location,
+        loc_b: Location,
+        ret: Location,
+    ) -> Result<(), CompileError> {
+        self.emit_cmpop(Size::S32, Condition::Ltu, loc_b, loc_a, ret)
+    }
+    fn i32_cmp_le_u(
+        &mut self,
+        loc_a: Location,
+        loc_b: Location,
+        ret: Location,
+    ) -> Result<(), CompileError> {
+        self.emit_cmpop(Size::S32, Condition::Geu, loc_b, loc_a, ret)
+    }
+    fn i32_cmp_lt_u(
+        &mut self,
+        loc_a: Location,
+        loc_b: Location,
+        ret: Location,
+    ) -> Result<(), CompileError> {
+        self.emit_cmpop(Size::S32, Condition::Ltu, loc_a, loc_b, ret)
+    }
+    fn i32_cmp_ne(
+        &mut self,
+        loc_a: Location,
+        loc_b: Location,
+        ret: Location,
+    ) -> Result<(), CompileError> {
+        self.emit_cmpop(Size::S32, Condition::Ne, loc_a, loc_b, ret)
+    }
+    fn i32_cmp_eq(
+        &mut self,
+        loc_a: Location,
+        loc_b: Location,
+        ret: Location,
+    ) -> Result<(), CompileError> {
+        self.emit_cmpop(Size::S32, Condition::Eq, loc_a, loc_b, ret)
+    }
+    fn i32_clz(&mut self, src: Location, dst: Location) -> Result<(), CompileError> {
+        self.emit_bitcount(Size::S32, Operator::I32Clz, src, dst)
+    }
+    fn i32_ctz(&mut self, src: Location, dst: Location) -> Result<(), CompileError> {
+        self.emit_bitcount(Size::S32, Operator::I32Ctz, src, dst)
+    }
+    fn i32_popcnt(&mut self, loc: Location, ret: Location) -> Result<(), CompileError> {
+        self.emit_bitcount(Size::S32, Operator::I32Popcnt, loc, ret)
+    }
+    fn i32_shl(
+        &mut self,
+        loc_a: Location,
+        loc_b: Location,
+        ret: Location,
+    ) -> Result<(), CompileError> {
+        self.emit_relaxed_alu3(Size::S32, AluOp::Sll, loc_a, loc_b, ret)
+    }
+    fn i32_shr(
+        &mut self,
+        loc_a: Location,
+        loc_b: Location,
+        ret: Location,
+    ) -> Result<(), CompileError> {
+        self.emit_relaxed_alu3(Size::S32, AluOp::Srl, loc_a, loc_b, ret)
+    }
+    fn i32_sar(
+        &mut self,
+        loc_a: Location,
+        loc_b: Location,
+        ret: Location,
+    ) -> Result<(), CompileError> {
+        self.emit_relaxed_alu3(Size::S32, AluOp::Sra, loc_a, loc_b, ret)
+    }
+    fn i32_rol(
+        &mut self,
+        loc_a: Location,
+        loc_b: Location,
+        ret: Location,
+    ) -> Result<(), CompileError> {
+        self.emit_rotate(Size::S32, true, loc_a, loc_b, ret)
+    }
+    fn i32_ror(
+        &mut self,
+        loc_a: Location,
+        loc_b: Location,
+        ret: Location,
+    ) -> Result<(), CompileError> {
+        self.emit_rotate(Size::S32, false, loc_a, loc_b, ret)
+    }
+    fn i32_load(
+        &mut self,
+        addr: Location,
+        memarg: &MemArg,
+        ret: Location,
+        need_check: bool,
+        memory64: bool,
+        imported_memories: bool,
+        offset: i32,
+        heap_access_oob: Label,
+        unaligned_atomic: Label,
+    ) -> Result<(), CompileError> {
+        self.memory_op(
+            addr,
+            memarg,
+            false,
+            4,
+            need_check,
+            memory64,
+            imported_memories,
+            offset,
+            heap_access_oob,
+            unaligned_atomic,
+            |this, addr| this.emit_load_to(Size::S32, false, ret, addr),
+        )
+    }
+    fn i32_load_8u(
+        &mut self,
+        addr: Location,
+        memarg: &MemArg,
+        ret: Location,
+        need_check: bool,
+        memory64: bool,
+        imported_memories: bool,
+        offset: i32,
+        heap_access_oob: Label,
+        unaligned_atomic: Label,
+    ) -> Result<(), CompileError> {
+        self.memory_op(
+            addr,
+            memarg,
+            false,
+            1,
+            need_check,
+            memory64,
+            imported_memories,
+            offset,
+            heap_access_oob,
+            unaligned_atomic,
+            |this, addr| this.emit_load_to(Size::S8, false, ret, addr),
+        )
+    }
+    fn i32_load_8s(
+        &mut self,
+        addr: Location,
+        memarg: &MemArg,
+        ret: Location,
+        need_check: bool,
+        memory64: bool,
+        imported_memories: bool,
+        offset: i32,
+        heap_access_oob: Label,
+        unaligned_atomic: Label,
+    ) -> Result<(), CompileError> {
+        self.memory_op(
+            addr,
+            memarg,
+            false,
+            1,
+            need_check,
+            memory64,
+            imported_memories,
+            offset,
+            heap_access_oob,
+            unaligned_atomic,
+            |this, addr| this.emit_load_to(Size::S8, true, ret, addr),
+        )
+    }
+    fn i32_load_16u(
+        &mut self,
+        addr: Location,
+        memarg: &MemArg,
+        ret: Location,
+        need_check: bool,
+        memory64: bool,
+        imported_memories: bool,
+        offset: i32,
+        heap_access_oob: Label,
+        unaligned_atomic: Label,
+    ) -> Result<(), CompileError> {
+        self.memory_op(
+            addr,
+            memarg,
+            false,
+            2,
+            need_check,
+            memory64,
+            imported_memories,
+            offset,
+            heap_access_oob,
+            unaligned_atomic,
+            |this, addr| this.emit_load_to(Size::S16, false, ret, addr),
+        )
+    }
+    fn i32_load_16s(
+        &mut self,
+        addr: Location,
+        memarg: &MemArg,
+        ret: Location,
+        need_check: bool,
+        memory64: bool,
+        imported_memories: bool,
+        offset: i32,
+        heap_access_oob: Label,
+        unaligned_atomic: Label,
+    ) -> Result<(), CompileError> {
+        self.memory_op(
+            addr,
+            memarg,
+            false,
+            2,
+            need_check,
+            memory64,
+            imported_memories,
+            offset,
+            heap_access_oob,
+            unaligned_atomic,
+            |this, addr| this.emit_load_to(Size::S16, true, ret, addr),
+        )
+    }
+    fn i32_atomic_load(
+        &mut self,
+        addr: Location,
+        memarg: &MemArg,
+        ret: Location,
+        need_check: bool,
+        memory64: bool,
+        imported_memories: bool,
+        offset: i32,
+        heap_access_oob: Label,
+        unaligned_atomic: Label,
+    ) -> Result<(), CompileError> {
+        self.memory_op(
+            addr,
+            memarg,
+            true,
+            4,
+            need_check,
+            memory64,
+            imported_memories,
+            offset,
+            heap_access_oob,
+            unaligned_atomic,
+            |this, addr| {
+                this.assembler.emit_fence()?;
+                this.emit_load_to(Size::S32, false, ret, addr)?;
+                this.assembler.emit_fence()
+            },
+        )
+    }
+    fn i32_atomic_load_8u(
+        &mut self,
+        addr: Location,
+        memarg: &MemArg,
+        ret: Location,
+        need_check: bool,
+        memory64: bool,
+        imported_memories: bool,
+        offset: i32,
+        heap_access_oob: Label,
+        unaligned_atomic: Label,
+    ) -> Result<(), CompileError> {
+        self.memory_op(
+            addr,
+            memarg,
+            true,
+            1,
+            need_check,
+            memory64,
+            imported_memories,
+            offset,
+            heap_access_oob,
+            unaligned_atomic,
+            |this, addr| {
+                this.assembler.emit_fence()?;
+                this.emit_load_to(Size::S8, false, ret, addr)?;
+                this.assembler.emit_fence()
+            },
+        )
+    }
+    fn i32_atomic_load_16u(
+        &mut self,
+        addr: Location,
+        memarg: &MemArg,
+        ret: Location,
+        need_check: bool,
+        memory64: bool,
+        imported_memories: bool,
+        offset: i32,
+        heap_access_oob: Label,
+        unaligned_atomic: Label,
+    ) -> Result<(), CompileError> {
+        self.memory_op(
+            addr,
+            memarg,
+            true,
+            2,
+            need_check,
+            memory64,
+            imported_memories,
+            offset,
+            heap_access_oob,
+            unaligned_atomic,
+            |this, addr| {
+                this.assembler.emit_fence()?;
+                this.emit_load_to(Size::S16, false, ret, addr)?;
+                this.assembler.emit_fence()
+            },
+        )
+    }
+    fn i32_save(
+        &mut self,
+        target_value: Location,
+        memarg: &MemArg,
+        target_addr: Location,
+        need_check: bool,
+        memory64: bool,
+        imported_memories: bool,
+        offset: i32,
+        heap_access_oob: Label,
+        unaligned_atomic: Label,
+    ) -> Result<(), CompileError> {
+        self.memory_op(
+            target_addr,
+            memarg,
+            false,
+            4,
+            need_check,
+            memory64,
+            imported_memories,
+            offset,
+            heap_access_oob,
+            unaligned_atomic,
+            |this, addr| this.emit_store_from(Size::S32, target_value, addr),
+        )
+    }
+    fn i32_save_8(
+        &mut self,
+        target_value: Location,
+        memarg: &MemArg,
+        target_addr: Location,
+        need_check: bool,
+        memory64: bool,
+        imported_memories: bool,
+        offset: i32,
+        heap_access_oob: Label,
+        unaligned_atomic: Label,
+    ) -> Result<(), CompileError> {
+        self.memory_op(
+            target_addr,
+            memarg,
+            false,
+            1,
+            need_check,
+            memory64,
+            imported_memories,
+            offset,
+            heap_access_oob,
+            unaligned_atomic,
+            |this, addr| this.emit_store_from(Size::S8, target_value, addr),
+        )
+    }
+    fn i32_save_16(
+        &mut self,
+        target_value: Location,
+        memarg: &MemArg,
+        target_addr: Location,
+        need_check: bool,
+        memory64: bool,
+        imported_memories: bool,
+        offset: i32,
+        heap_access_oob: Label,
+        unaligned_atomic: Label,
+    ) -> Result<(), CompileError> {
+        self.memory_op(
+            target_addr,
+            memarg,
+            false,
+            2,
+            need_check,
+            memory64,
+            imported_memories,
+            offset,
+            heap_access_oob,
+            unaligned_atomic,
+            |this, addr| this.emit_store_from(Size::S16, target_value, addr),
+        )
+    }
+    fn i32_atomic_save(
+        &mut self,
+        target_value: Location,
+        memarg: &MemArg,
+        target_addr: Location,
+        need_check: bool,
+        memory64: bool,
+        imported_memories: bool,
+        offset: i32,
+        heap_access_oob: Label,
+        unaligned_atomic: Label,
+    ) -> Result<(), CompileError> {
+        self.memory_op(
+            target_addr,
+            memarg,
+            true,
+            4,
+            need_check,
+            memory64,
+            imported_memories,
+            offset,
+            heap_access_oob,
+            unaligned_atomic,
+            |this, addr| {
+                this.assembler.emit_fence()?;
+                this.emit_store_from(Size::S32, target_value, addr)?;
+                this.assembler.emit_fence()
+            },
+        )
+    }
+    fn i32_atomic_save_8(
+        &mut self,
+        target_value: Location,
+        memarg: &MemArg,
+        target_addr: Location,
+        need_check: bool,
+        memory64: bool,
+        imported_memories: bool,
+        offset: i32,
+        heap_access_oob: Label,
+        unaligned_atomic: Label,
+    ) -> Result<(), CompileError> {
+        self.memory_op(
+            target_addr,
+            memarg,
+            true,
+            1,
+            need_check,
+            memory64,
+            imported_memories,
+            offset,
+            heap_access_oob,
+            unaligned_atomic,
+            |this, addr| {
+                this.assembler.emit_fence()?;
+                this.emit_store_from(Size::S8, target_value, addr)?;
+                this.assembler.emit_fence()
+            },
+        )
+    }
+    fn i32_atomic_save_16(
+        &mut self,
+        target_value: Location,
+        memarg: &MemArg,
+        target_addr: Location,
+        need_check: bool,
+        memory64: bool,
+        imported_memories: bool,
+        offset: i32,
+        heap_access_oob: Label,
+        unaligned_atomic: Label,
+    ) -> Result<(), CompileError> {
+        self.memory_op(
+            target_addr,
+            memarg,
+            true,
+            2,
+            need_check,
+            memory64,
+            imported_memories,
+            offset,
+            heap_access_oob,
+            unaligned_atomic,
+            |this, addr| {
+                this.assembler.emit_fence()?;
+                this.emit_store_from(Size::S16, target_value, addr)?;
+                this.assembler.emit_fence()
+            },
+        )
+    }
+    fn i32_atomic_add(
+        &mut self,
+        loc: Location,
+        target: Location,
+        memarg: &MemArg,
+        ret: Location,
+        need_check: bool,
+        memory64: bool,
+        imported_memories: bool,
+        offset: i32,
+        heap_access_oob: Label,
+        unaligned_atomic: Label,
+    ) -> Result<(), CompileError> {
+        self.memory_op(
+            target,
+            memarg,
+            true,
+            4,
+            need_check,
+            memory64,
+            imported_memories,
+            offset,
+            heap_access_oob,
+            unaligned_atomic,
+            |this, addr| this.emit_atomic_rmw(Size::S32, AtomicOp::Add, loc, addr, ret),
+        )
+    }
+    fn i32_atomic_add_8u(
+        &mut self,
+        loc: Location,
+        target: Location,
+        memarg: &MemArg,
+        ret: Location,
+        need_check: bool,
+        memory64: bool,
+        imported_memories: bool,
+        offset: i32,
+        heap_access_oob: Label,
+        unaligned_atomic: Label,
+    ) -> Result<(), CompileError> {
+        self.memory_op(
+            target,
+            memarg,
+            true,
+            1,
+            need_check,
+            memory64,
+            imported_memories,
+            offset,
+            heap_access_oob,
+            unaligned_atomic,
+            |this, addr| this.emit_atomic_rmw(Size::S8, AtomicOp::Add, loc, addr, ret),
+        )
+    }
+    fn i32_atomic_add_16u(
+        &mut self,
+        loc: Location,
+        target: Location,
+        memarg: &MemArg,
+        ret: Location,
+        need_check: bool,
+        memory64: bool,
+        imported_memories: bool,
+        offset: i32,
+        heap_access_oob: Label,
+        unaligned_atomic: Label,
+    ) -> Result<(), CompileError> {
+        self.memory_op(
+            target,
+            memarg,
+            true,
+            2,
+            need_check,
+            memory64,
+            imported_memories,
+            offset,
+            heap_access_oob,
+            unaligned_atomic,
+            |this, addr| this.emit_atomic_rmw(Size::S16, AtomicOp::Add, loc, addr, ret),
+        )
+    }
+    fn i32_atomic_sub(
+        &mut self,
+        loc: Location,
+        target: Location,
+        memarg: &MemArg,
+        ret: Location,
+        need_check: bool,
+        memory64: bool,
+        imported_memories: bool,
+        offset: i32,
+        heap_access_oob: Label,
+        unaligned_atomic: Label,
+    ) -> Result<(), CompileError> {
+        self.memory_op(
+            target,
+            memarg,
+            true,
+            4,
+            need_check,
+            memory64,
+            imported_memories,
+            offset,
+            heap_access_oob,
+            unaligned_atomic,
+            |this, addr| this.emit_atomic_rmw(Size::S32, AtomicOp::Sub, loc, addr, ret),
+        )
+    }
+    fn i32_atomic_sub_8u(
+        &mut self,
+        loc: Location,
+        target: Location,
+        memarg: &MemArg,
+        ret: Location,
+        need_check: bool,
+        memory64: bool,
+        imported_memories: bool,
+        offset: i32,
+        heap_access_oob: Label,
+        unaligned_atomic: Label,
+    ) -> Result<(), CompileError> {
+        self.memory_op(
+            target,
+            memarg,
+            true,
+            1,
+            need_check,
+            memory64,
+            imported_memories,
+            offset,
+            heap_access_oob,
+            unaligned_atomic,
+            |this, addr| this.emit_atomic_rmw(Size::S8, AtomicOp::Sub, loc, addr, ret),
+        )
+    }
+    fn i32_atomic_sub_16u(
+        &mut self,
+        loc: Location,
+        target: Location,
+        memarg: &MemArg,
+        ret: Location,
+        need_check: bool,
+        memory64: bool,
+        imported_memories: bool,
+        offset: i32,
+        heap_access_oob: Label,
+        unaligned_atomic: Label,
+    ) -> Result<(), CompileError> {
+        self.memory_op(
+            target,
+            memarg,
+            true,
+            2,
+            need_check,
+            memory64,
+            imported_memories,
+            offset,
+            heap_access_oob,
+            unaligned_atomic,
+            |this, addr| this.emit_atomic_rmw(Size::S16, AtomicOp::Sub, loc, addr, ret),
+        )
+    }
+    fn i32_atomic_and(
+        &mut self,
+        loc: Location,
+        target: Location,
+        memarg: &MemArg,
+        ret: Location,
+        need_check: bool,
+        memory64: bool,
+        imported_memories: bool,
+        offset: i32,
+        heap_access_oob: Label,
+        unaligned_atomic: Label,
+    ) -> Result<(), CompileError> {
+        self.memory_op(
+            target,
+            memarg,
+            true,
+            4,
+            need_check,
+            memory64,
+            imported_memories,
+            offset,
+            heap_access_oob,
+            unaligned_atomic,
+            |this, addr| this.emit_atomic_rmw(Size::S32, AtomicOp::And, loc, addr, ret),
+        )
+    }
+    fn i32_atomic_and_8u(
+        &mut self,
+        loc: Location,
+        target: Location,
+        memarg: &MemArg,
+        ret: Location,
+        need_check: bool,
+        memory64: bool,
+        imported_memories: bool,
+        offset: i32,
+        heap_access_oob: Label,
+        unaligned_atomic: Label,
+    ) -> Result<(), CompileError> {
+        self.memory_op(
+            target,
+            memarg,
+            true,
+            1,
+            need_check,
+            memory64,
+            imported_memories,
+            offset,
+            heap_access_oob,
+            unaligned_atomic,
+            |this, addr| this.emit_atomic_rmw(Size::S8, AtomicOp::And, loc, addr, ret),
+        )
+    }
+    fn i32_atomic_and_16u(
+        &mut self,
+        loc: Location,
+        target: Location,
+        memarg: &MemArg,
+        ret: Location,
+        need_check: bool,
+        memory64: bool,
+        imported_memories: bool,
+        offset: i32,
+        heap_access_oob: Label,
+        unaligned_atomic: Label,
+    ) -> Result<(), CompileError> {
+        self.memory_op(
+            target,
+            memarg,
+            true,
+            2,
+            need_check,
+            memory64,
+            imported_memories,
+            offset,
+            heap_access_oob,
+            unaligned_atomic,
+            |this, addr| this.emit_atomic_rmw(Size::S16, AtomicOp::And, loc, addr, ret),
+        )
+    }
+    fn i32_atomic_or(
+        &mut self,
+        loc: Location,
+        target: Location,
+        memarg: &MemArg,
+        ret: Location,
+        need_check: bool,
+        memory64: bool,
+        imported_memories: bool,
+        offset: i32,
+        heap_access_oob: Label,
+        unaligned_atomic: Label,
+    ) -> Result<(), CompileError> {
+        self.memory_op(
+            target,
+            memarg,
+            true,
+            4,
+            need_check,
+            memory64,
+            imported_memories,
+            offset,
+            heap_access_oob,
+            unaligned_atomic,
+            |this, addr| this.emit_atomic_rmw(Size::S32, AtomicOp::Or, loc, addr, ret),
+        )
+    }
+    fn i32_atomic_or_8u(
+        &mut self,
+        loc: Location,
+        target: Location,
+        memarg: &MemArg,
+        ret: Location,
+        need_check: bool,
+        memory64: bool,
+        imported_memories: bool,
+        offset: i32,
+        heap_access_oob: Label,
+        unaligned_atomic: Label,
+    ) -> Result<(), CompileError> {
+        self.memory_op(
+            target,
+            memarg,
+            true,
+            1,
+            need_check,
+            memory64,
+            imported_memories,
+            offset,
+            heap_access_oob,
+            unaligned_atomic,
+            |this, addr| this.emit_atomic_rmw(Size::S8, AtomicOp::Or, loc, addr, ret),
+        )
+    }
+    fn i32_atomic_or_16u(
+        &mut self,
+        loc: Location,
+        target: Location,
+        memarg: &MemArg,
+        ret: Location,
+        need_check: bool,
+        memory64: bool,
+        imported_memories: bool,
+        offset: i32,
+        heap_access_oob: Label,
+        unaligned_atomic: Label,
+    ) -> Result<(), CompileError> {
+        self.memory_op(
+            target,
+            memarg,
+            true,
+            2,
+            need_check,
+            memory64,
+            imported_memories,
+            offset,
+            heap_access_oob,
+            unaligned_atomic,
+            |this, addr| this.emit_atomic_rmw(Size::S16, AtomicOp::Or, loc, addr, ret),
+        )
+    }
+    fn i32_atomic_xor(
+        &mut self,
+        loc: Location,
+        target: Location,
+        memarg: &MemArg,
+        ret: Location,
+        need_check: bool,
+        memory64: bool,
+        imported_memories: bool,
+        offset: i32,
+        heap_access_oob: Label,
+        unaligned_atomic: Label,
+    ) -> Result<(), CompileError> {
+        self.memory_op(
+            target,
+            memarg,
+            true,
+            4,
+            need_check,
+            memory64,
+            imported_memories,
+            offset,
+            heap_access_oob,
+            unaligned_atomic,
+            |this, addr| this.emit_atomic_rmw(Size::S32, AtomicOp::Xor, loc, addr, ret),
+        )
+    }
+    fn i32_atomic_xor_8u(
+        &mut self,
+        loc: Location,
+        target: Location,
+        memarg: &MemArg,
+        ret: Location,
+        need_check: bool,
+        memory64: bool,
+        imported_memories: bool,
+        offset: i32,
+        heap_access_oob: Label,
+        unaligned_atomic: Label,
+    ) -> Result<(), CompileError> {
+        self.memory_op(
+            target,
+            memarg,
+            true,
+            1,
+            need_check,
+            memory64,
+            imported_memories,
+            offset,
+            heap_access_oob,
+            unaligned_atomic,
+            |this, addr| this.emit_atomic_rmw(Size::S8, AtomicOp::Xor, loc, addr, ret),
+        )
+    }
+    fn i32_atomic_xor_16u(
+        &mut self,
+        loc: Location,
+        target: Location,
+        memarg: &MemArg,
+        ret: Location,
+        need_check: bool,
+        memory64: bool,
+        imported_memories: bool,
+        offset: i32,
+        heap_access_oob: Label,
+        unaligned_atomic: Label,
+    ) -> Result<(), CompileError> {
+        self.memory_op(
+            target,
+            memarg,
+            true,
+            2,
+            need_check,
+            memory64,
+            imported_memories,
+            offset,
+            heap_access_oob,
+            unaligned_atomic,
+            |this, addr| this.emit_atomic_rmw(Size::S16, AtomicOp::Xor, loc, addr, ret),
+        )
+    }
+    fn i32_atomic_xchg(
+        &mut self,
+        loc: Location,
+        target: Location,
+        memarg: &MemArg,
+        ret: Location,
+        need_check: bool,
+        memory64: bool,
+        imported_memories: bool,
+        offset: i32,
+        heap_access_oob: Label,
+        unaligned_atomic: Label,
+    ) -> Result<(), CompileError> {
+        self.memory_op(
+            target,
+            memarg,
+            true,
+            4,
+            need_check,
+            memory64,
+            imported_memories,
+            offset,
+            heap_access_oob,
+            unaligned_atomic,
+            |this, addr| this.emit_atomic_rmw(Size::S32, AtomicOp::Xchg, loc, addr, ret),
+        )
+    }
+    fn i32_atomic_xchg_8u(
+        &mut self,
+        loc: Location,
+        target: Location,
+        memarg: &MemArg,
+        ret: Location,
+        need_check: bool,
+        memory64: bool,
+        imported_memories: bool,
+        offset: i32,
+        heap_access_oob: Label,
+        unaligned_atomic: Label,
+    ) -> Result<(), CompileError> {
+        self.memory_op(
+            target,
+            memarg,
+            true,
+            1,
+            need_check,
+            memory64,
+            imported_memories,
+            offset,
+            heap_access_oob,
+            unaligned_atomic,
+            |this, addr| this.emit_atomic_rmw(Size::S8, AtomicOp::Xchg, loc, addr, ret),
+        )
+    }
+    fn i32_atomic_xchg_16u(
+        &mut self,
+        loc: Location,
+        target: Location,
+        memarg: &MemArg,
+        ret: Location,
+        need_check: bool,
+        memory64: bool,
+        imported_memories: bool,
+        offset: i32,
+        heap_access_oob: Label,
+        unaligned_atomic: Label,
+    ) -> Result<(), CompileError> {
+        self.memory_op(
+            target,
+            memarg,
+            true,
+            2,
+            need_check,
+            memory64,
+            imported_memories,
+            offset,
+            heap_access_oob,
+            unaligned_atomic,
+            |this, addr| this.emit_atomic_rmw(Size::S16, AtomicOp::Xchg, loc, addr, ret),
+        )
+    }
+    fn i32_atomic_cmpxchg(
+        &mut self,
+        new: Location,
+        cmp: Location,
+        target: Location,
+        memarg: &MemArg,
+        ret: Location,
+        need_check: bool,
+        memory64: bool,
+        imported_memories: bool,
+        offset: i32,
+        heap_access_oob: Label,
+        unaligned_atomic: Label,
+    ) -> Result<(), CompileError> {
+        self.memory_op(
+            target,
+            memarg,
+            true,
+            4,
+            need_check,
+            memory64,
+            imported_memories,
+            offset,
+            heap_access_oob,
+            unaligned_atomic,
+            |this, addr| this.emit_atomic_cmpxchg(Size::S32, new, cmp, addr, ret),
+        )
+    }
+    fn i32_atomic_cmpxchg_8u(
+        &mut self,
+        new: Location,
+        cmp: Location,
+        target: Location,
+        memarg: &MemArg,
+        ret: Location,
+        need_check: bool,
+        memory64: bool,
+        imported_memories: bool,
+        offset: i32,
+        heap_access_oob: Label,
+        unaligned_atomic: Label,
+    ) -> Result<(), CompileError> {
+        self.memory_op(
+            target,
+            memarg,
+            true,
+            1,
+            need_check,
+            memory64,
+            imported_memories,
+            offset,
+            heap_access_oob,
+            unaligned_atomic,
+            |this, addr| this.emit_atomic_cmpxchg(Size::S8, new, cmp, addr, ret),
+        )
+    }
+    fn i32_atomic_cmpxchg_16u(
+        &mut self,
+        new: Location,
+        cmp: Location,
+        target: Location,
+        memarg: &MemArg,
+        ret: Location,
+        need_check: bool,
+        memory64: bool,
+        imported_memories: bool,
+        offset: i32,
+        heap_access_oob: Label,
+        unaligned_atomic: Label,
+    ) -> Result<(), CompileError> {
+        self.memory_op(
+            target,
+            memarg,
+            true,
+            2,
+            need_check,
+            memory64,
+            imported_memories,
+            offset,
+            heap_access_oob,
+            unaligned_atomic,
+            |this, addr| this.emit_atomic_cmpxchg(Size::S16, new, cmp, addr, ret),
+        )
+    }
+    fn emit_call_with_reloc(
+        &mut self,
+        _calling_convention: CallingConvention,
+        reloc_target: RelocationTarget,
+    ) -> Result<Vec<Relocation>, CompileError> {
+        let mut relocations = vec![];
+        let next = self.get_label();
+        let reloc_at = self.assembler.get_offset().0;
+        self.emit_label(next)?; // this is to be sure the current immediates are 0
+        self.assembler.emit_call_label(next)?;
+        relocations.push(Relocation {
+            kind: RelocationKind::RiscvCall,
+            reloc_target,
+            offset: reloc_at as u32,
+            addend: 0,
+        });
+        Ok(relocations)
+    }
+    fn emit_binop_add64(
+        &mut self,
+        loc_a: Location,
+        loc_b: Location,
+        ret: Location,
+    ) -> Result<(), CompileError> {
+        self.emit_relaxed_alu3(Size::S64, AluOp::Add, loc_a, loc_b, ret)
+    }
+    fn emit_binop_sub64(
+        &mut self,
+        loc_a: Location,
+        loc_b: Location,
+        ret: Location,
+    ) -> Result<(), CompileError> {
+        self.emit_relaxed_alu3(Size::S64, AluOp::Sub, loc_a, loc_b, ret)
+    }
+    fn emit_binop_mul64(
+        &mut self,
+        loc_a: Location,
+        loc_b: Location,
+        ret: Location,
+    ) -> Result<(), CompileError> {
+        self.emit_relaxed_alu3(Size::S64, AluOp::Mul, loc_a, loc_b, ret)
+    }
+    fn emit_binop_udiv64(
+        &mut self,
+        loc_a: Location,
+        loc_b: Location,
+        ret: Location,
+        integer_division_by_zero: Label,
+        _integer_overflow: Label,
+    ) -> Result<usize, CompileError> {
+        self.emit_divop(
+            Size::S64,
+            AluOp::Divu,
+            loc_a,
+            loc_b,
+            ret,
+            integer_division_by_zero,
+            None,
+        )
+    }
+    fn emit_binop_sdiv64(
+        &mut self,
+        loc_a: Location,
+        loc_b: Location,
+        ret: Location,
+        integer_division_by_zero: Label,
+        integer_overflow: Label,
+    ) -> Result<usize, CompileError> {
+        self.emit_divop(
+            Size::S64,
+            AluOp::Div,
+            loc_a,
+            loc_b,
+            ret,
+            integer_division_by_zero,
+            Some(integer_overflow),
+        )
+    }
+    fn emit_binop_urem64(
+        &mut self,
+        loc_a: Location,
+        loc_b: Location,
+        ret: Location,
+        integer_division_by_zero: Label,
+        _integer_overflow: Label,
+    ) -> Result<usize, CompileError> {
+        self.emit_divop(
+            Size::S64,
+            AluOp::Remu,
+            loc_a,
+            loc_b,
+            ret,
+            integer_division_by_zero,
+            None,
+        )
+    }
+    fn emit_binop_srem64(
+        &mut self,
+        loc_a: Location,
+        loc_b: Location,
+        ret: Location,
+        integer_division_by_zero: Label,
+        _integer_overflow: Label,
+    ) -> Result<usize, CompileError> {
+        self.emit_divop(
+            Size::S64,
+            AluOp::Rem,
+            loc_a,
+            loc_b,
+            ret,
+            integer_division_by_zero,
+            None,
+        )
+    }
+    fn emit_binop_and64(
+        &mut self,
+        loc_a: Location,
+        loc_b: Location,
+        ret: Location,
+    ) -> Result<(), CompileError> {
+        self.emit_relaxed_alu3(Size::S64, AluOp::And, loc_a, loc_b, ret)
+    }
+    fn emit_binop_or64(
+        &mut self,
+        loc_a: Location,
+        loc_b: Location,
+        ret: Location,
+    ) -> Result<(), CompileError> {
+        self.emit_relaxed_alu3(Size::S64, AluOp::Or, loc_a, loc_b, ret)
+    }
+    fn emit_binop_xor64(
+        &mut self,
+        loc_a: Location,
+        loc_b: Location,
+        ret: Location,
+    ) -> Result<(), CompileError> {
+        self.emit_relaxed_alu3(Size::S64, AluOp::Xor, loc_a, loc_b, ret)
+    }
+    fn i64_cmp_ge_s(
+        &mut self,
+        loc_a: Location,
+        loc_b: Location,
+        ret: Location,
+    ) -> Result<(), CompileError> {
+        self.emit_cmpop(Size::S64, Condition::Ge, loc_a, loc_b, ret)
+    }
+    fn i64_cmp_gt_s(
+        &mut self,
+        loc_a: Location,
+        loc_b: Location,
+        ret: Location,
+    ) -> Result<(), CompileError> {
+        self.emit_cmpop(Size::S64, Condition::Lt, loc_b, loc_a, ret)
+    }
+    fn i64_cmp_le_s(
+        &mut self,
+        loc_a: Location,
+        loc_b: Location,
+        ret: Location,
+    ) -> Result<(), CompileError> {
+        self.emit_cmpop(Size::S64, Condition::Ge, loc_b, loc_a, ret)
+    }
+    fn i64_cmp_lt_s(
+        &mut self,
+        loc_a: Location,
+        loc_b: Location,
+        ret: Location,
+    ) -> Result<(), CompileError> {
+        self.emit_cmpop(Size::S64, Condition::Lt, loc_a, loc_b, ret)
+    }
+    fn i64_cmp_ge_u(
+        &mut self,
+        loc_a: Location,
+        loc_b: Location,
+        ret: Location,
+    ) -> Result<(), CompileError> {
+        self.emit_cmpop(Size::S64, Condition::Geu, loc_a, loc_b, ret)
+    }
+    fn i64_cmp_gt_u(
+        &mut self,
+        loc_a: Location,
+        loc_b: Location,
+        ret: Location,
+    ) -> Result<(), CompileError> {
+        self.emit_cmpop(Size::S64, Condition::Ltu, loc_b, loc_a, ret)
+    }
+    fn i64_cmp_le_u(
+        &mut self,
+        loc_a: Location,
+        loc_b: Location,
+        ret: Location,
+    ) -> Result<(), CompileError> {
+        self.emit_cmpop(Size::S64, Condition::Geu, loc_b, loc_a, ret)
+    }
+    fn i64_cmp_lt_u(
+        &mut self,
+        loc_a: Location,
+        loc_b: Location,
+        ret: Location,
+    ) -> Result<(), CompileError> {
+        self.emit_cmpop(Size::S64, Condition::Ltu, loc_a, loc_b, ret)
+    }
+    fn i64_cmp_ne(
+        &mut self,
+        loc_a: Location,
+        loc_b: Location,
+        ret: Location,
+    ) -> Result<(), CompileError> {
+        self.emit_cmpop(Size::S64, Condition::Ne, loc_a, loc_b, ret)
+    }
+    fn i64_cmp_eq(
+        &mut self,
+        loc_a: Location,
+        loc_b: Location,
+        ret: Location,
+    ) -> Result<(), CompileError> {
+        self.emit_cmpop(Size::S64, Condition::Eq, loc_a, loc_b, ret)
+    }
+    fn i64_clz(&mut self, src: Location, dst: Location) -> Result<(), CompileError> {
+        self.emit_bitcount(Size::S64, Operator::I64Clz, src, dst)
+    }
+    fn i64_ctz(&mut self, src: Location, dst: Location) -> Result<(), CompileError> {
+        self.emit_bitcount(Size::S64, Operator::I64Ctz, src, dst)
+    }
+    fn i64_popcnt(&mut self, loc: Location, ret: Location) -> Result<(), CompileError> {
+        self.emit_bitcount(Size::S64, Operator::I64Popcnt, loc, ret)
+    }
+    fn i64_shl(
+        &mut self,
+        loc_a: Location,
+        loc_b: Location,
+        ret: Location,
+    ) -> Result<(), CompileError> {
+        self.emit_relaxed_alu3(Size::S64, AluOp::Sll, loc_a, loc_b, ret)
+    }
+    fn i64_shr(
+        &mut self,
+        loc_a: Location,
+        loc_b: Location,
+        ret: Location,
+    ) -> Result<(), CompileError> {
+        self.emit_relaxed_alu3(Size::S64, AluOp::Srl, loc_a, loc_b, ret)
+    }
+    fn i64_sar(
+        &mut self,
+        loc_a: Location,
+        loc_b: Location,
+        ret: Location,
+    ) -> Result<(), CompileError> {
+        self.emit_relaxed_alu3(Size::S64, AluOp::Sra, loc_a, loc_b, ret)
+    }
+    fn i64_rol(
+        &mut self,
+        loc_a: Location,
+        loc_b: Location,
+        ret: Location,
+    ) -> Result<(), CompileError> {
+        self.emit_rotate(Size::S64, true, loc_a, loc_b, ret)
+    }
+    fn i64_ror(
+        &mut self,
+        loc_a: Location,
+        loc_b: Location,
+        ret: Location,
+    ) -> Result<(), CompileError> {
+        self.emit_rotate(Size::S64, false, loc_a, loc_b, ret)
+    }
+    fn i64_load(
+        &mut self,
+        addr: Location,
+        memarg: &MemArg,
+        ret: Location,
+        need_check: bool,
+        memory64: bool,
+        imported_memories: bool,
+        offset: i32,
+        heap_access_oob: Label,
+        unaligned_atomic: Label,
+    ) -> Result<(), CompileError> {
+        self.memory_op(
+            addr,
+            memarg,
+            false,
+            8,
+            need_check,
+            memory64,
+            imported_memories,
+            offset,
+            heap_access_oob,
+            unaligned_atomic,
+            |this, addr| this.emit_load_to(Size::S64, false, ret, addr),
+        )
+    }
+    fn i64_load_8u(
+        &mut self,
+        addr: Location,
+        memarg: &MemArg,
+        ret: Location,
+        need_check: bool,
+        memory64: bool,
+        imported_memories: bool,
+        offset: i32,
+        heap_access_oob: Label,
+        unaligned_atomic: Label,
+    ) -> Result<(), CompileError> {
+        self.memory_op(
+            addr,
+            memarg,
+            false,
+            1,
+            need_check,
+            memory64,
+            imported_memories,
+            offset,
+            heap_access_oob,
+            unaligned_atomic,
+            |this, addr| this.emit_load_to(Size::S8, false, ret, addr),
+        )
+    }
+    fn i64_load_8s(
+        &mut self,
+        addr: Location,
+        memarg: &MemArg,
+        ret: Location,
+        need_check: bool,
+        memory64: bool,
+        imported_memories: bool,
+        offset: i32,
+        heap_access_oob: Label,
+        unaligned_atomic: Label,
+    ) -> Result<(), CompileError> {
+        self.memory_op(
+            addr,
+            memarg,
+            false,
+            1,
+            need_check,
+            memory64,
+            imported_memories,
+            offset,
+            heap_access_oob,
+            unaligned_atomic,
+            |this, addr| this.emit_load_to(Size::S8, true, ret, addr),
+        )
+    }
+    fn i64_load_16u(
+        &mut self,
+        addr: Location,
+        memarg: &MemArg,
+        ret: Location,
+        need_check: bool,
+        memory64: bool,
+        imported_memories: bool,
+        offset: i32,
+        heap_access_oob: Label,
+        unaligned_atomic: Label,
+    ) -> Result<(), CompileError> {
+        self.memory_op(
+            addr,
+            memarg,
+            false,
+            2,
+            need_check,
+            memory64,
+            imported_memories,
+            offset,
+            heap_access_oob,
+            unaligned_atomic,
+            |this, addr| this.emit_load_to(Size::S16, false, ret, addr),
+        )
+    }
+    fn i64_load_16s(
+        &mut self,
+        addr: Location,
+        memarg: &MemArg,
+        ret: Location,
+        need_check: bool,
+        memory64: bool,
+        imported_memories: bool,
+        offset: i32,
+        heap_access_oob: Label,
+        unaligned_atomic: Label,
+    ) -> Result<(), CompileError> {
+        self.memory_op(
+            addr,
+            memarg,
+            false,
+            2,
+            need_check,
+            memory64,
+            imported_memories,
+            offset,
+            heap_access_oob,
+            unaligned_atomic,
+            |this, addr| this.emit_load_to(Size::S16, true, ret, addr),
+        )
+    }
+    fn i64_load_32u(
+        &mut self,
+        addr: Location,
+        memarg: &MemArg,
+        ret: Location,
+        need_check: bool,
+        memory64: bool,
+        imported_memories: bool,
+        offset: i32,
+        heap_access_oob: Label,
+        unaligned_atomic: Label,
+    ) -> Result<(), CompileError> {
+        self.memory_op(
+            addr,
+            memarg,
+            false,
+            4,
+            need_check,
+            memory64,
+            imported_memories,
+            offset,
+            heap_access_oob,
+            unaligned_atomic,
+            |this, addr| this.emit_load_to(Size::S32, false, ret, addr),
+        )
+    }
+    fn i64_load_32s(
+        &mut self,
+        addr: Location,
+        memarg: &MemArg,
+        ret: Location,
+        need_check: bool,
+        memory64: bool,
+        imported_memories: bool,
+        offset: i32,
+        heap_access_oob: Label,
+        unaligned_atomic: Label,
+    ) -> Result<(), CompileError> {
+        self.memory_op(
+            addr,
+            memarg,
+            false,
+            4,
+            need_check,
+            memory64,
+            imported_memories,
+            offset,
+            heap_access_oob,
+            unaligned_atomic,
+            |this, addr| this.emit_load_to(Size::S32, true, ret, addr),
+        )
+    }
+    fn i64_atomic_load(
+        &mut self,
+        addr: Location,
+        memarg: &MemArg,
+        ret: Location,
+        need_check: bool,
+        memory64: bool,
+        imported_memories: bool,
+        offset: i32,
+        heap_access_oob: Label,
+        unaligned_atomic: Label,
+    ) -> Result<(), CompileError> {
+        self.memory_op(
+            addr,
+            memarg,
+            true,
+            8,
+            need_check,
+            memory64,
+            imported_memories,
+            offset,
+            heap_access_oob,
+            unaligned_atomic,
+            |this, addr| {
+                this.assembler.emit_fence()?;
+                this.emit_load_to(Size::S64, false, ret, addr)?;
+                this.assembler.emit_fence()
+            },
+        )
+    }
+    fn i64_atomic_load_8u(
+        &mut self,
+        addr: Location,
+        memarg: &MemArg,
+        ret: Location,
+        need_check: bool,
+        memory64: bool,
+        imported_memories: bool,
+        offset: i32,
+        heap_access_oob: Label,
+        unaligned_atomic: Label,
+    ) -> Result<(), CompileError> {
+        self.memory_op(
+            addr,
+            memarg,
+            true,
+            1,
+            need_check,
+            memory64,
+            imported_memories,
+            offset,
+            heap_access_oob,
+            unaligned_atomic,
+            |this, addr| {
+                this.assembler.emit_fence()?;
+                this.emit_load_to(Size::S8, false, ret, addr)?;
+                this.assembler.emit_fence()
+            },
+        )
+    }
+    fn i64_atomic_load_16u(
+        &mut self,
+        addr: Location,
+        memarg: &MemArg,
+        ret: Location,
+        need_check: bool,
+        memory64: bool,
+        imported_memories: bool,
+        offset: i32,
+        heap_access_oob: Label,
+        unaligned_atomic: Label,
+    ) -> Result<(), CompileError> {
+        self.memory_op(
+            addr,
+            memarg,
+            true,
+            2,
+            need_check,
+            memory64,
+            imported_memories,
+            offset,
+            heap_access_oob,
+            unaligned_atomic,
+            |this, addr| {
+                this.assembler.emit_fence()?;
+                this.emit_load_to(Size::S16, false, ret, addr)?;
+                this.assembler.emit_fence()
+            },
+        )
+    }
+    fn i64_atomic_load_32u(
+        &mut self,
+        addr: Location,
+        memarg: &MemArg,
+        ret: Location,
+        need_check: bool,
+        memory64: bool,
+        imported_memories: bool,
+        offset: i32,
+        heap_access_oob: Label,
+        unaligned_atomic: Label,
+    ) -> Result<(), CompileError> {
+        self.memory_op(
+            addr,
+            memarg,
+            true,
+            4,
+            need_check,
+            memory64,
+            imported_memories,
+            offset,
+            heap_access_oob,
+            unaligned_atomic,
+            |this, addr| {
+                this.assembler.emit_fence()?;
+                this.emit_load_to(Size::S32, false, ret, addr)?;
+                this.assembler.emit_fence()
+            },
+        )
+    }
+    fn i64_save(
+        &mut self,
+        target_value: Location,
+        memarg: &MemArg,
+        target_addr: Location,
+        need_check: bool,
+        memory64: bool,
+        imported_memories: bool,
+        offset: i32,
+        heap_access_oob: Label,
+        unaligned_atomic: Label,
+    ) -> Result<(), CompileError> {
+        self.memory_op(
+            target_addr,
+            memarg,
+            false,
+            8,
+            need_check,
+            memory64,
+            imported_memories,
+            offset,
+            heap_access_oob,
+            unaligned_atomic,
+            |this, addr| this.emit_store_from(Size::S64, target_value, addr),
+        )
+    }
+    fn i64_save_8(
+        &mut self,
+        target_value: Location,
+        memarg: &MemArg,
+        target_addr: Location,
+        need_check: bool,
+        memory64: bool,
+        imported_memories: bool,
+        offset: i32,
+        heap_access_oob: Label,
+        unaligned_atomic: Label,
+    ) -> Result<(), CompileError> {
+        self.memory_op(
+            target_addr,
+            memarg,
+            false,
+            1,
+            need_check,
+            memory64,
+            imported_memories,
+            offset,
+            heap_access_oob,
+            unaligned_atomic,
+            |this, addr| this.emit_store_from(Size::S8, target_value, addr),
+        )
+    }
+    fn i64_save_16(
+        &mut self,
+        target_value: Location,
+        memarg: &MemArg,
+        target_addr: Location,
+        need_check: bool,
+        memory64: bool,
+        imported_memories: bool,
+        offset: i32,
+        heap_access_oob: Label,
+        unaligned_atomic: Label,
+    ) -> Result<(), CompileError> {
+        self.memory_op(
+            target_addr,
+            memarg,
+            false,
+            2,
+            need_check,
+            memory64,
+            imported_memories,
+            offset,
+            heap_access_oob,
+            unaligned_atomic,
+            |this, addr| this.emit_store_from(Size::S16, target_value, addr),
+        )
+    }
+    fn i64_save_32(
+        &mut self,
+        target_value: Location,
+        memarg: &MemArg,
+        target_addr: Location,
+        need_check: bool,
+        memory64: bool,
+        imported_memories: bool,
+        offset: i32,
+        heap_access_oob: Label,
+        unaligned_atomic: Label,
+    ) -> Result<(), CompileError> {
+        self.memory_op(
+            target_addr,
+            memarg,
+            false,
+            4,
+            need_check,
+            memory64,
+            imported_memories,
+            offset,
+            heap_access_oob,
+            unaligned_atomic,
+            |this, addr| this.emit_store_from(Size::S32, target_value, addr),
+        )
+    }
+    fn i64_atomic_save(
+        &mut self,
+        target_value: Location,
+        memarg: &MemArg,
+        target_addr: Location,
+        need_check: bool,
+        memory64: bool,
+        imported_memories: bool,
+        offset: i32,
+        heap_access_oob: Label,
+        unaligned_atomic: Label,
+    ) -> Result<(), CompileError> {
+        self.memory_op(
+            target_addr,
+            memarg,
+            true,
+            8,
+            need_check,
+            memory64,
+            imported_memories,
+            offset,
+            heap_access_oob,
+            unaligned_atomic,
+            |this, addr| {
+                this.assembler.emit_fence()?;
+                this.emit_store_from(Size::S64, target_value, addr)?;
+                this.assembler.emit_fence()
+            },
+        )
+    }
+    fn i64_atomic_save_8(
+        &mut self,
+        target_value: Location,
+        memarg: &MemArg,
+        target_addr: Location,
+        need_check: bool,
+        memory64: bool,
+        imported_memories: bool,
+        offset: i32,
+        heap_access_oob: Label,
+        unaligned_atomic: Label,
+    ) -> Result<(), CompileError> {
+        self.memory_op(
+            target_addr,
+            memarg,
+            true,
+            1,
+            need_check,
+            memory64,
+            imported_memories,
+            offset,
+            heap_access_oob,
+            unaligned_atomic,
+            |this, addr| {
+                this.assembler.emit_fence()?;
+                this.emit_store_from(Size::S8, target_value, addr)?;
+                this.assembler.emit_fence()
+            },
+        )
+    }
+    fn i64_atomic_save_16(
+        &mut self,
+        target_value: Location,
+        memarg: &MemArg,
+        target_addr: Location,
+        need_check: bool,
+        memory64: bool,
+        imported_memories: bool,
+        offset: i32,
+        heap_access_oob: Label,
+        unaligned_atomic: Label,
+    ) -> Result<(), CompileError> {
+        self.memory_op(
+            target_addr,
+            memarg,
+            true,
+            2,
+            need_check,
+            memory64,
+            imported_memories,
+            offset,
+            heap_access_oob,
+            unaligned_atomic,
+            |this, addr| {
+                this.assembler.emit_fence()?;
+                this.emit_store_from(Size::S16, target_value, addr)?;
+                this.assembler.emit_fence()
+            },
+        )
+    }
+    fn i64_atomic_save_32(
+        &mut self,
+        target_value: Location,
+        memarg: &MemArg,
+        target_addr: Location,
+        need_check: bool,
+        memory64: bool,
+        imported_memories: bool,
+        offset: i32,
+        heap_access_oob: Label,
+        unaligned_atomic: Label,
+    ) -> Result<(), CompileError> {
+        self.memory_op(
+            target_addr,
+            memarg,
+            true,
+            4,
+            need_check,
+            memory64,
+            imported_memories,
+            offset,
+            heap_access_oob,
+            unaligned_atomic,
+            |this, addr| {
+                this.assembler.emit_fence()?;
+                this.emit_store_from(Size::S32, target_value, addr)?;
+                this.assembler.emit_fence()
+            },
+        )
+    }
+    fn i64_atomic_add(
+        &mut self,
+        loc: Location,
+        target: Location,
+        memarg: &MemArg,
+        ret: Location,
+        need_check: bool,
+        memory64: bool,
+        imported_memories: bool,
+        offset: i32,
+        heap_access_oob: Label,
+        unaligned_atomic: Label,
+    ) -> Result<(), CompileError> {
+        self.memory_op(
+            target,
+            memarg,
+            true,
+            8,
+            need_check,
+            memory64,
+            imported_memories,
+            offset,
+            heap_access_oob,
+            unaligned_atomic,
+            |this, addr| this.emit_atomic_rmw(Size::S64, AtomicOp::Add, loc, addr, ret),
+        )
+    }
+    fn i64_atomic_add_8u(
+        &mut self,
+        loc: Location,
+        target: Location,
+        memarg: &MemArg,
+        ret: Location,
+        need_check: bool,
+        memory64: bool,
+        imported_memories: bool,
+        offset: i32,
+        heap_access_oob: Label,
+        unaligned_atomic: Label,
+    ) -> Result<(), CompileError> {
+        self.memory_op(
+            target,
+            memarg,
+            true,
+            1,
+            need_check,
+            memory64,
+            imported_memories,
+            offset,
+            heap_access_oob,
+            unaligned_atomic,
+            |this, addr| this.emit_atomic_rmw(Size::S8, AtomicOp::Add, loc, addr, ret),
+        )
+    }
+    fn i64_atomic_add_16u(
+        &mut self,
+        loc: Location,
+        target: Location,
+        memarg: &MemArg,
+        ret: Location,
+        need_check: bool,
+        memory64: bool,
+        imported_memories: bool,
+        offset: i32,
+        heap_access_oob: Label,
+        unaligned_atomic: Label,
+    ) -> Result<(), CompileError> {
+        self.memory_op(
+            target,
+            memarg,
+            true,
+            2,
+            need_check,
+            memory64,
+            imported_memories,
+            offset,
+            heap_access_oob,
+            unaligned_atomic,
+            |this, addr| this.emit_atomic_rmw(Size::S16, AtomicOp::Add, loc, addr, ret),
+        )
+    }
+    fn i64_atomic_add_32u(
+        &mut self,
+        loc: Location,
+        target: Location,
+        memarg: &MemArg,
+        ret: Location,
+        need_check: bool,
+        memory64: bool,
+        imported_memories: bool,
+        offset: i32,
+        heap_access_oob: Label,
+        unaligned_atomic: Label,
+    ) -> Result<(), CompileError> {
+        self.memory_op(
+            target,
+            memarg,
+            true,
+            4,
+            need_check,
+            memory64,
+            imported_memories,
+            offset,
+            heap_access_oob,
+            unaligned_atomic,
+            |this, addr| this.emit_atomic_rmw(Size::S32, AtomicOp::Add, loc, addr, ret),
+        )
+    }
+    fn i64_atomic_sub(
+        &mut self,
+        loc: Location,
+        target: Location,
+        memarg: &MemArg,
+        ret: Location,
+        need_check: bool,
+        memory64: bool,
+        imported_memories: bool,
+        offset: i32,
+        heap_access_oob: Label,
+        unaligned_atomic: Label,
+    ) -> Result<(), CompileError> {
+        self.memory_op(
+            target,
+            memarg,
+            true,
+            8,
+            need_check,
+            memory64,
+            imported_memories,
+            offset,
+            heap_access_oob,
+            unaligned_atomic,
+            |this, addr| this.emit_atomic_rmw(Size::S64, AtomicOp::Sub, loc, addr, ret),
+        )
+    }
+    fn i64_atomic_sub_8u(
+        &mut self,
+        loc: Location,
+        target: Location,
+        memarg: &MemArg,
+        ret: Location,
+        need_check: bool,
+        memory64: bool,
+        imported_memories: bool,
+        offset: i32,
+        heap_access_oob: Label,
+        unaligned_atomic: Label,
+    ) -> Result<(), CompileError> {
+        self.memory_op(
+            target,
+            memarg,
+            true,
+            1,
+            need_check,
+            memory64,
+            imported_memories,
+            offset,
+            heap_access_oob,
+            unaligned_atomic,
+            |this, addr| this.emit_atomic_rmw(Size::S8, AtomicOp::Sub, loc, addr, ret),
+        )
+    }
+    fn i64_atomic_sub_16u(
+        &mut self,
+        loc: Location,
+        target: Location,
+        memarg: &MemArg,
+        ret: Location,
+        need_check: bool,
+        memory64: bool,
+        imported_memories: bool,
+        offset: i32,
+        heap_access_oob: Label,
+        unaligned_atomic: Label,
+    ) -> Result<(), CompileError> {
+        self.memory_op(
+            target,
+            memarg,
+            true,
+            2,
+            need_check,
+            memory64,
+            imported_memories,
+            offset,
+            heap_access_oob,
+            unaligned_atomic,
+            |this, addr| this.emit_atomic_rmw(Size::S16, AtomicOp::Sub, loc, addr, ret),
+        )
+    }
+    fn i64_atomic_sub_32u(
+        &mut self,
+        loc: Location,
+        target: Location,
+        memarg: &MemArg,
+        ret: Location,
+        need_check: bool,
+        memory64: bool,
+        imported_memories: bool,
+        offset: i32,
+        heap_access_oob: Label,
+        unaligned_atomic: Label,
+    ) -> Result<(), CompileError> {
+        self.memory_op(
+            target,
+            memarg,
+            true,
+            4,
+            need_check,
+            memory64,
+            imported_memories,
+            offset,
+            heap_access_oob,
+            unaligned_atomic,
+            |this, addr| this.emit_atomic_rmw(Size::S32, AtomicOp::Sub, loc, addr, ret),
+        )
+    }
+    fn i64_atomic_and(
+        &mut self,
+        loc: Location,
+        target: Location,
+        memarg: &MemArg,
+        ret: Location,
+        need_check: bool,
+        memory64: bool,
+        imported_memories: bool,
+        offset: i32,
+        heap_access_oob: Label,
+        unaligned_atomic: Label,
+    ) -> Result<(), CompileError> {
+        self.memory_op(
+            target,
+            memarg,
+            true,
+            8,
+            need_check,
+            memory64,
+            imported_memories,
+            offset,
+            heap_access_oob,
+            unaligned_atomic,
+            |this, addr| this.emit_atomic_rmw(Size::S64, AtomicOp::And, loc, addr, ret),
+        )
+    }
+    fn i64_atomic_and_8u(
+        &mut self,
+        loc: Location,
+        target: Location,
+        memarg: &MemArg,
+        ret: Location,
+        need_check: bool,
+        memory64: bool,
+        imported_memories: bool,
+        offset: i32,
+        heap_access_oob: Label,
+        unaligned_atomic: Label,
+    ) -> Result<(), CompileError> {
+        self.memory_op(
+            target,
+            memarg,
+            true,
+            1,
+            need_check,
+            memory64,
+            imported_memories,
+            offset,
+            heap_access_oob,
+            unaligned_atomic,
+            |this, addr| this.emit_atomic_rmw(Size::S8, AtomicOp::And, loc, addr, ret),
+        )
+    }
+    fn i64_atomic_and_16u(
+        &mut self,
+        loc: Location,
+        target: Location,
+        memarg: &MemArg,
+        ret: Location,
+        need_check: bool,
+        memory64: bool,
+        imported_memories: bool,
+        offset: i32,
+        heap_access_oob: Label,
+        unaligned_atomic: Label,
+    ) -> Result<(), CompileError> {
+        self.memory_op(
+            target,
+            memarg,
+            true,
+            2,
+            need_check,
+            memory64,
+            imported_memories,
+            offset,
+            heap_access_oob,
+            unaligned_atomic,
+            |this, addr| this.emit_atomic_rmw(Size::S16, AtomicOp::And, loc, addr, ret),
+        )
+    }
+    fn i64_atomic_and_32u(
+        &mut self,
+        loc: Location,
+        target: Location,
+        memarg: &MemArg,
+        ret: Location,
+        need_check: bool,
+        memory64: bool,
+        imported_memories: bool,
+        offset: i32,
+        heap_access_oob: Label,
+        unaligned_atomic: Label,
+    ) -> Result<(), CompileError> {
+        self.memory_op(
+            target,
+            memarg,
+            true,
+            4,
+            need_check,
+            memory64,
+            imported_memories,
+            offset,
+            heap_access_oob,
+            unaligned_atomic,
+            |this, addr| this.emit_atomic_rmw(Size::S32, AtomicOp::And, loc, addr, ret),
+        )
+    }
+    fn i64_atomic_or(
+        &mut self,
+        loc: Location,
+        target: Location,
+        memarg: &MemArg,
+        ret: Location,
+        need_check: bool,
+        memory64: bool,
+        imported_memories: bool,
+        offset: i32,
+        heap_access_oob: Label,
+        unaligned_atomic: Label,
+    ) -> Result<(), CompileError> {
+        self.memory_op(
+            target,
+            memarg,
+            true,
+            8,
+            need_check,
+            memory64,
+            imported_memories,
+            offset,
+            heap_access_oob,
+            unaligned_atomic,
+            |this, addr| this.emit_atomic_rmw(Size::S64, AtomicOp::Or, loc, addr, ret),
+        )
+    }
+    fn i64_atomic_or_8u(
+        &mut self,
+        loc: Location,
+        target: Location,
+        memarg: &MemArg,
+        ret: Location,
+        need_check: bool,
+        memory64: bool,
+        imported_memories: bool,
+        offset: i32,
+        heap_access_oob: Label,
+        unaligned_atomic: Label,
+    ) -> Result<(), CompileError> {
+        self.memory_op(
+            target,
+            memarg,
+            true,
+            1,
+            need_check,
+            memory64,
+            imported_memories,
+            offset,
+            heap_access_oob,
+            unaligned_atomic,
+            |this, addr| this.emit_atomic_rmw(Size::S8, AtomicOp::Or, loc, addr, ret),
+        )
+    }
+    fn i64_atomic_or_16u(
+        &mut self,
+        loc: Location,
+        target: Location,
+        memarg: &MemArg,
+        ret: Location,
+        need_check: bool,
+        memory64: bool,
+        imported_memories: bool,
+        offset: i32,
+        heap_access_oob: Label,
+        unaligned_atomic: Label,
+    ) -> Result<(), CompileError> {
+        self.memory_op(
+            target,
+            memarg,
+            true,
+            2,
+            need_check,
+            memory64,
+            imported_memories,
+            offset,
+            heap_access_oob,
+            unaligned_atomic,
+            |this, addr| this.emit_atomic_rmw(Size::S16, AtomicOp::Or, loc, addr, ret),
+        )
+    }
+    fn i64_atomic_or_32u(
+        &mut self,
+        loc: Location,
+        target: Location,
+        memarg: &MemArg,
+        ret: Location,
+        need_check: bool,
+        memory64: bool,
+        imported_memories: bool,
+        offset: i32,
+        heap_access_oob: Label,
+        unaligned_atomic: Label,
+    ) -> Result<(), CompileError> {
+        self.memory_op(
+            target,
+            memarg,
+            true,
+            4,
+            need_check,
+            memory64,
+            imported_memories,
+            offset,
+            heap_access_oob,
+            unaligned_atomic,
+            |this, addr| this.emit_atomic_rmw(Size::S32, AtomicOp::Or, loc, addr, ret),
+        )
+    }
+    fn i64_atomic_xor(
+        &mut self,
+        loc: Location,
+        target: Location,
+        memarg: &MemArg,
+        ret: Location,
+        need_check: bool,
+        memory64: bool,
+        imported_memories: bool,
+        offset: i32,
+        heap_access_oob: Label,
+        unaligned_atomic: Label,
+    ) -> Result<(), CompileError> {
+        self.memory_op(
+            target,
+            memarg,
+            true,
+            8,
+            need_check,
+            memory64,
+            imported_memories,
+            offset,
+            heap_access_oob,
+            unaligned_atomic,
+            |this, addr| this.emit_atomic_rmw(Size::S64, AtomicOp::Xor, loc, addr, ret),
+        )
+    }
+    fn i64_atomic_xor_8u(
+        &mut self,
+        loc: Location,
+        target: Location,
+        memarg: &MemArg,
+        ret: Location,
+        need_check: bool,
+        memory64: bool,
+        imported_memories: bool,
+        offset: i32,
+        heap_access_oob: Label,
+        unaligned_atomic: Label,
+    ) -> Result<(), CompileError> {
+        self.memory_op(
+            target,
+            memarg,
+            true,
+            1,
+            need_check,
+            memory64,
+            imported_memories,
+            offset,
+            heap_access_oob,
+            unaligned_atomic,
+            |this, addr| this.emit_atomic_rmw(Size::S8, AtomicOp::Xor, loc, addr, ret),
+        )
+    }
+    fn i64_atomic_xor_16u(
+        &mut self,
+        loc: Location,
+        target: Location,
+        memarg: &MemArg,
+        ret: Location,
+        need_check: bool,
+        memory64: bool,
+        imported_memories: bool,
+        offset: i32,
+        heap_access_oob: Label,
+        unaligned_atomic: Label,
+    ) -> Result<(), CompileError> {
+        self.memory_op(
+            target,
+            memarg,
+            true,
+            2,
+            need_check,
+            memory64,
+            imported_memories,
+            offset,
+            heap_access_oob,
+            unaligned_atomic,
+            |this, addr| this.emit_atomic_rmw(Size::S16, AtomicOp::Xor, loc, addr, ret),
+        )
+    }
+    fn i64_atomic_xor_32u(
+        &mut self,
+        loc: Location,
+        target: Location,
+        memarg: &MemArg,
+        ret: Location,
+        need_check: bool,
+        memory64: bool,
+        imported_memories: bool,
+        offset: i32,
+        heap_access_oob: Label,
+        unaligned_atomic: Label,
+    ) -> Result<(), CompileError> {
+        self.memory_op(
+            target,
+            memarg,
+            true,
+            4,
+            need_check,
+            memory64,
+            imported_memories,
+            offset,
+            heap_access_oob,
+            unaligned_atomic,
+            |this, addr| this.emit_atomic_rmw(Size::S32, AtomicOp::Xor, loc, addr, ret),
+        )
+    }
+    fn i64_atomic_xchg(
+        &mut self,
+        loc: Location,
+        target: Location,
+        memarg: &MemArg,
+        ret: Location,
+        need_check: bool,
+        memory64: bool,
+        imported_memories: bool,
+        offset: i32,
+        heap_access_oob: Label,
+        unaligned_atomic: Label,
+    ) -> Result<(), CompileError> {
+        self.memory_op(
+            target,
+            memarg,
+            true,
+            8,
+            need_check,
+            memory64,
+            imported_memories,
+            offset,
+            heap_access_oob,
+            unaligned_atomic,
+            |this, addr| this.emit_atomic_rmw(Size::S64, AtomicOp::Xchg, loc, addr, ret),
+        )
+    }
+    fn i64_atomic_xchg_8u(
+        &mut self,
+        loc: Location,
+        target: Location,
+        memarg: &MemArg,
+        ret: Location,
+        need_check: bool,
+        memory64: bool,
+        imported_memories: bool,
+        offset: i32,
+        heap_access_oob: Label,
+        unaligned_atomic: Label,
+    ) -> Result<(), CompileError> {
+        self.memory_op(
+            target,
+            memarg,
+            true,
+            1,
+            need_check,
+            memory64,
+            imported_memories,
+            offset,
+            heap_access_oob,
+            unaligned_atomic,
+            |this, addr| this.emit_atomic_rmw(Size::S8, AtomicOp::Xchg, loc, addr, ret),
+        )
+    }
+    fn i64_atomic_xchg_16u(
+        &mut self,
+        loc: Location,
+        target: Location,
+        memarg: &MemArg,
+        ret: Location,
+        need_check: bool,
+        memory64: bool,
+        imported_memories: bool,
+        offset: i32,
+        heap_access_oob: Label,
+        unaligned_atomic: Label,
+    ) -> Result<(), CompileError> {
+        self.memory_op(
+            target,
+            memarg,
+            true,
+            2,
+            need_check,
+            memory64,
+            imported_memories,
+            offset,
+            heap_access_oob,
+            unaligned_atomic,
+            |this, addr| this.emit_atomic_rmw(Size::S16, AtomicOp::Xchg, loc, addr, ret),
+        )
+    }
+    fn i64_atomic_xchg_32u(
+        &mut self,
+        loc: Location,
+        target: Location,
+        memarg: &MemArg,
+        ret: Location,
+        need_check: bool,
+        memory64: bool,
+        imported_memories: bool,
+        offset: i32,
+        heap_access_oob: Label,
+        unaligned_atomic: Label,
+    ) -> Result<(), CompileError> {
+        self.memory_op(
+            target,
+            memarg,
+            true,
+            4,
+            need_check,
+            memory64,
+            imported_memories,
+            offset,
+            heap_access_oob,
+            unaligned_atomic,
+            |this, addr| this.emit_atomic_rmw(Size::S32, AtomicOp::Xchg, loc, addr, ret),
+        )
+    }
+    fn i64_atomic_cmpxchg(
+        &mut self,
+        new: Location,
+        cmp: Location,
+        target: Location,
+        memarg: &MemArg,
+        ret: Location,
+        need_check: bool,
+        memory64: bool,
+        imported_memories: bool,
+        offset: i32,
+        heap_access_oob: Label,
+        unaligned_atomic: Label,
+    ) -> Result<(), CompileError> {
+        self.memory_op(
+            target,
+            memarg,
+            true,
+            8,
+            need_check,
+            memory64,
+            imported_memories,
+            offset,
+            heap_access_oob,
+            unaligned_atomic,
+            |this, addr| this.emit_atomic_cmpxchg(Size::S64, new, cmp, addr, ret),
+        )
+    }
+    fn i64_atomic_cmpxchg_8u(
+        &mut self,
+        new: Location,
+        cmp: Location,
+        target: Location,
+        memarg: &MemArg,
+        ret: Location,
+        need_check: bool,
+        memory64: bool,
+        imported_memories: bool,
+        offset: i32,
+        heap_access_oob: Label,
+        unaligned_atomic: Label,
+    ) -> Result<(), CompileError> {
+        self.memory_op(
+            target,
+            memarg,
+            true,
+            1,
+            need_check,
+            memory64,
+            imported_memories,
+            offset,
+            heap_access_oob,
+            unaligned_atomic,
+            |this, addr| this.emit_atomic_cmpxchg(Size::S8, new, cmp, addr, ret),
+        )
+    }
+    fn i64_atomic_cmpxchg_16u(
+        &mut self,
+        new: Location,
+        cmp: Location,
+        target: Location,
+        memarg: &MemArg,
+        ret: Location,
+        need_check: bool,
+        memory64: bool,
+        imported_memories: bool,
+        offset: i32,
+        heap_access_oob: Label,
+        unaligned_atomic: Label,
+    ) -> Result<(), CompileError> {
+        self.memory_op(
+            target,
+            memarg,
+            true,
+            2,
+            need_check,
+            memory64,
+            imported_memories,
+            offset,
+            heap_access_oob,
+            unaligned_atomic,
+            |this, addr| this.emit_atomic_cmpxchg(Size::S16, new, cmp, addr, ret),
+        )
+    }
+    fn i64_atomic_cmpxchg_32u(
+        &mut self,
+        new: Location,
+        cmp: Location,
+        target: Location,
+        memarg: &MemArg,
+        ret: Location,
+        need_check: bool,
+        memory64: bool,
+        imported_memories: bool,
+        offset: i32,
+        heap_access_oob: Label,
+        unaligned_atomic: Label,
+    ) -> Result<(), CompileError> {
+        self.memory_op(
+            target,
+            memarg,
+            true,
+            4,
+            need_check,
+            memory64,
+            imported_memories,
+            offset,
+            heap_access_oob,
+            unaligned_atomic,
+            |this, addr| this.emit_atomic_cmpxchg(Size::S32, new, cmp, addr, ret),
+        )
+    }
+    fn f32_load(
+        &mut self,
+        addr: Location,
+        memarg: &MemArg,
+        ret: Location,
+        need_check: bool,
+        memory64: bool,
+        imported_memories: bool,
+        offset: i32,
+        heap_access_oob: Label,
+        unaligned_atomic: Label,
+    ) -> Result<(), CompileError> {
+        self.memory_op(
+            addr,
+            memarg,
+            false,
+            4,
+            need_check,
+            memory64,
+            imported_memories,
+            offset,
+            heap_access_oob,
+            unaligned_atomic,
+            |this, addr| this.emit_load_to(Size::S32, false, ret, addr),
+        )
+    }
+    fn f32_save(
+        &mut self,
+        target_value: Location,
+        memarg: &MemArg,
+        target_addr: Location,
+        canonicalize: bool,
+        need_check: bool,
+        memory64: bool,
+        imported_memories: bool,
+        offset: i32,
+        heap_access_oob: Label,
+        unaligned_atomic: Label,
+    ) -> Result<(), CompileError> {
+        let canonicalize = canonicalize && self.arch_supports_canonicalize_nan();
+        self.memory_op(
+            target_addr,
+            memarg,
+            false,
+            4,
+            need_check,
+            memory64,
+            imported_memories,
+            offset,
+            heap_access_oob,
+            unaligned_atomic,
+            |this, addr| {
+                if !canonicalize {
+                    this.emit_store_from(Size::S32, target_value, addr)
+                } else {
+                    this.canonicalize_nan(Size::S32, target_value, Location::Memory(addr, 0))
+                }
+            },
+        )
+    }
+    fn f64_load(
+        &mut self,
+        addr: Location,
+        memarg: &MemArg,
+        ret: Location,
+        need_check: bool,
+        memory64: bool,
+        imported_memories: bool,
+        offset: i32,
+        heap_access_oob: Label,
+        unaligned_atomic: Label,
+    ) -> Result<(), CompileError> {
+        self.memory_op(
+            addr,
+            memarg,
+            false,
+            8,
+            need_check,
+            memory64,
+            imported_memories,
+            offset,
+            heap_access_oob,
+            unaligned_atomic,
+            |this, addr| this.emit_load_to(Size::S64, false, ret, addr),
+        )
+    }
+    fn f64_save(
+        &mut self,
+        target_value: Location,
+        memarg: &MemArg,
+        target_addr: Location,
+        canonicalize: bool,
+        need_check: bool,
+        memory64: bool,
+        imported_memories: bool,
+        offset: i32,
+        heap_access_oob: Label,
+        unaligned_atomic: Label,
+    ) -> Result<(), CompileError> {
+        let canonicalize = canonicalize && self.arch_supports_canonicalize_nan();
+        self.memory_op(
+            target_addr,
+            memarg,
+            false,
+            8,
+            need_check,
+            memory64,
+            imported_memories,
+            offset,
+            heap_access_oob,
+            unaligned_atomic,
+            |this, addr| {
+                if !canonicalize {
+                    this.emit_store_from(Size::S64, target_value, addr)
+                } else {
+                    this.canonicalize_nan(Size::S64, target_value, Location::Memory(addr, 0))
+                }
+            },
+        )
+    }
+    fn convert_f64_i64(
+        &mut self,
+        loc: Location,
+        signed: bool,
+        ret: Location,
+    ) -> Result<(), CompileError> {
+        self.emit_convert_from_int(Size::S64, signed, Size::S64, loc, ret)
+    }
+    fn convert_f64_i32(
+        &mut self,
+        loc: Location,
+        signed: bool,
+        ret: Location,
+    ) -> Result<(), CompileError> {
+        self.emit_convert_from_int(Size::S32, signed, Size::S64, loc, ret)
+    }
+    fn convert_f32_i64(
+        &mut self,
+        loc: Location,
+        signed: bool,
+        ret: Location,
+    ) -> Result<(), CompileError> {
+        self.emit_convert_from_int(Size::S64, signed, Size::S32, loc, ret)
+    }
+    fn convert_f32_i32(
+        &mut self,
+        loc: Location,
+        signed: bool,
+        ret: Location,
+    ) -> Result<(), CompileError> {
+        self.emit_convert_from_int(Size::S32, signed, Size::S32, loc, ret)
+    }
+    fn convert_i64_f64(
+        &mut self,
+        loc: Location,
+        ret: Location,
+        signed: bool,
+        sat: bool,
+    ) -> Result<(), CompileError> {
+        self.emit_convert_to_int(Size::S64, Size::S64, signed, sat, loc, ret)
+    }
+    fn convert_i32_f64(
+        &mut self,
+        loc: Location,
+        ret: Location,
+        signed: bool,
+        sat: bool,
+    ) -> Result<(), CompileError> {
+        self.emit_convert_to_int(Size::S64, Size::S32, signed, sat, loc, ret)
+    }
+    fn convert_i64_f32(
+        &mut self,
+        loc: Location,
+        ret: Location,
+        signed: bool,
+        sat: bool,
+    ) -> Result<(), CompileError> {
+        self.emit_convert_to_int(Size::S32, Size::S64, signed, sat, loc, ret)
+    }
+    fn convert_i32_f32(
+        &mut self,
+        loc: Location,
+        ret: Location,
+        signed: bool,
+        sat: bool,
+    ) -> Result<(), CompileError> {
+        self.emit_convert_to_int(Size::S32, Size::S32, signed, sat, loc, ret)
+    }
+    fn convert_f64_f32(&mut self, loc: Location, ret: Location) -> Result<(), CompileError> {
+        self.emit_convert_float(Size::S32, Size::S64, loc, ret)
+    }
+    fn convert_f32_f64(&mut self, loc: Location, ret: Location) -> Result<(), CompileError> {
+        self.emit_convert_float(Size::S64, Size::S32, loc, ret)
+    }
+    fn f64_neg(&mut self, loc: Location, ret: Location) -> Result<(), CompileError> {
+        self.emit_relaxed_fop3(Size::S64, FpOp::Sgnjn, loc, loc, ret)
+    }
+    fn f64_abs(&mut self, loc: Location, ret: Location) -> Result<(), CompileError> {
+        self.emit_relaxed_fop3(Size::S64, FpOp::Sgnjx, loc, loc, ret)
+    }
+    fn emit_i64_copysign(&mut self, tmp1: GPR, tmp2: GPR) -> Result<(), CompileError> {
+        self.assembler
+            .emit_mov_imm(SCRATCH, 0x7fff_ffff_ffff_ffff)?;
+        self.assembler
+            .emit_alu(Size::S64, AluOp::And, tmp1, tmp1, SCRATCH)?;
+        self.assembler
+            .emit_alu_imm(Size::S64, AluImmOp::Xori, SCRATCH, SCRATCH, -1)?;
+        self.assembler
+            .emit_alu(Size::S64, AluOp::And, tmp2, tmp2, SCRATCH)?;
+        self.assembler
+            .emit_alu(Size::S64, AluOp::Or, tmp1, tmp1, tmp2)
+    }
+    fn f64_sqrt(&mut self, loc: Location, ret: Location) -> Result<(), CompileError> {
+        self.emit_fsqrt(Size::S64, loc, ret)
+    }
+    fn f64_trunc(&mut self, loc: Location, ret: Location) -> Result<(), CompileError> {
+        self.emit_fround(Size::S64, RoundingMode::Rtz, loc, ret)
+    }
+    fn f64_ceil(&mut self, loc: Location, ret: Location) -> Result<(), CompileError> {
+        self.emit_fround(Size::S64, RoundingMode::Rup, loc, ret)
+    }
+    fn f64_floor(&mut self, loc: Location, ret: Location) -> Result<(), CompileError> {
+        self.emit_fround(Size::S64, RoundingMode::Rdn, loc, ret)
+    }
+    fn f64_nearest(&mut self, loc: Location, ret: Location) -> Result<(), CompileError> {
+        self.emit_fround(Size::S64, RoundingMode::Rne, loc, ret)
+    }
+    fn f64_cmp_ge(
+        &mut self,
+        loc_a: Location,
+        loc_b: Location,
+        ret: Location,
+    ) -> Result<(), CompileError> {
+        self.emit_fcmpop(Size::S64, FpCmp::Le, false, loc_b, loc_a, ret)
+    }
+    fn f64_cmp_gt(
+        &mut self,
+        loc_a: Location,
+        loc_b: Location,
+        ret: Location,
+    ) -> Result<(), CompileError> {
+        self.emit_fcmpop(Size::S64, FpCmp::Lt, false, loc_b, loc_a, ret)
+    }
+    fn f64_cmp_le(
+        &mut self,
+        loc_a: Location,
+        loc_b: Location,
+        ret: Location,
+    ) -> Result<(), CompileError> {
+        self.emit_fcmpop(Size::S64, FpCmp::Le, false, loc_a, loc_b, ret)
+    }
+    fn f64_cmp_lt(
+        &mut self,
+        loc_a: Location,
+        loc_b: Location,
+        ret: Location,
+    ) -> Result<(), CompileError> {
+        self.emit_fcmpop(Size::S64, FpCmp::Lt, false, loc_a, loc_b, ret)
+    }
+    fn f64_cmp_ne(
+        &mut self,
+        loc_a: Location,
+        loc_b: Location,
+        ret: Location,
+    ) -> Result<(), CompileError> {
+        self.emit_fcmpop(Size::S64, FpCmp::Eq, true, loc_a, loc_b, ret)
+    }
+    fn f64_cmp_eq(
+        &mut self,
+        loc_a: Location,
+        loc_b: Location,
+        ret: Location,
+    ) -> Result<(), CompileError> {
+        self.emit_fcmpop(Size::S64, FpCmp::Eq, false, loc_a, loc_b, ret)
+    }
+    fn f64_min(
+        &mut self,
+        loc_a: Location,
+        loc_b: Location,
+        ret: Location,
+    ) -> Result<(), CompileError> {
+        self.emit_fminmax(Size::S64, FpOp::Min, loc_a, loc_b, ret)
+    }
+    fn f64_max(
+        &mut self,
+        loc_a: Location,
+        loc_b: Location,
+        ret: Location,
+    ) -> Result<(), CompileError> {
+        self.emit_fminmax(Size::S64, FpOp::Max, loc_a, loc_b, ret)
+    }
+    fn f64_add(
+        &mut self,
+        loc_a: Location,
+        loc_b: Location,
+        ret: Location,
+    ) -> Result<(), CompileError> {
+        self.emit_relaxed_fop3(Size::S64, FpOp::Add, loc_a, loc_b, ret)
+    }
+    fn f64_sub(
+        &mut self,
+        loc_a: Location,
+        loc_b: Location,
+        ret: Location,
+    ) -> Result<(), CompileError> {
+        self.emit_relaxed_fop3(Size::S64, FpOp::Sub, loc_a, loc_b, ret)
+    }
+    fn f64_mul(
+        &mut self,
+        loc_a: Location,
+        loc_b: Location,
+        ret: Location,
+    ) -> Result<(), CompileError> {
+        self.emit_relaxed_fop3(Size::S64, FpOp::Mul, loc_a, loc_b, ret)
+    }
+    fn f64_div(
+        &mut self,
+        loc_a: Location,
+        loc_b: Location,
+        ret: Location,
+    ) -> Result<(), CompileError> {
+        self.emit_relaxed_fop3(Size::S64, FpOp::Div, loc_a, loc_b, ret)
+    }
+    fn f32_neg(&mut self, loc: Location, ret: Location) -> Result<(), CompileError> {
+        self.emit_relaxed_fop3(Size::S32, FpOp::Sgnjn, loc, loc, ret)
+    }
+    fn f32_abs(&mut self, loc: Location, ret: Location) -> Result<(), CompileError> {
+        self.emit_relaxed_fop3(Size::S32, FpOp::Sgnjx, loc, loc, ret)
+    }
+    fn emit_i32_copysign(&mut self, tmp1: GPR, tmp2: GPR) -> Result<(), CompileError> {
+        self.assembler.emit_mov_imm(SCRATCH, 0x7fff_ffff)?;
+        self.assembler
+            .emit_alu(Size::S64, AluOp::And, tmp1, tmp1, SCRATCH)?;
+        self.assembler
+            .emit_alu_imm(Size::S64, AluImmOp::Xori, SCRATCH, SCRATCH, -1)?;
+        self.assembler
+            .emit_alu(Size::S64, AluOp::And, tmp2, tmp2, SCRATCH)?;
+        self.assembler
+            .emit_alu(Size::S64, AluOp::Or, tmp1, tmp1, tmp2)
+    }
+    fn f32_sqrt(&mut self, loc: Location, ret: Location) -> Result<(), CompileError> {
+        self.emit_fsqrt(Size::S32, loc, ret)
+    }
+    fn f32_trunc(&mut self, loc: Location, ret: Location) -> Result<(), CompileError> {
+        self.emit_fround(Size::S32, RoundingMode::Rtz, loc, ret)
+    }
+    fn f32_ceil(&mut self, loc: Location, ret: Location) -> Result<(), CompileError> {
+        self.emit_fround(Size::S32, RoundingMode::Rup, loc, ret)
+    }
+    fn f32_floor(&mut self, loc: Location, ret: Location) -> Result<(), CompileError> {
+        self.emit_fround(Size::S32, RoundingMode::Rdn, loc, ret)
+    }
+    fn f32_nearest(&mut self, loc: Location, ret: Location) -> Result<(), CompileError> {
+        self.emit_fround(Size::S32, RoundingMode::Rne, loc, ret)
+    }
+    fn f32_cmp_ge(
+        &mut self,
+        loc_a: Location,
+        loc_b: Location,
+        ret: Location,
+    ) -> Result<(), CompileError> {
+        self.emit_fcmpop(Size::S32, FpCmp::Le, false, loc_b, loc_a, ret)
+    }
+    fn f32_cmp_gt(
+        &mut self,
+        loc_a: Location,
+        loc_b: Location,
+        ret: Location,
+    ) -> Result<(), CompileError> {
+        self.emit_fcmpop(Size::S32, FpCmp::Lt, false, loc_b, loc_a, ret)
+    }
+    fn f32_cmp_le(
+        &mut self,
+        loc_a: Location,
+        loc_b: Location,
+        ret: Location,
+    ) -> Result<(), CompileError> {
+        self.emit_fcmpop(Size::S32, FpCmp::Le, false, loc_a, loc_b, ret)
+    }
+    fn f32_cmp_lt(
+        &mut self,
+        loc_a: Location,
+        loc_b: Location,
+        ret: Location,
+    ) -> Result<(), CompileError> {
+        self.emit_fcmpop(Size::S32, FpCmp::Lt, false, loc_a, loc_b, ret)
+    }
+    fn f32_cmp_ne(
+        &mut self,
+        loc_a: Location,
+        loc_b: Location,
+        ret: Location,
+    ) -> Result<(), CompileError> {
+        self.emit_fcmpop(Size::S32, FpCmp::Eq, true, loc_a, loc_b, ret)
+    }
+    fn f32_cmp_eq(
+        &mut self,
+        loc_a: Location,
+        loc_b: Location,
+        ret: Location,
+    ) -> Result<(), CompileError> {
+        self.emit_fcmpop(Size::S32, FpCmp::Eq, false, loc_a, loc_b, ret)
+    }
+    fn f32_min(
+        &mut self,
+        loc_a: Location,
+        loc_b: Location,
+        ret: Location,
+    ) -> Result<(), CompileError> {
+        self.emit_fminmax(Size::S32, FpOp::Min, loc_a, loc_b, ret)
+    }
+    fn f32_max(
+        &mut self,
+        loc_a: Location,
+        loc_b: Location,
+        ret: Location,
+    ) -> Result<(), CompileError> {
+        self.emit_fminmax(Size::S32, FpOp::Max, loc_a, loc_b, ret)
+    }
+    fn f32_add(
+        &mut self,
+        loc_a: Location,
+        loc_b: Location,
+        ret: Location,
+    ) -> Result<(), CompileError> {
+        self.emit_relaxed_fop3(Size::S32, FpOp::Add, loc_a, loc_b, ret)
+    }
+    fn f32_sub(
+        &mut self,
+        loc_a: Location,
+        loc_b: Location,
+        ret: Location,
+    ) -> Result<(), CompileError> {
+        self.emit_relaxed_fop3(Size::S32, FpOp::Sub, loc_a, loc_b, ret)
+    }
+    fn f32_mul(
+        &mut self,
+        loc_a: Location,
+        loc_b: Location,
+        ret: Location,
+    ) -> Result<(), CompileError> {
+        self.emit_relaxed_fop3(Size::S32, FpOp::Mul, loc_a, loc_b, ret)
+    }
+    fn f32_div(
+        &mut self,
+        loc_a: Location,
+        loc_b: Location,
+        ret: Location,
+    ) -> Result<(), CompileError> {
+        self.emit_relaxed_fop3(Size::S32, FpOp::Div, loc_a, loc_b, ret)
+    }
+    fn emit_simd(
+        &mut self,
+        op: &Operator,
+        _args: &[Location],
+        _ret: &[Location],
+    ) -> Result<(), CompileError> {
+        codegen_error!("singlepass can't emit SIMD operator {:?}", op)
+    }
+    fn emit_simd_memory(
+        &mut self,
+        op: &Operator,
+        _addr: Location,
+        _args: &[Location],
+        _ret: &[Location],
+        _need_check: bool,
+        _memory64: bool,
+        _imported_memories: bool,
+        _offset: i32,
+        _heap_access_oob: Label,
+        _unaligned_atomic: Label,
+    ) -> Result<(), CompileError> {
+        codegen_error!("singlepass can't emit SIMD memory operator {:?}", op)
+    }
+    fn gen_std_trampoline(
+        &self,
+        sig: &FunctionType,
+        calling_convention: CallingConvention,
+    ) -> Result<FunctionBody, CompileError> {
+        gen_std_trampoline_riscv(sig, calling_convention)
+    }
+    // Generates dynamic import function call trampoline for a function type.
+    fn gen_std_dynamic_import_trampoline(
+        &self,
+        vmoffsets: &VMOffsets,
+        sig: &FunctionType,
+        calling_convention: CallingConvention,
+    ) -> Result<FunctionBody, CompileError> {
+        gen_std_dynamic_import_trampoline_riscv(vmoffsets, sig, calling_convention)
+    }
+    // Singlepass calls import functions through a trampoline.
+    fn gen_import_call_trampoline(
+        &self,
+        vmoffsets: &VMOffsets,
+        index: FunctionIndex,
+        sig: &FunctionType,
+        calling_convention: CallingConvention,
+    ) -> Result<CustomSection, CompileError> {
+        gen_import_call_trampoline_riscv(vmoffsets, index, sig, calling_convention)
+    }
+    #[cfg(feature = "unwind")]
+    fn gen_dwarf_unwind_info(&mut self, code_len: usize) -> Option<UnwindInstructions> {
+        let mut instructions = vec![];
+        for &(instruction_offset, ref inst) in &self.unwind_ops {
+            let instruction_offset = instruction_offset as u32;
+            match *inst {
+                UnwindOps::PushFP { up_to_sp } => {
+                    instructions.push((
+                        instruction_offset,
+                        CallFrameInstruction::CfaOffset(up_to_sp as i32),
+                    ));
+                    instructions.push((
+                        instruction_offset,
+                        CallFrameInstruction::Offset(RiscV::X8, -(up_to_sp as i32)),
+                    ));
+                }
+                UnwindOps::Push2Regs {
+                    reg1,
+                    reg2,
+                    up_to_sp,
+                } => {
+                    instructions.push((
+                        instruction_offset,
+                        CallFrameInstruction::CfaOffset(up_to_sp as i32),
+                    ));
+                    instructions.push((
+                        instruction_offset,
+                        CallFrameInstruction::Offset(dwarf_index(reg2), -(up_to_sp as i32) + 8),
+                    ));
+                    instructions.push((
+                        instruction_offset,
+                        CallFrameInstruction::Offset(dwarf_index(reg1), -(up_to_sp as i32)),
+                    ));
+                }
+                UnwindOps::DefineNewFrame => {
+                    instructions.push((
+                        instruction_offset,
+                        CallFrameInstruction::CfaRegister(RiscV::X8),
+                    ));
+                }
+                UnwindOps::SaveRegister { reg, bp_neg_offset } => instructions.push((
+                    instruction_offset,
+                    CallFrameInstruction::Offset(dwarf_index(reg), -bp_neg_offset),
+                )),
+            }
+        }
+        Some(UnwindInstructions {
+            instructions,
+            len: code_len as u32,
+        })
+    }
+    #[cfg(not(feature = "unwind"))]
+    fn gen_dwarf_unwind_info(&mut self, _code_len: usize) -> Option<UnwindInstructions> {
+        None
+    }
+
+    fn gen_windows_unwind_info(&mut self, _code_len: usize) -> Option<Vec<u8>> {
+        None
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn test_move_location(machine: &mut MachineRiscv, size: Size) -> Result<(), CompileError> {
+        machine.move_location(size, Location::GPR(GPR::X11), Location::GPR(GPR::X12))?;
+        machine.move_location(
+            size,
+            Location::GPR(GPR::X11),
+            Location::Memory(GPR::X12, 10),
+        )?;
+        machine.move_location(
+            size,
+            Location::GPR(GPR::X11),
+            Location::Memory(GPR::X12, -10),
+        )?;
+        machine.move_location(
+            size,
+            Location::GPR(GPR::X11),
+            Location::Memory(GPR::X12, 1024),
+        )?;
+        machine.move_location(
+            size,
+            Location::GPR(GPR::X11),
+            Location::Memory(GPR::X12, -1024),
+        )?;
+        machine.move_location(
+            size,
+            Location::Memory(GPR::X12, 10),
+            Location::GPR(GPR::X11),
+        )?;
+        machine.move_location(
+            size,
+            Location::Memory(GPR::X12, -10),
+            Location::GPR(GPR::X11),
+        )?;
+        machine.move_location(
+            size,
+            Location::Memory(GPR::X12, 1024),
+            Location::GPR(GPR::X11),
+        )?;
+        machine.move_location(
+            size,
+            Location::Memory(GPR::X12, -1024),
+            Location::GPR(GPR::X11),
+        )?;
+        machine.move_location(size, Location::GPR(GPR::X11), Location::SIMD(FPR::F0))?;
+        machine.move_location(size, Location::SIMD(FPR::F0), Location::GPR(GPR::X11))?;
+        machine.move_location(
+            size,
+            Location::SIMD(FPR::F0),
+            Location::Memory(GPR::X12, 10),
+        )?;
+        machine.move_location(
+            size,
+            Location::SIMD(FPR::F0),
+            Location::Memory(GPR::X12, -10),
+        )?;
+        machine.move_location(
+            size,
+            Location::SIMD(FPR::F0),
+            Location::Memory(GPR::X12, 1024),
+        )?;
+        machine.move_location(
+            size,
+            Location::SIMD(FPR::F0),
+            Location::Memory(GPR::X12, -1024),
+        )?;
+        machine.move_location(
+            size,
+            Location::Memory(GPR::X12, 10),
+            Location::SIMD(FPR::F0),
+        )?;
+        machine.move_location(
+            size,
+            Location::Memory(GPR::X12, -10),
+            Location::SIMD(FPR::F0),
+        )?;
+        machine.move_location(
+            size,
+            Location::Memory(GPR::X12, 1024),
+            Location::SIMD(FPR::F0),
+        )?;
+        machine.move_location(
+            size,
+            Location::Memory(GPR::X12, -1024),
+            Location::SIMD(FPR::F0),
+        )?;
+
+        Ok(())
+    }
+
+    fn test_move_location_extended(
+        machine: &mut MachineRiscv,
+        signed: bool,
+        sized: Size,
+    ) -> Result<(), CompileError> {
+        machine.move_location_extend(
+            sized,
+            signed,
+            Location::GPR(GPR::X10),
+            Size::S64,
+            Location::GPR(GPR::X11),
+        )?;
+        machine.move_location_extend(
+            sized,
+            signed,
+            Location::GPR(GPR::X10),
+            Size::S64,
+            Location::Memory(GPR::X11, 10),
+        )?;
+        machine.move_location_extend(
+            sized,
+            signed,
+            Location::GPR(GPR::X10),
+            Size::S64,
+            Location::Memory(GPR::X11, 16),
+        )?;
+        machine.move_location_extend(
+            sized,
+            signed,
+            Location::GPR(GPR::X10),
+            Size::S64,
+            Location::Memory(GPR::X11, -16),
+        )?;
+        machine.move_location_extend(
+            sized,
+            signed,
+            Location::GPR(GPR::X10),
+            Size::S64,
+            Location::Memory(GPR::X11, 1024),
+        )?;
+        machine.move_location_extend(
+            sized,
+            signed,
+            Location::GPR(GPR::X10),
+            Size::S64,
+            Location::Memory(GPR::X11, -1024),
+        )?;
+        machine.move_location_extend(
+            sized,
+            signed,
+            Location::Memory(GPR::X10, 10),
+            Size::S64,
+            Location::GPR(GPR::X11),
+        )?;
+
+        Ok(())
+    }
+
+    fn test_binop_op(
+        machine: &mut MachineRiscv,
+        op: fn(&mut MachineRiscv, Location, Location, Location) -> Result<(), CompileError>,
+    ) -> Result<(), CompileError> {
+        op(
+            machine,
+            Location::GPR(GPR::X12),
+            Location::GPR(GPR::X12),
+            Location::GPR(GPR::X10),
+        )?;
+        op(
+            machine,
+            Location::GPR(GPR::X12),
+            Location::Imm32(10),
+            Location::GPR(GPR::X10),
+        )?;
+        op(
+            machine,
+            Location::GPR(GPR::X10),
+            Location::GPR(GPR::X10),
+            Location::GPR(GPR::X10),
+        )?;
+        op(
+            machine,
+            Location::Imm32(10),
+            Location::GPR(GPR::X12),
+            Location::GPR(GPR::X10),
+        )?;
+        op(
+            machine,
+            Location::GPR(GPR::X10),
+            Location::GPR(GPR::X12),
+            Location::Memory(GPR::X10, 10),
+        )?;
+        op(
+            machine,
+            Location::GPR(GPR::X10),
+            Location::Memory(GPR::X12, 16),
+            Location::Memory(GPR::X10, 10),
+        )?;
+        op(
+            machine,
+            Location::Memory(GPR::X10, 0),
+            Location::Memory(GPR::X12, 16),
+            Location::Memory(GPR::X10, 10),
+        )?;
+
+        Ok(())
+    }
+
+    fn test_float_binop_op(
+        machine: &mut MachineRiscv,
+        op: fn(&mut MachineRiscv, Location, Location, Location) -> Result<(), CompileError>,
+    ) -> Result<(), CompileError> {
+        op(
+            machine,
+            Location::SIMD(FPR::F3),
+            Location::SIMD(FPR::F2),
+            Location::SIMD(FPR::F0),
+        )?;
+        op(
+            machine,
+            Location::SIMD(FPR::F0),
+            Location::SIMD(FPR::F2),
+            Location::SIMD(FPR::F0),
+        )?;
+        op(
+            machine,
+            Location::SIMD(FPR::F0),
+            Location::SIMD(FPR::F0),
+            Location::SIMD(FPR::F0),
+        )?;
+        op(
+            machine,
+            Location::Memory(GPR::X10, 0),
+            Location::SIMD(FPR::F2),
+            Location::SIMD(FPR::F0),
+        )?;
+        op(
+            machine,
+            Location::Memory(GPR::X10, 0),
+            Location::Memory(GPR::X11, 10),
+            Location::SIMD(FPR::F0),
+        )?;
+        op(
+            machine,
+            Location::Memory(GPR::X10, 0),
+            Location::Memory(GPR::X11, 16),
+            Location::Memory(GPR::X12, 32),
+        )?;
+        op(
+            machine,
+            Location::SIMD(FPR::F0),
+            Location::Memory(GPR::X11, 16),
+            Location::Memory(GPR::X12, 32),
+        )?;
+        op(
+            machine,
+            Location::SIMD(FPR::F0),
+            Location::SIMD(FPR::F1),
+            Location::Memory(GPR::X12, 32),
+        )?;
+
+        Ok(())
+    }
+
+    fn test_float_cmp_op(
+        machine: &mut MachineRiscv,
+        op: fn(&mut MachineRiscv, Location, Location, Location) -> Result<(), CompileError>,
+    ) -> Result<(), CompileError> {
+        op(
+            machine,
+            Location::SIMD(FPR::F3),
+            Location::SIMD(FPR::F2),
+            Location::GPR(GPR::X10),
+        )?;
+        op(
+            machine,
+            Location::SIMD(FPR::F0),
+            Location::SIMD(FPR::F0),
+            Location::GPR(GPR::X10),
+        )?;
+        op(
+            machine,
+            Location::Memory(GPR::X11, 0),
+            Location::SIMD(FPR::F2),
+            Location::GPR(GPR::X10),
+        )?;
+        op(
+            machine,
+            Location::Memory(GPR::X11, 0),
+            Location::Memory(GPR::X12, 10),
+            Location::GPR(GPR::X10),
+        )?;
+        op(
+            machine,
+            Location::Memory(GPR::X11, 0),
+            Location::Memory(GPR::X12, 16),
+            Location::Memory(GPR::X10, 32),
+        )?;
+        op(
+            machine,
+            Location::SIMD(FPR::F0),
+            Location::Memory(GPR::X12, 16),
+            Location::Memory(GPR::X10, 32),
+        )?;
+        op(
+            machine,
+            Location::SIMD(FPR::F0),
+            Location::SIMD(FPR::F1),
+            Location::Memory(GPR::X10, 32),
+        )?;
+
+        Ok(())
+    }
+
+    fn test_divop(
+        machine: &mut MachineRiscv,
+        op: fn(
+            &mut MachineRiscv,
+            Location,
+            Location,
+            Location,
+            Label,
+            Label,
+        ) -> Result<usize, CompileError>,
+    ) -> Result<(), CompileError> {
+        let integer_division_by_zero = machine.get_label();
+        let integer_overflow = machine.get_label();
+        op(
+            machine,
+            Location::GPR(GPR::X12),
+            Location::GPR(GPR::X13),
+            Location::GPR(GPR::X10),
+            integer_division_by_zero,
+            integer_overflow,
+        )?;
+        op(
+            machine,
+            Location::Memory(GPR::X12, 16),
+            Location::Imm32(10),
+            Location::Memory(GPR::X10, 32),
+            integer_division_by_zero,
+            integer_overflow,
+        )?;
+        machine.emit_label(integer_division_by_zero)?;
+        machine.emit_label(integer_overflow)?;
+
+        Ok(())
+    }
+
+    #[test]
+    fn tests_riscv() -> Result<(), CompileError> {
+        let mut machine = MachineRiscv::new(None);
+
+        test_move_location(&mut machine, Size::S32)?;
+        test_move_location(&mut machine, Size::S64)?;
+        test_move_location_extended(&mut machine, false, Size::S8)?;
+        test_move_location_extended(&mut machine, false, Size::S16)?;
+        test_move_location_extended(&mut machine, false, Size::S32)?;
+        test_move_location_extended(&mut machine, true, Size::S8)?;
+        test_move_location_extended(&mut machine, true, Size::S16)?;
+        test_move_location_extended(&mut machine, true, Size::S32)?;
+        test_binop_op(&mut machine, MachineRiscv::emit_binop_add32)?;
+        test_binop_op(&mut machine, MachineRiscv::emit_binop_add64)?;
+        test_binop_op(&mut machine, MachineRiscv::emit_binop_sub32)?;
+        test_binop_op(&mut machine, MachineRiscv::emit_binop_sub64)?;
+        test_binop_op(&mut machine, MachineRiscv::emit_binop_and32)?;
+        test_binop_op(&mut machine, MachineRiscv::emit_binop_and64)?;
+        test_binop_op(&mut machine, MachineRiscv::emit_binop_xor32)?;
+        test_binop_op(&mut machine, MachineRiscv::emit_binop_xor64)?;
+        test_binop_op(&mut machine, MachineRiscv::emit_binop_or32)?;
+        test_binop_op(&mut machine, MachineRiscv::emit_binop_or64)?;
+        test_binop_op(&mut machine, MachineRiscv::emit_binop_mul32)?;
+        test_binop_op(&mut machine, MachineRiscv::emit_binop_mul64)?;
+        test_divop(&mut machine, MachineRiscv::emit_binop_sdiv32)?;
+        test_divop(&mut machine, MachineRiscv::emit_binop_sdiv64)?;
+        test_divop(&mut machine, MachineRiscv::emit_binop_srem32)?;
+        test_float_binop_op(&mut machine, MachineRiscv::f32_add)?;
+        test_float_binop_op(&mut machine, MachineRiscv::f32_sub)?;
+        test_float_binop_op(&mut machine, MachineRiscv::f32_mul)?;
+        test_float_binop_op(&mut machine, MachineRiscv::f32_div)?;
+        test_float_cmp_op(&mut machine, MachineRiscv::f32_cmp_eq)?;
+        test_float_cmp_op(&mut machine, MachineRiscv::f32_cmp_lt)?;
+        test_float_cmp_op(&mut machine, MachineRiscv::f32_cmp_le)?;
+        test_binop_op(&mut machine, MachineRiscv::i32_shl)?;
+        test_binop_op(&mut machine, MachineRiscv::i64_sar)?;
+        test_binop_op(&mut machine, MachineRiscv::i32_rol)?;
+        test_binop_op(&mut machine, MachineRiscv::i32_cmp_lt_u)?;
+        test_binop_op(&mut machine, MachineRiscv::i64_cmp_ge_s)?;
+        test_float_binop_op(&mut machine, MachineRiscv::f64_min)?;
+        test_float_binop_op(&mut machine, MachineRiscv::f64_max)?;
+        test_float_cmp_op(&mut machine, MachineRiscv::f64_cmp_ne)?;
+        test_float_cmp_op(&mut machine, MachineRiscv::f64_cmp_gt)?;
+
+        Ok(())
+    }
+}
@@ -13,7 +13,7 @@ use dynasmrt::{x64::X64Relocation, DynasmError, VecAssembler};
 #[cfg(feature = "unwind")]
 use gimli::{write::CallFrameInstruction, X86_64};
 use std::ops::{Deref, DerefMut};
-use wasmer_compiler::wasmparser::ValType as WpType;
+use wasmer_compiler::wasmparser::{Operator, ValType as WpType};
 use wasmer_types::{
     CallingConvention, CompileError, CpuFeature, CustomSection, CustomSectionProtection,
     Relocation, RelocationKind, RelocationTarget, SectionBody, Target,
@@ -489,6 +489,7 @@ impl MachineX86_64 {
         check_alignment: bool,
         value_size: usize,
         need_check: bool,
+        memory64: bool,
         imported_memories: bool,
         offset: i32,
         heap_access_oob: Label,
@@ -528,16 +529,29 @@ impl MachineX86_64 {
         // Load effective address.
         // `base_loc` and `bound_loc` becomes INVALID after this line, because `tmp_addr`
         // might be reused.
+        let addr_size = if memory64 { Size::S64 } else { Size::S32 };
         self.assembler
-            .emit_mov(Size::S32, addr, Location::GPR(tmp_addr))?;
+            .emit_mov(addr_size, addr, Location::GPR(tmp_addr))?;
 
         // Add offset to memory address.
         if memarg.offset != 0 {
-            self.assembler.emit_add(
-                Size::S32,
-                Location::Imm32(memarg.offset as u32),
-                Location::GPR(tmp_addr),
-            )?;
+            if memarg.offset <= i32::MAX as u64 {
+                self.assembler.emit_add(
+                    addr_size,
+                    Location::Imm32(memarg.offset as u32),
+                    Location::GPR(tmp_addr),
+                )?;
+            } else {
+                // Only 64-bit memories have offsets this large, and x86_64
+                // can't add them as an immediate.
+                self.assembler.emit_mov(
+                    Size::S64,
+                    Location::Imm64(memarg.offset),
+                    Location::GPR(tmp2),
+                )?;
+                self.assembler
+                    .emit_add(addr_size, Location::GPR(tmp2), Location::GPR(tmp_addr))?;
+            }
 
             // Trap if offset calculation overflowed.
             self.assembler.emit_jmp(Condition::Carry, heap_access_oob)?;
@@ -560,11 +574,22 @@ impl MachineX86_64 {
                 .emit_mov(Size::S64, bound_loc, Location::GPR(tmp2))?;
 
             // We will compare the upper bound limit without having add the "temp_base" value, as it's a constant
-            self.assembler.emit_lea(
-                Size::S64,
-                Location::Memory(tmp2, -(value_size as i32)),
-                Location::GPR(tmp2),
-            )?;
+            if memory64 {
+                // A 64-bit address can be anywhere, so a memory smaller than
+                // the access must not wrap the bound around.
+                self.assembler.emit_sub(
+                    Size::S64,
+                    Location::Imm32(value_size as u32),
+                    Location::GPR(tmp2),
+                )?;
+                self.assembler.emit_jmp(Condition::Carry, heap_access_oob)?;
+            } else {
+                self.assembler.emit_lea(
+                    Size::S64,
+                    Location::Memory(tmp2, -(value_size as i32)),
+                    Location::GPR(tmp2),
+                )?;
+            }
             // Trap if the end address of the requested area is above that of the linear memory.
             self.assembler
                 .emit_cmp(Size::S64, Location::GPR(tmp2), Location::GPR(tmp_addr))?;
@@ -630,6 +655,7 @@ impl MachineX86_64 {
         memory_sz: Size,
         stack_sz: Size,
         need_check: bool,
+        memory64: bool,
         imported_memories: bool,
         offset: i32,
         heap_access_oob: Label,
@@ -659,6 +685,7 @@ impl MachineX86_64 {
             true,
             value_size,
             need_check,
+            memory64,
             imported_memories,
             offset,
             heap_access_oob,
@@ -1924,6 +1951,115 @@ impl MachineX86_64 {
         let v = trap as u8;
         self.assembler.emit_ud1_payload(v)
     }
+
+    /// Acquires the three temporary XMM registers used to lower a SIMD operator.
+    fn acquire_simd_temps(&mut self) -> Result<[XMM; 3], CompileError> {
+        let mut temps = [XMM::XMM0; 3];
+        for temp in temps.iter_mut() {
+            *temp = self.acquire_temp_simd().ok_or_else(|| {
+                CompileError::Codegen("singlepass cannot acquire temp simd".to_owned())
+            })?;
+        }
+        Ok(temps)
+    }
+    fn release_simd_temps(&mut self, temps: [XMM; 3]) {
+        for temp in temps.iter().rev() {
+            self.release_simd(*temp);
+        }
+    }
+    /// Loads the scalar `src` into the low lane of `dst`.
+    fn simd_load_scalar(&mut self, sz: Size, src: Location, dst: XMM) -> Result<(), CompileError> {
+        match src {
+            Location::GPR(_) | Location::SIMD(_) | Location::Memory(_, _) => {
+                self.move_location(sz, src, Location::SIMD(dst))
+            }
+            _ => self.emit_relaxed_mov(sz, src, Location::SIMD(dst)),
+        }
+    }
+    /// Inserts the scalar `src` into lane `lane` of `dst`.
+    fn simd_insert(
+        &mut self,
+        sz: Size,
+        src: Location,
+        lane: u8,
+        dst: XMM,
+    ) -> Result<(), CompileError> {
+        match src {
+            Location::GPR(src) => self
+                .assembler
+                .emit_pinsr(sz, GPROrMemory::GPR(src), lane, dst),
+            Location::Memory(base, disp) => {
+                self.assembler
+                    .emit_pinsr(sz, GPROrMemory::Memory(base, disp), lane, dst)
+            }
+            _ => {
+                let tmp = self.acquire_temp_gpr().ok_or_else(|| {
+                    CompileError::Codegen("singlepass cannot acquire temp gpr".to_owned())
+                })?;
+                let move_sz = if sz == Size::S64 {
+                    Size::S64
+                } else {
+                    Size::S32
+                };
+                self.move_location(move_sz, src, Location::GPR(tmp))?;
+                self.assembler
+                    .emit_pinsr(sz, GPROrMemory::GPR(tmp), lane, dst)?;
+                self.release_gpr(tmp);
+                Ok(())
+            }
+        }
+    }
+    /// Loads a v128 value, held as its low and high 64-bit halves, into `dst`.
+    fn simd_gather(&mut self, lo: Location, hi: Location, dst: XMM) -> Result<(), CompileError> {
+        self.simd_load_scalar(Size::S64, lo, dst)?;
+        self.simd_insert(Size::S64, hi, 1, dst)
+    }
+    /// Stores `src` into the low and high 64-bit halves of a v128 value.
+    fn simd_scatter(&mut self, src: XMM, lo: Location, hi: Location) -> Result<(), CompileError> {
+        self.assembler
+            .emit_mov(Size::S64, Location::SIMD(src), lo)?;
+        match hi {
+            Location::GPR(hi) => self
+                .assembler
+                .emit_pextr(Size::S64, src, 1, GPROrMemory::GPR(hi)),
+            Location::Memory(base, disp) => {
+                self.assembler
+                    .emit_pextr(Size::S64, src, 1, GPROrMemory::Memory(base, disp))
+            }
+            _ => codegen_error!("singlepass can't store a v128 half to {:?}", hi),
+        }
+    }
+    /// Materializes a 128-bit constant in `dst`.
+    fn simd_const(&mut self, value: u128, dst: XMM) -> Result<(), CompileError> {
+        if value == 0 {
+            return self.assembler.emit_sse(SseOp::Pxor, dst, dst);
+        }
+        if value == u128::MAX {
+            return self.assembler.emit_sse(SseOp::Pcmpeqd, dst, dst);
+        }
+        let (lo, hi) = (value as u64, (value >> 64) as u64);
+        let tmp = self.acquire_temp_gpr().ok_or_else(|| {
+            CompileError::Codegen("singlepass cannot acquire temp gpr".to_owned())
+        })?;
+        self.assembler
+            .emit_mov(Size::S64, Location::Imm64(lo), Location::GPR(tmp))?;
+        self.assembler
+            .emit_mov(Size::S64, Location::GPR(tmp), Location::SIMD(dst))?;
+        if hi != lo {
+            self.assembler
+                .emit_mov(Size::S64, Location::Imm64(hi), Location::GPR(tmp))?;
+        }
+        self.assembler
+            .emit_pinsr(Size::S64, GPROrMemory::GPR(tmp), 1, dst)?;
+        self.release_gpr(tmp);
+        Ok(())
+    }
+}
+
+/// Repeats the low `bits` bits of `value` in every lane of a 128-bit vector.
+fn simd_splat(value: u64, bits: u32) -> u128 {
+    let lane = value as u128 & ((1u128 << bits) - 1);
+    (0..128 / bits).fold(0, |acc, i| acc | lane << (i * bits))
 }
 
 impl Machine for MachineX86_64 {
@@ -2514,6 +2650,41 @@ impl Machine for MachineX86_64 {
         Ok(())
     }
 
+    fn emit_tier_up_entry(
+        &mut self,
+        vmctx: Location,
+        tiers: u32,
+        redirect: u32,
+        calls: u32,
+    ) -> Result<(), CompileError> {
+        // RAX passes no parameter in any calling convention.
+        let vmctx = match vmctx {
+            Location::GPR(vmctx) => vmctx,
+            _ => codegen_error!("singlepass emit_tier_up_entry unreachable"),
+        };
+        let run = self.get_label();
+        self.assembler.emit_mov(
+            Size::S64,
+            Location::Memory(vmctx, tiers as i32),
+            Location::GPR(GPR::RAX),
+        )?;
+        self.assembler.emit_add(
+            Size::S64,
+            Location::Imm32(1),
+            Location::Memory(GPR::RAX, calls as i32),
+        )?;
+        self.assembler.emit_mov(
+            Size::S64,
+            Location::Memory(GPR::RAX, redirect as i32),
+            Location::GPR(GPR::RAX),
+        )?;
+        self.assembler
+            .emit_test(Size::S64, Location::GPR(GPR::RAX), Location::GPR(GPR::RAX))?;
+        self.assembler.emit_jmp(Condition::Equal, run)?;
+        self.assembler.emit_jmp_location(Location::GPR(GPR::RAX))?;
+        self.emit_label(run)
+    }
+
     fn emit_function_prolog(&mut self) -> Result<(), CompileError> {
         self.emit_push(Size::S64, Location::GPR(GPR::RBP))?;
         self.emit_unwind_op(UnwindOps::PushFP { up_to_sp: 16 })?;
@@ -2655,6 +2826,9 @@ impl Machine for MachineX86_64 {
     fn get_simd_for_ret(&self) -> XMM {
         XMM::XMM0
     }
+    fn get_gpr_for_ret_high(&self) -> GPR {
+        GPR::RDX
+    }
 
     fn arch_requires_indirect_call_trampoline(&self) -> bool {
         self.assembler.arch_requires_indirect_call_trampoline()
@@ -3314,6 +3488,7 @@ impl Machine for MachineX86_64 {
         memarg: &MemArg,
         ret: Location,
         need_check: bool,
+        memory64: bool,
         imported_memories: bool,
         offset: i32,
         heap_access_oob: Label,
@@ -3325,6 +3500,7 @@ impl Machine for MachineX86_64 {
             false,
             4,
             need_check,
+            memory64,
             imported_memories,
             offset,
             heap_access_oob,
@@ -3345,6 +3521,7 @@ impl Machine for MachineX86_64 {
         memarg: &MemArg,
         ret: Location,
         need_check: bool,
+        memory64: bool,
         imported_memories: bool,
         offset: i32,
         heap_access_oob: Label,
@@ -3356,6 +3533,7 @@ impl Machine for MachineX86_64 {
             false,
             1,
             need_check,
+            memory64,
             imported_memories,
             offset,
             heap_access_oob,
@@ -3377,6 +3555,7 @@ impl Machine for MachineX86_64 {
         memarg: &MemArg,
         ret: Location,
         need_check: bool,
+        memory64: bool,
         imported_memories: bool,
         offset: i32,
         heap_access_oob: Label,
@@ -3388,6 +3567,7 @@ impl Machine for MachineX86_64 {
             false,
             1,
             need_check,
+            memory64,
             imported_memories,
             offset,
             heap_access_oob,
@@ -3409,6 +3589,7 @@ impl Machine for MachineX86_64 {
         memarg: &MemArg,
         ret: Location,
         need_check: bool,
+        memory64: bool,
         imported_memories: bool,
         offset: i32,
         heap_access_oob: Label,
@@ -3420,6 +3601,7 @@ impl Machine for MachineX86_64 {
             false,
             2,
             need_check,
+            memory64,
             imported_memories,
             offset,
             heap_access_oob,
@@ -3441,6 +3623,7 @@ impl Machine for MachineX86_64 {
         memarg: &MemArg,
         ret: Location,
         need_check: bool,
+        memory64: bool,
         imported_memories: bool,
         offset: i32,
         heap_access_oob: Label,
@@ -3452,6 +3635,7 @@ impl Machine for MachineX86_64 {
             false,
             2,
             need_check,
+            memory64,
             imported_memories,
             offset,
             heap_access_oob,
@@ -3473,6 +3657,7 @@ impl Machine for MachineX86_64 {
         memarg: &MemArg,
         ret: Location,
         need_check: bool,
+        memory64: bool,
         imported_memories: bool,
         offset: i32,
         heap_access_oob: Label,
@@ -3484,6 +3669,7 @@ impl Machine for MachineX86_64 {
             true,
             4,
             need_check,
+            memory64,
             imported_memories,
             offset,
             heap_access_oob,
@@ -3497,6 +3683,7 @@ impl Machine for MachineX86_64 {
         memarg: &MemArg,
         ret: Location,
         need_check: bool,
+        memory64: bool,
         imported_memories: bool,
         offset: i32,
         heap_access_oob: Label,
@@ -3508,6 +3695,7 @@ impl Machine for MachineX86_64 {
             true,
             1,
             need_check,
+            memory64,
             imported_memories,
             offset,
             heap_access_oob,
@@ -3528,6 +3716,7 @@ impl Machine for MachineX86_64 {
         memarg: &MemArg,
         ret: Location,
         need_check: bool,
+        memory64: bool,
         imported_memories: bool,
         offset: i32,
         heap_access_oob: Label,
@@ -3539,6 +3728,7 @@ impl Machine for MachineX86_64 {
             true,
             2,
             need_check,
+            memory64,
             imported_memories,
             offset,
             heap_access_oob,
@@ -3559,6 +3749,7 @@ impl Machine for MachineX86_64 {
         memarg: &MemArg,
         target_addr: Location,
         need_check: bool,
+        memory64: bool,
         imported_memories: bool,
         offset: i32,
         heap_access_oob: Label,
@@ -3570,6 +3761,7 @@ impl Machine for MachineX86_64 {
             false,
             4,
             need_check,
+            memory64,
             imported_memories,
             offset,
             heap_access_oob,
@@ -3590,6 +3782,7 @@ impl Machine for MachineX86_64 {
         memarg: &MemArg,
         target_addr: Location,
         need_check: bool,
+        memory64: bool,
         imported_memories: bool,
         offset: i32,
         heap_access_oob: Label,
@@ -3601,6 +3794,7 @@ impl Machine for MachineX86_64 {
             false,
             1,
             need_check,
+            memory64,
             imported_memories,
             offset,
             heap_access_oob,
@@ -3621,6 +3815,7 @@ impl Machine for MachineX86_64 {
         memarg: &MemArg,
         target_addr: Location,
         need_check: bool,
+        memory64: bool,
         imported_memories: bool,
         offset: i32,
         heap_access_oob: Label,
@@ -3632,6 +3827,7 @@ impl Machine for MachineX86_64 {
             false,
             2,
             need_check,
+            memory64,
             imported_memories,
             offset,
             heap_access_oob,
@@ -3655,6 +3851,7 @@ impl Machine for MachineX86_64 {
         memarg: &MemArg,
         target_addr: Location,
         need_check: bool,
+        memory64: bool,
         imported_memories: bool,
         offset: i32,
         heap_access_oob: Label,
@@ -3666,6 +3863,7 @@ impl Machine for MachineX86_64 {
             true,
             4,
             need_check,
+            memory64,
             imported_memories,
             offset,
             heap_access_oob,
@@ -3686,6 +3884,7 @@ impl Machine for MachineX86_64 {
         memarg: &MemArg,
         target_addr: Location,
         need_check: bool,
+        memory64: bool,
         imported_memories: bool,
         offset: i32,
         heap_access_oob: Label,
@@ -3697,6 +3896,7 @@ impl Machine for MachineX86_64 {
             true,
             1,
             need_check,
+            memory64,
             imported_memories,
             offset,
             heap_access_oob,
@@ -3717,6 +3917,7 @@ impl Machine for MachineX86_64 {
         memarg: &MemArg,
         target_addr: Location,
         need_check: bool,
+        memory64: bool,
         imported_memories: bool,
         offset: i32,
         heap_access_oob: Label,
@@ -3728,6 +3929,7 @@ impl Machine for MachineX86_64 {
             true,
             2,
             need_check,
+            memory64,
             imported_memories,
             offset,
             heap_access_oob,
@@ -3750,6 +3952,7 @@ impl Machine for MachineX86_64 {
         memarg: &MemArg,
         ret: Location,
         need_check: bool,
+        memory64: bool,
         imported_memories: bool,
         offset: i32,
         heap_access_oob: Label,
@@ -3765,6 +3968,7 @@ impl Machine for MachineX86_64 {
             true,
             4,
             need_check,
+            memory64,
             imported_memories,
             offset,
             heap_access_oob,
@@ -3789,6 +3993,7 @@ impl Machine for MachineX86_64 {
         memarg: &MemArg,
         ret: Location,
         need_check: bool,
+        memory64: bool,
         imported_memories: bool,
         offset: i32,
         heap_access_oob: Label,
@@ -3804,6 +4009,7 @@ impl Machine for MachineX86_64 {
             true,
             1,
             need_check,
+            memory64,
             imported_memories,
             offset,
             heap_access_oob,
@@ -3828,6 +4034,7 @@ impl Machine for MachineX86_64 {
         memarg: &MemArg,
         ret: Location,
         need_check: bool,
+        memory64: bool,
         imported_memories: bool,
         offset: i32,
         heap_access_oob: Label,
@@ -3843,6 +4050,7 @@ impl Machine for MachineX86_64 {
             true,
             2,
             need_check,
+            memory64,
             imported_memories,
             offset,
             heap_access_oob,
@@ -3867,6 +4075,7 @@ impl Machine for MachineX86_64 {
         memarg: &MemArg,
         ret: Location,
         need_check: bool,
+        memory64: bool,
         imported_memories: bool,
         offset: i32,
         heap_access_oob: Label,
@@ -3882,6 +4091,7 @@ impl Machine for MachineX86_64 {
             true,
             4,
             need_check,
+            memory64,
             imported_memories,
             offset,
             heap_access_oob,
@@ -3906,6 +4116,7 @@ impl Machine for MachineX86_64 {
         memarg: &MemArg,
         ret: Location,
         need_check: bool,
+        memory64: bool,
         imported_memories: bool,
         offset: i32,
         heap_access_oob: Label,
@@ -3921,6 +4132,7 @@ impl Machine for MachineX86_64 {
             true,
             1,
             need_check,
+            memory64,
             imported_memories,
             offset,
             heap_access_oob,
@@ -3945,6 +4157,7 @@ impl Machine for MachineX86_64 {
         memarg: &MemArg,
         ret: Location,
         need_check: bool,
+        memory64: bool,
         imported_memories: bool,
         offset: i32,
         heap_access_oob: Label,
@@ -3960,6 +4173,7 @@ impl Machine for MachineX86_64 {
             true,
             2,
             need_check,
+            memory64,
             imported_memories,
             offset,
             heap_access_oob,
@@ -3984,6 +4198,7 @@ impl Machine for MachineX86_64 {
         memarg: &MemArg,
         ret: Location,
         need_check: bool,
+        memory64: bool,
         imported_memories: bool,
         offset: i32,
         heap_access_oob: Label,
@@ -3998,6 +4213,7 @@ impl Machine for MachineX86_64 {
             Size::S32,
             Size::S32,
             need_check,
+            memory64,
             imported_memories,
             offset,
             heap_access_oob,
@@ -4016,6 +4232,7 @@ impl Machine for MachineX86_64 {
         memarg: &MemArg,
         ret: Location,
         need_check: bool,
+        memory64: bool,
         imported_memories: bool,
         offset: i32,
         heap_access_oob: Label,
@@ -4030,6 +4247,7 @@ impl Machine for MachineX86_64 {
             Size::S8,
             Size::S32,
             need_check,
+            memory64,
             imported_memories,
             offset,
             heap_access_oob,
@@ -4048,6 +4266,7 @@ impl Machine for MachineX86_64 {
         memarg: &MemArg,
         ret: Location,
         need_check: bool,
+        memory64: bool,
         imported_memories: bool,
         offset: i32,
         heap_access_oob: Label,
@@ -4062,6 +4281,7 @@ impl Machine for MachineX86_64 {
             Size::S16,
             Size::S32,
             need_check,
+            memory64,
             imported_memories,
             offset,
             heap_access_oob,
@@ -4080,6 +4300,7 @@ impl Machine for MachineX86_64 {
         memarg: &MemArg,
         ret: Location,
         need_check: bool,
+        memory64: bool,
         imported_memories: bool,
         offset: i32,
         heap_access_oob: Label,
@@ -4094,6 +4315,7 @@ impl Machine for MachineX86_64 {
             Size::S32,
             Size::S32,
             need_check,
+            memory64,
             imported_memories,
             offset,
             heap_access_oob,
@@ -4112,6 +4334,7 @@ impl Machine for MachineX86_64 {
         memarg: &MemArg,
         ret: Location,
         need_check: bool,
+        memory64: bool,
         imported_memories: bool,
         offset: i32,
         heap_access_oob: Label,
@@ -4126,6 +4349,7 @@ impl Machine for MachineX86_64 {
             Size::S8,
             Size::S32,
             need_check,
+            memory64,
             imported_memories,
             offset,
             heap_access_oob,
@@ -4144,6 +4368,7 @@ impl Machine for MachineX86_64 {
         memarg: &MemArg,
         ret: Location,
         need_check: bool,
+        memory64: bool,
         imported_memories: bool,
         offset: i32,
         heap_access_oob: Label,
@@ -4158,6 +4383,7 @@ impl Machine for MachineX86_64 {
             Size::S16,
             Size::S32,
             need_check,
+            memory64,
             imported_memories,
             offset,
             heap_access_oob,
@@ -4176,6 +4402,7 @@ impl Machine for MachineX86_64 {
         memarg: &MemArg,
         ret: Location,
         need_check: bool,
+        memory64: bool,
         imported_memories: bool,
         offset: i32,
         heap_access_oob: Label,
@@ -4190,6 +4417,7 @@ impl Machine for MachineX86_64 {
             Size::S32,
             Size::S32,
             need_check,
+            memory64,
             imported_memories,
             offset,
             heap_access_oob,
@@ -4208,6 +4436,7 @@ impl Machine for MachineX86_64 {
         memarg: &MemArg,
         ret: Location,
         need_check: bool,
+        memory64: bool,
         imported_memories: bool,
         offset: i32,
         heap_access_oob: Label,
@@ -4222,6 +4451,7 @@ impl Machine for MachineX86_64 {
             Size::S8,
             Size::S32,
             need_check,
+            memory64,
             imported_memories,
             offset,
             heap_access_oob,
@@ -4240,6 +4470,7 @@ impl Machine for MachineX86_64 {
         memarg: &MemArg,
         ret: Location,
         need_check: bool,
+        memory64: bool,
         imported_memories: bool,
         offset: i32,
         heap_access_oob: Label,
@@ -4254,6 +4485,7 @@ impl Machine for MachineX86_64 {
             Size::S16,
             Size::S32,
             need_check,
+            memory64,
             imported_memories,
             offset,
             heap_access_oob,
@@ -4272,6 +4504,7 @@ impl Machine for MachineX86_64 {
         memarg: &MemArg,
         ret: Location,
         need_check: bool,
+        memory64: bool,
         imported_memories: bool,
         offset: i32,
         heap_access_oob: Label,
@@ -4287,6 +4520,7 @@ impl Machine for MachineX86_64 {
             true,
             4,
             need_check,
+            memory64,
             imported_memories,
             offset,
             heap_access_oob,
@@ -4308,6 +4542,7 @@ impl Machine for MachineX86_64 {
         memarg: &MemArg,
         ret: Location,
         need_check: bool,
+        memory64: bool,
         imported_memories: bool,
         offset: i32,
         heap_access_oob: Label,
@@ -4324,6 +4559,7 @@ impl Machine for MachineX86_64 {
             true,
             1,
             need_check,
+            memory64,
             imported_memories,
             offset,
             heap_access_oob,
@@ -4345,6 +4581,7 @@ impl Machine for MachineX86_64 {
         memarg: &MemArg,
         ret: Location,
         need_check: bool,
+        memory64: bool,
         imported_memories: bool,
         offset: i32,
         heap_access_oob: Label,
@@ -4361,6 +4598,7 @@ impl Machine for MachineX86_64 {
             true,
             2,
             need_check,
+            memory64,
             imported_memories,
             offset,
             heap_access_oob,
@@ -4383,6 +4621,7 @@ impl Machine for MachineX86_64 {
         memarg: &MemArg,
         ret: Location,
         need_check: bool,
+        memory64: bool,
         imported_memories: bool,
         offset: i32,
         heap_access_oob: Label,
@@ -4410,6 +4649,7 @@ impl Machine for MachineX86_64 {
             true,
             4,
             need_check,
+            memory64,
             imported_memories,
             offset,
             heap_access_oob,
@@ -4437,6 +4677,7 @@ impl Machine for MachineX86_64 {
         memarg: &MemArg,
         ret: Location,
         need_check: bool,
+        memory64: bool,
         imported_memories: bool,
         offset: i32,
         heap_access_oob: Label,
@@ -4464,6 +4705,7 @@ impl Machine for MachineX86_64 {
             true,
             1,
             need_check,
+            memory64,
             imported_memories,
             offset,
             heap_access_oob,
@@ -4491,6 +4733,7 @@ impl Machine for MachineX86_64 {
         memarg: &MemArg,
         ret: Location,
         need_check: bool,
+        memory64: bool,
         imported_memories: bool,
         offset: i32,
         heap_access_oob: Label,
@@ -4518,6 +4761,7 @@ impl Machine for MachineX86_64 {
             true,
             2,
             need_check,
+            memory64,
             imported_memories,
             offset,
             heap_access_oob,
@@ -4983,6 +5227,7 @@ impl Machine for MachineX86_64 {
         memarg: &MemArg,
         ret: Location,
         need_check: bool,
+        memory64: bool,
         imported_memories: bool,
         offset: i32,
         heap_access_oob: Label,
@@ -4994,6 +5239,7 @@ impl Machine for MachineX86_64 {
             false,
             8,
             need_check,
+            memory64,
             imported_memories,
             offset,
             heap_access_oob,
@@ -5014,6 +5260,7 @@ impl Machine for MachineX86_64 {
         memarg: &MemArg,
         ret: Location,
         need_check: bool,
+        memory64: bool,
         imported_memories: bool,
         offset: i32,
         heap_access_oob: Label,
@@ -5025,6 +5272,7 @@ impl Machine for MachineX86_64 {
             false,
             1,
             need_check,
+            memory64,
             imported_memories,
             offset,
             heap_access_oob,
@@ -5046,6 +5294,7 @@ impl Machine for MachineX86_64 {
         memarg: &MemArg,
         ret: Location,
         need_check: bool,
+        memory64: bool,
         imported_memories: bool,
         offset: i32,
         heap_access_oob: Label,
@@ -5057,6 +5306,7 @@ impl Machine for MachineX86_64 {
             false,
             1,
             need_check,
+            memory64,
             imported_memories,
             offset,
             heap_access_oob,
@@ -5078,6 +5328,7 @@ impl Machine for MachineX86_64 {
         memarg: &MemArg,
         ret: Location,
         need_check: bool,
+        memory64: bool,
         imported_memories: bool,
         offset: i32,
         heap_access_oob: Label,
@@ -5089,6 +5340,7 @@ impl Machine for MachineX86_64 {
             false,
             2,
             need_check,
+            memory64,
             imported_memories,
             offset,
             heap_access_oob,
@@ -5110,6 +5362,7 @@ impl Machine for MachineX86_64 {
         memarg: &MemArg,
         ret: Location,
         need_check: bool,
+        memory64: bool,
         imported_memories: bool,
         offset: i32,
         heap_access_oob: Label,
@@ -5121,6 +5374,7 @@ impl Machine for MachineX86_64 {
             false,
             2,
             need_check,
+            memory64,
             imported_memories,
             offset,
             heap_access_oob,
@@ -5142,6 +5396,7 @@ impl Machine for MachineX86_64 {
         memarg: &MemArg,
         ret: Location,
         need_check: bool,
+        memory64: bool,
         imported_memories: bool,
         offset: i32,
         heap_access_oob: Label,
@@ -5153,6 +5408,7 @@ impl Machine for MachineX86_64 {
             false,
             4,
             need_check,
+            memory64,
             imported_memories,
             offset,
             heap_access_oob,
@@ -5186,6 +5442,7 @@ impl Machine for MachineX86_64 {
         memarg: &MemArg,
         ret: Location,
         need_check: bool,
+        memory64: bool,
         imported_memories: bool,
         offset: i32,
         heap_access_oob: Label,
@@ -5197,6 +5454,7 @@ impl Machine for MachineX86_64 {
             false,
             4,
             need_check,
+            memory64,
             imported_memories,
             offset,
             heap_access_oob,
@@ -5218,6 +5476,7 @@ impl Machine for MachineX86_64 {
         memarg: &MemArg,
         ret: Location,
         need_check: bool,
+        memory64: bool,
         imported_memories: bool,
         offset: i32,
         heap_access_oob: Label,
@@ -5229,6 +5488,7 @@ impl Machine for MachineX86_64 {
             true,
             8,
             need_check,
+            memory64,
             imported_memories,
             offset,
             heap_access_oob,
@@ -5242,6 +5502,7 @@ impl Machine for MachineX86_64 {
         memarg: &MemArg,
         ret: Location,
         need_check: bool,
+        memory64: bool,
         imported_memories: bool,
         offset: i32,
         heap_access_oob: Label,
@@ -5253,6 +5514,7 @@ impl Machine for MachineX86_64 {
             true,
             1,
             need_check,
+            memory64,
             imported_memories,
             offset,
             heap_access_oob,
@@ -5273,6 +5535,7 @@ impl Machine for MachineX86_64 {
         memarg: &MemArg,
         ret: Location,
         need_check: bool,
+        memory64: bool,
         imported_memories: bool,
         offset: i32,
         heap_access_oob: Label,
@@ -5284,6 +5547,7 @@ impl Machine for MachineX86_64 {
             true,
             2,
             need_check,
+            memory64,
             imported_memories,
             offset,
             heap_access_oob,
@@ -5304,6 +5568,7 @@ impl Machine for MachineX86_64 {
         memarg: &MemArg,
         ret: Location,
         need_check: bool,
+        memory64: bool,
         imported_memories: bool,
         offset: i32,
         heap_access_oob: Label,
@@ -5315,6 +5580,7 @@ impl Machine for MachineX86_64 {
             true,
             4,
             need_check,
+            memory64,
             imported_memories,
             offset,
             heap_access_oob,
@@ -5348,6 +5614,7 @@ impl Machine for MachineX86_64 {
         memarg: &MemArg,
         target_addr: Location,
         need_check: bool,
+        memory64: bool,
         imported_memories: bool,
         offset: i32,
         heap_access_oob: Label,
@@ -5359,6 +5626,7 @@ impl Machine for MachineX86_64 {
             false,
             8,
             need_check,
+            memory64,
             imported_memories,
             offset,
             heap_access_oob,
@@ -5379,6 +5647,7 @@ impl Machine for MachineX86_64 {
         memarg: &MemArg,
         target_addr: Location,
         need_check: bool,
+        memory64: bool,
         imported_memories: bool,
         offset: i32,
         heap_access_oob: Label,
@@ -5390,6 +5659,7 @@ impl Machine for MachineX86_64 {
             false,
             1,
             need_check,
+            memory64,
             imported_memories,
             offset,
             heap_access_oob,
@@ -5410,6 +5680,7 @@ impl Machine for MachineX86_64 {
         memarg: &MemArg,
         target_addr: Location,
         need_check: bool,
+        memory64: bool,
         imported_memories: bool,
         offset: i32,
         heap_access_oob: Label,
@@ -5421,6 +5692,7 @@ impl Machine for MachineX86_64 {
             false,
             2,
             need_check,
+            memory64,
             imported_memories,
             offset,
             heap_access_oob,
@@ -5441,6 +5713,7 @@ impl Machine for MachineX86_64 {
         memarg: &MemArg,
         target_addr: Location,
         need_check: bool,
+        memory64: bool,
         imported_memories: bool,
         offset: i32,
         heap_access_oob: Label,
@@ -5452,6 +5725,7 @@ impl Machine for MachineX86_64 {
             false,
             4,
             need_check,
+            memory64,
             imported_memories,
             offset,
             heap_access_oob,
@@ -5472,6 +5746,7 @@ impl Machine for MachineX86_64 {
         memarg: &MemArg,
         target_addr: Location,
         need_check: bool,
+        memory64: bool,
         imported_memories: bool,
         offset: i32,
         heap_access_oob: Label,
@@ -5483,6 +5758,7 @@ impl Machine for MachineX86_64 {
             true,
             8,
             need_check,
+            memory64,
             imported_memories,
             offset,
             heap_access_oob,
@@ -5496,6 +5772,7 @@ impl Machine for MachineX86_64 {
         memarg: &MemArg,
         target_addr: Location,
         need_check: bool,
+        memory64: bool,
         imported_memories: bool,
         offset: i32,
         heap_access_oob: Label,
@@ -5507,6 +5784,7 @@ impl Machine for MachineX86_64 {
             true,
             1,
             need_check,
+            memory64,
             imported_memories,
             offset,
             heap_access_oob,
@@ -5520,6 +5798,7 @@ impl Machine for MachineX86_64 {
         memarg: &MemArg,
         target_addr: Location,
         need_check: bool,
+        memory64: bool,
         imported_memories: bool,
         offset: i32,
         heap_access_oob: Label,
@@ -5531,6 +5810,7 @@ impl Machine for MachineX86_64 {
             true,
             2,
             need_check,
+            memory64,
             imported_memories,
             offset,
             heap_access_oob,
@@ -5544,6 +5824,7 @@ impl Machine for MachineX86_64 {
         memarg: &MemArg,
         target_addr: Location,
         need_check: bool,
+        memory64: bool,
         imported_memories: bool,
         offset: i32,
         heap_access_oob: Label,
@@ -5555,6 +5836,7 @@ impl Machine for MachineX86_64 {
             true,
             2,
             need_check,
+            memory64,
             imported_memories,
             offset,
             heap_access_oob,
@@ -5570,6 +5852,7 @@ impl Machine for MachineX86_64 {
         memarg: &MemArg,
         ret: Location,
         need_check: bool,
+        memory64: bool,
         imported_memories: bool,
         offset: i32,
         heap_access_oob: Label,
@@ -5585,6 +5868,7 @@ impl Machine for MachineX86_64 {
             true,
             8,
             need_check,
+            memory64,
             imported_memories,
             offset,
             heap_access_oob,
@@ -5609,6 +5893,7 @@ impl Machine for MachineX86_64 {
         memarg: &MemArg,
         ret: Location,
         need_check: bool,
+        memory64: bool,
         imported_memories: bool,
         offset: i32,
         heap_access_oob: Label,
@@ -5624,6 +5909,7 @@ impl Machine for MachineX86_64 {
             true,
             1,
             need_check,
+            memory64,
             imported_memories,
             offset,
             heap_access_oob,
@@ -5648,6 +5934,7 @@ impl Machine for MachineX86_64 {
         memarg: &MemArg,
         ret: Location,
         need_check: bool,
+        memory64: bool,
         imported_memories: bool,
         offset: i32,
         heap_access_oob: Label,
@@ -5663,6 +5950,7 @@ impl Machine for MachineX86_64 {
             true,
             2,
             need_check,
+            memory64,
             imported_memories,
             offset,
             heap_access_oob,
@@ -5687,6 +5975,7 @@ impl Machine for MachineX86_64 {
         memarg: &MemArg,
         ret: Location,
         need_check: bool,
+        memory64: bool,
         imported_memories: bool,
         offset: i32,
         heap_access_oob: Label,
@@ -5702,6 +5991,7 @@ impl Machine for MachineX86_64 {
             true,
             4,
             need_check,
+            memory64,
             imported_memories,
             offset,
             heap_access_oob,
@@ -5726,6 +6016,7 @@ impl Machine for MachineX86_64 {
         memarg: &MemArg,
         ret: Location,
         need_check: bool,
+        memory64: bool,
         imported_memories: bool,
         offset: i32,
         heap_access_oob: Label,
@@ -5741,6 +6032,7 @@ impl Machine for MachineX86_64 {
             true,
             8,
             need_check,
+            memory64,
             imported_memories,
             offset,
             heap_access_oob,
@@ -5765,6 +6057,7 @@ impl Machine for MachineX86_64 {
         memarg: &MemArg,
         ret: Location,
         need_check: bool,
+        memory64: bool,
         imported_memories: bool,
         offset: i32,
         heap_access_oob: Label,
@@ -5780,6 +6073,7 @@ impl Machine for MachineX86_64 {
             true,
             1,
             need_check,
+            memory64,
             imported_memories,
             offset,
             heap_access_oob,
@@ -5804,6 +6098,7 @@ impl Machine for MachineX86_64 {
         memarg: &MemArg,
         ret: Location,
         need_check: bool,
+        memory64: bool,
         imported_memories: bool,
         offset: i32,
         heap_access_oob: Label,
@@ -5819,6 +6114,7 @@ impl Machine for MachineX86_64 {
             true,
             2,
             need_check,
+            memory64,
             imported_memories,
             offset,
             heap_access_oob,
@@ -5843,6 +6139,7 @@ impl Machine for MachineX86_64 {
         memarg: &MemArg,
         ret: Location,
         need_check: bool,
+        memory64: bool,
         imported_memories: bool,
         offset: i32,
         heap_access_oob: Label,
@@ -5858,6 +6155,7 @@ impl Machine for MachineX86_64 {
             true,
             4,
             need_check,
+            memory64,
             imported_memories,
             offset,
             heap_access_oob,
@@ -5882,6 +6180,7 @@ impl Machine for MachineX86_64 {
         memarg: &MemArg,
         ret: Location,
         need_check: bool,
+        memory64: bool,
         imported_memories: bool,
         offset: i32,
         heap_access_oob: Label,
@@ -5896,6 +6195,7 @@ impl Machine for MachineX86_64 {
             Size::S64,
             Size::S64,
             need_check,
+            memory64,
             imported_memories,
             offset,
             heap_access_oob,
@@ -5914,6 +6214,7 @@ impl Machine for MachineX86_64 {
         memarg: &MemArg,
         ret: Location,
         need_check: bool,
+        memory64: bool,
         imported_memories: bool,
         offset: i32,
         heap_access_oob: Label,
@@ -5928,6 +6229,7 @@ impl Machine for MachineX86_64 {
             Size::S8,
             Size::S64,
             need_check,
+            memory64,
             imported_memories,
             offset,
             heap_access_oob,
@@ -5946,6 +6248,7 @@ impl Machine for MachineX86_64 {
         memarg: &MemArg,
         ret: Location,
         need_check: bool,
+        memory64: bool,
         imported_memories: bool,
         offset: i32,
         heap_access_oob: Label,
@@ -5960,6 +6263,7 @@ impl Machine for MachineX86_64 {
             Size::S16,
             Size::S64,
             need_check,
+            memory64,
             imported_memories,
             offset,
             heap_access_oob,
@@ -5978,6 +6282,7 @@ impl Machine for MachineX86_64 {
         memarg: &MemArg,
         ret: Location,
         need_check: bool,
+        memory64: bool,
         imported_memories: bool,
         offset: i32,
         heap_access_oob: Label,
@@ -5992,6 +6297,7 @@ impl Machine for MachineX86_64 {
             Size::S32,
             Size::S64,
             need_check,
+            memory64,
             imported_memories,
             offset,
             heap_access_oob,
@@ -6010,6 +6316,7 @@ impl Machine for MachineX86_64 {
         memarg: &MemArg,
         ret: Location,
         need_check: bool,
+        memory64: bool,
         imported_memories: bool,
         offset: i32,
         heap_access_oob: Label,
@@ -6024,6 +6331,7 @@ impl Machine for MachineX86_64 {
             Size::S64,
             Size::S64,
             need_check,
+            memory64,
             imported_memories,
             offset,
             heap_access_oob,
@@ -6041,6 +6349,7 @@ impl Machine for MachineX86_64 {
         memarg: &MemArg,
         ret: Location,
         need_check: bool,
+        memory64: bool,
         imported_memories: bool,
         offset: i32,
         heap_access_oob: Label,
@@ -6055,6 +6364,7 @@ impl Machine for MachineX86_64 {
             Size::S8,
             Size::S64,
             need_check,
+            memory64,
             imported_memories,
             offset,
             heap_access_oob,
@@ -6072,6 +6382,7 @@ impl Machine for MachineX86_64 {
         memarg: &MemArg,
         ret: Location,
         need_check: bool,
+        memory64: bool,
         imported_memories: bool,
         offset: i32,
         heap_access_oob: Label,
@@ -6086,6 +6397,7 @@ impl Machine for MachineX86_64 {
             Size::S16,
             Size::S64,
             need_check,
+            memory64,
             imported_memories,
             offset,
             heap_access_oob,
@@ -6103,6 +6415,7 @@ impl Machine for MachineX86_64 {
         memarg: &MemArg,
         ret: Location,
         need_check: bool,
+        memory64: bool,
         imported_memories: bool,
         offset: i32,
         heap_access_oob: Label,
@@ -6117,6 +6430,7 @@ impl Machine for MachineX86_64 {
             Size::S32,
             Size::S64,
             need_check,
+            memory64,
             imported_memories,
             offset,
             heap_access_oob,
@@ -6134,6 +6448,7 @@ impl Machine for MachineX86_64 {
         memarg: &MemArg,
         ret: Location,
         need_check: bool,
+        memory64: bool,
         imported_memories: bool,
         offset: i32,
         heap_access_oob: Label,
@@ -6148,6 +6463,7 @@ impl Machine for MachineX86_64 {
             Size::S64,
             Size::S64,
             need_check,
+            memory64,
             imported_memories,
             offset,
             heap_access_oob,
@@ -6165,6 +6481,7 @@ impl Machine for MachineX86_64 {
         memarg: &MemArg,
         ret: Location,
         need_check: bool,
+        memory64: bool,
         imported_memories: bool,
         offset: i32,
         heap_access_oob: Label,
@@ -6179,6 +6496,7 @@ impl Machine for MachineX86_64 {
             Size::S8,
             Size::S64,
             need_check,
+            memory64,
             imported_memories,
             offset,
             heap_access_oob,
@@ -6196,6 +6514,7 @@ impl Machine for MachineX86_64 {
         memarg: &MemArg,
         ret: Location,
         need_check: bool,
+        memory64: bool,
         imported_memories: bool,
         offset: i32,
         heap_access_oob: Label,
@@ -6210,6 +6529,7 @@ impl Machine for MachineX86_64 {
             Size::S16,
             Size::S64,
             need_check,
+            memory64,
             imported_memories,
             offset,
             heap_access_oob,
@@ -6227,6 +6547,7 @@ impl Machine for MachineX86_64 {
         memarg: &MemArg,
         ret: Location,
         need_check: bool,
+        memory64: bool,
         imported_memories: bool,
         offset: i32,
         heap_access_oob: Label,
@@ -6241,6 +6562,7 @@ impl Machine for MachineX86_64 {
             Size::S32,
             Size::S64,
             need_check,
+            memory64,
             imported_memories,
             offset,
             heap_access_oob,
@@ -6258,6 +6580,7 @@ impl Machine for MachineX86_64 {
         memarg: &MemArg,
         ret: Location,
         need_check: bool,
+        memory64: bool,
         imported_memories: bool,
         offset: i32,
         heap_access_oob: Label,
@@ -6273,6 +6596,7 @@ impl Machine for MachineX86_64 {
             true,
             8,
             need_check,
+            memory64,
             imported_memories,
             offset,
             heap_access_oob,
@@ -6294,6 +6618,7 @@ impl Machine for MachineX86_64 {
         memarg: &MemArg,
         ret: Location,
         need_check: bool,
+        memory64: bool,
         imported_memories: bool,
         offset: i32,
         heap_access_oob: Label,
@@ -6310,6 +6635,7 @@ impl Machine for MachineX86_64 {
             true,
             1,
             need_check,
+            memory64,
             imported_memories,
             offset,
             heap_access_oob,
@@ -6331,6 +6657,7 @@ impl Machine for MachineX86_64 {
         memarg: &MemArg,
         ret: Location,
         need_check: bool,
+        memory64: bool,
         imported_memories: bool,
         offset: i32,
         heap_access_oob: Label,
@@ -6347,6 +6674,7 @@ impl Machine for MachineX86_64 {
             true,
             2,
             need_check,
+            memory64,
             imported_memories,
             offset,
             heap_access_oob,
@@ -6368,6 +6696,7 @@ impl Machine for MachineX86_64 {
         memarg: &MemArg,
         ret: Location,
         need_check: bool,
+        memory64: bool,
         imported_memories: bool,
         offset: i32,
         heap_access_oob: Label,
@@ -6384,6 +6713,7 @@ impl Machine for MachineX86_64 {
             true,
             4,
             need_check,
+            memory64,
             imported_memories,
             offset,
             heap_access_oob,
@@ -6406,6 +6736,7 @@ impl Machine for MachineX86_64 {
         memarg: &MemArg,
         ret: Location,
         need_check: bool,
+        memory64: bool,
         imported_memories: bool,
         offset: i32,
         heap_access_oob: Label,
@@ -6433,6 +6764,7 @@ impl Machine for MachineX86_64 {
             true,
             8,
             need_check,
+            memory64,
             imported_memories,
             offset,
             heap_access_oob,
@@ -6460,6 +6792,7 @@ impl Machine for MachineX86_64 {
         memarg: &MemArg,
         ret: Location,
         need_check: bool,
+        memory64: bool,
         imported_memories: bool,
         offset: i32,
         heap_access_oob: Label,
@@ -6487,6 +6820,7 @@ impl Machine for MachineX86_64 {
             true,
             1,
             need_check,
+            memory64,
             imported_memories,
             offset,
             heap_access_oob,
@@ -6514,6 +6848,7 @@ impl Machine for MachineX86_64 {
         memarg: &MemArg,
         ret: Location,
         need_check: bool,
+        memory64: bool,
         imported_memories: bool,
         offset: i32,
         heap_access_oob: Label,
@@ -6541,6 +6876,7 @@ impl Machine for MachineX86_64 {
             true,
             2,
             need_check,
+            memory64,
             imported_memories,
             offset,
             heap_access_oob,
@@ -6568,6 +6904,7 @@ impl Machine for MachineX86_64 {
         memarg: &MemArg,
         ret: Location,
         need_check: bool,
+        memory64: bool,
         imported_memories: bool,
         offset: i32,
         heap_access_oob: Label,
@@ -6595,6 +6932,7 @@ impl Machine for MachineX86_64 {
             true,
             4,
             need_check,
+            memory64,
             imported_memories,
             offset,
             heap_access_oob,
@@ -6620,6 +6958,7 @@ impl Machine for MachineX86_64 {
         memarg: &MemArg,
         ret: Location,
         need_check: bool,
+        memory64: bool,
         imported_memories: bool,
         offset: i32,
         heap_access_oob: Label,
@@ -6631,6 +6970,7 @@ impl Machine for MachineX86_64 {
             false,
             4,
             need_check,
+            memory64,
             imported_memories,
             offset,
             heap_access_oob,
@@ -6652,6 +6992,7 @@ impl Machine for MachineX86_64 {
         target_addr: Location,
         canonicalize: bool,
         need_check: bool,
+        memory64: bool,
         imported_memories: bool,
         offset: i32,
         heap_access_oob: Label,
@@ -6664,6 +7005,7 @@ impl Machine for MachineX86_64 {
             false,
             4,
             need_check,
+            memory64,
             imported_memories,
             offset,
             heap_access_oob,
@@ -6688,6 +7030,7 @@ impl Machine for MachineX86_64 {
         memarg: &MemArg,
         ret: Location,
         need_check: bool,
+        memory64: bool,
         imported_memories: bool,
         offset: i32,
         heap_access_oob: Label,
@@ -6699,6 +7042,7 @@ impl Machine for MachineX86_64 {
             false,
             8,
             need_check,
+            memory64,
             imported_memories,
             offset,
             heap_access_oob,
@@ -6720,6 +7064,7 @@ impl Machine for MachineX86_64 {
         target_addr: Location,
         canonicalize: bool,
         need_check: bool,
+        memory64: bool,
         imported_memories: bool,
         offset: i32,
         heap_access_oob: Label,
@@ -6732,6 +7077,7 @@ impl Machine for MachineX86_64 {
             false,
             8,
             need_check,
+            memory64,
             imported_memories,
             offset,
             heap_access_oob,
@@ -7827,6 +8173,1078 @@ impl Machine for MachineX86_64 {
         self.emit_relaxed_avx(AssemblerX64::emit_vdivss, loc_a, loc_b, ret)
     }
 
+    fn emit_simd(
+        &mut self,
+        op: &Operator,
+        args: &[Location],
+        ret: &[Location],
+    ) -> Result<(), CompileError> {
+        let [x, y, z] = self.acquire_simd_temps()?;
+        // Load the first two v128 operands, the others are read by each operator.
+        if !matches!(*op, Operator::I64x2Mul) {
+            if args.len() >= 2 {
+                self.simd_gather(args[0], args[1], x)?;
+            }
+            if args.len() >= 4 {
+                self.simd_gather(args[2], args[3], y)?;
+            }
+        }
+        let result = match *op {
+            Operator::I8x16Splat
+            | Operator::I16x8Splat
+            | Operator::I32x4Splat
+            | Operator::F32x4Splat => {
+                self.simd_load_scalar(Size::S32, args[0], x)?;
+                match *op {
+                    Operator::I8x16Splat => {
+                        self.assembler.emit_sse(SseOp::Pxor, y, y)?;
+                        self.assembler.emit_sse(SseOp::Pshufb, y, x)?;
+                    }
+                    Operator::I16x8Splat => {
+                        self.assembler.emit_sse_imm(SseImmOp::Pshuflw, 0, x, x)?;
+                        self.assembler.emit_sse_imm(SseImmOp::Pshufd, 0, x, x)?;
+                    }
+                    _ => self.assembler.emit_sse_imm(SseImmOp::Pshufd, 0, x, x)?,
+                }
+                Some(x)
+            }
+            Operator::I64x2Splat | Operator::F64x2Splat => {
+                self.simd_load_scalar(Size::S64, args[0], x)?;
+                self.assembler.emit_sse(SseOp::Punpcklqdq, x, x)?;
+                Some(x)
+            }
+
+            Operator::I8x16ExtractLaneS { lane }
+            | Operator::I8x16ExtractLaneU { lane }
+            | Operator::I16x8ExtractLaneS { lane }
+            | Operator::I16x8ExtractLaneU { lane }
+            | Operator::I32x4ExtractLane { lane }
+            | Operator::I64x2ExtractLane { lane } => {
+                let (sz, signed) = match *op {
+                    Operator::I8x16ExtractLaneS { .. } => (Size::S8, true),
+                    Operator::I8x16ExtractLaneU { .. } => (Size::S8, false),
+                    Operator::I16x8ExtractLaneS { .. } => (Size::S16, true),
+                    Operator::I16x8ExtractLaneU { .. } => (Size::S16, false),
+                    Operator::I32x4ExtractLane { .. } => (Size::S32, false),
+                    _ => (Size::S64, false),
+                };
+                let tmp = self.acquire_temp_gpr().ok_or_else(|| {
+                    CompileError::Codegen("singlepass cannot acquire temp gpr".to_owned())
+                })?;
+                self.assembler
+                    .emit_pextr(sz, x, lane, GPROrMemory::GPR(tmp))?;
+                if signed {
+                    self.assembler.emit_movsx(
+                        sz,
+                        Location::GPR(tmp),
+                        Size::S32,
+                        Location::GPR(tmp),
+                    )?;
+                }
+                let ret_sz = if sz == Size::S64 {
+                    Size::S64
+                } else {
+                    Size::S32
+                };
+                self.emit_relaxed_mov(ret_sz, Location::GPR(tmp), ret[0])?;
+                self.release_gpr(tmp);
+                None
+            }
+            Operator::F32x4ExtractLane { lane } => {
+                if lane != 0 {
+                    self.assembler.emit_sse_imm(SseImmOp::Pshufd, lane, x, x)?;
+                }
+                self.move_location(Size::S32, Location::SIMD(x), ret[0])?;
+                None
+            }
+            Operator::F64x2ExtractLane { lane } => {
+                if lane != 0 {
+                    self.assembler.emit_sse_imm(SseImmOp::Pshufd, 0xee, x, x)?;
+                }
+                self.move_location(Size::S64, Location::SIMD(x), ret[0])?;
+                None
+            }
+            Operator::I8x16ReplaceLane { lane }
+            | Operator::I16x8ReplaceLane { lane }
+            | Operator::I32x4ReplaceLane { lane }
+            | Operator::I64x2ReplaceLane { lane }
+            | Operator::F32x4ReplaceLane { lane }
+            | Operator::F64x2ReplaceLane { lane } => {
+                let sz = match *op {
+                    Operator::I8x16ReplaceLane { .. } => Size::S8,
+                    Operator::I16x8ReplaceLane { .. } => Size::S16,
+                    Operator::I32x4ReplaceLane { .. } | Operator::F32x4ReplaceLane { .. } => {
+                        Size::S32
+                    }
+                    _ => Size::S64,
+                };
+                self.simd_insert(sz, args[2], lane, x)?;
+                Some(x)
+            }
+
+            Operator::V128AnyTrue
+            | Operator::I8x16AllTrue
+            | Operator::I16x8AllTrue
+            | Operator::I32x4AllTrue
+            | Operator::I64x2AllTrue => {
+                let (tested, condition) = if let Operator::V128AnyTrue = *op {
+                    (x, Condition::NotEqual)
+                } else {
+                    let cmp = match *op {
+                        Operator::I8x16AllTrue => SseOp::Pcmpeqb,
+                        Operator::I16x8AllTrue => SseOp::Pcmpeqw,
+                        Operator::I32x4AllTrue => SseOp::Pcmpeqd,
+                        _ => SseOp::Pcmpeqq,
+                    };
+                    // Set the lanes that are zero, and check that there is none.
+                    self.assembler.emit_sse(SseOp::Pxor, y, y)?;
+                    self.assembler.emit_sse(cmp, x, y)?;
+                    (y, Condition::Equal)
+                };
+                let tmp = self.acquire_temp_gpr().ok_or_else(|| {
+                    CompileError::Codegen("singlepass cannot acquire temp gpr".to_owned())
+                })?;
+                self.assembler
+                    .emit_mov(Size::S32, Location::Imm32(0), Location::GPR(tmp))?;
+                self.assembler.emit_sse(SseOp::Ptest, tested, tested)?;
+                self.assembler.emit_set(condition, tmp)?;
+                self.emit_relaxed_mov(Size::S32, Location::GPR(tmp), ret[0])?;
+                self.release_gpr(tmp);
+                None
+            }
+            Operator::I8x16Bitmask
+            | Operator::I16x8Bitmask
+            | Operator::I32x4Bitmask
+            | Operator::I64x2Bitmask => {
+                let tmp = self.acquire_temp_gpr().ok_or_else(|| {
+                    CompileError::Codegen("singlepass cannot acquire temp gpr".to_owned())
+                })?;
+                match *op {
+                    Operator::I8x16Bitmask => self.assembler.emit_pmovmskb(x, tmp)?,
+                    Operator::I16x8Bitmask => {
+                        // Saturating keeps the sign of each lane.
+                        self.assembler.emit_sse(SseOp::Packsswb, x, x)?;
+                        self.assembler.emit_pmovmskb(x, tmp)?;
+                        self.assembler.emit_and(
+                            Size::S32,
+                            Location::Imm32(0xff),
+                            Location::GPR(tmp),
+                        )?;
+                    }
+                    Operator::I32x4Bitmask => self.assembler.emit_movmskps(x, tmp)?,
+                    _ => self.assembler.emit_movmskpd(x, tmp)?,
+                }
+                self.emit_relaxed_mov(Size::S32, Location::GPR(tmp), ret[0])?;
+                self.release_gpr(tmp);
+                None
+            }
+
+            Operator::I8x16Shl
+            | Operator::I8x16ShrS
+            | Operator::I8x16ShrU
+            | Operator::I16x8Shl
+            | Operator::I16x8ShrS
+            | Operator::I16x8ShrU
+            | Operator::I32x4Shl
+            | Operator::I32x4ShrS
+            | Operator::I32x4ShrU
+            | Operator::I64x2Shl
+            | Operator::I64x2ShrS
+            | Operator::I64x2ShrU => {
+                let bits = match *op {
+                    Operator::I8x16Shl | Operator::I8x16ShrS | Operator::I8x16ShrU => 8,
+                    Operator::I16x8Shl | Operator::I16x8ShrS | Operator::I16x8ShrU => 16,
+                    Operator::I32x4Shl | Operator::I32x4ShrS | Operator::I32x4ShrU => 32,
+                    _ => 64,
+                };
+                let tmp = self.acquire_temp_gpr().ok_or_else(|| {
+                    CompileError::Codegen("singlepass cannot acquire temp gpr".to_owned())
+                })?;
+                self.emit_relaxed_mov(Size::S32, args[2], Location::GPR(tmp))?;
+                self.assembler.emit_and(
+                    Size::S32,
+                    Location::Imm32(bits - 1),
+                    Location::GPR(tmp),
+                )?;
+                if let Operator::I8x16ShrS = *op {
+                    // The bytes are shifted from the high half of 16-bit lanes.
+                    self.assembler
+                        .emit_add(Size::S32, Location::Imm32(8), Location::GPR(tmp))?;
+                }
+                self.assembler
+                    .emit_mov(Size::S32, Location::GPR(tmp), Location::SIMD(z))?;
+                self.release_gpr(tmp);
+                match *op {
+                    Operator::I8x16Shl => {
+                        // Shift 16-bit lanes, then clear the bits that crossed
+                        // into the high byte with a splat of `0xff << n`.
+                        self.assembler.emit_sse(SseOp::Psllw, z, x)?;
+                        self.assembler.emit_sse(SseOp::Pcmpeqd, y, y)?;
+                        self.assembler.emit_sse(SseOp::Psllw, z, y)?;
+                        self.assembler.emit_sse(SseOp::Pxor, z, z)?;
+                        self.assembler.emit_sse(SseOp::Pshufb, z, y)?;
+                        self.assembler.emit_sse(SseOp::Pand, y, x)?;
+                    }
+                    Operator::I8x16ShrU => {
+                        // Likewise, with a splat of `0xff >> n`.
+                        self.assembler.emit_sse(SseOp::Psrlw, z, x)?;
+                        self.assembler.emit_sse(SseOp::Pcmpeqd, y, y)?;
+                        self.assembler.emit_sse(SseOp::Psrlw, z, y)?;
+                        self.simd_const(simd_splat(1, 8), z)?;
+                        self.assembler.emit_sse(SseOp::Pshufb, z, y)?;
+                        self.assembler.emit_sse(SseOp::Pand, y, x)?;
+                    }
+                    Operator::I8x16ShrS => {
+                        self.assembler.emit_sse(SseOp::Movaps, x, y)?;
+                        self.assembler.emit_sse(SseOp::Punpcklbw, x, x)?;
+                        self.assembler.emit_sse(SseOp::Punpckhbw, y, y)?;
+                        self.assembler.emit_sse(SseOp::Psraw, z, x)?;
+                        self.assembler.emit_sse(SseOp::Psraw, z, y)?;
+                        self.assembler.emit_sse(SseOp::Packsswb, y, x)?;
+                    }
+                    Operator::I64x2ShrS => {
+                        // There's no arithmetic 64-bit shift before AVX-512, so
+                        // sign extend a logical shift with `(x ^ m) - m`, where
+                        // `m` is the sign bit shifted likewise.
+                        self.simd_const(simd_splat(1 << 63, 64), y)?;
+                        self.assembler.emit_sse(SseOp::Psrlq, z, y)?;
+                        self.assembler.emit_sse(SseOp::Psrlq, z, x)?;
+                        self.assembler.emit_sse(SseOp::Pxor, y, x)?;
+                        self.assembler.emit_sse(SseOp::Psubq, y, x)?;
+                    }
+                    _ => {
+                        let shift = match *op {
+                            Operator::I16x8Shl => SseOp::Psllw,
+                            Operator::I16x8ShrS => SseOp::Psraw,
+                            Operator::I16x8ShrU => SseOp::Psrlw,
+                            Operator::I32x4Shl => SseOp::Pslld,
+                            Operator::I32x4ShrS => SseOp::Psrad,
+                            Operator::I32x4ShrU => SseOp::Psrld,
+                            Operator::I64x2Shl => SseOp::Psllq,
+                            _ => SseOp::Psrlq,
+                        };
+                        self.assembler.emit_sse(shift, z, x)?;
+                    }
+                }
+                Some(x)
+            }
+
+            Operator::V128Bitselect => {
+                self.simd_gather(args[4], args[5], z)?;
+                self.assembler.emit_sse(SseOp::Pand, z, x)?;
+                self.assembler.emit_sse(SseOp::Pandn, y, z)?;
+                self.assembler.emit_sse(SseOp::Por, z, x)?;
+                Some(x)
+            }
+            Operator::V128Not => {
+                self.simd_const(u128::MAX, y)?;
+                self.assembler.emit_sse(SseOp::Pxor, y, x)?;
+                Some(x)
+            }
+            Operator::I8x16Abs | Operator::I16x8Abs | Operator::I32x4Abs => {
+                let abs = match *op {
+                    Operator::I8x16Abs => SseOp::Pabsb,
+                    Operator::I16x8Abs => SseOp::Pabsw,
+                    _ => SseOp::Pabsd,
+                };
+                self.assembler.emit_sse(abs, x, x)?;
+                Some(x)
+            }
+            Operator::I64x2Abs => {
+                self.assembler.emit_sse(SseOp::Pxor, y, y)?;
+                self.assembler.emit_sse(SseOp::Pcmpgtq, x, y)?;
+                self.assembler.emit_sse(SseOp::Pxor, y, x)?;
+                self.assembler.emit_sse(SseOp::Psubq, y, x)?;
+                Some(x)
+            }
+            Operator::I8x16Neg | Operator::I16x8Neg | Operator::I32x4Neg | Operator::I64x2Neg => {
+                let sub = match *op {
+                    Operator::I8x16Neg => SseOp::Psubb,
+                    Operator::I16x8Neg => SseOp::Psubw,
+                    Operator::I32x4Neg => SseOp::Psubd,
+                    _ => SseOp::Psubq,
+                };
+                self.assembler.emit_sse(SseOp::Pxor, y, y)?;
+                self.assembler.emit_sse(sub, x, y)?;
+                Some(y)
+            }
+            Operator::I8x16Popcnt => {
+                // Look up the count of each nibble in a table.
+                let table =
+                    (0..16u32).fold(0u128, |acc, i| acc | (i.count_ones() as u128) << (i * 8));
+                self.simd_const(simd_splat(0x0f, 8), z)?;
+                self.assembler.emit_sse(SseOp::Movaps, x, y)?;
+                self.assembler.emit_sse_shift_imm(SseOp::Psrlw, 4, y)?;
+                self.assembler.emit_sse(SseOp::Pand, z, y)?;
+                self.assembler.emit_sse(SseOp::Pand, z, x)?;
+                self.simd_const(table, z)?;
+                self.assembler.emit_sse(SseOp::Pshufb, x, z)?;
+                self.simd_const(table, x)?;
+                self.assembler.emit_sse(SseOp::Pshufb, y, x)?;
+                self.assembler.emit_sse(SseOp::Paddb, z, x)?;
+                Some(x)
+            }
+            Operator::I16x8ExtAddPairwiseI8x16S => {
+                self.simd_const(simd_splat(1, 8), y)?;
+                self.assembler.emit_sse(SseOp::Pmaddubsw, x, y)?;
+                Some(y)
+            }
+            Operator::I16x8ExtAddPairwiseI8x16U => {
+                self.simd_const(simd_splat(1, 8), y)?;
+                self.assembler.emit_sse(SseOp::Pmaddubsw, y, x)?;
+                Some(x)
+            }
+            Operator::I32x4ExtAddPairwiseI16x8S => {
+                self.simd_const(simd_splat(1, 16), y)?;
+                self.assembler.emit_sse(SseOp::Pmaddwd, y, x)?;
+                Some(x)
+            }
+            Operator::I32x4ExtAddPairwiseI16x8U => {
+                // Bias the lanes to make them signed, and fix the sums afterwards.
+                self.simd_const(simd_splat(0x8000, 16), y)?;
+                self.assembler.emit_sse(SseOp::Pxor, y, x)?;
+                self.simd_const(simd_splat(1, 16), y)?;
+                self.assembler.emit_sse(SseOp::Pmaddwd, y, x)?;
+                self.simd_const(simd_splat(0x10000, 32), y)?;
+                self.assembler.emit_sse(SseOp::Paddd, y, x)?;
+                Some(x)
+            }
+            Operator::I16x8ExtendLowI8x16S
+            | Operator::I16x8ExtendHighI8x16S
+            | Operator::I16x8ExtendLowI8x16U
+            | Operator::I16x8ExtendHighI8x16U
+            | Operator::I32x4ExtendLowI16x8S
+            | Operator::I32x4ExtendHighI16x8S
+            | Operator::I32x4ExtendLowI16x8U
+            | Operator::I32x4ExtendHighI16x8U
+            | Operator::I64x2ExtendLowI32x4S
+            | Operator::I64x2ExtendHighI32x4S
+            | Operator::I64x2ExtendLowI32x4U
+            | Operator::I64x2ExtendHighI32x4U => {
+                let (high, extend) = match *op {
+                    Operator::I16x8ExtendLowI8x16S => (false, SseOp::Pmovsxbw),
+                    Operator::I16x8ExtendHighI8x16S => (true, SseOp::Pmovsxbw),
+                    Operator::I16x8ExtendLowI8x16U => (false, SseOp::Pmovzxbw),
+                    Operator::I16x8ExtendHighI8x16U => (true, SseOp::Pmovzxbw),
+                    Operator::I32x4ExtendLowI16x8S => (false, SseOp::Pmovsxwd),
+                    Operator::I32x4ExtendHighI16x8S => (true, SseOp::Pmovsxwd),
+                    Operator::I32x4ExtendLowI16x8U => (false, SseOp::Pmovzxwd),
+                    Operator::I32x4ExtendHighI16x8U => (true, SseOp::Pmovzxwd),
+                    Operator::I64x2ExtendLowI32x4S => (false, SseOp::Pmovsxdq),
+                    Operator::I64x2ExtendHighI32x4S => (true, SseOp::Pmovsxdq),
+                    Operator::I64x2ExtendLowI32x4U => (false, SseOp::Pmovzxdq),
+                    _ => (true, SseOp::Pmovzxdq),
+                };
+                if high {
+                    self.assembler.emit_sse_imm(SseImmOp::Pshufd, 0xee, x, x)?;
+                }
+                self.assembler.emit_sse(extend, x, x)?;
+                Some(x)
+            }
+
+            Operator::F32x4Ceil
+            | Operator::F32x4Floor
+            | Operator::F32x4Trunc
+            | Operator::F32x4Nearest
+            | Operator::F64x2Ceil
+            | Operator::F64x2Floor
+            | Operator::F64x2Trunc
+            | Operator::F64x2Nearest => {
+                let (round, mode) = match *op {
+                    Operator::F32x4Nearest => (SseImmOp::Roundps, 0),
+                    Operator::F32x4Floor => (SseImmOp::Roundps, 1),
+                    Operator::F32x4Ceil => (SseImmOp::Roundps, 2),
+                    Operator::F32x4Trunc => (SseImmOp::Roundps, 3),
+                    Operator::F64x2Nearest => (SseImmOp::Roundpd, 0),
+                    Operator::F64x2Floor => (SseImmOp::Roundpd, 1),
+                    Operator::F64x2Ceil => (SseImmOp::Roundpd, 2),
+                    _ => (SseImmOp::Roundpd, 3),
+                };
+                self.assembler.emit_sse_imm(round, mode, x, x)?;
+                Some(x)
+            }
+            Operator::F32x4Abs | Operator::F32x4Neg | Operator::F64x2Abs | Operator::F64x2Neg => {
+                let (mask, bitop) = match *op {
+                    Operator::F32x4Abs => (simd_splat(0x7fff_ffff, 32), SseOp::Andps),
+                    Operator::F32x4Neg => (simd_splat(0x8000_0000, 32), SseOp::Xorps),
+                    Operator::F64x2Abs => (simd_splat(!(1 << 63), 64), SseOp::Andps),
+                    _ => (simd_splat(1 << 63, 64), SseOp::Xorps),
+                };
+                self.simd_const(mask, y)?;
+                self.assembler.emit_sse(bitop, y, x)?;
+                Some(x)
+            }
+            Operator::F32x4Sqrt => {
+                self.assembler.emit_sse(SseOp::Sqrtps, x, x)?;
+                Some(x)
+            }
+            Operator::F64x2Sqrt => {
+                self.assembler.emit_sse(SseOp::Sqrtpd, x, x)?;
+                Some(x)
+            }
+            Operator::I32x4TruncSatF32x4S => {
+                // Zero the NaN lanes, and turn the lanes overflowing to
+                // `i32::MIN` into `i32::MAX` when they were positive.
+                self.assembler.emit_sse(SseOp::Movaps, x, y)?;
+                self.assembler.emit_sse_imm(SseImmOp::Cmpps, 0, y, y)?;
+                self.assembler.emit_sse(SseOp::Andps, y, x)?;
+                self.simd_const(simd_splat(0x4f00_0000, 32), y)?;
+                self.assembler.emit_sse_imm(SseImmOp::Cmpps, 2, x, y)?;
+                self.assembler.emit_sse(SseOp::Cvttps2dq, x, x)?;
+                self.assembler.emit_sse(SseOp::Pxor, y, x)?;
+                Some(x)
+            }
+            Operator::I32x4TruncSatF32x4U => {
+                // Convert the lanes below 2^31 directly, and add the conversion
+                // of the part above 2^31, saturated, for the others.
+                self.assembler.emit_sse(SseOp::Pxor, z, z)?;
+                self.assembler.emit_sse(SseOp::Maxps, z, x)?;
+                self.simd_const(simd_splat(0x4f00_0000, 32), z)?;
+                self.assembler.emit_sse(SseOp::Movaps, x, y)?;
+                self.assembler.emit_sse(SseOp::Cvttps2dq, x, x)?;
+                self.assembler.emit_sse(SseOp::Subps, z, y)?;
+                self.assembler.emit_sse_imm(SseImmOp::Cmpps, 2, y, z)?;
+                self.assembler.emit_sse(SseOp::Cvttps2dq, y, y)?;
+                self.assembler.emit_sse(SseOp::Pxor, z, y)?;
+                self.assembler.emit_sse(SseOp::Pxor, z, z)?;
+                self.assembler.emit_sse(SseOp::Pmaxsd, z, y)?;
+                self.assembler.emit_sse(SseOp::Paddd, y, x)?;
+                Some(x)
+            }
+            Operator::F32x4ConvertI32x4S => {
+                self.assembler.emit_sse(SseOp::Cvtdq2ps, x, x)?;
+                Some(x)
+            }
+            Operator::F32x4ConvertI32x4U => {
+                // Convert the low 16 bits exactly, and the high ones halved so
+                // they fit in a signed lane, then sum them with a single rounding.
+                self.assembler.emit_sse(SseOp::Movaps, x, y)?;
+                self.assembler.emit_sse_shift_imm(SseOp::Pslld, 16, y)?;
+                self.assembler.emit_sse_shift_imm(SseOp::Psrld, 16, y)?;
+                self.assembler.emit_sse(SseOp::Psubd, y, x)?;
+                self.assembler.emit_sse(SseOp::Cvtdq2ps, y, y)?;
+                self.assembler.emit_sse_shift_imm(SseOp::Psrld, 1, x)?;
+                self.assembler.emit_sse(SseOp::Cvtdq2ps, x, x)?;
+                self.assembler.emit_sse(SseOp::Addps, x, x)?;
+                self.assembler.emit_sse(SseOp::Addps, y, x)?;
+                Some(x)
+            }
+            Operator::I32x4TruncSatF64x2SZero => {
+                self.assembler.emit_sse(SseOp::Movaps, x, y)?;
+                self.assembler.emit_sse_imm(SseImmOp::Cmppd, 0, y, y)?;
+                self.simd_const(simd_splat(2147483647f64.to_bits(), 64), z)?;
+                self.assembler.emit_sse(SseOp::Andpd, z, y)?;
+                self.assembler.emit_sse(SseOp::Minpd, y, x)?;
+                self.assembler.emit_sse(SseOp::Cvttpd2dq, x, x)?;
+                Some(x)
+            }
+            Operator::I32x4TruncSatF64x2UZero => {
+                // Clamp and truncate the lanes, then pick the integers from the
+                // mantissas of `2^52 + x`.
+                self.assembler.emit_sse(SseOp::Pxor, y, y)?;
+                self.assembler.emit_sse(SseOp::Maxpd, y, x)?;
+                self.simd_const(simd_splat(4294967295f64.to_bits(), 64), y)?;
+                self.assembler.emit_sse(SseOp::Minpd, y, x)?;
+                self.assembler.emit_sse_imm(SseImmOp::Roundpd, 3, x, x)?;
+                self.simd_const(simd_splat(4503599627370496f64.to_bits(), 64), y)?;
+                self.assembler.emit_sse(SseOp::Addpd, y, x)?;
+                self.assembler.emit_sse(SseOp::Pxor, y, y)?;
+                self.assembler.emit_sse_imm(SseImmOp::Shufps, 0x88, y, x)?;
+                Some(x)
+            }
+            Operator::F64x2ConvertLowI32x4S => {
+                self.assembler.emit_sse(SseOp::Cvtdq2pd, x, x)?;
+                Some(x)
+            }
+            Operator::F64x2ConvertLowI32x4U => {
+                // Build `2^52 + x` from the bits, and subtract `2^52`.
+                self.simd_const(simd_splat(0x4330_0000, 32), y)?;
+                self.assembler.emit_sse(SseOp::Unpcklps, y, x)?;
+                self.simd_const(simd_splat(4503599627370496f64.to_bits(), 64), y)?;
+                self.assembler.emit_sse(SseOp::Subpd, y, x)?;
+                Some(x)
+            }
+            Operator::F32x4DemoteF64x2Zero => {
+                self.assembler.emit_sse(SseOp::Cvtpd2ps, x, x)?;
+                Some(x)
+            }
+            Operator::F64x2PromoteLowF32x4 => {
+                self.assembler.emit_sse(SseOp::Cvtps2pd, x, x)?;
+                Some(x)
+            }
+
+            Operator::I8x16Shuffle { lanes } => {
+                // Pick the lanes of each operand, zeroing the others.
+                let (mut from_x, mut from_y) = (0u128, 0u128);
+                for (i, lane) in lanes.iter().enumerate() {
+                    let (in_x, in_y) = if *lane < 16 {
+                        (*lane, 0x80)
+                    } else {
+                        (0x80, *lane - 16)
+                    };
+                    from_x |= (in_x as u128) << (i * 8);
+                    from_y |= (in_y as u128) << (i * 8);
+                }
+                self.simd_const(from_x, z)?;
+                self.assembler.emit_sse(SseOp::Pshufb, z, x)?;
+                self.simd_const(from_y, z)?;
+                self.assembler.emit_sse(SseOp::Pshufb, z, y)?;
+                self.assembler.emit_sse(SseOp::Por, y, x)?;
+                Some(x)
+            }
+            Operator::I8x16Swizzle => {
+                // Set the top bit of the out of range indices, so they select zero.
+                self.simd_const(simd_splat(0x70, 8), z)?;
+                self.assembler.emit_sse(SseOp::Paddusb, z, y)?;
+                self.assembler.emit_sse(SseOp::Pshufb, y, x)?;
+                Some(x)
+            }
+
+            Operator::I8x16Ne | Operator::I16x8Ne | Operator::I32x4Ne | Operator::I64x2Ne => {
+                let eq = match *op {
+                    Operator::I8x16Ne => SseOp::Pcmpeqb,
+                    Operator::I16x8Ne => SseOp::Pcmpeqw,
+                    Operator::I32x4Ne => SseOp::Pcmpeqd,
+                    _ => SseOp::Pcmpeqq,
+                };
+                self.assembler.emit_sse(eq, y, x)?;
+                self.simd_const(u128::MAX, z)?;
+                self.assembler.emit_sse(SseOp::Pxor, z, x)?;
+                Some(x)
+            }
+            Operator::I8x16LtS
+            | Operator::I8x16LeS
+            | Operator::I8x16GeS
+            | Operator::I16x8LtS
+            | Operator::I16x8LeS
+            | Operator::I16x8GeS
+            | Operator::I32x4LtS
+            | Operator::I32x4LeS
+            | Operator::I32x4GeS
+            | Operator::I64x2LtS
+            | Operator::I64x2LeS
+            | Operator::I64x2GeS => {
+                let gt = match *op {
+                    Operator::I8x16LtS | Operator::I8x16LeS | Operator::I8x16GeS => SseOp::Pcmpgtb,
+                    Operator::I16x8LtS | Operator::I16x8LeS | Operator::I16x8GeS => SseOp::Pcmpgtw,
+                    Operator::I32x4LtS | Operator::I32x4LeS | Operator::I32x4GeS => SseOp::Pcmpgtd,
+                    _ => SseOp::Pcmpgtq,
+                };
+                // `a < b` is `b > a`, `a <= b` is `!(a > b)` and `a >= b` is
+                // `!(b > a)`.
+                let (swap, negate) = match *op {
+                    Operator::I8x16LtS
+                    | Operator::I16x8LtS
+                    | Operator::I32x4LtS
+                    | Operator::I64x2LtS => (true, false),
+                    Operator::I8x16LeS
+                    | Operator::I16x8LeS
+                    | Operator::I32x4LeS
+                    | Operator::I64x2LeS => (false, true),
+                    _ => (true, true),
+                };
+                let (src, dst) = if swap { (x, y) } else { (y, x) };
+                self.assembler.emit_sse(gt, src, dst)?;
+                if negate {
+                    self.simd_const(u128::MAX, z)?;
+                    self.assembler.emit_sse(SseOp::Pxor, z, dst)?;
+                }
+                Some(dst)
+            }
+            Operator::I8x16LtU
+            | Operator::I8x16GtU
+            | Operator::I8x16LeU
+            | Operator::I8x16GeU
+            | Operator::I16x8LtU
+            | Operator::I16x8GtU
+            | Operator::I16x8LeU
+            | Operator::I16x8GeU
+            | Operator::I32x4LtU
+            | Operator::I32x4GtU
+            | Operator::I32x4LeU
+            | Operator::I32x4GeU => {
+                let (eq, min, max) = match *op {
+                    Operator::I8x16LtU
+                    | Operator::I8x16GtU
+                    | Operator::I8x16LeU
+                    | Operator::I8x16GeU => (SseOp::Pcmpeqb, SseOp::Pminub, SseOp::Pmaxub),
+                    Operator::I16x8LtU
+                    | Operator::I16x8GtU
+                    | Operator::I16x8LeU
+                    | Operator::I16x8GeU => (SseOp::Pcmpeqw, SseOp::Pminuw, SseOp::Pmaxuw),
+                    _ => (SseOp::Pcmpeqd, SseOp::Pminud, SseOp::Pmaxud),
+                };
+                // `a >= b` is `max(a, b) == a` and `a <= b` is `min(a, b) == a`,
+                // the strict comparisons are their negation.
+                let (select, negate) = match *op {
+                    Operator::I8x16GeU | Operator::I16x8GeU | Operator::I32x4GeU => (max, false),
+                    Operator::I8x16LeU | Operator::I16x8LeU | Operator::I32x4LeU => (min, false),
+                    Operator::I8x16LtU | Operator::I16x8LtU | Operator::I32x4LtU => (max, true),
+                    _ => (min, true),
+                };
+                self.assembler.emit_sse(select, x, y)?;
+                self.assembler.emit_sse(eq, y, x)?;
+                if negate {
+                    self.simd_const(u128::MAX, z)?;
+                    self.assembler.emit_sse(SseOp::Pxor, z, x)?;
+                }
+                Some(x)
+            }
+            Operator::F32x4Eq
+            | Operator::F32x4Ne
+            | Operator::F32x4Lt
+            | Operator::F32x4Le
+            | Operator::F32x4Gt
+            | Operator::F32x4Ge
+            | Operator::F64x2Eq
+            | Operator::F64x2Ne
+            | Operator::F64x2Lt
+            | Operator::F64x2Le
+            | Operator::F64x2Gt
+            | Operator::F64x2Ge => {
+                let cmp = match *op {
+                    Operator::F32x4Eq
+                    | Operator::F32x4Ne
+                    | Operator::F32x4Lt
+                    | Operator::F32x4Le
+                    | Operator::F32x4Gt
+                    | Operator::F32x4Ge => SseImmOp::Cmpps,
+                    _ => SseImmOp::Cmppd,
+                };
+                // The predicates are EQ_OQ, LT_OS, LE_OS and NEQ_UQ, with the
+                // operands swapped for the greater than comparisons.
+                let (predicate, swap) = match *op {
+                    Operator::F32x4Eq | Operator::F64x2Eq => (0, false),
+                    Operator::F32x4Ne | Operator::F64x2Ne => (4, false),
+                    Operator::F32x4Lt | Operator::F64x2Lt => (1, false),
+                    Operator::F32x4Le | Operator::F64x2Le => (2, false),
+                    Operator::F32x4Gt | Operator::F64x2Gt => (1, true),
+                    _ => (2, true),
+                };
+                let (src, dst) = if swap { (x, y) } else { (y, x) };
+                self.assembler.emit_sse_imm(cmp, predicate, src, dst)?;
+                Some(dst)
+            }
+            Operator::V128AndNot => {
+                self.assembler.emit_sse(SseOp::Pandn, x, y)?;
+                Some(y)
+            }
+            Operator::I16x8Q15MulrSatS => {
+                // The only overflow is `i16::MIN * i16::MIN`, giving `i16::MIN`.
+                self.assembler.emit_sse(SseOp::Pmulhrsw, y, x)?;
+                self.simd_const(simd_splat(0x8000, 16), z)?;
+                self.assembler.emit_sse(SseOp::Pcmpeqw, x, z)?;
+                self.assembler.emit_sse(SseOp::Pxor, z, x)?;
+                Some(x)
+            }
+            Operator::I16x8ExtMulLowI8x16S
+            | Operator::I16x8ExtMulHighI8x16S
+            | Operator::I16x8ExtMulLowI8x16U
+            | Operator::I16x8ExtMulHighI8x16U
+            | Operator::I32x4ExtMulLowI16x8S
+            | Operator::I32x4ExtMulHighI16x8S
+            | Operator::I32x4ExtMulLowI16x8U
+            | Operator::I32x4ExtMulHighI16x8U => {
+                let (high, extend, mul) = match *op {
+                    Operator::I16x8ExtMulLowI8x16S => (false, SseOp::Pmovsxbw, SseOp::Pmullw),
+                    Operator::I16x8ExtMulHighI8x16S => (true, SseOp::Pmovsxbw, SseOp::Pmullw),
+                    Operator::I16x8ExtMulLowI8x16U => (false, SseOp::Pmovzxbw, SseOp::Pmullw),
+                    Operator::I16x8ExtMulHighI8x16U => (true, SseOp::Pmovzxbw, SseOp::Pmullw),
+                    Operator::I32x4ExtMulLowI16x8S => (false, SseOp::Pmovsxwd, SseOp::Pmulld),
+                    Operator::I32x4ExtMulHighI16x8S => (true, SseOp::Pmovsxwd, SseOp::Pmulld),
+                    Operator::I32x4ExtMulLowI16x8U => (false, SseOp::Pmovzxwd, SseOp::Pmulld),
+                    _ => (true, SseOp::Pmovzxwd, SseOp::Pmulld),
+                };
+                for reg in [x, y] {
+                    if high {
+                        self.assembler
+                            .emit_sse_imm(SseImmOp::Pshufd, 0xee, reg, reg)?;
+                    }
+                    self.assembler.emit_sse(extend, reg, reg)?;
+                }
+                self.assembler.emit_sse(mul, y, x)?;
+                Some(x)
+            }
+            Operator::I64x2ExtMulLowI32x4S
+            | Operator::I64x2ExtMulHighI32x4S
+            | Operator::I64x2ExtMulLowI32x4U
+            | Operator::I64x2ExtMulHighI32x4U => {
+                // Move the lanes to the even positions read by the multiplication.
+                let (order, mul) = match *op {
+                    Operator::I64x2ExtMulLowI32x4S => (0x50, SseOp::Pmuldq),
+                    Operator::I64x2ExtMulHighI32x4S => (0xfa, SseOp::Pmuldq),
+                    Operator::I64x2ExtMulLowI32x4U => (0x50, SseOp::Pmuludq),
+                    _ => (0xfa, SseOp::Pmuludq),
+                };
+                self.assembler.emit_sse_imm(SseImmOp::Pshufd, order, x, x)?;
+                self.assembler.emit_sse_imm(SseImmOp::Pshufd, order, y, y)?;
+                self.assembler.emit_sse(mul, y, x)?;
+                Some(x)
+            }
+            Operator::I64x2Mul => {
+                // There's no 64-bit lane multiplication before AVX-512, so
+                // multiply each half in general purpose registers.
+                let mut temps = [GPR::RAX; 3];
+                for temp in temps.iter_mut() {
+                    *temp = self.acquire_temp_gpr().ok_or_else(|| {
+                        CompileError::Codegen("singlepass cannot acquire temp gpr".to_owned())
+                    })?;
+                }
+                let [lo, hi, tmp] = temps;
+                for (half, dst) in [(0, lo), (1, hi)] {
+                    self.emit_relaxed_mov(Size::S64, args[half], Location::GPR(dst))?;
+                    self.emit_relaxed_mov(Size::S64, args[half + 2], Location::GPR(tmp))?;
+                    self.assembler
+                        .emit_imul(Size::S64, Location::GPR(tmp), Location::GPR(dst))?;
+                }
+                self.emit_relaxed_mov(Size::S64, Location::GPR(lo), ret[0])?;
+                self.emit_relaxed_mov(Size::S64, Location::GPR(hi), ret[1])?;
+                for temp in temps.iter().rev() {
+                    self.release_gpr(*temp);
+                }
+                None
+            }
+            Operator::F32x4Min | Operator::F64x2Min => {
+                // Compute the minimum both ways to merge zeroes of opposite signs
+                // and NaNs, then canonicalize the NaNs.
+                let (min, or, cmp, andn, shift, bits) = match *op {
+                    Operator::F32x4Min => (
+                        SseOp::Minps,
+                        SseOp::Orps,
+                        SseImmOp::Cmpps,
+                        SseOp::Andnps,
+                        SseOp::Psrld,
+                        10,
+                    ),
+                    _ => (
+                        SseOp::Minpd,
+                        SseOp::Orpd,
+                        SseImmOp::Cmppd,
+                        SseOp::Andnpd,
+                        SseOp::Psrlq,
+                        13,
+                    ),
+                };
+                self.assembler.emit_sse(SseOp::Movaps, x, z)?;
+                self.assembler.emit_sse(min, y, z)?;
+                self.assembler.emit_sse(min, x, y)?;
+                self.assembler.emit_sse(or, y, z)?;
+                self.assembler.emit_sse_imm(cmp, 3, z, y)?;
+                self.assembler.emit_sse(or, y, z)?;
+                self.assembler.emit_sse_shift_imm(shift, bits, y)?;
+                self.assembler.emit_sse(andn, z, y)?;
+                Some(y)
+            }
+            Operator::F32x4Max | Operator::F64x2Max => {
+                let (max, xor, or, sub, cmp, andn, shift, bits) = match *op {
+                    Operator::F32x4Max => (
+                        SseOp::Maxps,
+                        SseOp::Xorps,
+                        SseOp::Orps,
+                        SseOp::Subps,
+                        SseImmOp::Cmpps,
+                        SseOp::Andnps,
+                        SseOp::Psrld,
+                        10,
+                    ),
+                    _ => (
+                        SseOp::Maxpd,
+                        SseOp::Xorpd,
+                        SseOp::Orpd,
+                        SseOp::Subpd,
+                        SseImmOp::Cmppd,
+                        SseOp::Andnpd,
+                        SseOp::Psrlq,
+                        13,
+                    ),
+                };
+                self.assembler.emit_sse(SseOp::Movaps, x, z)?;
+                self.assembler.emit_sse(max, y, z)?;
+                self.assembler.emit_sse(max, x, y)?;
+                self.assembler.emit_sse(xor, z, y)?;
+                self.assembler.emit_sse(or, y, z)?;
+                self.assembler.emit_sse(SseOp::Movaps, z, x)?;
+                self.assembler.emit_sse(sub, y, x)?;
+                self.assembler.emit_sse_imm(cmp, 3, z, z)?;
+                self.assembler.emit_sse_shift_imm(shift, bits, z)?;
+                self.assembler.emit_sse(andn, x, z)?;
+                Some(z)
+            }
+            Operator::F32x4PMin
+            | Operator::F32x4PMax
+            | Operator::F64x2PMin
+            | Operator::F64x2PMax => {
+                // `pmin(a, b)` is `b < a ? b : a`, which is what `minps b, a` does.
+                let select = match *op {
+                    Operator::F32x4PMin => SseOp::Minps,
+                    Operator::F32x4PMax => SseOp::Maxps,
+                    Operator::F64x2PMin => SseOp::Minpd,
+                    _ => SseOp::Maxpd,
+                };
+                self.assembler.emit_sse(select, x, y)?;
+                Some(y)
+            }
+
+            _ => {
+                let binop = match *op {
+                    Operator::V128And => SseOp::Pand,
+                    Operator::V128Or => SseOp::Por,
+                    Operator::V128Xor => SseOp::Pxor,
+                    Operator::I8x16Eq => SseOp::Pcmpeqb,
+                    Operator::I16x8Eq => SseOp::Pcmpeqw,
+                    Operator::I32x4Eq => SseOp::Pcmpeqd,
+                    Operator::I64x2Eq => SseOp::Pcmpeqq,
+                    Operator::I8x16GtS => SseOp::Pcmpgtb,
+                    Operator::I16x8GtS => SseOp::Pcmpgtw,
+                    Operator::I32x4GtS => SseOp::Pcmpgtd,
+                    Operator::I64x2GtS => SseOp::Pcmpgtq,
+                    Operator::I8x16NarrowI16x8S => SseOp::Packsswb,
+                    Operator::I8x16NarrowI16x8U => SseOp::Packuswb,
+                    Operator::I16x8NarrowI32x4S => SseOp::Packssdw,
+                    Operator::I16x8NarrowI32x4U => SseOp::Packusdw,
+                    Operator::I8x16Add => SseOp::Paddb,
+                    Operator::I8x16AddSatS => SseOp::Paddsb,
+                    Operator::I8x16AddSatU => SseOp::Paddusb,
+                    Operator::I8x16Sub => SseOp::Psubb,
+                    Operator::I8x16SubSatS => SseOp::Psubsb,
+                    Operator::I8x16SubSatU => SseOp::Psubusb,
+                    Operator::I8x16MinS => SseOp::Pminsb,
+                    Operator::I8x16MinU => SseOp::Pminub,
+                    Operator::I8x16MaxS => SseOp::Pmaxsb,
+                    Operator::I8x16MaxU => SseOp::Pmaxub,
+                    Operator::I8x16AvgrU => SseOp::Pavgb,
+                    Operator::I16x8Add => SseOp::Paddw,
+                    Operator::I16x8AddSatS => SseOp::Paddsw,
+                    Operator::I16x8AddSatU => SseOp::Paddusw,
+                    Operator::I16x8Sub => SseOp::Psubw,
+                    Operator::I16x8SubSatS => SseOp::Psubsw,
+                    Operator::I16x8SubSatU => SseOp::Psubusw,
+                    Operator::I16x8Mul => SseOp::Pmullw,
+                    Operator::I16x8MinS => SseOp::Pminsw,
+                    Operator::I16x8MinU => SseOp::Pminuw,
+                    Operator::I16x8MaxS => SseOp::Pmaxsw,
+                    Operator::I16x8MaxU => SseOp::Pmaxuw,
+                    Operator::I16x8AvgrU => SseOp::Pavgw,
+                    Operator::I32x4Add => SseOp::Paddd,
+                    Operator::I32x4Sub => SseOp::Psubd,
+                    Operator::I32x4Mul => SseOp::Pmulld,
+                    Operator::I32x4MinS => SseOp::Pminsd,
+                    Operator::I32x4MinU => SseOp::Pminud,
+                    Operator::I32x4MaxS => SseOp::Pmaxsd,
+                    Operator::I32x4MaxU => SseOp::Pmaxud,
+                    Operator::I32x4DotI16x8S => SseOp::Pmaddwd,
+                    Operator::I64x2Add => SseOp::Paddq,
+                    Operator::I64x2Sub => SseOp::Psubq,
+                    Operator::F32x4Add => SseOp::Addps,
+                    Operator::F32x4Sub => SseOp::Subps,
+                    Operator::F32x4Mul => SseOp::Mulps,
+                    Operator::F32x4Div => SseOp::Divps,
+                    Operator::F64x2Add => SseOp::Addpd,
+                    Operator::F64x2Sub => SseOp::Subpd,
+                    Operator::F64x2Mul => SseOp::Mulpd,
+                    Operator::F64x2Div => SseOp::Divpd,
+                    _ => codegen_error!("singlepass can't emit SIMD operator {:?}", op),
+                };
+                self.assembler.emit_sse(binop, y, x)?;
+                Some(x)
+            }
+        };
+        if let Some(result) = result {
+            self.simd_scatter(result, ret[0], ret[1])?;
+        }
+        self.release_simd_temps([x, y, z]);
+        Ok(())
+    }
+
+    fn emit_simd_memory(
+        &mut self,
+        op: &Operator,
+        addr: Location,
+        args: &[Location],
+        ret: &[Location],
+        need_check: bool,
+        memory64: bool,
+        imported_memories: bool,
+        offset: i32,
+        heap_access_oob: Label,
+        unaligned_atomic: Label,
+    ) -> Result<(), CompileError> {
+        let [x, y, z] = self.acquire_simd_temps()?;
+        match *op {
+            Operator::V128Store { ref memarg } => {
+                self.simd_gather(args[0], args[1], x)?;
+                self.memory_op(
+                    addr,
+                    memarg,
+                    false,
+                    16,
+                    need_check,
+                    memory64,
+                    imported_memories,
+                    offset,
+                    heap_access_oob,
+                    unaligned_atomic,
+                    |this, addr| {
+                        this.assembler
+                            .emit_movdqu(XMMOrMemory::XMM(x), XMMOrMemory::Memory(addr, 0))
+                    },
+                )?;
+            }
+            Operator::V128Store8Lane { ref memarg, lane }
+            | Operator::V128Store16Lane { ref memarg, lane }
+            | Operator::V128Store32Lane { ref memarg, lane }
+            | Operator::V128Store64Lane { ref memarg, lane } => {
+                let (sz, value_size) = match *op {
+                    Operator::V128Store8Lane { .. } => (Size::S8, 1),
+                    Operator::V128Store16Lane { .. } => (Size::S16, 2),
+                    Operator::V128Store32Lane { .. } => (Size::S32, 4),
+                    _ => (Size::S64, 8),
+                };
+                self.simd_gather(args[0], args[1], x)?;
+                self.memory_op(
+                    addr,
+                    memarg,
+                    false,
+                    value_size,
+                    need_check,
+                    memory64,
+                    imported_memories,
+                    offset,
+                    heap_access_oob,
+                    unaligned_atomic,
+                    |this, addr| {
+                        this.assembler
+                            .emit_pextr(sz, x, lane, GPROrMemory::Memory(addr, 0))
+                    },
+                )?;
+            }
+            Operator::V128Load8Lane { ref memarg, lane }
+            | Operator::V128Load16Lane { ref memarg, lane }
+            | Operator::V128Load32Lane { ref memarg, lane }
+            | Operator::V128Load64Lane { ref memarg, lane } => {
+                let (sz, value_size) = match *op {
+                    Operator::V128Load8Lane { .. } => (Size::S8, 1),
+                    Operator::V128Load16Lane { .. } => (Size::S16, 2),
+                    Operator::V128Load32Lane { .. } => (Size::S32, 4),
+                    _ => (Size::S64, 8),
+                };
+                self.simd_gather(args[0], args[1], x)?;
+                self.memory_op(
+                    addr,
+                    memarg,
+                    false,
+                    value_size,
+                    need_check,
+                    memory64,
+                    imported_memories,
+                    offset,
+                    heap_access_oob,
+                    unaligned_atomic,
+                    |this, addr| {
+                        this.assembler
+                            .emit_pinsr(sz, GPROrMemory::Memory(addr, 0), lane, x)
+                    },
+                )?;
+                self.simd_scatter(x, ret[0], ret[1])?;
+            }
+            Operator::V128Load { ref memarg }
+            | Operator::V128Load8x8S { ref memarg }
+            | Operator::V128Load8x8U { ref memarg }
+            | Operator::V128Load16x4S { ref memarg }
+            | Operator::V128Load16x4U { ref memarg }
+            | Operator::V128Load32x2S { ref memarg }
+            | Operator::V128Load32x2U { ref memarg }
+            | Operator::V128Load8Splat { ref memarg }
+            | Operator::V128Load16Splat { ref memarg }
+            | Operator::V128Load32Splat { ref memarg }
+            | Operator::V128Load64Splat { ref memarg }
+            | Operator::V128Load32Zero { ref memarg }
+            | Operator::V128Load64Zero { ref memarg } => {
+                let value_size = match *op {
+                    Operator::V128Load { .. } => 16,
+                    Operator::V128Load8Splat { .. } => 1,
+                    Operator::V128Load16Splat { .. } => 2,
+                    Operator::V128Load32Splat { .. } | Operator::V128Load32Zero { .. } => 4,
+                    _ => 8,
+                };
+                self.memory_op(
+                    addr,
+                    memarg,
+                    false,
+                    value_size,
+                    need_check,
+                    memory64,
+                    imported_memories,
+                    offset,
+                    heap_access_oob,
+                    unaligned_atomic,
+                    |this, addr| match value_size {
+                        16 => this
+                            .assembler
+                            .emit_movdqu(XMMOrMemory::Memory(addr, 0), XMMOrMemory::XMM(x)),
+                        1 => {
+                            this.assembler
+                                .emit_pinsr(Size::S8, GPROrMemory::Memory(addr, 0), 0, x)
+                        }
+                        2 => {
+                            this.assembler
+                                .emit_pinsr(Size::S16, GPROrMemory::Memory(addr, 0), 0, x)
+                        }
+                        4 => this.assembler.emit_mov(
+                            Size::S32,
+                            Location::Memory(addr, 0),
+                            Location::SIMD(x),
+                        ),
+                        _ => this.assembler.emit_mov(
+                            Size::S64,
+                            Location::Memory(addr, 0),
+                            Location::SIMD(x),
+                        ),
+                    },
+                )?;
+                match *op {
+                    Operator::V128Load8x8S { .. } => {
+                        self.assembler.emit_sse(SseOp::Pmovsxbw, x, x)?
+                    }
+                    Operator::V128Load8x8U { .. } => {
+                        self.assembler.emit_sse(SseOp::Pmovzxbw, x, x)?
+                    }
+                    Operator::V128Load16x4S { .. } => {
+                        self.assembler.emit_sse(SseOp::Pmovsxwd, x, x)?
+                    }
+                    Operator::V128Load16x4U { .. } => {
+                        self.assembler.emit_sse(SseOp::Pmovzxwd, x, x)?
+                    }
+                    Operator::V128Load32x2S { .. } => {
+                        self.assembler.emit_sse(SseOp::Pmovsxdq, x, x)?
+                    }
+                    Operator::V128Load32x2U { .. } => {
+                        self.assembler.emit_sse(SseOp::Pmovzxdq, x, x)?
+                    }
+                    Operator::V128Load8Splat { .. } => {
+                        self.assembler.emit_sse(SseOp::Pxor, y, y)?;
+                        self.assembler.emit_sse(SseOp::Pshufb, y, x)?;
+                    }
+                    Operator::V128Load16Splat { .. } => {
+                        self.assembler.emit_sse_imm(SseImmOp::Pshuflw, 0, x, x)?;
+                        self.assembler.emit_sse_imm(SseImmOp::Pshufd, 0, x, x)?;
+                    }
+                    Operator::V128Load32Splat { .. } => {
+                        self.assembler.emit_sse_imm(SseImmOp::Pshufd, 0, x, x)?
+                    }
+                    Operator::V128Load64Splat { .. } => {
+                        self.assembler.emit_sse(SseOp::Punpcklqdq, x, x)?
+                    }
+                    _ => {}
+                }
+                self.simd_scatter(x, ret[0], ret[1])?;
+            }
+            _ => codegen_error!("singlepass can't emit SIMD memory operator {:?}", op),
+        }
+        self.release_simd_temps([x, y, z]);
+        Ok(())
+    }
+
     fn gen_std_trampoline(
         &self,
         sig: &FunctionType,
@@ -7835,9 +9253,21 @@ impl Machine for MachineX86_64 {
         // the cpu feature here is irrelevant
         let mut a = AssemblerX64::new(0, None)?;
 
+        // The offset in `args_rets` of each argument, a v128 being passed as
+        // two 64-bit halves.
+        let arg_offsets: Vec<usize> = sig
+            .params()
+            .iter()
+            .enumerate()
+            .flat_map(|(i, param)| match param {
+                Type::V128 => vec![i * 16, i * 16 + 8],
+                _ => vec![i * 16],
+            })
+            .collect();
+
         // Calculate stack offset.
         let mut stack_offset: u32 = 0;
-        for (i, _param) in sig.params().iter().enumerate() {
+        for i in 0..arg_offsets.len() {
             if let Location::Memory(_, _) =
                 self.get_simple_param_location(1 + i, calling_convention)
             {
@@ -7881,8 +9311,8 @@ impl Machine for MachineX86_64 {
         // `callee_vmctx` is already in the first argument register, so no need to move.
         {
             let mut n_stack_args: usize = 0;
-            for (i, _param) in sig.params().iter().enumerate() {
-                let src_loc = Location::Memory(GPR::R14, (i * 16) as _); // args_rets[i]
+            for (i, arg_offset) in arg_offsets.iter().enumerate() {
+                let src_loc = Location::Memory(GPR::R14, *arg_offset as _); // args_rets[i]
                 let dst_loc = self.get_simple_param_location(1 + i, calling_convention);
 
                 match dst_loc {
@@ -7925,6 +9355,13 @@ impl Machine for MachineX86_64 {
                 Location::GPR(GPR::RAX),
                 Location::Memory(GPR::R14, 0),
             )?;
+            if sig.results()[0] == Type::V128 {
+                a.emit_mov(
+                    Size::S64,
+                    Location::GPR(GPR::RDX),
+                    Location::Memory(GPR::R14, 8),
+                )?;
+            }
         }
 
         // Restore callee-saved registers.
@@ -7970,34 +9407,44 @@ impl Machine for MachineX86_64 {
             let mut stack_param_count: usize = 0;
 
             for (i, ty) in sig.params().iter().enumerate() {
-                let source_loc = match argalloc.next(*ty, calling_convention) {
-                    Some(X64Register::GPR(gpr)) => Location::GPR(gpr),
-                    Some(X64Register::XMM(xmm)) => Location::SIMD(xmm),
-                    None => {
-                        a.emit_mov(
-                            Size::S64,
-                            Location::Memory(
-                                GPR::RSP,
-                                (stack_padding * 2 + stack_offset + 8 + stack_param_count * 8) as _,
-                            ),
-                            Location::GPR(GPR::RAX),
-                        )?;
-                        stack_param_count += 1;
-                        Location::GPR(GPR::RAX)
-                    }
+                // A v128 comes as two 64-bit halves.
+                let (arg_ty, halves) = match ty {
+                    Type::V128 => (Type::I64, 2),
+                    _ => (*ty, 1),
                 };
-                a.emit_mov(
-                    Size::S64,
-                    source_loc,
-                    Location::Memory(GPR::RSP, (stack_padding + i * 16) as _),
-                )?;
+                for half in 0..halves {
+                    let source_loc = match argalloc.next(arg_ty, calling_convention) {
+                        Some(X64Register::GPR(gpr)) => Location::GPR(gpr),
+                        Some(X64Register::XMM(xmm)) => Location::SIMD(xmm),
+                        None => {
+                            a.emit_mov(
+                                Size::S64,
+                                Location::Memory(
+                                    GPR::RSP,
+                                    (stack_padding * 2 + stack_offset + 8 + stack_param_count * 8)
+                                        as _,
+                                ),
+                                Location::GPR(GPR::RAX),
+                            )?;
+                            stack_param_count += 1;
+                            Location::GPR(GPR::RAX)
+                        }
+                    };
+                    a.emit_mov(
+                        Size::S64,
+                        source_loc,
+                        Location::Memory(GPR::RSP, (stack_padding + i * 16 + half * 8) as _),
+                    )?;
+                }
 
-                // Zero upper 64 bits.
-                a.emit_mov(
-                    Size::S64,
-                    Location::Imm32(0),
-                    Location::Memory(GPR::RSP, (stack_padding + i * 16 + 8) as _),
-                )?;
+                if halves == 1 {
+                    // Zero upper 64 bits.
+                    a.emit_mov(
+                        Size::S64,
+                        Location::Imm32(0),
+                        Location::Memory(GPR::RSP, (stack_padding + i * 16 + 8) as _),
+                    )?;
+                }
             }
         }
 
@@ -8045,6 +9492,13 @@ impl Machine for MachineX86_64 {
                 Location::Memory(GPR::RSP, stack_padding as i32),
                 Location::GPR(GPR::RAX),
             )?;
+            if sig.results()[0] == Type::V128 {
+                a.emit_mov(
+                    Size::S64,
+                    Location::Memory(GPR::RSP, stack_padding as i32 + 8),
+                    Location::GPR(GPR::RDX),
+                )?;
+            }
         }
 
         // Release values array.
@@ -8084,17 +9538,22 @@ impl Machine for MachineX86_64 {
         // For the standard System V calling convention requires
         //  floating point arguments to be passed in XMM registers.
         //  Translation is expensive, so only do it if needed.
-        if sig
+        // A v128 is passed as two 64-bit halves.
+        let params: Vec<Type> = sig
             .params()
             .iter()
-            .any(|&x| x == Type::F32 || x == Type::F64)
-        {
+            .flat_map(|&ty| match ty {
+                Type::V128 => vec![Type::I64, Type::I64],
+                _ => vec![ty],
+            })
+            .collect();
+        if params.iter().any(|&x| x == Type::F32 || x == Type::F64) {
             match calling_convention {
                 CallingConvention::WindowsFastcall => {
                     let mut param_locations: Vec<Location> = vec![];
                     static PARAM_REGS: &[GPR] = &[GPR::RDX, GPR::R8, GPR::R9];
                     #[allow(clippy::needless_range_loop)]
-                    for i in 0..sig.params().len() {
+                    for i in 0..params.len() {
                         let loc = match i {
                             0..=2 => Location::GPR(PARAM_REGS[i]),
                             _ => Location::Memory(GPR::RSP, 32 + 8 + ((i - 3) * 8) as i32), // will not be used anyway
@@ -8104,7 +9563,7 @@ impl Machine for MachineX86_64 {
 
                     // Copy Float arguments to XMM from GPR.
                     let mut argalloc = ArgumentRegisterAllocator::default();
-                    for (i, ty) in sig.params().iter().enumerate() {
+                    for (i, ty) in params.iter().enumerate() {
                         let prev_loc = param_locations[i];
                         match argalloc.next(*ty, calling_convention) {
                             Some(X64Register::GPR(_gpr)) => continue,
@@ -8119,10 +9578,10 @@ impl Machine for MachineX86_64 {
                     let mut param_locations = vec![];
 
                     // Allocate stack space for arguments.
-                    let stack_offset: i32 = if sig.params().len() > 5 {
+                    let stack_offset: i32 = if params.len() > 5 {
                         5 * 8
                     } else {
-                        (sig.params().len() as i32) * 8
+                        (params.len() as i32) * 8
                     };
                     if stack_offset > 0 {
                         a.emit_sub(
@@ -8135,7 +9594,7 @@ impl Machine for MachineX86_64 {
                     // Store all arguments to the stack to prevent overwrite.
                     static PARAM_REGS: &[GPR] = &[GPR::RSI, GPR::RDX, GPR::RCX, GPR::R8, GPR::R9];
                     #[allow(clippy::needless_range_loop)]
-                    for i in 0..sig.params().len() {
+                    for i in 0..params.len() {
                         let loc = match i {
                             0..=4 => {
                                 let loc = Location::Memory(GPR::RSP, (i * 8) as i32);
@@ -8153,7 +9612,7 @@ impl Machine for MachineX86_64 {
                     let mut argalloc = ArgumentRegisterAllocator::default();
                     argalloc.next(Type::I64, calling_convention).unwrap(); // skip VMContext
                     let mut caller_stack_offset: i32 = 0;
-                    for (i, ty) in sig.params().iter().enumerate() {
+                    for (i, ty) in params.iter().enumerate() {
                         let prev_loc = param_locations[i];
                         let targ = match argalloc.next(*ty, calling_convention) {
                             Some(X64Register::GPR(gpr)) => Location::GPR(gpr),
@@ -0,0 +1,322 @@
+//! RISC-V 64 structures.
+
+use crate::common_decl::{MachineState, MachineValue, RegisterIndex};
+use crate::location::CombinedRegister;
+use crate::location::Reg as AbstractReg;
+use std::collections::BTreeMap;
+use std::slice::Iter;
+use wasmer_types::{CallingConvention, Type};
+
+/// General-purpose registers.
+#[repr(u8)]
+#[derive(Copy, Clone, Debug, Eq, PartialEq, Ord, PartialOrd, Hash)]
+#[allow(clippy::upper_case_acronyms)]
+pub enum GPR {
+    /// Hard-wired zero.
+    X0 = 0,
+    /// Return address (`ra`).
+    X1 = 1,
+    /// Stack pointer (`sp`).
+    X2 = 2,
+    /// Global pointer (`gp`).
+    X3 = 3,
+    /// Thread pointer (`tp`).
+    X4 = 4,
+    X5 = 5,
+    X6 = 6,
+    X7 = 7,
+    /// Frame pointer (`s0`).
+    X8 = 8,
+    X9 = 9,
+    X10 = 10,
+    X11 = 11,
+    X12 = 12,
+    X13 = 13,
+    X14 = 14,
+    X15 = 15,
+    X16 = 16,
+    X17 = 17,
+    X18 = 18,
+    X19 = 19,
+    X20 = 20,
+    X21 = 21,
+    X22 = 22,
+    X23 = 23,
+    X24 = 24,
+    X25 = 25,
+    X26 = 26,
+    X27 = 27,
+    X28 = 28,
+    X29 = 29,
+    X30 = 30,
+    X31 = 31,
+}
+
+/// Floating-point registers.
+#[repr(u8)]
+#[derive(Copy, Clone, Debug, Eq, PartialEq, Ord, PartialOrd, Hash)]
+#[allow(dead_code)]
+#[allow(clippy::upper_case_acronyms)]
+pub enum FPR {
+    F0 = 0,
+    F1 = 1,
+    F2 = 2,
+    F3 = 3,
+    F4 = 4,
+    F5 = 5,
+    F6 = 6,
+    F7 = 7,
+    F8 = 8,
+    F9 = 9,
+    F10 = 10,
+    F11 = 11,
+    F12 = 12,
+    F13 = 13,
+    F14 = 14,
+    F15 = 15,
+    F16 = 16,
+    F17 = 17,
+    F18 = 18,
+    F19 = 19,
+    F20 = 20,
+    F21 = 21,
+    F22 = 22,
+    F23 = 23,
+    F24 = 24,
+    F25 = 25,
+    F26 = 26,
+    F27 = 27,
+    F28 = 28,
+    F29 = 29,
+    F30 = 30,
+    F31 = 31,
+}
+
+impl AbstractReg for GPR {
+    fn is_callee_save(self) -> bool {
+        matches!(self.into_index(), 8 | 9 | 18..=27)
+    }
+    fn is_reserved(self) -> bool {
+        !matches!(self.into_index(), 5..=7 | 9..=26 | 28..=30)
+    }
+    fn into_index(self) -> usize {
+        self as usize
+    }
+    fn from_index(n: usize) -> Result<GPR, ()> {
+        match n {
+            0..=31 => Ok(*GPR::iterator().nth(n).unwrap()),
+            _ => Err(()),
+        }
+    }
+    fn iterator() -> Iter<'static, GPR> {
+        static GPRS: [GPR; 32] = [
+            GPR::X0,
+            GPR::X1,
+            GPR::X2,
+            GPR::X3,
+            GPR::X4,
+            GPR::X5,
+            GPR::X6,
+            GPR::X7,
+            GPR::X8,
+            GPR::X9,
+            GPR::X10,
+            GPR::X11,
+            GPR::X12,
+            GPR::X13,
+            GPR::X14,
+            GPR::X15,
+            GPR::X16,
+            GPR::X17,
+            GPR::X18,
+            GPR::X19,
+            GPR::X20,
+            GPR::X21,
+            GPR::X22,
+            GPR::X23,
+            GPR::X24,
+            GPR::X25,
+            GPR::X26,
+            GPR::X27,
+            GPR::X28,
+            GPR::X29,
+            GPR::X30,
+            GPR::X31,
+        ];
+        GPRS.iter()
+    }
+    fn to_dwarf(self) -> u16 {
+        self.into_index() as u16
+    }
+}
+
+impl AbstractReg for FPR {
+    fn is_callee_save(self) -> bool {
+        matches!(self.into_index(), 8 | 9 | 18..=27)
+    }
+    fn is_reserved(self) -> bool {
+        false
+    }
+    fn into_index(self) -> usize {
+        self as usize
+    }
+    fn from_index(n: usize) -> Result<FPR, ()> {
+        match n {
+            0..=31 => Ok(*FPR::iterator().nth(n).unwrap()),
+            _ => Err(()),
+        }
+    }
+    fn iterator() -> Iter<'static, FPR> {
+        const FPRS: [FPR; 32] = [
+            FPR::F0,
+            FPR::F1,
+            FPR::F2,
+            FPR::F3,
+            FPR::F4,
+            FPR::F5,
+            FPR::F6,
+            FPR::F7,
+            FPR::F8,
+            FPR::F9,
+            FPR::F10,
+            FPR::F11,
+            FPR::F12,
+            FPR::F13,
+            FPR::F14,
+            FPR::F15,
+            FPR::F16,
+            FPR::F17,
+            FPR::F18,
+            FPR::F19,
+            FPR::F20,
+            FPR::F21,
+            FPR::F22,
+            FPR::F23,
+            FPR::F24,
+            FPR::F25,
+            FPR::F26,
+            FPR::F27,
+            FPR::F28,
+            FPR::F29,
+            FPR::F30,
+            FPR::F31,
+        ];
+        FPRS.iter()
+    }
+    fn to_dwarf(self) -> u16 {
+        self.into_index() as u16 + 32
+    }
+}
+
+/// A machine register under the RISC-V 64 architecture.
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+#[allow(clippy::upper_case_acronyms)]
+pub enum RiscvRegister {
+    /// General-purpose registers.
+    GPR(GPR),
+    /// Floating-point registers.
+    FPR(FPR),
+}
+
+impl CombinedRegister for RiscvRegister {
+    /// Returns the index of the register.
+    fn to_index(&self) -> RegisterIndex {
+        match *self {
+            RiscvRegister::GPR(x) => RegisterIndex(x as usize),
+            RiscvRegister::FPR(x) => RegisterIndex(x as usize + 32),
+        }
+    }
+    /// Convert from a GPR register
+    fn from_gpr(x: u16) -> Self {
+        RiscvRegister::GPR(GPR::from_index(x as usize).unwrap())
+    }
+    /// Convert from an SIMD register
+    fn from_simd(x: u16) -> Self {
+        RiscvRegister::FPR(FPR::from_index(x as usize).unwrap())
+    }
+
+    /// Converts a DWARF regnum to RiscvRegister.
+    fn _from_dwarf_regnum(x: u16) -> Option<RiscvRegister> {
+        Some(match x {
+            0..=31 => RiscvRegister::GPR(GPR::from_index(x as usize).unwrap()),
+            32..=63 => RiscvRegister::FPR(FPR::from_index(x as usize - 32).unwrap()),
+            _ => return None,
+        })
+    }
+}
+
+/// An allocator that allocates registers for function arguments according to the LP64D ABI.
+#[derive(Default)]
+pub struct ArgumentRegisterAllocator {
+    n_gprs: usize,
+    n_fprs: usize,
+}
+
+impl ArgumentRegisterAllocator {
+    /// Allocates a register for argument type `ty`. Returns `None` if no register is available for this type.
+    ///
+    /// Floating-point arguments fall back to the integer registers once
+    /// `fa0`-`fa7` are exhausted, as the LP64D ABI requires.
+    pub fn next(
+        &mut self,
+        ty: Type,
+        calling_convention: CallingConvention,
+    ) -> Option<RiscvRegister> {
+        match calling_convention {
+            CallingConvention::SystemV => {
+                static GPR_SEQ: &[GPR] = &[
+                    GPR::X10,
+                    GPR::X11,
+                    GPR::X12,
+                    GPR::X13,
+                    GPR::X14,
+                    GPR::X15,
+                    GPR::X16,
+                    GPR::X17,
+                ];
+                static FPR_SEQ: &[FPR] = &[
+                    FPR::F10,
+                    FPR::F11,
+                    FPR::F12,
+                    FPR::F13,
+                    FPR::F14,
+                    FPR::F15,
+                    FPR::F16,
+                    FPR::F17,
+                ];
+                match ty {
+                    Type::F32 | Type::F64 if self.n_fprs < FPR_SEQ.len() => {
+                        let fpr = FPR_SEQ[self.n_fprs];
+                        self.n_fprs += 1;
+                        Some(RiscvRegister::FPR(fpr))
+                    }
+                    Type::I32 | Type::I64 | Type::F32 | Type::F64 => {
+                        if self.n_gprs < GPR_SEQ.len() {
+                            let gpr = GPR_SEQ[self.n_gprs];
+                            self.n_gprs += 1;
+                            Some(RiscvRegister::GPR(gpr))
+                        } else {
+                            None
+                        }
+                    }
+                    _ => todo!(
+                        "ArgumentRegisterAllocator::next: Unsupported type: {:?}",
+                        ty
+                    ),
+                }
+            }
+            _ => unimplemented!(),
+        }
+    }
+}
+
+/// Create a new `MachineState` with default values.
+pub fn new_machine_state() -> MachineState {
+    MachineState {
+        stack_values: vec![],
+        register_values: vec![MachineValue::Undefined; 32 + 32],
+        prev_frame: BTreeMap::new(),
+        wasm_stack: vec![],
+        wasm_inst_offset: std::usize::MAX,
+    }
+}
@@ -1,7 +1,7 @@
 #[cfg(feature = "unwind")]
 use gimli::write::{Address, CallFrameInstruction, CommonInformationEntry, FrameDescriptionEntry};
 #[cfg(feature = "unwind")]
-use gimli::{AArch64, Encoding, Format, X86_64};
+use gimli::{AArch64, Encoding, Format, RiscV, X86_64};
 use std::fmt::Debug;
 #[cfg(feature = "unwind")]
 use wasmer_types::Architecture;
@@ -76,6 +76,20 @@ pub fn create_systemv_cie(arch: Architecture) -> Option<gimli::write::CommonInfo
             entry.add_instruction(CallFrameInstruction::Cfa(AArch64::SP, 0));
             Some(entry)
         }
+        Architecture::Riscv64(_) => {
+            let mut entry = CommonInformationEntry::new(
+                Encoding {
+                    address_size: 8,
+                    format: Format::Dwarf32,
+                    version: 1,
+                },
+                1,
+                -8,
+                RiscV::RA,
+            );
+            entry.add_instruction(CallFrameInstruction::Cfa(RiscV::SP, 0));
+            Some(entry)
+        }
         _ => None,
     }
 }
@@ -13,6 +13,7 @@ use core::mem::MaybeUninit;
 use enumset::EnumSet;
 use rkyv::de::deserializers::SharedDeserializeMap;
 use rkyv::option::ArchivedOption;
+use rkyv::AlignedVec;
 use self_cell::self_cell;
 use shared_buffer::OwnedBuffer;
 use std::sync::Arc;
@@ -29,8 +30,10 @@ use wasmer_types::{
 };
 use wasmer_types::{
     CompiledFunctionFrameInfo, FunctionBody, HashAlgorithm, SerializableCompilation,
-    SerializableModule,
+    SerializableLazyFunction, SerializableModule,
 };
+#[cfg(feature = "compiler")]
+use wasmer_types::{CustomSectionProtection, SectionBody};
 use wasmer_types::{MetadataHeader, SerializeError};
 
 /// A compiled wasm module, ready to be instantiated.
@@ -54,7 +57,7 @@ impl ArtifactBuild {
         data: &[u8],
         target: &Target,
         memory_styles: PrimaryMap<MemoryIndex, MemoryStyle>,
-        table_styles: PrimaryMap<TableIndex, TableStyle>,
+        mut table_styles: PrimaryMap<TableIndex, TableStyle>,
         hash_algorithm: Option<HashAlgorithm>,
     ) -> Result<Self, CompileError> {
         let environ = ModuleEnvironment::new();
@@ -69,6 +72,12 @@ impl ArtifactBuild {
         let middlewares = compiler.get_middlewares();
         middlewares.apply_on_module_info(&mut module);
 
+        // The middlewares may have appended tables, which all share the
+        // same style.
+        while table_styles.len() < module.tables.len() {
+            table_styles.push(TableStyle::CallerChecksSignature);
+        }
+
         if let Some(hash_algorithm) = hash_algorithm {
             let hash = match hash_algorithm {
                 HashAlgorithm::Sha256 => ModuleHash::sha256(data),
@@ -118,13 +127,27 @@ impl ArtifactBuild {
             .iter()
             .map(|(_, section)| section.relocations.clone())
             .collect::<PrimaryMap<SectionIndex, _>>();
-        let libcall_trampolines_section = make_libcall_trampolines(target);
+        // Interpreted code doesn't call libcalls through trampolines, and must
+        // not require any executable memory.
+        let interpreted = compiler.is_interpreter();
+        let (libcall_trampolines_section, libcall_trampoline_len) = if interpreted {
+            let section = CustomSection {
+                protection: CustomSectionProtection::Read,
+                bytes: SectionBody::default(),
+                relocations: vec![],
+            };
+            (section, 0)
+        } else {
+            (
+                make_libcall_trampolines(target),
+                libcall_trampoline_len(target) as u32,
+            )
+        };
         custom_section_relocations.push(libcall_trampolines_section.relocations.clone());
         let libcall_trampolines = custom_sections.push(libcall_trampolines_section);
-        let libcall_trampoline_len = libcall_trampoline_len(target) as u32;
         let cpu_features = compiler.get_cpu_features_used(target.cpu_features());
 
-        let serializable_compilation = SerializableCompilation {
+        let mut serializable_compilation = SerializableCompilation {
             function_bodies,
             function_relocations,
             function_frame_info,
@@ -135,12 +158,21 @@ impl ArtifactBuild {
             debug: compilation.debug,
             libcall_trampolines,
             libcall_trampoline_len,
+            interpreted,
+            tier_up: compiler.tiers_up(),
+            // The functions are compiled from the binary on their first call.
+            lazy_binary: compiler.compiles_lazily().then(|| data.to_vec()),
+            lazy_functions: Vec::new(),
+            profiled: compiler.instruments_profile(),
         };
+        serializable_compilation.canonicalize();
         let serializable = SerializableModule {
             compilation: serializable_compilation,
             compile_info,
             data_initializers,
             cpu_features: cpu_features.as_u64(),
+            triple: target.triple().to_string(),
+            compiler: compiler.deterministic_id(),
         };
         Ok(Self { serializable })
     }
@@ -150,6 +182,34 @@ impl ArtifactBuild {
         Self { serializable }
     }
 
+    /// Deserialize a serialized `ArtifactBuild`, validating its layout.
+    ///
+    /// Unlike deserializing an `Artifact`, this doesn't load the code of the
+    /// module into memory, so it can be used on untrusted bytes and on
+    /// artifacts compiled for any target, to inspect them.
+    pub fn deserialize_checked(bytes: &[u8]) -> Result<Self, DeserializeError> {
+        if !Self::is_deserializable(bytes) {
+            return Err(DeserializeError::Incompatible(
+                "The provided bytes are not wasmer-universal".to_string(),
+            ));
+        }
+        // The archive must be aligned, which the bytes of a file read in
+        // memory may not be.
+        let mut aligned = AlignedVec::with_capacity(bytes.len());
+        aligned.extend_from_slice(bytes);
+        let bytes = &aligned[Self::MAGIC_HEADER.len()..];
+
+        let metadata_len = MetadataHeader::parse(bytes)?;
+        let metadata_slice = bytes
+            .get(MetadataHeader::LEN..MetadataHeader::LEN + metadata_len)
+            .ok_or(DeserializeError::InvalidByteLength {
+                expected: MetadataHeader::LEN + metadata_len,
+                got: bytes.len(),
+            })?;
+        let archived = SerializableModule::archive_from_slice_checked(metadata_slice)?;
+        SerializableModule::deserialize_from_archive(archived).map(Self::from_serializable)
+    }
+
     /// Get Functions Bodies ref
     pub fn get_function_bodies_ref(&self) -> &PrimaryMap<LocalFunctionIndex, FunctionBody> {
         &self.serializable.compilation.function_bodies
@@ -190,6 +250,60 @@ impl ArtifactBuild {
         self.serializable.compilation.libcall_trampoline_len as usize
     }
 
+    /// Get the target triple the module was compiled for
+    pub fn triple(&self) -> &str {
+        &self.serializable.triple
+    }
+
+    /// Get the deterministic id of the compiler the module was compiled
+    /// with, empty if it isn't known
+    pub fn compiler(&self) -> &str {
+        &self.serializable.compiler
+    }
+
+    /// Whether the function bodies are interpreter bytecode
+    pub fn is_interpreted(&self) -> bool {
+        self.serializable.compilation.interpreted
+    }
+
+    /// Whether the functions count their calls and may be redirected
+    pub fn tiers_up(&self) -> bool {
+        self.serializable.compilation.tier_up
+    }
+
+    /// Whether the functions record a profile while they run
+    pub fn is_profiled(&self) -> bool {
+        self.serializable.compilation.profiled
+    }
+
+    /// Get the wasm binary of a lazily compiled module
+    pub fn get_lazy_binary(&self) -> Option<&[u8]> {
+        self.serializable.compilation.lazy_binary.as_deref()
+    }
+
+    /// Get the functions of a lazily compiled module compiled so far
+    pub fn get_lazy_functions_ref(&self) -> &[SerializableLazyFunction] {
+        &self.serializable.compilation.lazy_functions
+    }
+
+    /// Serialize the artifact along with the functions of a lazily
+    /// compiled module compiled since it was created.
+    pub fn serialize_with_lazy_functions(
+        &self,
+        lazy_functions: &[SerializableLazyFunction],
+    ) -> Result<Vec<u8>, SerializeError> {
+        if lazy_functions.is_empty() {
+            return serialize_module(&self.serializable);
+        }
+        let mut module = self.serializable.clone();
+        module
+            .compilation
+            .lazy_functions
+            .extend_from_slice(lazy_functions);
+        module.compilation.canonicalize();
+        serialize_module(&module)
+    }
+
     /// Get Debug optional Dwarf ref
     pub fn get_debug_ref(&self) -> Option<&Dwarf> {
         self.serializable.compilation.debug.as_ref()
@@ -255,6 +369,10 @@ pub struct ModuleFromArchive<'a> {
     pub data_initializers: &'a rkyv::Archived<Box<[OwnedDataInitializer]>>,
     /// CPU Feature flags for this compilation
     pub cpu_features: u64,
+    /// Target triple for this compilation
+    pub triple: &'a str,
+    /// Deterministic id of the compiler used for this compilation
+    pub compiler: &'a str,
 
     // Keep the original module around for re-serialization
     original_module: &'a ArchivedSerializableModule,
@@ -269,6 +387,8 @@ impl<'a> ModuleFromArchive<'a> {
             compilation: &module.compilation,
             data_initializers: &module.data_initializers,
             cpu_features: module.cpu_features,
+            triple: module.triple.as_str(),
+            compiler: module.compiler.as_str(),
             original_module: module,
         })
     }
@@ -393,6 +513,80 @@ impl ArtifactBuildFromArchive {
             .libcall_trampoline_len as usize
     }
 
+    /// Get the target triple the module was compiled for
+    pub fn triple(&self) -> &str {
+        self.cell.borrow_dependent().triple
+    }
+
+    /// Get the deterministic id of the compiler the module was compiled
+    /// with, empty if it isn't known
+    pub fn compiler(&self) -> &str {
+        self.cell.borrow_dependent().compiler
+    }
+
+    /// Whether the function bodies are interpreter bytecode
+    pub fn is_interpreted(&self) -> bool {
+        self.cell.borrow_dependent().compilation.interpreted
+    }
+
+    /// Whether the functions count their calls and may be redirected
+    pub fn tiers_up(&self) -> bool {
+        self.cell.borrow_dependent().compilation.tier_up
+    }
+
+    /// Whether the functions record a profile while they run
+    pub fn is_profiled(&self) -> bool {
+        self.cell.borrow_dependent().compilation.profiled
+    }
+
+    /// Get the wasm binary of a lazily compiled module
+    pub fn get_lazy_binary(&self) -> Option<&[u8]> {
+        match self.cell.borrow_dependent().compilation.lazy_binary {
+            ArchivedOption::Some(ref binary) => Some(binary.as_slice()),
+            ArchivedOption::None => None,
+        }
+    }
+
+    /// Deserialize the functions of a lazily compiled module compiled so far
+    pub fn deserialize_lazy_functions(
+        &self,
+    ) -> Result<Vec<SerializableLazyFunction>, DeserializeError> {
+        let mut deserializer = SharedDeserializeMap::new();
+        rkyv::Deserialize::deserialize(
+            &self.cell.borrow_dependent().compilation.lazy_functions,
+            &mut deserializer,
+        )
+        .map_err(|e| DeserializeError::CorruptedBinary(format!("{:?}", e)))
+    }
+
+    /// Serialize the artifact along with the functions of a lazily
+    /// compiled module compiled since it was loaded.
+    pub fn serialize_with_lazy_functions(
+        &self,
+        lazy_functions: &[SerializableLazyFunction],
+    ) -> Result<Vec<u8>, SerializeError> {
+        // We could have stored the original bytes, but since the module info name
+        // is mutable, we have to assume the data may have changed and serialize
+        // everything all over again. Also, to be able to serialize, first we have
+        // to deserialize completely. Luckily, serializing a module that was already
+        // deserialized from a file makes little sense, so hopefully, this is not a
+        // common use-case.
+
+        let mut deserializer = SharedDeserializeMap::new();
+        let mut module: SerializableModule = rkyv::Deserialize::deserialize(
+            self.cell.borrow_dependent().original_module,
+            &mut deserializer,
+        )
+        .map_err(|e| SerializeError::Generic(e.to_string()))?;
+        module.compile_info = self.compile_info.clone();
+        module
+            .compilation
+            .lazy_functions
+            .extend_from_slice(lazy_functions);
+        module.compilation.canonicalize();
+        serialize_module(&module)
+    }
+
     /// Get Debug optional Dwarf ref
     pub fn get_debug_ref(&self) -> Option<&Dwarf> {
         match self.cell.borrow_dependent().compilation.debug {
@@ -461,21 +655,7 @@ impl<'a> ArtifactCreate<'a> for ArtifactBuildFromArchive {
     }
 
     fn serialize(&self) -> Result<Vec<u8>, SerializeError> {
-        // We could have stored the original bytes, but since the module info name
-        // is mutable, we have to assume the data may have changed and serialize
-        // everything all over again. Also, to be able to serialize, first we have
-        // to deserialize completely. Luckily, serializing a module that was already
-        // deserialized from a file makes little sense, so hopefully, this is not a
-        // common use-case.
-
-        let mut deserializer = SharedDeserializeMap::new();
-        let mut module: SerializableModule = rkyv::Deserialize::deserialize(
-            self.cell.borrow_dependent().original_module,
-            &mut deserializer,
-        )
-        .map_err(|e| SerializeError::Generic(e.to_string()))?;
-        module.compile_info = self.compile_info.clone();
-        serialize_module(&module)
+        self.serialize_with_lazy_functions(&[])
     }
 }
 
@@ -1,9 +1,11 @@
 //! Generic Artifact abstraction for Wasmer Engines.
 
 mod artifact_builder;
+mod summary;
 #[cfg(feature = "compiler")]
 mod trampoline;
 
 pub use self::artifact_builder::{ArtifactBuild, ArtifactBuildFromArchive, ModuleFromArchive};
+pub use self::summary::{ArtifactSummary, CodeSummary, FunctionSummary};
 #[cfg(feature = "compiler")]
 pub use self::trampoline::*;
@@ -0,0 +1,154 @@
+//! Summarize what an `ArtifactBuild` is made of, to inspect serialized
+//! artifacts and compare them.
+
+use super::ArtifactBuild;
+use crate::ArtifactCreate;
+use crate::Features;
+use enumset::EnumSet;
+use std::mem;
+use wasmer_types::{
+    CpuFeature, FunctionBody, FunctionIndex, InstructionAddressMap, TrapInformation,
+};
+
+/// The sizes of the code of a compiled function.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FunctionSummary {
+    /// The index of the function in the module.
+    pub index: FunctionIndex,
+    /// The name of the function, if the module has one for it.
+    pub name: Option<String>,
+    /// The size of the body of the function, in bytes.
+    pub code_size: usize,
+    /// The number of relocations of the body.
+    pub relocations: usize,
+    /// The size of the traps and address map of the function, in bytes.
+    pub frame_info_size: usize,
+}
+
+impl FunctionSummary {
+    /// The name of the function, or its index if it has none.
+    pub fn display_name(&self) -> String {
+        match &self.name {
+            Some(name) => name.clone(),
+            None => format!("<function {}>", self.index.as_u32()),
+        }
+    }
+}
+
+/// The number and total size of a kind of compiled code.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct CodeSummary {
+    /// How many there are.
+    pub count: usize,
+    /// Their total size, in bytes.
+    pub size: usize,
+}
+
+impl CodeSummary {
+    fn of<'a>(bodies: impl Iterator<Item = &'a FunctionBody>) -> Self {
+        bodies.fold(Self::default(), |summary, body| Self {
+            count: summary.count + 1,
+            size: summary.size + body.body.len(),
+        })
+    }
+}
+
+/// What an artifact is made of: how it was compiled, and the sizes of its
+/// functions, trampolines and sections.
+#[derive(Debug, Clone)]
+pub struct ArtifactSummary {
+    /// The deterministic id of the compiler, empty if it isn't known.
+    pub compiler: String,
+    /// The target triple the artifact was compiled for.
+    pub triple: String,
+    /// The CPU features the code of the artifact uses.
+    pub cpu_features: EnumSet<CpuFeature>,
+    /// The WebAssembly features enabled for the compilation.
+    pub features: Features,
+    /// The functions defined by the module.
+    pub functions: Vec<FunctionSummary>,
+    /// The trampolines calling the functions from the host, one per
+    /// signature.
+    pub function_call_trampolines: CodeSummary,
+    /// The trampolines calling the imported host functions.
+    pub dynamic_function_trampolines: CodeSummary,
+    /// The custom sections, including the libcall trampolines.
+    pub custom_sections: CodeSummary,
+    /// The number of relocations of the custom sections.
+    pub custom_section_relocations: usize,
+    /// The total size of the data initializers, in bytes.
+    pub data_size: usize,
+}
+
+impl ArtifactSummary {
+    /// Summarize `artifact`.
+    pub fn new(artifact: &ArtifactBuild) -> Self {
+        let module_info = artifact.module_info();
+        let relocations = artifact.get_function_relocations();
+        let frame_info = artifact.get_frame_info_ref();
+        let functions = artifact
+            .get_function_bodies_ref()
+            .iter()
+            .map(|(local_index, body)| {
+                let index = module_info.func_index(local_index);
+                let frame_info = &frame_info[local_index];
+                FunctionSummary {
+                    index,
+                    name: module_info.function_names.get(&index).cloned(),
+                    code_size: body.body.len(),
+                    relocations: relocations[local_index].len(),
+                    frame_info_size: frame_info.traps.len() * mem::size_of::<TrapInformation>()
+                        + frame_info.address_map.instructions.len()
+                            * mem::size_of::<InstructionAddressMap>(),
+                }
+            })
+            .collect();
+
+        let custom_sections = artifact.get_custom_sections_ref().values().fold(
+            CodeSummary::default(),
+            |summary, section| CodeSummary {
+                count: summary.count + 1,
+                size: summary.size + section.bytes.len(),
+            },
+        );
+
+        Self {
+            compiler: artifact.compiler().to_string(),
+            triple: artifact.triple().to_string(),
+            cpu_features: artifact.cpu_features(),
+            features: artifact.features().clone(),
+            functions,
+            function_call_trampolines: CodeSummary::of(
+                artifact.get_function_call_trampolines_ref().values(),
+            ),
+            dynamic_function_trampolines: CodeSummary::of(
+                artifact.get_dynamic_function_trampolines_ref().values(),
+            ),
+            custom_sections,
+            custom_section_relocations: artifact
+                .get_custom_section_relocations_ref()
+                .values()
+                .map(Vec::len)
+                .sum(),
+            data_size: artifact
+                .data_initializers()
+                .map(|init| init.data.len())
+                .sum(),
+        }
+    }
+
+    /// The total size of the bodies of the functions, in bytes.
+    pub fn code_size(&self) -> usize {
+        self.functions.iter().map(|f| f.code_size).sum()
+    }
+
+    /// The total number of relocations of the functions.
+    pub fn relocations(&self) -> usize {
+        self.functions.iter().map(|f| f.relocations).sum()
+    }
+
+    /// The total size of the frame info of the functions, in bytes.
+    pub fn frame_info_size(&self) -> usize {
+        self.functions.iter().map(|f| f.frame_info_size).sum()
+    }
+}
@@ -6,9 +6,11 @@ use crate::lib::std::sync::Arc;
 use crate::translator::ModuleMiddleware;
 use crate::FunctionBodyData;
 use crate::ModuleTranslationState;
+use core::num::NonZeroUsize;
 use enumset::EnumSet;
-use wasmer_types::compilation::function::Compilation;
+use wasmer_types::compilation::function::{Compilation, CompiledFunction};
 use wasmer_types::compilation::module::CompileModuleInfo;
+use wasmer_types::compilation::section::CustomSection;
 use wasmer_types::compilation::symbols::SymbolRegistry;
 use wasmer_types::compilation::target::Target;
 use wasmer_types::entity::PrimaryMap;
@@ -46,6 +48,82 @@ pub trait CompilerConfig {
         // in case they create an IR that they can verify.
     }
 
+    /// Use the deterministic semantics of the relaxed-SIMD operators.
+    ///
+    /// Relaxed-SIMD operators are allowed to give different results on
+    /// different architectures, so that each of them uses its fastest
+    /// instructions. With this enabled, they give the same results
+    /// everywhere, at a cost on targets without the matching instructions.
+    fn relaxed_simd_deterministic(&mut self, _enable: bool) {
+        // By default we do nothing, each backend will need to customize this
+        // in case they implement the relaxed-SIMD proposal.
+    }
+
+    /// Enable the memory watch.
+    ///
+    /// Loads and stores of the compiled code are reported to the
+    /// `MemoryWatch` of their instance, which is useful to find out which
+    /// code accesses some part of the linear memory.
+    ///
+    /// Only scalar loads and stores are reported: vector and atomic
+    /// accesses, and bulk memory operations, aren't. Modules with 64-bit
+    /// memories can't be compiled with the memory watch enabled.
+    fn enable_memory_watch(&mut self) {
+        // By default we do nothing, each backend will need to customize this
+        // in case they can route memory accesses through the memory watch.
+    }
+
+    /// Enable tier-up.
+    ///
+    /// Functions count their calls in the `VMFunctionTier`s of their
+    /// instance, and jump to the body set as their redirect once there is
+    /// one, so that hot functions can be replaced by code compiled later
+    /// with a slower, optimizing compiler.
+    fn enable_tier_up(&mut self) {
+        // By default we do nothing, each backend will need to customize this
+        // in case they can emit the entry sequences used by tier-up.
+    }
+
+    /// Enable lazy compilation.
+    ///
+    /// Modules are compiled to stubs, which compile their function on its
+    /// first call and jump to it from then on. This makes large modules
+    /// start quickly when few of their functions run.
+    fn enable_lazy_compilation(&mut self) {
+        // By default we do nothing, each backend will need to customize this
+        // in case they can emit stubs and compile functions on their own.
+    }
+
+    /// Enable profile instrumentation.
+    ///
+    /// Functions count their calls and how often each of their conditional
+    /// branches is taken in the `VMProfile` of their instance. The recorded
+    /// profile can be fed back to an optimizing compiler.
+    fn enable_profile_instrumentation(&mut self) {
+        // By default we do nothing, each backend will need to customize this
+        // in case they can emit the profile counters.
+    }
+
+    /// Enable debugging.
+    ///
+    /// Functions check for an attached debugger before each instruction,
+    /// so it can inspect them and stop at breakpoints. Functions compiled
+    /// without it never stop.
+    fn enable_debugging(&mut self) {
+        // By default we do nothing, each backend will need to customize this
+        // in case they can call a debugger.
+    }
+
+    /// Set the number of threads compiling the functions of a module.
+    ///
+    /// With `None`, the functions are compiled by the global thread pool,
+    /// which has a thread per CPU. The compiled code is the same whatever
+    /// the number of threads.
+    fn num_threads(&mut self, _num_threads: Option<NonZeroUsize>) {
+        // By default we do nothing, each backend will need to customize this
+        // in case they compile functions in parallel.
+    }
+
     /// Gets the custom compiler config
     fn compiler(self: Box<Self>) -> Box<dyn Compiler>;
 
@@ -74,6 +152,13 @@ pub trait Compiler: Send {
     /// Note that this is an API breaking change since 3.0
     fn name(&self) -> &str;
 
+    /// Returns an id for this compiler and the parts of its configuration
+    /// changing the generated code, so artifacts from different
+    /// configurations aren't mixed up.
+    fn deterministic_id(&self) -> String {
+        self.name().to_string()
+    }
+
     /// Validates a module.
     ///
     /// It returns the a succesful Result in case is valid, `CompileError` in case is not.
@@ -122,6 +207,26 @@ pub trait Compiler: Send {
         function_body_inputs: PrimaryMap<LocalFunctionIndex, FunctionBodyData<'_>>,
     ) -> Result<Compilation, CompileError>;
 
+    /// Compiles the function at `index` of a lazily compiled module (see
+    /// [`CompilerConfig::enable_lazy_compilation`]) on its own.
+    ///
+    /// It returns the function along with the custom section holding its
+    /// unwind information, if any, or a [`CompileError`]. The relocations
+    /// of the function refer to the custom sections of the [`Compilation`]
+    /// of the module.
+    fn compile_function(
+        &self,
+        _target: &Target,
+        _module: &CompileModuleInfo,
+        _module_translation: &ModuleTranslationState,
+        _index: LocalFunctionIndex,
+        _function_body_input: &FunctionBodyData<'_>,
+    ) -> Result<(CompiledFunction, Option<CustomSection>), CompileError> {
+        Err(CompileError::UnsupportedFeature(
+            "lazy compilation is not supported by this compiler".to_string(),
+        ))
+    }
+
     /// Compiles a module into a native object file.
     ///
     /// It returns the bytes as a `&[u8]` or a [`CompileError`].
@@ -146,4 +251,32 @@ pub trait Compiler: Send {
     fn get_cpu_features_used(&self, cpu_features: &EnumSet<CpuFeature>) -> EnumSet<CpuFeature> {
         *cpu_features
     }
+
+    /// Whether the function bodies produced by this compiler are interpreter
+    /// bytecode rather than native code.
+    ///
+    /// Interpreted artifacts are never made executable, and all their call
+    /// trampolines are replaced with the interpreter entry point when loaded.
+    fn is_interpreter(&self) -> bool {
+        false
+    }
+
+    /// Whether the functions compiled by this compiler count their calls and
+    /// jump to their redirect once set (see [`CompilerConfig::enable_tier_up`]).
+    fn tiers_up(&self) -> bool {
+        false
+    }
+
+    /// Whether this compiler compiles modules to stubs compiling their
+    /// functions on their first call (see
+    /// [`CompilerConfig::enable_lazy_compilation`]).
+    fn compiles_lazily(&self) -> bool {
+        false
+    }
+
+    /// Whether the functions compiled by this compiler record a profile
+    /// (see [`CompilerConfig::enable_profile_instrumentation`]).
+    fn instruments_profile(&self) -> bool {
+        false
+    }
 }
@@ -1,11 +1,14 @@
 //! Define `Artifact`, based on `ArtifactBuild`
 //! to allow compiling and instantiating to be done as separate steps.
 
+use crate::engine::lazy::LazyFunctions;
 use crate::engine::link::link_module;
 use crate::lib::std::vec::IntoIter;
 use crate::ArtifactBuild;
 use crate::ArtifactBuildFromArchive;
 use crate::ArtifactCreate;
+#[cfg(feature = "compiler")]
+use crate::BaseTunables;
 use crate::Features;
 use crate::FrameInfosVariant;
 use crate::ModuleEnvironment;
@@ -26,10 +29,9 @@ use std::sync::Arc;
 use wasmer_object::{emit_compilation, emit_data, get_object_for_target, Object};
 #[cfg(any(feature = "static-artifact-create", feature = "static-artifact-load"))]
 use wasmer_types::compilation::symbols::ModuleMetadata;
-use wasmer_types::entity::{BoxedSlice, PrimaryMap};
+use wasmer_types::entity::{BoxedSlice, EntityRef, PrimaryMap};
 use wasmer_types::ArchivedDataInitializerLocation;
 use wasmer_types::ArchivedOwnedDataInitializer;
-#[cfg(feature = "static-artifact-create")]
 use wasmer_types::CompileModuleInfo;
 use wasmer_types::DataInitializerLike;
 use wasmer_types::DataInitializerLocation;
@@ -37,12 +39,17 @@ use wasmer_types::DataInitializerLocationLike;
 use wasmer_types::MetadataHeader;
 use wasmer_types::{
     CompileError, CpuFeature, DataInitializer, DeserializeError, FunctionIndex, HashAlgorithm,
-    LocalFunctionIndex, MemoryIndex, ModuleInfo, OwnedDataInitializer, SignatureIndex, TableIndex,
-    Target,
+    LocalFunctionIndex, MemoryIndex, ModuleInfo, OwnedDataInitializer, Profile, SignatureIndex,
+    TableIndex, Target, Triple,
 };
 use wasmer_types::{SerializableModule, SerializeError};
-use wasmer_vm::{FunctionBodyPtr, MemoryStyle, TableStyle, VMSharedSignatureIndex, VMTrampoline};
-use wasmer_vm::{InstanceAllocator, StoreObjects, TrapHandlerFn, VMConfig, VMExtern, VMInstance};
+use wasmer_vm::{
+    FunctionBodyPtr, MemoryStyle, TableStyle, VMFunctionTier, VMSharedSignatureIndex, VMTrampoline,
+};
+use wasmer_vm::{
+    InstanceAllocator, LazyCompiler, StoreObjects, TrapHandlerFn, VMConfig, VMExtern, VMInstance,
+    VMProfile,
+};
 
 pub struct AllocatedArtifact {
     // This shows if the frame info has been regestered already or not.
@@ -59,6 +66,15 @@ pub struct AllocatedArtifact {
     finished_dynamic_function_trampolines: BoxedSlice<FunctionIndex, FunctionBodyPtr>,
     signatures: BoxedSlice<SignatureIndex, VMSharedSignatureIndex>,
     finished_function_lengths: BoxedSlice<LocalFunctionIndex, usize>,
+    // The tier-up state of the functions, shared by all the instances, if
+    // they were compiled for tier-up.
+    function_tiers: Option<Arc<[VMFunctionTier]>>,
+    // Compiles the functions on their first call, if they were compiled
+    // lazily.
+    lazy_functions: Option<Arc<LazyFunctions>>,
+    // Where the functions count their calls and branches, shared by all the
+    // instances, if they were compiled with profile instrumentation.
+    profile: Option<Arc<VMProfile>>,
 }
 
 #[derive(Debug, PartialEq, Eq, PartialOrd, Ord)]
@@ -94,6 +110,9 @@ impl Default for ArtifactId {
 pub struct Artifact {
     id: ArtifactId,
     artifact: ArtifactBuildVariant,
+    // Whether the functions of a lazily compiled module compiled while
+    // running are left out of the serialized artifact.
+    reproducible: bool,
     // The artifact will only be allocated in memory in case we can execute it
     // (that means, if the target != host then this will be None).
     allocated: Option<AllocatedArtifact>,
@@ -102,6 +121,7 @@ pub struct Artifact {
 /// Artifacts may be created as the result of the compilation of a wasm
 /// module, corresponding to `ArtifactBuildVariant::Plain`, or loaded
 /// from an archive, corresponding to `ArtifactBuildVariant::Archived`.
+#[allow(clippy::large_enum_variant)]
 pub enum ArtifactBuildVariant {
     Plain(ArtifactBuild),
     Archived(ArtifactBuildFromArchive),
@@ -140,7 +160,8 @@ impl Artifact {
             hash_algorithm,
         )?;
 
-        Self::from_parts(
+        Self::from_parts_with_engine(
+            Some(engine),
             &mut inner_engine,
             ArtifactBuildVariant::Plain(artifact),
             engine.target(),
@@ -160,6 +181,49 @@ impl Artifact {
         })
     }
 
+    /// Compile a data buffer for `target` and serialize the resulting
+    /// artifact, without loading it.
+    ///
+    /// This allows to compile modules ahead of time for other hosts, with
+    /// different architectures or CPU features. The module is compiled with
+    /// the compiler and features of `engine`, and the default tunables of
+    /// `target`.
+    #[cfg(feature = "compiler")]
+    pub fn precompile(
+        engine: &Engine,
+        data: &[u8],
+        target: &Target,
+        hash_algorithm: Option<HashAlgorithm>,
+    ) -> Result<Vec<u8>, CompileError> {
+        let mut inner_engine = engine.inner_mut();
+        let environ = ModuleEnvironment::new();
+        let translation = environ.translate(data).map_err(CompileError::Wasm)?;
+        let module = translation.module;
+        let tunables = BaseTunables::for_target(target);
+        let memory_styles: PrimaryMap<MemoryIndex, MemoryStyle> = module
+            .memories
+            .values()
+            .map(|memory_type| tunables.memory_style(memory_type))
+            .collect();
+        let table_styles: PrimaryMap<TableIndex, TableStyle> = module
+            .tables
+            .values()
+            .map(|table_type| tunables.table_style(table_type))
+            .collect();
+
+        let artifact = ArtifactBuild::new(
+            &mut inner_engine,
+            data,
+            target,
+            memory_styles,
+            table_styles,
+            hash_algorithm,
+        )?;
+        artifact
+            .serialize()
+            .map_err(|e| CompileError::Codegen(e.to_string()))
+    }
+
     /// This indicates if the Artifact is allocated and can be run by the current
     /// host. In case it can't be run (for example, if the artifact is cross compiled to
     /// other architecture), it will return false.
@@ -171,7 +235,8 @@ impl Artifact {
     ///
     /// This exists to allow us to compare two Artifacts for equality. Otherwise,
     /// comparing two trait objects unsafely relies on implementation details
-    /// of trait representation.
+    /// of trait representation. It is only valid within this process, and
+    /// isn't part of the serialized artifact.
     pub fn id(&self) -> &ArtifactId {
         &self.id
     }
@@ -184,6 +249,25 @@ impl Artifact {
         ))
     }
 
+    /// Deserialize an artifact signed with an
+    /// [`ArtifactSigner`](crate::ArtifactSigner), after checking that its
+    /// signature was made by the signer of `verifier` for a compatible
+    /// engine.
+    ///
+    /// Unlike [`Self::deserialize`], this is safe to call on untrusted
+    /// bytes, as long as the key of the signer is trusted.
+    #[cfg(feature = "signing")]
+    pub fn deserialize_verified(
+        engine: &Engine,
+        bytes: OwnedBuffer,
+        verifier: &crate::ArtifactVerifier,
+    ) -> Result<Self, DeserializeError> {
+        let bytes = verifier.verify(engine, bytes)?;
+        // SAFETY: the artifact was signed with the trusted key, for an
+        // engine compatible with this one.
+        unsafe { Self::deserialize(engine, bytes) }
+    }
+
     /// Deserialize a serialized artifact.
     ///
     /// # Safety
@@ -210,7 +294,66 @@ impl Artifact {
             }
         }
 
-        let artifact = ArtifactBuildFromArchive::try_new(bytes, |bytes| {
+        let artifact = Self::archive_checked(bytes)?;
+
+        let mut inner_engine = engine.inner_mut();
+        Self::from_parts_with_engine(
+            Some(engine),
+            &mut inner_engine,
+            ArtifactBuildVariant::Archived(artifact),
+            engine.target(),
+        )
+    }
+
+    /// Deserialize the serialized artifact among `candidates` that suits
+    /// the target of `engine` best, such as one of the artifacts created
+    /// with [`Self::precompile`] for several CPUs.
+    ///
+    /// The best artifact is the one using the most CPU features among the
+    /// ones compiled for the same architecture and operating system, which
+    /// only use CPU features of the target. Candidates serialized by another
+    /// version of Wasmer are skipped.
+    ///
+    /// # Safety
+    /// See [`Self::deserialize`].
+    pub unsafe fn deserialize_best(
+        engine: &Engine,
+        candidates: impl IntoIterator<Item = OwnedBuffer>,
+    ) -> Result<Self, DeserializeError> {
+        let mut best: Option<ArtifactBuildVariant> = None;
+        for bytes in candidates {
+            if !ArtifactBuild::is_deserializable(bytes.as_ref()) {
+                continue;
+            }
+            let artifact = match Self::archive_checked(bytes) {
+                Ok(artifact) => ArtifactBuildVariant::Archived(artifact),
+                Err(DeserializeError::Incompatible(_)) => continue,
+                Err(e) => return Err(e),
+            };
+            if artifact.check_target(engine.target()).is_err() {
+                continue;
+            }
+            let is_better = best.as_ref().map_or(true, |best| {
+                artifact.cpu_features().len() > best.cpu_features().len()
+            });
+            if is_better {
+                best = Some(artifact);
+            }
+        }
+        let artifact = best.ok_or_else(|| {
+            DeserializeError::Incompatible(format!(
+                "None of the artifacts can run on `{}`",
+                engine.target().triple()
+            ))
+        })?;
+
+        let mut inner_engine = engine.inner_mut();
+        Self::from_parts_with_engine(Some(engine), &mut inner_engine, artifact, engine.target())
+    }
+
+    /// Archive a serialized artifact, validating its layout.
+    fn archive_checked(bytes: OwnedBuffer) -> Result<ArtifactBuildFromArchive, DeserializeError> {
+        ArtifactBuildFromArchive::try_new(bytes, |bytes| {
             let bytes =
                 Self::get_byte_slice(bytes, ArtifactBuild::MAGIC_HEADER.len(), bytes.len())?;
 
@@ -219,14 +362,7 @@ impl Artifact {
             let metadata_slice = Self::get_byte_slice(metadata_slice, 0, metadata_len)?;
 
             SerializableModule::archive_from_slice_checked(metadata_slice)
-        })?;
-
-        let mut inner_engine = engine.inner_mut();
-        Self::from_parts(
-            &mut inner_engine,
-            ArtifactBuildVariant::Archived(artifact),
-            engine.target(),
-        )
+        })
     }
 
     /// Deserialize a serialized artifact.
@@ -267,7 +403,8 @@ impl Artifact {
         })?;
 
         let mut inner_engine = engine.inner_mut();
-        Self::from_parts(
+        Self::from_parts_with_engine(
+            Some(engine),
             &mut inner_engine,
             ArtifactBuildVariant::Archived(artifact),
             engine.target(),
@@ -275,31 +412,55 @@ impl Artifact {
     }
 
     /// Construct a `ArtifactBuild` from component parts.
+    ///
+    /// The functions of lazily compiled modules which weren't compiled yet
+    /// can't be called, since there is no engine to compile them.
     pub fn from_parts(
         engine_inner: &mut EngineInner,
         artifact: ArtifactBuildVariant,
         target: &Target,
     ) -> Result<Self, DeserializeError> {
+        Self::from_parts_with_engine(None, engine_inner, artifact, target)
+    }
+
+    fn from_parts_with_engine(
+        engine: Option<&Engine>,
+        engine_inner: &mut EngineInner,
+        artifact: ArtifactBuildVariant,
+        target: &Target,
+    ) -> Result<Self, DeserializeError> {
+        let reproducible = engine.map_or(false, Engine::reproducible);
         if !target.is_native() {
             return Ok(Self {
                 id: Default::default(),
                 artifact,
+                reproducible,
                 allocated: None,
             });
         } else {
-            // check if cpu features are compatible before anything else
-            let cpu_features = artifact.cpu_features();
-            if !target.cpu_features().is_superset(cpu_features) {
-                return Err(DeserializeError::Incompatible(format!(
-                    "Some CPU Features needed for the artifact are missing: {:?}",
-                    cpu_features.difference(*target.cpu_features())
-                )));
-            }
+            // check if the target and cpu features are compatible before
+            // anything else
+            artifact.check_target(target)?;
+        }
+        let interpreted = artifact.is_interpreted();
+        let tier_up = match &artifact {
+            ArtifactBuildVariant::Plain(p) => p.tiers_up(),
+            ArtifactBuildVariant::Archived(a) => a.tiers_up(),
+        };
+        let profiled = match &artifact {
+            ArtifactBuildVariant::Plain(p) => p.is_profiled(),
+            ArtifactBuildVariant::Archived(a) => a.is_profiled(),
+        };
+        if interpreted && !cfg!(feature = "interpreter") {
+            return Err(DeserializeError::Incompatible(
+                "The artifact was compiled for the interpreter, which is not enabled".to_string(),
+            ));
         }
         let module_info = artifact.module_info();
+        #[cfg_attr(not(feature = "interpreter"), allow(unused_mut))]
         let (
             finished_functions,
-            finished_function_call_trampolines,
+            mut finished_function_call_trampolines,
             finished_dynamic_function_trampolines,
             custom_sections,
         ) = match &artifact {
@@ -380,8 +541,17 @@ impl Artifact {
             None => None,
         };
 
-        // Make all code compiled thus far executable.
-        engine_inner.publish_compiled_code();
+        if interpreted {
+            // The function bodies are bytecode run by the interpreter entry
+            // point, so nothing needs to be made executable.
+            #[cfg(feature = "interpreter")]
+            for trampoline in finished_function_call_trampolines.values_mut() {
+                *trampoline = wasmer_vm::interpreter::call_trampoline;
+            }
+        } else {
+            // Make all code compiled thus far executable.
+            engine_inner.publish_compiled_code();
+        }
 
         engine_inner.publish_eh_frame(eh_frame)?;
 
@@ -400,10 +570,18 @@ impl Artifact {
         let finished_dynamic_function_trampolines =
             finished_dynamic_function_trampolines.into_boxed_slice();
         let signatures = signatures.into_boxed_slice();
+        let function_tiers = tier_up.then(|| {
+            finished_functions
+                .values()
+                .map(|_| VMFunctionTier::default())
+                .collect()
+        });
+        let profile = profiled.then(|| Arc::new(VMProfile::new(finished_functions.len())));
 
         let mut artifact = Self {
             id: Default::default(),
             artifact,
+            reproducible,
             allocated: Some(AllocatedArtifact {
                 frame_info_registered: false,
                 frame_info_registration: None,
@@ -412,6 +590,9 @@ impl Artifact {
                 finished_dynamic_function_trampolines,
                 signatures,
                 finished_function_lengths,
+                function_tiers,
+                lazy_functions: None,
+                profile,
             }),
         };
 
@@ -422,6 +603,60 @@ impl Artifact {
             engine_inner.register_frame_info(frame_info);
         }
 
+        let lazy_binary = match &artifact.artifact {
+            ArtifactBuildVariant::Plain(p) => p.get_lazy_binary(),
+            ArtifactBuildVariant::Archived(a) => a.get_lazy_binary(),
+        };
+        if let (Some(binary), Some(tiers)) = (lazy_binary, artifact.function_tiers()) {
+            let compiled = match &artifact.artifact {
+                ArtifactBuildVariant::Plain(p) => p.get_lazy_functions_ref().to_vec(),
+                ArtifactBuildVariant::Archived(a) => a.deserialize_lazy_functions()?,
+            };
+            let stubs = artifact
+                .finished_functions()
+                .values()
+                .zip(
+                    artifact
+                        .allocated
+                        .as_ref()
+                        .unwrap()
+                        .finished_function_lengths
+                        .values(),
+                )
+                .map(|(ptr, length)| FunctionExtent {
+                    ptr: *ptr,
+                    length: *length,
+                })
+                .collect();
+            let (libcall_trampolines, libcall_trampoline_len) = match &artifact.artifact {
+                ArtifactBuildVariant::Plain(p) => {
+                    (p.get_libcall_trampolines(), p.get_libcall_trampoline_len())
+                }
+                ArtifactBuildVariant::Archived(a) => {
+                    (a.get_libcall_trampolines(), a.get_libcall_trampoline_len())
+                }
+            };
+            let compile_info = CompileModuleInfo {
+                features: artifact.features().clone(),
+                module: artifact.create_module_info(),
+                memory_styles: artifact.memory_styles().clone(),
+                table_styles: artifact.table_styles().clone(),
+            };
+            let lazy_functions = LazyFunctions::new(
+                engine,
+                engine_inner,
+                compile_info,
+                binary,
+                stubs,
+                custom_sections,
+                libcall_trampolines,
+                libcall_trampoline_len,
+                tiers.clone(),
+                &compiled,
+            )?;
+            artifact.allocated.as_mut().unwrap().lazy_functions = Some(Arc::new(lazy_functions));
+        }
+
         Ok(artifact)
     }
 
@@ -485,7 +720,64 @@ impl<'a> ArtifactCreate<'a> for Artifact {
     }
 
     fn serialize(&self) -> Result<Vec<u8>, SerializeError> {
-        self.artifact.serialize()
+        // The functions of lazily compiled modules compiled so far are
+        // serialized too, so that they don't need to be compiled again,
+        // unless the artifact must only depend on the compilation.
+        match self.lazy_functions().filter(|_| !self.reproducible) {
+            Some(lazy_functions) => match &self.artifact {
+                ArtifactBuildVariant::Plain(p) => {
+                    p.serialize_with_lazy_functions(&lazy_functions.compiled())
+                }
+                ArtifactBuildVariant::Archived(a) => {
+                    a.serialize_with_lazy_functions(&lazy_functions.compiled())
+                }
+            },
+            None => self.artifact.serialize(),
+        }
+    }
+}
+
+impl ArtifactBuildVariant {
+    /// Get the target triple the artifact was compiled for.
+    pub fn triple(&self) -> &str {
+        match self {
+            Self::Plain(artifact) => artifact.triple(),
+            Self::Archived(artifact) => artifact.triple(),
+        }
+    }
+
+    /// Whether the functions of the artifact are bytecode run by the
+    /// interpreter rather than native code.
+    pub fn is_interpreted(&self) -> bool {
+        match self {
+            Self::Plain(artifact) => artifact.is_interpreted(),
+            Self::Archived(artifact) => artifact.is_interpreted(),
+        }
+    }
+
+    /// Check that the artifact can run on `target`: it must be compiled for
+    /// the same architecture and operating system, and only use CPU features
+    /// that `target` has.
+    fn check_target(&self, target: &Target) -> Result<(), DeserializeError> {
+        let same_platform = self.triple().parse::<Triple>().map_or(false, |triple| {
+            triple.architecture == target.triple().architecture
+                && triple.operating_system == target.triple().operating_system
+        });
+        if !same_platform {
+            return Err(DeserializeError::Incompatible(format!(
+                "The artifact was compiled for `{}`, not `{}`",
+                self.triple(),
+                target.triple()
+            )));
+        }
+        let cpu_features = self.cpu_features();
+        if !target.cpu_features().is_superset(cpu_features) {
+            return Err(DeserializeError::Incompatible(format!(
+                "Some CPU Features needed for the artifact are missing: {:?}",
+                cpu_features.difference(*target.cpu_features())
+            )));
+        }
+        Ok(())
     }
 }
 
@@ -757,6 +1049,34 @@ impl Artifact {
             .signatures
     }
 
+    /// Returns the tier-up state of the functions, if they were compiled
+    /// for tier-up.
+    pub fn function_tiers(&self) -> Option<&Arc<[VMFunctionTier]>> {
+        self.allocated.as_ref()?.function_tiers.as_ref()
+    }
+
+    fn lazy_functions(&self) -> Option<&Arc<LazyFunctions>> {
+        self.allocated.as_ref()?.lazy_functions.as_ref()
+    }
+
+    /// Returns the counts recorded by all the instances so far, if the
+    /// functions were compiled with profile instrumentation.
+    pub fn profile(&self) -> Option<Profile> {
+        Some(self.allocated.as_ref()?.profile.as_ref()?.to_profile())
+    }
+
+    /// Whether the function at `index` was redirected to optimized code.
+    pub fn is_tiered_up(&self, index: LocalFunctionIndex) -> bool {
+        self.function_tiers()
+            .map_or(false, |tiers| tiers[index.index()].redirect().is_some())
+    }
+
+    /// Whether the functions of the artifact are bytecode run by the
+    /// interpreter rather than native code.
+    pub fn is_interpreted(&self) -> bool {
+        self.artifact.is_interpreted()
+    }
+
     /// Do preinstantiation logic that is executed before instantiating
     #[allow(clippy::result_large_err)]
     pub fn preinstantiate(&self) -> Result<(), InstantiationError> {
@@ -785,6 +1105,16 @@ impl Artifact {
             )));
         }
 
+        // The `func_ptr` of interpreted functions points to their bytecode,
+        // which native code would jump to when calling them through an
+        // import or a table, so a store only ever holds one kind of code.
+        if context
+            .interpreted()
+            .map_or(false, |interpreted| interpreted != self.is_interpreted())
+        {
+            return Err(InstantiationError::MixedCode);
+        }
+
         self.preinstantiate()?;
 
         let module = self.create_module_info();
@@ -826,11 +1156,21 @@ impl Artifact {
             .map_err(InstantiationError::Link)?
             .into_boxed_slice();
 
-        let handle = VMInstance::new(
+        // Functions already tiered up are called directly in the new instance.
+        let finished_functions = match self.function_tiers() {
+            Some(tiers) => self
+                .finished_functions()
+                .iter()
+                .map(|(index, body)| tiers[index.index()].redirect().unwrap_or(*body))
+                .collect::<PrimaryMap<_, _>>()
+                .into_boxed_slice(),
+            None => self.finished_functions().clone(),
+        };
+        let mut handle = VMInstance::new(
             allocator,
             module,
             context,
-            self.finished_functions().clone(),
+            finished_functions,
             self.finished_function_call_trampolines().clone(),
             finished_memories,
             finished_tables,
@@ -839,6 +1179,18 @@ impl Artifact {
             self.signatures().clone(),
         )
         .map_err(InstantiationError::Start)?;
+        handle.set_function_tiers(self.function_tiers().cloned());
+        handle.set_lazy_compiler(
+            self.lazy_functions()
+                .map(|lazy_functions| lazy_functions.clone() as Arc<dyn LazyCompiler>),
+        );
+        handle.set_profile(self.allocated.as_ref().and_then(|a| a.profile.clone()));
+        // Code compiled with exception handling enabled propagates the
+        // exceptions thrown by the host.
+        if self.features().exceptions {
+            context.enable_exceptions();
+        }
+        context.set_interpreted(self.is_interpreted());
         Ok(handle)
     }
 
@@ -994,6 +1346,28 @@ impl Artifact {
             CompileError::Codegen(format!("{}", err))
         }
 
+        if compiler.is_interpreter() {
+            return Err(CompileError::UnsupportedFeature(
+                "static objects can't be generated for interpreted modules".to_string(),
+            ));
+        }
+        if compiler.tiers_up() {
+            return Err(CompileError::UnsupportedFeature(
+                "static objects can't be generated with tier-up enabled".to_string(),
+            ));
+        }
+        if compiler.compiles_lazily() {
+            return Err(CompileError::UnsupportedFeature(
+                "static objects can't be generated with lazy compilation enabled".to_string(),
+            ));
+        }
+        if compiler.instruments_profile() {
+            return Err(CompileError::UnsupportedFeature(
+                "static objects can't be generated with profile instrumentation enabled"
+                    .to_string(),
+            ));
+        }
+
         let target_triple = target.triple();
         let (mut metadata, module_translation, function_body_inputs) =
             Self::metadata(compiler, data, metadata_prefix, target, tunables, features)
@@ -1174,6 +1548,8 @@ impl Artifact {
             compile_info: metadata.compile_info,
             data_initializers: metadata.data_initializers,
             cpu_features: metadata.cpu_features,
+            triple: engine.target().triple().to_string(),
+            compiler: String::new(),
         });
 
         let finished_function_lengths = finished_functions
@@ -1185,6 +1561,7 @@ impl Artifact {
         Ok(Self {
             id: Default::default(),
             artifact: ArtifactBuildVariant::Plain(artifact),
+            reproducible: engine.reproducible(),
             allocated: Some(AllocatedArtifact {
                 frame_info_registered: false,
                 frame_info_registration: None,
@@ -1195,6 +1572,9 @@ impl Artifact {
                     .into_boxed_slice(),
                 signatures: signatures.into_boxed_slice(),
                 finished_function_lengths,
+                function_tiers: None,
+                lazy_functions: None,
+                profile: None,
             }),
         })
     }
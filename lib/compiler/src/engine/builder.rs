@@ -1,5 +1,8 @@
 use super::Engine;
 use crate::CompilerConfig;
+#[cfg(feature = "compiler")]
+#[cfg(not(target_arch = "wasm32"))]
+use crate::TierUp;
 use wasmer_types::{Features, HashAlgorithm, Target};
 
 /// The Builder contents of `Engine`
@@ -12,6 +15,12 @@ pub struct EngineBuilder {
     features: Option<Features>,
     /// The hashing algorithm
     hash_algorithm: Option<HashAlgorithm>,
+    /// Whether the serialized artifacts are reproducible
+    reproducible: bool,
+    /// The recompilation of the hot functions
+    #[cfg(feature = "compiler")]
+    #[cfg(not(target_arch = "wasm32"))]
+    tier_up: Option<TierUp>,
 }
 
 impl EngineBuilder {
@@ -25,6 +34,10 @@ impl EngineBuilder {
             target: None,
             features: None,
             hash_algorithm: None,
+            reproducible: false,
+            #[cfg(feature = "compiler")]
+            #[cfg(not(target_arch = "wasm32"))]
+            tier_up: None,
         }
     }
 
@@ -35,6 +48,10 @@ impl EngineBuilder {
             target: None,
             features: None,
             hash_algorithm: None,
+            reproducible: false,
+            #[cfg(feature = "compiler")]
+            #[cfg(not(target_arch = "wasm32"))]
+            tier_up: None,
         }
     }
 
@@ -56,17 +73,43 @@ impl EngineBuilder {
         self
     }
 
+    /// Serialize the compiled modules reproducibly
+    ///
+    /// See [`Engine::set_reproducible`] for the guarantees this gives.
+    pub fn set_reproducible(mut self, reproducible: bool) -> Self {
+        self.reproducible = reproducible;
+        self
+    }
+
+    /// Recompile the hot functions with an optimizing compiler
+    ///
+    /// The compiler of the engine is set up to count the calls of the
+    /// functions, and to jump to their optimized code once there is some.
+    #[cfg(feature = "compiler")]
+    #[cfg(not(target_arch = "wasm32"))]
+    pub fn set_tier_up(mut self, tier_up: Option<TierUp>) -> Self {
+        self.tier_up = tier_up;
+        self
+    }
+
     /// Build the `Engine` for this configuration
     #[cfg(feature = "compiler")]
     pub fn engine(self) -> Engine {
         let target = self.target.unwrap_or_default();
-        if let Some(compiler_config) = self.compiler_config {
+        if let Some(mut compiler_config) = self.compiler_config {
             let features = self
                 .features
                 .unwrap_or_else(|| compiler_config.default_features_for_target(&target));
+            #[cfg(not(target_arch = "wasm32"))]
+            if self.tier_up.is_some() {
+                compiler_config.enable_tier_up();
+            }
             let mut engine = Engine::new(compiler_config, target, features);
 
             engine.set_hash_algorithm(self.hash_algorithm);
+            engine.set_reproducible(self.reproducible);
+            #[cfg(not(target_arch = "wasm32"))]
+            engine.set_tier_up(self.tier_up);
 
             engine
         } else {
@@ -105,6 +105,12 @@ impl CodeMemory {
         let mut bytes = 0;
         let mut buf = self.mmap.as_mut_slice();
         for func in functions {
+            if func.body().is_empty() {
+                // Like empty data sections below, there may be no page to
+                // point into.
+                function_result.push(Self::view_as_mut_vmfunc_slice(&mut [][..]));
+                continue;
+            }
             let len = round_up(
                 Self::function_allocation_size(*func),
                 ARCH_FUNCTION_ALIGNMENT,
@@ -138,6 +144,12 @@ impl CodeMemory {
 
             for section in data_sections {
                 let section = section.bytes();
+                if section.is_empty() {
+                    // Nothing to copy, and `buf` doesn't point to any page
+                    // when everything else is empty too.
+                    data_section_result.push(&mut [][..]);
+                    continue;
+                }
                 assert_eq!(buf.as_mut_ptr() as usize % DATA_SECTION_ALIGNMENT, 0);
                 let len = round_up(section.len(), DATA_SECTION_ALIGNMENT);
                 let (s, next_buf) = buf.split_at_mut(len);
@@ -45,6 +45,11 @@ pub enum InstantiationError {
     #[error("module compiled with CPU feature that is missing from host")]
     CpuFeature(String),
 
+    /// The module is interpreted and the store already holds native
+    /// instances, or the other way around.
+    #[error("cannot mix interpreted and native modules in the same store")]
+    MixedCode,
+
     /// A runtime error occured while invoking the start function
     #[cfg(not(target_arch = "wasm32"))]
     #[error(transparent)]
@@ -10,6 +10,9 @@ use crate::CodeMemory;
 #[cfg(not(target_arch = "wasm32"))]
 use crate::GlobalFrameInfoRegistration;
 #[cfg(feature = "compiler")]
+#[cfg(not(target_arch = "wasm32"))]
+use crate::TierUp;
+#[cfg(feature = "compiler")]
 use crate::{Compiler, CompilerConfig};
 #[cfg(not(target_arch = "wasm32"))]
 use crate::{FunctionExtent, Tunables};
@@ -30,8 +33,8 @@ use wasmer_types::{CompileError, Features, ModuleInfo, Target};
 use wasmer_types::{CustomSectionLike, CustomSectionProtection, SectionIndex};
 #[cfg(not(target_arch = "wasm32"))]
 use wasmer_vm::{
-    FunctionBodyPtr, SectionBodyPtr, SignatureRegistry, VMFunctionBody, VMSharedSignatureIndex,
-    VMTrampoline,
+    FunctionBodyPtr, MemoryWatch, SectionBodyPtr, SignatureRegistry, VMFunctionBody,
+    VMSharedSignatureIndex, VMTrampoline,
 };
 
 /// A WebAssembly `Universal` Engine.
@@ -45,6 +48,12 @@ pub struct Engine {
     tunables: Arc<dyn Tunables + Send + Sync>,
     name: String,
     hash_algorithm: Option<HashAlgorithm>,
+    reproducible: bool,
+    #[cfg(not(target_arch = "wasm32"))]
+    memory_watch: Option<Arc<MemoryWatch>>,
+    #[cfg(feature = "compiler")]
+    #[cfg(not(target_arch = "wasm32"))]
+    tier_up: Option<Arc<TierUp>>,
 }
 
 impl Engine {
@@ -58,7 +67,7 @@ impl Engine {
         #[cfg(not(target_arch = "wasm32"))]
         let tunables = BaseTunables::for_target(&target);
         let compiler = compiler_config.compiler();
-        let name = format!("engine-{}", compiler.name());
+        let name = format!("engine-{}", compiler.deterministic_id());
         Self {
             inner: Arc::new(Mutex::new(EngineInner {
                 compiler: Some(compiler),
@@ -74,6 +83,11 @@ impl Engine {
             tunables: Arc::new(tunables),
             name,
             hash_algorithm: None,
+            reproducible: false,
+            #[cfg(not(target_arch = "wasm32"))]
+            memory_watch: None,
+            #[cfg(not(target_arch = "wasm32"))]
+            tier_up: None,
         }
     }
 
@@ -101,12 +115,59 @@ impl Engine {
         self.hash_algorithm
     }
 
+    /// Sets whether the modules compiled by this engine serialize
+    /// reproducibly
+    ///
+    /// Artifacts are always serialized in a canonical form: compiling the
+    /// same module with the same compiler configuration, features and
+    /// target gives the same bytes on any host, whatever the number of
+    /// threads compiling it. The serialized artifacts of lazily compiled
+    /// modules also hold the functions compiled while running them though,
+    /// which depend on what was called. A reproducible engine leaves them
+    /// out, so that serializing a module only depends on its compilation.
+    pub fn set_reproducible(&mut self, reproducible: bool) {
+        self.reproducible = reproducible;
+    }
+
+    /// Returns whether the modules compiled by this engine serialize
+    /// reproducibly
+    pub fn reproducible(&self) -> bool {
+        self.reproducible
+    }
+
+    /// Sets where instances report the memory accesses of code compiled
+    /// with the memory watch enabled
+    #[cfg(not(target_arch = "wasm32"))]
+    pub fn set_memory_watch(&mut self, memory_watch: Option<Arc<MemoryWatch>>) {
+        self.memory_watch = memory_watch;
+    }
+
+    /// Returns where instances report their memory accesses
+    #[cfg(not(target_arch = "wasm32"))]
+    pub fn memory_watch(&self) -> Option<&Arc<MemoryWatch>> {
+        self.memory_watch.as_ref()
+    }
+
+    /// Sets the `TierUp` recompiling the hot functions of the modules
+    /// compiled from now on
+    #[cfg(feature = "compiler")]
+    #[cfg(not(target_arch = "wasm32"))]
+    pub fn set_tier_up(&mut self, tier_up: Option<TierUp>) {
+        self.tier_up = tier_up.map(Arc::new);
+    }
+
+    /// Returns the `TierUp` recompiling the hot functions of the modules
+    #[cfg(feature = "compiler")]
+    #[cfg(not(target_arch = "wasm32"))]
+    pub fn tier_up(&self) -> Option<&TierUp> {
+        self.tier_up.as_deref()
+    }
+
     /// Returns the deterministic id of this engine
     pub fn deterministic_id(&self) -> &str {
-        // TODO: add a `deterministic_id` to the Compiler, so two
-        // compilers can actually serialize into a different deterministic_id
-        // if their configuration is different (eg. LLVM with optimizations vs LLVM
-        // without optimizations)
+        // TODO: compilers only account for part of their configuration in
+        // their deterministic id so far (eg. LLVM with optimizations vs LLVM
+        // without optimizations serialize into the same one)
         self.name.as_str()
     }
 
@@ -144,6 +205,12 @@ impl Engine {
             tunables: Arc::new(tunables),
             name: "engine-headless".to_string(),
             hash_algorithm: None,
+            reproducible: false,
+            #[cfg(not(target_arch = "wasm32"))]
+            memory_watch: None,
+            #[cfg(feature = "compiler")]
+            #[cfg(not(target_arch = "wasm32"))]
+            tier_up: None,
         }
     }
 
@@ -186,12 +253,26 @@ impl Engine {
     #[cfg(feature = "compiler")]
     #[cfg(not(target_arch = "wasm32"))]
     pub fn compile(&self, binary: &[u8]) -> Result<Arc<Artifact>, CompileError> {
-        Ok(Arc::new(Artifact::new(
+        let artifact = Arc::new(Artifact::new(
             self,
             binary,
             self.tunables.as_ref(),
             self.hash_algorithm,
-        )?))
+        )?);
+        if let Some(tier_up) = &self.tier_up {
+            tier_up.watch(self, &artifact, binary);
+        }
+        Ok(artifact)
+    }
+
+    /// Compile a WebAssembly binary for `target` and serialize the
+    /// resulting artifact, without loading it.
+    ///
+    /// See [`Artifact::precompile`].
+    #[cfg(feature = "compiler")]
+    #[cfg(not(target_arch = "wasm32"))]
+    pub fn precompile(&self, binary: &[u8], target: &Target) -> Result<Vec<u8>, CompileError> {
+        Artifact::precompile(self, binary, target, self.hash_algorithm)
     }
 
     /// Compile a WebAssembly binary
@@ -235,6 +316,35 @@ impl Engine {
         Ok(Arc::new(Artifact::deserialize(self, bytes)?))
     }
 
+    /// Deserializes the WebAssembly module among `candidates` that suits the
+    /// target of this engine best.
+    ///
+    /// # Safety
+    ///
+    /// See [`Artifact::deserialize_best`].
+    #[cfg(not(target_arch = "wasm32"))]
+    pub unsafe fn deserialize_best(
+        &self,
+        candidates: impl IntoIterator<Item = OwnedBuffer>,
+    ) -> Result<Arc<Artifact>, DeserializeError> {
+        Ok(Arc::new(Artifact::deserialize_best(self, candidates)?))
+    }
+
+    /// Deserializes a WebAssembly module which was serialized and signed
+    /// with an [`ArtifactSigner`](crate::ArtifactSigner), checking its
+    /// signature first.
+    #[cfg(feature = "signing")]
+    #[cfg(not(target_arch = "wasm32"))]
+    pub fn deserialize_verified(
+        &self,
+        bytes: OwnedBuffer,
+        verifier: &crate::ArtifactVerifier,
+    ) -> Result<Arc<Artifact>, DeserializeError> {
+        Ok(Arc::new(Artifact::deserialize_verified(
+            self, bytes, verifier,
+        )?))
+    }
+
     /// Deserializes a WebAssembly module from a path.
     ///
     /// # Safety
@@ -0,0 +1,259 @@
+//! Lazy compilation: the functions of lazily compiled modules are compiled
+//! on their first call, and kept in their artifact so that they are
+//! serialized along with it.
+
+use crate::engine::link::link_module;
+use crate::{register_function_frame_info, Engine, EngineInner, FunctionExtent};
+#[cfg(feature = "compiler")]
+use crate::{FunctionBodyData, ModuleEnvironment, ModuleTranslationState};
+use std::iter;
+#[cfg(feature = "compiler")]
+use std::ops::Range;
+use std::slice;
+use std::sync::{Arc, Mutex};
+use wasmer_types::entity::{EntityRef, PrimaryMap};
+use wasmer_types::{
+    CompileError, CompileModuleInfo, FunctionBody, LocalFunctionIndex, Relocation, SectionIndex,
+    SerializableLazyFunction,
+};
+use wasmer_vm::{FunctionBodyPtr, LazyCompiler, SectionBodyPtr, VMFunctionTier};
+
+/// Compiles the functions of a lazily compiled artifact on their first call.
+pub(crate) struct LazyFunctions {
+    // The engine compiling the functions, if the artifact was created by one.
+    engine: Option<Engine>,
+    compile_info: CompileModuleInfo,
+    binary: Vec<u8>,
+    // The custom sections of the artifact, which the functions refer to.
+    sections: PrimaryMap<SectionIndex, SectionBodyPtr>,
+    libcall_trampolines: SectionIndex,
+    libcall_trampoline_len: usize,
+    tiers: Arc<[VMFunctionTier]>,
+    state: Mutex<LazyState>,
+}
+
+/// The ranges of the function bodies in the binary along with their offsets,
+/// and the translation state of the module.
+#[cfg(feature = "compiler")]
+type Translation = (
+    PrimaryMap<LocalFunctionIndex, (Range<usize>, usize)>,
+    ModuleTranslationState,
+);
+
+struct LazyState {
+    // The stubs of the functions, which the compiled functions call.
+    stubs: PrimaryMap<LocalFunctionIndex, FunctionExtent>,
+    // The translation of the binary, once translated.
+    #[cfg(feature = "compiler")]
+    translation: Option<Translation>,
+    // The functions compiled since the artifact was created.
+    compiled: Vec<SerializableLazyFunction>,
+}
+
+/// # Safety
+/// The section pointers point to the custom sections of the artifact,
+/// which live as long as its engine and are only read when linking.
+unsafe impl Send for LazyFunctions {}
+/// # Safety
+/// The section pointers point to the custom sections of the artifact,
+/// which live as long as its engine and are only read when linking.
+unsafe impl Sync for LazyFunctions {}
+
+impl LazyFunctions {
+    /// Creates the lazy compilation state of an artifact, installing the
+    /// functions compiled before it was serialized.
+    #[allow(clippy::too_many_arguments)]
+    pub(crate) fn new(
+        engine: Option<&Engine>,
+        engine_inner: &mut EngineInner,
+        compile_info: CompileModuleInfo,
+        binary: &[u8],
+        stubs: PrimaryMap<LocalFunctionIndex, FunctionExtent>,
+        sections: PrimaryMap<SectionIndex, SectionBodyPtr>,
+        libcall_trampolines: SectionIndex,
+        libcall_trampoline_len: usize,
+        tiers: Arc<[VMFunctionTier]>,
+        compiled: &[SerializableLazyFunction],
+    ) -> Result<Self, CompileError> {
+        let mut lazy = Self {
+            engine: engine.cloned(),
+            compile_info,
+            binary: binary.to_vec(),
+            sections,
+            libcall_trampolines,
+            libcall_trampoline_len,
+            tiers,
+            state: Mutex::new(LazyState {
+                stubs,
+                #[cfg(feature = "compiler")]
+                translation: None,
+                compiled: Vec::new(),
+            }),
+        };
+        let stubs = &mut lazy.state.get_mut().unwrap().stubs;
+        for function in compiled {
+            let body = install(
+                engine_inner,
+                &lazy.compile_info,
+                stubs,
+                &lazy.sections,
+                lazy.libcall_trampolines,
+                lazy.libcall_trampoline_len,
+                function,
+            )?;
+            lazy.tiers[function.index.index()].set_redirect(body);
+        }
+        Ok(lazy)
+    }
+
+    /// The functions compiled since the artifact was created.
+    pub(crate) fn compiled(&self) -> Vec<SerializableLazyFunction> {
+        self.state.lock().unwrap().compiled.clone()
+    }
+}
+
+impl LazyCompiler for LazyFunctions {
+    #[cfg(feature = "compiler")]
+    fn compile_function(&self, index: LocalFunctionIndex) -> Result<FunctionBodyPtr, CompileError> {
+        let mut state = self.state.lock().unwrap();
+        let state = &mut *state;
+        // Another thread may have compiled the function meanwhile.
+        if let Some(body) = self.tiers[index.index()].redirect() {
+            return Ok(body);
+        }
+        let engine = self.engine.as_ref().ok_or_else(|| {
+            CompileError::Codegen(
+                "the function can't be compiled lazily without a compiler".to_string(),
+            )
+        })?;
+
+        if state.translation.is_none() {
+            let environ = ModuleEnvironment::new()
+                .translate(&self.binary)
+                .map_err(CompileError::Wasm)?;
+            let bodies = environ
+                .function_body_inputs
+                .values()
+                .map(|input| {
+                    let start = input.data.as_ptr() as usize - self.binary.as_ptr() as usize;
+                    (start..start + input.data.len(), input.module_offset)
+                })
+                .collect();
+            // SAFETY: `translate` always sets the translation state.
+            state.translation = Some((bodies, environ.module_translation_state.unwrap()));
+        }
+        let (bodies, module_translation) = state.translation.as_ref().unwrap();
+        let (range, module_offset) = bodies[index].clone();
+        let input = FunctionBodyData {
+            data: &self.binary[range],
+            module_offset,
+        };
+
+        let mut engine_inner = engine.inner_mut();
+        let (function, eh_frame) = engine_inner.compiler()?.compile_function(
+            engine.target(),
+            &self.compile_info,
+            module_translation,
+            index,
+            &input,
+        )?;
+        let function = SerializableLazyFunction {
+            index,
+            body: function.body,
+            relocations: function.relocations,
+            frame_info: function.frame_info,
+            eh_frame,
+        };
+        let body = install(
+            &mut engine_inner,
+            &self.compile_info,
+            &mut state.stubs,
+            &self.sections,
+            self.libcall_trampolines,
+            self.libcall_trampoline_len,
+            &function,
+        )?;
+        drop(engine_inner);
+
+        self.tiers[index.index()].set_redirect(body);
+        state.compiled.push(function);
+        Ok(body)
+    }
+
+    #[cfg(not(feature = "compiler"))]
+    fn compile_function(
+        &self,
+        _index: LocalFunctionIndex,
+    ) -> Result<FunctionBodyPtr, CompileError> {
+        Err(CompileError::Codegen(
+            "the function can't be compiled lazily without a compiler".to_string(),
+        ))
+    }
+}
+
+/// Allocates a lazily compiled function, links it against the stubs of the
+/// other functions and the sections of its artifact, and makes it executable.
+fn install(
+    engine_inner: &mut EngineInner,
+    compile_info: &CompileModuleInfo,
+    stubs: &mut PrimaryMap<LocalFunctionIndex, FunctionExtent>,
+    sections: &PrimaryMap<SectionIndex, SectionBodyPtr>,
+    libcall_trampolines: SectionIndex,
+    libcall_trampoline_len: usize,
+    function: &SerializableLazyFunction,
+) -> Result<FunctionBodyPtr, CompileError> {
+    let module = &compile_info.module;
+    let (allocated_functions, _, _, allocated_sections) = engine_inner.allocate(
+        module,
+        iter::once(&function.body),
+        iter::empty::<&FunctionBody>(),
+        iter::empty::<&FunctionBody>(),
+        function.eh_frame.iter(),
+    )?;
+    let extent = &allocated_functions[LocalFunctionIndex::new(0)];
+    let (body, length) = (extent.ptr, extent.length);
+
+    // The function calls itself directly, and the other functions through
+    // their stubs, which jump to them once they are compiled.
+    let index = function.index;
+    let stub = std::mem::replace(&mut stubs[index], FunctionExtent { ptr: body, length });
+    link_module(
+        module,
+        stubs,
+        iter::once((index, function.relocations.iter())),
+        sections,
+        iter::empty::<(SectionIndex, slice::Iter<Relocation>)>(),
+        libcall_trampolines,
+        libcall_trampoline_len,
+    );
+    if let Some(eh_frame) = &function.eh_frame {
+        link_module(
+            module,
+            stubs,
+            iter::empty::<(LocalFunctionIndex, slice::Iter<Relocation>)>(),
+            &allocated_sections,
+            iter::once((SectionIndex::new(0), eh_frame.relocations.iter())),
+            libcall_trampolines,
+            libcall_trampoline_len,
+        );
+    }
+    stubs[index] = stub;
+
+    let eh_frame = function.eh_frame.as_ref().map(|eh_frame| unsafe {
+        slice::from_raw_parts(
+            *allocated_sections[SectionIndex::new(0)],
+            eh_frame.bytes.len(),
+        )
+    });
+    engine_inner.publish_compiled_code();
+    engine_inner.publish_eh_frame(eh_frame)?;
+    if let Some(frame_info) = register_function_frame_info(
+        module.clone(),
+        index,
+        &FunctionExtent { ptr: body, length },
+        function.frame_info.clone(),
+    ) {
+        engine_inner.register_frame_info(frame_info);
+    }
+    Ok(body)
+}
@@ -20,7 +20,16 @@ mod code_memory;
 mod inner;
 #[cfg(feature = "translator")]
 #[cfg(not(target_arch = "wasm32"))]
+mod lazy;
+#[cfg(feature = "translator")]
+#[cfg(not(target_arch = "wasm32"))]
 mod link;
+#[cfg(feature = "signing")]
+#[cfg(not(target_arch = "wasm32"))]
+mod signing;
+#[cfg(feature = "compiler")]
+#[cfg(not(target_arch = "wasm32"))]
+mod tier_up;
 #[cfg(feature = "translator")]
 #[cfg(not(target_arch = "wasm32"))]
 mod unwind;
@@ -46,3 +55,9 @@ pub use self::inner::{Engine, EngineInner};
 #[cfg(feature = "translator")]
 #[cfg(not(target_arch = "wasm32"))]
 pub use self::link::link_module;
+#[cfg(feature = "signing")]
+#[cfg(not(target_arch = "wasm32"))]
+pub use self::signing::{ArtifactSigner, ArtifactVerifier, InvalidKeyError};
+#[cfg(feature = "compiler")]
+#[cfg(not(target_arch = "wasm32"))]
+pub use self::tier_up::TierUp;
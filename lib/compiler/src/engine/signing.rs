@@ -0,0 +1,256 @@
+//! Signing of serialized artifacts.
+//!
+//! Loading an artifact runs the code it contains, so artifacts shared
+//! through a cache or a registry must only be loaded when they come from a
+//! trusted party. A signed artifact is the serialized artifact followed by a
+//! trailer holding the fingerprint of the engine that compiled it and an
+//! ed25519 signature of both:
+//!
+//! ```text
+//! artifact | fingerprint | fingerprint length (u32 LE) | signature (64 bytes) | magic
+//! ```
+//!
+//! The artifact stays at the start of the buffer, so that it keeps its
+//! alignment once the trailer is stripped.
+
+use crate::Engine;
+use ring::digest::{Context, SHA256};
+use ring::rand::SystemRandom;
+use ring::signature::{Ed25519KeyPair, KeyPair, UnparsedPublicKey, ED25519};
+use shared_buffer::OwnedBuffer;
+use thiserror::Error;
+use wasmer_types::DeserializeError;
+
+/// The last bytes of a signed artifact.
+const MAGIC: &[u8; 16] = b"\0wasmer-signed\0\0";
+
+/// The length of an ed25519 signature.
+const SIGNATURE_LEN: usize = 64;
+
+/// The length of an ed25519 public key.
+const PUBLIC_KEY_LEN: usize = 32;
+
+/// The error of a key that isn't a valid ed25519 key.
+#[derive(Error, Debug)]
+#[error("invalid ed25519 key: {0}")]
+pub struct InvalidKeyError(String);
+
+/// Signs serialized artifacts, so that they can be loaded with
+/// [`Artifact::deserialize_verified`](crate::Artifact::deserialize_verified)
+/// by the holders of the public key.
+pub struct ArtifactSigner {
+    key_pair: Ed25519KeyPair,
+}
+
+impl ArtifactSigner {
+    /// Generates a new private key, as a PKCS#8 document.
+    pub fn generate_key() -> Result<Vec<u8>, InvalidKeyError> {
+        let document = Ed25519KeyPair::generate_pkcs8(&SystemRandom::new())
+            .map_err(|_| InvalidKeyError("the key could not be generated".to_string()))?;
+        Ok(document.as_ref().to_vec())
+    }
+
+    /// Creates a signer from a private key given as a PKCS#8 document.
+    pub fn from_pkcs8(key: &[u8]) -> Result<Self, InvalidKeyError> {
+        let key_pair = Ed25519KeyPair::from_pkcs8_maybe_unchecked(key)
+            .map_err(|e| InvalidKeyError(e.to_string()))?;
+        Ok(Self { key_pair })
+    }
+
+    /// The public key verifying the signatures of this signer.
+    pub fn public_key(&self) -> &[u8] {
+        self.key_pair.public_key().as_ref()
+    }
+
+    /// Signs the serialized `artifact` compiled by `engine`.
+    pub fn sign(&self, engine: &Engine, artifact: &[u8]) -> Vec<u8> {
+        let fingerprint = Fingerprint::of(engine).to_string();
+        let signature = self
+            .key_pair
+            .sign(digest(artifact, fingerprint.as_bytes()).as_ref());
+
+        let mut signed = Vec::with_capacity(
+            artifact.len() + fingerprint.len() + 4 + SIGNATURE_LEN + MAGIC.len(),
+        );
+        signed.extend_from_slice(artifact);
+        signed.extend_from_slice(fingerprint.as_bytes());
+        signed.extend_from_slice(&(fingerprint.len() as u32).to_le_bytes());
+        signed.extend_from_slice(signature.as_ref());
+        signed.extend_from_slice(MAGIC);
+        signed
+    }
+}
+
+/// Verifies the signatures of artifacts signed by an [`ArtifactSigner`].
+#[derive(Debug, Clone)]
+pub struct ArtifactVerifier {
+    public_key: UnparsedPublicKey<Vec<u8>>,
+}
+
+impl ArtifactVerifier {
+    /// Creates a verifier for the signatures of the signer with the given
+    /// public key.
+    pub fn new(public_key: &[u8]) -> Result<Self, InvalidKeyError> {
+        if public_key.len() != PUBLIC_KEY_LEN {
+            return Err(InvalidKeyError(format!(
+                "expected a public key of {} bytes, got {}",
+                PUBLIC_KEY_LEN,
+                public_key.len()
+            )));
+        }
+        Ok(Self {
+            public_key: UnparsedPublicKey::new(&ED25519, public_key.to_vec()),
+        })
+    }
+
+    /// Whether `bytes` look like a signed artifact.
+    pub fn is_signed(bytes: &[u8]) -> bool {
+        bytes.ends_with(MAGIC)
+    }
+
+    /// Checks that the signed artifact was signed by the signer of this
+    /// verifier for an engine compatible with `engine`, and returns the
+    /// artifact without its signature.
+    pub fn verify(
+        &self,
+        engine: &Engine,
+        signed: OwnedBuffer,
+    ) -> Result<OwnedBuffer, DeserializeError> {
+        let bytes = signed.as_slice();
+        if !Self::is_signed(bytes) {
+            return Err(DeserializeError::Signature(
+                "the artifact isn't signed".to_string(),
+            ));
+        }
+        let truncated = || DeserializeError::Signature("the signature is truncated".to_string());
+        let signature_start = bytes
+            .len()
+            .checked_sub(MAGIC.len() + SIGNATURE_LEN)
+            .ok_or_else(truncated)?;
+        let signature = &bytes[signature_start..signature_start + SIGNATURE_LEN];
+        let fingerprint_end = signature_start.checked_sub(4).ok_or_else(truncated)?;
+        let fingerprint_len =
+            u32::from_le_bytes(bytes[fingerprint_end..signature_start].try_into().unwrap())
+                as usize;
+        let artifact_end = fingerprint_end
+            .checked_sub(fingerprint_len)
+            .ok_or_else(truncated)?;
+        let fingerprint = &bytes[artifact_end..fingerprint_end];
+        let artifact = &bytes[..artifact_end];
+
+        self.public_key
+            .verify(digest(artifact, fingerprint).as_ref(), signature)
+            .map_err(|_| {
+                DeserializeError::Signature(
+                    "the artifact wasn't signed with the trusted key".to_string(),
+                )
+            })?;
+
+        let fingerprint = std::str::from_utf8(fingerprint)
+            .ok()
+            .and_then(Fingerprint::parse)
+            .ok_or_else(|| {
+                DeserializeError::Signature("the engine fingerprint is invalid".to_string())
+            })?;
+        fingerprint.check_compatible(&Fingerprint::of(engine))?;
+        Ok(signed.slice(..artifact_end))
+    }
+}
+
+/// The digest of an artifact and of the fingerprint of its engine, which
+/// is what gets signed.
+fn digest(artifact: &[u8], fingerprint: &[u8]) -> ring::digest::Digest {
+    let mut context = Context::new(&SHA256);
+    context.update(&(fingerprint.len() as u64).to_le_bytes());
+    context.update(fingerprint);
+    context.update(artifact);
+    context.finish()
+}
+
+/// What an artifact depends on in the engine that compiled it.
+#[derive(Debug, PartialEq, Eq)]
+struct Fingerprint {
+    /// The deterministic id of the engine, `None` for headless engines.
+    engine: Option<String>,
+    /// The target triple. The CPU features used by the artifact are part
+    /// of the artifact itself.
+    triple: String,
+    /// The enabled WebAssembly proposals, `None` for headless engines.
+    features: Option<String>,
+}
+
+impl Fingerprint {
+    fn of(engine: &Engine) -> Self {
+        let compiles = engine.inner().compiler().is_ok();
+        Self {
+            engine: compiles.then(|| engine.deterministic_id().to_string()),
+            triple: engine.target().triple().to_string(),
+            features: compiles.then(|| {
+                engine
+                    .inner()
+                    .features()
+                    .enabled_names()
+                    .collect::<Vec<_>>()
+                    .join(",")
+            }),
+        }
+    }
+
+    fn parse(s: &str) -> Option<Self> {
+        let mut fingerprint = Self {
+            engine: None,
+            triple: String::new(),
+            features: None,
+        };
+        for line in s.lines() {
+            let (key, value) = line.split_once('=')?;
+            match key {
+                "engine" => fingerprint.engine = Some(value.to_string()),
+                "triple" => fingerprint.triple = value.to_string(),
+                "features" => fingerprint.features = Some(value.to_string()),
+                _ => return None,
+            }
+        }
+        Some(fingerprint)
+    }
+
+    /// Checks that an artifact compiled by the engine of this fingerprint
+    /// can be loaded by the engine of `loader`. Headless engines don't know
+    /// which compiler and proposals their artifacts are compiled with, so
+    /// only their target is checked.
+    fn check_compatible(&self, loader: &Self) -> Result<(), DeserializeError> {
+        let mismatch = |what: &str, signed: &str, loader: &str| {
+            Err(DeserializeError::Signature(format!(
+                "the artifact was signed for the {} `{}`, not `{}`",
+                what, signed, loader
+            )))
+        };
+        if self.triple != loader.triple {
+            return mismatch("target", &self.triple, &loader.triple);
+        }
+        if let (Some(signed), Some(loader)) = (&self.engine, &loader.engine) {
+            if signed != loader {
+                return mismatch("engine", signed, loader);
+            }
+        }
+        if let (Some(signed), Some(loader)) = (&self.features, &loader.features) {
+            if signed != loader {
+                return mismatch("features", signed, loader);
+            }
+        }
+        Ok(())
+    }
+}
+
+impl std::fmt::Display for Fingerprint {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        if let Some(engine) = &self.engine {
+            writeln!(f, "engine={}", engine)?;
+        }
+        writeln!(f, "triple={}", self.triple)?;
+        if let Some(features) = &self.features {
+            writeln!(f, "features={}", features)?;
+        }
+        Ok(())
+    }
+}
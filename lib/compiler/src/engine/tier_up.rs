@@ -0,0 +1,338 @@
+//! Tier-up of the modules compiled by a fast compiler: their hot functions
+//! are recompiled in the background with an optimizing compiler.
+
+use crate::engine::link::link_module;
+use crate::{
+    libcall_trampoline_len, make_libcall_trampolines, register_frame_info, Artifact,
+    ArtifactCreate, Compiler, CompilerConfig, Engine, FrameInfosVariant, FunctionBodyData,
+    FunctionExtent, ModuleEnvironment,
+};
+use std::iter;
+use std::slice;
+use std::sync::{Arc, Mutex, Weak};
+use std::thread;
+use std::time::Duration;
+use wasmer_types::entity::{EntityRef, PrimaryMap};
+use wasmer_types::{
+    CompileError, CompileModuleInfo, FunctionBody, FunctionIndex, LocalFunctionIndex, ModuleInfo,
+    Relocation, SectionIndex, SignatureIndex, Type,
+};
+use wasmer_vm::VMFunctionTier;
+use wasmparser::{BinaryReader, Operator, ValType};
+
+/// The body compiled in place of the functions that aren't hot: no locals,
+/// `unreachable`, `end`. It is valid for any signature.
+const STUB_BODY: &[u8] = &[0x00, 0x00, 0x0b];
+
+/// Recompiles the hot functions of the modules compiled for tier-up with
+/// an optimizing compiler.
+///
+/// Every module compiled by an `Engine` with a `TierUp` is watched by a
+/// background thread, which checks the calls of its functions at a regular
+/// interval and recompiles the functions called at least `threshold` times
+/// since the module was compiled. The compiled functions then jump to the
+/// optimized code on entry, and new instances call it directly.
+///
+/// The compiler of the `Engine` must support tier-up (see
+/// [`CompilerConfig::enable_tier_up`]). Modules compiled with middlewares
+/// aren't tiered up, since the optimized code wouldn't run them. Neither
+/// are the functions passing `v128` values to or from other functions,
+/// since the compilers don't agree on how to pass them.
+pub struct TierUp {
+    compiler: Mutex<Box<dyn Compiler>>,
+    threshold: u64,
+    interval: Duration,
+}
+
+impl TierUp {
+    /// Creates a `TierUp` recompiling with the compiler of `compiler_config`
+    /// the functions called at least `threshold` times.
+    pub fn new<T>(compiler_config: T, threshold: u64) -> Self
+    where
+        T: Into<Box<dyn CompilerConfig>>,
+    {
+        Self {
+            compiler: Mutex::new(compiler_config.into().compiler()),
+            threshold,
+            interval: Duration::from_millis(10),
+        }
+    }
+
+    /// Sets how often the calls of the functions are checked, 10ms by default.
+    pub fn set_interval(mut self, interval: Duration) -> Self {
+        self.interval = interval;
+        self
+    }
+
+    /// The number of calls after which a function is recompiled.
+    pub fn threshold(&self) -> u64 {
+        self.threshold
+    }
+
+    /// Watches the functions of `artifact`, compiled from `binary`, until
+    /// the artifact is dropped.
+    pub(crate) fn watch(
+        self: &Arc<Self>,
+        engine: &Engine,
+        artifact: &Arc<Artifact>,
+        binary: &[u8],
+    ) {
+        let tiers = match artifact.function_tiers() {
+            Some(tiers) => tiers.clone(),
+            None => return,
+        };
+        let has_middlewares = engine
+            .inner()
+            .compiler()
+            .map_or(true, |compiler| !compiler.get_middlewares().is_empty());
+        if has_middlewares {
+            return;
+        }
+
+        let tier_up = self.clone();
+        let engine = engine.clone();
+        let artifact = Arc::downgrade(artifact);
+        let binary = binary.to_vec();
+        // Without a thread, the module simply keeps running as compiled.
+        let _ = thread::Builder::new()
+            .name("wasmer-tier-up".to_string())
+            .spawn(move || tier_up.run(&engine, &artifact, &binary, &tiers));
+    }
+
+    fn run(
+        &self,
+        engine: &Engine,
+        artifact: &Weak<Artifact>,
+        binary: &[u8],
+        tiers: &[VMFunctionTier],
+    ) {
+        let mut translation = None;
+        let mut recompiled = vec![false; tiers.len()];
+        loop {
+            thread::sleep(self.interval);
+            let artifact = match artifact.upgrade() {
+                Some(artifact) => artifact,
+                None => return,
+            };
+
+            let hot = tiers
+                .iter()
+                .enumerate()
+                .filter(|(index, tier)| !recompiled[*index] && tier.calls() >= self.threshold)
+                .map(|(index, _)| LocalFunctionIndex::new(index))
+                .collect::<Vec<_>>();
+            if hot.is_empty() {
+                continue;
+            }
+            for index in &hot {
+                recompiled[index.index()] = true;
+            }
+
+            if translation.is_none() {
+                match ModuleEnvironment::new().translate(binary) {
+                    Ok(environ) => translation = Some(environ),
+                    Err(_) => return,
+                }
+            }
+            let translation = translation.as_ref().unwrap();
+            let hot = hot
+                .into_iter()
+                .filter(|index| {
+                    passes_no_v128(
+                        &translation.module,
+                        *index,
+                        translation.function_body_inputs[*index].data,
+                    )
+                })
+                .collect::<Vec<_>>();
+            if hot.is_empty() {
+                continue;
+            }
+            // The functions keep running as compiled if the optimizing
+            // compiler can't handle the module.
+            if self
+                .recompile(engine, &artifact, translation, &hot, tiers)
+                .is_err()
+            {
+                return;
+            }
+        }
+    }
+
+    /// Compiles the `hot` functions with the optimizing compiler, and
+    /// redirects them to the optimized code.
+    fn recompile(
+        &self,
+        engine: &Engine,
+        artifact: &Artifact,
+        translation: &ModuleEnvironment,
+        hot: &[LocalFunctionIndex],
+        tiers: &[VMFunctionTier],
+    ) -> Result<(), CompileError> {
+        let mut is_hot = vec![false; tiers.len()];
+        for index in hot {
+            is_hot[index.index()] = true;
+        }
+        // The function indices must stay the same, so the other functions
+        // are compiled as stubs. Only the hot functions are ever called.
+        let function_body_inputs = translation
+            .function_body_inputs
+            .iter()
+            .map(|(index, body)| FunctionBodyData {
+                data: if is_hot[index.index()] {
+                    body.data
+                } else {
+                    STUB_BODY
+                },
+                module_offset: body.module_offset,
+            })
+            .collect::<PrimaryMap<LocalFunctionIndex, _>>();
+        let compile_info = CompileModuleInfo {
+            features: artifact.features().clone(),
+            module: artifact.create_module_info(),
+            memory_styles: artifact.memory_styles().clone(),
+            table_styles: artifact.table_styles().clone(),
+        };
+        let target = engine.target();
+        let compilation = self.compiler.lock().unwrap().compile_module(
+            target,
+            &compile_info,
+            translation.module_translation_state.as_ref().unwrap(),
+            function_body_inputs,
+        )?;
+
+        let mut custom_sections = compilation.custom_sections;
+        let libcall_trampolines = custom_sections.push(make_libcall_trampolines(target));
+        let libcall_trampoline_len = libcall_trampoline_len(target);
+
+        let module_info = artifact.module_info();
+        let mut engine_inner = engine.inner_mut();
+        // The stubs are allocated too, as the unwind info of the
+        // compilation refers to them.
+        let (allocated_functions, _, _, allocated_sections) = engine_inner.allocate(
+            module_info,
+            compilation
+                .functions
+                .values()
+                .map(|function| &function.body),
+            iter::empty::<&FunctionBody>(),
+            iter::empty::<&FunctionBody>(),
+            custom_sections.values(),
+        )?;
+
+        link_module(
+            module_info,
+            &allocated_functions,
+            iter::empty::<(LocalFunctionIndex, slice::Iter<Relocation>)>(),
+            &allocated_sections,
+            custom_sections
+                .iter()
+                .map(|(index, section)| (index, section.relocations.iter())),
+            libcall_trampolines,
+            libcall_trampoline_len,
+        );
+        // The hot functions call the others through their original code,
+        // which jumps to the optimized code once they are recompiled too.
+        let callees = artifact
+            .finished_functions()
+            .iter()
+            .map(|(index, body)| FunctionExtent {
+                ptr: if is_hot[index.index()] {
+                    allocated_functions[index].ptr
+                } else {
+                    tiers[index.index()].redirect().unwrap_or(*body)
+                },
+                length: 0,
+            })
+            .collect::<PrimaryMap<LocalFunctionIndex, _>>();
+        link_module(
+            module_info,
+            &callees,
+            hot.iter()
+                .map(|index| (*index, compilation.functions[*index].relocations.iter())),
+            &allocated_sections,
+            iter::empty::<(SectionIndex, slice::Iter<Relocation>)>(),
+            libcall_trampolines,
+            libcall_trampoline_len,
+        );
+
+        let eh_frame = compilation.debug.map(|debug| unsafe {
+            slice::from_raw_parts(
+                *allocated_sections[debug.eh_frame],
+                custom_sections[debug.eh_frame].bytes.len(),
+            )
+        });
+        engine_inner.publish_compiled_code();
+        engine_inner.publish_eh_frame(eh_frame)?;
+
+        let bodies = hot
+            .iter()
+            .map(|index| allocated_functions[*index].ptr)
+            .collect::<Vec<_>>();
+        let frame_infos = compilation
+            .functions
+            .into_iter()
+            .map(|(_, function)| function.frame_info)
+            .collect::<PrimaryMap<LocalFunctionIndex, _>>();
+        if let Some(frame_info) = register_frame_info(
+            artifact.create_module_info(),
+            &allocated_functions.into_boxed_slice(),
+            FrameInfosVariant::Owned(frame_infos),
+        ) {
+            engine_inner.register_frame_info(frame_info);
+        }
+        drop(engine_inner);
+
+        for (index, body) in hot.iter().zip(bodies) {
+            tiers[index.index()].set_redirect(body);
+        }
+        Ok(())
+    }
+}
+
+/// Whether the function `index`, with the body `data`, neither takes nor
+/// returns `v128` values, nor calls a function that does.
+///
+/// Singlepass passes `v128` values as two `i64` halves, unlike the
+/// optimizing compilers, so such calls between the baseline and the
+/// optimized code would mix up their arguments.
+fn passes_no_v128(module: &ModuleInfo, index: LocalFunctionIndex, data: &[u8]) -> bool {
+    let has_v128 = |signature: SignatureIndex| {
+        let ty = &module.signatures[signature];
+        ty.params()
+            .iter()
+            .chain(ty.results())
+            .any(|ty| *ty == Type::V128)
+    };
+    if has_v128(module.functions[module.func_index(index)]) {
+        return false;
+    }
+
+    let mut reader = BinaryReader::new(data);
+    let num_local_decls = match reader.read_var_u32() {
+        Ok(num_local_decls) => num_local_decls,
+        Err(_) => return false,
+    };
+    for _ in 0..num_local_decls {
+        if reader.read_var_u32().is_err() || reader.read::<ValType>().is_err() {
+            return false;
+        }
+    }
+    while !reader.eof() {
+        let signature = match reader.read_operator() {
+            Ok(Operator::Call { function_index } | Operator::ReturnCall { function_index }) => {
+                module.functions[FunctionIndex::from_u32(function_index)]
+            }
+            Ok(
+                Operator::CallIndirect { type_index, .. }
+                | Operator::ReturnCallIndirect { type_index, .. },
+            ) => SignatureIndex::from_u32(type_index),
+            Ok(_) => continue,
+            Err(_) => return false,
+        };
+        if has_v128(signature) {
+            return false;
+        }
+    }
+    true
+}
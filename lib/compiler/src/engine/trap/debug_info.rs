@@ -0,0 +1,486 @@
+//! Symbolication of backtraces using the DWARF debug information embedded
+//! in the custom sections of a WebAssembly module.
+//!
+//! Addresses in WebAssembly DWARF are offsets from the start of the contents
+//! of the code section, so the original module offsets recorded for each
+//! frame can be looked up directly once rebased.
+//!
+//! Nothing is parsed until a location is asked for, which usually only
+//! happens when a backtrace is displayed.
+use gimli::{
+    constants, AttributeValue, ColumnType, DebugInfoOffset, EndianSlice, EntriesTreeNode,
+    LittleEndian, SectionId, Unit, UnitSectionOffset,
+};
+use std::sync::{Arc, OnceLock};
+use wasmer_types::{ModuleInfo, SourceLocation, SourceMap};
+
+type Reader<'a> = EndianSlice<'a, LittleEndian>;
+type Dwarf<'a> = gimli::Dwarf<Reader<'a>>;
+/// A file, line and column.
+type Position = (Option<String>, Option<u32>, Option<u32>);
+
+/// The range of addresses covered by a compilation unit.
+#[derive(Debug)]
+struct UnitRange {
+    begin: u64,
+    end: u64,
+    offset: DebugInfoOffset,
+}
+
+/// A function containing an address, possibly inlined.
+struct Function {
+    name: Option<String>,
+    /// Where the function was inlined, if it was.
+    call_position: Option<Position>,
+}
+
+/// The DWARF of a module.
+#[derive(Debug)]
+pub(crate) struct DebugInfo {
+    module: Arc<ModuleInfo>,
+    /// The address ranges of the units, sorted, computed on the first lookup.
+    units: OnceLock<Vec<UnitRange>>,
+}
+
+impl DebugInfo {
+    /// Creates the DWARF of a module, without parsing it yet.
+    ///
+    /// Returns `None` if the module has no debug information.
+    pub(crate) fn new(module: Arc<ModuleInfo>) -> Option<Self> {
+        if !module
+            .custom_sections
+            .contains_key(SectionId::DebugInfo.name())
+        {
+            return None;
+        }
+        Some(Self {
+            module,
+            units: OnceLock::new(),
+        })
+    }
+
+    fn dwarf(&self) -> Result<Dwarf<'_>, gimli::Error> {
+        gimli::Dwarf::load(|id: SectionId| -> Result<Reader<'_>, gimli::Error> {
+            let data = match self.module.custom_sections.get(id.name()) {
+                Some(index) => &self.module.custom_sections_data[*index][..],
+                None => &[],
+            };
+            Ok(EndianSlice::new(data, LittleEndian))
+        })
+    }
+
+    /// Lists the address ranges of the units, ignoring the units which
+    /// can't be parsed.
+    fn unit_ranges(dwarf: &Dwarf<'_>) -> Vec<UnitRange> {
+        let mut ranges = Vec::new();
+        let mut headers = dwarf.units();
+        while let Ok(Some(header)) = headers.next() {
+            let offset = match header.offset() {
+                UnitSectionOffset::DebugInfoOffset(offset) => offset,
+                UnitSectionOffset::DebugTypesOffset(_) => continue,
+            };
+            let unit = match dwarf.unit(header) {
+                Ok(unit) => unit,
+                Err(_) => continue,
+            };
+            let mut unit_ranges = match dwarf.unit_ranges(&unit) {
+                Ok(unit_ranges) => unit_ranges,
+                Err(_) => continue,
+            };
+            while let Ok(Some(range)) = unit_ranges.next() {
+                if range.begin < range.end {
+                    ranges.push(UnitRange {
+                        begin: range.begin,
+                        end: range.end,
+                        offset,
+                    });
+                }
+            }
+        }
+        ranges.sort_by_key(|range| range.begin);
+        ranges
+    }
+
+    /// Returns the source locations of `address`, innermost inlined function
+    /// first, if the unit at `offset` describes it.
+    fn unit_source_locations(
+        dwarf: &Dwarf<'_>,
+        offset: DebugInfoOffset,
+        address: u64,
+    ) -> Result<Vec<SourceLocation>, gimli::Error> {
+        let unit = dwarf.unit(dwarf.debug_info.header_from_offset(offset)?)?;
+
+        let mut functions = Vec::new();
+        let mut tree = unit.entries_tree(None)?;
+        find_functions(dwarf, &unit, tree.root()?, address, &mut functions)?;
+        let mut position = find_line(dwarf, &unit, address)?;
+        if functions.is_empty() {
+            return Ok(position
+                .map(|(file, line, column)| SourceLocation::new(None, file, line, column))
+                .into_iter()
+                .collect());
+        }
+
+        // The innermost function is at the row of `address`, and each
+        // function it has been inlined into is where it was inlined.
+        let mut locations = Vec::new();
+        for function in functions.into_iter().rev() {
+            let (file, line, column) = position.take().unwrap_or_default();
+            locations.push(SourceLocation::new(function.name, file, line, column));
+            position = function.call_position;
+        }
+        Ok(locations)
+    }
+}
+
+impl SourceMap for DebugInfo {
+    fn source_locations(&self, module_offset: usize) -> Vec<SourceLocation> {
+        let address = match module_offset.checked_sub(self.module.code_section_offset) {
+            Some(address) => address as u64,
+            None => return Vec::new(),
+        };
+        let dwarf = match self.dwarf() {
+            Ok(dwarf) => dwarf,
+            Err(_) => return Vec::new(),
+        };
+        let units = self.units.get_or_init(|| Self::unit_ranges(&dwarf));
+        units
+            .iter()
+            .take_while(|range| range.begin <= address)
+            .filter(|range| address < range.end)
+            .filter_map(|range| Self::unit_source_locations(&dwarf, range.offset, address).ok())
+            .find(|locations| !locations.is_empty())
+            .unwrap_or_default()
+    }
+}
+
+/// Appends to `functions` the functions among the children of `node` which
+/// contain `address`, outermost first.
+fn find_functions(
+    dwarf: &Dwarf<'_>,
+    unit: &Unit<Reader<'_>>,
+    node: EntriesTreeNode<'_, '_, '_, Reader<'_>>,
+    address: u64,
+    functions: &mut Vec<Function>,
+) -> Result<(), gimli::Error> {
+    let mut children = node.children();
+    while let Some(child) = children.next()? {
+        let entry = child.entry();
+        match entry.tag() {
+            constants::DW_TAG_subprogram | constants::DW_TAG_inlined_subroutine => {
+                let mut ranges = dwarf.die_ranges(unit, entry)?;
+                let mut contains_address = false;
+                while let Some(range) = ranges.next()? {
+                    contains_address |= range.begin <= address && address < range.end;
+                }
+                if !contains_address {
+                    continue;
+                }
+                let call_position = if entry.tag() == constants::DW_TAG_inlined_subroutine {
+                    Some(call_position(dwarf, unit, entry)?)
+                } else {
+                    None
+                };
+                functions.push(Function {
+                    name: function_name(dwarf, unit, entry, 0)?,
+                    call_position,
+                });
+                return find_functions(dwarf, unit, child, address, functions);
+            }
+            // Inlined functions may be nested in lexical blocks, namespaces…
+            _ => find_functions(dwarf, unit, child, address, functions)?,
+        }
+    }
+    Ok(())
+}
+
+/// Returns the name of a function, following the entries it derives from.
+fn function_name(
+    dwarf: &Dwarf<'_>,
+    unit: &Unit<Reader<'_>>,
+    entry: &gimli::DebuggingInformationEntry<'_, '_, Reader<'_>>,
+    depth: usize,
+) -> Result<Option<String>, gimli::Error> {
+    // The linkage name is preferred, as it can be demangled.
+    for name in [
+        constants::DW_AT_linkage_name,
+        constants::DW_AT_MIPS_linkage_name,
+        constants::DW_AT_name,
+    ] {
+        if let Some(value) = entry.attr_value(name)? {
+            let name = dwarf.attr_string(unit, value)?;
+            return Ok(Some(name.to_string_lossy().into_owned()));
+        }
+    }
+    // Only follow references within the unit, a few levels deep.
+    if depth < 16 {
+        for origin in [
+            constants::DW_AT_abstract_origin,
+            constants::DW_AT_specification,
+        ] {
+            if let Some(AttributeValue::UnitRef(offset)) = entry.attr_value(origin)? {
+                let origin = unit.entry(offset)?;
+                return function_name(dwarf, unit, &origin, depth + 1);
+            }
+        }
+    }
+    Ok(None)
+}
+
+/// Returns where an inlined function was inlined.
+fn call_position(
+    dwarf: &Dwarf<'_>,
+    unit: &Unit<Reader<'_>>,
+    entry: &gimli::DebuggingInformationEntry<'_, '_, Reader<'_>>,
+) -> Result<Position, gimli::Error> {
+    let file = match entry.attr_value(constants::DW_AT_call_file)? {
+        Some(AttributeValue::FileIndex(index)) => file_path(dwarf, unit, index)?,
+        _ => None,
+    };
+    let udata = |name| -> Result<Option<u32>, gimli::Error> {
+        Ok(entry
+            .attr_value(name)?
+            .and_then(|value| value.udata_value())
+            .filter(|value| *value != 0)
+            .map(|value| value as u32))
+    };
+    Ok((
+        file,
+        udata(constants::DW_AT_call_line)?,
+        udata(constants::DW_AT_call_column)?,
+    ))
+}
+
+/// Returns the file, line and column of `address` in the line program of
+/// the unit.
+fn find_line(
+    dwarf: &Dwarf<'_>,
+    unit: &Unit<Reader<'_>>,
+    address: u64,
+) -> Result<Option<Position>, gimli::Error> {
+    let program = match &unit.line_program {
+        Some(program) => program.clone(),
+        None => return Ok(None),
+    };
+    let mut rows = program.rows();
+    let mut previous = None;
+    let mut found = None;
+    while let Some((_, row)) = rows.next_row()? {
+        if let Some(previous) = previous.replace(*row) {
+            if previous.address() <= address && address < row.address() {
+                found = Some(previous);
+                break;
+            }
+        }
+        if row.end_sequence() {
+            previous = None;
+        }
+    }
+    let row = match found {
+        Some(row) => row,
+        None => return Ok(None),
+    };
+    let file = file_path(dwarf, unit, row.file_index())?;
+    let line = row.line().map(|line| line.get() as u32);
+    let column = match row.column() {
+        ColumnType::LeftEdge => None,
+        ColumnType::Column(column) => Some(column.get() as u32),
+    };
+    Ok(Some((file, line, column)))
+}
+
+/// Returns the path of a file of the line program of the unit.
+fn file_path(
+    dwarf: &Dwarf<'_>,
+    unit: &Unit<Reader<'_>>,
+    index: u64,
+) -> Result<Option<String>, gimli::Error> {
+    let header = match &unit.line_program {
+        Some(program) => program.header(),
+        None => return Ok(None),
+    };
+    let file = match header.file(index) {
+        Some(file) => file,
+        None => return Ok(None),
+    };
+    let mut path = unit
+        .comp_dir
+        .map(|dir| dir.to_string_lossy().into_owned())
+        .unwrap_or_default();
+    if let Some(directory) = file.directory(header) {
+        push_path(
+            &mut path,
+            &dwarf.attr_string(unit, directory)?.to_string_lossy(),
+        );
+    }
+    push_path(
+        &mut path,
+        &dwarf.attr_string(unit, file.path_name())?.to_string_lossy(),
+    );
+    Ok(Some(path))
+}
+
+/// Appends `component` to `path`, unless it is absolute.
+fn push_path(path: &mut String, component: &str) {
+    let is_absolute = component.starts_with('/')
+        || component.starts_with('\\')
+        || component.get(1..3) == Some(":\\");
+    if is_absolute || path.is_empty() {
+        *path = component.to_string();
+    } else {
+        if !path.ends_with('/') {
+            path.push('/');
+        }
+        path.push_str(component);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use gimli::write::{
+        Address, AttributeValue, DwarfUnit, EndianVec, LineProgram, LineString, Sections,
+    };
+    use gimli::{constants, Encoding, Format, LineEncoding};
+
+    const CODE_SECTION_OFFSET: usize = 100;
+
+    /// Builds a module whose single function, at 0x10..0x1c in the code
+    /// section, is `main` from `main.c` with `helper` inlined at 0x14..0x18.
+    fn module_with_dwarf() -> ModuleInfo {
+        let encoding = Encoding {
+            format: Format::Dwarf32,
+            version: 4,
+            address_size: 4,
+        };
+        let mut dwarf = DwarfUnit::new(encoding);
+        dwarf.unit.line_program = LineProgram::new(
+            encoding,
+            LineEncoding::default(),
+            LineString::String(b"/src".to_vec()),
+            LineString::String(b"main.c".to_vec()),
+            None,
+        );
+        let program = &mut dwarf.unit.line_program;
+        let directory = program.default_directory();
+        let file = program.add_file(LineString::String(b"main.c".to_vec()), directory, None);
+        program.begin_sequence(Some(Address::Constant(0x10)));
+        for (offset, line) in [(0, 3), (4, 10), (8, 5)] {
+            program.row().file = file;
+            program.row().address_offset = offset;
+            program.row().line = line;
+            program.row().column = 7;
+            program.generate_row();
+        }
+        program.end_sequence(0xc);
+
+        let root = dwarf.unit.root();
+        let entry = dwarf.unit.get_mut(root);
+        entry.set(
+            constants::DW_AT_name,
+            AttributeValue::String(b"main.c".to_vec()),
+        );
+        entry.set(
+            constants::DW_AT_comp_dir,
+            AttributeValue::String(b"/src".to_vec()),
+        );
+        entry.set(
+            constants::DW_AT_low_pc,
+            AttributeValue::Address(Address::Constant(0x10)),
+        );
+        entry.set(constants::DW_AT_high_pc, AttributeValue::Udata(0xc));
+        entry.set(constants::DW_AT_stmt_list, AttributeValue::LineProgramRef);
+
+        let helper = dwarf.unit.add(root, constants::DW_TAG_subprogram);
+        let entry = dwarf.unit.get_mut(helper);
+        entry.set(
+            constants::DW_AT_name,
+            AttributeValue::String(b"helper".to_vec()),
+        );
+        entry.set(constants::DW_AT_inline, AttributeValue::Udata(1));
+
+        let main = dwarf.unit.add(root, constants::DW_TAG_subprogram);
+        let entry = dwarf.unit.get_mut(main);
+        entry.set(
+            constants::DW_AT_name,
+            AttributeValue::String(b"main".to_vec()),
+        );
+        entry.set(
+            constants::DW_AT_low_pc,
+            AttributeValue::Address(Address::Constant(0x10)),
+        );
+        entry.set(constants::DW_AT_high_pc, AttributeValue::Udata(0xc));
+
+        let inlined = dwarf.unit.add(main, constants::DW_TAG_inlined_subroutine);
+        let entry = dwarf.unit.get_mut(inlined);
+        entry.set(
+            constants::DW_AT_abstract_origin,
+            AttributeValue::UnitRef(helper),
+        );
+        entry.set(
+            constants::DW_AT_low_pc,
+            AttributeValue::Address(Address::Constant(0x14)),
+        );
+        entry.set(constants::DW_AT_high_pc, AttributeValue::Udata(4));
+        entry.set(
+            constants::DW_AT_call_file,
+            AttributeValue::FileIndex(Some(file)),
+        );
+        entry.set(constants::DW_AT_call_line, AttributeValue::Udata(4));
+        entry.set(constants::DW_AT_call_column, AttributeValue::Udata(9));
+
+        let mut sections = Sections::new(EndianVec::new(LittleEndian));
+        dwarf.write(&mut sections).unwrap();
+
+        let mut module = ModuleInfo::new();
+        module.code_section_offset = CODE_SECTION_OFFSET;
+        sections
+            .for_each(|id, data| {
+                if !data.slice().is_empty() {
+                    let index = module.custom_sections_data.push(data.slice().into());
+                    module.custom_sections.insert(id.name().to_string(), index);
+                }
+                Ok::<_, gimli::Error>(())
+            })
+            .unwrap();
+        module
+    }
+
+    fn describe(locations: &[SourceLocation]) -> Vec<(Option<&str>, u32, u32)> {
+        locations
+            .iter()
+            .map(|location| {
+                assert_eq!(location.file(), Some("/src/main.c"));
+                (
+                    location.function_name(),
+                    location.line().unwrap(),
+                    location.column().unwrap(),
+                )
+            })
+            .collect()
+    }
+
+    #[test]
+    fn source_locations() {
+        let debug_info = DebugInfo::new(Arc::new(module_with_dwarf())).unwrap();
+
+        let locations = debug_info.source_locations(CODE_SECTION_OFFSET + 0x11);
+        assert_eq!(describe(&locations), [(Some("main"), 3, 7)]);
+
+        let locations = debug_info.source_locations(CODE_SECTION_OFFSET + 0x15);
+        assert_eq!(
+            describe(&locations),
+            [(Some("helper"), 10, 7), (Some("main"), 4, 9)]
+        );
+
+        assert!(debug_info
+            .source_locations(CODE_SECTION_OFFSET + 0x40)
+            .is_empty());
+        assert!(debug_info.source_locations(10).is_empty());
+    }
+
+    #[test]
+    fn no_debug_info() {
+        assert!(DebugInfo::new(Arc::new(ModuleInfo::new())).is_none());
+    }
+}
@@ -27,6 +27,7 @@ use wasmer_types::{
 };
 use wasmer_vm::FunctionBodyPtr;
 
+use super::debug_info::DebugInfo;
 use crate::ArtifactBuildFromArchive;
 
 lazy_static::lazy_static! {
@@ -66,6 +67,8 @@ struct ModuleInfoFrameInfo {
     functions: BTreeMap<usize, FunctionInfo>,
     module: Arc<ModuleInfo>,
     frame_infos: FrameInfosVariant,
+    /// The DWARF of the module, if it has any.
+    debug_info: Option<Arc<DebugInfo>>,
 }
 
 impl ModuleInfoFrameInfo {
@@ -140,13 +143,19 @@ impl GlobalFrameInfo {
             None => instr_map.start_srcloc(),
         };
         let func_index = module.module.func_index(func.local_index);
-        Some(FrameInfo::new(
+        let frame_info = FrameInfo::new(
             module.module.name(),
             func_index.index() as u32,
             module.module.function_names.get(&func_index).cloned(),
             instr_map.start_srcloc(),
             instr,
-        ))
+        );
+        // The source locations are only looked up when asked for, without
+        // holding the lock.
+        match &module.debug_info {
+            Some(debug_info) => Some(frame_info.with_source_map(debug_info.clone())),
+            None => Some(frame_info),
+        }
     }
 
     /// Fetches trap information about a program counter in a backtrace.
@@ -200,6 +209,8 @@ pub enum FrameInfosVariant {
     Owned(PrimaryMap<LocalFunctionIndex, CompiledFunctionFrameInfo>),
     /// Archived frame infos
     Archived(ArtifactBuildFromArchive),
+    /// The frame info of a single function
+    Function(LocalFunctionIndex, CompiledFunctionFrameInfo),
 }
 
 impl FrameInfosVariant {
@@ -211,6 +222,8 @@ impl FrameInfosVariant {
                 .get_frame_info_ref()
                 .get(index)
                 .map(CompiledFunctionFrameInfoVariant::Archived),
+            Self::Function(function_index, frame_info) => (*function_index == index)
+                .then_some(CompiledFunctionFrameInfoVariant::Ref(frame_info)),
         }
     }
 }
@@ -384,6 +397,43 @@ pub fn register(
     if functions.is_empty() {
         return None;
     }
+    register_functions(module, min, max, functions, frame_infos)
+}
+
+/// Registers the frame information of a function compiled after the rest
+/// of its module, like the functions of lazily compiled modules.
+pub fn register_function(
+    module: Arc<ModuleInfo>,
+    index: LocalFunctionIndex,
+    extent: &FunctionExtent,
+    frame_info: CompiledFunctionFrameInfo,
+) -> Option<GlobalFrameInfoRegistration> {
+    let start = *extent.ptr as usize;
+    let end = start + extent.length - 1;
+    let functions = BTreeMap::from([(
+        end,
+        FunctionInfo {
+            start,
+            local_index: index,
+        },
+    )]);
+    register_functions(
+        module,
+        start,
+        end,
+        functions,
+        FrameInfosVariant::Function(index, frame_info),
+    )
+}
+
+fn register_functions(
+    module: Arc<ModuleInfo>,
+    min: usize,
+    max: usize,
+    functions: BTreeMap<usize, FunctionInfo>,
+    frame_infos: FrameInfosVariant,
+) -> Option<GlobalFrameInfoRegistration> {
+    let debug_info = DebugInfo::new(module.clone()).map(Arc::new);
 
     let mut info = FRAME_INFO.write().unwrap();
     // First up assert that our chunk of jit functions doesn't collide with
@@ -403,6 +453,7 @@ pub fn register(
             functions,
             module,
             frame_infos,
+            debug_info,
         },
     );
     assert!(prev.is_none());
@@ -1,7 +1,9 @@
+mod debug_info;
 mod frame_info;
 mod stack;
 pub use frame_info::{
-    register as register_frame_info, CompiledFunctionFrameInfoVariant, FrameInfosVariant,
-    FunctionExtent, GlobalFrameInfoRegistration, FRAME_INFO,
+    register as register_frame_info, register_function as register_function_frame_info,
+    CompiledFunctionFrameInfoVariant, FrameInfosVariant, FunctionExtent,
+    GlobalFrameInfoRegistration, FRAME_INFO,
 };
 pub use stack::get_trace_and_trapcode;
@@ -127,6 +127,12 @@ impl<'a> MiddlewareBinaryReader<'a> {
     pub fn set_middleware_chain(&mut self, stages: Vec<Box<dyn FunctionMiddleware>>) {
         self.chain = stages;
     }
+
+    /// Reads the offsets of memory operators as 64-bit integers, as the
+    /// memory64 proposal does.
+    pub fn allow_memarg64(&mut self, allow: bool) {
+        self.state.inner.allow_memarg64(allow);
+    }
 }
 
 impl<'a> FunctionBinaryReader<'a> for MiddlewareBinaryReader<'a> {
@@ -21,15 +21,16 @@ use wasmer_types::entity::packed_option::ReservedValue;
 use wasmer_types::entity::EntityRef;
 use wasmer_types::{
     DataIndex, ElemIndex, FunctionIndex, FunctionType, GlobalIndex, GlobalInit, GlobalType,
-    MemoryIndex, MemoryType, Pages, SignatureIndex, TableIndex, TableType, Type, V128,
+    MemoryIndex, MemoryType, Pages, SignatureIndex, TableIndex, TableType, TagIndex, Type, V128,
+    WASM64_MAX_PAGES,
 };
 use wasmer_types::{WasmError, WasmResult};
 use wasmparser::{
     self, Data, DataKind, DataSectionReader, Element, ElementItems, ElementKind,
     ElementSectionReader, Export, ExportSectionReader, ExternalKind, FunctionSectionReader,
     GlobalSectionReader, GlobalType as WPGlobalType, ImportSectionReader, MemorySectionReader,
-    MemoryType as WPMemoryType, NameSectionReader, Operator, TableSectionReader, TypeRef,
-    TypeSectionReader,
+    MemoryType as WPMemoryType, NameSectionReader, Operator, TableSectionReader, TagKind,
+    TagSectionReader, TypeRef, TypeSectionReader,
 };
 
 /// Helper function translating wasmparser types to Wasm Type.
@@ -50,6 +51,8 @@ pub fn wpreftype_to_type(ty: wasmparser::RefType) -> WasmResult<Type> {
         Ok(Type::ExternRef)
     } else if ty.is_func_ref() {
         Ok(Type::FuncRef)
+    } else if ty == wasmparser::RefType::EXNREF {
+        Ok(Type::ExceptionRef)
     } else {
         Err(wasm_unsupported!("unsupported reference type: {:?}", ty))
     }
@@ -60,10 +63,31 @@ pub fn wpheaptype_to_type(ty: wasmparser::HeapType) -> WasmResult<Type> {
     match ty {
         wasmparser::HeapType::Func => Ok(Type::FuncRef),
         wasmparser::HeapType::Extern => Ok(Type::ExternRef),
+        wasmparser::HeapType::Exn => Ok(Type::ExceptionRef),
         other => Err(wasm_unsupported!("unsupported reference type: {other:?}")),
     }
 }
 
+/// Converts a wasmparser memory type into a memory type.
+pub fn wpmemorytype_to_memorytype(ty: WPMemoryType) -> WasmResult<MemoryType> {
+    let WPMemoryType {
+        shared,
+        memory64,
+        initial,
+        maximum,
+    } = ty;
+    let minimum = u32::try_from(initial)
+        .map_err(|_| wasm_unsupported!("memory of {} pages is too large", initial))?;
+    // A maximum beyond what `Pages` can hold is never reachable anyway.
+    let maximum = maximum.map(|p| Pages(u32::try_from(p).unwrap_or(WASM64_MAX_PAGES)));
+    Ok(MemoryType {
+        minimum: Pages(minimum),
+        maximum,
+        shared,
+        memory64,
+    })
+}
+
 /// Parses the Type section of the wasm module.
 pub fn parse_type_section(
     types: TypeSectionReader,
@@ -122,24 +146,17 @@ pub fn parse_import_section<'data>(
                     field_name,
                 )?;
             }
-            TypeRef::Tag(_) => {
-                unimplemented!("exception handling not implemented yet")
+            TypeRef::Tag(tag) => {
+                let TagKind::Exception = tag.kind;
+                environ.declare_tag_import(
+                    SignatureIndex::from_u32(tag.func_type_idx),
+                    module_name,
+                    field_name,
+                )?;
             }
-            TypeRef::Memory(WPMemoryType {
-                shared,
-                memory64,
-                initial,
-                maximum,
-            }) => {
-                if memory64 {
-                    unimplemented!("64bit memory not implemented yet");
-                }
+            TypeRef::Memory(ty) => {
                 environ.declare_memory_import(
-                    MemoryType {
-                        minimum: Pages(initial as u32),
-                        maximum: maximum.map(|p| Pages(p as u32)),
-                        shared,
-                    },
+                    wpmemorytype_to_memorytype(ty)?,
                     module_name,
                     field_name,
                 )?;
@@ -220,20 +237,8 @@ pub fn parse_memory_section(
     environ.reserve_memories(memories.count())?;
 
     for entry in memories {
-        let WPMemoryType {
-            shared,
-            memory64,
-            initial,
-            maximum,
-        } = entry.map_err(from_binaryreadererror_wasmerror)?;
-        if memory64 {
-            unimplemented!("64bit memory not implemented yet");
-        }
-        environ.declare_memory(MemoryType {
-            minimum: Pages(initial as u32),
-            maximum: maximum.map(|p| Pages(p as u32)),
-            shared,
-        })?;
+        let ty = entry.map_err(from_binaryreadererror_wasmerror)?;
+        environ.declare_memory(wpmemorytype_to_memorytype(ty)?)?;
     }
 
     Ok(())
@@ -291,6 +296,22 @@ pub fn parse_global_section(
     Ok(())
 }
 
+/// Parses the Tag section of the wasm module.
+pub fn parse_tag_section(
+    tags: TagSectionReader,
+    environ: &mut ModuleEnvironment,
+) -> WasmResult<()> {
+    environ.reserve_tags(tags.count())?;
+
+    for entry in tags {
+        let tag = entry.map_err(from_binaryreadererror_wasmerror)?;
+        let TagKind::Exception = tag.kind;
+        environ.declare_tag(SignatureIndex::from_u32(tag.func_type_idx))?;
+    }
+
+    Ok(())
+}
+
 /// Parses the Export section of the wasm module.
 pub fn parse_export_section<'data>(
     exports: ExportSectionReader<'data>,
@@ -318,9 +339,7 @@ pub fn parse_export_section<'data>(
             ExternalKind::Global => {
                 environ.declare_global_export(GlobalIndex::new(index), field)?
             }
-            ExternalKind::Tag => {
-                unimplemented!("exception handling not implemented yet")
-            }
+            ExternalKind::Tag => environ.declare_tag_export(TagIndex::new(index), field)?,
         }
     }
 
@@ -452,6 +471,7 @@ pub fn parse_data_section<'data>(
                     .map_err(from_binaryreadererror_wasmerror)?
                 {
                     Operator::I32Const { value } => (None, value as u32 as usize),
+                    Operator::I64Const { value } => (None, value as u64 as usize),
                     Operator::GlobalGet { global_index } => {
                         (Some(GlobalIndex::from_u32(global_index)), 0)
                     }
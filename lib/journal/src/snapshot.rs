@@ -28,6 +28,8 @@ pub enum SnapshotTrigger {
     NonDeterministicCall,
     /// Bootstrapping process
     Bootstrap,
+    /// Explicitly requested by the host running the process
+    Explicit,
 }
 
 impl SnapshotTrigger {
@@ -64,6 +66,7 @@ impl FromStr for SnapshotTrigger {
             "stop" | "sigstop" => Self::Sigstop,
             "non-deterministic-call" => Self::NonDeterministicCall,
             "bootstrap" => Self::Bootstrap,
+            "explicit" => Self::Explicit,
             a => return Err(anyhow::format_err!("invalid or unknown trigger ({a})")),
         })
     }
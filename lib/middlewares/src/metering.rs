@@ -3,6 +3,10 @@
 //! operators executed. The WebAssembly instance execution is stopped
 //! when the limit is reached.
 //!
+//! With [`Metering::with_yield`], the execution is suspended instead:
+//! the instance calls the function set with [`set_yield_function`],
+//! which can provide new points before the execution continues.
+//!
 //! # Example
 //!
 //! [See the `metering` detailed and complete
@@ -13,10 +17,11 @@ use std::fmt;
 use std::sync::{Arc, Mutex};
 use wasmer::wasmparser::{BlockType as WpTypeOrFuncType, Operator};
 use wasmer::{
-    AsStoreMut, ExportIndex, FunctionMiddleware, GlobalInit, GlobalType, Instance,
-    LocalFunctionIndex, MiddlewareError, MiddlewareReaderState, ModuleMiddleware, Mutability, Type,
+    AsStoreMut, ExportIndex, Function, FunctionMiddleware, FunctionType, GlobalInit, GlobalType,
+    Instance, LocalFunctionIndex, MiddlewareError, MiddlewareReaderState, ModuleMiddleware,
+    Mutability, TableType, Type, Value,
 };
-use wasmer_types::{GlobalIndex, ModuleInfo};
+use wasmer_types::{GlobalIndex, ModuleInfo, SignatureIndex, TableIndex};
 
 #[derive(Clone)]
 struct MeteringGlobalIndexes(GlobalIndex, GlobalIndex);
@@ -37,6 +42,15 @@ impl MeteringGlobalIndexes {
     }
 }
 
+#[derive(Clone, Debug)]
+struct MeteringYieldIndexes {
+    /// The table in the current module holding the yield function.
+    table: TableIndex,
+
+    /// The signature of the yield function.
+    signature: SignatureIndex,
+}
+
 impl fmt::Debug for MeteringGlobalIndexes {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         f.debug_struct("MeteringGlobalIndexes")
@@ -89,6 +103,13 @@ pub struct Metering<F: Fn(&Operator) -> u64 + Send + Sync> {
 
     /// The global indexes for metering points.
     global_indexes: Mutex<Option<MeteringGlobalIndexes>>,
+
+    /// Whether the execution is suspended rather than stopped when
+    /// the points are exhausted.
+    yielding: bool,
+
+    /// The indexes used to call the yield function, if `yielding`.
+    yield_indexes: Mutex<Option<MeteringYieldIndexes>>,
 }
 
 /// The function-level metering middleware.
@@ -99,6 +120,9 @@ pub struct FunctionMetering<F: Fn(&Operator) -> u64 + Send + Sync> {
     /// The global indexes for metering points.
     global_indexes: MeteringGlobalIndexes,
 
+    /// The indexes used to call the yield function, if any.
+    yield_indexes: Option<MeteringYieldIndexes>,
+
     /// Accumulated cost of the current basic block.
     accumulated_cost: u64,
 }
@@ -129,8 +153,24 @@ impl<F: Fn(&Operator) -> u64 + Send + Sync> Metering<F> {
             initial_limit,
             cost_function: Arc::new(cost_function),
             global_indexes: Mutex::new(None),
+            yielding: false,
+            yield_indexes: Mutex::new(None),
         }
     }
+
+    /// Suspends the execution rather than stopping it when the points
+    /// are exhausted.
+    ///
+    /// Instead of trapping, the instance calls the function set with
+    /// [`set_yield_function`], and checks the remaining points again
+    /// once it returns. The yield function is expected to provide new
+    /// points with [`set_remaining_points`], or to return an error to
+    /// stop the execution. If no yield function is set, the execution
+    /// is stopped as usual.
+    pub fn with_yield(mut self) -> Self {
+        self.yielding = true;
+        self
+    }
 }
 
 impl<F: Fn(&Operator) -> u64 + Send + Sync> fmt::Debug for Metering<F> {
@@ -139,6 +179,7 @@ impl<F: Fn(&Operator) -> u64 + Send + Sync> fmt::Debug for Metering<F> {
             .field("initial_limit", &self.initial_limit)
             .field("cost_function", &"<function>")
             .field("global_indexes", &self.global_indexes)
+            .field("yielding", &self.yielding)
             .finish()
     }
 }
@@ -149,6 +190,7 @@ impl<F: Fn(&Operator) -> u64 + Send + Sync + 'static> ModuleMiddleware for Meter
         Box::new(FunctionMetering {
             cost_function: self.cost_function.clone(),
             global_indexes: self.global_indexes.lock().unwrap().clone().unwrap(),
+            yield_indexes: self.yield_indexes.lock().unwrap().clone(),
             accumulated_cost: 0,
         })
     }
@@ -192,7 +234,29 @@ impl<F: Fn(&Operator) -> u64 + Send + Sync + 'static> ModuleMiddleware for Meter
         *global_indexes = Some(MeteringGlobalIndexes(
             remaining_points_global_index,
             points_exhausted_global_index,
-        ))
+        ));
+
+        if self.yielding {
+            // Append a table holding the yield function, and its signature.
+            let yield_table_index =
+                module_info
+                    .tables
+                    .push(TableType::new(Type::FuncRef, 1, Some(1)));
+
+            module_info.exports.insert(
+                "wasmer_metering_yield".to_string(),
+                ExportIndex::Table(yield_table_index),
+            );
+
+            let yield_signature_index = module_info
+                .signatures
+                .push(FunctionType::new(vec![], vec![]));
+
+            *self.yield_indexes.lock().unwrap() = Some(MeteringYieldIndexes {
+                table: yield_table_index,
+                signature: yield_signature_index,
+            });
+        }
     }
 }
 
@@ -201,6 +265,7 @@ impl<F: Fn(&Operator) -> u64 + Send + Sync> fmt::Debug for FunctionMetering<F> {
         f.debug_struct("FunctionMetering")
             .field("cost_function", &"<function>")
             .field("global_indexes", &self.global_indexes)
+            .field("yield_indexes", &self.yield_indexes)
             .finish()
     }
 }
@@ -229,6 +294,28 @@ impl<F: Fn(&Operator) -> u64 + Send + Sync> FunctionMiddleware for FunctionMeter
             | Operator::Return // end of function - branch source
             => {
                 if self.accumulated_cost > 0 {
+                    if let Some(yield_indexes) = &self.yield_indexes {
+                        state.extend(&[
+                            // while unsigned(globals[remaining_points_index]) < unsigned(self.accumulated_cost) { yield(); }
+                            Operator::Loop { blockty: WpTypeOrFuncType::Empty },
+                            Operator::GlobalGet { global_index: self.global_indexes.remaining_points().as_u32() },
+                            Operator::I64Const { value: self.accumulated_cost as i64 },
+                            Operator::I64LtU,
+                            Operator::If { blockty: WpTypeOrFuncType::Empty },
+                            Operator::I32Const { value: 1 },
+                            Operator::GlobalSet { global_index: self.global_indexes.points_exhausted().as_u32() },
+                            Operator::I32Const { value: 0 },
+                            Operator::CallIndirect {
+                                type_index: yield_indexes.signature.as_u32(),
+                                table_index: yield_indexes.table.as_u32(),
+                                table_byte: 0,
+                            },
+                            Operator::Br { relative_depth: 1 },
+                            Operator::End,
+                            Operator::End,
+                        ]);
+                    }
+
                     state.extend(&[
                         // if unsigned(globals[remaining_points_index]) < unsigned(self.accumulated_cost) { throw(); }
                         Operator::GlobalGet { global_index: self.global_indexes.remaining_points().as_u32() },
@@ -348,13 +435,64 @@ pub fn set_remaining_points(ctx: &mut impl AsStoreMut, instance: &Instance, poin
         .expect("Can't set `wasmer_metering_points_exhausted` in Instance");
 }
 
+/// Set the function called by an [`Instance`][wasmer::Instance] when
+/// its points are exhausted, or unset it with `None`.
+///
+/// The function must take no parameters and return no results. See
+/// [`Metering::with_yield`] for what it is expected to do.
+///
+/// # Panic
+///
+/// The given [`Instance`][wasmer::Instance] must have been processed
+/// with a [`Metering`] middleware created with
+/// [`Metering::with_yield`] at compile time, otherwise this will
+/// panic.
+///
+/// # Example
+///
+/// ```rust
+/// use wasmer::{AsStoreMut, Function, FunctionEnv, FunctionEnvMut, Instance};
+/// use wasmer_middlewares::metering::{set_remaining_points, set_yield_function};
+///
+/// /// Give 10 new points to the instance each time its points are
+/// /// exhausted.
+/// fn refill_when_exhausted(store: &mut impl AsStoreMut, instance: &Instance) {
+///     let env = FunctionEnv::new(store, instance.clone());
+///     let refill = Function::new_typed_with_env(
+///         store,
+///         &env,
+///         |mut env: FunctionEnvMut<Instance>| {
+///             let (instance, mut store) = env.data_and_store_mut();
+///             set_remaining_points(&mut store, instance, 10);
+///         },
+///     );
+///
+///     set_yield_function(store, instance, Some(refill));
+/// }
+/// ```
+pub fn set_yield_function(
+    ctx: &mut impl AsStoreMut,
+    instance: &Instance,
+    function: Option<Function>,
+) {
+    instance
+        .exports
+        .get_table("wasmer_metering_yield")
+        .expect("Can't get `wasmer_metering_yield` from Instance")
+        .set(ctx, 0, Value::FuncRef(function))
+        .expect("Can't set `wasmer_metering_yield` in Instance");
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
 
     use std::sync::Arc;
     use wasmer::sys::EngineBuilder;
-    use wasmer::{imports, wat2wasm, CompilerConfig, Cranelift, Module, Store, TypedFunction};
+    use wasmer::{
+        imports, wat2wasm, CompilerConfig, Cranelift, FunctionEnv, FunctionEnvMut, Module, Store,
+        TypedFunction,
+    };
 
     fn cost_function(operator: &Operator) -> u64 {
         match operator {
@@ -484,4 +622,62 @@ mod tests {
             MeteringPoints::Remaining(4)
         );
     }
+
+    #[test]
+    fn yield_function_works() {
+        let metering = Arc::new(Metering::new(10, cost_function).with_yield());
+        let mut compiler_config = Cranelift::default();
+        compiler_config.push_middleware(metering);
+        let mut store = Store::new(EngineBuilder::new(compiler_config));
+        let module = Module::new(&store, bytecode()).unwrap();
+
+        // Instantiate
+        let instance = Instance::new(&mut store, &module, &imports! {}).unwrap();
+        let add_one: TypedFunction<i32, i32> = instance
+            .exports
+            .get_function("add_one")
+            .unwrap()
+            .typed(&store)
+            .unwrap();
+
+        // Without a yield function, the third call fails due to limit
+        add_one.call(&mut store, 1).unwrap();
+        add_one.call(&mut store, 1).unwrap();
+        assert!(add_one.call(&mut store, 1).is_err());
+        assert_eq!(
+            get_remaining_points(&mut store, &instance),
+            MeteringPoints::Exhausted
+        );
+
+        // Give 3 more points each time the points are exhausted: the
+        // call costs 4 points, so it is suspended twice before it can
+        // continue
+        let env = FunctionEnv::new(&mut store, (instance.clone(), 0));
+        let refill = Function::new_typed_with_env(
+            &mut store,
+            &env,
+            |mut env: FunctionEnvMut<(Instance, u64)>| {
+                let (data, mut store) = env.data_and_store_mut();
+                data.1 += 1;
+                set_remaining_points(&mut store, &data.0, 3 * data.1);
+            },
+        );
+        set_yield_function(&mut store, &instance, Some(refill));
+
+        set_remaining_points(&mut store, &instance, 0);
+        assert_eq!(add_one.call(&mut store, 1).unwrap(), 2);
+        assert_eq!(env.as_ref(&store).1, 2);
+        assert_eq!(
+            get_remaining_points(&mut store, &instance),
+            MeteringPoints::Remaining(2)
+        );
+
+        // Once unset, the execution is stopped again
+        set_yield_function(&mut store, &instance, None);
+        assert!(add_one.call(&mut store, 1).is_err());
+        assert_eq!(
+            get_remaining_points(&mut store, &instance),
+            MeteringPoints::Exhausted
+        );
+    }
 }
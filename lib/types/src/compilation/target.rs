@@ -50,6 +50,10 @@ pub enum CpuFeature {
     // ARM features
     NEON,
     // Risc-V features
+
+    // x86 feature, kept last so the bits of the features stored in
+    // serialized artifacts keep their meaning
+    FMA,
 }
 
 impl CpuFeature {
@@ -100,6 +104,9 @@ impl CpuFeature {
         if std::is_x86_feature_detected!("lzcnt") {
             features.insert(Self::LZCNT);
         }
+        if std::is_x86_feature_detected!("fma") {
+            features.insert(Self::FMA);
+        }
         features
     }
 
@@ -155,6 +162,7 @@ impl FromStr for CpuFeature {
             "avx512f" => Ok(Self::AVX512F),
             "lzcnt" => Ok(Self::LZCNT),
             "neon" => Ok(Self::NEON),
+            "fma" => Ok(Self::FMA),
             _ => Err(ParseCpuFeatureError::Missing(s.to_string())),
         }
     }
@@ -178,6 +186,7 @@ impl ToString for CpuFeature {
             Self::AVX512F => "avx512f",
             Self::LZCNT => "lzcnt",
             Self::NEON => "neon",
+            Self::FMA => "fma",
         }
         .to_string()
     }
@@ -43,6 +43,9 @@ pub enum DeserializeError {
         /// How many bytes the artifact contained
         got: usize,
     },
+    /// The signature of a signed artifact couldn't be verified
+    #[error("invalid signature: {0}")]
+    Signature(String),
 }
 
 /// Error type describing things that can go wrong when operating on Wasm Memories.
@@ -261,6 +264,19 @@ pub enum ParseCpuFeatureError {
     Missing(String),
 }
 
+/// The error that can happen while parsing a `str` to retrieve a
+/// [`Profile`](crate::Profile).
+#[derive(Debug)]
+#[cfg_attr(feature = "std", derive(Error))]
+pub enum ParseProfileError {
+    /// The first line isn't the header of a profile
+    #[cfg_attr(feature = "std", error("not a profile"))]
+    Header,
+    /// The counters of a function on the given line are invalid
+    #[cfg_attr(feature = "std", error("invalid function counters on line {0}"))]
+    Line(usize),
+}
+
 /// A convenient alias for a `Result` that uses `WasmError` as the error type.
 pub type WasmResult<T> = Result<T, WasmError>;
 
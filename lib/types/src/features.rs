@@ -118,6 +118,29 @@ impl Features {
         self
     }
 
+    /// Configures whether the WebAssembly relaxed SIMD proposal will be
+    /// enabled.
+    ///
+    /// The [WebAssembly relaxed SIMD proposal][proposal] is not currently
+    /// fully standardized and is undergoing development. Support for this
+    /// feature can be enabled through this method for appropriate WebAssembly
+    /// modules.
+    ///
+    /// This feature gates SIMD operators whose results may depend on the
+    /// target, such as `f32x4.relaxed_madd`. It builds on the SIMD proposal,
+    /// which is enabled along with it.
+    ///
+    /// This is `false` by default.
+    ///
+    /// [proposal]: https://github.com/WebAssembly/relaxed-simd
+    pub fn relaxed_simd(&mut self, enable: bool) -> &mut Self {
+        self.relaxed_simd = enable;
+        if enable {
+            self.simd = true;
+        }
+        self
+    }
+
     /// Configures whether the WebAssembly bulk memory operations proposal will
     /// be enabled.
     ///
@@ -195,6 +218,29 @@ impl Features {
         self
     }
 
+    /// Configures whether the WebAssembly exception-handling proposal will
+    /// be enabled.
+    ///
+    /// The [WebAssembly exception-handling proposal][proposal] is not
+    /// currently fully standardized and is undergoing development.
+    /// Support for this feature can be enabled through this method for
+    /// appropriate WebAssembly modules.
+    ///
+    /// This feature gates tags, the `exnref` type and the `try_table`,
+    /// `throw` and `throw_ref` instructions.
+    ///
+    /// Only the Cranelift compiler implements it for now: the LLVM and
+    /// Singlepass compilers reject the modules compiled with it enabled,
+    /// with a `CompileError::UnsupportedFeature`.
+    ///
+    /// This is `false` by default.
+    ///
+    /// [proposal]: https://github.com/WebAssembly/exception-handling
+    pub fn exceptions(&mut self, enable: bool) -> &mut Self {
+        self.exceptions = enable;
+        self
+    }
+
     /// Configures whether the WebAssembly multi-memory proposal will
     /// be enabled.
     ///
@@ -232,6 +278,27 @@ impl Features {
         self.memory64 = enable;
         self
     }
+
+    /// Returns the names of the enabled features, as spelled by the
+    /// proposals (e.g. `reference-types`), always in the same order.
+    pub fn enabled_names(&self) -> impl Iterator<Item = &'static str> {
+        [
+            ("threads", self.threads),
+            ("reference-types", self.reference_types),
+            ("simd", self.simd),
+            ("bulk-memory", self.bulk_memory),
+            ("multi-value", self.multi_value),
+            ("tail-call", self.tail_call),
+            ("module-linking", self.module_linking),
+            ("multi-memory", self.multi_memory),
+            ("memory64", self.memory64),
+            ("exceptions", self.exceptions),
+            ("relaxed-simd", self.relaxed_simd),
+            ("extended-const", self.extended_const),
+        ]
+        .into_iter()
+        .filter_map(|(name, enabled)| enabled.then_some(name))
+    }
 }
 
 impl Default for Features {
@@ -265,6 +332,22 @@ mod test_features {
         );
     }
 
+    #[test]
+    fn enabled_names() {
+        let mut features = Features::new();
+        features.threads(false).exceptions(true);
+        assert_eq!(
+            features.enabled_names().collect::<Vec<_>>(),
+            [
+                "reference-types",
+                "simd",
+                "bulk-memory",
+                "multi-value",
+                "exceptions"
+            ]
+        );
+    }
+
     #[test]
     fn enable_threads() {
         let mut features = Features::new();
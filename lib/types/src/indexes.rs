@@ -57,6 +57,12 @@ entity_impl!(LocalMemoryIndex);
 pub struct LocalGlobalIndex(u32);
 entity_impl!(LocalGlobalIndex);
 
+/// Index type of a tag defined locally inside the WebAssembly module.
+#[derive(Copy, Clone, PartialEq, Eq, Hash, PartialOrd, Ord, Debug)]
+#[cfg_attr(feature = "enable-serde", derive(Serialize, Deserialize))]
+pub struct LocalTagIndex(u32);
+entity_impl!(LocalTagIndex);
+
 /// Index type of a function (imported or local) inside the WebAssembly module.
 #[derive(
     Copy,
@@ -117,6 +123,26 @@ entity_impl!(TableIndex);
 pub struct GlobalIndex(u32);
 entity_impl!(GlobalIndex);
 
+/// Index type of an exception tag (imported or local) inside the WebAssembly module.
+#[derive(
+    Copy,
+    Clone,
+    PartialEq,
+    Eq,
+    Hash,
+    PartialOrd,
+    Ord,
+    Debug,
+    RkyvSerialize,
+    RkyvDeserialize,
+    Archive,
+    rkyv::CheckBytes,
+)]
+#[cfg_attr(feature = "enable-serde", derive(Serialize, Deserialize))]
+#[archive(as = "Self")]
+pub struct TagIndex(u32);
+entity_impl!(TagIndex);
+
 /// Index type of a linear memory (imported or local) inside the WebAssembly module.
 #[derive(
     Copy,
@@ -244,6 +270,8 @@ pub enum ExportIndex {
     Memory(MemoryIndex),
     /// Global export.
     Global(GlobalIndex),
+    /// Tag export.
+    Tag(TagIndex),
 }
 
 /// An entity to import.
@@ -272,4 +300,6 @@ pub enum ImportIndex {
     Memory(MemoryIndex),
     /// Global import.
     Global(GlobalIndex),
+    /// Tag import.
+    Tag(TagIndex),
 }
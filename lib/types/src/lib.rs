@@ -59,6 +59,7 @@ mod libcalls;
 mod memory;
 mod module;
 mod module_hash;
+mod profile;
 mod serialize;
 mod stack;
 mod store_id;
@@ -75,12 +76,13 @@ pub use crate::compilation::target::{
     Environment, OperatingSystem, PointerWidth, Target, Triple, Vendor,
 };
 pub use crate::serialize::{
-    ArchivedSerializableCompilation, ArchivedSerializableModule, MetadataHeader,
-    SerializableCompilation, SerializableModule,
+    ArchivedSerializableCompilation, ArchivedSerializableLazyFunction, ArchivedSerializableModule,
+    MetadataHeader, SerializableCompilation, SerializableLazyFunction, SerializableModule,
 };
 pub use error::{
     CompileError, DeserializeError, ImportError, MemoryError, MiddlewareError,
-    ParseCpuFeatureError, PreInstantiationError, SerializeError, WasmError, WasmResult,
+    ParseCpuFeatureError, ParseProfileError, PreInstantiationError, SerializeError, WasmError,
+    WasmResult,
 };
 
 /// The entity module, with common helpers for Rust structures
@@ -88,8 +90,8 @@ pub mod entity;
 pub use crate::features::Features;
 pub use crate::indexes::{
     CustomSectionIndex, DataIndex, ElemIndex, ExportIndex, FunctionIndex, GlobalIndex, ImportIndex,
-    LocalFunctionIndex, LocalGlobalIndex, LocalMemoryIndex, LocalTableIndex, MemoryIndex,
-    SignatureIndex, TableIndex,
+    LocalFunctionIndex, LocalGlobalIndex, LocalMemoryIndex, LocalTableIndex, LocalTagIndex,
+    MemoryIndex, SignatureIndex, TableIndex, TagIndex,
 };
 pub use crate::initializers::{
     ArchivedDataInitializerLocation, ArchivedOwnedDataInitializer, DataInitializer,
@@ -99,12 +101,14 @@ pub use crate::initializers::{
 pub use crate::memory::{Memory32, Memory64, MemorySize};
 pub use crate::module::{ExportsIterator, ImportKey, ImportsIterator, ModuleInfo};
 pub use crate::module_hash::{HashAlgorithm, ModuleHash};
+pub use crate::profile::{FunctionProfile, Profile};
 pub use crate::units::{
-    Bytes, PageCountOutOfRange, Pages, WASM_MAX_PAGES, WASM_MIN_PAGES, WASM_PAGE_SIZE,
+    Bytes, PageCountOutOfRange, Pages, WASM64_MAX_PAGES, WASM_MAX_PAGES, WASM_MIN_PAGES,
+    WASM_PAGE_SIZE,
 };
 pub use types::{
     ExportType, ExternType, FunctionType, GlobalInit, GlobalType, ImportType, MemoryType,
-    Mutability, TableType, Type, V128,
+    Mutability, TableType, TagType, Type, V128,
 };
 pub use value::{RawValue, ValueType};
 
@@ -137,7 +141,7 @@ pub use crate::compilation::unwind::{
     CompiledFunctionUnwindInfoReference,
 };
 
-pub use crate::stack::{FrameInfo, SourceLoc, TrapInformation};
+pub use crate::stack::{FrameInfo, SourceLoc, SourceLocation, SourceMap, TrapInformation};
 pub use crate::store_id::StoreId;
 
 /// Offset in bytes from the beginning of the function.
@@ -135,6 +135,63 @@ pub enum LibCall {
 
     /// memory.atomic.botify for imported memories
     ImportedMemory32AtomicNotify,
+
+    /// memory watch for loads
+    MemoryWatchLoad,
+
+    /// memory watch for stores
+    MemoryWatchStore,
+
+    /// throw
+    Throw,
+
+    /// throw_ref
+    ThrowRef,
+
+    /// catching the pending exception in a try_table
+    Catch,
+
+    /// fma.f32, for the relaxed-SIMD fused multiply-add
+    FmaF32,
+
+    /// fma.f64, for the relaxed-SIMD fused multiply-add
+    FmaF64,
+
+    /// memory.grow for local 64-bit memories
+    Memory64Grow,
+
+    /// memory.grow for imported 64-bit memories
+    ImportedMemory64Grow,
+
+    /// memory.size for local 64-bit memories
+    Memory64Size,
+
+    /// memory.size for imported 64-bit memories
+    ImportedMemory64Size,
+
+    /// memory.copy for local 64-bit memories
+    Memory64Copy,
+
+    /// memory.copy for imported 64-bit memories
+    ImportedMemory64Copy,
+
+    /// memory.fill for local 64-bit memories
+    Memory64Fill,
+
+    /// memory.fill for imported 64-bit memories
+    ImportedMemory64Fill,
+
+    /// memory.init for 64-bit memories
+    Memory64Init,
+
+    /// lazy compilation of a function
+    LazyCompile,
+
+    /// profile instrumentation counter
+    ProfileCount,
+
+    /// debugger hook of the functions compiled for debugging
+    DebugHook,
 }
 
 impl LibCall {
@@ -183,6 +240,25 @@ impl LibCall {
             Self::ImportedMemory32AtomicWait64 => "wasmer_vm_imported_memory32_atomic_wait64",
             Self::Memory32AtomicNotify => "wasmer_vm_memory32_atomic_notify",
             Self::ImportedMemory32AtomicNotify => "wasmer_vm_imported_memory32_atomic_notify",
+            Self::MemoryWatchLoad => "wasmer_vm_memory_watch_load",
+            Self::MemoryWatchStore => "wasmer_vm_memory_watch_store",
+            Self::Throw => "wasmer_vm_throw",
+            Self::ThrowRef => "wasmer_vm_throw_ref",
+            Self::Catch => "wasmer_vm_catch",
+            Self::FmaF32 => "wasmer_vm_f32_fma",
+            Self::FmaF64 => "wasmer_vm_f64_fma",
+            Self::Memory64Grow => "wasmer_vm_memory64_grow",
+            Self::ImportedMemory64Grow => "wasmer_vm_imported_memory64_grow",
+            Self::Memory64Size => "wasmer_vm_memory64_size",
+            Self::ImportedMemory64Size => "wasmer_vm_imported_memory64_size",
+            Self::Memory64Copy => "wasmer_vm_memory64_copy",
+            Self::ImportedMemory64Copy => "wasmer_vm_imported_memory64_copy",
+            Self::Memory64Fill => "wasmer_vm_memory64_fill",
+            Self::ImportedMemory64Fill => "wasmer_vm_imported_memory64_fill",
+            Self::Memory64Init => "wasmer_vm_memory64_init",
+            Self::LazyCompile => "wasmer_vm_lazy_compile",
+            Self::ProfileCount => "wasmer_vm_profile_count",
+            Self::DebugHook => "wasmer_vm_debug_hook",
         }
     }
 }
@@ -8,8 +8,8 @@ use crate::entity::{EntityRef, PrimaryMap};
 use crate::{
     CustomSectionIndex, DataIndex, ElemIndex, ExportIndex, ExportType, ExternType, FunctionIndex,
     FunctionType, GlobalIndex, GlobalInit, GlobalType, ImportIndex, ImportType, LocalFunctionIndex,
-    LocalGlobalIndex, LocalMemoryIndex, LocalTableIndex, MemoryIndex, MemoryType, ModuleHash,
-    SignatureIndex, TableIndex, TableInitializer, TableType,
+    LocalGlobalIndex, LocalMemoryIndex, LocalTableIndex, LocalTagIndex, MemoryIndex, MemoryType,
+    ModuleHash, SignatureIndex, TableIndex, TableInitializer, TableType, TagIndex, TagType,
 };
 
 use indexmap::IndexMap;
@@ -26,7 +26,7 @@ use std::fmt;
 use std::iter::ExactSizeIterator;
 use std::sync::atomic::{AtomicUsize, Ordering::SeqCst};
 
-#[derive(Debug, Clone, RkyvSerialize, RkyvDeserialize, Archive)]
+#[derive(Debug, Clone, PartialEq, Eq, RkyvSerialize, RkyvDeserialize, Archive)]
 #[archive_attr(derive(CheckBytes, Debug))]
 pub struct ModuleId {
     id: usize,
@@ -166,12 +166,20 @@ pub struct ModuleInfo {
     /// WebAssembly global variables (imported and local).
     pub globals: PrimaryMap<GlobalIndex, GlobalType>,
 
+    /// WebAssembly exception tags (imported and local), given by the
+    /// signature of their parameters.
+    pub tags: PrimaryMap<TagIndex, SignatureIndex>,
+
     /// Custom sections in the module.
     pub custom_sections: IndexMap<String, CustomSectionIndex>,
 
     /// The data for each CustomSection in the module.
     pub custom_sections_data: PrimaryMap<CustomSectionIndex, Box<[u8]>>,
 
+    /// Offset of the contents of the code section in the wasm file, which
+    /// the addresses in DWARF debug information are relative to.
+    pub code_section_offset: usize,
+
     /// Number of imported functions in the module.
     pub num_imported_functions: usize,
 
@@ -183,6 +191,9 @@ pub struct ModuleInfo {
 
     /// Number of imported globals in the module.
     pub num_imported_globals: usize,
+
+    /// Number of imported tags in the module.
+    pub num_imported_tags: usize,
 }
 
 /// Mirror version of ModuleInfo that can derive rkyv traits
@@ -204,12 +215,15 @@ pub struct ArchivableModuleInfo {
     tables: PrimaryMap<TableIndex, TableType>,
     memories: PrimaryMap<MemoryIndex, MemoryType>,
     globals: PrimaryMap<GlobalIndex, GlobalType>,
+    tags: PrimaryMap<TagIndex, SignatureIndex>,
     custom_sections: IndexMap<String, CustomSectionIndex>,
     custom_sections_data: PrimaryMap<CustomSectionIndex, Box<[u8]>>,
+    code_section_offset: usize,
     num_imported_functions: usize,
     num_imported_tables: usize,
     num_imported_memories: usize,
     num_imported_globals: usize,
+    num_imported_tags: usize,
 }
 
 impl From<ModuleInfo> for ArchivableModuleInfo {
@@ -230,12 +244,15 @@ impl From<ModuleInfo> for ArchivableModuleInfo {
             tables: it.tables,
             memories: it.memories,
             globals: it.globals,
+            tags: it.tags,
             custom_sections: it.custom_sections,
             custom_sections_data: it.custom_sections_data,
+            code_section_offset: it.code_section_offset,
             num_imported_functions: it.num_imported_functions,
             num_imported_tables: it.num_imported_tables,
             num_imported_memories: it.num_imported_memories,
             num_imported_globals: it.num_imported_globals,
+            num_imported_tags: it.num_imported_tags,
         }
     }
 }
@@ -259,12 +276,15 @@ impl From<ArchivableModuleInfo> for ModuleInfo {
             tables: it.tables,
             memories: it.memories,
             globals: it.globals,
+            tags: it.tags,
             custom_sections: it.custom_sections,
             custom_sections_data: it.custom_sections_data,
+            code_section_offset: it.code_section_offset,
             num_imported_functions: it.num_imported_functions,
             num_imported_tables: it.num_imported_tables,
             num_imported_memories: it.num_imported_memories,
             num_imported_globals: it.num_imported_globals,
+            num_imported_tags: it.num_imported_tags,
         }
     }
 }
@@ -319,12 +339,15 @@ impl PartialEq for ModuleInfo {
             && self.tables == other.tables
             && self.memories == other.memories
             && self.globals == other.globals
+            && self.tags == other.tags
             && self.custom_sections == other.custom_sections
             && self.custom_sections_data == other.custom_sections_data
+            && self.code_section_offset == other.code_section_offset
             && self.num_imported_functions == other.num_imported_functions
             && self.num_imported_tables == other.num_imported_tables
             && self.num_imported_memories == other.num_imported_memories
             && self.num_imported_globals == other.num_imported_globals
+            && self.num_imported_tags == other.num_imported_tags
     }
 }
 
@@ -382,6 +405,7 @@ impl ModuleInfo {
                     let global_type = self.globals.get(*i).unwrap();
                     ExternType::Global(*global_type)
                 }
+                ExportIndex::Tag(i) => ExternType::Tag(self.tag_type(*i)),
             };
             ExportType::new(name, extern_type)
         });
@@ -412,6 +436,7 @@ impl ModuleInfo {
                             let global_type = self.globals.get(*i).unwrap();
                             ExternType::Global(*global_type)
                         }
+                        ImportIndex::Tag(i) => ExternType::Tag(self.tag_type(*i)),
                     };
                     ImportType::new(module, field, extern_type)
                 });
@@ -505,6 +530,29 @@ impl ModuleInfo {
         index.index() < self.num_imported_globals
     }
 
+    /// Convert a `LocalTagIndex` into a `TagIndex`.
+    pub fn tag_index(&self, local_tag: LocalTagIndex) -> TagIndex {
+        TagIndex::new(self.num_imported_tags + local_tag.index())
+    }
+
+    /// Convert a `TagIndex` into a `LocalTagIndex`. Returns None if the
+    /// index is an imported tag.
+    pub fn local_tag_index(&self, tag: TagIndex) -> Option<LocalTagIndex> {
+        tag.index()
+            .checked_sub(self.num_imported_tags)
+            .map(LocalTagIndex::new)
+    }
+
+    /// Test whether the given tag index is for an imported tag.
+    pub fn is_imported_tag(&self, index: TagIndex) -> bool {
+        index.index() < self.num_imported_tags
+    }
+
+    /// Get the type of the given tag.
+    pub fn tag_type(&self, index: TagIndex) -> TagType {
+        TagType::new(self.signatures[self.tags[index]].params())
+    }
+
     /// Get the Module name
     pub fn name(&self) -> String {
         match self.name {
@@ -581,6 +629,13 @@ impl<I: Iterator<Item = ExportType> + Sized> ExportsIterator<I> {
             _ => None,
         })
     }
+    /// Get only the tags
+    pub fn tags(self) -> impl Iterator<Item = ExportType<TagType>> + Sized {
+        self.iter.filter_map(|extern_| match extern_.ty() {
+            ExternType::Tag(ty) => Some(ExportType::new(extern_.name(), ty.clone())),
+            _ => None,
+        })
+    }
 }
 
 impl<I: Iterator<Item = ExportType> + Sized> Iterator for ExportsIterator<I> {
@@ -644,6 +699,17 @@ impl<I: Iterator<Item = ImportType> + Sized> ImportsIterator<I> {
             _ => None,
         })
     }
+    /// Get only the tags
+    pub fn tags(self) -> impl Iterator<Item = ImportType<TagType>> + Sized {
+        self.iter.filter_map(|extern_| match extern_.ty() {
+            ExternType::Tag(ty) => Some(ImportType::new(
+                extern_.module(),
+                extern_.name(),
+                ty.clone(),
+            )),
+            _ => None,
+        })
+    }
 }
 
 impl<I: Iterator<Item = ImportType> + Sized> Iterator for ImportsIterator<I> {
@@ -0,0 +1,179 @@
+//! Profiles recorded by code compiled with profile instrumentation, to
+//! optimize later compilations of the same module.
+
+use crate::entity::EntityRef;
+use crate::error::ParseProfileError;
+use crate::LocalFunctionIndex;
+use std::collections::BTreeMap;
+use std::fmt::{self, Display, Formatter};
+use std::str::FromStr;
+
+/// The first line of a profile in text form.
+const HEADER: &str = "wasmer-profile 1";
+
+/// The counts recorded by the functions of a module compiled with profile
+/// instrumentation.
+///
+/// The counters of a function are the number of its calls, followed by the
+/// number of times each of its conditional branches (`if` and `br_if`, in
+/// the order of the code) was taken and not taken.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Profile {
+    functions: BTreeMap<LocalFunctionIndex, Vec<u64>>,
+}
+
+impl Profile {
+    /// Creates an empty profile.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Sets the counters of the function at `index`.
+    pub fn insert(&mut self, index: LocalFunctionIndex, counters: Vec<u64>) {
+        self.functions.insert(index, counters);
+    }
+
+    /// The counts of the function at `index`, if it was called.
+    pub fn function(&self, index: LocalFunctionIndex) -> Option<FunctionProfile<'_>> {
+        self.functions
+            .get(&index)
+            .map(|counters| FunctionProfile { counters })
+    }
+
+    /// The largest number of calls of a function.
+    pub fn max_entry_count(&self) -> u64 {
+        self.functions
+            .values()
+            .filter_map(|counters| counters.first().copied())
+            .max()
+            .unwrap_or(0)
+    }
+
+    /// Adds the counts of `other`, recorded by another run of the same
+    /// module.
+    pub fn merge(&mut self, other: &Self) {
+        for (index, counters) in &other.functions {
+            let merged = self.functions.entry(*index).or_default();
+            if merged.len() < counters.len() {
+                merged.resize(counters.len(), 0);
+            }
+            for (merged, count) in merged.iter_mut().zip(counters) {
+                *merged = merged.saturating_add(*count);
+            }
+        }
+    }
+}
+
+/// The counts recorded by a function.
+#[derive(Debug, Clone, Copy)]
+pub struct FunctionProfile<'a> {
+    counters: &'a [u64],
+}
+
+impl<'a> FunctionProfile<'a> {
+    /// The number of calls of the function.
+    pub fn entry_count(&self) -> u64 {
+        self.counters.first().copied().unwrap_or(0)
+    }
+
+    /// The number of conditional branches of the function.
+    pub fn num_branches(&self) -> usize {
+        self.counters.len().saturating_sub(1) / 2
+    }
+
+    /// The number of times the conditional branch at `index` was taken and
+    /// not taken.
+    pub fn branch(&self, index: usize) -> Option<(u64, u64)> {
+        let taken = *self.counters.get(1 + 2 * index)?;
+        let not_taken = *self.counters.get(2 + 2 * index)?;
+        Some((taken, not_taken))
+    }
+}
+
+impl Display for Profile {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        writeln!(f, "{}", HEADER)?;
+        for (index, counters) in &self.functions {
+            write!(f, "{}", index.index())?;
+            for counter in counters {
+                write!(f, " {}", counter)?;
+            }
+            writeln!(f)?;
+        }
+        Ok(())
+    }
+}
+
+impl FromStr for Profile {
+    type Err = ParseProfileError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mut lines = s.lines();
+        if lines.next() != Some(HEADER) {
+            return Err(ParseProfileError::Header);
+        }
+        let mut profile = Self::new();
+        for (line_number, line) in lines.enumerate() {
+            let invalid = || ParseProfileError::Line(line_number + 2);
+            let mut numbers = line.split_whitespace();
+            let index = numbers
+                .next()
+                .ok_or_else(invalid)?
+                .parse::<usize>()
+                .map_err(|_| invalid())?;
+            let counters = numbers
+                .map(|counter| counter.parse::<u64>().map_err(|_| invalid()))
+                .collect::<Result<Vec<_>, _>>()?;
+            profile.insert(LocalFunctionIndex::new(index), counters);
+        }
+        Ok(profile)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn roundtrip() {
+        let mut profile = Profile::new();
+        profile.insert(LocalFunctionIndex::new(0), vec![10, 7, 3]);
+        profile.insert(LocalFunctionIndex::new(3), vec![1]);
+
+        let text = profile.to_string();
+        assert_eq!(text, "wasmer-profile 1\n0 10 7 3\n3 1\n");
+        assert_eq!(text.parse::<Profile>().unwrap(), profile);
+
+        let function = profile.function(LocalFunctionIndex::new(0)).unwrap();
+        assert_eq!(function.entry_count(), 10);
+        assert_eq!(function.num_branches(), 1);
+        assert_eq!(function.branch(0), Some((7, 3)));
+        assert_eq!(function.branch(1), None);
+        assert!(profile.function(LocalFunctionIndex::new(1)).is_none());
+        assert_eq!(profile.max_entry_count(), 10);
+    }
+
+    #[test]
+    fn merge() {
+        let mut profile = Profile::new();
+        profile.insert(LocalFunctionIndex::new(0), vec![1, 1, 0]);
+        let mut other = Profile::new();
+        other.insert(LocalFunctionIndex::new(0), vec![2, 0, 2]);
+        other.insert(LocalFunctionIndex::new(1), vec![5]);
+
+        profile.merge(&other);
+        assert_eq!(profile.to_string(), "wasmer-profile 1\n0 3 1 2\n1 5\n");
+    }
+
+    #[test]
+    fn invalid() {
+        assert!(matches!(
+            "0 1 2".parse::<Profile>(),
+            Err(ParseProfileError::Header)
+        ));
+        assert!(matches!(
+            "wasmer-profile 1\n0 1\nx 2\n".parse::<Profile>(),
+            Err(ParseProfileError::Line(3))
+        ));
+    }
+}
@@ -16,7 +16,7 @@ use std::convert::TryInto;
 use std::mem;
 
 /// The compilation related data for a serialized modules
-#[derive(Archive, Default, RkyvDeserialize, RkyvSerialize)]
+#[derive(Archive, Clone, Default, RkyvDeserialize, RkyvSerialize)]
 #[allow(missing_docs)]
 #[archive_attr(derive(CheckBytes, Debug))]
 pub struct SerializableCompilation {
@@ -33,9 +33,71 @@ pub struct SerializableCompilation {
     pub libcall_trampolines: SectionIndex,
     // Length of each libcall trampoline.
     pub libcall_trampoline_len: u32,
+    // Whether the function bodies are interpreter bytecode.
+    pub interpreted: bool,
+    // Whether the functions count their calls and may be redirected to
+    // code compiled later.
+    pub tier_up: bool,
+    // The wasm binary of the module, if its functions are compiled on their
+    // first call.
+    pub lazy_binary: Option<Vec<u8>>,
+    // The functions of a lazily compiled module compiled so far.
+    pub lazy_functions: Vec<SerializableLazyFunction>,
+    // Whether the functions record a profile while they run.
+    pub profiled: bool,
+}
+
+/// A function of a lazily compiled module, compiled on its first call.
+#[derive(Archive, Clone, RkyvDeserialize, RkyvSerialize)]
+#[archive_attr(derive(CheckBytes, Debug))]
+pub struct SerializableLazyFunction {
+    /// The index of the function.
+    pub index: LocalFunctionIndex,
+    /// The compiled body of the function.
+    pub body: FunctionBody,
+    /// The relocations of the body.
+    pub relocations: Vec<Relocation>,
+    /// The frame info of the function.
+    pub frame_info: CompiledFunctionFrameInfo,
+    /// The section holding the unwind info of the function, if any.
+    pub eh_frame: Option<CustomSection>,
 }
 
 impl SerializableCompilation {
+    /// Put the compilation in its canonical form, so that compiling a
+    /// module twice with the same configuration serializes to the same
+    /// bytes, whatever order the compiler emitted things in.
+    ///
+    /// Functions, trampolines and custom sections are already ordered by
+    /// their index. This sorts the relocations by offset, and the functions
+    /// of a lazily compiled module, which are recorded in the order they
+    /// were first called, by index.
+    pub fn canonicalize(&mut self) {
+        let relocations = self
+            .function_relocations
+            .values_mut()
+            .chain(self.custom_section_relocations.values_mut())
+            .chain(
+                self.custom_sections
+                    .values_mut()
+                    .map(|section| &mut section.relocations),
+            );
+        for relocations in relocations {
+            relocations.sort_by_key(|relocation| relocation.offset);
+        }
+        for function in &mut self.lazy_functions {
+            function
+                .relocations
+                .sort_by_key(|relocation| relocation.offset);
+            if let Some(eh_frame) = &mut function.eh_frame {
+                eh_frame
+                    .relocations
+                    .sort_by_key(|relocation| relocation.offset);
+            }
+        }
+        self.lazy_functions.sort_by_key(|function| function.index);
+    }
+
     /// Serialize a Compilation into bytes
     /// The bytes will have the following format:
     /// RKYV serialization (any length) + POS (8 bytes)
@@ -51,7 +113,7 @@ impl SerializableCompilation {
 }
 
 /// Serializable struct that is able to serialize from and to a `ArtifactInfo`.
-#[derive(Archive, RkyvDeserialize, RkyvSerialize)]
+#[derive(Archive, Clone, RkyvDeserialize, RkyvSerialize)]
 #[allow(missing_docs)]
 #[archive_attr(derive(CheckBytes, Debug))]
 pub struct SerializableModule {
@@ -63,6 +125,11 @@ pub struct SerializableModule {
     pub data_initializers: Box<[OwnedDataInitializer]>,
     /// CPU Feature flags for this compilation
     pub cpu_features: u64,
+    /// Target triple for this compilation
+    pub triple: String,
+    /// Deterministic id of the compiler used for this compilation, empty
+    /// if it isn't known
+    pub compiler: String,
 }
 
 fn to_serialize_error(err: impl std::error::Error) -> SerializeError {
@@ -211,7 +278,7 @@ pub struct MetadataHeader {
 impl MetadataHeader {
     /// Current ABI version. Increment this any time breaking changes are made
     /// to the format of the serialized data.
-    pub const CURRENT_VERSION: u32 = 7;
+    pub const CURRENT_VERSION: u32 = 22;
 
     /// Magic number to identify wasmer metadata.
     const MAGIC: [u8; 8] = *b"WASMER\0\0";
@@ -1,4 +1,6 @@
 use crate::SourceLoc;
+use std::fmt;
+use std::sync::Arc;
 
 /// Description of a frame in a backtrace.
 ///
@@ -16,6 +18,8 @@ pub struct FrameInfo {
     func_start: SourceLoc,
     /// The source location of the instruction
     instr: SourceLoc,
+    /// The map to the guest's source code, if the module has one
+    source_map: Option<Arc<dyn SourceMap>>,
 }
 
 impl FrameInfo {
@@ -33,9 +37,16 @@ impl FrameInfo {
             function_name,
             func_start,
             instr,
+            source_map: None,
         }
     }
 
+    /// Attaches the map to the guest's source code to this frame.
+    pub fn with_source_map(mut self, source_map: Arc<dyn SourceMap>) -> Self {
+        self.source_map = Some(source_map);
+        self
+    }
+
     /// Returns the WebAssembly function index for this frame.
     ///
     /// This function index is the index in the function index space of the
@@ -93,4 +104,84 @@ impl FrameInfo {
     pub fn func_offset(&self) -> usize {
         (self.instr.bits() - self.func_start.bits()) as usize
     }
+
+    /// Returns the locations in the guest's source code this frame's program
+    /// counter maps to.
+    ///
+    /// These come from the DWARF debug information embedded in the module,
+    /// so this is empty when the module was built without it. When functions
+    /// were inlined, there is a location for each of them: the innermost
+    /// inlined function comes first and the function the frame belongs to
+    /// comes last.
+    ///
+    /// The locations are looked up on each call, as this is only needed to
+    /// display the backtrace.
+    pub fn source_locations(&self) -> Vec<SourceLocation> {
+        match &self.source_map {
+            Some(source_map) => source_map.source_locations(self.module_offset()),
+            None => Vec::new(),
+        }
+    }
+}
+
+/// Maps the instructions of a WebAssembly module to the source code it was
+/// compiled from.
+pub trait SourceMap: fmt::Debug + Send + Sync {
+    /// Returns the source locations of the instruction at `module_offset`,
+    /// the innermost inlined function first.
+    fn source_locations(&self, module_offset: usize) -> Vec<SourceLocation>;
+}
+
+/// A location in the source code a WebAssembly module was compiled from, as
+/// described by its DWARF debug information.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SourceLocation {
+    /// The name of the function, possibly mangled
+    function_name: Option<String>,
+    /// The path of the source file
+    file: Option<String>,
+    /// The line in the source file
+    line: Option<u32>,
+    /// The column in the line
+    column: Option<u32>,
+}
+
+impl SourceLocation {
+    /// Creates a new [SourceLocation].
+    pub fn new(
+        function_name: Option<String>,
+        file: Option<String>,
+        line: Option<u32>,
+        column: Option<u32>,
+    ) -> Self {
+        Self {
+            function_name,
+            file,
+            line,
+            column,
+        }
+    }
+
+    /// Returns the name of the source function, if known.
+    ///
+    /// This is the name found in the debug information, which is usually
+    /// mangled for languages like Rust or C++.
+    pub fn function_name(&self) -> Option<&str> {
+        self.function_name.as_deref()
+    }
+
+    /// Returns the path of the source file, if known.
+    pub fn file(&self) -> Option<&str> {
+        self.file.as_deref()
+    }
+
+    /// Returns the line in the source file, if known.
+    pub fn line(&self) -> Option<u32> {
+        self.line
+    }
+
+    /// Returns the column in the line, if known.
+    pub fn column(&self) -> Option<u32> {
+        self.column
+    }
 }
@@ -4,6 +4,6 @@ mod frame;
 mod sourceloc;
 mod trap;
 
-pub use frame::FrameInfo;
+pub use frame::{FrameInfo, SourceLocation, SourceMap};
 pub use sourceloc::SourceLoc;
 pub use trap::TrapInformation;
@@ -69,6 +69,9 @@ pub enum TrapCode {
 
     /// An atomic memory access was attempted with an unaligned pointer.
     UnalignedAtomic = 10,
+
+    /// A null reference was used where a non-null one is required.
+    NullReference = 11,
 }
 
 impl TrapCode {
@@ -86,6 +89,7 @@ impl TrapCode {
             Self::BadConversionToInteger => "invalid conversion to integer",
             Self::UnreachableCodeReached => "unreachable",
             Self::UnalignedAtomic => "unaligned atomic access",
+            Self::NullReference => "null reference",
         }
     }
 }
@@ -104,6 +108,7 @@ impl Display for TrapCode {
             Self::BadConversionToInteger => "bad_toint",
             Self::UnreachableCodeReached => "unreachable",
             Self::UnalignedAtomic => "unalign_atom",
+            Self::NullReference => "null_ref",
         };
         f.write_str(identifier)
     }
@@ -125,6 +130,7 @@ impl FromStr for TrapCode {
             "bad_toint" => Ok(Self::BadConversionToInteger),
             "unreachable" => Ok(Self::UnreachableCodeReached),
             "unalign_atom" => Ok(Self::UnalignedAtomic),
+            "null_ref" => Ok(Self::NullReference),
             _ => Err(()),
         }
     }
@@ -4,7 +4,7 @@ use crate::lib::std::fmt;
 use crate::lib::std::format;
 use crate::lib::std::string::{String, ToString};
 use crate::lib::std::vec::Vec;
-use crate::units::Pages;
+use crate::units::{Pages, WASM64_MAX_PAGES};
 
 use bytecheck::CheckBytes;
 use rkyv::{Archive, Deserialize as RkyvDeserialize, Serialize as RkyvSerialize};
@@ -36,6 +36,8 @@ pub enum Type {
     ExternRef, /* = 128 */
     /// A reference to a Wasm function.
     FuncRef,
+    /// A reference to a Wasm exception.
+    ExceptionRef,
 }
 
 impl Type {
@@ -50,7 +52,7 @@ impl Type {
 
     /// Returns true if `Type` matches either of the reference types.
     pub fn is_ref(self) -> bool {
-        matches!(self, Self::ExternRef | Self::FuncRef)
+        matches!(self, Self::ExternRef | Self::FuncRef | Self::ExceptionRef)
     }
 }
 
@@ -121,6 +123,8 @@ pub enum ExternType {
     Table(TableType),
     /// This external type is the type of a WebAssembly memory.
     Memory(MemoryType),
+    /// This external type is the type of a WebAssembly exception tag.
+    Tag(TagType),
 }
 
 fn is_global_compatible(exported: GlobalType, imported: GlobalType) -> bool {
@@ -175,11 +179,13 @@ fn is_memory_compatible(
         minimum: exported_minimum,
         maximum: exported_maximum,
         shared: exported_shared,
+        memory64: exported_memory64,
     } = exported;
     let MemoryType {
         minimum: imported_minimum,
         maximum: imported_maximum,
         shared: imported_shared,
+        memory64: imported_memory64,
     } = imported;
 
     imported_minimum.0 <= imported_runtime_size.unwrap_or(exported_minimum.0)
@@ -187,6 +193,7 @@ fn is_memory_compatible(
             || (!exported_maximum.is_none()
                 && imported_maximum.unwrap() >= exported_maximum.unwrap()))
         && exported_shared == imported_shared
+        && exported_memory64 == imported_memory64
 }
 
 macro_rules! accessors {
@@ -219,6 +226,7 @@ impl ExternType {
         (Global(GlobalType) global unwrap_global)
         (Table(TableType) table unwrap_table)
         (Memory(MemoryType) memory unwrap_memory)
+        (Tag(TagType) tag unwrap_tag)
     }
     /// Check if two externs are compatible
     pub fn is_compatible_with(&self, other: &Self, runtime_size: Option<u32>) -> bool {
@@ -227,6 +235,7 @@ impl ExternType {
             (Self::Global(a), Self::Global(b)) => is_global_compatible(*a, *b),
             (Self::Table(a), Self::Table(b)) => is_table_compatible(a, b, runtime_size),
             (Self::Memory(a), Self::Memory(b)) => is_memory_compatible(a, b, runtime_size),
+            (Self::Tag(a), Self::Tag(b)) => a == b,
             // The rest of possibilities, are not compatible
             _ => false,
         }
@@ -479,6 +488,51 @@ impl fmt::Display for TableType {
     }
 }
 
+// Tag Types
+
+/// A descriptor for a WebAssembly exception tag.
+///
+/// Tags identify the exceptions thrown by `throw` and caught by
+/// `try_table`, and describe the values carried by these exceptions.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "enable-serde", derive(Serialize, Deserialize))]
+#[derive(RkyvSerialize, RkyvDeserialize, Archive)]
+#[archive_attr(derive(CheckBytes, Debug))]
+pub struct TagType {
+    /// The types of the values carried by the exceptions of this tag.
+    params: Box<[Type]>,
+}
+
+impl TagType {
+    /// Creates a new tag descriptor for exceptions carrying values of the
+    /// given types.
+    pub fn new<Params>(params: Params) -> Self
+    where
+        Params: Into<Box<[Type]>>,
+    {
+        Self {
+            params: params.into(),
+        }
+    }
+
+    /// The types of the values carried by the exceptions of this tag.
+    pub fn params(&self) -> &[Type] {
+        &self.params
+    }
+}
+
+impl fmt::Display for TagType {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let params = self
+            .params
+            .iter()
+            .map(|p| format!("{:?}", p))
+            .collect::<Vec<_>>()
+            .join(", ");
+        write!(f, "[{}]", params)
+    }
+}
+
 // Memory Types
 
 /// A descriptor for a WebAssembly memory type.
@@ -496,6 +550,9 @@ pub struct MemoryType {
     pub maximum: Option<Pages>,
     /// Whether the memory may be shared between multiple threads.
     pub shared: bool,
+    /// Whether the memory is indexed with 64-bit addresses, as defined by
+    /// the memory64 proposal.
+    pub memory64: bool,
 }
 
 impl MemoryType {
@@ -509,6 +566,22 @@ impl MemoryType {
             minimum: minimum.into(),
             maximum: maximum.map(Into::into),
             shared,
+            memory64: false,
+        }
+    }
+
+    /// Turns this descriptor into the one of a 64-bit memory.
+    pub fn with_memory64(mut self, memory64: bool) -> Self {
+        self.memory64 = memory64;
+        self
+    }
+
+    /// Returns the largest number of pages this memory can ever hold.
+    pub fn max_pages(&self) -> Pages {
+        if self.memory64 {
+            Pages(WASM64_MAX_PAGES)
+        } else {
+            Pages::max_value()
         }
     }
 }
@@ -516,10 +589,11 @@ impl MemoryType {
 impl fmt::Display for MemoryType {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
         let shared = if self.shared { "shared" } else { "not shared" };
+        let index = if self.memory64 { "i64 " } else { "" };
         if let Some(maximum) = self.maximum {
-            write!(f, "{} ({:?}..{:?})", shared, self.minimum, maximum)
+            write!(f, "{}{} ({:?}..{:?})", index, shared, self.minimum, maximum)
         } else {
-            write!(f, "{} ({:?}..)", shared, self.minimum)
+            write!(f, "{}{} ({:?}..)", index, shared, self.minimum)
         }
     }
 }
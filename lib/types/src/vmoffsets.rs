@@ -140,9 +140,88 @@ impl VMBuiltinFunctionIndex {
     pub const fn get_imported_memory_atomic_notify_index() -> Self {
         Self(29)
     }
+    /// Returns an index for the memory watch of loads.
+    pub const fn get_memory_watch_load_index() -> Self {
+        Self(30)
+    }
+    /// Returns an index for the memory watch of stores.
+    pub const fn get_memory_watch_store_index() -> Self {
+        Self(31)
+    }
+    /// Returns an index for wasm's `throw` builtin function.
+    pub const fn get_throw_index() -> Self {
+        Self(32)
+    }
+    /// Returns an index for wasm's `throw_ref` builtin function.
+    pub const fn get_throw_ref_index() -> Self {
+        Self(33)
+    }
+    /// Returns an index for the builtin function catching the pending
+    /// exception in a `try_table`.
+    pub const fn get_catch_index() -> Self {
+        Self(34)
+    }
+    /// Returns an index for wasm's `memory.grow` builtin function on 64-bit
+    /// memories.
+    pub const fn get_memory64_grow_index() -> Self {
+        Self(35)
+    }
+    /// Returns an index for wasm's imported `memory.grow` builtin function on
+    /// 64-bit memories.
+    pub const fn get_imported_memory64_grow_index() -> Self {
+        Self(36)
+    }
+    /// Returns an index for wasm's `memory.size` builtin function on 64-bit
+    /// memories.
+    pub const fn get_memory64_size_index() -> Self {
+        Self(37)
+    }
+    /// Returns an index for wasm's imported `memory.size` builtin function on
+    /// 64-bit memories.
+    pub const fn get_imported_memory64_size_index() -> Self {
+        Self(38)
+    }
+    /// Returns an index for wasm's `memory.copy` for locally defined 64-bit
+    /// memories.
+    pub const fn get_memory64_copy_index() -> Self {
+        Self(39)
+    }
+    /// Returns an index for wasm's `memory.copy` for imported 64-bit memories.
+    pub const fn get_imported_memory64_copy_index() -> Self {
+        Self(40)
+    }
+    /// Returns an index for wasm's `memory.fill` for locally defined 64-bit
+    /// memories.
+    pub const fn get_memory64_fill_index() -> Self {
+        Self(41)
+    }
+    /// Returns an index for wasm's `memory.fill` for imported 64-bit memories.
+    pub const fn get_imported_memory64_fill_index() -> Self {
+        Self(42)
+    }
+    /// Returns an index for wasm's `memory.init` instruction on 64-bit
+    /// memories.
+    pub const fn get_memory64_init_index() -> Self {
+        Self(43)
+    }
+    /// Returns an index for the builtin function compiling a function of
+    /// a lazily compiled module on its first call.
+    pub const fn get_lazy_compile_index() -> Self {
+        Self(44)
+    }
+    /// Returns an index for the builtin function counting an event of a
+    /// function compiled with profile instrumentation.
+    pub const fn get_profile_count_index() -> Self {
+        Self(45)
+    }
+    /// Returns an index for the builtin function calling the attached
+    /// debugger before an instruction of a function compiled for debugging.
+    pub const fn get_debug_hook_index() -> Self {
+        Self(46)
+    }
     /// Returns the total number of builtin functions.
     pub const fn builtin_functions_total_number() -> u32 {
-        30
+        47
     }
 
     /// Return the index as an u32 number.
@@ -188,6 +267,8 @@ pub struct VMOffsets {
     num_local_memories: u32,
     /// The number of defined globals in the module.
     num_local_globals: u32,
+    /// The largest number of parameters of the module signatures.
+    num_tail_call_args: u32,
 
     vmctx_signature_ids_begin: u32,
     vmctx_imported_functions_begin: u32,
@@ -202,6 +283,10 @@ pub struct VMOffsets {
     vmctx_gas_limiter_pointer: u32,
     vmctx_stack_limit_begin: u32,
     vmctx_stack_limit_initial_begin: u32,
+    vmctx_pending_exception_pointer: u32,
+    vmctx_function_tiers_pointer: u32,
+    vmctx_memory_watch_pointer: u32,
+    vmctx_tail_call_begin: u32,
     size_of_vmctx: u32,
 }
 
@@ -218,6 +303,14 @@ impl VMOffsets {
             num_local_tables: cast_to_u32(module.tables.len()),
             num_local_memories: cast_to_u32(module.memories.len()),
             num_local_globals: cast_to_u32(module.globals.len()),
+            num_tail_call_args: cast_to_u32(
+                module
+                    .signatures
+                    .values()
+                    .map(|signature| signature.params().len())
+                    .max()
+                    .unwrap_or(0),
+            ),
             vmctx_signature_ids_begin: 0,
             vmctx_imported_functions_begin: 0,
             vmctx_imported_tables_begin: 0,
@@ -231,6 +324,10 @@ impl VMOffsets {
             vmctx_gas_limiter_pointer: 0,
             vmctx_stack_limit_begin: 0,
             vmctx_stack_limit_initial_begin: 0,
+            vmctx_pending_exception_pointer: 0,
+            vmctx_function_tiers_pointer: 0,
+            vmctx_memory_watch_pointer: 0,
+            vmctx_tail_call_begin: 0,
             size_of_vmctx: 0,
         };
         ret.precompute();
@@ -252,6 +349,7 @@ impl VMOffsets {
             num_local_tables: 0,
             num_local_memories: 0,
             num_local_globals: 0,
+            num_tail_call_args: 0,
             vmctx_signature_ids_begin: 0,
             vmctx_imported_functions_begin: 0,
             vmctx_imported_tables_begin: 0,
@@ -265,6 +363,10 @@ impl VMOffsets {
             vmctx_gas_limiter_pointer: 0,
             vmctx_stack_limit_begin: 0,
             vmctx_stack_limit_initial_begin: 0,
+            vmctx_pending_exception_pointer: 0,
+            vmctx_function_tiers_pointer: 0,
+            vmctx_memory_watch_pointer: 0,
+            vmctx_tail_call_begin: 0,
             size_of_vmctx: 0,
         }
     }
@@ -356,7 +458,29 @@ impl VMOffsets {
             u32::from(self.pointer_size),
         );
         self.vmctx_stack_limit_initial_begin = self.vmctx_stack_limit_begin.checked_add(4).unwrap();
-        self.size_of_vmctx = self.vmctx_stack_limit_begin.checked_add(4).unwrap();
+        self.vmctx_pending_exception_pointer = align(
+            self.vmctx_stack_limit_initial_begin.checked_add(4).unwrap(),
+            u32::from(self.pointer_size),
+        );
+        self.vmctx_function_tiers_pointer = self
+            .vmctx_pending_exception_pointer
+            .checked_add(u32::from(self.pointer_size))
+            .unwrap();
+        self.vmctx_memory_watch_pointer = self
+            .vmctx_function_tiers_pointer
+            .checked_add(u32::from(self.pointer_size))
+            .unwrap();
+        self.vmctx_tail_call_begin = align(
+            self.vmctx_memory_watch_pointer
+                .checked_add(u32::from(self.pointer_size))
+                .unwrap(),
+            16,
+        );
+        self.size_of_vmctx = offset_by(
+            self.vmctx_tail_call_args(),
+            self.num_tail_call_args,
+            u32::from(self.size_of_vmtail_call_arg()),
+        );
     }
 }
 
@@ -589,6 +713,24 @@ impl VMOffsets {
     }
 }
 
+/// Offsets for `VMFunctionTier`.
+impl VMOffsets {
+    /// The offset of the `redirect` field.
+    pub const fn vmfunction_tier_redirect(&self) -> u8 {
+        0
+    }
+
+    /// The offset of the `calls` field.
+    pub const fn vmfunction_tier_calls(&self) -> u8 {
+        8
+    }
+
+    /// Return the size of `VMFunctionTier`.
+    pub const fn size_of_vmfunction_tier(&self) -> u8 {
+        16
+    }
+}
+
 /// Offsets for `VMContext`.
 impl VMOffsets {
     /// The offset of the `signature_ids` array.
@@ -643,6 +785,66 @@ impl VMOffsets {
         self.size_of_vmctx
     }
 
+    /// The offset of the pointer to the exception being thrown in the store
+    /// of the instance, a store handle that is zero when no exception is
+    /// being thrown.
+    pub fn vmctx_pending_exception_pointer(&self) -> u32 {
+        self.vmctx_pending_exception_pointer
+    }
+
+    /// The offset of the pointer to the `VMFunctionTier`s of the local
+    /// functions, or null if the module wasn't compiled for tier-up.
+    pub fn vmctx_function_tiers_pointer(&self) -> u32 {
+        self.vmctx_function_tiers_pointer
+    }
+
+    /// The offset of the pointer to the bounds of the ranges watched by the
+    /// memory watch of the instance, a start and an end offset as two
+    /// `u64`. Instances without a memory watch point to empty bounds.
+    pub fn vmctx_memory_watch_pointer(&self) -> u32 {
+        self.vmctx_memory_watch_pointer
+    }
+
+    /// The offset of the function body of the pending tail call, or null
+    /// if no tail call is pending.
+    pub fn vmctx_tail_call_callee(&self) -> u32 {
+        self.vmctx_tail_call_begin
+    }
+
+    /// The offset of the vmctx to pass to the function of the pending tail call.
+    pub fn vmctx_tail_call_callee_vmctx(&self) -> u32 {
+        self.vmctx_tail_call_begin + u32::from(self.pointer_size)
+    }
+
+    /// The offset of the signature index of the pending tail call, a
+    /// 32-bit `SignatureIndex` of the module.
+    pub fn vmctx_tail_call_signature(&self) -> u32 {
+        self.vmctx_tail_call_begin + 2 * u32::from(self.pointer_size)
+    }
+
+    /// The offset of the 32-bit flag set by a function running tail calls
+    /// right before calling a function of the same instance. The callee may
+    /// then leave its own tail calls pending instead of running them.
+    pub fn vmctx_tail_call_driven(&self) -> u32 {
+        self.vmctx_tail_call_signature() + 4
+    }
+
+    /// The offset of the arguments of the pending tail call.
+    pub fn vmctx_tail_call_args(&self) -> u32 {
+        align(self.vmctx_tail_call_driven() + 4, 16)
+    }
+
+    /// The offset of the tail call state, which extends until the end of
+    /// the `VMContext`.
+    pub fn vmctx_tail_call_begin(&self) -> u32 {
+        self.vmctx_tail_call_begin
+    }
+
+    /// The size of each argument of the pending tail call.
+    pub const fn size_of_vmtail_call_arg(&self) -> u8 {
+        16
+    }
+
     /// Return the offset to `VMSharedSignatureIndex` index `index`.
     pub fn vmctx_vmshared_signature_id(&self, index: SignatureIndex) -> u32 {
         assert_lt!(index.as_u32(), self.num_signature_ids);
@@ -0,0 +1,144 @@
+//! Support for debugging guests.
+//!
+//! While a [`Debugger`] is attached, the functions compiled for debugging
+//! (see `CompilerConfig::enable_debugging`) call it before running each
+//! instruction, with access to the state of the current frame. The
+//! interpreter does so directly, and Singlepass through the
+//! [`wasmer_vm_debug_hook`] builtin. Stopping at a breakpoint is then just a
+//! matter of not returning until the user resumes execution.
+//!
+//! [`wasmer_vm_debug_hook`]: crate::libcalls::wasmer_vm_debug_hook
+
+use crate::vmcontext::VMContext;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, RwLock};
+use wasmer_types::{FunctionIndex, GlobalIndex, MemoryIndex, ModuleInfo, RawValue, Type};
+
+/// A debugger for the functions compiled for debugging.
+pub trait Debugger: Send + Sync {
+    /// Called before each instruction runs, on the thread running it.
+    fn on_instruction(&self, frame: &mut DebugFrame<'_>);
+}
+
+static ATTACHED: AtomicBool = AtomicBool::new(false);
+static DEBUGGER: RwLock<Option<Arc<dyn Debugger>>> = RwLock::new(None);
+
+/// Attaches a debugger to all the code of the process compiled for
+/// debugging, replacing the previous one if any.
+pub fn attach_debugger(debugger: Arc<dyn Debugger>) {
+    *DEBUGGER.write().unwrap() = Some(debugger);
+    ATTACHED.store(true, Ordering::SeqCst);
+}
+
+/// Detaches the current debugger.
+pub fn detach_debugger() {
+    ATTACHED.store(false, Ordering::SeqCst);
+    DEBUGGER.write().unwrap().take();
+}
+
+/// Whether a debugger may be attached, checked before every instruction of
+/// the functions compiled for debugging.
+#[inline]
+pub(crate) fn is_attached() -> bool {
+    ATTACHED.load(Ordering::Relaxed)
+}
+
+pub(crate) fn debugger() -> Option<Arc<dyn Debugger>> {
+    DEBUGGER.read().unwrap().clone()
+}
+
+/// The state of the function a [`Debugger`] stopped in.
+///
+/// Values are exposed as their raw bits: `i32` and `f32` values use the low
+/// 32 bits, and references are pointers. Singlepass stores a `v128` local as
+/// two values, its low half first.
+pub struct DebugFrame<'a> {
+    pub(crate) vmctx: *mut VMContext,
+    pub(crate) function_index: u32,
+    pub(crate) module_offset: usize,
+    pub(crate) locals: &'a mut [u64],
+}
+
+impl DebugFrame<'_> {
+    /// The module of the function.
+    pub fn module(&self) -> &ModuleInfo {
+        unsafe { (*self.vmctx).instance().module_ref() }
+    }
+
+    /// The index of the function in its module.
+    pub fn function_index(&self) -> FunctionIndex {
+        FunctionIndex::from_u32(self.function_index)
+    }
+
+    /// The offset of the next instruction in the Wasm module.
+    pub fn module_offset(&self) -> usize {
+        self.module_offset
+    }
+
+    /// The parameters of the function followed by its locals.
+    pub fn locals(&self) -> &[u64] {
+        self.locals
+    }
+
+    /// The parameters and locals of the function, for modification.
+    pub fn locals_mut(&mut self) -> &mut [u64] {
+        self.locals
+    }
+
+    /// Reads a global of the instance, returning `None` if there is no such
+    /// global or if it doesn't fit in 64 bits.
+    pub fn global(&self, index: GlobalIndex) -> Option<u64> {
+        let ty = self.module().globals.get(index)?.ty;
+        unsafe {
+            let global = (*self.vmctx).instance().global_definition_ptr(index);
+            let raw = global.as_ref().val;
+            match ty {
+                Type::I32 | Type::F32 => Some(raw.u32 as u64),
+                Type::I64 | Type::F64 => Some(raw.u64),
+                Type::FuncRef => Some(raw.funcref as u64),
+                Type::ExternRef => Some(raw.externref as u64),
+                Type::V128 | Type::ExceptionRef => None,
+            }
+        }
+    }
+
+    /// Writes a global of the instance, returning `false` if there is no
+    /// such global or if it doesn't fit in 64 bits.
+    ///
+    /// Unlike `global.set`, this works on immutable globals too.
+    pub fn set_global(&mut self, index: GlobalIndex, value: u64) -> bool {
+        let raw = match self.module().globals.get(index).map(|global| global.ty) {
+            Some(Type::I32 | Type::F32) => RawValue { u32: value as u32 },
+            Some(Type::I64 | Type::F64) => RawValue { u64: value },
+            Some(Type::FuncRef) => RawValue {
+                funcref: value as usize,
+            },
+            Some(Type::ExternRef) => RawValue {
+                externref: value as usize,
+            },
+            Some(Type::V128 | Type::ExceptionRef) | None => return false,
+        };
+        unsafe {
+            let global = (*self.vmctx).instance().global_definition_ptr(index);
+            (*global.as_ptr()).val = raw;
+        }
+        true
+    }
+
+    /// The first linear memory of the instance, if any.
+    pub fn memory(&mut self) -> Option<&mut [u8]> {
+        if self.module().memories.is_empty() {
+            return None;
+        }
+        unsafe {
+            let memory = (*self.vmctx)
+                .instance()
+                .memory_definition_ptr(MemoryIndex::from_u32(0));
+            let memory = memory.as_ref();
+            Some(std::slice::from_raw_parts_mut(
+                memory.base,
+                memory.current_length,
+            ))
+        }
+    }
+}
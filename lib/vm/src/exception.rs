@@ -0,0 +1,75 @@
+//! Exceptions of the exception-handling proposal.
+//!
+//! Tags and exceptions are store objects. A thrown exception is recorded as
+//! the pending exception of the store: code compiled with exception
+//! handling enabled checks it after each call, and either catches it in a
+//! `try_table` or returns to its caller right away, until the exception
+//! reaches the host.
+
+use crate::store::InternalStoreHandle;
+use wasmer_types::{RawValue, TagType};
+
+/// A tag, identifying the exceptions thrown with it.
+#[derive(Debug)]
+pub struct VMTag {
+    ty: TagType,
+}
+
+impl VMTag {
+    /// Creates a new tag of the given type.
+    pub fn new(ty: TagType) -> Self {
+        Self { ty }
+    }
+
+    /// Returns the type of the tag.
+    pub fn ty(&self) -> &TagType {
+        &self.ty
+    }
+}
+
+/// An exception, thrown by Wasm code or by the host.
+#[derive(Debug)]
+pub struct VMException {
+    tag: InternalStoreHandle<VMTag>,
+    payload: Box<[RawValue]>,
+}
+
+impl VMException {
+    /// Creates a new exception of the given tag, carrying the values of
+    /// `payload`.
+    pub fn new(tag: InternalStoreHandle<VMTag>, payload: Box<[RawValue]>) -> Self {
+        Self { tag, payload }
+    }
+
+    /// Returns the tag of the exception.
+    pub fn tag(&self) -> InternalStoreHandle<VMTag> {
+        self.tag
+    }
+
+    /// Returns the values carried by the exception.
+    pub fn payload(&self) -> &[RawValue] {
+        &self.payload
+    }
+}
+
+/// Represents a reference to an exception within WebAssembly.
+#[repr(transparent)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct VMExceptionRef(pub InternalStoreHandle<VMException>);
+
+impl VMExceptionRef {
+    /// Converts the `VMExceptionRef` into a `RawValue`.
+    pub fn into_raw(self) -> RawValue {
+        RawValue {
+            exnref: self.0.index(),
+        }
+    }
+
+    /// Extracts a `VMExceptionRef` from a `RawValue`.
+    ///
+    /// # Safety
+    /// `raw` must be a valid `VMExceptionRef` instance.
+    pub unsafe fn from_raw(raw: RawValue) -> Option<Self> {
+        InternalStoreHandle::from_index(raw.exnref).map(Self)
+    }
+}
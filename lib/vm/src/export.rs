@@ -1,6 +1,7 @@
 // This file contains code from external sources.
 // Attributions: https://github.com/wasmerio/wasmer/blob/main/ATTRIBUTIONS.md
 
+use crate::exception::VMTag;
 use crate::global::VMGlobal;
 use crate::memory::VMMemory;
 use crate::store::InternalStoreHandle;
@@ -24,6 +25,9 @@ pub enum VMExtern {
 
     /// A global export value.
     Global(InternalStoreHandle<VMGlobal>),
+
+    /// A tag export value.
+    Tag(InternalStoreHandle<VMTag>),
 }
 
 /// A function export value.
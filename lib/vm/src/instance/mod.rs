@@ -10,18 +10,24 @@ mod allocator;
 
 use crate::export::VMExtern;
 use crate::imports::Imports;
+use crate::memory_watch::{MemoryWatchBounds, NO_MEMORY_WATCH_BOUNDS};
 use crate::store::{InternalStoreHandle, StoreObjects};
 use crate::table::TableElement;
 use crate::trap::{catch_traps, Trap, TrapCode};
 use crate::vmcontext::{
     memory32_atomic_check32, memory32_atomic_check64, memory_copy, memory_fill,
     VMBuiltinFunctionsArray, VMCallerCheckedAnyfunc, VMContext, VMFunctionContext,
-    VMFunctionImport, VMFunctionKind, VMGlobalDefinition, VMGlobalImport, VMMemoryDefinition,
-    VMMemoryImport, VMSharedSignatureIndex, VMTableDefinition, VMTableImport, VMTrampoline,
+    VMFunctionImport, VMFunctionKind, VMFunctionTier, VMGlobalDefinition, VMGlobalImport,
+    VMMemoryDefinition, VMMemoryImport, VMSharedSignatureIndex, VMTableDefinition, VMTableImport,
+    VMTrampoline,
+};
+use crate::{FunctionBodyPtr, MaybeInstanceOwned, TrapHandlerFn};
+use crate::{
+    LazyCompiler, LinearMemory, MemoryAccess, MemoryAccessKind, MemoryWatch, NotifyLocation,
+    VMProfile,
 };
-use crate::{FunctionBodyPtr, MaybeInstanceOwned, TrapHandlerFn, VMFunctionBody};
-use crate::{LinearMemory, NotifyLocation};
 use crate::{VMConfig, VMFuncRef, VMFunction, VMGlobal, VMMemory, VMTable};
+use crate::{VMException, VMExceptionRef, VMTag};
 pub use allocator::InstanceAllocator;
 use memoffset::offset_of;
 use more_asserts::assert_lt;
@@ -36,9 +42,10 @@ use std::slice;
 use std::sync::Arc;
 use wasmer_types::entity::{packed_option::ReservedValue, BoxedSlice, EntityRef, PrimaryMap};
 use wasmer_types::{
-    DataIndex, DataInitializer, ElemIndex, ExportIndex, FunctionIndex, GlobalIndex, GlobalInit,
-    LocalFunctionIndex, LocalGlobalIndex, LocalMemoryIndex, LocalTableIndex, MemoryError,
-    MemoryIndex, ModuleInfo, Pages, SignatureIndex, TableIndex, TableInitializer, VMOffsets,
+    CompileError, DataIndex, DataInitializer, ElemIndex, ExportIndex, FunctionIndex, GlobalIndex,
+    GlobalInit, LocalFunctionIndex, LocalGlobalIndex, LocalMemoryIndex, LocalTableIndex,
+    MemoryError, MemoryIndex, ModuleInfo, Pages, RawValue, SignatureIndex, TableIndex,
+    TableInitializer, TagIndex, TagType, VMOffsets,
 };
 
 /// A WebAssembly instance.
@@ -68,6 +75,9 @@ pub(crate) struct Instance {
     /// WebAssembly global data.
     globals: BoxedSlice<LocalGlobalIndex, InternalStoreHandle<VMGlobal>>,
 
+    /// WebAssembly tags, imported and local.
+    tags: BoxedSlice<TagIndex, InternalStoreHandle<VMTag>>,
+
     /// Pointers to functions in executable memory.
     functions: BoxedSlice<LocalFunctionIndex, FunctionBodyPtr>,
 
@@ -90,6 +100,22 @@ pub(crate) struct Instance {
     /// will point to elements here for functions imported by this instance.
     imported_funcrefs: BoxedSlice<FunctionIndex, NonNull<VMCallerCheckedAnyfunc>>,
 
+    /// Where to report the memory accesses of code compiled with the memory
+    /// watch enabled.
+    memory_watch: Option<Arc<MemoryWatch>>,
+
+    /// The tier-up state of the local functions, for code compiled to be
+    /// redirected to a later compilation of itself.
+    function_tiers: Option<Arc<[VMFunctionTier]>>,
+
+    /// Compiles the local functions of lazily compiled modules on their
+    /// first call.
+    lazy_compiler: Option<Arc<dyn LazyCompiler>>,
+
+    /// Where the local functions of modules compiled with profile
+    /// instrumentation count their calls and branches.
+    profile: Option<Arc<VMProfile>>,
+
     /// Additional context used by compiled WebAssembly code. This
     /// field is last, and represents a dynamically-sized array that
     /// extends beyond the nominal end of the struct (similar to a
@@ -308,6 +334,33 @@ impl Instance {
         unsafe { self.vmctx_plus_offset(self.offsets.vmctx_globals_begin()) }
     }
 
+    /// Return a pointer to the `VMMemoryDefinition` of a locally defined or
+    /// imported memory.
+    pub(crate) fn memory_definition_ptr(&self, index: MemoryIndex) -> NonNull<VMMemoryDefinition> {
+        if let Some(local_index) = self.module.local_memory_index(index) {
+            self.memory_ptr(local_index)
+        } else {
+            self.imported_memory(index).definition
+        }
+    }
+
+    /// Return a pointer to the `VMGlobalDefinition` of a locally defined or
+    /// imported global.
+    pub(crate) fn global_definition_ptr(&self, index: GlobalIndex) -> NonNull<VMGlobalDefinition> {
+        if let Some(local_index) = self.module.local_global_index(index) {
+            self.global_ptr(local_index)
+        } else {
+            self.imported_global(index).definition
+        }
+    }
+
+    /// Return the shared signature index of the given module signature.
+    #[cfg(feature = "interpreter")]
+    pub(crate) fn signature_id(&self, index: SignatureIndex) -> VMSharedSignatureIndex {
+        let index = usize::try_from(index.as_u32()).unwrap();
+        unsafe { *self.signature_ids_ptr().add(index) }
+    }
+
     /// Return a pointer to the `VMBuiltinFunctionsArray`.
     fn builtin_functions_ptr(&self) -> *mut VMBuiltinFunctionsArray {
         unsafe { self.vmctx_plus_offset(self.offsets.vmctx_builtin_functions_begin()) }
@@ -334,33 +387,24 @@ impl Instance {
             None => return Ok(()),
         };
 
-        let (callee_address, callee_vmctx) = match self.module.local_func_index(start_index) {
-            Some(local_index) => {
-                let body = self
-                    .functions
-                    .get(local_index)
-                    .expect("function index is out of bounds")
-                    .0;
-                (
-                    body as *const _,
-                    VMFunctionContext {
-                        vmctx: self.vmctx_ptr(),
-                    },
-                )
-            }
-            None => {
-                assert_lt!(start_index.index(), self.module.num_imported_functions);
-                let import = self.imported_function(start_index);
-                (import.body, import.environment)
-            }
+        // Go through the call trampoline rather than jumping to the function
+        // body directly: the body isn't native code for interpreted modules.
+        let anyfunc = unsafe {
+            *self
+                .func_ref(start_index)
+                .expect("start function index is valid")
+                .0
+                .as_ref()
         };
 
         // Make the call.
         unsafe {
             catch_traps(trap_handler, config, || {
-                mem::transmute::<*const VMFunctionBody, unsafe extern "C" fn(VMFunctionContext)>(
-                    callee_address,
-                )(callee_vmctx)
+                (anyfunc.call_trampoline)(
+                    anyfunc.vmctx.vmctx,
+                    anyfunc.func_ptr,
+                    ptr::NonNull::<RawValue>::dangling().as_ptr(),
+                )
             })
         }
     }
@@ -668,9 +712,9 @@ impl Instance {
     pub(crate) fn local_memory_copy(
         &self,
         memory_index: LocalMemoryIndex,
-        dst: u32,
-        src: u32,
-        len: u32,
+        dst: u64,
+        src: u64,
+        len: u64,
     ) -> Result<(), Trap> {
         // https://webassembly.github.io/reference-types/core/exec/instructions.html#exec-memory-copy
 
@@ -683,9 +727,9 @@ impl Instance {
     pub(crate) fn imported_memory_copy(
         &self,
         memory_index: MemoryIndex,
-        dst: u32,
-        src: u32,
-        len: u32,
+        dst: u64,
+        src: u64,
+        len: u64,
     ) -> Result<(), Trap> {
         let import = self.imported_memory(memory_index);
         let memory = unsafe { import.definition.as_ref() };
@@ -701,9 +745,9 @@ impl Instance {
     pub(crate) fn local_memory_fill(
         &self,
         memory_index: LocalMemoryIndex,
-        dst: u32,
+        dst: u64,
         val: u32,
-        len: u32,
+        len: u64,
     ) -> Result<(), Trap> {
         let memory = self.memory(memory_index);
         // The following memory fill is not synchronized and is not atomic:
@@ -718,9 +762,9 @@ impl Instance {
     pub(crate) fn imported_memory_fill(
         &self,
         memory_index: MemoryIndex,
-        dst: u32,
+        dst: u64,
         val: u32,
-        len: u32,
+        len: u64,
     ) -> Result<(), Trap> {
         let import = self.imported_memory(memory_index);
         let memory = unsafe { import.definition.as_ref() };
@@ -739,7 +783,7 @@ impl Instance {
         &self,
         memory_index: MemoryIndex,
         data_index: DataIndex,
-        dst: u32,
+        dst: u64,
         src: u32,
         len: u32,
     ) -> Result<(), Trap> {
@@ -754,8 +798,8 @@ impl Instance {
             .checked_add(len)
             .map_or(true, |n| n as usize > data.len())
             || dst
-                .checked_add(len)
-                .map_or(true, |m| usize::try_from(m).unwrap() > current_length)
+                .checked_add(u64::from(len))
+                .map_or(true, |m| m > current_length as u64)
         {
             return Err(Trap::lib(TrapCode::HeapAccessOutOfBounds));
         }
@@ -926,6 +970,123 @@ impl Instance {
         }
     }
 
+    /// Throws a new exception of the given tag, carrying the values stored
+    /// at `payload`.
+    ///
+    /// # Safety
+    ///
+    /// `payload` must point to as many values as the tag has parameters.
+    pub(crate) unsafe fn throw(&mut self, tag_index: TagIndex, payload: *const RawValue) {
+        let tag = self.tags[tag_index];
+        let len = tag.get(self.context()).ty().params().len();
+        let payload = slice::from_raw_parts(payload, len).into();
+        let exception =
+            InternalStoreHandle::new(self.context_mut(), VMException::new(tag, payload));
+        self.context()
+            .set_pending_exception(Some(VMExceptionRef(exception)));
+    }
+
+    /// Throws the given exception again.
+    pub(crate) fn throw_ref(&self, exception: VMExceptionRef) {
+        self.context().set_pending_exception(Some(exception));
+    }
+
+    /// Catches the pending exception if it has the given tag, or whatever
+    /// its tag when there is none, storing the values it carries at
+    /// `payload`.
+    ///
+    /// # Safety
+    ///
+    /// `payload` must have room for as many values as the tag has parameters.
+    pub(crate) unsafe fn catch(
+        &self,
+        tag_index: Option<TagIndex>,
+        payload: *mut RawValue,
+    ) -> Option<VMExceptionRef> {
+        let context = self.context();
+        let exception = context.pending_exception()?;
+        let vmexception = exception.0.get(context);
+        if let Some(tag_index) = tag_index {
+            if vmexception.tag() != self.tags[tag_index] {
+                return None;
+            }
+            ptr::copy_nonoverlapping(
+                vmexception.payload().as_ptr(),
+                payload,
+                vmexception.payload().len(),
+            );
+        }
+        context.set_pending_exception(None);
+        Some(exception)
+    }
+
+    /// Reports a memory access to the memory watch, if it touches a watched
+    /// range. This is called before the access, so the value of loads is
+    /// read from memory here.
+    pub(crate) fn report_memory_access(
+        &self,
+        kind: MemoryAccessKind,
+        function_index: FunctionIndex,
+        memory_index: MemoryIndex,
+        offset: u64,
+        size: u32,
+        value: u64,
+    ) {
+        let memory_watch = match &self.memory_watch {
+            Some(memory_watch) if memory_watch.is_watched(offset, size) => memory_watch,
+            _ => return,
+        };
+        let value = match kind {
+            MemoryAccessKind::Store if size < 8 => value & ((1 << (size * 8)) - 1),
+            MemoryAccessKind::Store => value,
+            MemoryAccessKind::Load => {
+                let memory = self.get_memory(memory_index);
+                match offset.checked_add(size as u64) {
+                    // Out of bounds accesses trap right after.
+                    Some(end) if end <= memory.current_length as u64 && size <= 8 => {
+                        let mut bytes = [0; 8];
+                        unsafe {
+                            ptr::copy_nonoverlapping(
+                                memory.base.add(offset as usize),
+                                bytes.as_mut_ptr(),
+                                size as usize,
+                            );
+                        }
+                        u64::from_le_bytes(bytes)
+                    }
+                    _ => return,
+                }
+            }
+        };
+        memory_watch.report(&MemoryAccess {
+            kind,
+            function_index,
+            memory_index,
+            offset,
+            size,
+            value,
+        });
+    }
+
+    /// Compiles a local function of a lazily compiled module, called by its
+    /// stub on its first call.
+    pub(crate) fn lazy_compile(&self, index: LocalFunctionIndex) -> Result<(), CompileError> {
+        match &self.lazy_compiler {
+            Some(lazy_compiler) => lazy_compiler.compile_function(index).map(|_| ()),
+            None => Err(CompileError::Codegen(
+                "the function can't be compiled lazily without a compiler".to_string(),
+            )),
+        }
+    }
+
+    /// Counts an event of a local function compiled with profile
+    /// instrumentation.
+    pub(crate) fn profile_count(&self, index: LocalFunctionIndex, counter: u32, num_counters: u32) {
+        if let Some(profile) = &self.profile {
+            profile.count(index, counter, num_counters);
+        }
+    }
+
     /// Perform an Atomic.Notify
     pub(crate) fn local_memory_notify(
         &mut self,
@@ -1030,6 +1191,22 @@ impl VMInstance {
             .map(|m| m.get(context).vmglobal())
             .collect::<PrimaryMap<LocalGlobalIndex, _>>()
             .into_boxed_slice();
+        let tags = imports
+            .tags
+            .values()
+            .copied()
+            .chain(
+                module
+                    .tags
+                    .values()
+                    .skip(module.num_imported_tags)
+                    .map(|sig_index| {
+                        let ty = TagType::new(module.signatures[*sig_index].params());
+                        InternalStoreHandle::new(context, VMTag::new(ty))
+                    }),
+            )
+            .collect::<PrimaryMap<TagIndex, _>>()
+            .into_boxed_slice();
         let passive_data = RefCell::new(
             module
                 .passive_data
@@ -1052,12 +1229,17 @@ impl VMInstance {
                 memories: finished_memories,
                 tables: finished_tables,
                 globals: finished_globals,
+                tags,
                 functions: finished_functions,
                 function_call_trampolines: finished_function_call_trampolines,
                 passive_elements: Default::default(),
                 passive_data,
                 funcrefs,
                 imported_funcrefs,
+                memory_watch: None,
+                function_tiers: None,
+                lazy_compiler: None,
+                profile: None,
                 vmctx: VMContext {},
             };
 
@@ -1119,6 +1301,25 @@ impl VMInstance {
             instance.builtin_functions_ptr(),
             VMBuiltinFunctionsArray::initialized(),
         );
+        ptr::write(
+            instance.vmctx_plus_offset(instance.offsets.vmctx_pending_exception_pointer()),
+            instance.context().pending_exception_ptr(),
+        );
+        ptr::write(
+            instance.vmctx_plus_offset(instance.offsets.vmctx_function_tiers_pointer()),
+            ptr::null::<VMFunctionTier>(),
+        );
+        ptr::write(
+            instance.vmctx_plus_offset(instance.offsets.vmctx_memory_watch_pointer()),
+            &NO_MEMORY_WATCH_BOUNDS as *const MemoryWatchBounds,
+        );
+        // No tail call is pending, and no function is driven yet.
+        let tail_call_begin = instance.offsets.vmctx_tail_call_begin();
+        ptr::write_bytes(
+            instance.vmctx_plus_offset::<u8>(tail_call_begin),
+            0,
+            (instance.offsets.size_of_vmctx() - tail_call_begin) as usize,
+        );
 
         // Perform infallible initialization in this constructor, while fallible
         // initialization is deferred to the `initialize` method.
@@ -1161,6 +1362,52 @@ impl VMInstance {
         Ok(())
     }
 
+    /// Sets where to report the memory accesses of code compiled with the
+    /// memory watch enabled.
+    pub fn set_memory_watch(&mut self, memory_watch: Option<Arc<MemoryWatch>>) {
+        let instance = self.instance_mut();
+        let bounds = memory_watch
+            .as_ref()
+            .map_or(&NO_MEMORY_WATCH_BOUNDS, |memory_watch| {
+                memory_watch.bounds()
+            });
+        unsafe {
+            ptr::write(
+                instance.vmctx_plus_offset(instance.offsets.vmctx_memory_watch_pointer()),
+                bounds as *const MemoryWatchBounds,
+            );
+        }
+        instance.memory_watch = memory_watch;
+    }
+
+    /// Sets the tier-up state of the local functions, for modules compiled
+    /// to count their calls and to be redirected to a later compilation.
+    pub fn set_function_tiers(&mut self, function_tiers: Option<Arc<[VMFunctionTier]>>) {
+        let instance = self.instance_mut();
+        let tiers = function_tiers
+            .as_ref()
+            .map_or(ptr::null(), |tiers| tiers.as_ptr());
+        unsafe {
+            ptr::write(
+                instance.vmctx_plus_offset(instance.offsets.vmctx_function_tiers_pointer()),
+                tiers,
+            );
+        }
+        instance.function_tiers = function_tiers;
+    }
+
+    /// Sets what compiles the local functions of lazily compiled modules on
+    /// their first call.
+    pub fn set_lazy_compiler(&mut self, lazy_compiler: Option<Arc<dyn LazyCompiler>>) {
+        self.instance_mut().lazy_compiler = lazy_compiler;
+    }
+
+    /// Sets where the local functions of modules compiled with profile
+    /// instrumentation count their calls and branches.
+    pub fn set_profile(&mut self, profile: Option<Arc<VMProfile>>) {
+        self.instance_mut().profile = profile;
+    }
+
     /// Return a reference to the vmctx used by compiled wasm code.
     pub fn vmctx(&self) -> &VMContext {
         self.instance().vmctx()
@@ -1253,6 +1500,7 @@ impl VMInstance {
                 };
                 VMExtern::Global(handle)
             }
+            ExportIndex::Tag(index) => VMExtern::Tag(instance.tags[index]),
         }
     }
 
@@ -1335,10 +1583,16 @@ fn get_memory_init_start(init: &DataInitializer<'_>, instance: &Instance) -> usi
 
     if let Some(base) = init.location.base {
         let val = unsafe {
-            if let Some(def_index) = instance.module.local_global_index(base) {
-                instance.global(def_index).val.u32
+            let val = if let Some(def_index) = instance.module.local_global_index(base) {
+                instance.global(def_index).val
             } else {
-                instance.imported_global(base).definition.as_ref().val.u32
+                instance.imported_global(base).definition.as_ref().val
+            };
+            // The base of a 64-bit memory is an `i64` global.
+            if instance.module.memories[init.location.memory_index].memory64 {
+                val.u64
+            } else {
+                val.u32.into()
             }
         };
         start += usize::try_from(val).unwrap();
@@ -0,0 +1,1081 @@
+//! The interpreter loop.
+
+use super::Function;
+use crate::debug::{self, DebugFrame};
+use crate::libcalls::{
+    wasmer_vm_f32_ceil, wasmer_vm_f32_floor, wasmer_vm_f32_nearest, wasmer_vm_f32_trunc,
+    wasmer_vm_f64_ceil, wasmer_vm_f64_floor, wasmer_vm_f64_nearest, wasmer_vm_f64_trunc,
+};
+use crate::store::InternalStoreHandle;
+use crate::table::TableElement;
+use crate::trap::{on_host_stack, Trap, TrapCode};
+use crate::vmcontext::{VMCallerCheckedAnyfunc, VMContext, VMMemoryDefinition};
+use crate::{VMExternRef, VMFuncRef};
+use std::ptr::{self, NonNull};
+use wasmer_types::{
+    DataIndex, ElemIndex, FunctionIndex, FunctionType, GlobalIndex, MemoryIndex, RawValue,
+    SignatureIndex, TableIndex, Type, WASM_PAGE_SIZE,
+};
+use wasmparser::{BinaryReader, MemArg, Operator};
+
+/// A branch target.
+#[derive(Clone, Copy)]
+struct Label {
+    /// Height of the value stack when entering the block, minus its
+    /// parameters.
+    height: usize,
+    /// Number of values carried by a branch to this label.
+    arity: usize,
+    /// Where execution continues after a branch to this label.
+    cont: usize,
+    is_loop: bool,
+}
+
+/// An activation of an interpreted function.
+#[derive(Clone, Copy)]
+struct Frame {
+    vmctx: *mut VMContext,
+    function: Function<'static>,
+    /// Index of the first local (including parameters) on the value stack.
+    locals: usize,
+    /// Index of the label of the function body.
+    label_base: usize,
+    /// Where execution resumes once a callee returns.
+    position: usize,
+}
+
+/// The state of the interpreter for a call from outside.
+///
+/// Calls between interpreted functions don't recurse on the native stack:
+/// they push a new frame on the same machine, with the arguments of the call
+/// becoming the first locals of the callee in place.
+pub(super) struct Machine {
+    stack: Vec<u64>,
+    labels: Vec<Label>,
+    frames: Vec<Frame>,
+    max_frames: usize,
+}
+
+fn trap(code: TrapCode) -> Trap {
+    Trap::lib(code)
+}
+
+fn reader_at(code: &[u8], position: usize) -> BinaryReader<'_> {
+    BinaryReader::new_with_offset(&code[position..], position)
+}
+
+/// Converts a raw value of the given type into a stack slot.
+pub(super) unsafe fn from_raw(ty: Type, raw: RawValue) -> u64 {
+    match ty {
+        Type::I32 | Type::F32 => raw.u32 as u64,
+        Type::I64 | Type::F64 => raw.u64,
+        Type::FuncRef => raw.funcref as u64,
+        Type::ExternRef => raw.externref as u64,
+        Type::V128 | Type::ExceptionRef => unreachable!("rejected by the interpreter compiler"),
+    }
+}
+
+/// Converts a stack slot of the given type into a raw value.
+pub(super) fn to_raw(ty: Type, value: u64) -> RawValue {
+    match ty {
+        Type::I32 | Type::F32 => RawValue { u32: value as u32 },
+        Type::I64 | Type::F64 => RawValue { u64: value },
+        Type::FuncRef => RawValue {
+            funcref: value as usize,
+        },
+        Type::ExternRef => RawValue {
+            externref: value as usize,
+        },
+        Type::V128 | Type::ExceptionRef => unreachable!("rejected by the interpreter compiler"),
+    }
+}
+
+fn element_to_raw(element: TableElement) -> u64 {
+    match element {
+        TableElement::FuncRef(func_ref) => func_ref.map_or(0, |f| f.0.as_ptr() as usize as u64),
+        TableElement::ExternRef(extern_ref) => extern_ref.map_or(0, |e| e.0.index() as u64),
+    }
+}
+
+fn raw_to_element(ty: Type, value: u64) -> TableElement {
+    match ty {
+        Type::ExternRef => TableElement::ExternRef(
+            InternalStoreHandle::from_index(value as usize).map(VMExternRef),
+        ),
+        _ => TableElement::FuncRef(
+            NonNull::new(value as usize as *mut VMCallerCheckedAnyfunc).map(VMFuncRef),
+        ),
+    }
+}
+
+fn f32_min(a: f32, b: f32) -> f32 {
+    if a.is_nan() || b.is_nan() {
+        f32::NAN
+    } else if a == b {
+        // Picks -0.0 over 0.0.
+        f32::from_bits(a.to_bits() | b.to_bits())
+    } else {
+        a.min(b)
+    }
+}
+
+fn f32_max(a: f32, b: f32) -> f32 {
+    if a.is_nan() || b.is_nan() {
+        f32::NAN
+    } else if a == b {
+        // Picks 0.0 over -0.0.
+        f32::from_bits(a.to_bits() & b.to_bits())
+    } else {
+        a.max(b)
+    }
+}
+
+fn f64_min(a: f64, b: f64) -> f64 {
+    if a.is_nan() || b.is_nan() {
+        f64::NAN
+    } else if a == b {
+        f64::from_bits(a.to_bits() | b.to_bits())
+    } else {
+        a.min(b)
+    }
+}
+
+fn f64_max(a: f64, b: f64) -> f64 {
+    if a.is_nan() || b.is_nan() {
+        f64::NAN
+    } else if a == b {
+        f64::from_bits(a.to_bits() & b.to_bits())
+    } else {
+        a.max(b)
+    }
+}
+
+/// Checks that a float truncates to an integer within `(lower, upper)`,
+/// both bounds being exclusive.
+fn check_trunc(value: f64, lower: f64, upper: f64) -> Result<(), Trap> {
+    if value.is_nan() {
+        Err(trap(TrapCode::BadConversionToInteger))
+    } else if value <= lower || value >= upper {
+        Err(trap(TrapCode::IntegerOverflow))
+    } else {
+        Ok(())
+    }
+}
+
+macro_rules! unop {
+    ($m:ident, $pop:ident, $push:ident, |$a:ident| $e:expr) => {{
+        let $a = $m.$pop();
+        $m.$push($e)
+    }};
+}
+
+macro_rules! binop {
+    ($m:ident, $pop:ident, $push:ident, |$a:ident, $b:ident| $e:expr) => {{
+        let $b = $m.$pop();
+        let $a = $m.$pop();
+        $m.$push($e)
+    }};
+}
+
+macro_rules! load {
+    ($m:ident, $vmctx:ident, $memarg:ident, $n:literal, |$b:ident| $e:expr) => {{
+        let $b = $m.load::<$n>($vmctx, &$memarg)?;
+        $m.push($e)
+    }};
+}
+
+macro_rules! store {
+    ($m:ident, $vmctx:ident, $memarg:ident, |$v:ident| $e:expr) => {{
+        let $v = $m.pop();
+        $m.store($vmctx, &$memarg, $e)?
+    }};
+}
+
+macro_rules! div_s {
+    ($m:ident, $pop:ident, $push:ident, $min:expr) => {{
+        let b = $m.$pop();
+        let a = $m.$pop();
+        if b == 0 {
+            return Err(trap(TrapCode::IntegerDivisionByZero));
+        }
+        if a == $min && b == -1 {
+            return Err(trap(TrapCode::IntegerOverflow));
+        }
+        $m.$push(a / b)
+    }};
+}
+
+macro_rules! checked_op {
+    ($m:ident, $pop:ident, $push:ident, |$a:ident, $b:ident| $e:expr) => {{
+        let $b = $m.$pop();
+        let $a = $m.$pop();
+        if $b == 0 {
+            return Err(trap(TrapCode::IntegerDivisionByZero));
+        }
+        $m.$push($e)
+    }};
+}
+
+macro_rules! trunc {
+    ($m:ident, $pop:ident, $push:ident, $ty:ty, $lower:expr, $upper:expr) => {{
+        let a = $m.$pop();
+        check_trunc(f64::from(a), $lower, $upper)?;
+        $m.$push(a as $ty)
+    }};
+}
+
+impl Machine {
+    pub(super) fn new(max_frames: usize) -> Self {
+        Self {
+            stack: Vec::with_capacity(64),
+            labels: Vec::with_capacity(16),
+            frames: Vec::with_capacity(8),
+            max_frames,
+        }
+    }
+
+    /// Calls `function` with the arguments in `values`, writing the results
+    /// back to `values`.
+    pub(super) unsafe fn call_from_host(
+        &mut self,
+        vmctx: *mut VMContext,
+        function: Function<'static>,
+        values: *mut RawValue,
+    ) -> Result<(), Trap> {
+        let signature = Self::signature(vmctx, FunctionIndex::from_u32(function.index));
+        for (i, ty) in signature.params().iter().enumerate() {
+            self.stack.push(from_raw(*ty, *values.add(i)));
+        }
+        self.enter(vmctx, function)?;
+        if function.debugging {
+            self.run::<true>()?;
+        } else {
+            self.run::<false>()?;
+        }
+        for (i, ty) in signature.results().iter().enumerate() {
+            *values.add(i) = to_raw(*ty, self.stack[i]);
+        }
+        Ok(())
+    }
+
+    unsafe fn signature<'a>(vmctx: *mut VMContext, index: FunctionIndex) -> &'a FunctionType {
+        let module = (*vmctx).instance().module_ref();
+        &module.signatures[module.functions[index]]
+    }
+
+    #[inline]
+    fn push(&mut self, value: u64) {
+        self.stack.push(value);
+    }
+
+    #[inline]
+    fn pop(&mut self) -> u64 {
+        self.stack.pop().expect("value stack underflow")
+    }
+
+    fn push_i32(&mut self, value: i32) {
+        self.push(value as u32 as u64);
+    }
+
+    fn pop_i32(&mut self) -> i32 {
+        self.pop() as u32 as i32
+    }
+
+    fn push_u32(&mut self, value: u32) {
+        self.push(value as u64);
+    }
+
+    fn pop_u32(&mut self) -> u32 {
+        self.pop() as u32
+    }
+
+    fn push_bool(&mut self, value: bool) {
+        self.push(value as u64);
+    }
+
+    fn push_i64(&mut self, value: i64) {
+        self.push(value as u64);
+    }
+
+    fn pop_i64(&mut self) -> i64 {
+        self.pop() as i64
+    }
+
+    fn push_u64(&mut self, value: u64) {
+        self.push(value);
+    }
+
+    fn pop_u64(&mut self) -> u64 {
+        self.pop()
+    }
+
+    fn push_f32(&mut self, value: f32) {
+        self.push(value.to_bits() as u64);
+    }
+
+    fn pop_f32(&mut self) -> f32 {
+        f32::from_bits(self.pop() as u32)
+    }
+
+    fn push_f64(&mut self, value: f64) {
+        self.push(value.to_bits());
+    }
+
+    fn pop_f64(&mut self) -> f64 {
+        f64::from_bits(self.pop())
+    }
+
+    /// Pushes the frame of an interpreted function whose arguments are on
+    /// top of the stack.
+    unsafe fn enter(
+        &mut self,
+        vmctx: *mut VMContext,
+        function: Function<'static>,
+    ) -> Result<(), Trap> {
+        if self.frames.len() == self.max_frames {
+            return Err(trap(TrapCode::StackOverflow));
+        }
+
+        let signature = Self::signature(vmctx, FunctionIndex::from_u32(function.index));
+        let locals = self.stack.len() - signature.params().len();
+        self.stack
+            .resize(self.stack.len() + function.num_locals as usize, 0);
+        self.frames.push(Frame {
+            vmctx,
+            function,
+            locals,
+            label_base: self.labels.len(),
+            position: 0,
+        });
+        self.labels.push(Label {
+            height: self.stack.len(),
+            arity: signature.results().len(),
+            cont: function.code.len(),
+            is_loop: false,
+        });
+        Ok(())
+    }
+
+    /// Pops the current frame, moving its results in place of its locals.
+    ///
+    /// Returns the frame to resume, if any.
+    fn leave(&mut self) -> Option<Frame> {
+        let frame = self.frames.pop().unwrap();
+        let arity = self.labels[frame.label_base].arity;
+        self.labels.truncate(frame.label_base);
+        let results = self.stack.len() - arity;
+        self.stack.copy_within(results.., frame.locals);
+        self.stack.truncate(frame.locals + arity);
+        self.frames.last().copied()
+    }
+
+    /// Calls a function that isn't interpreted through its call trampoline.
+    unsafe fn call_foreign(&mut self, anyfunc: VMCallerCheckedAnyfunc, signature: &FunctionType) {
+        let params = signature.params();
+        let results = signature.results();
+        let mut values = vec![RawValue { u128: 0 }; params.len().max(results.len())];
+        let args = self.stack.len() - params.len();
+        for (i, ty) in params.iter().enumerate() {
+            values[i] = to_raw(*ty, self.stack[args + i]);
+        }
+        self.stack.truncate(args);
+        (anyfunc.call_trampoline)(anyfunc.vmctx.vmctx, anyfunc.func_ptr, values.as_mut_ptr());
+        for (i, ty) in results.iter().enumerate() {
+            self.push(from_raw(*ty, values[i]));
+        }
+    }
+
+    /// Branches to the label `depth` levels up, returning where execution
+    /// continues or `None` when leaving the function.
+    fn branch(&mut self, depth: u32, label_base: usize) -> Option<usize> {
+        let index = self.labels.len() - 1 - depth as usize;
+        let label = self.labels[index];
+        let values = self.stack.len() - label.arity;
+        self.stack.copy_within(values.., label.height);
+        self.stack.truncate(label.height + label.arity);
+        if index == label_base {
+            None
+        } else if label.is_loop {
+            self.labels.truncate(index + 1);
+            Some(label.cont)
+        } else {
+            self.labels.truncate(index);
+            Some(label.cont)
+        }
+    }
+
+    unsafe fn memory(vmctx: *mut VMContext, index: u32) -> VMMemoryDefinition {
+        *(*vmctx)
+            .instance()
+            .memory_definition_ptr(MemoryIndex::from_u32(index))
+            .as_ref()
+    }
+
+    /// Pops an address and checks that `len` bytes from it are in bounds of
+    /// the memory.
+    unsafe fn address(
+        &mut self,
+        vmctx: *mut VMContext,
+        memarg: &MemArg,
+        len: usize,
+    ) -> Result<*mut u8, Trap> {
+        let address = self.pop_u32() as u64 + memarg.offset;
+        let memory = Self::memory(vmctx, memarg.memory);
+        match address.checked_add(len as u64) {
+            Some(end) if end <= memory.current_length as u64 => {
+                Ok(memory.base.add(address as usize))
+            }
+            _ => Err(trap(TrapCode::HeapAccessOutOfBounds)),
+        }
+    }
+
+    unsafe fn load<const N: usize>(
+        &mut self,
+        vmctx: *mut VMContext,
+        memarg: &MemArg,
+    ) -> Result<[u8; N], Trap> {
+        let address = self.address(vmctx, memarg, N)?;
+        Ok(ptr::read_unaligned(address as *const [u8; N]))
+    }
+
+    unsafe fn store<const N: usize>(
+        &mut self,
+        vmctx: *mut VMContext,
+        memarg: &MemArg,
+        bytes: [u8; N],
+    ) -> Result<(), Trap> {
+        let address = self.address(vmctx, memarg, N)?;
+        ptr::write_unaligned(address as *mut [u8; N], bytes);
+        Ok(())
+    }
+
+    /// Checks that `len` bytes from `offset` are in bounds of `memory`.
+    fn memory_range(memory: &VMMemoryDefinition, offset: u32, len: u32) -> Result<usize, Trap> {
+        if offset as u64 + len as u64 > memory.current_length as u64 {
+            Err(trap(TrapCode::HeapAccessOutOfBounds))
+        } else {
+            Ok(offset as usize)
+        }
+    }
+
+    /// Lets the attached debugger inspect the current frame before the
+    /// instruction at `position` runs.
+    #[cold]
+    fn debug(&mut self, vmctx: *mut VMContext, function: Function, frame: Frame, position: usize) {
+        if let Some(debugger) = debug::debugger() {
+            let end = self.labels[frame.label_base].height;
+            debugger.on_instruction(&mut DebugFrame {
+                vmctx,
+                function_index: function.index,
+                module_offset: function.code_offset as usize + position,
+                locals: &mut self.stack[frame.locals..end],
+            });
+        }
+    }
+
+    /// Executes the current frame until it returns.
+    ///
+    /// The loop is instantiated once for functions compiled for debugging
+    /// and once for the others, so only the former check for a debugger.
+    /// Calls to a function of the other kind go through its trampoline.
+    #[allow(clippy::cognitive_complexity)]
+    unsafe fn run<const DEBUGGING: bool>(&mut self) -> Result<(), Trap> {
+        let mut frame = *self.frames.last().unwrap();
+        let mut vmctx = frame.vmctx;
+        let mut module = (*vmctx).instance().module_ref();
+        let mut function = frame.function;
+        let mut code = function.code;
+        let mut reader = reader_at(code, 0);
+
+        // Switches execution to another frame.
+        macro_rules! resume {
+            ($frame:expr) => {{
+                frame = $frame;
+                vmctx = frame.vmctx;
+                module = (*vmctx).instance().module_ref();
+                function = frame.function;
+                code = function.code;
+                reader = reader_at(code, frame.position);
+            }};
+        }
+
+        // Returns from the current frame.
+        macro_rules! ret {
+            () => {
+                match self.leave() {
+                    Some(caller) => resume!(caller),
+                    None => return Ok(()),
+                }
+            };
+        }
+
+        macro_rules! branch {
+            ($depth:expr) => {
+                match self.branch($depth, frame.label_base) {
+                    Some(cont) => reader = reader_at(code, cont),
+                    None => ret!(),
+                }
+            };
+        }
+
+        macro_rules! call {
+            ($anyfunc:expr, $signature:expr) => {{
+                let anyfunc: VMCallerCheckedAnyfunc = $anyfunc;
+                let callee = (anyfunc.call_trampoline as usize == super::call_trampoline as usize)
+                    .then(|| Function::from_body(anyfunc.func_ptr))
+                    .filter(|callee| callee.debugging == DEBUGGING);
+                if let Some(callee) = callee {
+                    self.frames.last_mut().unwrap().position = reader.original_position();
+                    self.enter(anyfunc.vmctx.vmctx, callee)?;
+                    resume!(*self.frames.last().unwrap());
+                } else {
+                    self.call_foreign(anyfunc, $signature);
+                }
+            }};
+        }
+
+        loop {
+            let position = reader.original_position();
+            if DEBUGGING && debug::is_attached() {
+                self.debug(vmctx, function, frame, position);
+            }
+            let op = reader
+                .read_operator()
+                .expect("interpreted code was validated at compile time");
+            match op {
+                Operator::Unreachable => return Err(trap(TrapCode::UnreachableCodeReached)),
+                Operator::Nop => {}
+                Operator::Block { .. } => {
+                    let block = function.block(position as u32);
+                    self.labels.push(Label {
+                        height: self.stack.len() - block.params as usize,
+                        arity: block.results as usize,
+                        cont: block.end as usize,
+                        is_loop: false,
+                    });
+                }
+                Operator::Loop { .. } => {
+                    let block = function.block(position as u32);
+                    self.labels.push(Label {
+                        height: self.stack.len() - block.params as usize,
+                        arity: block.params as usize,
+                        cont: reader.original_position(),
+                        is_loop: true,
+                    });
+                }
+                Operator::If { .. } => {
+                    let condition = self.pop_i32();
+                    let block = function.block(position as u32);
+                    let label = Label {
+                        height: self.stack.len() - block.params as usize,
+                        arity: block.results as usize,
+                        cont: block.end as usize,
+                        is_loop: false,
+                    };
+                    if condition != 0 {
+                        self.labels.push(label);
+                    } else if let Some(else_) = block.else_ {
+                        self.labels.push(label);
+                        // Skip the `else` opcode itself.
+                        reader = reader_at(code, else_ as usize + 1);
+                    } else {
+                        reader = reader_at(code, block.end as usize);
+                    }
+                }
+                Operator::Else => {
+                    // The `then` branch is done, skip the `else` one.
+                    let label = self.labels.pop().unwrap();
+                    reader = reader_at(code, label.cont);
+                }
+                Operator::End => {
+                    if self.labels.len() == frame.label_base + 1 {
+                        ret!();
+                    } else {
+                        self.labels.pop();
+                    }
+                }
+                Operator::Br { relative_depth } => branch!(relative_depth),
+                Operator::BrIf { relative_depth } => {
+                    if self.pop_i32() != 0 {
+                        branch!(relative_depth);
+                    }
+                }
+                Operator::BrTable { targets } => {
+                    let index = self.pop_u32();
+                    let depth = if index < targets.len() {
+                        targets
+                            .targets()
+                            .nth(index as usize)
+                            .unwrap()
+                            .expect("br_table was validated")
+                    } else {
+                        targets.default()
+                    };
+                    branch!(depth);
+                }
+                Operator::Return => ret!(),
+                Operator::Call { function_index } => {
+                    let index = FunctionIndex::from_u32(function_index);
+                    let anyfunc = *(*vmctx).instance().func_ref(index).unwrap().0.as_ref();
+                    call!(anyfunc, &module.signatures[module.functions[index]]);
+                }
+                Operator::CallIndirect {
+                    type_index,
+                    table_index,
+                    ..
+                } => {
+                    let index = self.pop_u32();
+                    let element = (*vmctx)
+                        .instance_mut()
+                        .get_table(TableIndex::from_u32(table_index))
+                        .get(index)
+                        .ok_or_else(|| trap(TrapCode::TableAccessOutOfBounds))?;
+                    let func_ref = match element {
+                        TableElement::FuncRef(Some(func_ref)) => func_ref,
+                        _ => return Err(trap(TrapCode::IndirectCallToNull)),
+                    };
+                    let anyfunc = *func_ref.0.as_ref();
+                    let signature_index = SignatureIndex::from_u32(type_index);
+                    if anyfunc.type_index != (*vmctx).instance().signature_id(signature_index) {
+                        return Err(trap(TrapCode::BadSignature));
+                    }
+                    call!(anyfunc, &module.signatures[signature_index]);
+                }
+
+                Operator::Drop => {
+                    self.pop();
+                }
+                Operator::Select | Operator::TypedSelect { .. } => {
+                    let condition = self.pop_i32();
+                    let b = self.pop();
+                    let a = self.pop();
+                    self.push(if condition != 0 { a } else { b });
+                }
+                Operator::LocalGet { local_index } => {
+                    self.push(self.stack[frame.locals + local_index as usize]);
+                }
+                Operator::LocalSet { local_index } => {
+                    let value = self.pop();
+                    self.stack[frame.locals + local_index as usize] = value;
+                }
+                Operator::LocalTee { local_index } => {
+                    self.stack[frame.locals + local_index as usize] = *self.stack.last().unwrap();
+                }
+                Operator::GlobalGet { global_index } => {
+                    let index = GlobalIndex::from_u32(global_index);
+                    let global = (*vmctx).instance().global_definition_ptr(index);
+                    self.push(from_raw(module.globals[index].ty, global.as_ref().val));
+                }
+                Operator::GlobalSet { global_index } => {
+                    let index = GlobalIndex::from_u32(global_index);
+                    let global = (*vmctx).instance().global_definition_ptr(index);
+                    let value = self.pop();
+                    (*global.as_ptr()).val = to_raw(module.globals[index].ty, value);
+                }
+
+                Operator::I32Load { memarg } => {
+                    load!(self, vmctx, memarg, 4, |b| u32::from_le_bytes(b) as u64)
+                }
+                Operator::I64Load { memarg } => {
+                    load!(self, vmctx, memarg, 8, |b| u64::from_le_bytes(b))
+                }
+                Operator::F32Load { memarg } => {
+                    load!(self, vmctx, memarg, 4, |b| u32::from_le_bytes(b) as u64)
+                }
+                Operator::F64Load { memarg } => {
+                    load!(self, vmctx, memarg, 8, |b| u64::from_le_bytes(b))
+                }
+                Operator::I32Load8S { memarg } => {
+                    load!(self, vmctx, memarg, 1, |b| b[0] as i8 as i32 as u32 as u64)
+                }
+                Operator::I32Load8U { memarg } => {
+                    load!(self, vmctx, memarg, 1, |b| b[0] as u64)
+                }
+                Operator::I32Load16S { memarg } => load!(self, vmctx, memarg, 2, |b| {
+                    i16::from_le_bytes(b) as i32 as u32 as u64
+                }),
+                Operator::I32Load16U { memarg } => {
+                    load!(self, vmctx, memarg, 2, |b| u16::from_le_bytes(b) as u64)
+                }
+                Operator::I64Load8S { memarg } => {
+                    load!(self, vmctx, memarg, 1, |b| b[0] as i8 as i64 as u64)
+                }
+                Operator::I64Load8U { memarg } => {
+                    load!(self, vmctx, memarg, 1, |b| b[0] as u64)
+                }
+                Operator::I64Load16S { memarg } => {
+                    load!(self, vmctx, memarg, 2, |b| i16::from_le_bytes(b) as i64
+                        as u64)
+                }
+                Operator::I64Load16U { memarg } => {
+                    load!(self, vmctx, memarg, 2, |b| u16::from_le_bytes(b) as u64)
+                }
+                Operator::I64Load32S { memarg } => {
+                    load!(self, vmctx, memarg, 4, |b| i32::from_le_bytes(b) as i64
+                        as u64)
+                }
+                Operator::I64Load32U { memarg } => {
+                    load!(self, vmctx, memarg, 4, |b| u32::from_le_bytes(b) as u64)
+                }
+                Operator::I32Store { memarg } | Operator::F32Store { memarg } => {
+                    store!(self, vmctx, memarg, |v| (v as u32).to_le_bytes())
+                }
+                Operator::I64Store { memarg } | Operator::F64Store { memarg } => {
+                    store!(self, vmctx, memarg, |v| v.to_le_bytes())
+                }
+                Operator::I32Store8 { memarg } | Operator::I64Store8 { memarg } => {
+                    store!(self, vmctx, memarg, |v| [v as u8])
+                }
+                Operator::I32Store16 { memarg } | Operator::I64Store16 { memarg } => {
+                    store!(self, vmctx, memarg, |v| (v as u16).to_le_bytes())
+                }
+                Operator::I64Store32 { memarg } => {
+                    store!(self, vmctx, memarg, |v| (v as u32).to_le_bytes())
+                }
+                Operator::MemorySize { mem, .. } => {
+                    let memory = Self::memory(vmctx, mem);
+                    self.push_u32((memory.current_length / WASM_PAGE_SIZE) as u32);
+                }
+                Operator::MemoryGrow { mem, .. } => {
+                    let delta = self.pop_u32();
+                    let pages = on_host_stack(|| {
+                        let instance = (*vmctx).instance_mut();
+                        let index = MemoryIndex::from_u32(mem);
+                        match instance.module_ref().local_memory_index(index) {
+                            Some(local_index) => instance.memory_grow(local_index, delta),
+                            None => instance.imported_memory_grow(index, delta),
+                        }
+                        .map_or(u32::MAX, |pages| pages.0)
+                    });
+                    self.push_u32(pages);
+                }
+
+                Operator::I32Const { value } => self.push_i32(value),
+                Operator::I64Const { value } => self.push_i64(value),
+                Operator::F32Const { value } => self.push(value.bits() as u64),
+                Operator::F64Const { value } => self.push(value.bits()),
+
+                Operator::RefNull { .. } => self.push(0),
+                Operator::RefIsNull => unop!(self, pop, push_bool, |a| a == 0),
+                Operator::RefFunc { function_index } => {
+                    let func_ref = (*vmctx)
+                        .instance()
+                        .func_ref(FunctionIndex::from_u32(function_index))
+                        .unwrap();
+                    self.push(func_ref.0.as_ptr() as usize as u64);
+                }
+
+                Operator::I32Eqz => unop!(self, pop_i32, push_bool, |a| a == 0),
+                Operator::I32Eq => binop!(self, pop_i32, push_bool, |a, b| a == b),
+                Operator::I32Ne => binop!(self, pop_i32, push_bool, |a, b| a != b),
+                Operator::I32LtS => binop!(self, pop_i32, push_bool, |a, b| a < b),
+                Operator::I32LtU => binop!(self, pop_u32, push_bool, |a, b| a < b),
+                Operator::I32GtS => binop!(self, pop_i32, push_bool, |a, b| a > b),
+                Operator::I32GtU => binop!(self, pop_u32, push_bool, |a, b| a > b),
+                Operator::I32LeS => binop!(self, pop_i32, push_bool, |a, b| a <= b),
+                Operator::I32LeU => binop!(self, pop_u32, push_bool, |a, b| a <= b),
+                Operator::I32GeS => binop!(self, pop_i32, push_bool, |a, b| a >= b),
+                Operator::I32GeU => binop!(self, pop_u32, push_bool, |a, b| a >= b),
+                Operator::I64Eqz => unop!(self, pop_i64, push_bool, |a| a == 0),
+                Operator::I64Eq => binop!(self, pop_i64, push_bool, |a, b| a == b),
+                Operator::I64Ne => binop!(self, pop_i64, push_bool, |a, b| a != b),
+                Operator::I64LtS => binop!(self, pop_i64, push_bool, |a, b| a < b),
+                Operator::I64LtU => binop!(self, pop_u64, push_bool, |a, b| a < b),
+                Operator::I64GtS => binop!(self, pop_i64, push_bool, |a, b| a > b),
+                Operator::I64GtU => binop!(self, pop_u64, push_bool, |a, b| a > b),
+                Operator::I64LeS => binop!(self, pop_i64, push_bool, |a, b| a <= b),
+                Operator::I64LeU => binop!(self, pop_u64, push_bool, |a, b| a <= b),
+                Operator::I64GeS => binop!(self, pop_i64, push_bool, |a, b| a >= b),
+                Operator::I64GeU => binop!(self, pop_u64, push_bool, |a, b| a >= b),
+                Operator::F32Eq => binop!(self, pop_f32, push_bool, |a, b| a == b),
+                Operator::F32Ne => binop!(self, pop_f32, push_bool, |a, b| a != b),
+                Operator::F32Lt => binop!(self, pop_f32, push_bool, |a, b| a < b),
+                Operator::F32Gt => binop!(self, pop_f32, push_bool, |a, b| a > b),
+                Operator::F32Le => binop!(self, pop_f32, push_bool, |a, b| a <= b),
+                Operator::F32Ge => binop!(self, pop_f32, push_bool, |a, b| a >= b),
+                Operator::F64Eq => binop!(self, pop_f64, push_bool, |a, b| a == b),
+                Operator::F64Ne => binop!(self, pop_f64, push_bool, |a, b| a != b),
+                Operator::F64Lt => binop!(self, pop_f64, push_bool, |a, b| a < b),
+                Operator::F64Gt => binop!(self, pop_f64, push_bool, |a, b| a > b),
+                Operator::F64Le => binop!(self, pop_f64, push_bool, |a, b| a <= b),
+                Operator::F64Ge => binop!(self, pop_f64, push_bool, |a, b| a >= b),
+
+                Operator::I32Clz => unop!(self, pop_u32, push_u32, |a| a.leading_zeros()),
+                Operator::I32Ctz => unop!(self, pop_u32, push_u32, |a| a.trailing_zeros()),
+                Operator::I32Popcnt => unop!(self, pop_u32, push_u32, |a| a.count_ones()),
+                Operator::I32Add => binop!(self, pop_i32, push_i32, |a, b| a.wrapping_add(b)),
+                Operator::I32Sub => binop!(self, pop_i32, push_i32, |a, b| a.wrapping_sub(b)),
+                Operator::I32Mul => binop!(self, pop_i32, push_i32, |a, b| a.wrapping_mul(b)),
+                Operator::I32DivS => div_s!(self, pop_i32, push_i32, i32::MIN),
+                Operator::I32DivU => checked_op!(self, pop_u32, push_u32, |a, b| a / b),
+                Operator::I32RemS => {
+                    checked_op!(self, pop_i32, push_i32, |a, b| a.wrapping_rem(b))
+                }
+                Operator::I32RemU => checked_op!(self, pop_u32, push_u32, |a, b| a % b),
+                Operator::I32And => binop!(self, pop_i32, push_i32, |a, b| a & b),
+                Operator::I32Or => binop!(self, pop_i32, push_i32, |a, b| a | b),
+                Operator::I32Xor => binop!(self, pop_i32, push_i32, |a, b| a ^ b),
+                Operator::I32Shl => {
+                    binop!(self, pop_i32, push_i32, |a, b| a.wrapping_shl(b as u32))
+                }
+                Operator::I32ShrS => {
+                    binop!(self, pop_i32, push_i32, |a, b| a.wrapping_shr(b as u32))
+                }
+                Operator::I32ShrU => binop!(self, pop_u32, push_u32, |a, b| a.wrapping_shr(b)),
+                Operator::I32Rotl => binop!(self, pop_u32, push_u32, |a, b| a.rotate_left(b)),
+                Operator::I32Rotr => binop!(self, pop_u32, push_u32, |a, b| a.rotate_right(b)),
+                Operator::I64Clz => {
+                    unop!(self, pop_u64, push_u64, |a| a.leading_zeros() as u64)
+                }
+                Operator::I64Ctz => {
+                    unop!(self, pop_u64, push_u64, |a| a.trailing_zeros() as u64)
+                }
+                Operator::I64Popcnt => unop!(self, pop_u64, push_u64, |a| a.count_ones() as u64),
+                Operator::I64Add => binop!(self, pop_i64, push_i64, |a, b| a.wrapping_add(b)),
+                Operator::I64Sub => binop!(self, pop_i64, push_i64, |a, b| a.wrapping_sub(b)),
+                Operator::I64Mul => binop!(self, pop_i64, push_i64, |a, b| a.wrapping_mul(b)),
+                Operator::I64DivS => div_s!(self, pop_i64, push_i64, i64::MIN),
+                Operator::I64DivU => checked_op!(self, pop_u64, push_u64, |a, b| a / b),
+                Operator::I64RemS => {
+                    checked_op!(self, pop_i64, push_i64, |a, b| a.wrapping_rem(b))
+                }
+                Operator::I64RemU => checked_op!(self, pop_u64, push_u64, |a, b| a % b),
+                Operator::I64And => binop!(self, pop_i64, push_i64, |a, b| a & b),
+                Operator::I64Or => binop!(self, pop_i64, push_i64, |a, b| a | b),
+                Operator::I64Xor => binop!(self, pop_i64, push_i64, |a, b| a ^ b),
+                Operator::I64Shl => {
+                    binop!(self, pop_i64, push_i64, |a, b| a.wrapping_shl(b as u32))
+                }
+                Operator::I64ShrS => {
+                    binop!(self, pop_i64, push_i64, |a, b| a.wrapping_shr(b as u32))
+                }
+                Operator::I64ShrU => {
+                    binop!(self, pop_u64, push_u64, |a, b| a.wrapping_shr(b as u32))
+                }
+                Operator::I64Rotl => {
+                    binop!(self, pop_u64, push_u64, |a, b| a.rotate_left(b as u32))
+                }
+                Operator::I64Rotr => {
+                    binop!(self, pop_u64, push_u64, |a, b| a.rotate_right(b as u32))
+                }
+
+                Operator::F32Abs => unop!(self, pop_f32, push_f32, |a| a.abs()),
+                Operator::F32Neg => unop!(self, pop_f32, push_f32, |a| -a),
+                Operator::F32Ceil => unop!(self, pop_f32, push_f32, |a| wasmer_vm_f32_ceil(a)),
+                Operator::F32Floor => unop!(self, pop_f32, push_f32, |a| wasmer_vm_f32_floor(a)),
+                Operator::F32Trunc => unop!(self, pop_f32, push_f32, |a| wasmer_vm_f32_trunc(a)),
+                Operator::F32Nearest => {
+                    unop!(self, pop_f32, push_f32, |a| wasmer_vm_f32_nearest(a))
+                }
+                Operator::F32Sqrt => unop!(self, pop_f32, push_f32, |a| a.sqrt()),
+                Operator::F32Add => binop!(self, pop_f32, push_f32, |a, b| a + b),
+                Operator::F32Sub => binop!(self, pop_f32, push_f32, |a, b| a - b),
+                Operator::F32Mul => binop!(self, pop_f32, push_f32, |a, b| a * b),
+                Operator::F32Div => binop!(self, pop_f32, push_f32, |a, b| a / b),
+                Operator::F32Min => binop!(self, pop_f32, push_f32, |a, b| f32_min(a, b)),
+                Operator::F32Max => binop!(self, pop_f32, push_f32, |a, b| f32_max(a, b)),
+                Operator::F32Copysign => binop!(self, pop_f32, push_f32, |a, b| a.copysign(b)),
+                Operator::F64Abs => unop!(self, pop_f64, push_f64, |a| a.abs()),
+                Operator::F64Neg => unop!(self, pop_f64, push_f64, |a| -a),
+                Operator::F64Ceil => unop!(self, pop_f64, push_f64, |a| wasmer_vm_f64_ceil(a)),
+                Operator::F64Floor => unop!(self, pop_f64, push_f64, |a| wasmer_vm_f64_floor(a)),
+                Operator::F64Trunc => unop!(self, pop_f64, push_f64, |a| wasmer_vm_f64_trunc(a)),
+                Operator::F64Nearest => {
+                    unop!(self, pop_f64, push_f64, |a| wasmer_vm_f64_nearest(a))
+                }
+                Operator::F64Sqrt => unop!(self, pop_f64, push_f64, |a| a.sqrt()),
+                Operator::F64Add => binop!(self, pop_f64, push_f64, |a, b| a + b),
+                Operator::F64Sub => binop!(self, pop_f64, push_f64, |a, b| a - b),
+                Operator::F64Mul => binop!(self, pop_f64, push_f64, |a, b| a * b),
+                Operator::F64Div => binop!(self, pop_f64, push_f64, |a, b| a / b),
+                Operator::F64Min => binop!(self, pop_f64, push_f64, |a, b| f64_min(a, b)),
+                Operator::F64Max => binop!(self, pop_f64, push_f64, |a, b| f64_max(a, b)),
+                Operator::F64Copysign => binop!(self, pop_f64, push_f64, |a, b| a.copysign(b)),
+
+                Operator::I32WrapI64 => unop!(self, pop_i64, push_i32, |a| a as i32),
+                Operator::I32TruncF32S => {
+                    trunc!(self, pop_f32, push_i32, i32, -2147483904.0, 2147483648.0)
+                }
+                Operator::I32TruncF32U => trunc!(self, pop_f32, push_u32, u32, -1.0, 4294967296.0),
+                Operator::I32TruncF64S => {
+                    trunc!(self, pop_f64, push_i32, i32, -2147483649.0, 2147483648.0)
+                }
+                Operator::I32TruncF64U => trunc!(self, pop_f64, push_u32, u32, -1.0, 4294967296.0),
+                Operator::I64ExtendI32S => unop!(self, pop_i32, push_i64, |a| a as i64),
+                Operator::I64ExtendI32U => unop!(self, pop_u32, push_u64, |a| a as u64),
+                Operator::I64TruncF32S => trunc!(
+                    self,
+                    pop_f32,
+                    push_i64,
+                    i64,
+                    -9223373136366403584.0,
+                    9223372036854775808.0
+                ),
+                Operator::I64TruncF32U => {
+                    trunc!(self, pop_f32, push_u64, u64, -1.0, 18446744073709551616.0)
+                }
+                Operator::I64TruncF64S => trunc!(
+                    self,
+                    pop_f64,
+                    push_i64,
+                    i64,
+                    -9223372036854777856.0,
+                    9223372036854775808.0
+                ),
+                Operator::I64TruncF64U => {
+                    trunc!(self, pop_f64, push_u64, u64, -1.0, 18446744073709551616.0)
+                }
+                Operator::F32ConvertI32S => unop!(self, pop_i32, push_f32, |a| a as f32),
+                Operator::F32ConvertI32U => unop!(self, pop_u32, push_f32, |a| a as f32),
+                Operator::F32ConvertI64S => unop!(self, pop_i64, push_f32, |a| a as f32),
+                Operator::F32ConvertI64U => unop!(self, pop_u64, push_f32, |a| a as f32),
+                Operator::F32DemoteF64 => unop!(self, pop_f64, push_f32, |a| a as f32),
+                Operator::F64ConvertI32S => unop!(self, pop_i32, push_f64, |a| a as f64),
+                Operator::F64ConvertI32U => unop!(self, pop_u32, push_f64, |a| a as f64),
+                Operator::F64ConvertI64S => unop!(self, pop_i64, push_f64, |a| a as f64),
+                Operator::F64ConvertI64U => unop!(self, pop_u64, push_f64, |a| a as f64),
+                Operator::F64PromoteF32 => unop!(self, pop_f32, push_f64, |a| a as f64),
+                // Values are kept as raw bits on the stack.
+                Operator::I32ReinterpretF32
+                | Operator::I64ReinterpretF64
+                | Operator::F32ReinterpretI32
+                | Operator::F64ReinterpretI64 => {}
+                Operator::I32Extend8S => unop!(self, pop_i32, push_i32, |a| a as i8 as i32),
+                Operator::I32Extend16S => unop!(self, pop_i32, push_i32, |a| a as i16 as i32),
+                Operator::I64Extend8S => unop!(self, pop_i64, push_i64, |a| a as i8 as i64),
+                Operator::I64Extend16S => unop!(self, pop_i64, push_i64, |a| a as i16 as i64),
+                Operator::I64Extend32S => unop!(self, pop_i64, push_i64, |a| a as i32 as i64),
+                // `as` casts from floats to integers saturate.
+                Operator::I32TruncSatF32S => unop!(self, pop_f32, push_i32, |a| a as i32),
+                Operator::I32TruncSatF32U => unop!(self, pop_f32, push_u32, |a| a as u32),
+                Operator::I32TruncSatF64S => unop!(self, pop_f64, push_i32, |a| a as i32),
+                Operator::I32TruncSatF64U => unop!(self, pop_f64, push_u32, |a| a as u32),
+                Operator::I64TruncSatF32S => unop!(self, pop_f32, push_i64, |a| a as i64),
+                Operator::I64TruncSatF32U => unop!(self, pop_f32, push_u64, |a| a as u64),
+                Operator::I64TruncSatF64S => unop!(self, pop_f64, push_i64, |a| a as i64),
+                Operator::I64TruncSatF64U => unop!(self, pop_f64, push_u64, |a| a as u64),
+
+                Operator::MemoryInit { data_index, mem } => {
+                    let len = self.pop_u32();
+                    let src = self.pop_u32();
+                    let dst = self.pop_u32();
+                    (*vmctx).instance().memory_init(
+                        MemoryIndex::from_u32(mem),
+                        DataIndex::from_u32(data_index),
+                        dst.into(),
+                        src,
+                        len,
+                    )?;
+                }
+                Operator::DataDrop { data_index } => {
+                    let instance = (*vmctx).instance();
+                    on_host_stack(|| instance.data_drop(DataIndex::from_u32(data_index)));
+                }
+                Operator::MemoryCopy { dst_mem, src_mem } => {
+                    let len = self.pop_u32();
+                    let src = self.pop_u32();
+                    let dst = self.pop_u32();
+                    let src_memory = Self::memory(vmctx, src_mem);
+                    let dst_memory = Self::memory(vmctx, dst_mem);
+                    let src = Self::memory_range(&src_memory, src, len)?;
+                    let dst = Self::memory_range(&dst_memory, dst, len)?;
+                    ptr::copy(
+                        src_memory.base.add(src),
+                        dst_memory.base.add(dst),
+                        len as usize,
+                    );
+                }
+                Operator::MemoryFill { mem } => {
+                    let len = self.pop_u32();
+                    let value = self.pop_u32();
+                    let dst = self.pop_u32();
+                    let memory = Self::memory(vmctx, mem);
+                    let dst = Self::memory_range(&memory, dst, len)?;
+                    ptr::write_bytes(memory.base.add(dst), value as u8, len as usize);
+                }
+                Operator::TableInit { elem_index, table } => {
+                    let len = self.pop_u32();
+                    let src = self.pop_u32();
+                    let dst = self.pop_u32();
+                    (*vmctx).instance_mut().table_init(
+                        TableIndex::from_u32(table),
+                        ElemIndex::from_u32(elem_index),
+                        dst,
+                        src,
+                        len,
+                    )?;
+                }
+                Operator::ElemDrop { elem_index } => {
+                    let instance = (*vmctx).instance();
+                    on_host_stack(|| instance.elem_drop(ElemIndex::from_u32(elem_index)));
+                }
+                Operator::TableCopy {
+                    dst_table,
+                    src_table,
+                } => {
+                    let len = self.pop_u32();
+                    let src = self.pop_u32();
+                    let dst = self.pop_u32();
+                    let dst_table = TableIndex::from_u32(dst_table);
+                    let src_table = TableIndex::from_u32(src_table);
+                    if dst_table == src_table {
+                        let table = (*vmctx).instance_mut().get_table(dst_table);
+                        table.copy_within(dst, src, len)?;
+                    } else {
+                        let dst_table = (*vmctx).instance_mut().get_table(dst_table);
+                        let src_table = (*vmctx).instance_mut().get_table(src_table);
+                        dst_table.copy(src_table, dst, src, len)?;
+                    }
+                }
+                Operator::TableFill { table } => {
+                    let len = self.pop_u32();
+                    let value = self.pop();
+                    let dst = self.pop_u32();
+                    let instance = (*vmctx).instance_mut();
+                    let index = TableIndex::from_u32(table);
+                    let element = raw_to_element(instance.get_table(index).ty().ty, value);
+                    instance.table_fill(index, dst, element, len)?;
+                }
+                Operator::TableGet { table } => {
+                    let index = self.pop_u32();
+                    let element = (*vmctx)
+                        .instance_mut()
+                        .get_table(TableIndex::from_u32(table))
+                        .get(index)
+                        .ok_or_else(|| trap(TrapCode::TableAccessOutOfBounds))?;
+                    self.push(element_to_raw(element));
+                }
+                Operator::TableSet { table } => {
+                    let value = self.pop();
+                    let index = self.pop_u32();
+                    let table = (*vmctx)
+                        .instance_mut()
+                        .get_table(TableIndex::from_u32(table));
+                    let element = raw_to_element(table.ty().ty, value);
+                    table.set(index, element)?;
+                }
+                Operator::TableGrow { table } => {
+                    let delta = self.pop_u32();
+                    let value = self.pop();
+                    let size = on_host_stack(|| {
+                        let table = (*vmctx)
+                            .instance_mut()
+                            .get_table(TableIndex::from_u32(table));
+                        let element = raw_to_element(table.ty().ty, value);
+                        table.grow(delta, element).unwrap_or(u32::MAX)
+                    });
+                    self.push_u32(size);
+                }
+                Operator::TableSize { table } => {
+                    let size = (*vmctx)
+                        .instance_mut()
+                        .get_table(TableIndex::from_u32(table))
+                        .size();
+                    self.push_u32(size);
+                }
+
+                op => unreachable!("operator {:?} is rejected at compile time", op),
+            }
+        }
+    }
+}
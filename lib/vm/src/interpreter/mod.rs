@@ -0,0 +1,428 @@
+//! Runtime support for modules compiled to interpreter bytecode.
+//!
+//! The interpreter doesn't need any executable memory: the "function bodies"
+//! of an interpreted artifact are plain data, laid out by [`encode_function`],
+//! and every call trampoline of the artifact is [`call_trampoline`]. Since all
+//! calls into Wasm go through the call trampoline of a
+//! [`VMCallerCheckedAnyfunc`], interpreted functions can be called from the
+//! host, from host functions and from each other like native ones.
+//!
+//! The bytecode is the original Wasm operator stream of the function,
+//! decoded on the fly, plus a side table holding the position of the `else`
+//! and `end` of every block so branches don't need to scan the code.
+//!
+//! [`VMCallerCheckedAnyfunc`]: crate::VMCallerCheckedAnyfunc
+
+#![allow(clippy::float_arithmetic)]
+
+mod exec;
+
+use crate::trap::{default_stack_size, raise_lib_trap};
+use crate::vmcontext::VMContext;
+use crate::VMFunctionBody;
+use std::convert::TryInto;
+use wasmer_types::RawValue;
+use wasmparser::Operator;
+
+
+/// The header is made of the function index, the number of locals (not
+/// counting the parameters), the offset of the code in the Wasm module,
+/// whether the function was compiled for debugging, the number of blocks
+/// and the code length.
+const HEADER_LEN: usize = 24;
+
+/// Each block is described by its start, `else` and `end` positions followed
+/// by its parameter and result counts.
+const BLOCK_LEN: usize = 16;
+
+/// Marks blocks without an `else` branch.
+const NO_ELSE: u32 = u32::MAX;
+
+/// How much of the Wasm stack size an interpreted frame accounts for, to
+/// limit the call depth about as much as native code would be.
+const FRAME_COST: usize = 128;
+
+/// Control flow information about a `block`, `loop` or `if`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct BlockInfo {
+    /// Position of the instruction starting the block.
+    pub start: u32,
+    /// Position of the `else` instruction, if any.
+    pub else_: Option<u32>,
+    /// Position right after the matching `end` instruction.
+    pub end: u32,
+    /// Number of values the block takes from the stack.
+    pub params: u16,
+    /// Number of values the block leaves on the stack.
+    pub results: u16,
+}
+
+/// Encodes an interpreted function body.
+///
+/// `code` is the operator stream of the function, starting right after the
+/// local declarations, and `code_offset` its offset in the Wasm module;
+/// block positions are relative to its start and `blocks` must be sorted by
+/// start position. Only functions encoded with `debugging` call the
+/// attached [`Debugger`].
+pub fn encode_function(
+    function_index: u32,
+    num_locals: u32,
+    code_offset: u32,
+    debugging: bool,
+    blocks: &[BlockInfo],
+    code: &[u8],
+) -> Vec<u8> {
+    let mut body = Vec::with_capacity(HEADER_LEN + blocks.len() * BLOCK_LEN + code.len());
+    body.extend_from_slice(&function_index.to_le_bytes());
+    body.extend_from_slice(&num_locals.to_le_bytes());
+    body.extend_from_slice(&code_offset.to_le_bytes());
+    body.extend_from_slice(&(debugging as u32).to_le_bytes());
+    body.extend_from_slice(&(blocks.len() as u32).to_le_bytes());
+    body.extend_from_slice(&(code.len() as u32).to_le_bytes());
+    for block in blocks {
+        body.extend_from_slice(&block.start.to_le_bytes());
+        body.extend_from_slice(&block.else_.unwrap_or(NO_ELSE).to_le_bytes());
+        body.extend_from_slice(&block.end.to_le_bytes());
+        body.extend_from_slice(&block.params.to_le_bytes());
+        body.extend_from_slice(&block.results.to_le_bytes());
+    }
+    body.extend_from_slice(code);
+    body
+}
+
+/// Whether the interpreter can run `op`.
+///
+/// Modules are checked with this at compile time, so the interpreter itself
+/// never has to deal with unsupported operators.
+pub fn supports_operator(op: &Operator) -> bool {
+    matches!(
+        op,
+        Operator::Unreachable
+            | Operator::Nop
+            | Operator::Block { .. }
+            | Operator::Loop { .. }
+            | Operator::If { .. }
+            | Operator::Else
+            | Operator::End
+            | Operator::Br { .. }
+            | Operator::BrIf { .. }
+            | Operator::BrTable { .. }
+            | Operator::Return
+            | Operator::Call { .. }
+            | Operator::CallIndirect { .. }
+            | Operator::Drop
+            | Operator::Select
+            | Operator::TypedSelect { .. }
+            | Operator::LocalGet { .. }
+            | Operator::LocalSet { .. }
+            | Operator::LocalTee { .. }
+            | Operator::GlobalGet { .. }
+            | Operator::GlobalSet { .. }
+            | Operator::I32Load { .. }
+            | Operator::I64Load { .. }
+            | Operator::F32Load { .. }
+            | Operator::F64Load { .. }
+            | Operator::I32Load8S { .. }
+            | Operator::I32Load8U { .. }
+            | Operator::I32Load16S { .. }
+            | Operator::I32Load16U { .. }
+            | Operator::I64Load8S { .. }
+            | Operator::I64Load8U { .. }
+            | Operator::I64Load16S { .. }
+            | Operator::I64Load16U { .. }
+            | Operator::I64Load32S { .. }
+            | Operator::I64Load32U { .. }
+            | Operator::I32Store { .. }
+            | Operator::F32Store { .. }
+            | Operator::I64Store { .. }
+            | Operator::F64Store { .. }
+            | Operator::I32Store8 { .. }
+            | Operator::I64Store8 { .. }
+            | Operator::I32Store16 { .. }
+            | Operator::I64Store16 { .. }
+            | Operator::I64Store32 { .. }
+            | Operator::MemorySize { .. }
+            | Operator::MemoryGrow { .. }
+            | Operator::I32Const { .. }
+            | Operator::I64Const { .. }
+            | Operator::F32Const { .. }
+            | Operator::F64Const { .. }
+            | Operator::RefNull { .. }
+            | Operator::RefIsNull
+            | Operator::RefFunc { .. }
+            | Operator::I32Eqz
+            | Operator::I32Eq
+            | Operator::I32Ne
+            | Operator::I32LtS
+            | Operator::I32LtU
+            | Operator::I32GtS
+            | Operator::I32GtU
+            | Operator::I32LeS
+            | Operator::I32LeU
+            | Operator::I32GeS
+            | Operator::I32GeU
+            | Operator::I64Eqz
+            | Operator::I64Eq
+            | Operator::I64Ne
+            | Operator::I64LtS
+            | Operator::I64LtU
+            | Operator::I64GtS
+            | Operator::I64GtU
+            | Operator::I64LeS
+            | Operator::I64LeU
+            | Operator::I64GeS
+            | Operator::I64GeU
+            | Operator::F32Eq
+            | Operator::F32Ne
+            | Operator::F32Lt
+            | Operator::F32Gt
+            | Operator::F32Le
+            | Operator::F32Ge
+            | Operator::F64Eq
+            | Operator::F64Ne
+            | Operator::F64Lt
+            | Operator::F64Gt
+            | Operator::F64Le
+            | Operator::F64Ge
+            | Operator::I32Clz
+            | Operator::I32Ctz
+            | Operator::I32Popcnt
+            | Operator::I32Add
+            | Operator::I32Sub
+            | Operator::I32Mul
+            | Operator::I32DivS
+            | Operator::I32DivU
+            | Operator::I32RemS
+            | Operator::I32RemU
+            | Operator::I32And
+            | Operator::I32Or
+            | Operator::I32Xor
+            | Operator::I32Shl
+            | Operator::I32ShrS
+            | Operator::I32ShrU
+            | Operator::I32Rotl
+            | Operator::I32Rotr
+            | Operator::I64Clz
+            | Operator::I64Ctz
+            | Operator::I64Popcnt
+            | Operator::I64Add
+            | Operator::I64Sub
+            | Operator::I64Mul
+            | Operator::I64DivS
+            | Operator::I64DivU
+            | Operator::I64RemS
+            | Operator::I64RemU
+            | Operator::I64And
+            | Operator::I64Or
+            | Operator::I64Xor
+            | Operator::I64Shl
+            | Operator::I64ShrS
+            | Operator::I64ShrU
+            | Operator::I64Rotl
+            | Operator::I64Rotr
+            | Operator::F32Abs
+            | Operator::F32Neg
+            | Operator::F32Ceil
+            | Operator::F32Floor
+            | Operator::F32Trunc
+            | Operator::F32Nearest
+            | Operator::F32Sqrt
+            | Operator::F32Add
+            | Operator::F32Sub
+            | Operator::F32Mul
+            | Operator::F32Div
+            | Operator::F32Min
+            | Operator::F32Max
+            | Operator::F32Copysign
+            | Operator::F64Abs
+            | Operator::F64Neg
+            | Operator::F64Ceil
+            | Operator::F64Floor
+            | Operator::F64Trunc
+            | Operator::F64Nearest
+            | Operator::F64Sqrt
+            | Operator::F64Add
+            | Operator::F64Sub
+            | Operator::F64Mul
+            | Operator::F64Div
+            | Operator::F64Min
+            | Operator::F64Max
+            | Operator::F64Copysign
+            | Operator::I32WrapI64
+            | Operator::I32TruncF32S
+            | Operator::I32TruncF32U
+            | Operator::I32TruncF64S
+            | Operator::I32TruncF64U
+            | Operator::I64ExtendI32S
+            | Operator::I64ExtendI32U
+            | Operator::I64TruncF32S
+            | Operator::I64TruncF32U
+            | Operator::I64TruncF64S
+            | Operator::I64TruncF64U
+            | Operator::F32ConvertI32S
+            | Operator::F32ConvertI32U
+            | Operator::F32ConvertI64S
+            | Operator::F32ConvertI64U
+            | Operator::F32DemoteF64
+            | Operator::F64ConvertI32S
+            | Operator::F64ConvertI32U
+            | Operator::F64ConvertI64S
+            | Operator::F64ConvertI64U
+            | Operator::F64PromoteF32
+            | Operator::I32ReinterpretF32
+            | Operator::I64ReinterpretF64
+            | Operator::F32ReinterpretI32
+            | Operator::F64ReinterpretI64
+            | Operator::I32Extend8S
+            | Operator::I32Extend16S
+            | Operator::I64Extend8S
+            | Operator::I64Extend16S
+            | Operator::I64Extend32S
+            | Operator::I32TruncSatF32S
+            | Operator::I32TruncSatF32U
+            | Operator::I32TruncSatF64S
+            | Operator::I32TruncSatF64U
+            | Operator::I64TruncSatF32S
+            | Operator::I64TruncSatF32U
+            | Operator::I64TruncSatF64S
+            | Operator::I64TruncSatF64U
+            | Operator::MemoryInit { .. }
+            | Operator::DataDrop { .. }
+            | Operator::MemoryCopy { .. }
+            | Operator::MemoryFill { .. }
+            | Operator::TableInit { .. }
+            | Operator::ElemDrop { .. }
+            | Operator::TableCopy { .. }
+            | Operator::TableFill { .. }
+            | Operator::TableGet { .. }
+            | Operator::TableSet { .. }
+            | Operator::TableGrow { .. }
+            | Operator::TableSize { .. }
+    )
+}
+
+/// A decoded view of an interpreted function body.
+#[derive(Clone, Copy)]
+struct Function<'a> {
+    index: u32,
+    num_locals: u32,
+    code_offset: u32,
+    debugging: bool,
+    blocks: &'a [u8],
+    code: &'a [u8],
+}
+
+impl<'a> Function<'a> {
+    /// Decodes the function at `body`.
+    ///
+    /// # Safety
+    ///
+    /// `body` must point to a function encoded with [`encode_function`].
+    unsafe fn from_body(body: *const VMFunctionBody) -> Self {
+        let body = body as *const u8;
+        let header = std::slice::from_raw_parts(body, HEADER_LEN);
+        let field = |i: usize| u32::from_le_bytes(header[i * 4..i * 4 + 4].try_into().unwrap());
+        let blocks_len = field(4) as usize * BLOCK_LEN;
+        let blocks = std::slice::from_raw_parts(body.add(HEADER_LEN), blocks_len);
+        let code = std::slice::from_raw_parts(body.add(HEADER_LEN + blocks_len), field(5) as usize);
+        Self {
+            index: field(0),
+            num_locals: field(1),
+            code_offset: field(2),
+            debugging: field(3) != 0,
+            blocks,
+            code,
+        }
+    }
+
+    /// Looks up the block starting at `start`.
+    fn block(&self, start: u32) -> BlockInfo {
+        let count = self.blocks.len() / BLOCK_LEN;
+        let (mut lo, mut hi) = (0, count);
+        while lo < hi {
+            let mid = (lo + hi) / 2;
+            let info = self.block_at(mid);
+            match info.start.cmp(&start) {
+                std::cmp::Ordering::Less => lo = mid + 1,
+                std::cmp::Ordering::Greater => hi = mid,
+                std::cmp::Ordering::Equal => return info,
+            }
+        }
+        panic!("no block information at offset {}", start)
+    }
+
+    fn block_at(&self, index: usize) -> BlockInfo {
+        let record = &self.blocks[index * BLOCK_LEN..(index + 1) * BLOCK_LEN];
+        let word = |i: usize| u32::from_le_bytes(record[i..i + 4].try_into().unwrap());
+        let half = |i: usize| u16::from_le_bytes(record[i..i + 2].try_into().unwrap());
+        let else_ = word(4);
+        BlockInfo {
+            start: word(0),
+            else_: (else_ != NO_ELSE).then_some(else_),
+            end: word(8),
+            params: half(12),
+            results: half(14),
+        }
+    }
+}
+
+/// The call trampoline of every function of an interpreted module.
+///
+/// Like the trampolines generated by the compilers, it reads the arguments
+/// from `values` and writes the results back to it.
+///
+/// # Safety
+///
+/// `vmctx` must be the context of the instance owning `body`, and `body`
+/// must point to a function encoded with [`encode_function`]. Traps unwind
+/// to the innermost `catch_traps`.
+pub unsafe extern "C" fn call_trampoline(
+    vmctx: *mut VMContext,
+    body: *const VMFunctionBody,
+    values: *mut RawValue,
+) {
+    let result = {
+        let mut machine = exec::Machine::new(default_stack_size() / FRAME_COST);
+        machine.call_from_host(vmctx, Function::from_body(body), values)
+    };
+    if let Err(trap) = result {
+        raise_lib_trap(trap);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn function_round_trip() {
+        let blocks = [
+            BlockInfo {
+                start: 0,
+                else_: None,
+                end: 10,
+                params: 0,
+                results: 1,
+            },
+            BlockInfo {
+                start: 3,
+                else_: Some(6),
+                end: 9,
+                params: 2,
+                results: 0,
+            },
+        ];
+        let code = [
+            0x02, 0x7f, 0x0b, 0x04, 0x40, 0x01, 0x05, 0x01, 0x0b, 0x0b, 0x0b,
+        ];
+        let body = encode_function(7, 3, 120, true, &blocks, &code);
+        let function = unsafe { Function::from_body(body.as_ptr() as *const VMFunctionBody) };
+        assert_eq!(function.index, 7);
+        assert_eq!(function.num_locals, 3);
+        assert_eq!(function.code_offset, 120);
+        assert!(function.debugging);
+        assert_eq!(function.code, &code);
+        assert_eq!(function.block(0), blocks[0]);
+        assert_eq!(function.block(3), blocks[1]);
+    }
+}
@@ -0,0 +1,16 @@
+//! Compilation of the functions of lazily compiled modules.
+//!
+//! The functions of a lazily compiled module are first compiled to stubs,
+//! which call into the VM on their first call. The [`LazyCompiler`] of the
+//! instance then compiles the function, and redirects the stub to it
+//! through the [`VMFunctionTier`](crate::VMFunctionTier) of the function.
+
+use crate::FunctionBodyPtr;
+use wasmer_types::{CompileError, LocalFunctionIndex};
+
+/// Compiles the functions of a lazily compiled module on their first call.
+pub trait LazyCompiler: Send + Sync {
+    /// Compiles the function at `index`, unless it was already compiled,
+    /// redirects its stub to the compiled code and returns it.
+    fn compile_function(&self, index: LocalFunctionIndex) -> Result<FunctionBodyPtr, CompileError>;
+}
@@ -15,15 +15,20 @@
 )]
 #![cfg_attr(docsrs, feature(doc_cfg, doc_auto_cfg))]
 
+mod debug;
+mod exception;
 mod export;
 mod extern_ref;
 mod function_env;
 mod global;
 mod imports;
 mod instance;
+mod lazy;
 mod memory;
+mod memory_watch;
 mod mmap;
 mod probestack;
+mod profile;
 mod sig_registry;
 mod store;
 mod table;
@@ -31,22 +36,29 @@ mod threadconditions;
 mod trap;
 mod vmcontext;
 
+#[cfg(feature = "interpreter")]
+pub mod interpreter;
 pub mod libcalls;
 
 use std::ptr::NonNull;
 
+pub use crate::debug::{attach_debugger, detach_debugger, DebugFrame, Debugger};
+pub use crate::exception::{VMException, VMExceptionRef, VMTag};
 pub use crate::export::*;
 pub use crate::extern_ref::{VMExternObj, VMExternRef};
 pub use crate::function_env::VMFunctionEnvironment;
 pub use crate::global::*;
 pub use crate::imports::Imports;
 pub use crate::instance::{InstanceAllocator, VMInstance};
+pub use crate::lazy::LazyCompiler;
 pub use crate::memory::{
     initialize_memory_with_data, LinearMemory, NotifyLocation, VMMemory, VMOwnedMemory,
     VMSharedMemory,
 };
+pub use crate::memory_watch::{MemoryAccess, MemoryAccessKind, MemoryWatch};
 pub use crate::mmap::{Mmap, MmapType};
 pub use crate::probestack::PROBESTACK;
+pub use crate::profile::VMProfile;
 pub use crate::sig_registry::SignatureRegistry;
 pub use crate::store::{InternalStoreHandle, MaybeInstanceOwned, StoreHandle, StoreObjects};
 pub use crate::table::{TableElement, VMTable};
@@ -55,8 +67,9 @@ pub use crate::threadconditions::{ThreadConditions, ThreadConditionsHandle, Wait
 pub use crate::trap::*;
 pub use crate::vmcontext::{
     VMCallerCheckedAnyfunc, VMContext, VMDynamicFunctionContext, VMFunctionContext,
-    VMFunctionImport, VMFunctionKind, VMGlobalDefinition, VMGlobalImport, VMMemoryDefinition,
-    VMMemoryImport, VMSharedSignatureIndex, VMTableDefinition, VMTableImport, VMTrampoline,
+    VMFunctionImport, VMFunctionKind, VMFunctionTier, VMGlobalDefinition, VMGlobalImport,
+    VMMemoryDefinition, VMMemoryImport, VMSharedSignatureIndex, VMTableDefinition, VMTableImport,
+    VMTrampoline,
 };
 pub use wasmer_types::LibCall;
 pub use wasmer_types::MemoryError;
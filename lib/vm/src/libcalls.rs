@@ -39,13 +39,13 @@
 
 use crate::probestack::PROBESTACK;
 use crate::table::{RawTableElement, TableElement};
-use crate::trap::{raise_lib_trap, Trap, TrapCode};
+use crate::trap::{raise_lib_trap, raise_user_trap, Trap, TrapCode};
 use crate::vmcontext::VMContext;
-use crate::{on_host_stack, VMFuncRef};
+use crate::{on_host_stack, DebugFrame, MemoryAccessKind, VMExceptionRef, VMFuncRef};
 pub use wasmer_types::LibCall;
 use wasmer_types::{
-    DataIndex, ElemIndex, FunctionIndex, LocalMemoryIndex, LocalTableIndex, MemoryIndex,
-    TableIndex, Type,
+    DataIndex, ElemIndex, FunctionIndex, LocalFunctionIndex, LocalMemoryIndex, LocalTableIndex,
+    MemoryIndex, RawValue, TableIndex, TagIndex, Type,
 };
 
 /// Implementation of f32.ceil
@@ -66,6 +66,13 @@ pub extern "C" fn wasmer_vm_f32_trunc(x: f32) -> f32 {
     x.trunc()
 }
 
+/// Implementation of a fused multiply-add of f32 lanes, for targets without
+/// native FMA instructions.
+#[no_mangle]
+pub extern "C" fn wasmer_vm_f32_fma(x: f32, y: f32, z: f32) -> f32 {
+    x.mul_add(y, z)
+}
+
 /// Implementation of f32.nearest
 #[allow(clippy::float_arithmetic, clippy::float_cmp)]
 #[no_mangle]
@@ -111,6 +118,13 @@ pub extern "C" fn wasmer_vm_f64_trunc(x: f64) -> f64 {
     x.trunc()
 }
 
+/// Implementation of a fused multiply-add of f64 lanes, for targets without
+/// native FMA instructions.
+#[no_mangle]
+pub extern "C" fn wasmer_vm_f64_fma(x: f64, y: f64, z: f64) -> f64 {
+    x.mul_add(y, z)
+}
+
 /// Implementation of f64.nearest
 #[allow(clippy::float_arithmetic, clippy::float_cmp)]
 #[no_mangle]
@@ -211,6 +225,79 @@ pub unsafe extern "C" fn wasmer_vm_imported_memory32_size(
     instance.imported_memory_size(memory_index).0
 }
 
+/// Implementation of memory.grow for locally-defined 64-bit memories.
+///
+/// # Safety
+///
+/// `vmctx` must be dereferenceable.
+#[no_mangle]
+pub unsafe extern "C" fn wasmer_vm_memory64_grow(
+    vmctx: *mut VMContext,
+    delta: u64,
+    memory_index: u32,
+) -> u64 {
+    on_host_stack(|| {
+        let instance = (*vmctx).instance_mut();
+        let memory_index = LocalMemoryIndex::from_u32(memory_index);
+
+        u32::try_from(delta)
+            .ok()
+            .and_then(|delta| instance.memory_grow(memory_index, delta).ok())
+            .map_or(u64::max_value(), |pages| pages.0.into())
+    })
+}
+
+/// Implementation of memory.grow for imported 64-bit memories.
+///
+/// # Safety
+///
+/// `vmctx` must be dereferenceable.
+#[no_mangle]
+pub unsafe extern "C" fn wasmer_vm_imported_memory64_grow(
+    vmctx: *mut VMContext,
+    delta: u64,
+    memory_index: u32,
+) -> u64 {
+    on_host_stack(|| {
+        let instance = (*vmctx).instance_mut();
+        let memory_index = MemoryIndex::from_u32(memory_index);
+
+        u32::try_from(delta)
+            .ok()
+            .and_then(|delta| instance.imported_memory_grow(memory_index, delta).ok())
+            .map_or(u64::max_value(), |pages| pages.0.into())
+    })
+}
+
+/// Implementation of memory.size for locally-defined 64-bit memories.
+///
+/// # Safety
+///
+/// `vmctx` must be dereferenceable.
+#[no_mangle]
+pub unsafe extern "C" fn wasmer_vm_memory64_size(vmctx: *mut VMContext, memory_index: u32) -> u64 {
+    let instance = (*vmctx).instance();
+    let memory_index = LocalMemoryIndex::from_u32(memory_index);
+
+    instance.memory_size(memory_index).0.into()
+}
+
+/// Implementation of memory.size for imported 64-bit memories.
+///
+/// # Safety
+///
+/// `vmctx` must be dereferenceable.
+#[no_mangle]
+pub unsafe extern "C" fn wasmer_vm_imported_memory64_size(
+    vmctx: *mut VMContext,
+    memory_index: u32,
+) -> u64 {
+    let instance = (*vmctx).instance();
+    let memory_index = MemoryIndex::from_u32(memory_index);
+
+    instance.imported_memory_size(memory_index).0.into()
+}
+
 /// Implementation of `table.copy`.
 ///
 /// # Safety
@@ -531,7 +618,7 @@ pub unsafe extern "C" fn wasmer_vm_memory32_copy(
     let result = {
         let memory_index = LocalMemoryIndex::from_u32(memory_index);
         let instance = (*vmctx).instance();
-        instance.local_memory_copy(memory_index, dst, src, len)
+        instance.local_memory_copy(memory_index, dst.into(), src.into(), len.into())
     };
     if let Err(trap) = result {
         raise_lib_trap(trap);
@@ -554,7 +641,7 @@ pub unsafe extern "C" fn wasmer_vm_imported_memory32_copy(
     let result = {
         let memory_index = MemoryIndex::from_u32(memory_index);
         let instance = (*vmctx).instance();
-        instance.imported_memory_copy(memory_index, dst, src, len)
+        instance.imported_memory_copy(memory_index, dst.into(), src.into(), len.into())
     };
     if let Err(trap) = result {
         raise_lib_trap(trap);
@@ -577,7 +664,7 @@ pub unsafe extern "C" fn wasmer_vm_memory32_fill(
     let result = {
         let memory_index = LocalMemoryIndex::from_u32(memory_index);
         let instance = (*vmctx).instance();
-        instance.local_memory_fill(memory_index, dst, val, len)
+        instance.local_memory_fill(memory_index, dst.into(), val, len.into())
     };
     if let Err(trap) = result {
         raise_lib_trap(trap);
@@ -600,7 +687,7 @@ pub unsafe extern "C" fn wasmer_vm_imported_memory32_fill(
     let result = {
         let memory_index = MemoryIndex::from_u32(memory_index);
         let instance = (*vmctx).instance();
-        instance.imported_memory_fill(memory_index, dst, val, len)
+        instance.imported_memory_fill(memory_index, dst.into(), val, len.into())
     };
     if let Err(trap) = result {
         raise_lib_trap(trap);
@@ -620,6 +707,123 @@ pub unsafe extern "C" fn wasmer_vm_memory32_init(
     dst: u32,
     src: u32,
     len: u32,
+) {
+    let result = {
+        let memory_index = MemoryIndex::from_u32(memory_index);
+        let data_index = DataIndex::from_u32(data_index);
+        let instance = (*vmctx).instance();
+        instance.memory_init(memory_index, data_index, dst.into(), src, len)
+    };
+    if let Err(trap) = result {
+        raise_lib_trap(trap);
+    }
+}
+
+/// Implementation of `memory.copy` for locally defined 64-bit memories.
+///
+/// # Safety
+///
+/// `vmctx` must be dereferenceable.
+#[no_mangle]
+pub unsafe extern "C" fn wasmer_vm_memory64_copy(
+    vmctx: *mut VMContext,
+    memory_index: u32,
+    dst: u64,
+    src: u64,
+    len: u64,
+) {
+    let result = {
+        let memory_index = LocalMemoryIndex::from_u32(memory_index);
+        let instance = (*vmctx).instance();
+        instance.local_memory_copy(memory_index, dst, src, len)
+    };
+    if let Err(trap) = result {
+        raise_lib_trap(trap);
+    }
+}
+
+/// Implementation of `memory.copy` for imported 64-bit memories.
+///
+/// # Safety
+///
+/// `vmctx` must be dereferenceable.
+#[no_mangle]
+pub unsafe extern "C" fn wasmer_vm_imported_memory64_copy(
+    vmctx: *mut VMContext,
+    memory_index: u32,
+    dst: u64,
+    src: u64,
+    len: u64,
+) {
+    let result = {
+        let memory_index = MemoryIndex::from_u32(memory_index);
+        let instance = (*vmctx).instance();
+        instance.imported_memory_copy(memory_index, dst, src, len)
+    };
+    if let Err(trap) = result {
+        raise_lib_trap(trap);
+    }
+}
+
+/// Implementation of `memory.fill` for locally defined 64-bit memories.
+///
+/// # Safety
+///
+/// `vmctx` must be dereferenceable.
+#[no_mangle]
+pub unsafe extern "C" fn wasmer_vm_memory64_fill(
+    vmctx: *mut VMContext,
+    memory_index: u32,
+    dst: u64,
+    val: u32,
+    len: u64,
+) {
+    let result = {
+        let memory_index = LocalMemoryIndex::from_u32(memory_index);
+        let instance = (*vmctx).instance();
+        instance.local_memory_fill(memory_index, dst, val, len)
+    };
+    if let Err(trap) = result {
+        raise_lib_trap(trap);
+    }
+}
+
+/// Implementation of `memory.fill` for imported 64-bit memories.
+///
+/// # Safety
+///
+/// `vmctx` must be dereferenceable.
+#[no_mangle]
+pub unsafe extern "C" fn wasmer_vm_imported_memory64_fill(
+    vmctx: *mut VMContext,
+    memory_index: u32,
+    dst: u64,
+    val: u32,
+    len: u64,
+) {
+    let result = {
+        let memory_index = MemoryIndex::from_u32(memory_index);
+        let instance = (*vmctx).instance();
+        instance.imported_memory_fill(memory_index, dst, val, len)
+    };
+    if let Err(trap) = result {
+        raise_lib_trap(trap);
+    }
+}
+
+/// Implementation of `memory.init` for 64-bit memories.
+///
+/// # Safety
+///
+/// `vmctx` must be dereferenceable.
+#[no_mangle]
+pub unsafe extern "C" fn wasmer_vm_memory64_init(
+    vmctx: *mut VMContext,
+    memory_index: u32,
+    data_index: u32,
+    dst: u64,
+    src: u32,
+    len: u32,
 ) {
     let result = {
         let memory_index = MemoryIndex::from_u32(memory_index);
@@ -815,6 +1019,188 @@ pub unsafe extern "C" fn wasmer_vm_imported_memory32_atomic_notify(
     result.unwrap()
 }
 
+/// Implementation of the memory watch for loads.
+///
+/// # Safety
+///
+/// `vmctx` must be dereferenceable.
+#[no_mangle]
+pub unsafe extern "C" fn wasmer_vm_memory_watch_load(
+    vmctx: *mut VMContext,
+    function_index: u32,
+    memory_index: u32,
+    address: u32,
+    offset: u32,
+    size: u32,
+) {
+    let instance = (*vmctx).instance();
+    instance.report_memory_access(
+        MemoryAccessKind::Load,
+        FunctionIndex::from_u32(function_index),
+        MemoryIndex::from_u32(memory_index),
+        address as u64 + offset as u64,
+        size,
+        0,
+    );
+}
+
+/// Implementation of the memory watch for stores.
+///
+/// # Safety
+///
+/// `vmctx` must be dereferenceable.
+#[no_mangle]
+pub unsafe extern "C" fn wasmer_vm_memory_watch_store(
+    vmctx: *mut VMContext,
+    function_index: u32,
+    memory_index: u32,
+    address: u32,
+    offset: u32,
+    size: u32,
+    value: u64,
+) {
+    let instance = (*vmctx).instance();
+    instance.report_memory_access(
+        MemoryAccessKind::Store,
+        FunctionIndex::from_u32(function_index),
+        MemoryIndex::from_u32(memory_index),
+        address as u64 + offset as u64,
+        size,
+        value,
+    );
+}
+
+/// Implementation of the compilation of the functions of lazily compiled
+/// modules, called by their stubs on their first call.
+///
+/// # Safety
+///
+/// `vmctx` must be dereferenceable.
+#[no_mangle]
+pub unsafe extern "C" fn wasmer_vm_lazy_compile(vmctx: *mut VMContext, function_index: u32) {
+    let result = on_host_stack(|| {
+        let instance = (*vmctx).instance();
+        instance.lazy_compile(LocalFunctionIndex::from_u32(function_index))
+    });
+    if let Err(error) = result {
+        raise_user_trap(Box::new(error));
+    }
+}
+
+/// Implementation of the counters of functions compiled with profile
+/// instrumentation, called on entry and at each conditional branch.
+///
+/// # Safety
+///
+/// `vmctx` must be dereferenceable.
+#[no_mangle]
+pub unsafe extern "C" fn wasmer_vm_profile_count(
+    vmctx: *mut VMContext,
+    function_index: u32,
+    counter: u32,
+    num_counters: u32,
+) {
+    let instance = (*vmctx).instance();
+    instance.profile_count(
+        LocalFunctionIndex::from_u32(function_index),
+        counter,
+        num_counters,
+    );
+}
+
+/// Calls the attached debugger, if any, before the instruction at
+/// `module_offset` of a function compiled for debugging.
+///
+/// The compiled code copies the parameters and locals of the function to
+/// `locals_offset` bytes below its `frame` pointer before the call, and back
+/// from there after, so the debugger can modify them.
+///
+/// # Safety
+///
+/// `vmctx` must be dereferenceable, and `num_locals` values must be stored
+/// `locals_offset` bytes below `frame`.
+#[no_mangle]
+pub unsafe extern "C" fn wasmer_vm_debug_hook(
+    vmctx: *mut VMContext,
+    function_index: u32,
+    module_offset: u32,
+    frame: *mut u8,
+    locals_offset: u32,
+    num_locals: u32,
+) {
+    if !crate::debug::is_attached() {
+        return;
+    }
+    let locals = frame.sub(locals_offset as usize) as *mut u64;
+    let locals = std::slice::from_raw_parts_mut(locals, num_locals as usize);
+    on_host_stack(|| {
+        if let Some(debugger) = crate::debug::debugger() {
+            debugger.on_instruction(&mut DebugFrame {
+                vmctx,
+                function_index,
+                module_offset: module_offset as usize,
+                locals,
+            });
+        }
+    })
+}
+
+/// Implementation of `throw`.
+///
+/// # Safety
+///
+/// `vmctx` must be dereferenceable, and `payload` must point to as many
+/// values as the tag has parameters.
+#[no_mangle]
+pub unsafe extern "C" fn wasmer_vm_throw(
+    vmctx: *mut VMContext,
+    tag_index: u32,
+    payload: *const RawValue,
+) {
+    let instance = (*vmctx).instance_mut();
+    instance.throw(TagIndex::from_u32(tag_index), payload);
+}
+
+/// Implementation of `throw_ref`.
+///
+/// # Safety
+///
+/// `vmctx` must be dereferenceable.
+#[no_mangle]
+pub unsafe extern "C" fn wasmer_vm_throw_ref(vmctx: *mut VMContext, exception: usize) {
+    let instance = (*vmctx).instance();
+    match VMExceptionRef::from_raw(RawValue { exnref: exception }) {
+        Some(exception) => instance.throw_ref(exception),
+        None => raise_lib_trap(Trap::lib(TrapCode::NullReference)),
+    }
+}
+
+/// Catches the pending exception in a `try_table`, if it has the tag of
+/// index `tag_index`, or whatever its tag when `tag_index` is `u32::MAX`.
+///
+/// Returns the caught exception, or null if the exception doesn't match.
+///
+/// # Safety
+///
+/// `vmctx` must be dereferenceable, and `payload` must have room for as
+/// many values as the tag has parameters.
+#[no_mangle]
+pub unsafe extern "C" fn wasmer_vm_catch(
+    vmctx: *mut VMContext,
+    tag_index: u32,
+    payload: *mut RawValue,
+) -> usize {
+    let instance = (*vmctx).instance();
+    let tag_index = if tag_index == u32::MAX {
+        None
+    } else {
+        Some(TagIndex::from_u32(tag_index))
+    };
+    instance
+        .catch(tag_index, payload)
+        .map_or(0, |exception| exception.into_raw().exnref)
+}
+
 /// The function pointer to a libcall
 pub fn function_pointer(libcall: LibCall) -> usize {
     match libcall {
@@ -855,5 +1241,24 @@ pub fn function_pointer(libcall: LibCall) -> usize {
         LibCall::ImportedMemory32AtomicWait64 => wasmer_vm_imported_memory32_atomic_wait64 as usize,
         LibCall::Memory32AtomicNotify => wasmer_vm_memory32_atomic_notify as usize,
         LibCall::ImportedMemory32AtomicNotify => wasmer_vm_imported_memory32_atomic_notify as usize,
+        LibCall::MemoryWatchLoad => wasmer_vm_memory_watch_load as usize,
+        LibCall::MemoryWatchStore => wasmer_vm_memory_watch_store as usize,
+        LibCall::Throw => wasmer_vm_throw as usize,
+        LibCall::ThrowRef => wasmer_vm_throw_ref as usize,
+        LibCall::Catch => wasmer_vm_catch as usize,
+        LibCall::FmaF32 => wasmer_vm_f32_fma as usize,
+        LibCall::FmaF64 => wasmer_vm_f64_fma as usize,
+        LibCall::Memory64Grow => wasmer_vm_memory64_grow as usize,
+        LibCall::ImportedMemory64Grow => wasmer_vm_imported_memory64_grow as usize,
+        LibCall::Memory64Size => wasmer_vm_memory64_size as usize,
+        LibCall::ImportedMemory64Size => wasmer_vm_imported_memory64_size as usize,
+        LibCall::Memory64Copy => wasmer_vm_memory64_copy as usize,
+        LibCall::ImportedMemory64Copy => wasmer_vm_imported_memory64_copy as usize,
+        LibCall::Memory64Fill => wasmer_vm_memory64_fill as usize,
+        LibCall::ImportedMemory64Fill => wasmer_vm_imported_memory64_fill as usize,
+        LibCall::Memory64Init => wasmer_vm_memory64_init as usize,
+        LibCall::LazyCompile => wasmer_vm_lazy_compile as usize,
+        LibCall::ProfileCount => wasmer_vm_profile_count as usize,
+        LibCall::DebugHook => wasmer_vm_debug_hook as usize,
     }
 }
@@ -50,13 +50,12 @@ impl WasmMmap {
             return Ok(self.size);
         }
 
-        let new_pages = self
-            .size
-            .checked_add(delta)
-            .ok_or(MemoryError::CouldNotGrow {
+        let new_pages = Pages(self.size.0.checked_add(delta.0).ok_or(
+            MemoryError::CouldNotGrow {
                 current: self.size,
                 attempted_delta: delta,
-            })?;
+            },
+        )?);
         let prev_pages = self.size;
 
         if let Some(maximum) = conf.maximum {
@@ -71,7 +70,7 @@ impl WasmMmap {
         // Wasm linear memories are never allowed to grow beyond what is
         // indexable. If the memory has no maximum, enforce the greatest
         // limit here.
-        if new_pages >= Pages::max_value() {
+        if new_pages >= conf.memory.max_pages() {
             // Linear memory size would exceed the index range.
             return Err(MemoryError::CouldNotGrow {
                 current: self.size,
@@ -191,6 +190,17 @@ impl VMMemoryConfig {
     }
 }
 
+/// What the pages of a new memory are mapped from.
+#[derive(Debug)]
+enum MemoryBacking {
+    /// Anonymous zeroed pages.
+    None,
+    /// A file, resized to the size of the memory.
+    File(std::fs::File, MmapType),
+    /// A memory image, mapped copy-on-write.
+    Image(std::fs::File),
+}
+
 /// A linear memory instance.
 #[derive(Debug)]
 pub struct VMOwnedMemory {
@@ -209,7 +219,7 @@ impl VMOwnedMemory {
     /// This creates a `Memory` with owned metadata: this can be used to create a memory
     /// that will be imported into Wasm modules.
     pub fn new(memory: &MemoryType, style: &MemoryStyle) -> Result<Self, MemoryError> {
-        unsafe { Self::new_internal(memory, style, None, None, MmapType::Private) }
+        unsafe { Self::new_internal(memory, style, None, MemoryBacking::None) }
     }
 
     /// Create a new linear memory instance with specified minimum and maximum number of wasm pages
@@ -224,7 +234,29 @@ impl VMOwnedMemory {
         backing_file: std::fs::File,
         memory_type: MmapType,
     ) -> Result<Self, MemoryError> {
-        unsafe { Self::new_internal(memory, style, None, Some(backing_file), memory_type) }
+        unsafe {
+            Self::new_internal(
+                memory,
+                style,
+                None,
+                MemoryBacking::File(backing_file, memory_type),
+            )
+        }
+    }
+
+    /// Create a new linear memory instance with specified minimum and maximum number of wasm pages
+    /// that starts with the contents of a memory image, such as the one produced by
+    /// `wasmer preinit --memory-image`. The pages of the image are mapped lazily and
+    /// copy-on-write, and the file is never resized nor modified.
+    ///
+    /// This creates a `Memory` with owned metadata: this can be used to create a memory
+    /// that will be imported into Wasm modules.
+    pub fn new_with_image(
+        memory: &MemoryType,
+        style: &MemoryStyle,
+        image: std::fs::File,
+    ) -> Result<Self, MemoryError> {
+        unsafe { Self::new_internal(memory, style, None, MemoryBacking::Image(image)) }
     }
 
     /// Create a new linear memory instance with specified minimum and maximum number of wasm pages.
@@ -239,13 +271,7 @@ impl VMOwnedMemory {
         style: &MemoryStyle,
         vm_memory_location: NonNull<VMMemoryDefinition>,
     ) -> Result<Self, MemoryError> {
-        Self::new_internal(
-            memory,
-            style,
-            Some(vm_memory_location),
-            None,
-            MmapType::Private,
-        )
+        Self::new_internal(memory, style, Some(vm_memory_location), MemoryBacking::None)
     }
 
     /// Create a new linear memory instance with specified minimum and maximum number of wasm pages
@@ -264,13 +290,11 @@ impl VMOwnedMemory {
         backing_file: Option<std::fs::File>,
         memory_type: MmapType,
     ) -> Result<Self, MemoryError> {
-        Self::new_internal(
-            memory,
-            style,
-            Some(vm_memory_location),
-            backing_file,
-            memory_type,
-        )
+        let backing = match backing_file {
+            Some(backing_file) => MemoryBacking::File(backing_file, memory_type),
+            None => MemoryBacking::None,
+        };
+        Self::new_internal(memory, style, Some(vm_memory_location), backing)
     }
 
     /// Build a `Memory` with either self-owned or VM owned metadata.
@@ -278,21 +302,22 @@ impl VMOwnedMemory {
         memory: &MemoryType,
         style: &MemoryStyle,
         vm_memory_location: Option<NonNull<VMMemoryDefinition>>,
-        backing_file: Option<std::fs::File>,
-        memory_type: MmapType,
+        backing: MemoryBacking,
     ) -> Result<Self, MemoryError> {
-        if memory.minimum > Pages::max_value() {
+        let max_pages = memory.max_pages();
+        if memory.minimum > max_pages {
             return Err(MemoryError::MinimumMemoryTooLarge {
                 min_requested: memory.minimum,
-                max_allowed: Pages::max_value(),
+                max_allowed: max_pages,
             });
         }
-        // `maximum` cannot be set to more than `65536` pages.
+        // `maximum` cannot be set to more than `65536` pages, unless the
+        // memory is a 64-bit one.
         if let Some(max) = memory.maximum {
-            if max > Pages::max_value() {
+            if max > max_pages {
                 return Err(MemoryError::MaximumMemoryTooLarge {
                     max_requested: max,
-                    max_allowed: Pages::max_value(),
+                    max_allowed: max_pages,
                 });
             }
             if max < memory.minimum {
@@ -319,9 +344,21 @@ impl VMOwnedMemory {
         let mapped_pages = memory.minimum;
         let mapped_bytes = mapped_pages.bytes();
 
-        let mut alloc =
-            Mmap::accessible_reserved(mapped_bytes.0, request_bytes, backing_file, memory_type)
-                .map_err(MemoryError::Region)?;
+        let mut alloc = match backing {
+            MemoryBacking::None => {
+                Mmap::accessible_reserved(mapped_bytes.0, request_bytes, None, MmapType::Private)
+            }
+            MemoryBacking::File(backing_file, memory_type) => Mmap::accessible_reserved(
+                mapped_bytes.0,
+                request_bytes,
+                Some(backing_file),
+                memory_type,
+            ),
+            MemoryBacking::Image(image) => {
+                Mmap::accessible_reserved_with_image(mapped_bytes.0, request_bytes, image)
+            }
+        }
+        .map_err(MemoryError::Region)?;
 
         let base_ptr = alloc.as_mut_ptr();
         let mem_length = memory
@@ -469,6 +506,19 @@ impl VMSharedMemory {
         Ok(VMOwnedMemory::new_with_file(memory, style, backing_file, memory_type)?.to_shared())
     }
 
+    /// Create a new linear memory instance with specified minimum and maximum number of wasm pages
+    /// that starts with the contents of a memory image (see [`VMOwnedMemory::new_with_image`]).
+    ///
+    /// This creates a `Memory` with owned metadata: this can be used to create a memory
+    /// that will be imported into Wasm modules.
+    pub fn new_with_image(
+        memory: &MemoryType,
+        style: &MemoryStyle,
+        image: std::fs::File,
+    ) -> Result<Self, MemoryError> {
+        Ok(VMOwnedMemory::new_with_image(memory, style, image)?.to_shared())
+    }
+
     /// Create a new linear memory instance with specified minimum and maximum number of wasm pages.
     ///
     /// This creates a `Memory` with metadata owned by a VM, pointed to by
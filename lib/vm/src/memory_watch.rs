@@ -0,0 +1,159 @@
+//! Reporting the accesses of guests to watched ranges of linear memory.
+//!
+//! Modules compiled with the memory watch enabled check each load and store
+//! against the bounds of the watched ranges, read through the `VMContext`,
+//! and only call into the VM for accesses within them. The VM then reports
+//! the accesses overlapping a watched range to the [`MemoryWatch`] of the
+//! instance. Accesses to 64-bit memories, vector accesses and atomic
+//! operations aren't reported.
+
+use std::fmt;
+use std::ops::Range;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::RwLock;
+use wasmer_types::{FunctionIndex, MemoryIndex};
+
+/// The smallest range containing all the watched ranges, as its start and
+/// end offsets. Compiled code reads it without locking, so it only needs
+/// to be a superset of the watched ranges.
+pub(crate) type MemoryWatchBounds = [AtomicU64; 2];
+
+/// The bounds of instances without a memory watch, which no access is
+/// within.
+pub(crate) static NO_MEMORY_WATCH_BOUNDS: MemoryWatchBounds =
+    [AtomicU64::new(u64::MAX), AtomicU64::new(0)];
+
+/// Whether a memory access reads or writes memory.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum MemoryAccessKind {
+    /// A load, reporting the value read.
+    Load,
+    /// A store, reporting the value written.
+    Store,
+}
+
+/// An access of a guest to a watched range of its linear memory.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct MemoryAccess {
+    /// Whether the guest reads or writes memory.
+    pub kind: MemoryAccessKind,
+    /// The function doing the access.
+    pub function_index: FunctionIndex,
+    /// The memory being accessed.
+    pub memory_index: MemoryIndex,
+    /// The offset of the access in the memory.
+    pub offset: u64,
+    /// The size of the access in bytes.
+    pub size: u32,
+    /// The value read or written, zero-extended to 64 bits. Floats are
+    /// given as their bit pattern.
+    pub value: u64,
+}
+
+/// The ranges of linear memory being watched, and what to do when a guest
+/// accesses them.
+///
+/// The ranges can be changed while guests run.
+pub struct MemoryWatch {
+    ranges: RwLock<Vec<Range<u64>>>,
+    bounds: MemoryWatchBounds,
+    callback: Box<dyn Fn(&MemoryAccess) + Send + Sync>,
+}
+
+impl MemoryWatch {
+    /// Creates a memory watch calling `callback` for each access to a
+    /// watched range. No range is watched initially.
+    pub fn new(callback: impl Fn(&MemoryAccess) + Send + Sync + 'static) -> Self {
+        Self {
+            ranges: RwLock::new(Vec::new()),
+            bounds: [AtomicU64::new(u64::MAX), AtomicU64::new(0)],
+            callback: Box::new(callback),
+        }
+    }
+
+    /// Starts watching the given range of offsets, in every memory.
+    pub fn watch(&self, range: Range<u64>) {
+        let mut ranges = self.ranges.write().unwrap();
+        ranges.push(range);
+        self.update_bounds(&ranges);
+    }
+
+    /// Stops watching all ranges.
+    pub fn clear(&self) {
+        let mut ranges = self.ranges.write().unwrap();
+        ranges.clear();
+        self.update_bounds(&ranges);
+    }
+
+    fn update_bounds(&self, ranges: &[Range<u64>]) {
+        let start = ranges.iter().map(|range| range.start).min();
+        let end = ranges.iter().map(|range| range.end).max();
+        self.bounds[0].store(start.unwrap_or(u64::MAX), Ordering::Relaxed);
+        self.bounds[1].store(end.unwrap_or(0), Ordering::Relaxed);
+    }
+
+    /// The bounds read by compiled code.
+    pub(crate) fn bounds(&self) -> &MemoryWatchBounds {
+        &self.bounds
+    }
+
+    /// Returns the watched ranges.
+    pub fn ranges(&self) -> Vec<Range<u64>> {
+        self.ranges.read().unwrap().clone()
+    }
+
+    /// Whether an access of `size` bytes at `offset` touches a watched range.
+    pub fn is_watched(&self, offset: u64, size: u32) -> bool {
+        let end = offset.saturating_add(size as u64);
+        self.ranges
+            .read()
+            .unwrap()
+            .iter()
+            .any(|range| offset < range.end && range.start < end)
+    }
+
+    /// Reports an access to a watched range.
+    pub(crate) fn report(&self, access: &MemoryAccess) {
+        (self.callback)(access);
+    }
+}
+
+impl fmt::Debug for MemoryWatch {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("MemoryWatch")
+            .field("ranges", &self.ranges())
+            .finish()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn overlapping_accesses_are_watched() {
+        let watch = MemoryWatch::new(|_| {});
+        watch.watch(16..32);
+        assert!(!watch.is_watched(0, 16));
+        assert!(watch.is_watched(12, 8));
+        assert!(watch.is_watched(31, 1));
+        assert!(!watch.is_watched(32, 4));
+        watch.clear();
+        assert!(!watch.is_watched(16, 4));
+    }
+
+    #[test]
+    fn bounds_contain_the_watched_ranges() {
+        let watch = MemoryWatch::new(|_| {});
+        let bounds = || {
+            let [start, end] = watch.bounds();
+            (start.load(Ordering::Relaxed), end.load(Ordering::Relaxed))
+        };
+        assert_eq!(bounds(), (u64::MAX, 0));
+        watch.watch(64..72);
+        watch.watch(16..32);
+        assert_eq!(bounds(), (16, 72));
+        watch.clear();
+        assert_eq!(bounds(), (u64::MAX, 0));
+    }
+}
@@ -161,6 +161,77 @@ impl Mmap {
         })
     }
 
+    /// Create a new `Mmap` whose first pages are a private, copy-on-write mapping of `image`.
+    ///
+    /// The pages of the image are only read from the file when they are first accessed and
+    /// writes are never propagated back to it. The accessible size is extended to cover the
+    /// whole image (rounded up to the page size) if it is larger than `accessible_size`.
+    /// `mapping_size` must be a native page-size multiple.
+    #[cfg(not(target_os = "windows"))]
+    pub fn accessible_reserved_with_image(
+        accessible_size: usize,
+        mapping_size: usize,
+        image: std::fs::File,
+    ) -> Result<Self, String> {
+        use std::os::fd::AsRawFd;
+
+        let page_size = region::page::size();
+        let image_len = image.metadata().map_err(|e| e.to_string())?.len() as usize;
+        let image_size = round_up_to_page_size(image_len, page_size).min(mapping_size);
+        let accessible_size = accessible_size.max(image_size);
+
+        let result =
+            Self::accessible_reserved(accessible_size, mapping_size, None, MmapType::Private)?;
+        if image_size == 0 {
+            return Ok(result);
+        }
+
+        // Map the image over the start of the reservation, the bytes of the last
+        // page which are past the end of the file will read as zeros.
+        let ptr = unsafe {
+            libc::mmap(
+                result.ptr as *mut libc::c_void,
+                image_size,
+                libc::PROT_READ | libc::PROT_WRITE,
+                libc::MAP_PRIVATE | libc::MAP_FIXED,
+                image.as_raw_fd(),
+                0,
+            )
+        };
+        if ptr as isize == -1_isize {
+            return Err(io::Error::last_os_error().to_string());
+        }
+
+        Ok(result)
+    }
+
+    /// Create a new `Mmap` whose first pages hold a copy of `image`.
+    ///
+    /// Windows has no copy-on-write file mappings which can be placed in a
+    /// reservation, so the image is read eagerly.
+    #[cfg(target_os = "windows")]
+    pub fn accessible_reserved_with_image(
+        accessible_size: usize,
+        mapping_size: usize,
+        mut image: std::fs::File,
+    ) -> Result<Self, String> {
+        use std::io::Read;
+
+        let page_size = region::page::size();
+        let mut contents = Vec::new();
+        image
+            .read_to_end(&mut contents)
+            .map_err(|e| e.to_string())?;
+        contents.truncate(mapping_size);
+        let image_size = round_up_to_page_size(contents.len(), page_size).min(mapping_size);
+        let accessible_size = accessible_size.max(image_size);
+
+        let mut result =
+            Self::accessible_reserved(accessible_size, mapping_size, None, MmapType::Private)?;
+        result.as_mut_slice()[..contents.len()].copy_from_slice(&contents);
+        Ok(result)
+    }
+
     /// Create a new `Mmap` pointing to `accessible_size` bytes of page-aligned accessible memory,
     /// within a reserved mapping of `mapping_size` bytes. `accessible_size` and `mapping_size`
     /// must be native page-size multiples.
@@ -400,4 +471,30 @@ mod tests {
         assert_eq!(round_up_to_page_size(4096, 4096), 4096);
         assert_eq!(round_up_to_page_size(4097, 4096), 8192);
     }
+
+    #[cfg(not(target_os = "windows"))]
+    #[test]
+    fn test_private_image_mapping() {
+        use std::io::{Read, Seek, Write};
+
+        let page_size = region::page::size();
+        let mut image = tempfile::tempfile().unwrap();
+        image.write_all(b"pre-initialized").unwrap();
+
+        let mut mmap =
+            Mmap::accessible_reserved_with_image(0, page_size * 4, image.try_clone().unwrap())
+                .unwrap();
+        assert_eq!(mmap.as_slice_accessible().len(), page_size);
+        assert_eq!(&mmap.as_slice()[..15], b"pre-initialized");
+        assert!(mmap.as_slice()[15..page_size].iter().all(|b| *b == 0));
+
+        // Writes to the mapping must not leak into the image.
+        mmap.as_mut_slice()[..3].copy_from_slice(b"PRE");
+        drop(mmap);
+
+        let mut contents = Vec::new();
+        image.rewind().unwrap();
+        image.read_to_end(&mut contents).unwrap();
+        assert_eq!(contents, b"pre-initialized");
+    }
 }
@@ -0,0 +1,77 @@
+//! Recording the profile of modules compiled with profile instrumentation.
+//!
+//! Functions compiled with profile instrumentation call into the VM on
+//! entry and at each conditional branch, which increments the matching
+//! counter of the [`VMProfile`] of the instance.
+
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::OnceLock;
+use wasmer_types::entity::EntityRef;
+use wasmer_types::{LocalFunctionIndex, Profile};
+
+/// The counters of the local functions of a module compiled with profile
+/// instrumentation, shared by its instances.
+pub struct VMProfile {
+    functions: Box<[OnceLock<Box<[AtomicU64]>>]>,
+}
+
+impl VMProfile {
+    /// Creates the counters of `num_functions` local functions, all zero.
+    pub fn new(num_functions: usize) -> Self {
+        Self {
+            functions: (0..num_functions).map(|_| OnceLock::new()).collect(),
+        }
+    }
+
+    /// Increments the counter at `counter` of the function at `index`,
+    /// which has `num_counters` counters.
+    pub fn count(&self, index: LocalFunctionIndex, counter: u32, num_counters: u32) {
+        let Some(function) = self.functions.get(index.index()) else {
+            return;
+        };
+        let counters =
+            function.get_or_init(|| (0..num_counters).map(|_| AtomicU64::new(0)).collect());
+        if let Some(counter) = counters.get(counter as usize) {
+            counter.fetch_add(1, Ordering::Relaxed);
+        }
+    }
+
+    /// The counts recorded so far, for the functions that were called.
+    pub fn to_profile(&self) -> Profile {
+        let mut profile = Profile::new();
+        for (index, function) in self.functions.iter().enumerate() {
+            if let Some(counters) = function.get() {
+                profile.insert(
+                    LocalFunctionIndex::new(index),
+                    counters
+                        .iter()
+                        .map(|counter| counter.load(Ordering::Relaxed))
+                        .collect(),
+                );
+            }
+        }
+        profile
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn count() {
+        let profile = VMProfile::new(3);
+        profile.count(LocalFunctionIndex::new(1), 0, 3);
+        profile.count(LocalFunctionIndex::new(1), 0, 3);
+        profile.count(LocalFunctionIndex::new(1), 2, 3);
+        // Out of range counters are ignored.
+        profile.count(LocalFunctionIndex::new(1), 3, 3);
+        profile.count(LocalFunctionIndex::new(3), 0, 1);
+
+        let profile = profile.to_profile();
+        assert!(profile.function(LocalFunctionIndex::new(0)).is_none());
+        let function = profile.function(LocalFunctionIndex::new(1)).unwrap();
+        assert_eq!(function.entry_count(), 2);
+        assert_eq!(function.branch(0), Some((0, 1)));
+    }
+}
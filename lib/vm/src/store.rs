@@ -1,8 +1,10 @@
 use crate::{
-    VMExternObj, VMFunction, VMFunctionEnvironment, VMGlobal, VMInstance, VMMemory, VMTable,
+    VMException, VMExceptionRef, VMExternObj, VMFunction, VMFunctionEnvironment, VMGlobal,
+    VMInstance, VMMemory, VMTable, VMTag,
 };
 use core::slice::Iter;
-use std::{cell::UnsafeCell, fmt, marker::PhantomData, num::NonZeroUsize, ptr::NonNull};
+use std::cell::{Cell, UnsafeCell};
+use std::{fmt, marker::PhantomData, num::NonZeroUsize, ptr::NonNull};
 use wasmer_types::StoreId;
 
 /// Trait to represent an object managed by a context. This is implemented on
@@ -33,6 +35,8 @@ impl_context_object! {
     memories => VMMemory,
     extern_objs => VMExternObj,
     function_environments => VMFunctionEnvironment,
+    tags => VMTag,
+    exceptions => VMException,
 }
 
 /// Set of objects managed by a context.
@@ -46,6 +50,16 @@ pub struct StoreObjects {
     instances: Vec<VMInstance>,
     extern_objs: Vec<VMExternObj>,
     function_environments: Vec<VMFunctionEnvironment>,
+    tags: Vec<VMTag>,
+    exceptions: Vec<VMException>,
+    /// The exception being thrown, as the index of its handle or zero. It
+    /// is boxed so that compiled code can keep a pointer to it.
+    pending_exception: Box<Cell<usize>>,
+    /// Whether the instances of the store propagate pending exceptions.
+    exceptions_enabled: bool,
+    /// Whether the instances of the store run interpreted code, unknown
+    /// until the first one is created.
+    interpreted: Option<bool>,
 }
 
 impl StoreObjects {
@@ -78,6 +92,59 @@ impl StoreObjects {
         }
     }
 
+    /// Returns the exception being thrown, if any.
+    pub fn pending_exception(&self) -> Option<VMExceptionRef> {
+        InternalStoreHandle::from_index(self.pending_exception.get()).map(VMExceptionRef)
+    }
+
+    /// Sets or clears the exception being thrown.
+    pub fn set_pending_exception(&self, exception: Option<VMExceptionRef>) {
+        self.pending_exception
+            .set(exception.map_or(0, |exception| exception.0.index()));
+    }
+
+    /// Takes the exception being thrown, if any, leaving none pending.
+    pub fn take_pending_exception(&self) -> Option<VMExceptionRef> {
+        let exception = self.pending_exception();
+        self.set_pending_exception(None);
+        exception
+    }
+
+    /// Returns a pointer to the exception being thrown, as read by
+    /// compiled code.
+    pub(crate) fn pending_exception_ptr(&self) -> *mut usize {
+        self.pending_exception.as_ptr()
+    }
+
+    /// Whether the instances of the store propagate pending exceptions,
+    /// which is the case once an instance compiled with exception handling
+    /// enabled is added to the store.
+    ///
+    /// Otherwise, exceptions thrown by the host unwind the stack like traps.
+    pub fn exceptions_enabled(&self) -> bool {
+        self.exceptions_enabled
+    }
+
+    /// Records that the instances of the store propagate pending exceptions.
+    pub fn enable_exceptions(&mut self) {
+        self.exceptions_enabled = true;
+    }
+
+    /// Whether the instances of the store run interpreted code, or `None`
+    /// if no instance was created yet.
+    ///
+    /// Interpreted and native instances can't share a store: native code
+    /// calls functions through their body pointer, which is bytecode for
+    /// interpreted functions.
+    pub fn interpreted(&self) -> Option<bool> {
+        self.interpreted
+    }
+
+    /// Records whether the instances of the store run interpreted code.
+    pub fn set_interpreted(&mut self, interpreted: bool) {
+        self.interpreted = Some(interpreted);
+    }
+
     /// Return an immutable iterator over all globals
     pub fn iter_globals(&self) -> Iter<VMGlobal> {
         self.globals.iter()
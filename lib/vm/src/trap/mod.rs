@@ -9,6 +9,8 @@ mod trap;
 mod traphandlers;
 
 pub use trap::Trap;
+#[cfg(feature = "interpreter")]
+pub(crate) use traphandlers::default_stack_size;
 pub use traphandlers::{
     catch_traps, on_host_stack, raise_lib_trap, raise_user_trap, set_stack_size,
     wasmer_call_trampoline, TrapHandlerFn, VMConfig,
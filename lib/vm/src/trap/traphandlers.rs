@@ -78,6 +78,13 @@ pub fn set_stack_size(size: usize) {
     DEFAULT_STACK_SIZE.store(size.max(8 * 1024).min(100 * 1024 * 1024), Ordering::Relaxed);
 }
 
+/// Returns the stack size used for Wasm code when the `VMConfig` doesn't
+/// specify one.
+#[cfg(feature = "interpreter")]
+pub(crate) fn default_stack_size() -> usize {
+    DEFAULT_STACK_SIZE.load(Ordering::Relaxed)
+}
+
 cfg_if::cfg_if! {
     if #[cfg(unix)] {
         /// Function which may handle custom signals while processing traps.
@@ -110,6 +117,15 @@ unsafe fn process_illegal_op(addr: usize) -> Option<TrapCode> {
             None
         }
     }
+    if cfg!(target_arch = "riscv64") {
+        // An all-zero halfword is illegal, the payload is in the following halfword.
+        let insn = read_unaligned(addr as *mut u32);
+        val = if insn & 0xffff == 0 {
+            Some((insn >> 16) as u8)
+        } else {
+            None
+        }
+    }
     match val.and_then(|val| {
         if val & MAGIC == MAGIC {
             Some(val & 0xf)
@@ -130,6 +146,7 @@ unsafe fn process_illegal_op(addr: usize) -> Option<TrapCode> {
             8 => Some(TrapCode::BadConversionToInteger),
             9 => Some(TrapCode::UnreachableCodeReached),
             10 => Some(TrapCode::UnalignedAtomic),
+            11 => Some(TrapCode::NullReference),
             _ => None,
         },
     }
@@ -9,12 +9,12 @@ use crate::instance::Instance;
 use crate::memory::VMMemory;
 use crate::store::InternalStoreHandle;
 use crate::trap::{Trap, TrapCode};
-use crate::VMFunctionBody;
 use crate::VMTable;
+use crate::{FunctionBodyPtr, VMFunctionBody};
 use crate::{VMBuiltinFunctionIndex, VMFunction};
 use std::convert::TryFrom;
 use std::ptr::{self, NonNull};
-use std::sync::atomic::{AtomicPtr, Ordering};
+use std::sync::atomic::{AtomicPtr, AtomicU64, Ordering};
 use std::u32;
 use wasmer_types::RawValue;
 
@@ -317,17 +317,17 @@ mod test_vmglobal_import {
 /// caller's responsibility to synchronize.
 pub(crate) unsafe fn memory_copy(
     mem: &VMMemoryDefinition,
-    dst: u32,
-    src: u32,
-    len: u32,
+    dst: u64,
+    src: u64,
+    len: u64,
 ) -> Result<(), Trap> {
     // https://webassembly.github.io/reference-types/core/exec/instructions.html#exec-memory-copy
     if src
         .checked_add(len)
-        .map_or(true, |n| usize::try_from(n).unwrap() > mem.current_length)
+        .map_or(true, |n| n > mem.current_length as u64)
         || dst
             .checked_add(len)
-            .map_or(true, |m| usize::try_from(m).unwrap() > mem.current_length)
+            .map_or(true, |m| m > mem.current_length as u64)
     {
         return Err(Trap::lib(TrapCode::HeapAccessOutOfBounds));
     }
@@ -356,13 +356,13 @@ pub(crate) unsafe fn memory_copy(
 /// caller's responsibility to synchronize.
 pub(crate) unsafe fn memory_fill(
     mem: &VMMemoryDefinition,
-    dst: u32,
+    dst: u64,
     val: u32,
-    len: u32,
+    len: u64,
 ) -> Result<(), Trap> {
     if dst
         .checked_add(len)
-        .map_or(true, |m| usize::try_from(m).unwrap() > mem.current_length)
+        .map_or(true, |m| m > mem.current_length as u64)
     {
         return Err(Trap::lib(TrapCode::HeapAccessOutOfBounds));
     }
@@ -628,6 +628,69 @@ mod test_vmcaller_checked_anyfunc {
     }
 }
 
+/// The tier-up state of a local function, for code compiled to count its
+/// calls and to jump to a later compilation of itself once there is one.
+#[derive(Debug, Default)]
+#[repr(C)]
+pub struct VMFunctionTier {
+    /// The body the function jumps to on entry, or null to run the
+    /// function as compiled.
+    pub redirect: AtomicPtr<VMFunctionBody>,
+    /// The number of calls of the function so far. The counting isn't
+    /// atomic, so calls from several threads may get lost.
+    pub calls: AtomicU64,
+}
+
+impl VMFunctionTier {
+    /// The body the function jumps to on entry, if any.
+    pub fn redirect(&self) -> Option<FunctionBodyPtr> {
+        let redirect = self.redirect.load(Ordering::Acquire);
+        if redirect.is_null() {
+            None
+        } else {
+            Some(FunctionBodyPtr(redirect))
+        }
+    }
+
+    /// Makes the function jump to `body` on entry.
+    pub fn set_redirect(&self, body: FunctionBodyPtr) {
+        self.redirect
+            .store(*body as *mut VMFunctionBody, Ordering::Release);
+    }
+
+    /// The number of calls of the function so far.
+    pub fn calls(&self) -> u64 {
+        self.calls.load(Ordering::Relaxed)
+    }
+}
+
+#[cfg(test)]
+mod test_vmfunction_tier {
+    use super::VMFunctionTier;
+    use crate::VMOffsets;
+    use memoffset::offset_of;
+    use std::mem::size_of;
+    use wasmer_types::ModuleInfo;
+
+    #[test]
+    fn check_vmfunction_tier_offsets() {
+        let module = ModuleInfo::new();
+        let offsets = VMOffsets::new(size_of::<*mut u8>() as u8, &module);
+        assert_eq!(
+            size_of::<VMFunctionTier>(),
+            usize::from(offsets.size_of_vmfunction_tier())
+        );
+        assert_eq!(
+            offset_of!(VMFunctionTier, redirect),
+            usize::from(offsets.vmfunction_tier_redirect())
+        );
+        assert_eq!(
+            offset_of!(VMFunctionTier, calls),
+            usize::from(offsets.vmfunction_tier_calls())
+        );
+    }
+}
+
 /// An array that stores addresses of builtin functions. We translate code
 /// to use indirect calls. This way, we don't have to patch the code.
 #[repr(C)]
@@ -710,6 +773,38 @@ impl VMBuiltinFunctionsArray {
             wasmer_vm_memory32_atomic_notify as usize;
         ptrs[VMBuiltinFunctionIndex::get_imported_memory_atomic_notify_index().index() as usize] =
             wasmer_vm_imported_memory32_atomic_notify as usize;
+        ptrs[VMBuiltinFunctionIndex::get_memory_watch_load_index().index() as usize] =
+            wasmer_vm_memory_watch_load as usize;
+        ptrs[VMBuiltinFunctionIndex::get_memory_watch_store_index().index() as usize] =
+            wasmer_vm_memory_watch_store as usize;
+        ptrs[VMBuiltinFunctionIndex::get_throw_index().index() as usize] = wasmer_vm_throw as usize;
+        ptrs[VMBuiltinFunctionIndex::get_throw_ref_index().index() as usize] =
+            wasmer_vm_throw_ref as usize;
+        ptrs[VMBuiltinFunctionIndex::get_catch_index().index() as usize] = wasmer_vm_catch as usize;
+        ptrs[VMBuiltinFunctionIndex::get_memory64_grow_index().index() as usize] =
+            wasmer_vm_memory64_grow as usize;
+        ptrs[VMBuiltinFunctionIndex::get_imported_memory64_grow_index().index() as usize] =
+            wasmer_vm_imported_memory64_grow as usize;
+        ptrs[VMBuiltinFunctionIndex::get_memory64_size_index().index() as usize] =
+            wasmer_vm_memory64_size as usize;
+        ptrs[VMBuiltinFunctionIndex::get_imported_memory64_size_index().index() as usize] =
+            wasmer_vm_imported_memory64_size as usize;
+        ptrs[VMBuiltinFunctionIndex::get_memory64_copy_index().index() as usize] =
+            wasmer_vm_memory64_copy as usize;
+        ptrs[VMBuiltinFunctionIndex::get_imported_memory64_copy_index().index() as usize] =
+            wasmer_vm_imported_memory64_copy as usize;
+        ptrs[VMBuiltinFunctionIndex::get_memory64_fill_index().index() as usize] =
+            wasmer_vm_memory64_fill as usize;
+        ptrs[VMBuiltinFunctionIndex::get_imported_memory64_fill_index().index() as usize] =
+            wasmer_vm_imported_memory64_fill as usize;
+        ptrs[VMBuiltinFunctionIndex::get_memory64_init_index().index() as usize] =
+            wasmer_vm_memory64_init as usize;
+        ptrs[VMBuiltinFunctionIndex::get_lazy_compile_index().index() as usize] =
+            wasmer_vm_lazy_compile as usize;
+        ptrs[VMBuiltinFunctionIndex::get_profile_count_index().index() as usize] =
+            wasmer_vm_profile_count as usize;
+        ptrs[VMBuiltinFunctionIndex::get_debug_hook_index().index() as usize] =
+            wasmer_vm_debug_hook as usize;
 
         debug_assert!(ptrs.iter().cloned().all(|p| p != 0));
 
@@ -595,6 +595,21 @@ impl WasiProcess {
         self.wait_for_checkpoint_finish()
     }
 
+    /// Takes a snapshot of the process the next time its threads are
+    /// able to, returning a future that can be waited on for the
+    /// snapshot to complete
+    ///
+    /// Note: If you ignore the returned future the checkpoint will still
+    /// occur but it will execute asynchronously
+    #[cfg(feature = "journal")]
+    pub fn snapshot(
+        &self,
+        trigger: SnapshotTrigger,
+    ) -> std::pin::Pin<Box<dyn futures::Future<Output = ()> + Send + Sync>> {
+        do_checkpoint_from_outside(&self.inner, WasiProcessCheckpoint::Snapshot { trigger });
+        self.wait_for_checkpoint_finish()
+    }
+
     /// Disables the journaling functionality
     pub fn disable_journaling_after_checkpoint(&self) {
         let mut guard = self.inner.0.lock().unwrap();
@@ -1,3 +1,4 @@
+use std::num::NonZeroUsize;
 use std::sync::Arc;
 use wasmer::sys::Features;
 use wasmer::{CompilerConfig, ModuleMiddleware, Store};
@@ -15,6 +16,8 @@ pub struct Config {
     pub features: Option<Features>,
     pub middlewares: Vec<Arc<dyn ModuleMiddleware>>,
     pub canonicalize_nans: bool,
+    pub relaxed_simd_deterministic: bool,
+    pub num_threads: Option<NonZeroUsize>,
 }
 
 impl Config {
@@ -23,6 +26,8 @@ impl Config {
             compiler,
             features: None,
             canonicalize_nans: false,
+            relaxed_simd_deterministic: false,
+            num_threads: None,
             middlewares: vec![],
         }
     }
@@ -39,6 +44,14 @@ impl Config {
         self.canonicalize_nans = canonicalize_nans;
     }
 
+    pub fn set_relaxed_simd_deterministic(&mut self, relaxed_simd_deterministic: bool) {
+        self.relaxed_simd_deterministic = relaxed_simd_deterministic;
+    }
+
+    pub fn set_num_threads(&mut self, num_threads: Option<NonZeroUsize>) {
+        self.num_threads = num_threads;
+    }
+
     pub fn store(&self) -> Store {
         let compiler_config = self.compiler_config(self.canonicalize_nans);
         let engine = self.engine(compiler_config);
@@ -71,6 +84,8 @@ impl Config {
             Compiler::Cranelift => {
                 let mut compiler = wasmer_compiler_cranelift::Cranelift::new();
                 compiler.canonicalize_nans(canonicalize_nans);
+                compiler.relaxed_simd_deterministic(self.relaxed_simd_deterministic);
+                compiler.num_threads(self.num_threads);
                 compiler.enable_verifier();
                 self.add_middlewares(&mut compiler);
                 Box::new(compiler)
@@ -79,6 +94,8 @@ impl Config {
             Compiler::LLVM => {
                 let mut compiler = wasmer_compiler_llvm::LLVM::new();
                 compiler.canonicalize_nans(canonicalize_nans);
+                compiler.relaxed_simd_deterministic(self.relaxed_simd_deterministic);
+                compiler.num_threads(self.num_threads);
                 compiler.enable_verifier();
                 self.add_middlewares(&mut compiler);
                 Box::new(compiler)
@@ -87,6 +104,8 @@ impl Config {
             Compiler::Singlepass => {
                 let mut compiler = wasmer_compiler_singlepass::Singlepass::new();
                 compiler.canonicalize_nans(canonicalize_nans);
+                compiler.relaxed_simd_deterministic(self.relaxed_simd_deterministic);
+                compiler.num_threads(self.num_threads);
                 compiler.enable_verifier();
                 self.add_middlewares(&mut compiler);
                 Box::new(compiler)
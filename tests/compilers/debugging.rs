@@ -0,0 +1,70 @@
+use anyhow::Result;
+
+use std::sync::{Arc, Mutex};
+use wasmer::sys::{attach_debugger, detach_debugger, DebugFrame, Debugger};
+use wasmer::*;
+
+struct Recorder {
+    module: Module,
+    offsets: Mutex<Vec<usize>>,
+}
+
+impl Debugger for Recorder {
+    fn on_instruction(&self, frame: &mut DebugFrame<'_>) {
+        // Other tests may run code compiled for debugging at the same time.
+        if frame.module().id != self.module.info().id {
+            return;
+        }
+        let mut offsets = self.offsets.lock().unwrap();
+        if offsets.is_empty() {
+            frame.locals_mut()[0] = 5;
+        }
+        offsets.push(frame.module_offset());
+    }
+}
+
+const WAT: &str = r#"(module (func (export "double") (param i32) (result i32)
+    (local i64 v128)
+    (i32.add (local.get 0) (local.get 0))))"#;
+
+#[compiler_test(debugging)]
+fn debugger_sees_and_changes_locals(config: crate::Config) -> Result<()> {
+    let mut compiler_config = config.compiler_config(false);
+    compiler_config.enable_debugging();
+    let mut store = Store::new(config.engine(compiler_config));
+    let module = Module::new(&store, WAT)?;
+    let instance = Instance::new(&mut store, &module, &imports! {})?;
+    let double: TypedFunction<i32, i32> = instance.exports.get_typed_function(&store, "double")?;
+
+    let recorder = Arc::new(Recorder {
+        module: module.clone(),
+        offsets: Mutex::new(Vec::new()),
+    });
+    attach_debugger(recorder.clone());
+    let result = double.call(&mut store, 1);
+    detach_debugger();
+
+    assert_eq!(result?, 10);
+    let offsets = recorder.offsets.lock().unwrap();
+    // Both `local.get`, `i32.add` and the final `end`.
+    assert_eq!(offsets.len(), 4);
+    assert!(offsets.windows(2).all(|pair| pair[0] < pair[1]));
+    drop(offsets);
+
+    // Functions compiled without debugging never call the debugger.
+    let mut store = config.store();
+    let module = Module::new(&store, WAT)?;
+    let instance = Instance::new(&mut store, &module, &imports! {})?;
+    let double: TypedFunction<i32, i32> = instance.exports.get_typed_function(&store, "double")?;
+    let recorder = Arc::new(Recorder {
+        module: module.clone(),
+        offsets: Mutex::new(Vec::new()),
+    });
+    attach_debugger(recorder.clone());
+    let result = double.call(&mut store, 1);
+    detach_debugger();
+
+    assert_eq!(result?, 2);
+    assert!(recorder.offsets.lock().unwrap().is_empty());
+    Ok(())
+}
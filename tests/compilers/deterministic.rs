@@ -1,4 +1,6 @@
 use anyhow::Result;
+use std::fmt::Write;
+use std::num::NonZeroUsize;
 use wasmer::{wat2wasm, Module, Store};
 
 fn compile_and_compare(wasm: &[u8]) -> Result<()> {
@@ -42,3 +44,90 @@ fn deterministic_table() -> Result<()> {
 
     compile_and_compare(&wasm_bytes)
 }
+
+#[compiler_test(deterministic)]
+fn deterministic_across_num_threads(mut config: crate::Config) -> Result<()> {
+    // Enough functions for each thread to get several of them.
+    let mut functions = String::new();
+    for i in 0..64 {
+        write!(
+            functions,
+            "(func (export \"f{i}\") (param i32) (result i32)
+                (i32.add (call $g (local.get 0)) (i32.const {i})))"
+        )?;
+    }
+    let wat = format!(
+        "(module
+            (func $g (param i32) (result i32) (i32.mul (local.get 0) (i32.const 3)))
+            {functions})"
+    );
+    let wasm_bytes = wat2wasm(wat.as_bytes())?;
+
+    let mut serialized = Vec::new();
+    for num_threads in [1, 2, 8] {
+        config.set_num_threads(NonZeroUsize::new(num_threads));
+        let store = config.store();
+        let module = Module::new(&store, &wasm_bytes)?;
+        serialized.push(module.serialize()?);
+    }
+    assert!(serialized.windows(2).all(|pair| pair[0] == pair[1]));
+
+    Ok(())
+}
+
+#[compiler_test(deterministic)]
+fn deterministic_across_threads_and_engine_state(config: crate::Config) -> Result<()> {
+    let wasm_bytes = wat2wasm(
+        br#"
+(module
+  (type $t (func (param i32) (result i32)))
+  (import "env" "f" (func $f (type $t)))
+  (table 2 funcref)
+  (func $g (param i64) (result i64) (local.get 0))
+  (func $h (type $t) (call $f (call_indirect (type $t) (local.get 0) (i32.const 0))))
+  (elem (i32.const 0) $h $f)
+  (export "g" (func $g))
+  (export "h" (func $h)))
+"#,
+    )?;
+    let other_bytes = wat2wasm(
+        br#"
+(module
+  (func (param f64 f32) (result f32) (local.get 1))
+  (func (param i64) (result f64) (f64.const 1)))
+"#,
+    )?;
+
+    // Each thread compiles other modules first, so that the engines
+    // register their signatures and hand out ids in a different order.
+    let serialized = std::thread::scope(|scope| {
+        let handles = (0..4)
+            .map(|i| {
+                let config = &config;
+                let wasm_bytes = &wasm_bytes;
+                let other_bytes = &other_bytes;
+                scope.spawn(move || -> Result<_> {
+                    let store = config.store();
+                    let mut others = Vec::new();
+                    for _ in 0..i {
+                        others.push(Module::new(&store, other_bytes)?);
+                    }
+                    let module = Module::new(&store, wasm_bytes)?;
+                    Ok(module.serialize()?)
+                })
+            })
+            .collect::<Vec<_>>();
+        handles
+            .into_iter()
+            .map(|handle| handle.join().unwrap())
+            .collect::<Result<Vec<_>>>()
+    })?;
+    assert!(serialized.windows(2).all(|pair| pair[0] == pair[1]));
+
+    // Serializing is canonical too.
+    let store = config.store();
+    let module = unsafe { Module::deserialize(&store, serialized[0].clone())? };
+    assert!(module.serialize()? == serialized[0]);
+
+    Ok(())
+}
@@ -0,0 +1,193 @@
+//! Tests of the exception-handling proposal: exceptions thrown and caught
+//! in WebAssembly, by the host, and across both.
+//!
+//! The `wat` crate doesn't know about `try_table` and `throw_ref` yet, so
+//! the modules are written in the binary format, with their text format
+//! alongside.
+//!
+//! Only Cranelift implements exceptions so far, so the other compilers are
+//! checked to reject the modules instead.
+
+use anyhow::Result;
+use wasmer::sys::Features;
+use wasmer::*;
+
+fn features() -> Features {
+    let mut features = Features::default();
+    features.exceptions(true);
+    features
+}
+
+/// Compiles a module using exceptions, or checks that the compiler rejects
+/// it if it doesn't implement them.
+fn compile(config: &crate::Config, store: &Store, wasm: &[u8]) -> Result<Option<Module>> {
+    match Module::new(store, wasm) {
+        Err(CompileError::UnsupportedFeature(_))
+            if config.compiler != crate::Compiler::Cranelift =>
+        {
+            Ok(None)
+        }
+        module => Ok(Some(module?)),
+    }
+}
+
+/// ```wat
+/// (module
+///   (tag $e (export "tag") (param i32))
+///   (func $thrower (param i32)
+///     (throw $e (local.get 0)))
+///   (func (export "run") (param i32) (result i32)
+///     (block $h (result i32)
+///       (try_table (catch $e $h)
+///         (call $thrower (local.get 0)))
+///       (i32.const -1)))
+///   (func (export "run_direct") (param i32) (result i32)
+///     (block $h (result i32)
+///       (try_table (catch $e $h)
+///         (throw $e (local.get 0)))
+///       (i32.const -1))))
+/// ```
+const CATCH_MODULE: &[u8] = &[
+    0x00, 0x61, 0x73, 0x6d, 0x01, 0x00, 0x00, 0x00, // header
+    0x01, 0x0a, 0x02, // type section
+    0x60, 0x01, 0x7f, 0x00, // (param i32)
+    0x60, 0x01, 0x7f, 0x01, 0x7f, // (param i32) (result i32)
+    0x03, 0x04, 0x03, 0x00, 0x01, 0x01, // function section
+    0x0d, 0x03, 0x01, 0x00, 0x00, // tag section
+    0x07, 0x1a, 0x03, // export section
+    0x03, b'r', b'u', b'n', 0x00, 0x01, // "run"
+    0x0a, b'r', b'u', b'n', b'_', b'd', b'i', b'r', b'e', b'c', b't', 0x00,
+    0x02, // "run_direct"
+    0x03, b't', b'a', b'g', 0x04, 0x00, // "tag"
+    0x0a, 0x2e, 0x03, // code section
+    0x06, 0x00, // $thrower
+    0x20, 0x00, 0x08, 0x00, 0x0b, //
+    0x12, 0x00, // run
+    0x02, 0x7f, 0x1f, 0x40, 0x01, 0x00, 0x00, 0x00, //
+    0x20, 0x00, 0x10, 0x00, 0x0b, //
+    0x41, 0x7f, 0x0b, 0x0b, //
+    0x12, 0x00, // run_direct
+    0x02, 0x7f, 0x1f, 0x40, 0x01, 0x00, 0x00, 0x00, //
+    0x20, 0x00, 0x08, 0x00, 0x0b, //
+    0x41, 0x7f, 0x0b, 0x0b, //
+];
+
+/// ```wat
+/// (module
+///   (import "host" "tag" (tag $e (param i32)))
+///   (import "host" "throw" (func $host_throw (param i32)))
+///   (func (export "throw") (param i32)
+///     (throw $e (local.get 0)))
+///   (func (export "call_host") (param i32) (result i32)
+///     (block $h (result i32)
+///       (try_table (catch $e $h)
+///         (call $host_throw (local.get 0)))
+///       (i32.const -1)))
+///   (func (export "rethrow") (param i32)
+///     (block $h (result exnref)
+///       (try_table (catch_all_ref $h)
+///         (call $host_throw (local.get 0)))
+///       (return))
+///     (throw_ref)))
+/// ```
+const HOST_MODULE: &[u8] = &[
+    0x00, 0x61, 0x73, 0x6d, 0x01, 0x00, 0x00, 0x00, // header
+    0x01, 0x0a, 0x02, // type section
+    0x60, 0x01, 0x7f, 0x00, // (param i32)
+    0x60, 0x01, 0x7f, 0x01, 0x7f, // (param i32) (result i32)
+    0x02, 0x1a, 0x02, // import section
+    0x04, b'h', b'o', b's', b't', 0x03, b't', b'a', b'g', 0x04, 0x00, 0x00, // "host" "tag"
+    0x04, b'h', b'o', b's', b't', 0x05, b't', b'h', b'r', b'o', b'w', 0x00,
+    0x00, // "host" "throw"
+    0x03, 0x04, 0x03, 0x00, 0x01, 0x00, // function section
+    0x07, 0x1f, 0x03, // export section
+    0x05, b't', b'h', b'r', b'o', b'w', 0x00, 0x01, // "throw"
+    0x09, b'c', b'a', b'l', b'l', b'_', b'h', b'o', b's', b't', 0x00, 0x02, // "call_host"
+    0x07, b'r', b'e', b't', b'h', b'r', b'o', b'w', 0x00, 0x03, // "rethrow"
+    0x0a, 0x2d, 0x03, // code section
+    0x06, 0x00, // throw
+    0x20, 0x00, 0x08, 0x00, 0x0b, //
+    0x12, 0x00, // call_host
+    0x02, 0x7f, 0x1f, 0x40, 0x01, 0x00, 0x00, 0x00, //
+    0x20, 0x00, 0x10, 0x00, 0x0b, //
+    0x41, 0x7f, 0x0b, 0x0b, //
+    0x11, 0x00, // rethrow
+    0x02, 0x69, 0x1f, 0x40, 0x01, 0x03, 0x00, //
+    0x20, 0x00, 0x10, 0x00, 0x0b, //
+    0x0f, 0x0b, 0x0a, 0x0b, //
+];
+
+#[compiler_test(exceptions)]
+fn catch_in_try_table(mut config: crate::Config) -> Result<()> {
+    config.set_features(features());
+    let mut store = config.store();
+    let module = match compile(&config, &store, CATCH_MODULE)? {
+        Some(module) => module,
+        None => return Ok(()),
+    };
+    let instance = Instance::new(&mut store, &module, &imports! {})?;
+
+    let tag = instance.exports.get_tag("tag")?;
+    assert_eq!(tag.ty(&store).params(), &[Type::I32]);
+
+    let run: TypedFunction<i32, i32> = instance.exports.get_typed_function(&store, "run")?;
+    assert_eq!(run.call(&mut store, 5)?, 5);
+    let run_direct: TypedFunction<i32, i32> =
+        instance.exports.get_typed_function(&store, "run_direct")?;
+    assert_eq!(run_direct.call(&mut store, 7)?, 7);
+    Ok(())
+}
+
+#[compiler_test(exceptions)]
+fn exceptions_between_host_and_guest(mut config: crate::Config) -> Result<()> {
+    config.set_features(features());
+    let mut store = config.store();
+    let module = match compile(&config, &store, HOST_MODULE)? {
+        Some(module) => module,
+        None => return Ok(()),
+    };
+
+    let tag = Tag::new(&mut store, TagType::new([Type::I32]));
+    let env = FunctionEnv::new(&mut store, tag.clone());
+    let host_throw = Function::new_typed_with_env(
+        &mut store,
+        &env,
+        |mut env: FunctionEnvMut<Tag>, value: i32| -> Result<(), RuntimeError> {
+            let tag = env.data().clone();
+            Err(Exception::new(&mut env, &tag, &[Value::I32(value)])?.into())
+        },
+    );
+    let imports = imports! {
+        "host" => {
+            "tag" => tag.clone(),
+            "throw" => host_throw,
+        },
+    };
+    let instance = Instance::new(&mut store, &module, &imports)?;
+
+    // An exception thrown by the guest reaches the host.
+    let throw = instance.exports.get_function("throw")?;
+    let error = throw.call(&mut store, &[Value::I32(3)]).unwrap_err();
+    let exception = error.downcast::<Exception>().unwrap();
+    assert_eq!(exception.tag(&mut store), tag);
+    assert_eq!(exception.payload(&mut store), vec![Value::I32(3)]);
+
+    let throw: TypedFunction<i32, ()> = throw.typed(&store)?;
+    let error = throw.call(&mut store, 4).unwrap_err();
+    assert!(error.is::<Exception>());
+
+    // An exception thrown by the host is caught by the guest.
+    let call_host: TypedFunction<i32, i32> =
+        instance.exports.get_typed_function(&store, "call_host")?;
+    assert_eq!(call_host.call(&mut store, 5)?, 5);
+
+    // And thrown again with `throw_ref` to the host.
+    let rethrow: TypedFunction<i32, ()> = instance.exports.get_typed_function(&store, "rethrow")?;
+    let error = rethrow.call(&mut store, 6).unwrap_err();
+    let exception = error.downcast::<Exception>().unwrap();
+    assert_eq!(exception.payload(&mut store), vec![Value::I32(6)]);
+
+    // Nothing is left pending once the calls return.
+    assert_eq!(call_host.call(&mut store, 7)?, 7);
+    Ok(())
+}
@@ -83,6 +83,43 @@ fn dynamic_function(config: crate::Config) -> Result<()> {
     Ok(())
 }
 
+#[compiler_test(imports)]
+fn dynamic_function_with_v128(config: crate::Config) -> Result<()> {
+    let mut store = config.store();
+    let wat = r#"
+        (import "host" "swap" (func $swap (param v128 i32 v128) (result v128)))
+        (func (export "run") (param v128 v128) (result v128)
+            local.get 0
+            i32.const 7
+            local.get 1
+            call $swap
+            v128.const i32x4 1 1 1 1
+            i32x4.add
+        )
+    "#;
+    let module = Module::new(&store, wat)?;
+    let imports = imports! {
+        "host" => {
+            "swap" => Function::new(&mut store, FunctionType::new(vec![ValueType::V128, ValueType::I32, ValueType::V128], vec![ValueType::V128]), |values| {
+                    assert_eq!(values[1], Value::I32(7));
+                    Ok(vec![Value::V128(values[2].unwrap_v128().rotate_left(64) ^ values[0].unwrap_v128())])
+                }),
+        }
+    };
+    let instance = Instance::new(&mut store, &module, &imports)?;
+    let run = instance.exports.get_function("run")?;
+    let a: u128 = 0x0000_0004_0000_0003_0000_0002_0000_0001;
+    let b: u128 = 0x0000_0040_0000_0030_0000_0020_0000_0010;
+    let result = run.call(&mut store, &[Value::V128(a), Value::V128(b)])?;
+    assert_eq!(
+        result.to_vec(),
+        vec![Value::V128(
+            (b.rotate_left(64) ^ a) + 0x0000_0001_0000_0001_0000_0001_0000_0001
+        )]
+    );
+    Ok(())
+}
+
 #[compiler_test(imports)]
 fn dynamic_function_with_env(config: crate::Config) -> Result<()> {
     let mut store = config.store();
@@ -507,3 +544,46 @@ fn instance_local_memory_lifetime(config: crate::Config) -> Result<()> {
 
     Ok(())
 }
+
+#[compiler_test(imports)]
+fn tail_calls_to_imported_functions(mut config: crate::Config) -> Result<()> {
+    let mut features = wasmer::sys::Features::default();
+    features.tail_call(true);
+    config.set_features(features);
+    let mut store = config.store();
+
+    let wat = r#"(module
+    (func $count (export "count") (param i64) (result i64)
+      (if (result i64) (i64.eqz (local.get 0))
+        (then (i64.const 7))
+        (else (return_call $count (i64.sub (local.get 0) (i64.const 1)))))))"#;
+    let module = Module::new(&store, wat)?;
+    let counter = Instance::new(&mut store, &module, &imports! {})?;
+
+    let wat = r#"(module
+    (import "host" "double" (func $double (param i32) (result i32)))
+    (import "counter" "count" (func $count (param i64) (result i64)))
+    (func $down (export "down") (param i32) (result i32)
+      (if (result i32) (i32.eqz (local.get 0))
+        (then (return_call $double (i32.const 21)))
+        (else (return_call $down (i32.sub (local.get 0) (i32.const 1))))))
+    (func (export "count") (param i64) (result i64)
+      (return_call $count (local.get 0))))"#;
+    let module = Module::new(&store, wat)?;
+    let imports = imports! {
+        "host" => {
+            "double" => Function::new_typed(&mut store, |x: i32| x * 2),
+        },
+        "counter" => {
+            "count" => counter.exports.get_function("count")?.clone(),
+        },
+    };
+    let instance = Instance::new(&mut store, &module, &imports)?;
+
+    let down: TypedFunction<i32, i32> = instance.exports.get_typed_function(&store, "down")?;
+    assert_eq!(down.call(&mut store, 0)?, 42);
+    assert_eq!(down.call(&mut store, 1_000_000)?, 42);
+    let count: TypedFunction<i64, i64> = instance.exports.get_typed_function(&store, "count")?;
+    assert_eq!(count.call(&mut store, 1_000_000)?, 7);
+    Ok(())
+}
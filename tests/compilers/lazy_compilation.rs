@@ -0,0 +1,143 @@
+#![cfg(feature = "singlepass")]
+
+use anyhow::Result;
+use wasmer::sys::{CompilerConfig, EngineBuilder, Singlepass};
+use wasmer::*;
+use wasmer_types::TrapCode;
+
+const WAT: &str = r#"(module
+    (func $fac (export "fac") (param i64) (result i64)
+        (if (result i64) (i64.eqz (local.get 0))
+            (then (i64.const 1))
+            (else (i64.mul (local.get 0) (call $fac (i64.sub (local.get 0) (i64.const 1)))))))
+    (func $div (export "div") (param i32 i32) (result i32)
+        (i32.div_s (local.get 0) (local.get 1)))
+    (func $add (export "add") (param i32 i32) (result i32)
+        (local i32)
+        (local.set 2 (i32.add (local.get 0) (local.get 1)))
+        (local.get 2))
+    (func (export "add_via_call") (param i32) (result i32)
+        (call $add (local.get 0) (i32.const 10)))
+)"#;
+
+fn engine() -> Engine {
+    let mut compiler = Singlepass::new();
+    compiler.enable_lazy_compilation();
+    EngineBuilder::new(compiler).engine().into()
+}
+
+#[test]
+fn lazy_functions_compute() -> Result<()> {
+    let mut store = Store::new(engine());
+    let module = Module::new(&store, WAT)?;
+    let instance = Instance::new(&mut store, &module, &imports! {})?;
+    let fac: TypedFunction<i64, i64> = instance.exports.get_typed_function(&store, "fac")?;
+    let add: TypedFunction<(i32, i32), i32> = instance.exports.get_typed_function(&store, "add")?;
+    let add_via_call: TypedFunction<i32, i32> = instance
+        .exports
+        .get_typed_function(&store, "add_via_call")?;
+
+    assert_eq!(fac.call(&mut store, 20)?, 2432902008176640000);
+    assert_eq!(fac.call(&mut store, 5)?, 120);
+    assert_eq!(add_via_call.call(&mut store, 5)?, 15);
+    assert_eq!(add.call(&mut store, 1, 2)?, 3);
+
+    // New instances share the compiled functions.
+    let instance = Instance::new(&mut store, &module, &imports! {})?;
+    let fac: TypedFunction<i64, i64> = instance.exports.get_typed_function(&store, "fac")?;
+    assert_eq!(fac.call(&mut store, 3)?, 6);
+
+    Ok(())
+}
+
+#[test]
+fn lazy_functions_trap() -> Result<()> {
+    let mut store = Store::new(engine());
+    let module = Module::new(&store, WAT)?;
+    let instance = Instance::new(&mut store, &module, &imports! {})?;
+    let div: TypedFunction<(i32, i32), i32> = instance.exports.get_typed_function(&store, "div")?;
+
+    let error = div.call(&mut store, 1, 0).unwrap_err();
+    assert_eq!(error.to_trap(), Some(TrapCode::IntegerDivisionByZero));
+    assert_eq!(div.call(&mut store, 7, 2)?, 3);
+
+    Ok(())
+}
+
+#[test]
+fn lazy_functions_are_serialized() -> Result<()> {
+    let mut store = Store::new(engine());
+    let module = Module::new(&store, WAT)?;
+    let instance = Instance::new(&mut store, &module, &imports! {})?;
+    let fac: TypedFunction<i64, i64> = instance.exports.get_typed_function(&store, "fac")?;
+    assert_eq!(fac.call(&mut store, 5)?, 120);
+    let serialized = module.serialize()?;
+
+    // Without a compiler, only the functions compiled before serializing
+    // can run.
+    let mut store = Store::new(EngineBuilder::headless());
+    let module = unsafe { Module::deserialize(&store, serialized.clone()) }?;
+    let instance = Instance::new(&mut store, &module, &imports! {})?;
+    let fac: TypedFunction<i64, i64> = instance.exports.get_typed_function(&store, "fac")?;
+    let div: TypedFunction<(i32, i32), i32> = instance.exports.get_typed_function(&store, "div")?;
+    assert_eq!(fac.call(&mut store, 20)?, 2432902008176640000);
+    let error = div.call(&mut store, 7, 2).unwrap_err();
+    assert!(matches!(
+        error.downcast::<CompileError>(),
+        Ok(CompileError::Codegen(_))
+    ));
+
+    let mut store = Store::new(engine());
+    let module = unsafe { Module::deserialize(&store, serialized) }?;
+    let instance = Instance::new(&mut store, &module, &imports! {})?;
+    let fac: TypedFunction<i64, i64> = instance.exports.get_typed_function(&store, "fac")?;
+    let div: TypedFunction<(i32, i32), i32> = instance.exports.get_typed_function(&store, "div")?;
+    assert_eq!(fac.call(&mut store, 20)?, 2432902008176640000);
+    assert_eq!(div.call(&mut store, 7, 2)?, 3);
+
+    Ok(())
+}
+
+#[test]
+fn lazy_functions_are_serialized_in_canonical_order() -> Result<()> {
+    let mut serialized = Vec::new();
+    for fac_first in [true, false] {
+        let mut store = Store::new(engine());
+        let module = Module::new(&store, WAT)?;
+        let instance = Instance::new(&mut store, &module, &imports! {})?;
+        let fac: TypedFunction<i64, i64> = instance.exports.get_typed_function(&store, "fac")?;
+        let add: TypedFunction<(i32, i32), i32> =
+            instance.exports.get_typed_function(&store, "add")?;
+        if fac_first {
+            assert_eq!(fac.call(&mut store, 5)?, 120);
+            assert_eq!(add.call(&mut store, 1, 2)?, 3);
+        } else {
+            assert_eq!(add.call(&mut store, 1, 2)?, 3);
+            assert_eq!(fac.call(&mut store, 5)?, 120);
+        }
+        serialized.push(module.serialize()?);
+    }
+    assert!(serialized[0] == serialized[1]);
+
+    Ok(())
+}
+
+#[test]
+fn reproducible_engines_leave_out_lazy_functions() -> Result<()> {
+    let mut compiler = Singlepass::new();
+    compiler.enable_lazy_compilation();
+    let engine: Engine = EngineBuilder::new(compiler)
+        .set_reproducible(true)
+        .engine()
+        .into();
+    let mut store = Store::new(engine);
+    let module = Module::new(&store, WAT)?;
+    let before = module.serialize()?;
+
+    let instance = Instance::new(&mut store, &module, &imports! {})?;
+    let fac: TypedFunction<i64, i64> = instance.exports.get_typed_function(&store, "fac")?;
+    assert_eq!(fac.call(&mut store, 5)?, 120);
+    assert!(module.serialize()? == before);
+
+    Ok(())
+}
@@ -6,14 +6,24 @@
 extern crate compiler_test_derive;
 
 mod config;
+mod debugging;
 mod deterministic;
+mod exceptions;
 mod imports;
 mod issues;
+mod lazy_compilation;
+mod memory64;
+mod memory_watch;
 mod metering;
 mod middlewares;
+mod precompile;
+mod profile;
+mod relaxed_simd;
 // mod multi_value_imports;
 mod artifact;
 mod serialize;
+mod signing;
+mod tier_up;
 mod traps;
 mod typed_functions;
 mod wasi;
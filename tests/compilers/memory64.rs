@@ -0,0 +1,108 @@
+//! Tests of the memory64 proposal: linear memories indexed with 64-bit
+//! addresses.
+
+use anyhow::Result;
+use wasmer::sys::Features;
+use wasmer::*;
+
+const WAT: &str = r#"
+(module
+  (memory (export "memory") i64 1 100000)
+  (data (i64.const 16) "wasm64")
+  (data $passive "passive")
+  (func (export "load") (param i64) (result i32)
+    (i32.load (local.get 0)))
+  (func (export "load8") (param i64) (result i32)
+    (i32.load8_u (local.get 0)))
+  (func (export "load_far") (param i64) (result i32)
+    (i32.load offset=0x100000000 (local.get 0)))
+  (func (export "store") (param i64 i32)
+    (i32.store (local.get 0) (local.get 1)))
+  (func (export "size") (result i64)
+    (memory.size))
+  (func (export "grow") (param i64) (result i64)
+    (memory.grow (local.get 0)))
+  (func (export "fill") (param i64 i32 i64)
+    (memory.fill (local.get 0) (local.get 1) (local.get 2)))
+  (func (export "copy") (param i64 i64 i64)
+    (memory.copy (local.get 0) (local.get 1) (local.get 2)))
+  (func (export "init") (param i64 i32 i32)
+    (memory.init $passive (local.get 0) (local.get 1) (local.get 2))))
+"#;
+
+fn features() -> Features {
+    let mut features = Features::default();
+    features.memory64(true);
+    features
+}
+
+#[compiler_test(memory64)]
+fn accesses(mut config: crate::Config) -> Result<()> {
+    config.set_features(features());
+    let mut store = config.store();
+    let module = Module::new(&store, WAT)?;
+    let instance = Instance::new(&mut store, &module, &imports! {})?;
+
+    let memory = instance.exports.get_memory("memory")?;
+    let ty = memory.ty(&store);
+    assert!(ty.memory64);
+    assert_eq!(ty.maximum, Some(Pages(100000)));
+
+    let load: TypedFunction<i64, i32> = instance.exports.get_typed_function(&store, "load")?;
+    let load8: TypedFunction<i64, i32> = instance.exports.get_typed_function(&store, "load8")?;
+    let load_far: TypedFunction<i64, i32> =
+        instance.exports.get_typed_function(&store, "load_far")?;
+    let store_fn: TypedFunction<(i64, i32), ()> =
+        instance.exports.get_typed_function(&store, "store")?;
+
+    assert_eq!(load.call(&mut store, 16)?, i32::from_le_bytes(*b"wasm"));
+    store_fn.call(&mut store, 65532, 42)?;
+    assert_eq!(load.call(&mut store, 65532)?, 42);
+    assert!(load.call(&mut store, 65533).is_err());
+    // Truncating these addresses to 32 bits would make them in bounds.
+    assert!(store_fn.call(&mut store, 1 << 32, 1).is_err());
+    assert!(load.call(&mut store, -1).is_err());
+    assert!(load_far.call(&mut store, 0).is_err());
+    assert!(load_far.call(&mut store, -(1 << 32)).is_err());
+    assert_eq!(load8.call(&mut store, 0)?, 0);
+
+    let size: TypedFunction<(), i64> = instance.exports.get_typed_function(&store, "size")?;
+    let grow: TypedFunction<i64, i64> = instance.exports.get_typed_function(&store, "grow")?;
+    assert_eq!(size.call(&mut store)?, 1);
+    assert_eq!(grow.call(&mut store, 1)?, 1);
+    assert_eq!(size.call(&mut store)?, 2);
+    assert_eq!(grow.call(&mut store, 1 << 32)?, -1);
+    assert_eq!(grow.call(&mut store, 100000)?, -1);
+    assert_eq!(size.call(&mut store)?, 2);
+    store_fn.call(&mut store, 0x1fffc, 7)?;
+    assert_eq!(load.call(&mut store, 0x1fffc)?, 7);
+
+    let fill: TypedFunction<(i64, i32, i64), ()> =
+        instance.exports.get_typed_function(&store, "fill")?;
+    let copy: TypedFunction<(i64, i64, i64), ()> =
+        instance.exports.get_typed_function(&store, "copy")?;
+    let init: TypedFunction<(i64, i32, i32), ()> =
+        instance.exports.get_typed_function(&store, "init")?;
+
+    fill.call(&mut store, 0x100, 0xab, 4)?;
+    assert_eq!(load.call(&mut store, 0x100)?, 0xababababu32 as i32);
+    assert!(fill.call(&mut store, 1 << 32, 0, 1).is_err());
+    assert!(fill.call(&mut store, -2, 0, 4).is_err());
+
+    copy.call(&mut store, 0x200, 16, 6)?;
+    assert_eq!(load8.call(&mut store, 0x205)?, b'4' as i32);
+    assert!(copy.call(&mut store, 1 << 32, 16, 1).is_err());
+    assert!(copy.call(&mut store, 0, 1 << 32, 1).is_err());
+
+    init.call(&mut store, 0x300, 0, 7)?;
+    assert_eq!(load8.call(&mut store, 0x306)?, b'e' as i32);
+    assert!(init.call(&mut store, 1 << 32, 0, 1).is_err());
+    Ok(())
+}
+
+#[compiler_test(memory64)]
+fn requires_the_feature(config: crate::Config) -> Result<()> {
+    let store = config.store();
+    assert!(Module::new(&store, WAT).is_err());
+    Ok(())
+}
@@ -0,0 +1,124 @@
+use anyhow::Result;
+
+use std::sync::{Arc, Mutex};
+use wasmer::sys::NativeEngineExt;
+use wasmer::*;
+use wasmer_types::entity::EntityRef;
+use wasmer_types::{FunctionIndex, MemoryIndex};
+
+#[compiler_test(memory_watch)]
+fn accesses_to_watched_ranges_are_reported(config: crate::Config) -> Result<()> {
+    let mut compiler_config = config.compiler_config(false);
+    compiler_config.enable_memory_watch();
+    let mut engine = config.engine(compiler_config);
+
+    let accesses = Arc::new(Mutex::new(Vec::new()));
+    let watch = {
+        let accesses = accesses.clone();
+        Arc::new(MemoryWatch::new(move |access: &MemoryAccess| {
+            accesses.lock().unwrap().push(*access)
+        }))
+    };
+    watch.watch(16..24);
+    engine.set_memory_watch(Some(watch));
+
+    let mut store = Store::new(engine);
+    let wat = r#"(module
+        (memory 1)
+        (func (export "run") (param $addr i32) (result i32)
+            (i64.store offset=4 (local.get $addr) (i64.const 0x1122334455667788))
+            (i32.store (i32.const 0) (i32.const 1))
+            (i32.load8_u offset=20 (i32.const 0)))
+)"#;
+    let module = Module::new(&store, wat)?;
+    let instance = Instance::new(&mut store, &module, &imports! {})?;
+    let run: TypedFunction<i32, i32> = instance.exports.get_typed_function(&store, "run")?;
+    assert_eq!(run.call(&mut store, 12)?, 0x44);
+
+    let accesses = accesses.lock().unwrap();
+    assert_eq!(
+        &accesses[..],
+        &[
+            MemoryAccess {
+                kind: MemoryAccessKind::Store,
+                function_index: FunctionIndex::new(0),
+                memory_index: MemoryIndex::new(0),
+                offset: 16,
+                size: 8,
+                value: 0x1122334455667788,
+            },
+            MemoryAccess {
+                kind: MemoryAccessKind::Load,
+                function_index: FunctionIndex::new(0),
+                memory_index: MemoryIndex::new(0),
+                offset: 20,
+                size: 1,
+                value: 0x44,
+            },
+        ]
+    );
+    Ok(())
+}
+
+fn watched_engine(config: &crate::Config) -> (Engine, Arc<MemoryWatch>, Arc<Mutex<Vec<u64>>>) {
+    let mut compiler_config = config.compiler_config(false);
+    compiler_config.enable_memory_watch();
+    let mut engine = config.engine(compiler_config);
+
+    let offsets = Arc::new(Mutex::new(Vec::new()));
+    let watch = {
+        let offsets = offsets.clone();
+        Arc::new(MemoryWatch::new(move |access: &MemoryAccess| {
+            offsets.lock().unwrap().push(access.offset)
+        }))
+    };
+    engine.set_memory_watch(Some(watch.clone()));
+    (engine, watch, offsets)
+}
+
+#[compiler_test(memory_watch)]
+fn ranges_can_change_while_instances_run(config: crate::Config) -> Result<()> {
+    let (engine, watch, offsets) = watched_engine(&config);
+    let mut store = Store::new(engine);
+    let wat = r#"(module
+        (memory 1)
+        (func (export "run") (param $addr i32)
+            (i32.store (local.get $addr) (i32.const 1)))
+)"#;
+    let module = Module::new(&store, wat)?;
+    let instance = Instance::new(&mut store, &module, &imports! {})?;
+    let run: TypedFunction<i32, ()> = instance.exports.get_typed_function(&store, "run")?;
+
+    run.call(&mut store, 16)?;
+    watch.watch(64..68);
+    watch.watch(16..20);
+    // Within the bounds of the watched ranges, but not in any of them.
+    run.call(&mut store, 32)?;
+    run.call(&mut store, 64)?;
+    watch.clear();
+    run.call(&mut store, 64)?;
+
+    assert_eq!(&offsets.lock().unwrap()[..], &[64]);
+    Ok(())
+}
+
+#[compiler_test(memory_watch)]
+fn modules_with_64_bit_memories_are_rejected(mut config: crate::Config) -> Result<()> {
+    let mut features = wasmer::sys::Features::default();
+    features.memory64(true);
+    config.set_features(features);
+    let (engine, _watch, _offsets) = watched_engine(&config);
+    let store = Store::new(engine);
+    let wat = r#"(module
+        (memory i64 1)
+        (func (export "run") (param $addr i64)
+            (i32.store (local.get $addr) (i32.const 1)))
+)"#;
+    let error = Module::new(&store, wat).unwrap_err();
+    assert!(
+        matches!(error, CompileError::UnsupportedFeature(_)),
+        "{}",
+        error
+    );
+    Ok(())
+}
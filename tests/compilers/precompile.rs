@@ -0,0 +1,86 @@
+use anyhow::Result;
+use wasmer::*;
+
+const WAT: &str = r#"
+    (module
+        (func (export "add") (param i32 i32) (result i32)
+            local.get 0
+            local.get 1
+            i32.add)
+    )
+"#;
+
+/// An older CPU of `architecture` than the host, for the operating system
+/// of the host.
+fn baseline(architecture: Architecture) -> Target {
+    let mut triple = Triple::host();
+    triple.architecture = architecture;
+    let mut cpu_features = CpuFeature::set();
+    // Singlepass requires SSE 4.2 on x86_64.
+    if architecture == Architecture::X86_64 {
+        cpu_features |= CpuFeature::SSE2
+            | CpuFeature::SSE3
+            | CpuFeature::SSSE3
+            | CpuFeature::SSE41
+            | CpuFeature::SSE42
+            | CpuFeature::POPCNT;
+    }
+    Target::new(triple, cpu_features)
+}
+
+/// An architecture other than the one of the host.
+fn other_architecture() -> Architecture {
+    match Triple::host().architecture {
+        Architecture::X86_64 => "aarch64".parse().unwrap(),
+        _ => Architecture::X86_64,
+    }
+}
+
+#[compiler_test(precompile)]
+fn precompile_for_the_host(config: crate::Config) -> Result<()> {
+    let mut store = config.store();
+    let wasm = wat2wasm(WAT.as_bytes())?;
+
+    let bytes = Module::precompile_for(&store, &wasm, &Target::default())?;
+    let module = unsafe { Module::deserialize(&store, bytes)? };
+    let instance = Instance::new(&mut store, &module, &imports! {})?;
+    let add: TypedFunction<(i32, i32), i32> = instance.exports.get_typed_function(&store, "add")?;
+    assert_eq!(add.call(&mut store, 1, 2)?, 3);
+    Ok(())
+}
+
+#[compiler_test(precompile)]
+fn artifacts_of_other_architectures_are_rejected(config: crate::Config) -> Result<()> {
+    let store = config.store();
+    let wasm = wat2wasm(WAT.as_bytes())?;
+
+    let bytes = Module::precompile_for(&store, &wasm, &baseline(other_architecture()))?;
+    let result = unsafe { Module::deserialize(&store, bytes) };
+    assert!(matches!(result, Err(DeserializeError::Incompatible(_))));
+    Ok(())
+}
+
+#[compiler_test(precompile)]
+fn deserialize_best(config: crate::Config) -> Result<()> {
+    let mut store = config.store();
+    let wasm = wat2wasm(WAT.as_bytes())?;
+    let host = Target::default();
+
+    let candidates = vec![
+        Module::precompile_for(&store, &wasm, &baseline(host.triple().architecture))?,
+        Module::precompile_for(&store, &wasm, &host)?,
+        Module::precompile_for(&store, &wasm, &baseline(other_architecture()))?,
+    ];
+    let module = unsafe { Module::deserialize_best(&store, candidates)? };
+    let instance = Instance::new(&mut store, &module, &imports! {})?;
+    let add: TypedFunction<(i32, i32), i32> = instance.exports.get_typed_function(&store, "add")?;
+    assert_eq!(add.call(&mut store, 1, 2)?, 3);
+
+    let candidates = vec![Module::precompile_for(
+        &store,
+        &wasm,
+        &baseline(other_architecture()),
+    )?];
+    assert!(unsafe { Module::deserialize_best(&store, candidates) }.is_err());
+    Ok(())
+}
@@ -0,0 +1,51 @@
+use anyhow::Result;
+
+use wasmer::*;
+use wasmer_types::entity::EntityRef;
+
+const WAT: &str = r#"(module
+    (func $abs (export "abs") (param i32) (result i32)
+        (if (result i32) (i32.lt_s (local.get 0) (i32.const 0))
+            (then (i32.sub (i32.const 0) (local.get 0)))
+            (else (local.get 0))))
+    (func $unused (export "unused") (result i32)
+        (i32.const 7))
+)"#;
+
+#[compiler_test(profile)]
+fn calls_and_branches_are_counted(config: crate::Config) -> Result<()> {
+    let mut compiler_config = config.compiler_config(false);
+    compiler_config.enable_profile_instrumentation();
+    let mut store = Store::new(config.engine(compiler_config));
+    let module = Module::new(&store, WAT)?;
+    let instance = Instance::new(&mut store, &module, &imports! {})?;
+    let abs: TypedFunction<i32, i32> = instance.exports.get_typed_function(&store, "abs")?;
+    assert_eq!(abs.call(&mut store, -1)?, 1);
+    assert_eq!(abs.call(&mut store, 2)?, 2);
+    assert_eq!(abs.call(&mut store, 3)?, 3);
+
+    let profile = module.profile().unwrap();
+    let function = profile.function(LocalFunctionIndex::new(0)).unwrap();
+    assert_eq!(function.entry_count(), 3);
+    assert_eq!(function.branch(0), Some((1, 2)));
+    assert!(profile.function(LocalFunctionIndex::new(1)).is_none());
+
+    // The profile survives a roundtrip through its text form.
+    assert_eq!(profile.to_string().parse::<Profile>()?, profile);
+
+    #[cfg(feature = "llvm")]
+    {
+        let mut compiler_config = wasmer_compiler_llvm::LLVM::new();
+        compiler_config.profile(Some(std::sync::Arc::new(profile)));
+        let mut store = Store::new(config.engine(Box::new(compiler_config)));
+        let module = Module::new(&store, WAT)?;
+        let instance = Instance::new(&mut store, &module, &imports! {})?;
+        let abs: TypedFunction<i32, i32> = instance.exports.get_typed_function(&store, "abs")?;
+        let unused: TypedFunction<(), i32> =
+            instance.exports.get_typed_function(&store, "unused")?;
+        assert_eq!(abs.call(&mut store, -5)?, 5);
+        assert_eq!(unused.call(&mut store)?, 7);
+        assert!(module.profile().is_none());
+    }
+    Ok(())
+}
@@ -0,0 +1,262 @@
+//! Tests of the relaxed SIMD operators, with their deterministic semantics
+//! and with the ones of the target.
+
+use anyhow::Result;
+use wasmer::sys::Features;
+use wasmer::*;
+
+const WAT: &str = r#"
+(module
+  (func (export "madd") (param v128 v128 v128) (result v128)
+    (f32x4.relaxed_madd (local.get 0) (local.get 1) (local.get 2)))
+  (func (export "nmadd") (param v128 v128 v128) (result v128)
+    (f64x2.relaxed_nmadd (local.get 0) (local.get 1) (local.get 2)))
+  (func (export "min") (param v128 v128) (result v128)
+    (f32x4.relaxed_min (local.get 0) (local.get 1)))
+  (func (export "max") (param v128 v128) (result v128)
+    (f64x2.relaxed_max (local.get 0) (local.get 1)))
+  (func (export "swizzle") (param v128 v128) (result v128)
+    (i8x16.relaxed_swizzle (local.get 0) (local.get 1)))
+  (func (export "laneselect") (param v128 v128 v128) (result v128)
+    (i32x4.relaxed_laneselect (local.get 0) (local.get 1) (local.get 2)))
+  (func (export "trunc_s") (param v128) (result v128)
+    (i32x4.relaxed_trunc_f32x4_s (local.get 0)))
+  (func (export "trunc_u") (param v128) (result v128)
+    (i32x4.relaxed_trunc_f64x2_u_zero (local.get 0)))
+  (func (export "q15mulr") (param v128 v128) (result v128)
+    (i16x8.relaxed_q15mulr_s (local.get 0) (local.get 1)))
+  (func (export "dot") (param v128 v128) (result v128)
+    (i16x8.relaxed_dot_i8x16_i7x16_s (local.get 0) (local.get 1)))
+  (func (export "dot_add") (param v128 v128 v128) (result v128)
+    (i32x4.relaxed_dot_i8x16_i7x16_add_s (local.get 0) (local.get 1) (local.get 2))))
+"#;
+
+fn f32x4(lanes: [f32; 4]) -> u128 {
+    lanes
+        .iter()
+        .rev()
+        .fold(0, |v, lane| v << 32 | lane.to_bits() as u128)
+}
+
+fn to_f32x4(v: u128) -> [f32; 4] {
+    [0, 1, 2, 3].map(|lane| f32::from_bits((v >> (32 * lane)) as u32))
+}
+
+fn f64x2(lanes: [f64; 2]) -> u128 {
+    (lanes[1].to_bits() as u128) << 64 | lanes[0].to_bits() as u128
+}
+
+fn to_f64x2(v: u128) -> [f64; 2] {
+    [0, 1].map(|lane| f64::from_bits((v >> (64 * lane)) as u64))
+}
+
+fn i8x16(lanes: [i8; 16]) -> u128 {
+    u128::from_le_bytes(lanes.map(|lane| lane as u8))
+}
+
+fn i16x8(lanes: [i16; 8]) -> u128 {
+    lanes
+        .iter()
+        .rev()
+        .fold(0, |v, lane| v << 16 | *lane as u16 as u128)
+}
+
+fn i32x4(lanes: [i32; 4]) -> u128 {
+    lanes
+        .iter()
+        .rev()
+        .fold(0, |v, lane| v << 32 | *lane as u32 as u128)
+}
+
+struct Relaxed {
+    store: Store,
+    instance: Instance,
+}
+
+impl Relaxed {
+    fn new(mut config: crate::Config, deterministic: bool) -> Result<Self> {
+        let mut features = Features::default();
+        features.relaxed_simd(true);
+        config.set_features(features);
+        config.set_relaxed_simd_deterministic(deterministic);
+        let mut store = config.store();
+        let module = Module::new(&store, WAT)?;
+        let instance = Instance::new(&mut store, &module, &imports! {})?;
+        Ok(Self { store, instance })
+    }
+
+    fn call(&mut self, name: &str, args: &[u128]) -> Result<u128> {
+        let f = self.instance.exports.get_function(name)?;
+        let args = args.iter().map(|v| Value::V128(*v)).collect::<Vec<_>>();
+        match *f.call(&mut self.store, &args)? {
+            [Value::V128(v)] => Ok(v),
+            ref results => anyhow::bail!("unexpected results: {:?}", results),
+        }
+    }
+
+    fn unary(&mut self, name: &str, a: u128) -> Result<u128> {
+        self.call(name, &[a])
+    }
+
+    fn binary(&mut self, name: &str, a: u128, b: u128) -> Result<u128> {
+        self.call(name, &[a, b])
+    }
+
+    fn ternary(&mut self, name: &str, a: u128, b: u128, c: u128) -> Result<u128> {
+        self.call(name, &[a, b, c])
+    }
+}
+
+// (1 + 2^-23) * (1 - 2^-23) - 1 is -2^-46 when fused, and 0 otherwise.
+const MADD_A: f32 = 1.0 + f32::EPSILON;
+const MADD_B: f32 = 1.0 - f32::EPSILON;
+const MADD_FUSED: f32 = -1.0 / (1u64 << 46) as f32;
+// (1 + 2^-52) * (1 - 2^-52) - 1 is -2^-104 when fused, and 0 otherwise.
+const NMADD_A: f64 = 1.0 + f64::EPSILON;
+const NMADD_B: f64 = 1.0 - f64::EPSILON;
+const NMADD_FUSED: f64 = 1.0 / (1u128 << 104) as f64;
+
+#[compiler_test(relaxed_simd)]
+fn deterministic_semantics(config: crate::Config) -> Result<()> {
+    let mut relaxed = Relaxed::new(config, true)?;
+
+    let madd = relaxed.ternary(
+        "madd",
+        f32x4([MADD_A; 4]),
+        f32x4([MADD_B; 4]),
+        f32x4([-1.0; 4]),
+    )?;
+    assert_eq!(to_f32x4(madd), [MADD_FUSED; 4]);
+    let nmadd = relaxed.ternary(
+        "nmadd",
+        f64x2([NMADD_A; 2]),
+        f64x2([NMADD_B; 2]),
+        f64x2([1.0; 2]),
+    )?;
+    assert_eq!(to_f64x2(nmadd), [NMADD_FUSED; 2]);
+
+    let min = relaxed.binary(
+        "min",
+        f32x4([f32::NAN, 1.0, -0.0, 2.0]),
+        f32x4([1.0, f32::NAN, 0.0, 3.0]),
+    )?;
+    let min = to_f32x4(min);
+    assert!(min[0].is_nan() && min[1].is_nan());
+    assert_eq!(min[2].to_bits(), (-0.0f32).to_bits());
+    assert_eq!(min[3], 2.0);
+    let max = relaxed.binary("max", f64x2([f64::NAN, 0.0]), f64x2([1.0, -0.0]))?;
+    let max = to_f64x2(max);
+    assert!(max[0].is_nan());
+    assert_eq!(max[1].to_bits(), 0.0f64.to_bits());
+
+    let table = i8x16([
+        10, 11, 12, 13, 14, 15, 16, 17, 18, 19, 20, 21, 22, 23, 24, 25,
+    ]);
+    let indices = i8x16([0, 15, 16, -1, 127, -128, 3, 2, 1, 0, 0, 0, 0, 0, 0, 0]);
+    assert_eq!(
+        relaxed.binary("swizzle", table, indices)?,
+        i8x16([10, 25, 0, 0, 0, 0, 13, 12, 11, 10, 10, 10, 10, 10, 10, 10])
+    );
+
+    assert_eq!(
+        relaxed.ternary(
+            "laneselect",
+            i32x4([1, 2, 3, 4]),
+            i32x4([5, 6, 7, 8]),
+            i32x4([-1, 0, -1, 0]),
+        )?,
+        i32x4([1, 6, 3, 8])
+    );
+
+    assert_eq!(
+        relaxed.unary("trunc_s", f32x4([f32::NAN, 3e9, -3e9, -1.5]))?,
+        i32x4([0, i32::MAX, i32::MIN, -1])
+    );
+    assert_eq!(
+        relaxed.unary("trunc_u", f64x2([-1.0, 5e9]))?,
+        i32x4([0, -1, 0, 0])
+    );
+
+    assert_eq!(
+        relaxed.binary(
+            "q15mulr",
+            i16x8([i16::MIN, 16384, 0, 0, 0, 0, 0, 0]),
+            i16x8([i16::MIN, 16384, 0, 0, 0, 0, 0, 0]),
+        )?,
+        i16x8([i16::MAX, 8192, 0, 0, 0, 0, 0, 0])
+    );
+
+    let a = i8x16([100, 100, -100, 3, -128, -128, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0]);
+    let b = i8x16([50, 50, 50, 4, -128, -128, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0]);
+    assert_eq!(
+        relaxed.binary("dot", a, b)?,
+        i16x8([10000, -4988, i16::MIN, 0, 0, 0, 0, 0])
+    );
+    let a = i8x16([100, 100, 100, 100, -1, 2, 3, 4, 0, 0, 0, 0, 1, 1, 1, 1]);
+    let b = i8x16([50, 50, 50, 50, 1, 1, 1, 1, 0, 0, 0, 0, 1, 1, 1, 1]);
+    assert_eq!(
+        relaxed.ternary("dot_add", a, b, i32x4([1, 2, 3, 4]))?,
+        i32x4([20001, 10, 3, 8])
+    );
+    Ok(())
+}
+
+#[compiler_test(relaxed_simd)]
+fn target_semantics(config: crate::Config) -> Result<()> {
+    let mut relaxed = Relaxed::new(config, false)?;
+
+    // Whether the multiply-add is fused depends on the target, but it's
+    // the same for all the lanes.
+    let madd = relaxed.ternary(
+        "madd",
+        f32x4([MADD_A; 4]),
+        f32x4([MADD_B; 4]),
+        f32x4([-1.0; 4]),
+    )?;
+    let madd = to_f32x4(madd);
+    assert!(madd == [MADD_FUSED; 4] || madd == [0.0; 4], "{:?}", madd);
+    let nmadd = relaxed.ternary(
+        "nmadd",
+        f64x2([NMADD_A; 2]),
+        f64x2([NMADD_B; 2]),
+        f64x2([1.0; 2]),
+    )?;
+    let nmadd = to_f64x2(nmadd);
+    assert!(
+        nmadd == [NMADD_FUSED; 2] || nmadd == [0.0; 2],
+        "{:?}",
+        nmadd
+    );
+
+    // Either operand may be returned for NaNs.
+    let min = relaxed.binary("min", f32x4([f32::NAN, 1.0, 4.0, 2.0]), f32x4([1.0; 4]))?;
+    let min = to_f32x4(min);
+    assert!(min[0].is_nan() || min[0] == 1.0);
+    assert_eq!(&min[1..], &[1.0, 1.0, 1.0]);
+    let max = relaxed.binary("max", f64x2([-1.0, 3.0]), f64x2([2.0, f64::NAN]))?;
+    let max = to_f64x2(max);
+    assert_eq!(max[0], 2.0);
+    assert!(max[1].is_nan() || max[1] == 3.0);
+
+    // In-range inputs give the same results as with the deterministic
+    // semantics.
+    let table = i8x16([
+        10, 11, 12, 13, 14, 15, 16, 17, 18, 19, 20, 21, 22, 23, 24, 25,
+    ]);
+    let indices = i8x16([15, 14, 13, 12, 11, 10, 9, 8, 7, 6, 5, 4, 3, 2, 1, 0]);
+    assert_eq!(
+        relaxed.binary("swizzle", table, indices)?,
+        i8x16([25, 24, 23, 22, 21, 20, 19, 18, 17, 16, 15, 14, 13, 12, 11, 10])
+    );
+    assert_eq!(
+        relaxed.unary("trunc_s", f32x4([1.5, -2.5, 100.0, 0.0]))?,
+        i32x4([1, -2, 100, 0])
+    );
+    let a = i8x16([100, 100, -100, 3, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0]);
+    let b = i8x16([50, 50, 50, 4, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0]);
+    assert_eq!(
+        relaxed.binary("dot", a, b)?,
+        i16x8([10000, -4988, 0, 0, 0, 0, 0, 0])
+    );
+    Ok(())
+}
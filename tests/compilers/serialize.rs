@@ -85,3 +85,55 @@ fn test_deserialize(config: crate::Config) -> Result<()> {
     assert_eq!(result.to_vec(), vec![Value::I64(1500)]);
     Ok(())
 }
+
+#[compiler_test(serialize)]
+fn test_summarize(config: crate::Config) -> Result<()> {
+    use wasmer::sys::{ArtifactBuild, ArtifactSummary, NativeEngineExt};
+
+    let store = config.store();
+    let wat = r#"
+        (module
+            (func $hello (import "" "hello"))
+            (func $run (export "run") (call $hello))
+            (func $add (export "add") (param i32 i32) (result i32)
+                (i32.add (local.get 0) (local.get 1)))
+        )
+    "#;
+
+    let module = Module::new(&store, wat)?;
+    let serialized_bytes = module.serialize()?;
+    let artifact = ArtifactBuild::deserialize_checked(&serialized_bytes)?;
+    let summary = ArtifactSummary::new(&artifact);
+
+    assert_eq!(summary.triple, store.engine().target().triple().to_string());
+    assert!(!summary.compiler.is_empty());
+    let names = summary
+        .functions
+        .iter()
+        .map(|function| function.name.as_deref())
+        .collect::<Vec<_>>();
+    assert_eq!(names, [Some("run"), Some("add")]);
+    assert!(summary.functions.iter().all(|f| f.code_size > 0));
+    assert_eq!(summary.dynamic_function_trampolines.count, 1);
+
+    assert!(ArtifactBuild::deserialize_checked(&serialized_bytes[1..]).is_err());
+    Ok(())
+}
+
+#[cfg(feature = "interpreter")]
+#[compiler_test(serialize)]
+fn test_interpreted_and_native_instances_dont_mix(config: crate::Config) -> Result<()> {
+    let wat = r#"(module (func (export "answer") (result i32) (i32.const 42)))"#;
+    let interpreted = {
+        let store = Store::new(wasmer_compiler_interpreter::Interpreter::new());
+        Module::new(&store, wat)?.serialize()?
+    };
+
+    let mut store = config.store();
+    let native = Module::new(&store, wat)?;
+    let interpreted = unsafe { Module::deserialize(&store, interpreted)? };
+    Instance::new(&mut store, &native, &imports! {})?;
+    let error = Instance::new(&mut store, &interpreted, &imports! {}).unwrap_err();
+    assert!(matches!(error, InstantiationError::MixedCode), "{}", error);
+    Ok(())
+}
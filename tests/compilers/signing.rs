@@ -0,0 +1,55 @@
+use anyhow::Result;
+use wasmer::sys::{ArtifactSigner, ArtifactVerifier};
+use wasmer::*;
+
+const WAT: &str = r#"
+    (module
+        (func (export "add") (param i32 i32) (result i32)
+            local.get 0
+            local.get 1
+            i32.add)
+    )
+"#;
+
+#[compiler_test(signing)]
+fn signed_roundtrip(config: crate::Config) -> Result<()> {
+    let mut store = config.store();
+    let module = Module::new(&store, WAT)?;
+
+    let signer = ArtifactSigner::from_pkcs8(&ArtifactSigner::generate_key()?)?;
+    let signed = module.serialize_signed(&store, &signer)?;
+    assert!(ArtifactVerifier::is_signed(&signed));
+
+    let verifier = ArtifactVerifier::new(signer.public_key())?;
+    let module = Module::deserialize_checked(&store, signed, &verifier)?;
+    let instance = Instance::new(&mut store, &module, &imports! {})?;
+    let add: TypedFunction<(i32, i32), i32> = instance.exports.get_typed_function(&store, "add")?;
+    assert_eq!(add.call(&mut store, 1, 2)?, 3);
+    Ok(())
+}
+
+#[compiler_test(signing)]
+fn untrusted_artifacts_are_rejected(config: crate::Config) -> Result<()> {
+    let store = config.store();
+    let module = Module::new(&store, WAT)?;
+
+    let signer = ArtifactSigner::from_pkcs8(&ArtifactSigner::generate_key()?)?;
+    let verifier = ArtifactVerifier::new(signer.public_key())?;
+    let signed = module.serialize_signed(&store, &signer)?;
+
+    // Unsigned artifacts.
+    let unsigned = module.serialize()?;
+    assert!(Module::deserialize_checked(&store, unsigned, &verifier).is_err());
+
+    // Tampered artifacts.
+    let mut tampered = signed.to_vec();
+    let middle = tampered.len() / 2;
+    tampered[middle] ^= 1;
+    assert!(Module::deserialize_checked(&store, tampered, &verifier).is_err());
+
+    // Artifacts signed with another key.
+    let other = ArtifactSigner::from_pkcs8(&ArtifactSigner::generate_key()?)?;
+    let other = ArtifactVerifier::new(other.public_key())?;
+    assert!(Module::deserialize_checked(&store, signed, &other).is_err());
+    Ok(())
+}
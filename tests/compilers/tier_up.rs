@@ -0,0 +1,145 @@
+#![cfg(all(feature = "singlepass", feature = "cranelift"))]
+
+use anyhow::Result;
+use std::thread;
+use std::time::{Duration, Instant};
+use wasmer::sys::{Cranelift, EngineBuilder, Singlepass, TierUp};
+use wasmer::*;
+use wasmer_types::entity::EntityRef;
+use wasmer_types::{FunctionIndex, TrapCode};
+
+const WAT: &str = r#"(module
+    (func $fac (export "fac") (param i64) (result i64)
+        (if (result i64) (i64.eqz (local.get 0))
+            (then (i64.const 1))
+            (else (i64.mul (local.get 0) (call $fac (i64.sub (local.get 0) (i64.const 1)))))))
+    (func $div (export "div") (param i32 i32) (result i32)
+        (i32.div_s (local.get 0) (local.get 1)))
+    (func $cold (export "cold") (result i32)
+        (i32.const 7))
+)"#;
+
+const SIMD_WAT: &str = r#"(module
+    (func $splat (export "splat") (param i32) (result v128)
+        (i32x4.splat (local.get 0)))
+    (func $first (export "first") (param i32) (result i32)
+        (i32x4.extract_lane 0 (call $splat (local.get 0))))
+    (func $double (export "double") (param i32) (result i32)
+        (i32.add (local.get 0) (local.get 0)))
+)"#;
+
+fn engine(threshold: u64) -> Engine {
+    let tier_up = TierUp::new(Cranelift::new(), threshold).set_interval(Duration::from_millis(1));
+    EngineBuilder::new(Singlepass::new())
+        .set_tier_up(Some(tier_up))
+        .engine()
+        .into()
+}
+
+fn wait_for_tier_up(module: &Module, index: usize) -> bool {
+    let start = Instant::now();
+    while start.elapsed() < Duration::from_secs(30) {
+        if module.is_tiered_up(FunctionIndex::new(index)) {
+            return true;
+        }
+        thread::sleep(Duration::from_millis(1));
+    }
+    false
+}
+
+#[test]
+fn hot_functions_are_tiered_up() -> Result<()> {
+    let mut store = Store::new(engine(100));
+    let module = Module::new(&store, WAT)?;
+    let instance = Instance::new(&mut store, &module, &imports! {})?;
+    let fac: TypedFunction<i64, i64> = instance.exports.get_typed_function(&store, "fac")?;
+    let cold: TypedFunction<(), i32> = instance.exports.get_typed_function(&store, "cold")?;
+
+    assert!(!module.is_tiered_up(FunctionIndex::new(0)));
+    for _ in 0..10 {
+        assert_eq!(fac.call(&mut store, 20)?, 2432902008176640000);
+    }
+    assert!(wait_for_tier_up(&module, 0));
+    assert!(!module.is_tiered_up(FunctionIndex::new(2)));
+
+    // Both the existing instance, which enters the optimized code through
+    // the baseline code, and new instances, which call it directly, keep
+    // computing the same results.
+    assert_eq!(fac.call(&mut store, 20)?, 2432902008176640000);
+    assert_eq!(cold.call(&mut store)?, 7);
+    let instance = Instance::new(&mut store, &module, &imports! {})?;
+    let fac: TypedFunction<i64, i64> = instance.exports.get_typed_function(&store, "fac")?;
+    assert_eq!(fac.call(&mut store, 5)?, 120);
+
+    Ok(())
+}
+
+#[test]
+fn tiered_up_functions_trap() -> Result<()> {
+    let mut store = Store::new(engine(10));
+    let module = Module::new(&store, WAT)?;
+    let instance = Instance::new(&mut store, &module, &imports! {})?;
+    let div: TypedFunction<(i32, i32), i32> = instance.exports.get_typed_function(&store, "div")?;
+
+    for _ in 0..10 {
+        assert_eq!(div.call(&mut store, 7, 2)?, 3);
+    }
+    assert!(wait_for_tier_up(&module, 1));
+
+    let error = div.call(&mut store, 1, 0).unwrap_err();
+    assert_eq!(error.to_trap(), Some(TrapCode::IntegerDivisionByZero));
+    assert_eq!(div.call(&mut store, -9, 3)?, -3);
+
+    Ok(())
+}
+
+// SIMD is not implemented in the riscv64 Singlepass backend.
+#[cfg(not(target_arch = "riscv64"))]
+#[test]
+fn functions_passing_v128_are_not_tiered_up() -> Result<()> {
+    let mut store = Store::new(engine(10));
+    let module = Module::new(&store, SIMD_WAT)?;
+    let instance = Instance::new(&mut store, &module, &imports! {})?;
+    let splat = instance.exports.get_function("splat")?;
+    let first: TypedFunction<i32, i32> = instance.exports.get_typed_function(&store, "first")?;
+    let double: TypedFunction<i32, i32> = instance.exports.get_typed_function(&store, "double")?;
+
+    // `splat` and `first` are hot before `double` is, so they have been
+    // skipped once `double` is tiered up.
+    for _ in 0..10 {
+        assert_eq!(
+            &*splat.call(&mut store, &[Value::I32(3)])?,
+            &[Value::V128(0x3_0000_0003_0000_0003_0000_0003)]
+        );
+        assert_eq!(first.call(&mut store, 3)?, 3);
+    }
+    for _ in 0..10 {
+        assert_eq!(double.call(&mut store, 3)?, 6);
+    }
+    assert!(wait_for_tier_up(&module, 2));
+    assert!(!module.is_tiered_up(FunctionIndex::new(0)));
+    assert!(!module.is_tiered_up(FunctionIndex::new(1)));
+
+    assert_eq!(
+        &*splat.call(&mut store, &[Value::I32(5)])?,
+        &[Value::V128(0x5_0000_0005_0000_0005_0000_0005)]
+    );
+    assert_eq!(first.call(&mut store, 5)?, 5);
+
+    Ok(())
+}
+
+#[test]
+fn modules_without_tier_up_are_not_watched() -> Result<()> {
+    let mut store = Store::new(EngineBuilder::new(Singlepass::new()).engine());
+    let module = Module::new(&store, WAT)?;
+    let instance = Instance::new(&mut store, &module, &imports! {})?;
+    let fac: TypedFunction<i64, i64> = instance.exports.get_typed_function(&store, "fac")?;
+
+    for _ in 0..100 {
+        fac.call(&mut store, 20)?;
+    }
+    assert!(!module.is_tiered_up(FunctionIndex::new(0)));
+
+    Ok(())
+}
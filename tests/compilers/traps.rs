@@ -1,6 +1,7 @@
 use anyhow::Result;
 use std::panic::{self, AssertUnwindSafe};
 use wasmer::*;
+use wasmer_types::TrapCode;
 
 #[compiler_test(traps)]
 fn test_trap_return(config: crate::Config) -> Result<()> {
@@ -274,6 +275,7 @@ fn trap_start_function_import(config: crate::Config) -> Result<()> {
         InstantiationError::Link(_)
         | InstantiationError::DifferentStores
         | InstantiationError::DifferentArchOS
+        | InstantiationError::MixedCode
         | InstantiationError::CpuFeature(_) => {
             panic!("It should be a start error")
         }
@@ -490,3 +492,34 @@ fn present_after_module_drop(config: crate::Config) -> Result<()> {
         // assert_eq!(t.trace()[0].func_index(), 0);
     }
 }
+
+#[compiler_test(traps)]
+fn signed_division_overflow_traps(config: crate::Config) -> Result<()> {
+    let mut store = config.store();
+    let wat = r#"(module
+        (func (export "div32") (param i32 i32) (result i32)
+            (i32.div_s (local.get 0) (local.get 1)))
+        (func (export "div64") (param i64 i64) (result i64)
+            (i64.div_s (local.get 0) (local.get 1)))
+        (func (export "rem32") (param i32 i32) (result i32)
+            (i32.rem_s (local.get 0) (local.get 1)))
+    )"#;
+    let module = Module::new(&store, wat)?;
+    let instance = Instance::new(&mut store, &module, &imports! {})?;
+    let div32: TypedFunction<(i32, i32), i32> =
+        instance.exports.get_typed_function(&store, "div32")?;
+    let div64: TypedFunction<(i64, i64), i64> =
+        instance.exports.get_typed_function(&store, "div64")?;
+    let rem32: TypedFunction<(i32, i32), i32> =
+        instance.exports.get_typed_function(&store, "rem32")?;
+
+    let error = div32.call(&mut store, i32::MIN, -1).unwrap_err();
+    assert_eq!(error.to_trap(), Some(TrapCode::IntegerOverflow));
+    let error = div64.call(&mut store, i64::MIN, -1).unwrap_err();
+    assert_eq!(error.to_trap(), Some(TrapCode::IntegerOverflow));
+    // Neither is an overflow.
+    assert_eq!(div32.call(&mut store, i32::MIN, 1)?, i32::MIN);
+    assert_eq!(div64.call(&mut store, -1, -1)?, 1);
+    assert_eq!(rem32.call(&mut store, i32::MIN, -1)?, 0);
+    Ok(())
+}
@@ -73,3 +73,34 @@ fn gen_c_header_works_pirita() -> anyhow::Result<()> {
 
     Ok(())
 }
+
+#[test]
+fn gen_c_header_works_shared() -> anyhow::Result<()> {
+    let temp_dir = tempfile::tempdir()?;
+    let operating_dir: PathBuf = temp_dir.path().to_owned();
+
+    let wasm_path = operating_dir.join(fixtures::qjs());
+    let out_path = temp_dir.path().join("header.h");
+
+    let cmd = Command::new(get_wasmer_path())
+        .arg("gen-c-header")
+        .arg(&wasm_path)
+        .arg("-o")
+        .arg(&out_path)
+        .arg("--shared")
+        .output()
+        .unwrap();
+    assert!(cmd.status.success());
+
+    let file = std::fs::read_to_string(&out_path).expect("no header.h file");
+    assert!(
+        file.contains(
+            "wasm_module_t *wasmer_shared_module_new(wasm_store_t *store, const char *name);"
+        ),
+        "no wasmer_shared_module_new in file"
+    );
+    assert!(file.contains("\"qjs\""), "no qjs module in file");
+    assert!(!file.contains("wasmer_function_"));
+
+    Ok(())
+}